@@ -0,0 +1,719 @@
+use crate::cli::format::format_duration_pretty;
+use crate::data::identifier::Identifier;
+use crate::data::local_time;
+use crate::data::report;
+use crate::data::{BASIC_DATE_FORMAT, BASIC_TIME_FORMAT};
+use crate::serde::pretty_duration;
+use clap::ValueEnum;
+use log::error;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use time::format_description::BorrowedFormatItem;
+use time::format_description::well_known::Rfc3339;
+use time::{
+    Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday,
+    format_description,
+};
+use uuid::Uuid;
+
+static SHORT_TIME_FORMAT: LazyLock<Vec<BorrowedFormatItem<'_>>> = LazyLock::new(|| {
+    format_description::parse("[hour padding:none]:[minute padding:zero]").unwrap()
+});
+
+/// parse a time of day given as `HH:MM` or `HH:MM:SS`, a dot may separate the parts, e.g. `9.30`
+pub fn parse_time_arg(s: &str) -> Result<Time, String> {
+    let trimmed = s.trim();
+    let normalized = if trimmed.contains(':') {
+        trimmed.to_string()
+    } else {
+        trimmed.replace('.', ":")
+    };
+    Time::parse(&normalized, &*BASIC_TIME_FORMAT)
+        .or_else(|_| Time::parse(&normalized, &*SHORT_TIME_FORMAT))
+        .map_err(|_| format!("invalid time '{s}', expected HH:MM, HH:MM:SS or HH.MM"))
+}
+
+/// time of day argument which may refer to the current time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeArg {
+    #[default]
+    Now,
+    At(Time),
+    /// the given duration before now, e.g. `-15m`
+    Ago(Duration),
+}
+
+impl TimeArg {
+    /// time of day, resolving `now` using the current clock
+    pub fn resolve(&self) -> Time {
+        match self {
+            TimeArg::Now => local_time::now_time(),
+            TimeArg::At(time) => *time,
+            TimeArg::Ago(ago) => local_time::now_time() - *ago,
+        }
+    }
+
+    /// time of day like [`TimeArg::resolve`], refusing relative times which reach
+    /// into the previous day, days begin at `boundary`
+    pub fn resolve_within_day(&self, boundary: Option<Time>) -> std::io::Result<Time> {
+        if let TimeArg::Ago(ago) = self {
+            let now = local_time::to_day_time(local_time::now_time(), boundary);
+            if *ago > now - Time::MIDNIGHT {
+                error!(
+                    "{} ago is before the start of the day",
+                    format_duration_pretty(ago, false)
+                );
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Time lies before the start of the day",
+                ));
+            }
+        }
+        Ok(self.resolve())
+    }
+}
+
+/// parse a time of day given as `now`, `HH:MM` or `HH:MM:SS`
+pub fn parse_time_or_now_arg(s: &str) -> Result<TimeArg, String> {
+    if s.eq_ignore_ascii_case("now") {
+        Ok(TimeArg::Now)
+    } else {
+        parse_time_arg(s).map(TimeArg::At)
+    }
+}
+
+/// values of options accepting a leading hyphen, such as `-3d`, must not take the next option
+/// if the value itself was left out, e.g. `--date --json`
+fn reject_option(s: &str) -> Result<(), String> {
+    match s.strip_prefix("--") {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_alphabetic()) => Err(format!(
+            "a value is required but '{s}' is an option, write e.g. --date=-3d for offsets"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// parse a time of day given as `now`, `HH:MM`, `HH:MM:SS` or relative to now as `-DURATION`
+pub fn parse_time_or_ago_arg(s: &str) -> Result<TimeArg, String> {
+    reject_option(s)?;
+    match s.trim().strip_prefix('-') {
+        Some(ago) => parse_duration_arg(ago).map(TimeArg::Ago),
+        None => parse_time_or_now_arg(s),
+    }
+}
+
+/// parse a date given as `YYYY-MM-DD`
+pub fn parse_date_arg(s: &str) -> Result<Date, String> {
+    Date::parse(s, &*BASIC_DATE_FORMAT)
+        .map_err(|_| format!("invalid date '{s}', expected YYYY-MM-DD"))
+}
+
+/// day argument which may be relative to today
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateArg {
+    At(Date),
+    /// the given number of days before today, negative for days after today
+    DaysAgo(i64),
+    /// the most recent day of the weekday, today if today is that weekday.
+    /// weekdays never refer to the future
+    Last(Weekday),
+}
+
+impl DateArg {
+    /// the day, with `today` honoring the day boundary, see `JobConfig::today`
+    pub fn resolve(&self, today: Date) -> Date {
+        match self {
+            DateArg::At(date) => *date,
+            DateArg::DaysAgo(days) => today
+                .checked_sub(Duration::days(*days))
+                .unwrap_or(if *days > 0 { Date::MIN } else { Date::MAX }),
+            DateArg::Last(weekday) => {
+                let back = (today.weekday().number_days_from_monday() + 7
+                    - weekday.number_days_from_monday())
+                    % 7;
+                today - Duration::days(back as i64)
+            }
+        }
+    }
+}
+
+/// parse a day given as `YYYY-MM-DD`, `today`, `yesterday`, a weekday, e.g. `monday` or `mon`,
+/// or as an offset in days or weeks, e.g. `-3d`, `+1d` or `-2w`
+pub fn parse_relative_date_arg(s: &str) -> Result<DateArg, String> {
+    reject_option(s)?;
+    let error = || {
+        format!(
+            "invalid date '{s}', expected YYYY-MM-DD, today, yesterday, a weekday such as monday or an offset such as -3d"
+        )
+    };
+    let trimmed = s.trim().to_ascii_lowercase();
+    if let Ok(date) = parse_date_arg(&trimmed) {
+        return Ok(DateArg::At(date));
+    }
+    match trimmed.as_str() {
+        "today" => return Ok(DateArg::DaysAgo(0)),
+        "yesterday" => return Ok(DateArg::DaysAgo(1)),
+        _ => {}
+    }
+    if let Some(weekday) = parse_weekday(&trimmed) {
+        return Ok(DateArg::Last(weekday));
+    }
+
+    let (sign, offset) = match trimmed.split_at_checked(1) {
+        Some(("-", offset)) => (1, offset),
+        Some(("+", offset)) => (-1, offset),
+        _ => return Err(error()),
+    };
+    let Some((unit_start, _)) = offset.char_indices().next_back() else {
+        return Err(error());
+    };
+    let (count, unit) = offset.split_at(unit_start);
+    let unit_days = match unit {
+        "d" => 1,
+        "w" => 7,
+        _ => return Err(error()),
+    };
+    if count.is_empty() || !count.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(error());
+    }
+    count
+        .parse::<i64>()
+        .ok()
+        .and_then(|count| count.checked_mul(unit_days * sign))
+        .map(DateArg::DaysAgo)
+        .ok_or_else(error)
+}
+
+/// day of `s` relative to `today`, see [`parse_relative_date_arg`]
+pub fn parse_relative_date(s: &str, today: Date) -> Result<Date, String> {
+    parse_relative_date_arg(s).map(|date| date.resolve(today))
+}
+
+/// weekday by its english name or its first three letters
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    let mut weekday = Weekday::Monday;
+    for _ in 0..7 {
+        let name = weekday.to_string().to_ascii_lowercase();
+        if s == name || s == &name[..3] {
+            return Some(weekday);
+        }
+        weekday = weekday.next();
+    }
+    None
+}
+
+/// named date range, resolved relative to today
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodArg {
+    Today,
+    Yesterday,
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    LastMonth,
+    ThisQuarter,
+    ThisYear,
+    #[value(name = "last-7-days")]
+    Last7Days,
+    #[value(name = "last-30-days")]
+    Last30Days,
+}
+
+impl PeriodArg {
+    /// first and last day of the period (inclusive), weeks start on `week_start`
+    pub fn resolve(&self, today: Date, week_start: Weekday) -> (Date, Date) {
+        let month_start = today.replace_day(1).unwrap_or(today);
+        let month_end = |start: Date| {
+            start
+                .replace_day(start.month().length(start.year()))
+                .unwrap_or(start)
+        };
+        match self {
+            PeriodArg::Today => (today, today),
+            PeriodArg::Yesterday => {
+                let yesterday = today - Duration::days(1);
+                (yesterday, yesterday)
+            }
+            PeriodArg::ThisWeek => {
+                let start = report::week_start(today, week_start);
+                (start, start + Duration::days(6))
+            }
+            PeriodArg::LastWeek => {
+                let start = report::week_start(today, week_start) - Duration::days(7);
+                (start, start + Duration::days(6))
+            }
+            PeriodArg::ThisMonth => (month_start, month_end(month_start)),
+            PeriodArg::LastMonth => {
+                let start = (month_start - Duration::days(1))
+                    .replace_day(1)
+                    .unwrap_or(month_start);
+                (start, month_end(start))
+            }
+            PeriodArg::ThisQuarter => {
+                let first_month = Month::try_from((today.month() as u8 - 1) / 3 * 3 + 1)
+                    .unwrap_or(Month::January);
+                let start = Date::from_calendar_date(today.year(), first_month, 1).unwrap_or(today);
+                let last_month = first_month.next().next();
+                let end = Date::from_calendar_date(
+                    today.year(),
+                    last_month,
+                    last_month.length(today.year()),
+                )
+                .unwrap_or(today);
+                (start, end)
+            }
+            PeriodArg::ThisYear => (
+                today.replace_ordinal(1).unwrap_or(today),
+                Date::from_calendar_date(today.year(), Month::December, 31).unwrap_or(today),
+            ),
+            PeriodArg::Last7Days => (today - Duration::days(6), today),
+            PeriodArg::Last30Days => (today - Duration::days(29), today),
+        }
+    }
+}
+
+/// parse a point in time given as RFC 3339 or `YYYY-MM-DD HH:MM[:SS]` in the local timezone
+pub fn parse_datetime_arg(s: &str) -> Result<OffsetDateTime, String> {
+    if let Ok(datetime) = OffsetDateTime::parse(s, &Rfc3339) {
+        return Ok(datetime);
+    }
+
+    let (date, time) = s
+        .split_once(['T', ' '])
+        .ok_or_else(|| format!("invalid date and time '{s}', expected YYYY-MM-DD HH:MM[:SS]"))?;
+    let datetime = PrimitiveDateTime::new(parse_date_arg(date)?, parse_time_arg(time)?);
+    let offset = UtcOffset::local_offset_at(datetime.assume_utc()).unwrap_or(UtcOffset::UTC);
+
+    Ok(datetime.assume_offset(offset))
+}
+
+/// parse an identifier given as UUID or name (optionally prefixed with `@`)
+pub fn parse_identifier_arg(s: &str) -> Result<Identifier, String> {
+    if s.is_empty() {
+        return Err("identifier must not be empty".to_string());
+    }
+    Ok(match Uuid::from_str(s) {
+        Ok(id) => Identifier::Uuid(id),
+        Err(_) => Identifier::ByName(s.strip_prefix('@').unwrap_or(s).to_string()),
+    })
+}
+
+/// parse a duration given as a number with unit, e.g. `90s`, `5m` or `2h`, or as a sum
+/// of such components, e.g. `1h30m` or `7h 30m`, see [`pretty_duration::parse_duration`]
+///
+/// the number may have a decimal point or comma, e.g. `1.5h` or `1,5h`, as long as
+/// the duration is a whole number of seconds
+pub fn parse_duration_arg(s: &str) -> Result<Duration, String> {
+    pretty_duration::parse_duration(s).map_err(|e| {
+        format!("{e}, expected a number followed by s, m or h, e.g. 90s, 5m, 2h, 1,5h or 1h30m")
+    })
+}
+
+/// parse a project weight given as `PROJECT=WEIGHT`, e.g. `@projA=2`
+pub fn parse_project_weight(s: &str) -> Result<(Identifier, f32), String> {
+    let (project, weight) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("invalid weight '{s}', expected PROJECT=WEIGHT"))?;
+    let project = Identifier::from_str(project).map_err(|e| format!("invalid project: {e}"))?;
+    let weight = weight
+        .parse::<f32>()
+        .ok()
+        .filter(|w| w.is_finite() && *w >= 0.0)
+        .ok_or_else(|| format!("invalid weight '{weight}', expected a non-negative number"))?;
+
+    Ok((project, weight))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandStatus;
+    use clap::Parser;
+
+    #[test]
+    fn test_parse_time_arg() {
+        let accepted = [
+            ("12:30", (12, 30, 0)),
+            ("9:05", (9, 5, 0)),
+            ("09:05", (9, 5, 0)),
+            ("13:15:20", (13, 15, 20)),
+            ("9.30", (9, 30, 0)),
+            ("09.30", (9, 30, 0)),
+            ("13.15.20", (13, 15, 20)),
+            (" 9:30 ", (9, 30, 0)),
+        ];
+        for (input, (hour, minute, second)) in accepted {
+            assert_eq!(
+                parse_time_arg(input),
+                Ok(Time::from_hms(hour, minute, second).unwrap()),
+                "{input:?}"
+            );
+        }
+
+        for input in ["25:00", "noon", "9", "9:30.15", "9,30", ""] {
+            let error = parse_time_arg(input).unwrap_err();
+            assert!(
+                error.contains("HH:MM, HH:MM:SS or HH.MM"),
+                "{input:?}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_datetime_arg() {
+        let parsed = parse_datetime_arg("2024-05-03T12:30:00+02:00").unwrap();
+        assert_eq!(parsed.offset(), UtcOffset::from_hms(2, 0, 0).unwrap());
+        assert_eq!(parsed.time(), Time::from_hms(12, 30, 0).unwrap());
+
+        let parsed = parse_datetime_arg("2024-05-03 08:15").unwrap();
+        assert_eq!(
+            parsed.date(),
+            Date::from_calendar_date(2024, Month::May, 3).unwrap()
+        );
+        assert_eq!(parsed.time(), Time::from_hms(8, 15, 0).unwrap());
+
+        assert!(parse_datetime_arg("2024-05-03").is_err());
+        assert!(parse_datetime_arg("yesterday 08:15").is_err());
+    }
+
+    #[test]
+    fn test_parse_project_weight() {
+        assert_eq!(
+            parse_project_weight("@projA=2.5"),
+            Ok((Identifier::ByName("projA".into()), 2.5))
+        );
+        assert!(parse_project_weight("@projA").is_err());
+        assert!(parse_project_weight("@projA=-1").is_err());
+        assert!(parse_project_weight("=1").is_err());
+    }
+
+    #[test]
+    fn test_parse_date_arg() {
+        assert_eq!(
+            parse_date_arg("2024-05-03"),
+            Ok(Date::from_calendar_date(2024, Month::May, 3).unwrap())
+        );
+        assert!(parse_date_arg("2024-13-01").is_err());
+        assert!(parse_date_arg("03.05.2024").is_err());
+    }
+
+    #[test]
+    fn test_parse_relative_date() {
+        // a friday
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let may = |day| Date::from_calendar_date(2024, Month::May, day).unwrap();
+        let accepted = [
+            (
+                "2024-04-30",
+                Date::from_calendar_date(2024, Month::April, 30).unwrap(),
+            ),
+            (
+                "2024-06-01",
+                Date::from_calendar_date(2024, Month::June, 1).unwrap(),
+            ),
+            ("today", today),
+            ("Today", today),
+            (" yesterday ", may(2)),
+            // the most recent weekday, never a future one
+            ("friday", today),
+            ("thursday", may(2)),
+            (
+                "monday",
+                Date::from_calendar_date(2024, Month::April, 29).unwrap(),
+            ),
+            (
+                "saturday",
+                Date::from_calendar_date(2024, Month::April, 27).unwrap(),
+            ),
+            (
+                "sunday",
+                Date::from_calendar_date(2024, Month::April, 28).unwrap(),
+            ),
+            (
+                "tue",
+                Date::from_calendar_date(2024, Month::April, 30).unwrap(),
+            ),
+            ("WED", may(1)),
+            ("-0d", today),
+            ("-1d", may(2)),
+            (
+                "-3d",
+                Date::from_calendar_date(2024, Month::April, 30).unwrap(),
+            ),
+            ("+2d", may(5)),
+            (
+                "-1w",
+                Date::from_calendar_date(2024, Month::April, 26).unwrap(),
+            ),
+            ("+1w", may(10)),
+        ];
+        for (input, expected) in accepted {
+            assert_eq!(parse_relative_date(input, today), Ok(expected), "{input:?}");
+        }
+        assert_eq!(parse_relative_date("-99999999d", today), Ok(Date::MIN));
+
+        let rejected = [
+            "",
+            "now",
+            "tomorrow",
+            "mo",
+            "mondays",
+            "3d",
+            "-3",
+            "-d",
+            "--3d",
+            "-3m",
+            "-1.5d",
+            "- 3d",
+            "-3d2d",
+            "03.05.2024",
+            "2024-13-01",
+            "-99999999999999999999d",
+        ];
+        for input in rejected {
+            let error = parse_relative_date(input, today).unwrap_err();
+            assert_eq!(
+                error,
+                format!(
+                    "invalid date '{input}', expected YYYY-MM-DD, today, yesterday, a weekday such as monday or an offset such as -3d"
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_relative_dates_reject_multibyte_units_and_options() {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        for input in ["-3é", "+é", "-ü"] {
+            assert!(parse_relative_date(input, today).is_err(), "{input:?}");
+        }
+
+        // a missing value does not take the next option
+        let error = parse_relative_date_arg("--json").unwrap_err();
+        assert!(error.contains("'--json' is an option"), "{error}");
+        assert!(parse_time_or_ago_arg("--json").is_err());
+        assert!(CommandStatus::try_parse_from(["status", "--date", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_time_or_now_arg() {
+        assert_eq!(parse_time_or_now_arg("now"), Ok(TimeArg::Now));
+        assert_eq!(parse_time_or_now_arg("NOW"), Ok(TimeArg::Now));
+        assert_eq!(
+            parse_time_or_now_arg("9:30"),
+            Ok(TimeArg::At(Time::from_hms(9, 30, 0).unwrap()))
+        );
+        assert!(parse_time_or_now_arg("later").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_or_ago_arg() {
+        assert_eq!(
+            parse_time_or_ago_arg("08:45:30"),
+            Ok(TimeArg::At(Time::from_hms(8, 45, 30).unwrap()))
+        );
+        assert_eq!(
+            parse_time_or_ago_arg("-15m"),
+            Ok(TimeArg::Ago(Duration::minutes(15)))
+        );
+        assert_eq!(
+            parse_time_or_ago_arg("-1h30m"),
+            Ok(TimeArg::Ago(Duration::minutes(90)))
+        );
+        assert!(parse_time_or_ago_arg("-").is_err());
+        assert!(parse_time_or_ago_arg("-9:00").is_err());
+        assert!(parse_time_or_ago_arg("15m").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_arg() {
+        let accepted = [
+            ("90s", Duration::seconds(90)),
+            ("5m", Duration::minutes(5)),
+            ("2h", Duration::hours(2)),
+            ("1.5h", Duration::minutes(90)),
+            ("1,5h", Duration::minutes(90)),
+            ("1.75h", Duration::minutes(105)),
+            ("0,5m", Duration::seconds(30)),
+            (" 15m ", Duration::minutes(15)),
+            ("1.50h", Duration::minutes(90)),
+            ("1h30m", Duration::minutes(90)),
+            ("2h5m30s", Duration::seconds(7530)),
+            ("1.5h15m", Duration::minutes(105)),
+            ("1h 30m", Duration::minutes(90)),
+            ("5 min", Duration::minutes(5)),
+        ];
+        for (input, expected) in accepted {
+            assert_eq!(parse_duration_arg(input), Ok(expected), "{input:?}");
+        }
+
+        let rejected = [
+            "5", "m", "-5m", "1.5", ".5h", "1.h", "1.5.5h", "1,5,h", "1.5d", "", "1h30", "1hm",
+        ];
+        for input in rejected {
+            let error = parse_duration_arg(input).unwrap_err();
+            assert!(
+                error.contains("e.g. 90s, 5m, 2h, 1,5h or 1h30m"),
+                "{input:?}: {error}"
+            );
+        }
+
+        // sub-second durations are rejected rather than rounded
+        for input in ["1.333h", "1.5s", "0.001m"] {
+            let error = parse_duration_arg(input).unwrap_err();
+            assert!(error.contains("second precision"), "{input:?}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_parse_duration_arg_rejects_long_fractions() {
+        for input in ["1.99999999999999999h", "0.9223372036854775807h"] {
+            let error = parse_duration_arg(input).unwrap_err();
+            assert!(error.contains("expected a number"), "{input:?}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_parse_duration_arg_rejects_multibyte_units() {
+        for input in ["5é", "1h5€", "1,5ü", "ß"] {
+            let error = parse_duration_arg(input).unwrap_err();
+            assert!(error.contains("expected a number"), "{input:?}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_parse_identifier_arg() {
+        let id = Uuid::from_u128(42);
+        assert_eq!(
+            parse_identifier_arg(&id.to_string()),
+            Ok(Identifier::Uuid(id))
+        );
+        assert_eq!(
+            parse_identifier_arg("@meeting"),
+            Ok(Identifier::ByName("meeting".into()))
+        );
+        assert_eq!(
+            parse_identifier_arg("meeting"),
+            Ok(Identifier::ByName("meeting".into()))
+        );
+        assert!(parse_identifier_arg("").is_err());
+    }
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_period_arg() {
+        use Month::*;
+        use PeriodArg::*;
+        let cases = [
+            // leap February
+            (
+                ThisMonth,
+                date(2024, February, 15),
+                (date(2024, February, 1), date(2024, February, 29)),
+            ),
+            (
+                LastMonth,
+                date(2024, March, 31),
+                (date(2024, February, 1), date(2024, February, 29)),
+            ),
+            (
+                LastMonth,
+                date(2023, March, 1),
+                (date(2023, February, 1), date(2023, February, 28)),
+            ),
+            (
+                LastMonth,
+                date(2024, January, 10),
+                (date(2023, December, 1), date(2023, December, 31)),
+            ),
+            (
+                ThisQuarter,
+                date(2024, February, 29),
+                (date(2024, January, 1), date(2024, March, 31)),
+            ),
+            (
+                ThisQuarter,
+                date(2024, April, 1),
+                (date(2024, April, 1), date(2024, June, 30)),
+            ),
+            (
+                ThisQuarter,
+                date(2024, December, 31),
+                (date(2024, October, 1), date(2024, December, 31)),
+            ),
+            (
+                ThisYear,
+                date(2024, December, 31),
+                (date(2024, January, 1), date(2024, December, 31)),
+            ),
+            (
+                Today,
+                date(2024, May, 2),
+                (date(2024, May, 2), date(2024, May, 2)),
+            ),
+            (
+                Yesterday,
+                date(2024, January, 1),
+                (date(2023, December, 31), date(2023, December, 31)),
+            ),
+            (
+                Last7Days,
+                date(2024, March, 3),
+                (date(2024, February, 26), date(2024, March, 3)),
+            ),
+            (
+                Last30Days,
+                date(2024, March, 1),
+                (date(2024, February, 1), date(2024, March, 1)),
+            ),
+            (
+                Last30Days,
+                date(2023, March, 1),
+                (date(2023, January, 31), date(2023, March, 1)),
+            ),
+            // 2024-01-03 is a Wednesday
+            (
+                ThisWeek,
+                date(2024, January, 3),
+                (date(2024, January, 1), date(2024, January, 7)),
+            ),
+            (
+                LastWeek,
+                date(2024, January, 3),
+                (date(2023, December, 25), date(2023, December, 31)),
+            ),
+        ];
+        for (period, today, expected) in cases {
+            assert_eq!(
+                period.resolve(today, Weekday::Monday),
+                expected,
+                "{period:?} on {today}"
+            );
+        }
+
+        assert_eq!(
+            ThisWeek.resolve(date(2024, January, 3), Weekday::Sunday),
+            (date(2023, December, 31), date(2024, January, 6))
+        );
+        assert_eq!(
+            LastWeek.resolve(date(2023, December, 31), Weekday::Sunday),
+            (date(2023, December, 24), date(2023, December, 30))
+        );
+    }
+
+    #[test]
+    fn test_period_arg_names() {
+        for (name, period) in [
+            ("this-week", PeriodArg::ThisWeek),
+            ("last-month", PeriodArg::LastMonth),
+            ("this-quarter", PeriodArg::ThisQuarter),
+            ("last-7-days", PeriodArg::Last7Days),
+            ("last-30-days", PeriodArg::Last30Days),
+        ] {
+            assert_eq!(PeriodArg::from_str(name, false), Ok(period));
+        }
+    }
+}
@@ -0,0 +1,496 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{
+    DateArg, TimeArg, parse_duration_arg, parse_relative_date_arg, parse_time_arg,
+    parse_time_or_now_arg,
+};
+use crate::cli::day::ensure_unlocked;
+use crate::cli::format::{format_duration_pretty, format_table};
+use crate::cli::show::class_name;
+use crate::data::app_config::AppConfig;
+use crate::data::blocker::Blocker;
+use crate::data::day::DayInner;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::reference::{Reference, ReferenceError};
+use crate::data::text::normalize_name;
+use crate::error::Error;
+use clap::Parser;
+use itertools::Itertools;
+use log::{error, info};
+use std::fmt::Write;
+use time::{Date, Duration, Time};
+use uuid::Uuid;
+
+/// Blockers represent fixed amounts of time such as doctor appointments
+#[derive(Parser)]
+pub enum CommandBlocker {
+    /// Add a blocker, leave out --to and --duration if its end is not known yet
+    #[clap(aliases = ["new", "create"])]
+    Add {
+        /// Classification of the blocked time
+        #[arg(short, long = "class")]
+        classification: Identifier,
+        /// Start of the blocker (HH:MM[:SS] or "now")
+        #[arg(short, long, value_parser = parse_time_or_now_arg, default_value = "now")]
+        from: TimeArg,
+        /// End of the blocker (HH:MM[:SS]), open-ended if neither --to nor --duration is given
+        #[arg(short, long, value_parser = parse_time_arg)]
+        to: Option<Time>,
+        /// Length of the blocker instead of its end, e.g. 45m or 1h30m
+        #[arg(long, value_parser = parse_duration_arg, conflicts_with = "to", value_name = "DURATION")]
+        duration: Option<Duration>,
+        /// Short name for the blocker
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Project name
+        #[arg(short, long)]
+        project: Vec<Identifier>,
+    },
+    /// Complete an open blocker now
+    #[clap(aliases = ["end", "close"])]
+    Stop {
+        /// Id of the blocker
+        id: Uuid,
+        /// Day of the blocker (e.g. 2024-05-03 or yesterday), searches all days if omitted
+        #[arg(long, value_parser = parse_relative_date_arg, allow_hyphen_values = true)]
+        date: Option<DateArg>,
+    },
+    /// List the blockers of a day with their ids
+    #[clap(aliases = ["ls"])]
+    List {
+        /// Day to list (YYYY-MM-DD, or e.g. yesterday, monday, -3d), defaults to today
+        #[arg(long, value_parser = parse_relative_date_arg, allow_hyphen_values = true)]
+        date: Option<DateArg>,
+    },
+    /// Remove a blocker
+    #[clap(aliases = ["rm", "delete"])]
+    Remove {
+        /// Blocker UUID, UUID prefix, short id (see `timetrax blocker list`) or @name
+        blocker: Reference,
+        /// Day of the blocker (YYYY-MM-DD, or e.g. yesterday, monday, -3d), defaults to today
+        #[arg(long, value_parser = parse_relative_date_arg, allow_hyphen_values = true)]
+        date: Option<DateArg>,
+    },
+}
+
+/// table of the blockers of the day, the short ids are accepted by `blocker remove`
+fn render_blockers(job_config: &JobConfig, date: Date, day: Option<&DayInner>) -> String {
+    let mut out = String::new();
+    let Some(day) = day.filter(|day| !day.blockers.is_empty()) else {
+        let _ = writeln!(out, "No blockers on {}.", date);
+        return out;
+    };
+
+    let header = ["ID", "UUID", "CLASS", "TIME", "DURATION", "PROJECTS"];
+    let mut rows = vec![header.map(String::from).to_vec()];
+    for blocker in &day.blockers {
+        rows.push(vec![
+            blocker.short_id(),
+            blocker.id.to_string(),
+            class_name(job_config, &blocker.class),
+            blocker.to_string(),
+            blocker
+                .time
+                .to_day_time(job_config.day_boundary)
+                .duration()
+                .map(|duration| format_duration_pretty(duration, false))
+                .unwrap_or_else(|| "?".to_string()),
+            blocker
+                .projects
+                .iter()
+                .map(|project| {
+                    job_config
+                        .resolve_project(project)
+                        .map(|project| project.inner.name.clone())
+                        .unwrap_or_else(|| project.to_string())
+                })
+                .join(", "),
+        ]);
+    }
+
+    let _ = writeln!(out, "Blockers of {}:", date);
+    for line in format_table(&rows) {
+        let _ = writeln!(out, "{}", line);
+    }
+    out
+}
+
+/// resolve a blocker of the day by UUID, UUID prefix, short id or name
+fn find_blocker<'a>(
+    date: Date,
+    day: &'a DayInner,
+    reference: &Reference,
+) -> std::io::Result<&'a Blocker> {
+    reference.resolve(&day.blockers).map_err(|e| match e {
+        ReferenceError::NotFound => {
+            error!(
+                "No blocker of {} matches the reference '{}'",
+                date, reference
+            );
+            std::io::Error::new(std::io::ErrorKind::NotFound, "Blocker not found")
+        }
+        ReferenceError::Ambiguous(ids) => {
+            error!(
+                "Blocker reference '{}' matches several blockers, use one of the ids: {}",
+                reference,
+                ids.iter().join(", ")
+            );
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Ambiguous blocker reference",
+            )
+        }
+    })
+}
+
+impl ExecutableCommand for CommandBlocker {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        match self {
+            CommandBlocker::Add {
+                classification,
+                from,
+                to,
+                duration,
+                name,
+                project,
+            } => {
+                if job_config.resolve_class(classification).is_none() {
+                    error!("Failed to resolve classification: {:?}", classification);
+                    return Err(Error::UnknownClass(classification.clone()));
+                }
+                if let Some(id) = project
+                    .iter()
+                    .find(|id| job_config.resolve_project(*id).is_none())
+                {
+                    error!("Failed to resolve project: {:?}", id);
+                    return Err(Error::UnknownProject((*id).clone()));
+                }
+
+                let from = from.resolve();
+                let to = match duration {
+                    Some(duration) => {
+                        let Some(to) =
+                            local_time::add_within_day(from, *duration, job_config.day_boundary)
+                        else {
+                            error!(
+                                "A blocker of {} starting at {} would last into the next day",
+                                format_duration_pretty(*duration, false),
+                                from
+                            );
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidInput,
+                                "Blocker ends on the next day",
+                            )
+                            .into());
+                        };
+                        Some(to)
+                    }
+                    None => *to,
+                };
+                if let Some(to) = to
+                    && local_time::to_day_time(to, job_config.day_boundary)
+                        <= local_time::to_day_time(from, job_config.day_boundary)
+                {
+                    error!("The blocker must end after it starts: {} >= {}", from, to);
+                    return Err(Error::InvalidInterval {
+                        start: from,
+                        end: to,
+                    });
+                }
+
+                let name = match name {
+                    Some(name) => normalize_name(name, config.max_name_length)
+                        .inspect_err(|e| error!("Invalid name '{}': {}", name, e))?,
+                    None => None,
+                };
+                let blocker = Blocker {
+                    id: Uuid::new_v4(),
+                    name,
+                    class: classification.clone(),
+                    time: Interval {
+                        start: from,
+                        end: to,
+                    },
+                    projects: project.clone(),
+                };
+
+                info!("Adding blocker: {:?}", blocker);
+                manager
+                    .get_or_create_day_mut(job_config.today())
+                    .blockers
+                    .push(blocker.clone());
+                manager.save()?;
+                println!("Added blocker {}: {}", blocker.id, blocker);
+            }
+            CommandBlocker::Stop { id, date } => {
+                let date = date.map(|date| date.resolve(job_config.today()));
+                let Some(date) = manager.locate_blocker(*id, date) else {
+                    error!("Blocker {} not found", id);
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "Blocker not found",
+                    )
+                    .into());
+                };
+
+                if manager
+                    .get_or_create_day_ref(date)
+                    .find_blocker(*id)
+                    .is_some_and(|blocker| blocker.time.is_complete())
+                {
+                    error!("Blocker {} is already complete", id);
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Blocker already complete",
+                    )
+                    .into());
+                }
+
+                let messages = manager
+                    .get_or_create_day_mut(date)
+                    .find_blocker_mut(*id)
+                    .map(|blocker| stop_blocker(blocker, job_config.day_boundary))
+                    .unwrap_or_default();
+                manager.save()?;
+                for message in messages {
+                    println!("{}", message);
+                }
+            }
+            CommandBlocker::List { date } => {
+                let today = job_config.today();
+                let date = date.map_or(today, |date| date.resolve(today));
+                let day = manager.day(date);
+                print!("{}", render_blockers(job_config, date, day));
+            }
+            CommandBlocker::Remove { blocker, date } => {
+                let today = job_config.today();
+                let date = date.map_or(today, |date| date.resolve(today));
+                let Some(day) = manager.day(date) else {
+                    error!("There are no blockers on {}", date);
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "No day file for the date",
+                    )
+                    .into());
+                };
+                ensure_unlocked(date, day)?;
+                let blocker = find_blocker(date, day, blocker)?.clone();
+
+                info!("Removing blocker of {}: {:?}", date, blocker);
+                manager
+                    .get_or_create_day_mut(date)
+                    .blockers
+                    .retain(|other| other.id != blocker.id);
+                manager.save()?;
+                println!("Removed blocker {}: {}", blocker.id, blocker);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// complete an open blocker now, returning the messages reporting it
+pub fn stop_blocker(blocker: &mut Blocker, boundary: Option<Time>) -> Vec<String> {
+    info!("Stopping blocker: {:?}", blocker);
+    let mut messages = vec![];
+    if blocker.time.complete_now(boundary) {
+        messages.push(
+            "Warning: the clock moved backwards, the blocker was stopped with zero length."
+                .to_string(),
+        );
+    }
+    messages.push(format!("Stopped blocker: {blocker}"));
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPop;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Duration, Month};
+
+    fn setup() -> (ManualClock, TempDataDir, Date) {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        (clock, dir, date)
+    }
+
+    #[test]
+    fn test_open_blocker_closed_by_pop() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+
+        dir.execute(&CommandBlocker::parse_from([
+            "blocker", "add", "-c", "@work", "--from", "now",
+        ]))
+        .unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.blockers.len(), 1);
+        assert!(!day.blockers[0].time.is_complete());
+
+        clock.advance(Duration::minutes(90));
+        dir.execute(&CommandPop::parse_from(["pop", "--blocker"]))
+            .unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.blockers[0].time.duration(), Some(Duration::minutes(90)));
+    }
+
+    #[test]
+    fn test_open_blocker_closed_by_stop() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+
+        dir.execute(&CommandBlocker::parse_from([
+            "blocker", "add", "-c", "@work",
+        ]))
+        .unwrap();
+        let id = dir.day(date).unwrap().unwrap().blockers[0].id;
+
+        clock.advance(Duration::minutes(30));
+        let stop = CommandBlocker::parse_from(["blocker", "stop", &id.to_string()]);
+        dir.execute(&stop).unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.blockers[0].time.duration(), Some(Duration::minutes(30)));
+        assert!(dir.execute(&stop).is_err());
+    }
+
+    #[test]
+    fn test_add_blocker_validates() {
+        let (clock, dir, _) = setup();
+        let _guard = clock.install();
+
+        assert!(
+            dir.execute(&CommandBlocker::parse_from([
+                "blocker", "add", "-c", "@unknown",
+            ]))
+            .is_err()
+        );
+        assert!(
+            dir.execute(&CommandBlocker::parse_from([
+                "blocker", "add", "-c", "@work", "--from", "10:00", "--to", "9:00",
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_add_blocker_with_duration() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+
+        dir.execute(&CommandBlocker::parse_from([
+            "blocker",
+            "add",
+            "-c",
+            "@work",
+            "--from",
+            "10:00",
+            "--duration",
+            "1h30m",
+            "-n",
+            "doctor",
+        ]))
+        .unwrap();
+
+        // reloaded from the day file written by the manager
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(
+            day.blockers[0].time.start,
+            Time::from_hms(10, 0, 0).unwrap()
+        );
+        assert_eq!(
+            day.blockers[0].time.end,
+            Some(Time::from_hms(11, 30, 0).unwrap())
+        );
+        assert_eq!(day.blockers[0].name.as_deref(), Some("doctor"));
+
+        assert!(
+            CommandBlocker::try_parse_from([
+                "blocker",
+                "add",
+                "-c",
+                "@work",
+                "--to",
+                "11:00",
+                "--duration",
+                "1h",
+            ])
+            .is_err()
+        );
+        assert!(
+            dir.execute(&CommandBlocker::parse_from([
+                "blocker",
+                "add",
+                "-c",
+                "@work",
+                "--from",
+                "23:00",
+                "--duration",
+                "2h",
+            ]))
+            .is_err()
+        );
+        assert_eq!(dir.day(date).unwrap().unwrap().blockers.len(), 1);
+    }
+
+    #[test]
+    fn test_list_and_remove_blockers() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        let job_config = dir.job_config().unwrap();
+        assert_eq!(
+            render_blockers(&job_config, date, None),
+            "No blockers on 2024-05-03.\n"
+        );
+
+        for (from, to) in [("08:00", "09:00"), ("13:00", "14:30")] {
+            dir.execute(&CommandBlocker::parse_from([
+                "blocker", "add", "-c", "@work", "--from", from, "--to", to,
+            ]))
+            .unwrap();
+        }
+        let day = dir.day(date).unwrap().unwrap();
+        let listed = render_blockers(&job_config, date, Some(&day));
+        let lines = listed.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "Blockers of 2024-05-03:");
+        assert!(lines[1].starts_with("ID "));
+        for (line, blocker) in lines[2..].iter().zip(&day.blockers) {
+            assert!(line.starts_with(&format!("{}  {}", blocker.short_id(), blocker.id)));
+            assert!(line.contains(" work "));
+        }
+        assert!(lines[3].ends_with("1h 30m"));
+
+        let remove = |reference: &str| {
+            dir.execute(&CommandBlocker::parse_from([
+                "blocker", "remove", reference,
+            ]))
+        };
+        assert_eq!(
+            remove("zzzzzz").unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        remove(&day.blockers[0].short_id()).unwrap();
+        let remaining = dir.day(date).unwrap().unwrap().blockers;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, day.blockers[1].id);
+
+        remove(&day.blockers[1].id.to_string()).unwrap();
+        assert!(dir.day(date).unwrap().unwrap().blockers.is_empty());
+        assert!(remove(&day.blockers[1].id.to_string()).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{parse_date_arg, parse_time_arg};
+use crate::cli::format::format_duration_pretty;
+use crate::data::activity::{Activity, ActivityOrigin};
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::error::Error;
+use clap::Parser;
+use log::{error, info, warn};
+use std::collections::BTreeMap;
+use time::{Date, Duration, Time};
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub struct CommandBreak {
+    /// Start of the break (HH:MM)
+    #[arg(short, long, value_parser = parse_time_arg)]
+    from: Time,
+    /// End of the break (HH:MM)
+    #[arg(short, long, value_parser = parse_time_arg)]
+    to: Time,
+    /// Classification of the break
+    #[arg(short, long = "class", default_value = "@break")]
+    classification: Identifier,
+    /// Day to insert the break into (YYYY-MM-DD), defaults to today
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+    /// Short name for the break
+    #[arg(short, long)]
+    name: Option<String>,
+}
+
+/// per-class totals of the day's closure, clamped to `end` if given
+fn class_totals(
+    job_config: &JobConfig,
+    day: &DayInner,
+    end: Option<Time>,
+) -> BTreeMap<Identifier, Duration> {
+    let (closure, _) = Activity::calculate_activity_closure(job_config, &day.activities, None, end);
+    Activity::summarize_by_class(&closure)
+}
+
+/// check if any activity of the day overlaps the given interval
+fn overlaps_any(day: &DayInner, from: Time, to: Time, boundary: Option<Time>) -> bool {
+    let (from, to) = (
+        local_time::to_day_time(from, boundary),
+        local_time::to_day_time(to, boundary),
+    );
+    day.activities
+        .iter()
+        .map(|a| a.time.to_day_time(boundary))
+        .any(|time| time.start < to && time.end_time_or_end_of_day() > from)
+}
+
+impl ExecutableCommand for CommandBreak {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let now = local_time::now();
+        let today = job_config.date_of(now);
+        let date = self.date.unwrap_or(today);
+        let boundary = job_config.day_boundary;
+
+        if local_time::to_day_time(self.from, boundary)
+            >= local_time::to_day_time(self.to, boundary)
+        {
+            error!(
+                "The break must start before it ends: {} >= {}",
+                self.from, self.to
+            );
+            return Err(Error::InvalidInterval {
+                start: self.from,
+                end: self.to,
+            });
+        }
+
+        if date > today
+            || (date == today
+                && local_time::to_day_time(self.to, boundary)
+                    > local_time::to_day_time(now.time(), boundary))
+        {
+            error!("The break must not end in the future.");
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Break ends in the future",
+            )
+            .into());
+        }
+
+        if job_config.resolve_class(&self.classification).is_none() {
+            error!(
+                "Failed to resolve classification: {:?}",
+                self.classification
+            );
+            return Err(Error::UnknownClass(self.classification.clone()));
+        }
+
+        let end_limit = (date == today).then_some(now.time());
+        let day = manager.get_or_create_day_mut(date);
+
+        if !overlaps_any(day, self.from, self.to, boundary) {
+            warn!("No activity overlaps the break. The break extends the tracked time of the day.");
+        }
+
+        let before = class_totals(job_config, day, end_limit);
+
+        let activity = Activity {
+            id: Uuid::new_v4(),
+            class: self.classification.clone(),
+            name: self.name.clone(),
+            projects: vec![],
+            weights: BTreeMap::new(),
+            description: None,
+            created_at: Some(now),
+            modified_at: None,
+            tags: vec![],
+            evidence: vec![],
+            origin: Some(ActivityOrigin::Explicit),
+            extra: Default::default(),
+            time: Interval {
+                start: self.from,
+                end: Some(self.to),
+            },
+        };
+        info!("Inserting break: {activity}");
+        day.activities.push(activity.clone());
+
+        let after = class_totals(job_config, day, end_limit);
+        manager.save()?;
+        println!("Inserted break: {activity}");
+
+        println!("Totals per class:");
+        for class in before
+            .keys()
+            .chain(after.keys().filter(|k| !before.contains_key(*k)))
+        {
+            let old = before.get(class).copied().unwrap_or_default();
+            let new = after.get(class).copied().unwrap_or_default();
+            let name = match job_config.resolve_class(class) {
+                Some(class) => class.inner.name.as_str(),
+                None => {
+                    error!("Failed to resolve class with id {}", class);
+                    "ERR"
+                }
+            };
+            println!(
+                " - {}: {} -> {} ({}{})",
+                name,
+                format_duration_pretty(old, false),
+                format_duration_pretty(new, false),
+                if new >= old { "+" } else { "" },
+                format_duration_pretty(new - old, false)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn time(h: u8, m: u8) -> Time {
+        Time::from_hms(h, m, 0).unwrap()
+    }
+
+    fn activity(class: &str, start: Time, end: Option<Time>) -> Activity {
+        testing::activity(class, start, end)
+    }
+
+    #[test]
+    fn test_break_reduces_work() {
+        let job_config = JobConfig::default();
+        let work = job_config
+            .resolve_class(Identifier::ByName("work".into()))
+            .unwrap()
+            .id;
+        let pause = job_config
+            .resolve_class(Identifier::ByName("break".into()))
+            .unwrap()
+            .id;
+        let mut day = DayInner::default();
+        day.activities.push(activity("work", time(9, 0), None));
+
+        assert!(overlaps_any(&day, time(12, 30), time(13, 15), None));
+        let before = class_totals(&job_config, &day, Some(time(14, 0)));
+        day.activities
+            .push(activity("break", time(12, 30), Some(time(13, 15))));
+        let after = class_totals(&job_config, &day, Some(time(14, 0)));
+
+        assert_eq!(before[&work.into()], Duration::hours(5));
+        assert_eq!(after[&work.into()], Duration::minutes(4 * 60 + 15));
+        assert_eq!(after[&pause.into()], Duration::minutes(45));
+    }
+
+    #[test]
+    fn test_break_without_overlap() {
+        let mut day = DayInner::default();
+        day.activities
+            .push(activity("work", time(9, 0), Some(time(12, 0))));
+
+        assert!(!overlaps_any(&day, time(12, 30), time(13, 15), None));
+        assert!(overlaps_any(&day, time(11, 30), time(13, 15), None));
+    }
+}
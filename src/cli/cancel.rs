@@ -0,0 +1,217 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_duration_arg;
+use crate::cli::day::ensure_unlocked;
+use crate::cli::format::format_elapsed;
+use crate::data::app_config::AppConfig;
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::reference::Reference;
+use clap::Parser;
+use log::{error, info};
+use time::Duration;
+
+#[derive(Parser)]
+pub struct CommandCancel {
+    /// Open activity to discard (UUID, UUID prefix, short id or @name), defaults to the most
+    /// recent one
+    #[arg(long)]
+    id: Option<Reference>,
+    /// Refuse to discard activities that already ran longer than this, e.g. 5m
+    #[arg(long, value_parser = parse_duration_arg, value_name = "DURATION")]
+    keep_if_longer_than: Option<Duration>,
+    /// Discard the activity even if it ran longer than --keep-if-longer-than
+    #[arg(short, long)]
+    force: bool,
+}
+
+impl ExecutableCommand for CommandCancel {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let now = local_time::now();
+        let date = job_config.date_of(now);
+        let boundary = job_config.day_boundary;
+        let day = manager.get_or_create_day_ref(date);
+        ensure_unlocked(date, day)?;
+
+        let activity = match &self.id {
+            Some(reference) => {
+                let activity = day.find_activity_by_reference(reference).map_err(|e| {
+                    error!("{}", e);
+                    std::io::Error::from(e)
+                })?;
+                if activity.time.is_complete() {
+                    error!(
+                        "{} already ended, completed activities are removed with `timetrax remove`",
+                        activity
+                    );
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Activity already ended",
+                    )
+                    .into());
+                }
+                activity
+            }
+            None => {
+                let Some(activity) = day
+                    .activities
+                    .iter()
+                    .filter(|a| !a.time.is_complete())
+                    .max_by_key(|a| local_time::to_day_time(a.time.start, boundary))
+                else {
+                    println!("No open activities.");
+                    return Ok(());
+                };
+                activity
+            }
+        }
+        .clone();
+
+        let elapsed = activity
+            .time
+            .to_day_time(boundary)
+            .elapsed_at(local_time::to_day_time(now.time(), boundary));
+        if let Some(limit) = self.keep_if_longer_than
+            && elapsed > limit
+            && !self.force
+        {
+            error!(
+                "{} is running for {} already, pass --force to discard it anyway",
+                activity,
+                format_elapsed(elapsed)
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Activity ran longer than --keep-if-longer-than",
+            )
+            .into());
+        }
+
+        info!("Cancelling activity: {:?}", activity);
+        manager
+            .get_or_create_day_mut(date)
+            .delete_activity(activity.id, now);
+        manager.save()?;
+        Journal::new(config, manager.data_path()).append(&JournalEntry {
+            at: now,
+            command: "cancel".to_string(),
+            date,
+            removed: vec![activity.clone()],
+            added: vec![],
+        })?;
+        println!(
+            "Discarded activity: {} (ran {})",
+            activity,
+            format_elapsed(elapsed)
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{CommandPop, CommandPush};
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Date, Month, Time};
+
+    fn setup() -> (ManualClock, TempDataDir, Date) {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        (clock, dir, date)
+    }
+
+    fn push(dir: &TempDataDir, name: &str) {
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", name,
+        ]))
+        .unwrap();
+    }
+
+    fn cancel(dir: &TempDataDir, args: &[&str]) -> crate::error::Result<()> {
+        dir.execute(&CommandCancel::parse_from(["cancel"].iter().chain(args)))
+    }
+
+    fn names(dir: &TempDataDir, date: Date) -> Vec<String> {
+        dir.day(date)
+            .unwrap()
+            .unwrap()
+            .activities
+            .into_iter()
+            .filter_map(|activity| activity.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_cancel_discards_the_most_recent_open_activity() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        push(&dir, "coding");
+        clock.advance(Duration::minutes(5));
+        push(&dir, "mistake");
+        clock.advance(Duration::seconds(30));
+
+        cancel(&dir, &[]).unwrap();
+        assert_eq!(names(&dir, date), ["coding"]);
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(entries[0].command, "cancel");
+        assert_eq!(entries[0].removed[0].name.as_deref(), Some("mistake"));
+
+        // the remaining activity is still open, it is not ended
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(!day.activities[0].time.is_complete());
+
+        let id = day.activities[0].short_id();
+        cancel(&dir, &["--id", &id]).unwrap();
+        assert!(names(&dir, date).is_empty());
+        cancel(&dir, &[]).unwrap();
+    }
+
+    #[test]
+    fn test_cancel_duration_guard() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        push(&dir, "long");
+        clock.advance(Duration::minutes(10));
+
+        let result = cancel(&dir, &["--keep-if-longer-than", "5m"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(names(&dir, date), ["long"]);
+
+        cancel(&dir, &["--keep-if-longer-than", "15m"]).unwrap();
+        assert!(names(&dir, date).is_empty());
+
+        push(&dir, "forced");
+        clock.advance(Duration::minutes(10));
+        cancel(&dir, &["--keep-if-longer-than", "5m", "--force"]).unwrap();
+        assert!(names(&dir, date).is_empty());
+    }
+
+    #[test]
+    fn test_cancel_never_discards_completed_activities() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        push(&dir, "done");
+        clock.advance(Duration::minutes(1));
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+
+        cancel(&dir, &[]).unwrap();
+        assert_eq!(names(&dir, date), ["done"]);
+
+        let id = dir.day(date).unwrap().unwrap().activities[0].short_id();
+        let result = cancel(&dir, &["--id", &id, "--force"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(names(&dir, date), ["done"]);
+    }
+}
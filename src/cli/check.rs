@@ -0,0 +1,198 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{DateArg, parse_relative_date_arg};
+use crate::data::app_config::AppConfig;
+use crate::data::integrity::{Problem, Severity};
+use crate::data::job_config::JobConfig;
+use crate::data::manager::Manager;
+use clap::Parser;
+use log::error;
+use std::fmt::Write;
+use time::Date;
+
+#[derive(Parser, Default)]
+pub struct CommandCheck {
+    /// Check every day instead of a single one
+    #[arg(short, long, conflicts_with = "date")]
+    all: bool,
+    /// Day to check (e.g. 2024-05-03 or yesterday), defaults to today
+    #[arg(long, value_parser = parse_relative_date_arg, allow_hyphen_values = true)]
+    date: Option<DateArg>,
+    /// Fail on warnings as well, e.g. when run from cron
+    #[arg(long)]
+    exit_nonzero_on_warning: bool,
+}
+
+/// the problems as one line each, followed by a summary of the checked days
+fn render_problems(days: usize, problems: &[(Date, Problem)]) -> String {
+    let mut out = String::new();
+    for (date, problem) in problems {
+        let _ = writeln!(out, "{} {}", date, problem);
+    }
+    let count = |severity| {
+        problems
+            .iter()
+            .filter(|(_, problem)| problem.severity() == severity)
+            .count()
+    };
+    if problems.is_empty() {
+        let _ = writeln!(out, "Checked {} day(s), no problems found.", days);
+    } else {
+        let _ = writeln!(
+            out,
+            "Checked {} day(s): {} error(s), {} warning(s).",
+            days,
+            count(Severity::Error),
+            count(Severity::Warning)
+        );
+    }
+    out
+}
+
+impl CommandCheck {
+    /// the checked days and the problems found on them
+    fn check(&self, job_config: &JobConfig, manager: &Manager) -> (usize, Vec<(Date, Problem)>) {
+        let days = if self.all {
+            manager
+                .days()
+                .map(|(date, day)| (*date, day))
+                .collect::<Vec<_>>()
+        } else {
+            let date = (self.date.map(|date| date.resolve(job_config.today())))
+                .unwrap_or_else(|| job_config.today());
+            manager
+                .day(date)
+                .map(|day| (date, day))
+                .into_iter()
+                .collect()
+        };
+        let problems = days
+            .iter()
+            .flat_map(|(date, day)| {
+                (day.problems(job_config).into_iter()).map(|problem| (*date, problem))
+            })
+            .collect();
+        (days.len(), problems)
+    }
+
+    /// worst severity failing the check
+    fn failing(&self) -> Severity {
+        if self.exit_nonzero_on_warning {
+            Severity::Warning
+        } else {
+            Severity::Error
+        }
+    }
+}
+
+impl ExecutableCommand for CommandCheck {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let (days, problems) = self.check(job_config, &manager);
+        print!("{}", render_problems(days, &problems));
+
+        if problems
+            .iter()
+            .any(|(_, problem)| problem.severity() >= self.failing())
+        {
+            error!("The check found problems");
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Integrity check failed",
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Month, Time};
+
+    /// a data directory with the days of testdata/broken
+    fn broken_fixture() -> TempDataDir {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/broken");
+        for entry in std::fs::read_dir(fixture).unwrap() {
+            let path = entry.unwrap().path();
+            dir.write_day_file(
+                path.file_name().unwrap().to_str().unwrap(),
+                &std::fs::read_to_string(&path).unwrap(),
+            )
+            .unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_check_broken_days() {
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(12, 0, 0).unwrap())).install();
+        let dir = broken_fixture();
+        let job_config = dir.job_config().unwrap();
+        let manager = dir.manager().unwrap();
+        let check = |args: &[&str]| {
+            CommandCheck::parse_from(["check"].iter().chain(args)).check(&job_config, &manager)
+        };
+
+        let (days, problems) = check(&["--all"]);
+        assert_eq!(days, 3);
+        let text = render_problems(days, &problems);
+        assert!(text.starts_with(
+            "2024-05-02 error 00000000-0000-4000-8000-000000000002: activity ends at 10:00:00.0 \
+             before it starts at 11:00:00.0\n"
+        ));
+        assert!(text.ends_with("Checked 3 day(s): 2 error(s), 5 warning(s).\n"));
+        assert!(text.contains(
+            "2024-05-06 warning 00000000-0000-4000-8000-00000000000b: activity overlaps the \
+             identical activity 00000000-0000-4000-8000-00000000000a\n"
+        ));
+        assert_eq!(manager.warn_problems(&job_config), problems.len());
+
+        let (days, problems) = check(&["--date", "2024-05-03"]);
+        assert_eq!(
+            render_problems(days, &problems),
+            "Checked 1 day(s), no problems found.\n"
+        );
+        // today
+        assert_eq!(check(&[]).1.len(), 1);
+        assert_eq!(check(&["--date", "2024-05-01"]).0, 0);
+    }
+
+    #[test]
+    fn test_check_exit_status() {
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(12, 0, 0).unwrap())).install();
+        let dir = broken_fixture();
+
+        assert!(
+            dir.execute(&CommandCheck::parse_from(["check", "--all"]))
+                .is_err()
+        );
+        // today only has a warning
+        dir.execute(&CommandCheck::parse_from(["check"])).unwrap();
+        assert!(
+            dir.execute(&CommandCheck::parse_from([
+                "check",
+                "--exit-nonzero-on-warning"
+            ]))
+            .is_err()
+        );
+        dir.execute(&CommandCheck::parse_from([
+            "check",
+            "--date",
+            "2024-05-03",
+            "--exit-nonzero-on-warning",
+        ]))
+        .unwrap();
+        assert!(CommandCheck::try_parse_from(["check", "--all", "--date", "today"]).is_err());
+    }
+}
@@ -1,11 +1,19 @@
 use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_identifier_arg;
+use crate::cli::color::parse_color_arg;
+use crate::cli::day::ensure_unlocked;
 use crate::data::activity_class::{ActivityClass, ActivityClassInner};
 use crate::data::app_config::AppConfig;
 use crate::data::identifier::Identifier;
 use crate::data::job_config::JobConfig;
 use crate::data::manager::Manager;
+use crate::data::report::ReportFilter;
+use crate::data::text::{did_you_mean, require_name};
+use crate::error::Error;
 use clap::Parser;
-use log::error;
+use itertools::Itertools;
+use log::{error, warn};
+use time::Date;
 use uuid::Uuid;
 
 #[derive(Parser, Default)]
@@ -19,6 +27,12 @@ pub enum CommandClass {
     Remove {
         /// Class identifier
         class: Identifier,
+        /// Move the activities and blockers of the class to this class
+        #[arg(short, long, value_name = "CLASS", value_parser = parse_identifier_arg)]
+        reassign_to: Option<Identifier>,
+        /// Remove the class even if activities or blockers still refer to it
+        #[arg(short, long, conflicts_with = "reassign_to")]
+        force: bool,
     },
     /// Add a new class
     #[clap(aliases = ["new", "create"])]
@@ -29,17 +43,108 @@ pub enum CommandClass {
         priority: i32,
         /// Description of the class
         description: Option<String>,
+        /// Terminal color of the class, a name like red or a hex code like #ff8800
+        #[arg(short, long, value_parser = parse_color_arg)]
+        color: Option<String>,
+        /// Pushing an activity of the class stops the open activities
+        #[arg(short = 'x', long)]
+        exclusive: bool,
+    },
+    /// Change name, priority, description, color or exclusivity of a class
+    #[clap(aliases = ["modify", "set"])]
+    Edit {
+        /// Class identifier
+        class: Identifier,
+        /// New class name
+        #[arg(short, long)]
+        name: Option<String>,
+        /// New priority
+        #[arg(short, long)]
+        priority: Option<i32>,
+        /// New description of the class
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Terminal color of the class, a name like red or a hex code like #ff8800
+        #[arg(short, long, value_parser = parse_color_arg)]
+        color: Option<String>,
+        /// Remove the color of the class
+        #[arg(long, conflicts_with = "color")]
+        clear_color: bool,
+        /// Whether pushing an activity of the class stops the open activities
+        #[arg(short = 'x', long)]
+        exclusive: Option<bool>,
     },
 }
 
+/// ids of the activities and blockers of all days with the class `id`, by date
+fn class_references(job_config: &JobConfig, manager: &Manager, id: Uuid) -> Vec<(Date, Uuid)> {
+    let is_removed =
+        |class: &Identifier| job_config.resolve_class(class).is_some_and(|c| c.id == id);
+    manager
+        .days()
+        .flat_map(|(date, day)| {
+            let activities = day
+                .activities
+                .iter()
+                .filter(|a| is_removed(&a.class))
+                .map(|a| a.id);
+            let blockers = day
+                .blockers
+                .iter()
+                .filter(|b| is_removed(&b.class))
+                .map(|b| b.id);
+            activities
+                .chain(blockers)
+                .map(|id| (*date, id))
+                .collect_vec()
+        })
+        .collect()
+}
+
+/// e.g. `Unknown class 'wrok'. Did you mean: work?`
+pub fn unknown_class_message(job_config: &JobConfig, class: &Identifier) -> String {
+    match class {
+        Identifier::ByName(name) => {
+            let suggestions = job_config.suggest_class(name);
+            let names = suggestions
+                .iter()
+                .map(|class| class.inner.name.as_str())
+                .collect::<Vec<_>>();
+            format!("Unknown class '{}'.{}", name, did_you_mean(&names))
+        }
+        Identifier::Uuid(id) => format!("Unknown class {}.", id),
+    }
+}
+
+/// fails with the names of the known classes if the filter refers to an unknown one
+pub fn check_class_filter(
+    job_config: &JobConfig,
+    filter: &ReportFilter,
+) -> crate::error::Result<()> {
+    let unknown = filter.unknown_classes(job_config);
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    error!(
+        "Unknown class {}, known classes: {}",
+        unknown.iter().join(", "),
+        job_config
+            .classes
+            .iter()
+            .map(|class| &class.inner.name)
+            .join(", ")
+    );
+    Err(Error::UnknownClass(unknown[0].clone()))
+}
+
 impl ExecutableCommand for CommandClass {
-    type Error = std::io::Error;
+    type Error = crate::error::Error;
     type Output = ();
     fn execute(
         &self,
-        _config: &AppConfig,
+        config: &AppConfig,
         job_config: &mut JobConfig,
-        _manager: Manager,
+        manager: Manager,
     ) -> Result<Self::Output, Self::Error> {
         match self {
             CommandClass::List => {
@@ -67,13 +172,14 @@ impl ExecutableCommand for CommandClass {
                 name,
                 description,
                 priority,
+                color,
+                exclusive,
             } => {
+                let name = &require_name(name, config.max_name_length)
+                    .inspect_err(|e| error!("Invalid class name '{}': {}", name, e))?;
                 if job_config.classes.iter().any(|p| p.inner.name == *name) {
                     error!("Activity class with name '{}' already exists", name);
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Activity class already exists",
-                    ));
+                    return Err(std::io::Error::other("Activity class already exists").into());
                 }
 
                 let new_class = ActivityClass {
@@ -81,6 +187,9 @@ impl ExecutableCommand for CommandClass {
                     inner: ActivityClassInner {
                         name: name.clone(),
                         description: description.clone(),
+                        target_share: None,
+                        color: color.clone(),
+                        exclusive: *exclusive,
                         priority: *priority,
                     },
                 };
@@ -88,30 +197,274 @@ impl ExecutableCommand for CommandClass {
 
                 println!("Added new activity class: {}", name);
             }
-            CommandClass::Remove { class } => {
-                let len_before = job_config.classes.len();
-
-                job_config.classes.retain(|p| match class {
-                    Identifier::Uuid(id) => &p.id != id,
-                    Identifier::ByName(name) => &p.inner.name != name,
-                });
-
-                let len_after = job_config.classes.len();
-
-                if len_before == len_after {
-                    error!("Activity class not found: {:?}", class);
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        "Activity class not found",
-                    ));
-                } else {
-                    println!("Removed activity class: {:?}", class);
+            CommandClass::Edit {
+                class,
+                name,
+                priority,
+                description,
+                color,
+                clear_color,
+                exclusive,
+            } => {
+                let name = match name {
+                    Some(name) => Some(
+                        require_name(name, config.max_name_length)
+                            .inspect_err(|e| error!("Invalid class name '{}': {}", name, e))?,
+                    ),
+                    None => None,
+                };
+                if let Some(name) = &name
+                    && job_config
+                        .classes
+                        .iter()
+                        .any(|c| c.inner.name == *name && !c.identifier_matches(class))
+                {
+                    error!("Activity class with name '{}' already exists", name);
+                    return Err(std::io::Error::other("Activity class already exists").into());
                 }
 
-                // todo remove reference from other activities
+                let Some(target) = job_config.resolve_class_mut(class) else {
+                    error!("{}", unknown_class_message(job_config, class));
+                    return Err(Error::UnknownClass(class.clone()));
+                };
+
+                if let Some(name) = name {
+                    target.inner.name = name;
+                }
+                if let Some(priority) = priority {
+                    target.inner.priority = *priority;
+                }
+                if let Some(description) = description {
+                    target.inner.description = Some(description.clone());
+                }
+                if let Some(color) = color {
+                    target.inner.color = Some(color.clone());
+                }
+                if *clear_color {
+                    target.inner.color = None;
+                }
+                if let Some(exclusive) = exclusive {
+                    target.inner.exclusive = *exclusive;
+                }
+                println!("Updated activity class: {}", target.inner.name);
+            }
+            CommandClass::Remove {
+                class,
+                reassign_to,
+                force,
+            } => {
+                let Some(removed) = job_config.resolve_class(class).cloned() else {
+                    error!("{}", unknown_class_message(job_config, class));
+                    return Err(Error::UnknownClass(class.clone()));
+                };
+                let replacement = match reassign_to {
+                    Some(target) => {
+                        let Some(replacement) = job_config.resolve_class(target) else {
+                            error!("{}", unknown_class_message(job_config, target));
+                            return Err(Error::UnknownClass(target.clone()));
+                        };
+                        if replacement.id == removed.id {
+                            error!("Cannot reassign the activities of a class to itself");
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidInput,
+                                "Replacement is the removed class",
+                            )
+                            .into());
+                        }
+                        Some(replacement.id)
+                    }
+                    None => None,
+                };
+
+                let mut manager = manager;
+                let references = class_references(job_config, &manager, removed.id);
+                if !references.is_empty() {
+                    match replacement {
+                        Some(replacement) => {
+                            for date in references.iter().map(|(date, _)| *date).dedup() {
+                                if let Some(day) = manager.day(date) {
+                                    ensure_unlocked(date, day)?;
+                                }
+                            }
+                            for (date, id) in &references {
+                                let day = manager.get_or_create_day_mut(*date);
+                                let activities =
+                                    day.activities.iter_mut().map(|a| (a.id, &mut a.class));
+                                let blockers =
+                                    day.blockers.iter_mut().map(|b| (b.id, &mut b.class));
+                                for (_, class) in
+                                    activities.chain(blockers).filter(|(other, _)| other == id)
+                                {
+                                    *class = Identifier::Uuid(replacement);
+                                }
+                            }
+                            println!(
+                                "Reassigned {} activities and blockers to {}",
+                                references.len(),
+                                job_config
+                                    .resolve_class(Identifier::Uuid(replacement))
+                                    .map(|class| class.inner.name.as_str())
+                                    .unwrap_or_default()
+                            );
+                        }
+                        None if *force => {
+                            warn!(
+                                "{} activities and blockers keep referring to the removed class {}",
+                                references.len(),
+                                removed.inner.name
+                            );
+                        }
+                        None => {
+                            error!(
+                                "The class {} is still used, pass --reassign-to CLASS to move the activities and blockers or --force to keep them",
+                                removed.inner.name
+                            );
+                            println!("Activities and blockers of {}:", removed.inner.name);
+                            for (date, id) in &references {
+                                println!(" - {} {}", date, id);
+                            }
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidInput,
+                                "Activity class still in use",
+                            )
+                            .into());
+                        }
+                    }
+                }
+
+                job_config.classes.retain(|c| c.id != removed.id);
+                println!("Removed activity class: {}", removed.inner.name);
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDataDir;
+    use time::Month;
+
+    const CLASS_REFERENCE_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/class_reference_day.json"
+    ));
+
+    fn setup() -> (Date, TempDataDir) {
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-06.json", CLASS_REFERENCE_DAY)
+            .unwrap();
+        (date, dir)
+    }
+
+    fn class_id(dir: &TempDataDir, name: &str) -> Uuid {
+        dir.job_config()
+            .unwrap()
+            .resolve_class(Identifier::ByName(name.into()))
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn test_remove_class_in_use_is_refused() {
+        let (date, dir) = setup();
+        let result = dir.execute(&CommandClass::parse_from(["class", "remove", "break"]));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+        let job_config = dir.job_config().unwrap();
+        assert!(
+            job_config
+                .resolve_class(Identifier::ByName("break".into()))
+                .is_some()
+        );
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities[1].class, Identifier::ByName("break".into()));
+        assert_eq!(day.blockers[0].class, Identifier::ByName("break".into()));
+    }
+
+    #[test]
+    fn test_remove_class_reassigns_references() {
+        let (date, dir) = setup();
+        let work = class_id(&dir, "work");
+        dir.execute(&CommandClass::parse_from([
+            "class",
+            "remove",
+            "break",
+            "--reassign-to",
+            "work",
+        ]))
+        .unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        assert!(
+            job_config
+                .resolve_class(Identifier::ByName("break".into()))
+                .is_none()
+        );
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities[0].class, Identifier::ByName("work".into()));
+        assert_eq!(day.activities[1].class, Identifier::Uuid(work));
+        assert_eq!(day.blockers[0].class, Identifier::Uuid(work));
+    }
+
+    #[test]
+    fn test_remove_class_reassign_to_itself_is_refused() {
+        let (_, dir) = setup();
+        let result = dir.execute(&CommandClass::parse_from([
+            "class",
+            "remove",
+            "break",
+            "--reassign-to",
+            "break",
+        ]));
+        assert!(result.is_err());
+        assert!(
+            CommandClass::try_parse_from([
+                "class",
+                "remove",
+                "break",
+                "--reassign-to",
+                "work",
+                "--force",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_remove_class_forced_keeps_references() {
+        let (date, dir) = setup();
+        dir.execute(&CommandClass::parse_from([
+            "class", "remove", "break", "--force",
+        ]))
+        .unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        assert!(
+            job_config
+                .resolve_class(Identifier::ByName("break".into()))
+                .is_none()
+        );
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities[1].class, Identifier::ByName("break".into()));
+        assert_eq!(day.blockers[0].class, Identifier::ByName("break".into()));
+    }
+
+    #[test]
+    fn test_remove_unused_class() {
+        let (_, dir) = setup();
+        dir.execute(&CommandClass::parse_from(["class", "add", "errand", "3"]))
+            .unwrap();
+        dir.execute(&CommandClass::parse_from(["class", "remove", "errand"]))
+            .unwrap();
+        let job_config = dir.job_config().unwrap();
+        assert!(
+            job_config
+                .resolve_class(Identifier::ByName("errand".into()))
+                .is_none()
+        );
+    }
+}
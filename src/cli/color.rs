@@ -0,0 +1,126 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// terminal color of a class, a name like `red` or a hex code like `#ff8800`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// one of the 16 standard colors, the SGR code of the foreground
+    Named(u8),
+    Rgb(u8, u8, u8),
+}
+
+const NAMED_COLORS: [(&str, u8); 16] = [
+    ("black", 30),
+    ("red", 31),
+    ("green", 32),
+    ("yellow", 33),
+    ("blue", 34),
+    ("magenta", 35),
+    ("cyan", 36),
+    ("white", 37),
+    ("gray", 90),
+    ("bright-red", 91),
+    ("bright-green", 92),
+    ("bright-yellow", 93),
+    ("bright-blue", 94),
+    ("bright-magenta", 95),
+    ("bright-cyan", 96),
+    ("bright-white", 97),
+];
+
+pub const RED: Color = Color::Named(31);
+pub const GREEN: Color = Color::Named(32);
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+                _ => Err(format!(
+                    "invalid color '{s}', expected a hex code like #ff8800"
+                )),
+            };
+        }
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, code)| Color::Named(*code))
+            .ok_or_else(|| {
+                format!(
+                    "unknown color '{s}', expected a hex code like #ff8800 or one of {}",
+                    NAMED_COLORS.map(|(name, _)| name).join(", ")
+                )
+            })
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Color::Named(code) => match NAMED_COLORS.iter().find(|(_, c)| c == code) {
+                Some((name, _)) => write!(f, "{name}"),
+                None => write!(f, "{code}"),
+            },
+            Color::Rgb(r, g, b) => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+        }
+    }
+}
+
+impl Color {
+    /// `text` wrapped in the escape sequences of the color
+    pub fn paint(&self, text: &str) -> String {
+        match self {
+            Color::Named(code) => format!("\x1b[{code}m{text}\x1b[0m"),
+            Color::Rgb(r, g, b) => format!("\x1b[38;2;{r};{g};{b}m{text}\x1b[0m"),
+        }
+    }
+}
+
+/// parser of clap arguments, normalizes the color
+pub fn parse_color_arg(s: &str) -> Result<String, String> {
+    Color::from_str(s).map(|color| color.to_string())
+}
+
+/// columns taken by the text on a terminal, escape sequences take none
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip up to and including the final letter of the sequence
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_colors() {
+        assert_eq!("red".parse(), Ok(Color::Named(31)));
+        assert_eq!(" Bright-Blue ".parse(), Ok(Color::Named(94)));
+        assert_eq!("#FF8800".parse(), Ok(Color::Rgb(255, 136, 0)));
+        assert!("#ff88".parse::<Color>().is_err());
+        assert!("#gg8800".parse::<Color>().is_err());
+        assert!("pink".parse::<Color>().is_err());
+        assert_eq!(parse_color_arg("#FF8800"), Ok("#ff8800".to_string()));
+        assert_eq!(parse_color_arg("Green"), Ok("green".to_string()));
+
+        let painted = RED.paint("late");
+        assert_eq!(painted, "\x1b[31mlate\x1b[0m");
+        assert_eq!(visible_width(&painted), 4);
+        assert_eq!(visible_width(&Color::Rgb(1, 2, 3).paint("äb")), 2);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{parse_date_arg, parse_duration_arg};
+use crate::cli::format::format_duration_pretty;
+use crate::data::app_config::AppConfig;
+use crate::data::compact;
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use clap::Parser;
+use itertools::Itertools;
+use log::info;
+use time::{Date, Duration};
+
+#[derive(Parser)]
+pub struct CommandCompact {
+    /// Day to compact (YYYY-MM-DD), defaults to today
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+    /// Compact all days before the given date (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date_arg, conflicts_with = "date")]
+    before: Option<Date>,
+    /// Largest gap between fragments that are merged, e.g. 90s or 5m
+    #[arg(short, long, value_parser = parse_duration_arg)]
+    tolerance: Option<Duration>,
+    /// Only print which activities would be merged
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl ExecutableCommand for CommandCompact {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let tolerance = self.tolerance.unwrap_or(config.compact_tolerance);
+        let dates = match (self.date, self.before) {
+            (_, Some(before)) => manager.dates().filter(|date| *date < before).collect_vec(),
+            (date, None) => {
+                let date = date.unwrap_or_else(|| job_config.today());
+                manager
+                    .contains_day(date)
+                    .then_some(date)
+                    .into_iter()
+                    .collect()
+            }
+        };
+        let journal = Journal::new(config, manager.data_path());
+
+        let mut fragments = 0;
+        let mut merged = 0;
+        let mut entries = Vec::new();
+        for date in dates {
+            let Some(day) = manager.annotated_day_mut(date) else {
+                continue;
+            };
+            let merges = compact::plan(day.inner(), tolerance);
+            if merges.is_empty() {
+                continue;
+            }
+            if day.inner().locked {
+                println!("Skipping locked day {}.", date);
+                continue;
+            }
+
+            for merge in &merges {
+                println!(
+                    "{}: merging {} fragments into {} ({})",
+                    date,
+                    merge.fragments.len(),
+                    merge.merged,
+                    format_duration_pretty(merge.merged.time.duration().unwrap_or_default(), true)
+                );
+                for fragment in &merge.fragments {
+                    println!("   - {}", fragment);
+                }
+            }
+            fragments += merges
+                .iter()
+                .map(|merge| merge.fragments.len())
+                .sum::<usize>();
+            merged += merges.len();
+
+            if self.dry_run {
+                continue;
+            }
+
+            info!("Compacting {} activities of {}", merges.len(), date);
+            compact::apply(day.inner_mut(), &merges, local_time::now());
+            entries.extend(merges.into_iter().map(|merge| JournalEntry {
+                at: local_time::now(),
+                command: "compact".to_string(),
+                date,
+                removed: merge.fragments,
+                added: vec![merge.merged],
+            }));
+        }
+
+        // nothing is journaled if the days cannot be saved
+        manager.save()?;
+        for entry in &entries {
+            journal.append(entry)?;
+        }
+
+        if merged == 0 {
+            println!("Nothing to compact.");
+        } else if self.dry_run {
+            println!(
+                "Would merge {} fragments into {} activities.",
+                fragments, merged
+            );
+        } else {
+            println!("Merged {} fragments into {} activities.", fragments, merged);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::Activity;
+    use crate::data::day::{Day, DayInner};
+    use crate::testing::{self, TempDataDir};
+    use time::{Month, Time};
+
+    fn fragment(start: (u8, u8), end: (u8, u8)) -> Activity {
+        Activity {
+            name: Some("coding".into()),
+            ..testing::activity(
+                "work",
+                Time::from_hms(start.0, start.1, 0).unwrap(),
+                Some(Time::from_hms(end.0, end.1, 0).unwrap()),
+            )
+        }
+    }
+
+    fn day(date: Date, locked: bool) -> Day {
+        Day {
+            date,
+            inner: DayInner {
+                activities: vec![fragment((9, 0), (9, 30)), fragment((9, 30), (10, 0))],
+                locked,
+                ..DayInner::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_compact_before_skips_locked_days() {
+        let first = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let second = first.next_day().unwrap();
+        let third = second.next_day().unwrap();
+        let dir = TempDataDir::with_days(
+            &JobConfig::default(),
+            [day(first, false), day(second, true), day(third, false)],
+        )
+        .unwrap();
+
+        dir.execute(&CommandCompact::parse_from([
+            "compact",
+            "--before",
+            "2024-05-04",
+            "--dry-run",
+        ]))
+        .unwrap();
+        assert_eq!(dir.day(first).unwrap().unwrap().activities.len(), 2);
+
+        dir.execute(&CommandCompact::parse_from([
+            "compact",
+            "--before",
+            "2024-05-04",
+        ]))
+        .unwrap();
+
+        let compacted = dir.day(first).unwrap().unwrap();
+        assert_eq!(compacted.activities.len(), 1);
+        assert_eq!(
+            compacted.activities[0].time.duration(),
+            Some(Duration::hours(1))
+        );
+        assert_eq!(dir.day(second).unwrap().unwrap().activities.len(), 2);
+        assert_eq!(dir.day(third).unwrap().unwrap().activities.len(), 2);
+
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].date, first);
+        assert_eq!(entries[0].removed.len(), 2);
+        assert_eq!(entries[0].added[0].id, compacted.activities[0].id);
+    }
+}
@@ -1,9 +1,10 @@
-use crate::cli::{AppArgs, ExecutableCommand};
+use crate::cli::{AppArgs, ExecutableCommand, POSITIONAL_COMPLETIONS};
 use crate::data::app_config::AppConfig;
 use crate::data::job_config::JobConfig;
 use crate::data::manager::Manager;
 use clap::{CommandFactory, Parser, ValueEnum};
 use clap_complete::Shell;
+use itertools::Itertools;
 use log::error;
 use std::io::BufWriter;
 use std::path::PathBuf;
@@ -19,7 +20,7 @@ pub struct CommandCompletion {
 }
 
 impl ExecutableCommand for CommandCompletion {
-    type Error = std::io::Error;
+    type Error = crate::error::Error;
     type Output = ();
     fn execute(
         &self,
@@ -45,10 +46,11 @@ impl ExecutableCommand for CommandCompletion {
                         error!(" - {}", s);
                     }
 
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        format!("Unsupported shell: {}", shell_name),
-                    ));
+                    return Err(std::io::Error::other(format!(
+                        "Unsupported shell: {}",
+                        shell_name
+                    ))
+                    .into());
                 }
             };
 
@@ -59,3 +61,259 @@ impl ExecutableCommand for CommandCompletion {
         Ok(())
     }
 }
+
+/// kind of value a positional argument expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// class identifier, e.g. `@work`
+    Class,
+    /// project identifier, e.g. `@projA`
+    Project,
+    /// identifier of one of today's activities
+    Activity,
+    /// date of a tracked day (YYYY-MM-DD)
+    Date,
+}
+
+/// positional argument of a subcommand completed dynamically
+#[derive(Debug, Clone, Copy)]
+pub struct PositionalCompletion {
+    /// canonical names of the (sub)commands, e.g. `["class", "remove"]`
+    pub path: &'static [&'static str],
+    /// position among the positional arguments of the subcommand
+    pub index: usize,
+    pub kind: CompletionKind,
+}
+
+/// Print completion candidates for a partial command line, used by shell completion scripts
+#[derive(Parser)]
+pub struct CommandComplete {
+    /// Words of the command line after the program name, the last one is completed
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    words: Vec<String>,
+}
+
+/// completion kind of the last word of `words` and the partial value typed so far
+pub fn completion_kind(words: &[String]) -> Option<(CompletionKind, &str)> {
+    let (partial, words) = words.split_last()?;
+
+    let mut command = AppArgs::command();
+    command.build();
+    let mut command = &command;
+    let mut path = Vec::new();
+    let mut positionals = 0;
+    let mut skip_value = false;
+    for word in words {
+        if std::mem::take(&mut skip_value) {
+            continue;
+        }
+
+        if let Some(long) = word.strip_prefix("--") {
+            skip_value = !long.contains('=')
+                && command
+                    .get_arguments()
+                    .find(|arg| arg.get_long() == Some(long))
+                    .is_some_and(|arg| arg.get_action().takes_values());
+        } else if let Some(short) = word.strip_prefix('-')
+            && short.chars().count() == 1
+        {
+            skip_value = command
+                .get_arguments()
+                .find(|arg| arg.get_short().map(String::from).as_deref() == Some(short))
+                .is_some_and(|arg| arg.get_action().takes_values());
+        } else if word.starts_with('-') {
+            // short flag with an attached value, e.g. `-c@work`
+        } else if positionals == 0
+            && let Some(subcommand) = command.find_subcommand(word)
+        {
+            path.push(subcommand.get_name());
+            command = subcommand;
+        } else {
+            positionals += 1;
+        }
+    }
+    if skip_value || partial.starts_with('-') {
+        return None;
+    }
+
+    POSITIONAL_COMPLETIONS
+        .iter()
+        .find(|completion| completion.path == path.as_slice() && completion.index == positionals)
+        .map(|completion| (completion.kind, partial.as_str()))
+}
+
+/// candidates for the last word of `words`, empty if it is not completed dynamically
+pub fn complete(job_config: &JobConfig, manager: &Manager, words: &[String]) -> Vec<String> {
+    let Some((kind, partial)) = completion_kind(words) else {
+        return Vec::new();
+    };
+
+    let candidates = match kind {
+        CompletionKind::Class => job_config
+            .classes
+            .iter()
+            .map(|class| format!("@{}", class.inner.name))
+            .collect_vec(),
+        CompletionKind::Project => job_config
+            .projects
+            .iter()
+            .map(|project| format!("@{}", project.inner.name))
+            .collect_vec(),
+        CompletionKind::Activity => manager
+            .day(job_config.today())
+            .into_iter()
+            .flat_map(|day| &day.activities)
+            .flat_map(|activity| {
+                let name = activity.name.as_ref().map(|name| format!("@{name}"));
+                name.into_iter().chain([activity.id.to_string()])
+            })
+            .unique()
+            .collect_vec(),
+        CompletionKind::Date => [job_config.today()]
+            .into_iter()
+            .chain(manager.dates().rev())
+            .unique()
+            .map(|date| date.to_string())
+            .collect_vec(),
+    };
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(partial))
+        .collect()
+}
+
+impl ExecutableCommand for CommandComplete {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        for candidate in complete(job_config, &manager, &self.words) {
+            println!("{}", candidate);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{CommandProject, CommandPush};
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Date, Month, Time};
+
+    fn words(line: &str) -> Vec<String> {
+        let mut words = line.split(' ').map(String::from).collect_vec();
+        if line.ends_with(' ') {
+            words.pop();
+            words.push(String::new());
+        }
+        words
+    }
+
+    fn find_command<'a>(command: &'a clap::Command, path: &[&str]) -> &'a clap::Command {
+        path.iter().fold(command, |command, name| {
+            command
+                .find_subcommand(name)
+                .unwrap_or_else(|| panic!("unknown subcommand {name}"))
+        })
+    }
+
+    #[test]
+    fn test_registry_matches_definitions() {
+        let command = AppArgs::command();
+        for completion in POSITIONAL_COMPLETIONS {
+            let subcommand = find_command(&command, completion.path);
+            assert!(
+                subcommand.get_positionals().nth(completion.index).is_some(),
+                "{:?} has no positional {}",
+                completion.path,
+                completion.index
+            );
+        }
+
+        // identifier and date positionals must not be forgotten
+        fn check(command: &clap::Command, path: &mut Vec<String>) {
+            for (index, positional) in command.get_positionals().enumerate() {
+                if ["class", "project", "activity", "date"].contains(&positional.get_id().as_str())
+                {
+                    assert!(
+                        POSITIONAL_COMPLETIONS
+                            .iter()
+                            .any(|c| c.path.iter().eq(path.iter()) && c.index == index),
+                        "{:?} positional {} is not completed",
+                        path,
+                        positional.get_id()
+                    );
+                }
+            }
+            for subcommand in command.get_subcommands() {
+                path.push(subcommand.get_name().to_string());
+                check(subcommand, path);
+                path.pop();
+            }
+        }
+        check(&command, &mut Vec::new());
+    }
+
+    #[test]
+    fn test_completion_kind() {
+        assert_eq!(
+            completion_kind(&words("class remove @w")),
+            Some((CompletionKind::Class, "@w"))
+        );
+        assert_eq!(
+            completion_kind(&words("cls rm ")),
+            Some((CompletionKind::Class, ""))
+        );
+        assert_eq!(
+            completion_kind(&words("--now 2024-05-03T09:00:00Z project edit ")),
+            Some((CompletionKind::Project, ""))
+        );
+        assert_eq!(
+            completion_kind(&words("-d /tmp/data day lock 2024")),
+            Some((CompletionKind::Date, "2024"))
+        );
+        // options, their values and further positionals are not completed
+        assert_eq!(completion_kind(&words("project edit @a --client ")), None);
+        assert_eq!(completion_kind(&words("project edit @a ")), None);
+        assert_eq!(completion_kind(&words("class remove --")), None);
+        assert_eq!(completion_kind(&words("status ")), None);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandProject::parse_from(["project", "add", "projA"]))
+            .unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding",
+        ]))
+        .unwrap();
+        let job_config = dir.job_config().unwrap();
+        let manager = dir.manager().unwrap();
+        let candidates = |line: &str| complete(&job_config, &manager, &words(line));
+
+        assert_eq!(
+            candidates("class remove @"),
+            ["@work", "@break", "@holiday"]
+        );
+        assert_eq!(candidates("class remove @b"), ["@break"]);
+        assert_eq!(candidates("project remove "), ["@projA"]);
+        assert_eq!(candidates("day unlock "), ["2024-05-03"]);
+
+        let activity = &manager.day(date).unwrap().activities[0];
+        assert_eq!(
+            candidates("describe "),
+            ["@coding".to_string(), activity.id.to_string()]
+        );
+        assert!(candidates("push -c ").is_empty());
+    }
+}
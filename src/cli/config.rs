@@ -0,0 +1,446 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::format::format_table;
+use crate::data::app_config::{
+    APP_CONFIG_KEYS, AppConfig, ConfigSource, DURATION_KEYS, WEEKDAY_DURATION_KEYS,
+    update_config_file,
+};
+use crate::data::job_config::JobConfig;
+use crate::data::manager::Manager;
+use crate::serde::pretty_duration::{self, parse_duration};
+use crate::serde::pretty_weekday_durations::{parse_weekday_name, weekday_name};
+use clap::Parser;
+use log::{error, info, warn};
+use serde_json::{Map, Value};
+use std::fmt::Write;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use time::Weekday;
+
+#[derive(Parser)]
+pub enum CommandConfig {
+    /// Show the app configuration and where each setting comes from
+    #[clap(aliases = ["list", "ls"])]
+    Show,
+    /// Print a setting of the app configuration
+    Get {
+        /// Name of the setting, e.g. work_quota_default
+        key: String,
+    },
+    /// Change a setting in the app config file, creating the file if needed
+    Set {
+        /// Name of the setting, e.g. work_quota_default or work_quota_per_weekday.friday
+        key: String,
+        /// New value, durations may be written like 7h 30m or 7.5h
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+    /// Print the location of the app config file
+    Path,
+    /// Export the job config (classes and projects) to a portable file
+    #[clap(aliases = ["export"])]
+    ExportJob {
+        /// File to write the job config to
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Strip all projects from the exported config
+        #[arg(long)]
+        no_projects: bool,
+    },
+    /// Import a job config from a file
+    #[clap(aliases = ["import"])]
+    ImportJob {
+        /// File to read the job config from
+        file: PathBuf,
+        /// Union classes and projects by UUID instead of replacing the current config
+        #[arg(short, long)]
+        merge: bool,
+        /// Replace the current config even if it was modified
+        #[arg(short, long, conflicts_with = "merge")]
+        force: bool,
+    },
+}
+
+impl CommandConfig {
+    /// the subcommand only uses the app config, so it runs without locking or creating the
+    /// data directory
+    pub fn uses_data_directory(&self) -> bool {
+        !matches!(
+            self,
+            CommandConfig::Show
+                | CommandConfig::Get { .. }
+                | CommandConfig::Set { .. }
+                | CommandConfig::Path
+        )
+    }
+}
+
+/// the setting if the app config has such a setting, with the weekday of keys like
+/// `work_quota_per_weekday.friday`
+fn check_key(key: &str) -> std::io::Result<(&str, Option<Weekday>)> {
+    if APP_CONFIG_KEYS.contains(&key) {
+        return Ok((key, None));
+    }
+    if let Some((setting, weekday)) = key.split_once('.')
+        && WEEKDAY_DURATION_KEYS.contains(&setting)
+        && let Some(weekday) = parse_weekday_name(weekday)
+    {
+        return Ok((setting, Some(weekday)));
+    }
+    error!(
+        "Unknown setting '{}', use one of: {}, or one weekday of {} like {}.friday",
+        key,
+        APP_CONFIG_KEYS.join(", "),
+        WEEKDAY_DURATION_KEYS.join(", "),
+        WEEKDAY_DURATION_KEYS[0]
+    );
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "Unknown setting",
+    ))
+}
+
+/// value of a setting as printed, unset settings as `-`
+fn display_value(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => "-".to_string(),
+        Some(Value::String(value)) => value.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// JSON value to store for a setting given on the command line. strings are taken as is,
+/// other settings are parsed as JSON, e.g. `true`, `5` or `["Mo", ...]`
+fn parse_value(key: &str, value: &str, current: Option<&Value>) -> Result<Value, String> {
+    if DURATION_KEYS.contains(&key) {
+        let duration = parse_duration(value)?;
+        return pretty_duration::serialize(&duration, serde_json::value::Serializer)
+            .map_err(|e| e.to_string());
+    }
+    match current {
+        None if !WEEKDAY_DURATION_KEYS.contains(&key) => Ok(Value::String(value.to_string())),
+        Some(Value::String(_)) => Ok(Value::String(value.to_string())),
+        _ => serde_json::from_str(value)
+            .map_err(|e| format!("Invalid value '{}' for {}: {}", value, key, e)),
+    }
+}
+
+/// value of `setting` with the duration of `weekday` set to `value`, keeping the other weekdays
+fn set_weekday_value(
+    current: Option<&Value>,
+    weekday: Weekday,
+    value: &str,
+) -> Result<Value, String> {
+    let duration =
+        pretty_duration::serialize(&parse_duration(value)?, serde_json::value::Serializer)
+            .map_err(|e| e.to_string())?;
+    let mut weekdays = match current {
+        Some(Value::Object(weekdays)) => weekdays.clone(),
+        _ => Map::new(),
+    };
+    weekdays.insert(weekday_name(weekday), duration);
+    Ok(Value::Object(weekdays))
+}
+
+/// table of all settings with their values and sources
+fn render_settings(config: &AppConfig) -> crate::error::Result<String> {
+    let values = config.values()?;
+    let mut rows = vec![["KEY", "VALUE", "SOURCE"].map(String::from).to_vec()];
+    for key in APP_CONFIG_KEYS {
+        rows.push(vec![
+            key.to_string(),
+            display_value(values.get(key)),
+            config.source(key).to_string(),
+        ]);
+    }
+    let mut out = String::new();
+    for line in format_table(&rows) {
+        let _ = writeln!(out, "{}", line);
+    }
+    Ok(out)
+}
+
+/// the app config file of `config`, if there is a place for one
+fn config_file(config: &AppConfig) -> std::io::Result<&Path> {
+    config.file.as_deref().ok_or_else(|| {
+        error!("There is no app config file, pass one with --config");
+        std::io::Error::new(std::io::ErrorKind::NotFound, "No app config file")
+    })
+}
+
+impl ExecutableCommand for CommandConfig {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        _manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        match self {
+            CommandConfig::Show => print!("{}", render_settings(config)?),
+            CommandConfig::Get { key } => {
+                let (setting, weekday) = check_key(key)?;
+                let values = config.values()?;
+                let value = values.get(setting);
+                let value = match weekday {
+                    Some(weekday) => value.and_then(|value| value.get(weekday_name(weekday))),
+                    None => value,
+                };
+                println!("{}", display_value(value));
+            }
+            CommandConfig::Set { key, value } => {
+                let (setting, weekday) = check_key(key)?;
+                let path = config_file(config)?;
+                let current = config.values()?;
+                let parsed = match weekday {
+                    Some(weekday) => set_weekday_value(current.get(setting), weekday, value),
+                    None => parse_value(setting, value, current.get(setting)),
+                };
+                let parsed = parsed.map_err(|e| {
+                    error!("{}", e);
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+                })?;
+                update_config_file(path, setting, parsed.clone())?;
+                println!(
+                    "Set {} to {} in {}",
+                    key,
+                    display_value(Some(&parsed)),
+                    path.display()
+                );
+                match config.source(setting) {
+                    ConfigSource::Environment => {
+                        warn!("An environment variable overrides the setting {}", setting)
+                    }
+                    ConfigSource::Flag => warn!("A flag overrides the setting {}", setting),
+                    ConfigSource::Default | ConfigSource::File => {}
+                }
+            }
+            CommandConfig::Path => println!("{}", config_file(config)?.display()),
+            CommandConfig::ExportJob {
+                output,
+                no_projects,
+            } => {
+                let portable = job_config.portable(!*no_projects);
+
+                let file = File::create(output).inspect_err(|e| {
+                    error!("Failed to open {} for writing: {}", output.display(), e);
+                })?;
+                serde_json::to_writer_pretty(file, &portable).map_err(|e| {
+                    error!("Failed to write job config to {}: {}", output.display(), e);
+                    std::io::Error::other(e)
+                })?;
+
+                println!(
+                    "Exported {} classes and {} projects to {}",
+                    portable.classes.len(),
+                    portable.projects.len(),
+                    output.display()
+                );
+            }
+            CommandConfig::ImportJob { file, merge, force } => {
+                let reader = File::open(file).inspect_err(|e| {
+                    error!("Failed to open {}: {}", file.display(), e);
+                })?;
+                let imported: JobConfig = serde_json::from_reader(reader).map_err(|e| {
+                    error!("Failed to parse job config at {}: {}", file.display(), e);
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+                })?;
+
+                if let Err(e) = imported.validate_target_shares() {
+                    error!("Invalid job config at {}: {}", file.display(), e);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e).into());
+                }
+
+                if *merge {
+                    let mut merged = job_config.clone();
+                    let decisions = merged.merge(imported);
+                    if let Err(e) = merged.validate_target_shares() {
+                        error!("The merged job config would be invalid: {}", e);
+                        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e).into());
+                    }
+                    *job_config = merged;
+                    for decision in &decisions {
+                        println!(" - {decision}");
+                    }
+                    println!("Merged {} entries from {}", decisions.len(), file.display());
+                } else {
+                    if !job_config.is_default() && !*force {
+                        error!(
+                            "The current job config was modified. Use --merge to combine both configs or --force to replace it."
+                        );
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::AlreadyExists,
+                            "Refusing to replace modified job config",
+                        )
+                        .into());
+                    }
+
+                    info!("Replacing job config with {}", file.display());
+                    *job_config = imported;
+                    println!(
+                        "Imported {} classes and {} projects from {}",
+                        job_config.classes.len(),
+                        job_config.projects.len(),
+                        file.display()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::AppArgs;
+    use crate::data::app_config::AppConfigDisk;
+    use crate::testing::TempDataDir;
+    use time::Duration;
+
+    /// app config as loaded at startup from the config file inside `dir`
+    fn load(dir: &TempDataDir) -> AppConfig {
+        let args = AppArgs::parse_from(["timetrax"]);
+        let file = dir.path().join("config/timetrax/config.json");
+        let disk = AppConfigDisk::load(&file).unwrap();
+        let config = AppConfig::resolve(&args, disk, &|_| None).unwrap();
+        AppConfig {
+            file: Some(file),
+            ..config
+        }
+    }
+
+    fn config(dir: &TempDataDir, args: &[&str]) -> crate::error::Result<()> {
+        let command = CommandConfig::parse_from(["config"].iter().chain(args));
+        command.execute(&load(dir), &mut JobConfig::default(), dir.manager()?)
+    }
+
+    /// value and source of the setting in the output of `config show`
+    fn shown(config: &AppConfig, key: &str) -> (String, String) {
+        let shown = render_settings(config).unwrap();
+        let line = shown
+            .lines()
+            .find(|line| line.split_whitespace().next() == Some(key))
+            .unwrap();
+        let mut columns = line.split_whitespace().skip(1).collect::<Vec<_>>();
+        let source = columns.pop().unwrap().to_string();
+        (columns.join(" "), source)
+    }
+
+    #[test]
+    fn test_set_then_show() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let defaults = load(&dir);
+        assert!(render_settings(&defaults).unwrap().starts_with("KEY"));
+        let default = |value: &str| (value.to_string(), "default".to_string());
+        assert_eq!(
+            shown(&defaults, "work_quota_default"),
+            default("08h 00m 00s")
+        );
+        assert_eq!(shown(&defaults, "break_reminder_after"), default("-"));
+
+        // the file and its folder are created
+        config(&dir, &["set", "work_quota_default", "7.5h"]).unwrap();
+        config(&dir, &["set", "backup_keep", "3"]).unwrap();
+        config(&dir, &["set", "strict_parsing", "true"]).unwrap();
+        config(&dir, &["set", "job_config_file_name", "123"]).unwrap();
+        config(&dir, &["set", "break_reminder_after", "90m"]).unwrap();
+
+        let loaded = load(&dir);
+        assert_eq!(loaded.work_quota_default, Duration::minutes(450));
+        assert_eq!(loaded.backup_keep, 3);
+        assert!(loaded.strict_parsing);
+        assert_eq!(loaded.job_config_file_name, "123");
+        assert_eq!(loaded.break_reminder_after, Some(Duration::minutes(90)));
+        let file = |value: &str| (value.to_string(), "file".to_string());
+        assert_eq!(shown(&loaded, "work_quota_default"), file("07h 30m 00s"));
+        assert_eq!(shown(&loaded, "backup_keep"), file("3"));
+        assert_eq!(shown(&loaded, "break_reminder_after"), file("01h 30m 00s"));
+        assert_eq!(
+            shown(&loaded, "min_gap"),
+            ("00h 01m 00s".to_string(), "default".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_single_weekdays() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        config(&dir, &["set", "work_quota_per_weekday.friday", "6h"]).unwrap();
+        config(&dir, &["set", "work_quota_per_weekday.Saturday", "2h 30m"]).unwrap();
+        config(&dir, &["set", "work_quota_per_weekday.friday", "5h"]).unwrap();
+
+        let loaded = load(&dir);
+        let mut expected = [None; 7];
+        expected[4] = Some(Duration::hours(5));
+        expected[5] = Some(Duration::minutes(150));
+        assert_eq!(loaded.work_quota_per_weekday, expected);
+        assert_eq!(
+            shown(&loaded, "work_quota_per_weekday"),
+            (
+                r#"{"friday":"05h 00m 00s","saturday":"02h 30m 00s"}"#.to_string(),
+                "file".to_string()
+            )
+        );
+
+        // the whole map at once replaces the other weekdays
+        config(
+            &dir,
+            &["set", "work_quota_per_weekday", r#"{"monday": "4h"}"#],
+        )
+        .unwrap();
+        let mut expected = [None; 7];
+        expected[0] = Some(Duration::hours(4));
+        assert_eq!(load(&dir).work_quota_per_weekday, expected);
+
+        for key in [
+            "work_quota_per_weekday.caturday",
+            "work_quota_default.friday",
+        ] {
+            assert!(config(&dir, &["set", key, "1h"]).is_err(), "{key}");
+        }
+        assert!(config(&dir, &["set", "work_quota_per_weekday.friday", "-1h"]).is_err());
+    }
+
+    #[test]
+    fn test_set_keeps_unknown_fields() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let file = load(&dir).file.unwrap();
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(
+            &file,
+            r#"{"from_a_newer_version": [1, 2], "min_gap": "5m"}"#,
+        )
+        .unwrap();
+
+        config(&dir, &["set", "min_gap", "2m"]).unwrap();
+        let stored: Value = serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(stored["from_a_newer_version"], serde_json::json!([1, 2]));
+        assert_eq!(stored["min_gap"], "00h 02m 00s");
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys_and_invalid_values() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let file = load(&dir).file.unwrap();
+        for args in [
+            &["set", "work_quota", "8h"][..],
+            &["get", "colour"],
+            &["set", "work_quota_default", "eight hours"],
+            &["set", "work_quota_default", "-1h"],
+            &["set", "backup_keep", "many"],
+            &["set", "week_start", "Caturday"],
+        ] {
+            let error = config(&dir, args).unwrap_err();
+            assert!(
+                matches!(
+                    error.kind(),
+                    std::io::ErrorKind::InvalidInput | std::io::ErrorKind::InvalidData
+                ),
+                "{args:?}: {error}"
+            );
+        }
+        // nothing was written
+        assert!(!file.exists());
+    }
+}
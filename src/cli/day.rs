@@ -0,0 +1,216 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{parse_date_arg, parse_duration_arg};
+use crate::cli::format::format_duration_pretty;
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::Manager;
+use crate::data::report::{day_quota, is_working_day};
+use clap::Parser;
+use log::error;
+use time::{Date, Duration};
+
+#[derive(Parser)]
+pub enum CommandDay {
+    /// Lock a day against modifications
+    Lock {
+        /// Day to lock (YYYY-MM-DD), defaults to today
+        #[arg(value_parser = parse_date_arg)]
+        date: Option<Date>,
+    },
+    /// Allow modifications of a locked day again
+    Unlock {
+        /// Day to unlock (YYYY-MM-DD), defaults to today
+        #[arg(value_parser = parse_date_arg)]
+        date: Option<Date>,
+    },
+    /// Override the work quota of a day, e.g. `day quota --date 2024-06-07 6h`
+    Quota {
+        /// Work quota of the day, e.g. 6h or 7h30m
+        #[arg(
+            value_parser = parse_duration_arg,
+            required_unless_present_any = ["show", "default"]
+        )]
+        quota: Option<Duration>,
+        /// Day to change (YYYY-MM-DD), defaults to today
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<Date>,
+        /// Print the effective work quota of the day
+        #[arg(long, conflicts_with_all = ["quota", "default"])]
+        show: bool,
+        /// Use the default work quota of the app config again
+        #[arg(long, conflicts_with = "quota")]
+        default: bool,
+    },
+}
+
+/// refuse to modify locked days
+pub fn ensure_unlocked(date: Date, day: &DayInner) -> std::io::Result<()> {
+    if day.locked {
+        error!("The day {} is locked. Unlock it first to modify it.", date);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "Day is locked",
+        ));
+    }
+    Ok(())
+}
+
+/// the effective work quota of the day and where it comes from
+fn describe_quota(
+    config: &AppConfig,
+    job_config: &JobConfig,
+    date: Date,
+    day: Option<&DayInner>,
+) -> String {
+    let defaults = config.daily_quotas();
+    let quota = format_duration_pretty(day_quota(job_config, date, day, defaults), false);
+    let weekday = date.weekday().number_days_from_monday() as usize;
+    match (
+        day.and_then(|day| day.work_quota),
+        job_config.public_holiday(date),
+    ) {
+        (Some(_), _) => format!(
+            "Work quota of {}: {} (overridden, the default is {})",
+            date,
+            quota,
+            format_duration_pretty(day_quota(job_config, date, None, defaults), false)
+        ),
+        (None, Some(holiday)) => format!(
+            "Work quota of {}: {} (public holiday: {})",
+            date, quota, holiday.name
+        ),
+        (None, None) if config.work_quota_per_weekday[weekday].is_some() => format!(
+            "Work quota of {}: {} (default of {})",
+            date,
+            quota,
+            date.weekday()
+        ),
+        (None, None) if is_working_day(date) => {
+            format!("Work quota of {}: {} (default)", date, quota)
+        }
+        (None, None) => format!("Work quota of {}: {} (weekend)", date, quota),
+    }
+}
+
+impl CommandDay {
+    fn quota(
+        config: &AppConfig,
+        job_config: &JobConfig,
+        date: Date,
+        quota: Option<Duration>,
+        show: bool,
+        manager: &mut Manager,
+    ) -> crate::error::Result<()> {
+        if show {
+            let day = manager.day(date);
+            println!("{}", describe_quota(config, job_config, date, day));
+            return Ok(());
+        }
+
+        ensure_unlocked(date, manager.get_or_create_day_ref(date))?;
+        if quota.is_some_and(|quota| quota.is_negative()) {
+            error!("The work quota must not be negative");
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Negative work quota",
+            )
+            .into());
+        }
+        manager.get_or_create_day_mut(date).work_quota = quota;
+        manager.save()?;
+        let day = manager.day(date);
+        println!("{}", describe_quota(config, job_config, date, day));
+        Ok(())
+    }
+}
+
+impl ExecutableCommand for CommandDay {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let (date, locked) = match self {
+            CommandDay::Lock { date } => (date, true),
+            CommandDay::Unlock { date } => (date, false),
+            CommandDay::Quota {
+                quota,
+                date,
+                show,
+                default: _,
+            } => {
+                let date = date.unwrap_or_else(|| job_config.today());
+                return Self::quota(config, job_config, date, *quota, *show, &mut manager);
+            }
+        };
+        let date = date.unwrap_or_else(|| job_config.today());
+
+        if manager.get_or_create_day_ref(date).locked == locked {
+            println!(
+                "Day {} is already {}.",
+                date,
+                if locked { "locked" } else { "unlocked" }
+            );
+            return Ok(());
+        }
+
+        manager.get_or_create_day_mut(date).locked = locked;
+        manager.save()?;
+        println!(
+            "{} day {}.",
+            if locked { "Locked" } else { "Unlocked" },
+            date
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDataDir;
+    use time::Month;
+
+    fn day(dir: &TempDataDir, args: &[&str]) -> crate::error::Result<()> {
+        dir.execute(&CommandDay::parse_from(["day"].iter().chain(args)))
+    }
+
+    #[test]
+    fn test_day_quota_override() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let friday = Date::from_calendar_date(2024, Month::June, 7).unwrap();
+        let config = dir.app_config();
+        assert_eq!(
+            describe_quota(config, &JobConfig::default(), friday, None),
+            "Work quota of 2024-06-07: 8h 0m (default)"
+        );
+
+        day(&dir, &["quota", "--date", "2024-06-07", "6h"]).unwrap();
+        let stored = dir.day(friday).unwrap().unwrap();
+        assert_eq!(stored.work_quota, Some(Duration::hours(6)));
+        assert_eq!(
+            describe_quota(config, &JobConfig::default(), friday, Some(&stored)),
+            "Work quota of 2024-06-07: 6h 0m (overridden, the default is 8h 0m)"
+        );
+        day(&dir, &["quota", "--date", "2024-06-07", "--show"]).unwrap();
+
+        day(&dir, &["quota", "--date", "2024-06-07", "--default"]).unwrap();
+        assert_eq!(dir.day(friday).unwrap().unwrap().work_quota, None);
+
+        day(&dir, &["lock", "2024-06-07"]).unwrap();
+        let result = day(&dir, &["quota", "--date", "2024-06-07", "6h"]);
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+
+        assert!(CommandDay::try_parse_from(["day", "quota"]).is_err());
+        assert!(CommandDay::try_parse_from(["day", "quota", "6h", "--default"]).is_err());
+        assert!(CommandDay::try_parse_from(["day", "quota", "6h", "--show"]).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{parse_date_arg, parse_identifier_arg};
+use crate::cli::day::ensure_unlocked;
+use crate::cli::editor::{Editor, default_editor, normalize_text};
+use crate::data::app_config::AppConfig;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use clap::Parser;
+use log::error;
+use time::Date;
+
+#[derive(Parser)]
+pub struct CommandDescribe {
+    /// Activity UUID or @name, names must be unique on the day
+    #[arg(value_parser = parse_identifier_arg)]
+    activity: Identifier,
+    /// Day of the activity (YYYY-MM-DD), searches all days if omitted
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+}
+
+impl CommandDescribe {
+    /// edit the description using the given editor
+    pub fn describe_with(
+        &self,
+        editor: &dyn Editor,
+        mut manager: Manager,
+    ) -> crate::error::Result<()> {
+        let date = manager
+            .locate_activity(&self.activity, self.date)
+            .inspect_err(|e| error!("{}", e))?;
+
+        let day = manager.get_or_create_day_ref(date);
+        ensure_unlocked(date, day)?;
+
+        let activity = day.find_activity(&self.activity)?;
+        let id = activity.id;
+        let current = activity.description.clone().unwrap_or_default();
+        let description = normalize_text(&editor.edit(&current)?);
+
+        if description.as_deref().unwrap_or_default() == current {
+            println!("Description unchanged.");
+            return Ok(());
+        }
+
+        if let Ok(activity) = manager
+            .get_or_create_day_mut(date)
+            .find_activity_mut(&Identifier::Uuid(id))
+        {
+            activity.description = description;
+            activity.modified_at = Some(local_time::now());
+            let activity = activity.clone();
+            manager.save()?;
+            match &activity.description {
+                Some(_) => println!("Updated description of {}", activity),
+                None => println!("Cleared description of {}", activity),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ExecutableCommand for CommandDescribe {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        _job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        self.describe_with(default_editor().as_ref(), manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Duration, Month, Time};
+    use uuid::Uuid;
+
+    fn setup() -> (ManualClock, TempDataDir, Date, Uuid) {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        {
+            let _guard = clock.install();
+            dir.execute(&CommandPush::parse_from([
+                "push", "-c", "@work", "-d", "old",
+            ]))
+            .unwrap();
+        }
+        let id = dir.day(date).unwrap().unwrap().activities[0].id;
+        (clock, dir, date, id)
+    }
+
+    #[test]
+    fn test_describe_stores_normalized_text() {
+        let (clock, dir, date, id) = setup();
+        let _guard = clock.install();
+        clock.advance(Duration::hours(1));
+
+        let command = CommandDescribe::parse_from(["describe", &id.to_string()]);
+        let editor = |initial: &str| {
+            assert_eq!(initial, "old");
+            Ok("new\nsecond line\n\n".to_string())
+        };
+        command
+            .describe_with(&editor, dir.manager().unwrap())
+            .unwrap();
+
+        let activity = dir.day(date).unwrap().unwrap().activities[0].clone();
+        assert_eq!(activity.description.as_deref(), Some("new\nsecond line"));
+        assert_eq!(
+            activity.modified_at,
+            Some(at(date, Time::from_hms(10, 0, 0).unwrap()))
+        );
+
+        let editor = |_: &str| Ok("\n".to_string());
+        command
+            .describe_with(&editor, dir.manager().unwrap())
+            .unwrap();
+        let activity = dir.day(date).unwrap().unwrap().activities[0].clone();
+        assert_eq!(activity.description, None);
+    }
+
+    #[test]
+    fn test_describe_refuses_locked_day() {
+        let (_clock, dir, date, id) = setup();
+        dir.manager().unwrap().get_or_create_day_mut(date).locked = true;
+
+        let command = CommandDescribe::parse_from(["describe", &id.to_string()]);
+        let editor = |_: &str| -> std::io::Result<String> { panic!("editor must not open") };
+        let result = command.describe_with(&editor, dir.manager().unwrap());
+
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+        let activity = dir.day(date).unwrap().unwrap().activities[0].clone();
+        assert_eq!(activity.description.as_deref(), Some("old"));
+    }
+
+    #[test]
+    fn test_describe_unknown_activity() {
+        let (_clock, dir, date, _) = setup();
+        let command = CommandDescribe::parse_from([
+            "describe",
+            &Uuid::nil().to_string(),
+            "--date",
+            &date.to_string(),
+        ]);
+        let editor = |_: &str| Ok(String::new());
+
+        let result = command.describe_with(&editor, dir.manager().unwrap());
+
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_describe_ambiguous_name() {
+        let (clock, dir, _, _) = setup();
+        let _guard = clock.install();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "meeting",
+        ]))
+        .unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "meeting",
+        ]))
+        .unwrap();
+
+        let command = CommandDescribe::parse_from(["describe", "@meeting"]);
+        let editor = |_: &str| -> std::io::Result<String> { panic!("editor must not open") };
+        let result = command.describe_with(&editor, dir.manager().unwrap());
+
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,395 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_date_arg;
+use crate::cli::format::format_duration_pretty;
+use crate::cli::show::class_name;
+use crate::data::activity::Activity;
+use crate::data::activity_diff::ActivitiesDiff;
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::report::day_closure;
+use crate::data::team::ForeignData;
+use clap::Parser;
+use log::error;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+use time::{Date, Duration, OffsetDateTime};
+
+#[derive(Parser)]
+pub struct CommandDiffData {
+    /// Older data directory to compare the current one with, e.g. a backup or an unpacked archive
+    old: PathBuf,
+    /// First day to compare (YYYY-MM-DD)
+    #[arg(short, long, value_parser = parse_date_arg)]
+    from: Option<Date>,
+    /// Last day to compare (YYYY-MM-DD)
+    #[arg(short, long, value_parser = parse_date_arg)]
+    to: Option<Date>,
+    /// Print a JSON object instead of text
+    #[arg(long)]
+    json: bool,
+}
+
+/// total of a class in the old and the current version of a day
+struct ClassDelta {
+    class: String,
+    old: Duration,
+    new: Duration,
+}
+
+/// differences of a day between the old and the current data
+struct DayDelta<'a> {
+    date: Date,
+    old: Option<&'a DayInner>,
+    new: Option<&'a DayInner>,
+    activities: ActivitiesDiff,
+    totals: Vec<ClassDelta>,
+}
+
+impl DayDelta<'_> {
+    fn only_in(&self) -> Option<&'static str> {
+        match (self.old, self.new) {
+            (Some(_), None) => Some("old"),
+            (None, Some(_)) => Some("current"),
+            _ => None,
+        }
+    }
+}
+
+/// time per class name of the day, classes of both versions are matched by name
+fn class_totals(
+    job_config: &JobConfig,
+    date: Date,
+    day: Option<&DayInner>,
+    now: OffsetDateTime,
+) -> BTreeMap<String, Duration> {
+    let Some(day) = day else {
+        return BTreeMap::new();
+    };
+    let (closure, _) = day_closure(job_config, date, day, now);
+    let mut totals = BTreeMap::new();
+    for (class, duration) in Activity::summarize_by_class(&closure) {
+        *totals
+            .entry(class_name(job_config, &class))
+            .or_insert(Duration::ZERO) += duration;
+    }
+    totals
+}
+
+fn format_delta(old: Duration, new: Duration) -> String {
+    let delta = new.saturating_sub(old);
+    let sign = if delta.is_positive() { "+" } else { "" };
+    format!("{}{}", sign, format_duration_pretty(delta, false))
+}
+
+impl CommandDiffData {
+    fn includes(&self, date: Date) -> bool {
+        self.from.is_none_or(|from| from <= date) && self.to.is_none_or(|to| date <= to)
+    }
+
+    /// days differing between `old` and the current data, in date order
+    fn compare<'a>(
+        &self,
+        old: &'a ForeignData,
+        job_config: &JobConfig,
+        manager: &'a Manager,
+        now: OffsetDateTime,
+    ) -> Vec<DayDelta<'a>> {
+        let dates = old
+            .manager()
+            .dates()
+            .chain(manager.dates())
+            .filter(|date| self.includes(*date))
+            .collect::<BTreeSet<_>>();
+
+        dates
+            .into_iter()
+            .filter_map(|date| {
+                let old_day = old.manager().day(date);
+                let new_day = manager.day(date);
+                let activities = ActivitiesDiff::between(
+                    old_day.map_or(&[], |day| &day.activities),
+                    new_day.map_or(&[], |day| &day.activities),
+                );
+
+                let old_totals = class_totals(&old.job_config, date, old_day, now);
+                let mut new_totals = class_totals(job_config, date, new_day, now);
+                let mut totals = old_totals
+                    .into_iter()
+                    .map(|(class, old)| {
+                        let new = new_totals.remove(&class).unwrap_or_default();
+                        ClassDelta { class, old, new }
+                    })
+                    .collect::<Vec<_>>();
+                totals.extend(new_totals.into_iter().map(|(class, new)| ClassDelta {
+                    class,
+                    old: Duration::ZERO,
+                    new,
+                }));
+                totals.retain(|delta| delta.old != delta.new);
+                totals.sort_by(|a, b| a.class.cmp(&b.class));
+
+                let changed = !activities.is_empty() || !totals.is_empty();
+                changed.then_some(DayDelta {
+                    date,
+                    old: old_day,
+                    new: new_day,
+                    activities,
+                    totals,
+                })
+            })
+            .collect()
+    }
+
+    fn render_text(&self, days: &[DayDelta]) -> String {
+        let mut out = String::new();
+        if days.is_empty() {
+            out.push_str("No differences.\n");
+            return out;
+        }
+        for day in days {
+            match day.only_in() {
+                Some(side) => writeln!(out, "{} (only in {}):", day.date, side),
+                None => writeln!(out, "{}:", day.date),
+            }
+            .unwrap();
+            for activity in &day.activities.added {
+                writeln!(out, "  + {} [{}]", activity, activity.short_id()).unwrap();
+            }
+            for activity in &day.activities.removed {
+                writeln!(out, "  - {} [{}]", activity, activity.short_id()).unwrap();
+            }
+            for diff in &day.activities.modified {
+                let current = day
+                    .new
+                    .and_then(|new| new.activities.iter().find(|a| a.id == diff.id));
+                match current {
+                    Some(activity) => writeln!(out, "  ~ {} [{}]", activity, activity.short_id()),
+                    None => writeln!(out, "  ~ {}", diff.id),
+                }
+                .unwrap();
+                for change in &diff.changes {
+                    let value = |value: &Option<serde_json::Value>| {
+                        value
+                            .as_ref()
+                            .map_or("(none)".to_string(), |value| value.to_string())
+                    };
+                    writeln!(
+                        out,
+                        "      {}: {} -> {}",
+                        change.field,
+                        value(&change.old),
+                        value(&change.new)
+                    )
+                    .unwrap();
+                }
+            }
+            for delta in &day.totals {
+                writeln!(
+                    out,
+                    "  {}: {} -> {} ({})",
+                    delta.class,
+                    format_duration_pretty(delta.old, false),
+                    format_duration_pretty(delta.new, false),
+                    format_delta(delta.old, delta.new)
+                )
+                .unwrap();
+            }
+        }
+        out
+    }
+
+    fn render_json(&self, days: &[DayDelta]) -> String {
+        let days = days
+            .iter()
+            .map(|day| {
+                json!({
+                    "date": day.date.to_string(),
+                    "only_in": day.only_in(),
+                    "added": day.activities.added,
+                    "removed": day.activities.removed,
+                    "modified": day.activities.modified,
+                    "class_totals": day.totals.iter().map(|delta| json!({
+                        "class": delta.class,
+                        "old_seconds": delta.old.whole_seconds(),
+                        "new_seconds": delta.new.whole_seconds(),
+                        "delta_seconds": delta.new.saturating_sub(delta.old).whole_seconds(),
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "old": self.old.display().to_string(),
+            "days": days,
+        })
+        .to_string()
+    }
+
+    /// the differences from the old directory to the current data, as text or JSON
+    pub fn render(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        manager: &Manager,
+        now: OffsetDateTime,
+    ) -> crate::error::Result<String> {
+        if !self.old.is_dir() {
+            error!(
+                "{} is not a data directory, unpack archives before comparing",
+                self.old.display()
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Old data is not a directory",
+            )
+            .into());
+        }
+        let old = ForeignData::open(config, &self.old)?;
+        let days = self.compare(&old, job_config, manager, now);
+        Ok(if self.json {
+            self.render_json(&days)
+        } else {
+            self.render_text(&days)
+        })
+    }
+}
+
+impl ExecutableCommand for CommandDiffData {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let output = self.render(config, job_config, &manager, local_time::now())?;
+        if self.json {
+            println!("{}", output);
+        } else {
+            print!("{}", output);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TempDataDir, at};
+    use std::path::Path;
+    use time::{Month, Time};
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata/diff_data")
+            .join(name)
+    }
+
+    fn setup() -> TempDataDir {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        for file in ["2024-05-02.json", "2024-05-06.json"] {
+            let contents = std::fs::read_to_string(fixture("new").join(file)).unwrap();
+            dir.write_day_file(file, &contents).unwrap();
+        }
+        dir
+    }
+
+    fn diff(dir: &TempDataDir, args: &[&str]) -> String {
+        let old = fixture("old");
+        let args = ["diff-data", old.to_str().unwrap()]
+            .into_iter()
+            .chain(args.iter().copied());
+        let now = at(
+            Date::from_calendar_date(2024, Month::May, 10).unwrap(),
+            Time::from_hms(18, 0, 0).unwrap(),
+        );
+        let manager = Manager::open_read_only(dir.app_config(), dir.path()).unwrap();
+        CommandDiffData::parse_from(args)
+            .render(dir.app_config(), &dir.job_config().unwrap(), &manager, now)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_diff_data_text() {
+        let dir = setup();
+        let output = diff(&dir, &[]);
+        let expected = "\
+2024-05-02:
+  + 14:00:00 - 15:00:00: review [REVIEW]
+  - 13:00:00 - 14:00:00: meeting [MEETING]
+  ~ 08:30:00 - 12:00:00: coding parser [CODING_PARSER]
+      name: \"coding\" -> \"coding parser\"
+      time: {\"end\":\"12:00:00\",\"start\":\"09:00:00\"} -> {\"end\":\"12:00:00\",\"start\":\"08:30:00\"}
+  work: 4h 0m -> 4h 30m (+30m)
+2024-05-03 (only in old):
+  - 09:00:00 - 10:00:00: standup [STANDUP]
+  work: 1h 0m -> 0m (-1h 0m)
+2024-05-06 (only in current):
+  + 09:00:00 - 11:00:00: planning [PLANNING]
+  work: 0m -> 2h 0m (+2h 0m)
+";
+        // short ids are hashes of the activities, look them up by name
+        let old = ForeignData::open(dir.app_config(), &fixture("old")).unwrap();
+        let manager = Manager::open_read_only(dir.app_config(), dir.path()).unwrap();
+        let expected = old
+            .manager()
+            .days()
+            .chain(manager.days())
+            .flat_map(|(_, day)| day.activities.iter())
+            .fold(expected.to_string(), |expected, activity| {
+                let name = activity.name.as_deref().unwrap().replace(' ', "_");
+                expected.replace(
+                    &format!("[{}]", name.to_uppercase()),
+                    &format!("[{}]", activity.short_id()),
+                )
+            });
+        assert_eq!(output, expected);
+
+        assert!(
+            diff(&dir, &["--from", "2024-05-03", "--to", "2024-05-05"])
+                .starts_with("2024-05-03 (only in old):\n")
+        );
+        assert_eq!(diff(&dir, &["--from", "2024-05-07"]), "No differences.\n");
+    }
+
+    #[test]
+    fn test_diff_data_json() {
+        let dir = setup();
+        let output: serde_json::Value =
+            serde_json::from_str(&diff(&dir, &["--json", "--to", "2024-05-02"])).unwrap();
+        let day = &output["days"][0];
+        assert_eq!(output["days"].as_array().unwrap().len(), 1);
+        assert_eq!(day["date"], "2024-05-02");
+        assert_eq!(day["only_in"], serde_json::Value::Null);
+        assert_eq!(day["added"][0]["name"], "review");
+        assert_eq!(day["removed"][0]["name"], "meeting");
+        assert_eq!(
+            day["modified"][0]["changes"][0],
+            json!({"field": "name", "old": "coding", "new": "coding parser"})
+        );
+        assert_eq!(
+            day["class_totals"],
+            json!([{"class": "work", "old_seconds": 14400, "new_seconds": 16200,
+                    "delta_seconds": 1800}])
+        );
+    }
+
+    #[test]
+    fn test_diff_data_rejects_files() {
+        let dir = setup();
+        let file = fixture("old").join("job.json");
+        let manager = Manager::open_read_only(dir.app_config(), dir.path()).unwrap();
+        let result = CommandDiffData::parse_from(["diff-data", file.to_str().unwrap()]).render(
+            dir.app_config(),
+            &JobConfig::default(),
+            &manager,
+            local_time::now(),
+        );
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
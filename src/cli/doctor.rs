@@ -0,0 +1,493 @@
+use crate::cli::ExecutableCommand;
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::identifier::Identifier;
+use crate::data::integrity::{Problem, ProblemKind, Record};
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::legacy::{fix_legacy_identifiers, legacy_identifiers};
+use crate::data::local_time;
+use crate::data::manager::{AnnotatedDayInformation, Manager};
+use crate::data::text::{NameError, normalize_day_names, normalize_name};
+use clap::Parser;
+use itertools::Itertools;
+use log::{info, warn};
+use std::fmt::{Display, Formatter, Write};
+use time::{Date, Time};
+
+#[derive(Parser, Default)]
+pub struct CommandDoctor {
+    /// Rewrite class and project names stored under the legacy `@` normalization
+    #[arg(long)]
+    fix_identifiers: bool,
+    /// Trim and collapse the whitespace of activity and blocker names, blank names are removed
+    #[arg(long)]
+    fix: bool,
+}
+
+/// a single problem found in the data directory
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Finding {
+    pub date: Date,
+    pub message: String,
+    /// command fixing or inspecting the problem
+    pub fix: String,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.date, self.message)
+    }
+}
+
+/// the problem as a finding, activities ending before they start are fixed by editing their end
+fn problem_finding(date: Date, day: &DayInner, problem: Problem) -> Finding {
+    let activity = day.activities.iter().find(|a| a.id == problem.id);
+    let fix = match (&problem.kind, activity) {
+        (ProblemKind::InvertedInterval { .. }, Some(a)) if problem.record == Record::Activity => {
+            format!("timetrax edit {} --date {date} --end HH:MM", a.short_id())
+        }
+        _ => format!("timetrax show --date {date}"),
+    };
+    Finding {
+        date,
+        message: format!("{} {} {}", problem.record, problem.id, problem.kind),
+        fix,
+    }
+}
+
+/// the [`DayInner::inverted_intervals`] of the day
+fn check_inverted_intervals(date: Date, day: &DayInner, boundary: Option<Time>) -> Vec<Finding> {
+    day.inverted_intervals(boundary)
+        .into_iter()
+        .map(|problem| problem_finding(date, day, problem))
+        .collect()
+}
+
+/// duplicate ids, unknown classes and projects and empty project identifiers, see
+/// [`DayInner::problems`]. identical overlaps are left to `timetrax reconcile`, which
+/// proposes merging them, and legacy identifiers to [`check_legacy_identifiers`]
+fn check_records(job_config: &JobConfig, date: Date, day: &DayInner) -> Vec<Finding> {
+    let legacy = legacy_identifiers(job_config, day);
+    let is_legacy = |identifier: &Identifier| legacy.iter().any(|l| &l.stored == identifier);
+    let unresolved = day
+        .unresolved_identifiers(job_config)
+        .into_iter()
+        .filter(|problem| match &problem.kind {
+            ProblemKind::UnknownClass(identifier) | ProblemKind::UnknownProject(identifier) => {
+                !is_legacy(identifier)
+            }
+            _ => true,
+        });
+    let problems = day.duplicate_ids().into_iter();
+    problems
+        .chain(unresolved)
+        .chain(day.empty_project_identifiers())
+        .map(|problem| problem_finding(date, day, problem))
+        .collect()
+}
+
+/// activities and blockers of past days which were never stopped
+fn check_stale_open(date: Date, day: &DayInner, today: Date) -> Vec<Finding> {
+    if date >= today {
+        return Vec::new();
+    }
+
+    let activities = day
+        .activities
+        .iter()
+        .filter(|a| !a.time.is_complete())
+        .map(|a| Finding {
+            date,
+            message: format!(
+                "activity {} started at {} was never stopped",
+                a.id, a.time.start
+            ),
+            fix: format!("timetrax reconcile --date {date}"),
+        });
+    let blockers = day
+        .blockers
+        .iter()
+        .filter(|b| !b.time.is_complete())
+        .map(|b| Finding {
+            date,
+            message: format!(
+                "blocker {} started at {} was never stopped",
+                b.id, b.time.start
+            ),
+            fix: format!("timetrax blocker stop {} --date {date}", b.id),
+        });
+
+    activities.chain(blockers).collect()
+}
+
+/// day files carrying fields unknown to timetrax, they are preserved on save
+fn check_unknown_fields(date: Date, day: &AnnotatedDayInformation) -> Vec<Finding> {
+    let unknown = day.inner().unknown_fields();
+    if unknown.is_empty() {
+        return Vec::new();
+    }
+
+    let file = day
+        .origin()
+        .map(|origin| origin.display().to_string())
+        .unwrap_or_else(|| "unsaved day".to_string());
+    vec![Finding {
+        date,
+        message: format!(
+            "{} carries unknown fields (kept as is): {}",
+            file,
+            unknown.join(", ")
+        ),
+        fix: format!("timetrax show --date {date}"),
+    }]
+}
+
+/// class and project names which only resolve because every `@` used to be removed from names
+fn check_legacy_identifiers(job_config: &JobConfig, date: Date, day: &DayInner) -> Vec<Finding> {
+    legacy_identifiers(job_config, day)
+        .into_iter()
+        .map(|legacy| Finding {
+            date,
+            message: legacy.to_string(),
+            fix: "timetrax doctor --fix-identifiers".to_string(),
+        })
+        .collect()
+}
+
+/// activity and blocker names which are blank, carry extra whitespace or are too long
+fn check_names(config: &AppConfig, date: Date, day: &DayInner) -> Vec<Finding> {
+    let activities = day.activities.iter().map(|a| {
+        let fix = format!("timetrax edit {} --date {date} --name NAME", a.short_id());
+        ("activity", a.id, &a.name, fix)
+    });
+    let blockers = day.blockers.iter().map(|b| {
+        let fix = format!("timetrax show --date {date}");
+        ("blocker", b.id, &b.name, fix)
+    });
+
+    activities
+        .chain(blockers)
+        .filter_map(|(kind, id, name, fix)| {
+            let name = name.as_deref()?;
+            let (message, fix) = match normalize_name(name, config.max_name_length) {
+                Ok(normalized) if normalized.as_deref() == Some(name) => return None,
+                Err(NameError::TooLong { length, max }) => (
+                    format!(
+                        "{kind} {id} has a name of {length} characters, at most {max} are allowed"
+                    ),
+                    fix,
+                ),
+                _ => (
+                    format!("{kind} {id} has the unnormalized name '{name}'"),
+                    "timetrax doctor --fix".to_string(),
+                ),
+            };
+            Some(Finding { date, message, fix })
+        })
+        .collect()
+}
+
+/// all problems of a single day, `today` decides which open records are stale
+pub(crate) fn check_day(
+    config: &AppConfig,
+    job_config: &JobConfig,
+    date: Date,
+    day: &AnnotatedDayInformation,
+    today: Date,
+) -> Vec<Finding> {
+    let mut findings = check_inverted_intervals(date, day.inner(), job_config.day_boundary);
+    findings.extend(check_records(job_config, date, day.inner()));
+    findings.extend(check_stale_open(date, day.inner(), today));
+    findings.extend(check_unknown_fields(date, day));
+    findings.extend(check_legacy_identifiers(job_config, date, day.inner()));
+    findings.extend(check_names(config, date, day.inner()));
+    findings
+}
+
+/// the findings with their fixes as a Markdown list, followed by how to inspect the affected days
+pub(crate) fn render_findings(findings: &[Finding]) -> String {
+    let mut out = String::new();
+    if findings.is_empty() {
+        let _ = writeln!(out, "No problems found.");
+        return out;
+    }
+
+    let _ = writeln!(out, "Found {} problem(s):", findings.len());
+    for finding in findings {
+        let _ = writeln!(out, " - {finding}");
+        let _ = writeln!(out, "   fix: `{}`", finding.fix);
+    }
+    let dates = findings.iter().map(|finding| finding.date).unique();
+    for date in dates {
+        let _ = writeln!(
+            out,
+            "Inspect the records of {date} using: `timetrax show --date {date}`"
+        );
+    }
+    out
+}
+
+impl CommandDoctor {
+    /// apply `fix` to all unlocked days, returning the number of changes, the changed
+    /// activities are journaled
+    fn fix_days<F: Fn(&mut DayInner) -> usize>(
+        &self,
+        config: &AppConfig,
+        manager: &mut Manager,
+        what: &str,
+        fix: F,
+    ) -> crate::error::Result<()> {
+        let now = local_time::now();
+        let journal = Journal::new(config, manager.data_path());
+        let dates = manager.dates().collect::<Vec<_>>();
+
+        // journaled and reported once the days are saved
+        let mut changes = Vec::new();
+        for date in dates {
+            let day = manager.get_or_create_day_ref(date);
+            let mut fixed_day = day.clone();
+            let fixed = fix(&mut fixed_day);
+            if fixed == 0 {
+                continue;
+            }
+            if day.locked {
+                warn!("Not fixing the {} of the locked day {}", what, date);
+                continue;
+            }
+            let (removed, added): (Vec<_>, Vec<_>) = day
+                .activities
+                .iter()
+                .zip(&fixed_day.activities)
+                .filter(|(before, after)| {
+                    serde_json::to_value(before).ok() != serde_json::to_value(after).ok()
+                })
+                .map(|(before, after)| (before.clone(), after.clone()))
+                .unzip();
+            info!("Fixed {} {} on {}", fixed, what, date);
+            *manager.get_or_create_day_mut(date) = fixed_day;
+            changes.push((date, fixed, removed, added));
+        }
+
+        manager.save()?;
+        for (date, fixed, removed, added) in changes {
+            if !removed.is_empty() {
+                journal.append(&JournalEntry {
+                    at: now,
+                    command: "doctor".to_string(),
+                    date,
+                    removed,
+                    added,
+                })?;
+            }
+            println!("Fixed {} {} on {}.", fixed, what, date);
+        }
+        Ok(())
+    }
+}
+
+impl ExecutableCommand for CommandDoctor {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        if self.fix_identifiers {
+            self.fix_days(config, &mut manager, "legacy identifier(s)", |day| {
+                fix_legacy_identifiers(job_config, day)
+            })?;
+        }
+        if self.fix {
+            self.fix_days(config, &mut manager, "name(s)", |day| {
+                normalize_day_names(day, config.max_name_length)
+            })?;
+        }
+
+        let today = job_config.today();
+        let findings = manager
+            .annotated_days_in(..)
+            .flat_map(|(date, day)| check_day(config, job_config, *date, day, today))
+            .collect::<Vec<_>>();
+
+        print!("{}", render_findings(&findings));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::Activity;
+    use crate::data::blocker::Blocker;
+    use crate::data::identifier::Identifier;
+    use crate::data::interval::Interval;
+    use crate::testing::{self, TempDataDir};
+    use time::Month;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_check_inverted_intervals() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let mut day = DayInner::default();
+        for (start, end) in [(9, Some(10)), (12, Some(11)), (13, None)] {
+            day.activities.push(Activity {
+                id: Uuid::from_u128(start as u128),
+                ..testing::activity(
+                    "work",
+                    Time::from_hms(start, 0, 0).unwrap(),
+                    end.map(|h| Time::from_hms(h, 0, 0).unwrap()),
+                )
+            });
+        }
+
+        let findings = check_inverted_intervals(date, &day, None);
+
+        assert_eq!(findings.len(), 1);
+        assert!(
+            findings[0]
+                .message
+                .contains(&Uuid::from_u128(12).to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_records() {
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let day: crate::data::day::Day = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/broken/2024-05-02.json"
+        )))
+        .unwrap();
+
+        let findings = check_records(&JobConfig::default(), date, &day.inner);
+
+        let messages = findings
+            .iter()
+            .map(|f| f.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "activity 00000000-0000-4000-8000-000000000003 shares its id with an earlier activity",
+                "activity 00000000-0000-4000-8000-000000000004 refers to the unknown class @retired",
+                "activity 00000000-0000-4000-8000-000000000005 refers to the unknown project @gone",
+                "activity 00000000-0000-4000-8000-000000000005 has an empty project identifier",
+            ]
+        );
+        assert!(
+            findings
+                .iter()
+                .all(|f| f.fix == "timetrax show --date 2024-05-02")
+        );
+    }
+
+    #[test]
+    fn test_check_stale_open() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let open = Interval {
+            start: Time::from_hms(9, 0, 0).unwrap(),
+            end: None,
+        };
+        let mut day = DayInner::default();
+        day.activities.push(Activity {
+            id: Uuid::from_u128(1),
+            ..testing::activity("work", open.start, open.end)
+        });
+        day.blockers.push(Blocker {
+            id: Uuid::from_u128(2),
+            name: None,
+            class: Identifier::ByName("work".into()),
+            time: open,
+            projects: vec![],
+        });
+
+        assert!(check_stale_open(date, &day, date).is_empty());
+
+        let findings = check_stale_open(date, &day, date.next_day().unwrap());
+        assert_eq!(findings.len(), 2);
+        assert!(findings[1].message.starts_with("blocker"));
+    }
+
+    #[test]
+    fn test_fix_identifiers() {
+        let job_config: JobConfig = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/legacy_identifiers/job.json"
+        )))
+        .unwrap();
+        let dir = TempDataDir::new(&job_config).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/legacy_identifiers/old_day.json"
+            )),
+        )
+        .unwrap();
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let legacy = |dir: &TempDataDir| {
+            let manager = dir.manager().unwrap();
+            let today = date.next_day().unwrap();
+            check_day(
+                dir.app_config(),
+                &job_config,
+                date,
+                manager.annotated_day(date).unwrap(),
+                today,
+            )
+            .len()
+        };
+        assert_eq!(legacy(&dir), 4);
+
+        dir.execute(&CommandDoctor::parse_from(["doctor"])).unwrap();
+        assert_eq!(legacy(&dir), 4);
+
+        dir.execute(&CommandDoctor::parse_from(["doctor", "--fix-identifiers"]))
+            .unwrap();
+        assert_eq!(legacy(&dir), 0);
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].command, "doctor");
+        assert_eq!(entries[0].added.len(), 1);
+    }
+
+    #[test]
+    fn test_check_and_fix_names() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            r#"{"date": "2024-05-02", "activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+                 "name": " messy  name", "time": {"start": "09:00:00", "end": "10:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000002", "class": "@work",
+                 "name": "  ", "time": {"start": "10:00:00", "end": "11:00:00"}}
+            ]}"#,
+        )
+        .unwrap();
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let findings = |dir: &TempDataDir| {
+            let manager = dir.manager().unwrap();
+            check_names(dir.app_config(), date, manager.day(date).unwrap())
+        };
+
+        let found = findings(&dir);
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0].message,
+            "activity 00000000-0000-4000-8000-000000000001 has the unnormalized name ' messy  name'"
+        );
+        assert_eq!(found[0].fix, "timetrax doctor --fix");
+
+        dir.execute(&CommandDoctor::parse_from(["doctor", "--fix"]))
+            .unwrap();
+        assert!(findings(&dir).is_empty());
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities[0].name.as_deref(), Some("messy name"));
+        assert_eq!(day.activities[1].name, None);
+    }
+}
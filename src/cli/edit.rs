@@ -0,0 +1,414 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{DateArg, parse_identifier_arg, parse_relative_date_arg, parse_time_arg};
+use crate::cli::day::ensure_unlocked;
+use crate::cli::editor::normalize_text;
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::day::{ActivityLookupError, DayInner};
+use crate::data::day_limit::DayLimit;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::reference::Reference;
+use crate::data::text::normalize_name;
+use crate::error::Error;
+use clap::Parser;
+use log::{error, info, warn};
+use time::{Date, Time};
+
+#[derive(Parser)]
+pub struct CommandEdit {
+    /// Activity UUID, UUID prefix, short id (see `timetrax show`) or @name
+    activity: Reference,
+    /// Day of the activity (YYYY-MM-DD, or e.g. yesterday, monday, -3d), defaults to today
+    #[arg(long, value_parser = parse_relative_date_arg, allow_hyphen_values = true)]
+    date: Option<DateArg>,
+    /// New short name of the activity
+    #[arg(short, long)]
+    name: Option<String>,
+    /// New classification of the activity
+    #[arg(short, long, value_parser = parse_identifier_arg)]
+    class: Option<Identifier>,
+    /// New start time (HH:MM)
+    #[arg(long, value_parser = parse_time_arg)]
+    start: Option<Time>,
+    /// New end time (HH:MM), also stops an open activity
+    #[arg(long, value_parser = parse_time_arg)]
+    end: Option<Time>,
+    /// Add a project, may be given multiple times
+    #[arg(long, value_parser = parse_identifier_arg)]
+    add_project: Vec<Identifier>,
+    /// Remove a project including its weight, may be given multiple times
+    #[arg(long, value_parser = parse_identifier_arg)]
+    remove_project: Vec<Identifier>,
+    /// New description, an empty text clears it
+    #[arg(short, long)]
+    description: Option<String>,
+    /// Save the change even if the activities of the day add up to more than a day
+    #[arg(short, long)]
+    force: bool,
+}
+
+/// print the activities of the day so that the user can pick the right reference
+pub(crate) fn print_candidates(date: Date, day: &DayInner) {
+    if day.activities.is_empty() {
+        println!("There are no activities on {}.", date);
+        return;
+    }
+    println!("Activities of {}:", date);
+    for activity in &day.activities {
+        println!(" - {} {} {}", activity.short_id(), activity.id, activity);
+    }
+}
+
+/// true if both identifiers refer to the same project of the job config
+fn same_project(job_config: &JobConfig, a: &Identifier, b: &Identifier) -> bool {
+    match (job_config.resolve_project(a), job_config.resolve_project(b)) {
+        (Some(a), Some(b)) => a.id == b.id,
+        _ => a == b,
+    }
+}
+
+impl CommandEdit {
+    /// apply the requested changes to a copy of `activity`
+    fn apply(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        activity: &Activity,
+    ) -> crate::error::Result<Activity> {
+        let mut edited = activity.clone();
+
+        if let Some(name) = &self.name {
+            edited.name = normalize_name(name, config.max_name_length)
+                .inspect_err(|e| error!("Invalid name '{}': {}", name, e))?;
+        }
+        if let Some(class) = &self.class {
+            if job_config.resolve_class(class).is_none() {
+                error!("Failed to resolve classification: {:?}", class);
+                return Err(Error::UnknownClass(class.clone()));
+            }
+            edited.class = class.clone();
+        }
+        if let Some(description) = &self.description {
+            edited.description = normalize_text(description);
+        }
+
+        if let Some(start) = self.start {
+            edited.time.start = start;
+        }
+        if let Some(end) = self.end {
+            edited.time.end = Some(end);
+        }
+        if let Some(end) = edited.time.end {
+            let boundary = job_config.day_boundary;
+            if local_time::to_day_time(end, boundary)
+                < local_time::to_day_time(edited.time.start, boundary)
+            {
+                error!(
+                    "The activity would end at {} before it starts at {}",
+                    end, edited.time.start
+                );
+                return Err(Error::InvalidInterval {
+                    start: edited.time.start,
+                    end,
+                });
+            }
+        }
+
+        for project in &self.remove_project {
+            let before = edited.projects.len();
+            edited
+                .projects
+                .retain(|assigned| !same_project(job_config, assigned, project));
+            edited
+                .weights
+                .retain(|weighted, _| !same_project(job_config, weighted, project));
+            if edited.projects.len() == before {
+                error!("Project {} is not assigned to {}", project, activity);
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Project not assigned to the activity",
+                )
+                .into());
+            }
+        }
+        for project in &self.add_project {
+            if job_config.resolve_project(project).is_none() {
+                error!("Failed to resolve project: {:?}", project);
+                return Err(Error::UnknownProject(project.clone()));
+            }
+            if edited
+                .projects
+                .iter()
+                .any(|assigned| same_project(job_config, assigned, project))
+            {
+                warn!("Project {} is already assigned to {}", project, activity);
+                continue;
+            }
+            edited.projects.push(project.clone());
+        }
+
+        Ok(edited)
+    }
+
+    fn edit(&self, job_config: &JobConfig, manager: &mut Manager) -> crate::error::Result<()> {
+        let config = manager.app_config();
+        let today = job_config.today();
+        let date = self.date.map_or(today, |date| date.resolve(today));
+        let Some(day) = manager.day(date) else {
+            error!("There are no activities on {}", date);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No day file for the date",
+            )
+            .into());
+        };
+        ensure_unlocked(date, day)?;
+
+        let activity = match day.find_activity_by_reference(&self.activity) {
+            Ok(activity) => activity,
+            Err(e) => {
+                error!("{}", e);
+                if !matches!(e, ActivityLookupError::AmbiguousReference(..)) {
+                    print_candidates(date, day);
+                }
+                return Err(e.into());
+            }
+        };
+        let id = activity.id;
+        let edited = self.apply(config, job_config, activity)?;
+
+        if serde_json::to_value(&edited).ok() == serde_json::to_value(activity).ok() {
+            println!("Activity unchanged.");
+            return Ok(());
+        }
+
+        let mut checked = day.clone();
+        if let Ok(activity) = checked.find_activity_mut(&Identifier::Uuid(id)) {
+            *activity = edited.clone();
+        }
+        if let Err(e) = DayLimit::new(config, job_config).check(date, &checked) {
+            if !self.force {
+                e.log();
+                error!("Not editing the activity, see --force");
+                return Err(e.into());
+            }
+            warn!("{}, editing anyway", e);
+            manager.force_day_limit();
+        }
+
+        let day = manager.get_or_create_day_mut(date);
+        let Ok(activity) = day.find_activity_mut(&Identifier::Uuid(id)) else {
+            return Ok(());
+        };
+        info!("Editing activity {:?} to {:?}", activity, edited);
+        *activity = Activity {
+            modified_at: Some(local_time::now()),
+            ..edited
+        };
+        let activity = activity.clone();
+        manager.save()?;
+        println!("Updated activity: {}", activity);
+        Ok(())
+    }
+}
+
+impl ExecutableCommand for CommandEdit {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        self.edit(job_config, &mut manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::data::project::{Project, ProjectInner};
+    use crate::data::reference::MIN_UUID_PREFIX_LENGTH;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Duration, Month, OffsetDateTime};
+    use uuid::Uuid;
+
+    fn setup() -> (ManualClock, TempDataDir, Date) {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let job_config = JobConfig {
+            projects: ["alpha", "beta"]
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| Project {
+                    id: Uuid::from_u128(i as u128 + 1),
+                    inner: ProjectInner {
+                        name: name.to_string(),
+                        description: None,
+                        client: None,
+                        target_share: None,
+                    },
+                })
+                .collect(),
+            ..JobConfig::default()
+        };
+        let dir = TempDataDir::new(&job_config).unwrap();
+        {
+            let _guard = clock.install();
+            dir.execute(&CommandPush::parse_from([
+                "push", "-c", "@work", "-n", "tpyo", "-p", "@alpha", "-w", "@alpha=2",
+            ]))
+            .unwrap();
+        }
+        (clock, dir, date)
+    }
+
+    fn edit(dir: &TempDataDir, args: &[&str]) -> crate::error::Result<()> {
+        dir.execute(&CommandEdit::parse_from(["edit"].iter().chain(args)))
+    }
+
+    fn activity(dir: &TempDataDir, date: Date) -> Activity {
+        dir.day(date).unwrap().unwrap().activities[0].clone()
+    }
+
+    fn created(date: Date) -> Option<OffsetDateTime> {
+        Some(at(date, Time::from_hms(9, 0, 0).unwrap()))
+    }
+
+    #[test]
+    fn test_edit_each_field() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        clock.advance(Duration::hours(1));
+        let short_id = activity(&dir, date).short_id();
+
+        edit(&dir, &[&short_id, "--name", "typo"]).unwrap();
+        assert_eq!(activity(&dir, date).name.as_deref(), Some("typo"));
+
+        edit(&dir, &["@typo", "--class", "@break"]).unwrap();
+        assert_eq!(
+            activity(&dir, date).class,
+            Identifier::ByName("break".into())
+        );
+
+        edit(&dir, &["@typo", "--description", "fixed"]).unwrap();
+        assert_eq!(activity(&dir, date).description.as_deref(), Some("fixed"));
+
+        edit(&dir, &["@typo", "--start", "8:30", "--end", "9:45"]).unwrap();
+        let time = activity(&dir, date).time;
+        assert_eq!(time.start, Time::from_hms(8, 30, 0).unwrap());
+        assert_eq!(time.end, Some(Time::from_hms(9, 45, 0).unwrap()));
+
+        edit(&dir, &["@typo", "--add-project", "@beta"]).unwrap();
+        edit(
+            &dir,
+            &["@typo", "--remove-project", &Uuid::from_u128(1).to_string()],
+        )
+        .unwrap();
+        let edited = activity(&dir, date);
+        assert_eq!(edited.projects, [Identifier::ByName("beta".into())]);
+        assert!(edited.weights.is_empty());
+
+        edit(&dir, &["@typo", "--description", ""]).unwrap();
+        let edited = activity(&dir, date);
+        assert_eq!(edited.description, None);
+        assert_eq!(edited.created_at, created(date));
+        assert_eq!(
+            edited.modified_at,
+            Some(at(date, Time::from_hms(10, 0, 0).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_edit_rejects_invalid_changes() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+
+        let kind = |args: &[&str]| edit(&dir, args).unwrap_err().kind();
+        assert!(matches!(
+            edit(&dir, &["@tpyo", "--class", "@unknown"]),
+            Err(Error::UnknownClass(_))
+        ));
+        assert!(matches!(
+            edit(&dir, &["@tpyo", "--add-project", "@unknown"]),
+            Err(Error::UnknownProject(_))
+        ));
+        assert_eq!(
+            kind(&["@tpyo", "--remove-project", "@beta"]),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            kind(&["@tpyo", "--start", "10:00", "--end", "9:00"]),
+            std::io::ErrorKind::InvalidInput
+        );
+
+        let unchanged = activity(&dir, date);
+        assert_eq!(unchanged.name.as_deref(), Some("tpyo"));
+        assert_eq!(unchanged.modified_at, None);
+        assert_eq!(unchanged.created_at, created(date));
+    }
+
+    #[test]
+    fn test_edit_references() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        let (first, second) = (&day.activities[0], &day.activities[1]);
+
+        // a UUID prefix, with or without dashes
+        let prefix = (first
+            .id
+            .simple()
+            .to_string()
+            .chars()
+            .zip(second.id.simple().to_string().chars())
+            .take_while(|(a, b)| a == b)
+            .count()
+            + 1)
+        .max(MIN_UUID_PREFIX_LENGTH);
+        let simple = first.id.simple().to_string();
+        edit(&dir, &[&simple[..prefix], "--name", "by prefix"]).unwrap();
+        assert_eq!(activity(&dir, date).name.as_deref(), Some("by prefix"));
+        edit(&dir, &[&first.id.to_string(), "--name", "by id"]).unwrap();
+        assert_eq!(activity(&dir, date).name.as_deref(), Some("by id"));
+
+        assert_eq!(
+            edit(&dir, &["zzzzzzzzzz", "--name", "x"])
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            edit(&dir, &["@tpyo", "--date", "2024-05-02", "--name", "x"])
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_edit_refuses_days_over_a_day() {
+        let (clock, mut dir, date) = setup();
+        dir.app_config_mut().max_day_total = Duration::hours(20);
+        clock.set(at(date, Time::from_hms(12, 0, 0).unwrap()));
+        let _guard = clock.install();
+
+        // the open activity runs until the end of the day, covering all 24h
+        let result = edit(&dir, &["@tpyo", "--start", "00:00"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            activity(&dir, date).time.start,
+            Time::from_hms(9, 0, 0).unwrap()
+        );
+
+        edit(&dir, &["@tpyo", "--start", "00:00", "--force"]).unwrap();
+        assert_eq!(activity(&dir, date).time.start, Time::MIDNIGHT);
+    }
+}
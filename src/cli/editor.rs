@@ -0,0 +1,112 @@
+use log::{error, trace};
+use std::io::{IsTerminal, Read};
+use std::process::Command;
+use uuid::Uuid;
+
+/// interactively edits a piece of text
+pub trait Editor {
+    /// returns the edited text, trailing newlines are not yet normalized
+    fn edit(&self, initial: &str) -> std::io::Result<String>;
+}
+
+impl<F: Fn(&str) -> std::io::Result<String>> Editor for F {
+    fn edit(&self, initial: &str) -> std::io::Result<String> {
+        self(initial)
+    }
+}
+
+/// opens the text in `$VISUAL`/`$EDITOR` (falling back to `vi`) using a temp file
+pub struct ExternalEditor {
+    command: String,
+}
+
+impl ExternalEditor {
+    pub fn from_env() -> Self {
+        let command = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|command| !command.trim().is_empty())
+            .unwrap_or_else(|| "vi".to_string());
+        Self { command }
+    }
+}
+
+impl Editor for ExternalEditor {
+    fn edit(&self, initial: &str) -> std::io::Result<String> {
+        let path = std::env::temp_dir().join(format!("timetrax-{}.txt", Uuid::new_v4()));
+        std::fs::write(&path, format!("{initial}\n"))?;
+
+        // editors like `code --wait` carry arguments
+        let mut parts = self.command.split_whitespace();
+        let program = parts.next().unwrap_or("vi");
+        trace!("Launching editor {} on {}", self.command, path.display());
+        let status = Command::new(program).args(parts).arg(&path).status();
+
+        let result = match status {
+            Err(e) => {
+                error!("Failed to launch editor '{}': {}", self.command, e);
+                Err(e)
+            }
+            Ok(status) if !status.success() => {
+                error!("Editor '{}' exited with {}", self.command, status);
+                Err(std::io::Error::other("Editor exited unsuccessfully"))
+            }
+            Ok(_) => std::fs::read_to_string(&path).inspect_err(|e| {
+                error!("Failed to read edited text (not UTF-8?): {}", e);
+            }),
+        };
+
+        let _ = std::fs::remove_file(&path);
+        result
+    }
+}
+
+/// reads the text from stdin, ignoring the initial text
+pub struct StdinEditor;
+
+impl Editor for StdinEditor {
+    fn edit(&self, _initial: &str) -> std::io::Result<String> {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        Ok(text)
+    }
+}
+
+/// external editor when run interactively, stdin otherwise
+pub fn default_editor() -> Box<dyn Editor> {
+    if std::io::stdin().is_terminal() {
+        Box::new(ExternalEditor::from_env())
+    } else {
+        Box::new(StdinEditor)
+    }
+}
+
+/// strips trailing line breaks, `None` if nothing but whitespace remains
+pub fn normalize_text(text: &str) -> Option<String> {
+    let text = text.trim_end_matches(['\n', '\r']);
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        assert_eq!(normalize_text("line\n"), Some("line".to_string()));
+        assert_eq!(
+            normalize_text("first\r\nsecond\r\n\n"),
+            Some("first\r\nsecond".to_string())
+        );
+        assert_eq!(
+            normalize_text("  indented\n"),
+            Some("  indented".to_string())
+        );
+        assert_eq!(normalize_text("\n"), None);
+        assert_eq!(normalize_text(" \n\t\n"), None);
+    }
+}
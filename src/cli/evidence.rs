@@ -0,0 +1,128 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{parse_date_arg, parse_identifier_arg};
+use crate::cli::day::ensure_unlocked;
+use crate::data::app_config::AppConfig;
+use crate::data::evidence::EvidenceEntry;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use clap::Parser;
+use log::{error, info};
+use time::Date;
+
+#[derive(Parser)]
+pub struct CommandEvidence {
+    /// Activity UUID or @name, names must be unique on the day
+    #[arg(value_parser = parse_identifier_arg)]
+    activity: Identifier,
+    /// Short context, e.g. the file being edited or the window title
+    text: String,
+    /// Day of the activity (YYYY-MM-DD), searches all days if omitted
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+}
+
+impl ExecutableCommand for CommandEvidence {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        _job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let text = self.text.trim();
+        if text.is_empty() {
+            error!("Evidence must not be empty");
+            return Err(
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "Empty evidence").into(),
+            );
+        }
+
+        let date = manager
+            .locate_activity(&self.activity, self.date)
+            .inspect_err(|e| error!("{}", e))?;
+        ensure_unlocked(date, manager.get_or_create_day_ref(date))?;
+
+        let activity = manager
+            .get_or_create_day_mut(date)
+            .find_activity_mut(&self.activity)?;
+        let dropped = activity.add_evidence(
+            EvidenceEntry {
+                at: local_time::now(),
+                text: text.to_string(),
+            },
+            config.evidence_limit,
+        );
+        if dropped > 0 {
+            info!("Dropped {} old evidence entries of {}", dropped, activity);
+        }
+        let activity = activity.to_string();
+        manager.save()?;
+        println!("Added evidence to {}", activity);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Duration, Month, Time};
+
+    #[test]
+    fn test_evidence_attaches_to_activity() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding",
+        ]))
+        .unwrap();
+
+        for file in ["src/main.rs", "src/lib.rs", "  "] {
+            clock.advance(Duration::minutes(5));
+            let _ = dir.execute(&CommandEvidence::parse_from(["evidence", "@coding", file]));
+        }
+
+        let evidence = dir.day(date).unwrap().unwrap().activities[0]
+            .evidence
+            .clone();
+        assert_eq!(evidence.len(), 2);
+        assert_eq!(evidence[0].text, "src/main.rs");
+        assert_eq!(evidence[0].at, at(date, Time::from_hms(9, 5, 0).unwrap()));
+        assert_eq!(evidence[1].text, "src/lib.rs");
+    }
+
+    #[test]
+    fn test_evidence_refuses_locked_day() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding",
+        ]))
+        .unwrap();
+        dir.manager().unwrap().get_or_create_day_mut(date).locked = true;
+
+        let result = dir.execute(&CommandEvidence::parse_from([
+            "evidence",
+            "@coding",
+            "src/main.rs",
+        ]));
+
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+        assert!(
+            dir.day(date).unwrap().unwrap().activities[0]
+                .evidence
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,986 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{PeriodArg, parse_date_arg};
+use crate::cli::mapping::{MappingOptions, Prompt, StdinPrompt, format_unmapped, load_mappings};
+use crate::data::BASIC_TIME_FORMAT;
+use crate::data::activity::Activity;
+use crate::data::activity_closure::ClosureDiagnostics;
+use crate::data::app_config::AppConfig;
+use crate::data::evidence::EvidenceEntry;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::{AnnotatedDayInformation, Manager};
+use crate::data::mapping::{MappingKind, MappingStore};
+use crate::data::report::day_closure;
+use clap::{Args, Parser};
+use log::{error, warn};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use time::{Date, OffsetDateTime, Time};
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub enum CommandExport {
+    /// One JSON object per activity and line (JSON Lines)
+    #[clap(aliases = ["json-lines", "ndjson"])]
+    Jsonl(ExportOptions),
+    /// One row per activity with a header line (CSV), e.g. for payroll tools
+    Csv(CsvOptions),
+    /// One calendar event per activity (iCalendar), e.g. to overlay tracked time onto a calendar
+    #[clap(aliases = ["ics", "icalendar"])]
+    Ical(ExportOptions),
+}
+
+#[derive(Args, Clone)]
+pub struct CsvOptions {
+    #[command(flatten)]
+    export: ExportOptions,
+    /// Clamp open activities to now (or the end of past days) instead of skipping them
+    #[arg(long)]
+    clamp_open: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ExportOptions {
+    /// Export a named period, e.g. last-week or this-month
+    #[arg(long, value_enum, conflicts_with_all = ["from", "to"])]
+    period: Option<PeriodArg>,
+    /// First day to export (YYYY-MM-DD), defaults to today
+    #[arg(short, long, value_parser = parse_date_arg)]
+    from: Option<Date>,
+    /// Last day to export (YYYY-MM-DD), defaults to the first day
+    #[arg(short, long, value_parser = parse_date_arg)]
+    to: Option<Date>,
+    /// Export the activity closure (non-overlapping segments) instead of the raw activities
+    #[arg(long)]
+    closure: bool,
+    /// File to write to, defaults to stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Fail instead of warning if closure segments refer to unknown classes
+    #[arg(long, requires = "closure")]
+    strict: bool,
+    #[command(flatten)]
+    mapping: MappingOptions,
+}
+
+/// resolves class and project names, each identifier at most once per run
+///
+/// names are translated to the given system if it has a mapping for them
+struct NameCache<'a> {
+    job_config: &'a JobConfig,
+    mappings: Option<(&'a MappingStore, &'a str)>,
+    classes: HashMap<Identifier, String>,
+    projects: HashMap<Identifier, String>,
+    /// names without a mapping of the system
+    unmapped: BTreeSet<(MappingKind, String)>,
+}
+
+impl<'a> NameCache<'a> {
+    fn new(job_config: &'a JobConfig, mappings: Option<(&'a MappingStore, &'a str)>) -> Self {
+        Self {
+            job_config,
+            mappings,
+            classes: HashMap::new(),
+            projects: HashMap::new(),
+            unmapped: BTreeSet::new(),
+        }
+    }
+
+    /// external name of the class or project, its own name if unmapped
+    fn translate(&mut self, kind: MappingKind, id: Uuid, name: &str) -> String {
+        let Some((mappings, system)) = self.mappings else {
+            return name.to_string();
+        };
+        match mappings.to_external(self.job_config, system, kind, id) {
+            Some(external) => external.to_string(),
+            None => {
+                self.unmapped.insert((kind, name.to_string()));
+                name.to_string()
+            }
+        }
+    }
+
+    fn class(&mut self, class: &Identifier) -> String {
+        if let Some(name) = self.classes.get(class) {
+            return name.clone();
+        }
+        let name = match self.job_config.resolve_class(class) {
+            Some(resolved) => self.translate(MappingKind::Class, resolved.id, &resolved.inner.name),
+            None => {
+                error!("Failed to resolve class with id {}", class);
+                class.to_string()
+            }
+        };
+        self.classes.insert(class.clone(), name.clone());
+        name
+    }
+
+    fn project(&mut self, project: &Identifier) -> String {
+        if let Some(name) = self.projects.get(project) {
+            return name.clone();
+        }
+        let name = match self.job_config.resolve_project(project) {
+            Some(resolved) => {
+                self.translate(MappingKind::Project, resolved.id, &resolved.inner.name)
+            }
+            None => {
+                error!("Failed to resolve project with id {}", project);
+                project.to_string()
+            }
+        };
+        self.projects.insert(project.clone(), name.clone());
+        name
+    }
+}
+
+/// denormalized activity, one per exported line
+#[derive(Serialize)]
+struct ExportRecord<'a> {
+    id: Uuid,
+    date: Date,
+    class: String,
+    projects: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(with = "time::serde::rfc3339")]
+    start: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    end: Option<OffsetDateTime>,
+    duration_seconds: Option<i64>,
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    evidence: &'a [EvidenceEntry],
+    origin: Option<&'a Path>,
+}
+
+/// times before the day boundary belong to the next calendar date
+fn timestamp(date: Date, time: Time, boundary: Option<Time>) -> OffsetDateTime {
+    let datetime = local_time::day_datetime(date, time, boundary);
+    datetime.assume_offset(local_time::local_offset_at(
+        datetime.date(),
+        datetime.time(),
+    ))
+}
+
+/// closure segments of the given days
+fn day_closures<'a, I: Iterator<Item = (&'a Date, &'a AnnotatedDayInformation)>>(
+    job_config: &JobConfig,
+    days: I,
+    now: OffsetDateTime,
+) -> (BTreeMap<Date, Vec<Activity>>, ClosureDiagnostics) {
+    let mut diagnostics = ClosureDiagnostics::default();
+    let closures = days
+        .map(|(date, day)| {
+            let (closure, day_diagnostics) = day_closure(job_config, *date, day.inner(), now);
+            diagnostics.merge(day_diagnostics);
+            (*date, closure)
+        })
+        .collect();
+    (closures, diagnostics)
+}
+
+/// write one line per activity (or closure segment if `closures` are given) of the given days,
+/// returns the number of lines
+fn write_jsonl<'a, W: Write, I: Iterator<Item = (&'a Date, &'a AnnotatedDayInformation)>>(
+    writer: &mut W,
+    job_config: &JobConfig,
+    names: &mut NameCache,
+    days: I,
+    closures: Option<&BTreeMap<Date, Vec<Activity>>>,
+) -> std::io::Result<usize> {
+    let mut count = 0;
+    let boundary = job_config.day_boundary;
+
+    for (date, day) in days {
+        let activities: &[Activity] = match closures {
+            Some(closures) => closures.get(date).map(Vec::as_slice).unwrap_or_default(),
+            None => &day.inner().activities,
+        };
+
+        for activity in activities {
+            // closure segments are given in day time
+            let time = if closures.is_some() {
+                activity.time.from_day_time(boundary)
+            } else {
+                activity.time.clone()
+            };
+            let record = ExportRecord {
+                id: activity.id,
+                date: *date,
+                class: names.class(&activity.class),
+                projects: activity
+                    .projects
+                    .iter()
+                    .map(|project| names.project(project))
+                    .collect(),
+                name: activity.name.as_deref(),
+                description: activity.description.as_deref(),
+                start: timestamp(*date, time.start, boundary),
+                end: time.end.map(|end| timestamp(*date, end, boundary)),
+                duration_seconds: time
+                    .to_day_time(boundary)
+                    .duration()
+                    .map(|d| d.whole_seconds()),
+                tags: &activity.tags,
+                evidence: &activity.evidence,
+                origin: day.origin(),
+            };
+
+            serde_json::to_writer(&mut *writer, &record).map_err(std::io::Error::other)?;
+            writer.write_all(b"\n")?;
+            count += 1;
+        }
+    }
+
+    writer.flush()?;
+    Ok(count)
+}
+
+/// quote the field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+fn format_time(time: Time) -> std::io::Result<String> {
+    time.format(&*BASIC_TIME_FORMAT)
+        .map_err(std::io::Error::other)
+}
+
+/// write a header and one row per activity (or closure segment if `closures` are given),
+/// returns the number of rows
+///
+/// open activities are completed at `clamp_open` (or the end of past days) if given, skipped otherwise
+fn write_csv<'a, W: Write, I: Iterator<Item = (&'a Date, &'a AnnotatedDayInformation)>>(
+    writer: &mut W,
+    job_config: &JobConfig,
+    names: &mut NameCache,
+    days: I,
+    closures: Option<&BTreeMap<Date, Vec<Activity>>>,
+    clamp_open: Option<OffsetDateTime>,
+) -> std::io::Result<usize> {
+    let mut count = 0;
+    let boundary = job_config.day_boundary;
+
+    writeln!(
+        writer,
+        "date,start,end,duration_seconds,class,name,projects,tags"
+    )?;
+    for (date, day) in days {
+        let activities: &[Activity] = match closures {
+            Some(closures) => closures.get(date).map(Vec::as_slice).unwrap_or_default(),
+            None => &day.inner().activities,
+        };
+
+        for activity in activities {
+            // closure segments are given in day time
+            let mut time = if closures.is_some() {
+                activity.time.from_day_time(boundary)
+            } else {
+                activity.time.clone()
+            };
+            if !time.is_complete() {
+                let Some(now) = clamp_open else {
+                    warn!(
+                        "Skipping open activity {} on {}, see --clamp-open",
+                        activity.id, date
+                    );
+                    continue;
+                };
+                let end = if *date == job_config.date_of(now) {
+                    now.time()
+                } else {
+                    local_time::from_day_time(Time::MAX, boundary)
+                };
+                time.complete_at(end, boundary);
+            }
+            let Some(end) = time.end else { continue };
+            let duration = time.to_day_time(boundary).duration().unwrap_or_default();
+
+            let projects = activity
+                .projects
+                .iter()
+                .map(|project| names.project(project))
+                .collect::<Vec<_>>()
+                .join(";");
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{}",
+                date,
+                format_time(time.start)?,
+                format_time(end)?,
+                duration.whole_seconds(),
+                csv_field(&names.class(&activity.class)),
+                csv_field(activity.name.as_deref().unwrap_or_default()),
+                csv_field(&projects),
+                csv_field(&activity.tags.join(";")),
+            )?;
+            count += 1;
+        }
+    }
+
+    writer.flush()?;
+    Ok(count)
+}
+
+/// escape text values, see RFC 5545 section 3.3.11
+fn ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\r', "")
+        .replace('\n', "\\n")
+}
+
+/// UTC date-time, e.g. `20240502T070000Z`
+fn ical_datetime(datetime: OffsetDateTime) -> String {
+    let utc = datetime.to_offset(time::UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        utc.year(),
+        utc.month() as u8,
+        utc.day(),
+        utc.hour(),
+        utc.minute(),
+        utc.second()
+    )
+}
+
+/// write a content line, folded after 75 octets, see RFC 5545 section 3.1
+fn write_ical_line<W: Write>(writer: &mut W, line: &str) -> std::io::Result<()> {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            writer.write_all(b"\r\n ")?;
+            width = 1;
+        }
+        write!(writer, "{}", c)?;
+        width += c.len_utf8();
+    }
+    writer.write_all(b"\r\n")
+}
+
+/// write a calendar with one event per activity (or closure segment if `closures` are given),
+/// returns the number of events
+///
+/// open activities end at `now` and are marked with `X-TIMETRAX-OPEN:TRUE`
+fn write_ical<'a, W: Write, I: Iterator<Item = (&'a Date, &'a AnnotatedDayInformation)>>(
+    writer: &mut W,
+    job_config: &JobConfig,
+    names: &mut NameCache,
+    days: I,
+    closures: Option<&BTreeMap<Date, Vec<Activity>>>,
+    now: OffsetDateTime,
+) -> std::io::Result<usize> {
+    let mut count = 0;
+    let boundary = job_config.day_boundary;
+
+    write_ical_line(writer, "BEGIN:VCALENDAR")?;
+    write_ical_line(writer, "VERSION:2.0")?;
+    write_ical_line(writer, "PRODID:-//timetrax//timetrax//EN")?;
+    for (date, day) in days {
+        let activities: &[Activity] = match closures {
+            Some(closures) => closures.get(date).map(Vec::as_slice).unwrap_or_default(),
+            None => &day.inner().activities,
+        };
+
+        for activity in activities {
+            // closure segments are given in day time
+            let time = if closures.is_some() {
+                activity.time.from_day_time(boundary)
+            } else {
+                activity.time.clone()
+            };
+            let summary = match &activity.name {
+                Some(name) => name.clone(),
+                None => names.class(&activity.class),
+            };
+            let categories = activity
+                .projects
+                .iter()
+                .map(|project| names.project(project))
+                .chain(activity.tags.iter().cloned())
+                .map(|category| ical_text(&category))
+                .collect::<Vec<_>>();
+
+            write_ical_line(writer, "BEGIN:VEVENT")?;
+            write_ical_line(writer, &format!("UID:{}@timetrax", activity.id))?;
+            write_ical_line(writer, &format!("DTSTAMP:{}", ical_datetime(now)))?;
+            write_ical_line(
+                writer,
+                &format!(
+                    "DTSTART:{}",
+                    ical_datetime(timestamp(*date, time.start, boundary))
+                ),
+            )?;
+            let end = match time.end {
+                Some(end) => timestamp(*date, end, boundary),
+                None => now,
+            };
+            write_ical_line(writer, &format!("DTEND:{}", ical_datetime(end)))?;
+            write_ical_line(writer, &format!("SUMMARY:{}", ical_text(&summary)))?;
+            if !categories.is_empty() {
+                write_ical_line(writer, &format!("CATEGORIES:{}", categories.join(",")))?;
+            }
+            if !time.is_complete() {
+                write_ical_line(writer, "X-TIMETRAX-OPEN:TRUE")?;
+            }
+            write_ical_line(writer, "END:VEVENT")?;
+            count += 1;
+        }
+    }
+    write_ical_line(writer, "END:VCALENDAR")?;
+
+    writer.flush()?;
+    Ok(count)
+}
+
+/// ask for the external names of the classes and projects of `activities` the system has no
+/// mapping for, valid answers are added to `mappings`. returns the number of added mappings
+fn ask_unmapped<'a>(
+    job_config: &JobConfig,
+    mappings: &mut MappingStore,
+    system: &str,
+    activities: impl Iterator<Item = &'a Activity>,
+    prompt: &dyn Prompt,
+) -> std::io::Result<usize> {
+    let mut referenced = BTreeSet::new();
+    for activity in activities {
+        if let Some(class) = job_config.resolve_class(&activity.class) {
+            referenced.insert((MappingKind::Class, class.inner.name.as_str(), class.id));
+        }
+        for project in &activity.projects {
+            if let Some(project) = job_config.resolve_project(project) {
+                referenced.insert((
+                    MappingKind::Project,
+                    project.inner.name.as_str(),
+                    project.id,
+                ));
+            }
+        }
+    }
+
+    let mut added = 0;
+    for (kind, name, id) in referenced {
+        if mappings.to_external(job_config, system, kind, id).is_some() {
+            continue;
+        }
+        let question = format!("Name of the {} {} in {}", kind, name, system);
+        let Some(external) = prompt.prompt(&question)? else {
+            continue;
+        };
+        match mappings.add(job_config, system, kind, &external, &Identifier::Uuid(id)) {
+            Ok(_) => added += 1,
+            Err(e) => warn!("{}, the {} {} stays unmapped", e, kind, name),
+        }
+    }
+    Ok(added)
+}
+
+impl CommandExport {
+    fn options(&self) -> &ExportOptions {
+        match self {
+            CommandExport::Jsonl(options) | CommandExport::Ical(options) => options,
+            CommandExport::Csv(options) => &options.export,
+        }
+    }
+
+    /// `--interactive` stores the answers in the mappings of the data directory
+    pub fn writes_mappings(&self) -> bool {
+        self.options().mapping.interactive
+    }
+}
+
+impl ExecutableCommand for CommandExport {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let options = self.options();
+        let clamp_open = matches!(self, CommandExport::Csv(options) if options.clamp_open);
+
+        let now = local_time::now();
+        let (from, to) = match options.period {
+            Some(period) => period.resolve(job_config.date_of(now), config.week_start),
+            None => {
+                let from = options.from.unwrap_or(job_config.date_of(now));
+                (from, options.to.unwrap_or(from))
+            }
+        };
+        if from > to {
+            error!("The export must start before it ends: {} > {}", from, to);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Export starts after it ends",
+            )
+            .into());
+        }
+        manager.load_range(from, to);
+        let days = manager.annotated_days_in(from..=to);
+
+        // closures are computed up front so strict mode fails before anything is written
+        let closures = options
+            .closure
+            .then(|| day_closures(job_config, days.clone(), now));
+        if let Some((_, diagnostics)) = &closures {
+            diagnostics.check(options.strict)?;
+            if let Some(warning) = diagnostics.warning() {
+                eprintln!("{}", warning);
+            }
+        }
+        let closures = closures.as_ref().map(|(closures, _)| closures);
+
+        let system = options.mapping.system.as_deref();
+        let mut mappings = match system {
+            Some(system) => load_mappings(config, job_config, &manager, system)?,
+            None => MappingStore::default(),
+        };
+        if let Some(system) = system
+            && options.mapping.interactive
+        {
+            let activities: Vec<&Activity> = match closures {
+                Some(closures) => closures.values().flatten().collect(),
+                None => days
+                    .clone()
+                    .flat_map(|(_, day)| &day.inner().activities)
+                    .collect(),
+            };
+            let added = ask_unmapped(
+                job_config,
+                &mut mappings,
+                system,
+                activities.into_iter(),
+                &StdinPrompt,
+            )?;
+            if added > 0 {
+                mappings.save(&MappingStore::path(config, manager.data_path()))?;
+            }
+        }
+        let mut names = NameCache::new(job_config, system.map(|system| (&mappings, system)));
+
+        let mut writer: BufWriter<Box<dyn Write>> = match &options.output {
+            Some(path) => BufWriter::new(Box::new(File::create(path).inspect_err(|e| {
+                error!("Failed to open {} for writing: {}", path.display(), e);
+            })?)),
+            None => BufWriter::new(Box::new(std::io::stdout().lock())),
+        };
+        let result = match self {
+            CommandExport::Jsonl(_) => {
+                write_jsonl(&mut writer, job_config, &mut names, days, closures)
+            }
+            CommandExport::Csv(_) => write_csv(
+                &mut writer,
+                job_config,
+                &mut names,
+                days,
+                closures,
+                clamp_open.then_some(now),
+            ),
+            CommandExport::Ical(_) => {
+                write_ical(&mut writer, job_config, &mut names, days, closures, now)
+            }
+        };
+        result.inspect_err(|e| {
+            error!("Failed to export activities: {}", e);
+        })?;
+
+        if let Some(system) = system {
+            eprint!("{}", format_unmapped(system, &names.unmapped));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{CommandPop, CommandPush};
+    use crate::data::project::{Project, ProjectInner};
+    use crate::testing::{Clock, ManualClock, TempDataDir, at};
+    use time::{Duration, Month};
+
+    #[test]
+    fn test_strict_closure_export_rejects_unknown_classes() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/unknown_class_day.json"
+            )),
+        )
+        .unwrap();
+        let output = dir.path().join("export.jsonl");
+        let export = |strict: bool| {
+            let mut args = vec![
+                "export",
+                "jsonl",
+                "-f",
+                "2024-05-02",
+                "--closure",
+                "-o",
+                output.to_str().unwrap(),
+            ];
+            if strict {
+                args.push("--strict");
+            }
+            dir.execute(&CommandExport::parse_from(args))
+        };
+
+        assert!(export(true).is_err());
+        assert!(!output.exists());
+
+        export(false).unwrap();
+        let lines = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(lines.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_jsonl_lines_parse_independently() {
+        let first = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let clock = ManualClock::new(at(first, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        // two activities on the first day, one on the next, one outside the range
+        for date in [
+            first,
+            first,
+            first.next_day().unwrap(),
+            first + Duration::days(5),
+        ] {
+            clock.set(at(date, clock.now().time() + Duration::hours(1)));
+            dir.execute(&CommandPush::parse_from([
+                "push",
+                "-c",
+                "@work",
+                "-t",
+                "deep-work",
+            ]))
+            .unwrap();
+            clock.advance(Duration::minutes(30));
+            dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+        }
+
+        let manager = dir.manager().unwrap();
+        let mut output = Vec::new();
+        let job_config = dir.job_config().unwrap();
+        let count = write_jsonl(
+            &mut output,
+            &job_config,
+            &mut NameCache::new(&job_config, None),
+            manager.annotated_days_in(first..=first.next_day().unwrap()),
+            None,
+        )
+        .unwrap();
+
+        let lines = String::from_utf8(output).unwrap();
+        let records = lines
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(count, 3);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["class"], "work");
+        assert_eq!(records[0]["date"], "2024-05-02");
+        assert_eq!(records[0]["duration_seconds"], 1800);
+        assert_eq!(records[0]["tags"][0], "deep-work");
+        assert!(
+            records[0]["start"]
+                .as_str()
+                .unwrap()
+                .starts_with("2024-05-02T10:00:00")
+        );
+        assert!(
+            records[0]["origin"]
+                .as_str()
+                .unwrap()
+                .ends_with("2024-05-02.json")
+        );
+    }
+
+    #[test]
+    fn test_csv_export_of_fixture_day() {
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let now = at(date, Time::from_hms(15, 0, 0).unwrap());
+        let _guard = ManualClock::new(now).install();
+        let project = |name: &str| Project {
+            id: Uuid::new_v4(),
+            inner: ProjectInner {
+                name: name.to_string(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        };
+        let job_config = JobConfig {
+            projects: vec![project("alpha"), project("beta, gamma")],
+            ..JobConfig::default()
+        };
+        let dir = TempDataDir::new(&job_config).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/export/2024-05-02.json"
+            )),
+        )
+        .unwrap();
+        let job_config = dir.job_config().unwrap();
+        let manager = dir.manager().unwrap();
+        let csv = |clamp_open: Option<OffsetDateTime>| {
+            let mut output = Vec::new();
+            let count = write_csv(
+                &mut output,
+                &job_config,
+                &mut NameCache::new(&job_config, None),
+                manager.annotated_days_in(date..=date),
+                None,
+                clamp_open,
+            )
+            .unwrap();
+            (count, String::from_utf8(output).unwrap())
+        };
+
+        let header = "date,start,end,duration_seconds,class,name,projects,tags\n";
+        let rows = "2024-05-02,09:00:00,11:30:00,9000,work,\"coding, reviews\",\"alpha;beta, gamma\",deep-work;oncall\n\
+                    2024-05-02,12:00:00,12:45:00,2700,break,\"lunch \"\"long\"\"\",,\n";
+        assert_eq!(csv(None), (2, format!("{header}{rows}")));
+        assert_eq!(
+            csv(Some(now)),
+            (
+                3,
+                format!("{header}{rows}2024-05-02,13:00:00,15:00:00,7200,work,writing,alpha,\n")
+            )
+        );
+    }
+
+    #[test]
+    fn test_ical_export_of_fixture_day() {
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let now = at(date, Time::from_hms(15, 0, 0).unwrap());
+        let _guard = ManualClock::new(now).install();
+        let job_config = JobConfig {
+            projects: ["alpha", "beta, gamma"]
+                .map(|name| Project {
+                    id: Uuid::new_v4(),
+                    inner: ProjectInner {
+                        name: name.to_string(),
+                        description: None,
+                        client: None,
+                        target_share: None,
+                    },
+                })
+                .to_vec(),
+            ..JobConfig::default()
+        };
+        let dir = TempDataDir::new(&job_config).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/export/2024-05-02.json"
+            )),
+        )
+        .unwrap();
+        let manager = dir.manager().unwrap();
+        let mut output = Vec::new();
+        let job_config = dir.job_config().unwrap();
+        let count = write_ical(
+            &mut output,
+            &job_config,
+            &mut NameCache::new(&job_config, None),
+            manager.annotated_days_in(date..=date),
+            None,
+            now,
+        )
+        .unwrap();
+
+        // unfold the content lines and split them into events of (name, value) properties
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.split("\r\n").all(|line| line.len() <= 75));
+        let unfolded = text.replace("\r\n ", "");
+        let events = unfolded
+            .split("BEGIN:VEVENT\r\n")
+            .skip(1)
+            .map(|event| {
+                event
+                    .lines()
+                    .take_while(|line| *line != "END:VEVENT")
+                    .map(|line| line.split_once(':').unwrap())
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .collect::<Vec<_>>();
+        let at = |time: Time| ical_datetime(timestamp(date, time, None));
+
+        assert_eq!(count, 3);
+        assert_eq!(events.len(), 3);
+        assert!(unfolded.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(unfolded.ends_with("END:VCALENDAR\r\n"));
+
+        assert_eq!(
+            events[0]["UID"],
+            "8d2e4f6a-1b3c-4d5e-8f7a-9b0c1d2e3f01@timetrax"
+        );
+        assert_eq!(events[0]["DTSTART"], at(Time::from_hms(9, 0, 0).unwrap()));
+        assert_eq!(events[0]["DTEND"], at(Time::from_hms(11, 30, 0).unwrap()));
+        assert_eq!(events[0]["SUMMARY"], "coding\\, reviews");
+        assert_eq!(
+            events[0]["CATEGORIES"],
+            "alpha,beta\\, gamma,deep-work,oncall"
+        );
+        assert!(!events[0].contains_key("X-TIMETRAX-OPEN"));
+
+        assert_eq!(events[1]["SUMMARY"], "lunch \"long\"");
+        assert!(!events[1].contains_key("CATEGORIES"));
+
+        assert_eq!(events[2]["DTEND"], ical_datetime(now));
+        assert_eq!(events[2]["X-TIMETRAX-OPEN"], "TRUE");
+    }
+
+    #[test]
+    fn test_ical_text_escapes_separators() {
+        assert_eq!(ical_text("lunch; late"), "lunch\\; late");
+        assert_eq!(ical_text("a, b"), "a\\, b");
+        assert_eq!(ical_text("C:\\temp"), "C:\\\\temp");
+        assert_eq!(ical_text("two\r\nlines"), "two\\nlines");
+    }
+
+    #[test]
+    fn test_interactive_export_locks_the_data_directory() {
+        use crate::cli::AppArgs;
+        let read_only = |args: &[&str]| {
+            let args = AppArgs::parse_from(["timetrax", "export"].iter().chain(args));
+            args.command.unwrap().is_read_only()
+        };
+        assert!(read_only(&["csv"]));
+        assert!(read_only(&["jsonl", "--system", "clockify"]));
+        assert!(!read_only(&[
+            "csv",
+            "--system",
+            "clockify",
+            "--interactive"
+        ]));
+        assert!(!read_only(&[
+            "ical",
+            "--system",
+            "clockify",
+            "--interactive"
+        ]));
+    }
+
+    #[test]
+    fn test_csv_export_translates_mapped_names() {
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let now = at(date, Time::from_hms(15, 0, 0).unwrap());
+        let _guard = ManualClock::new(now).install();
+        let job_config = JobConfig {
+            projects: ["alpha", "beta, gamma"]
+                .map(|name| Project {
+                    id: Uuid::new_v4(),
+                    inner: ProjectInner {
+                        name: name.to_string(),
+                        description: None,
+                        client: None,
+                        target_share: None,
+                    },
+                })
+                .to_vec(),
+            ..JobConfig::default()
+        };
+        let dir = TempDataDir::new(&job_config).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/export/2024-05-02.json"
+            )),
+        )
+        .unwrap();
+        let job_config = dir.job_config().unwrap();
+        let manager = dir.manager().unwrap();
+        let mut mappings = MappingStore::default();
+        mappings
+            .add(
+                &job_config,
+                "clockify",
+                MappingKind::Project,
+                "Alpha GmbH",
+                &Identifier::ByName("alpha".into()),
+            )
+            .unwrap();
+
+        // classes are asked for, skipping one leaves it unmapped
+        let activities = manager
+            .annotated_days_in(date..=date)
+            .flat_map(|(_, day)| &day.inner().activities);
+        let prompt = |question: &str| {
+            Ok(match question {
+                "Name of the class work in clockify" => Some("Billable".to_string()),
+                "Name of the project beta, gamma in clockify" => Some("Alpha GmbH".to_string()),
+                _ => None,
+            })
+        };
+        let added = ask_unmapped(&job_config, &mut mappings, "clockify", activities, &prompt);
+        // the project answer clashes with the existing mapping of alpha
+        assert_eq!(added.unwrap(), 1);
+
+        let mut names = NameCache::new(&job_config, Some((&mappings, "clockify")));
+        let mut output = Vec::new();
+        write_csv(
+            &mut output,
+            &job_config,
+            &mut names,
+            manager.annotated_days_in(date..=date),
+            None,
+            None,
+        )
+        .unwrap();
+        let csv = String::from_utf8(output).unwrap();
+        assert!(csv.contains(",Billable,\"coding, reviews\",\"Alpha GmbH;beta, gamma\","));
+        assert!(csv.contains(",break,\"lunch"));
+        assert_eq!(
+            names.unmapped,
+            BTreeSet::from([
+                (MappingKind::Class, "break".to_string()),
+                (MappingKind::Project, "beta, gamma".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_ical_lines_are_folded() {
+        let mut output = Vec::new();
+        write_ical_line(&mut output, &format!("SUMMARY:{}", "ä".repeat(50))).unwrap();
+
+        let text = String::from_utf8(output).unwrap();
+        let lines = text
+            .strip_suffix("\r\n")
+            .unwrap()
+            .split("\r\n")
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert_eq!(
+            lines.join("\r\n").replace("\r\n ", ""),
+            format!("SUMMARY:{}", "ä".repeat(50))
+        );
+    }
+}
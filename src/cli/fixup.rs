@@ -0,0 +1,216 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_time_arg;
+use crate::cli::day::ensure_unlocked;
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use clap::Parser;
+use itertools::Itertools;
+use log::error;
+use std::fmt::Write;
+use time::{Date, Duration, Time};
+
+#[derive(Parser)]
+pub struct CommandFixup {
+    /// Stop the forgotten activities at the given time of their day (HH:MM)
+    #[arg(long, value_parser = parse_time_arg, conflicts_with = "close_end_of_day")]
+    close_at: Option<Time>,
+    /// Stop the forgotten activities at the end of their day
+    #[arg(long)]
+    close_end_of_day: bool,
+}
+
+/// warning listing the activities of previous days which were never stopped
+pub fn stale_warning(stale: &[(Date, &Activity)]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Warning: {} activities of previous days were never stopped:",
+        stale.len()
+    );
+    for (date, activity) in stale {
+        let _ = writeln!(out, " - {}: {}", date, activity);
+    }
+    let _ = writeln!(
+        out,
+        "Stop them with: timetrax fixup --close-at HH:MM or timetrax fixup --close-end-of-day"
+    );
+    out
+}
+
+impl ExecutableCommand for CommandFixup {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let today = job_config.today();
+        let boundary = job_config.day_boundary;
+        let stale = manager.stale_open_activities(today);
+        if stale.is_empty() {
+            println!("No forgotten open activities.");
+            return Ok(());
+        }
+
+        let end = match self.close_at {
+            Some(end) => end,
+            None if self.close_end_of_day => {
+                // last second of the day in day time
+                local_time::from_day_time(Time::MIDNIGHT - Duration::SECOND, boundary)
+            }
+            None => {
+                print!("{}", stale_warning(&stale));
+                return Ok(());
+            }
+        };
+        if let Some((date, later)) = stale.iter().find(|(_, activity)| {
+            local_time::to_day_time(end, boundary)
+                < local_time::to_day_time(activity.time.start, boundary)
+        }) {
+            error!(
+                "The activity of {} starts after {}, choose a later time: {}",
+                date, end, later
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Activity starts after the given end",
+            )
+            .into());
+        }
+        for date in stale.iter().map(|(date, _)| *date).dedup() {
+            if let Some(day) = manager.day(date) {
+                ensure_unlocked(date, day)?;
+            }
+        }
+
+        let stopped = manager.close_stale_activities(today, end, boundary);
+        manager.save()?;
+        for (date, activity) in stopped {
+            println!("Stopped activity of {}: {}", date, activity);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Clock, ManualClock, TempDataDir, at};
+    use time::Month;
+
+    const STALE_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/stale_day.json"
+    ));
+
+    fn setup() -> (ManualClock, TempDataDir) {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(today, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", STALE_DAY).unwrap();
+        dir.write_day_file(
+            "2024-04-29.json",
+            &STALE_DAY.replace("2024-05-02", "2024-04-29"),
+        )
+        .unwrap();
+        (clock, dir)
+    }
+
+    fn stale_ends(dir: &TempDataDir) -> Vec<Option<Time>> {
+        ["2024-04-29", "2024-05-02"]
+            .map(|date| Date::parse(date, &*crate::data::BASIC_DATE_FORMAT).unwrap())
+            .iter()
+            .map(|date| dir.day(*date).unwrap().unwrap().activities[1].time.end)
+            .collect()
+    }
+
+    #[test]
+    fn test_stale_activities_of_all_previous_days_are_detected() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let today = clock.now().date();
+        let manager = dir.manager().unwrap();
+
+        let stale = manager.stale_open_activities(today);
+        assert_eq!(
+            stale.iter().map(|(date, _)| date.to_string()).collect_vec(),
+            ["2024-04-29", "2024-05-02"]
+        );
+        let warning = stale_warning(&stale);
+        assert!(
+            warning.starts_with("Warning: 2 activities of previous days were never stopped:\n")
+        );
+        assert!(warning.contains(" - 2024-04-29: "));
+        // the activities of today are not stale
+        let before_yesterday = manager.stale_open_activities(today.previous_day().unwrap());
+        assert!(!before_yesterday.is_empty());
+        assert!(
+            before_yesterday
+                .iter()
+                .all(|(date, _)| date.to_string() == "2024-04-29")
+        );
+
+        drop(manager);
+
+        // without a mode only the activities are listed
+        dir.execute(&CommandFixup::parse_from(["fixup"])).unwrap();
+        assert_eq!(stale_ends(&dir), [None, None]);
+    }
+
+    #[test]
+    fn test_fixup_close_at() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+
+        dir.execute(&CommandFixup::parse_from(["fixup", "--close-at", "17:30"]))
+            .unwrap();
+
+        let end = Some(Time::from_hms(17, 30, 0).unwrap());
+        assert_eq!(stale_ends(&dir), [end, end]);
+        assert!(
+            dir.manager()
+                .unwrap()
+                .stale_open_activities(clock.now().date())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_fixup_close_at_refuses_times_before_the_start() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+
+        let result = dir.execute(&CommandFixup::parse_from(["fixup", "--close-at", "12:30"]));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(stale_ends(&dir), [None, None]);
+
+        let mut manager = dir.manager().unwrap();
+        let closed = manager.close_stale_activities(
+            clock.now().date(),
+            Time::from_hms(12, 30, 0).unwrap(),
+            None,
+        );
+        assert!(closed.is_empty());
+    }
+
+    #[test]
+    fn test_fixup_close_end_of_day() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+
+        dir.execute(&CommandFixup::parse_from(["fixup", "--close-end-of-day"]))
+            .unwrap();
+
+        let end = Some(Time::from_hms(23, 59, 59).unwrap());
+        assert_eq!(stale_ends(&dir), [end, end]);
+        assert!(
+            CommandFixup::try_parse_from(["fixup", "--close-end-of-day", "--close-at", "18:00"])
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::format::format_duration_pretty;
+use crate::cli::show::{class_name, format_interval, format_time};
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::Manager;
+use clap::{ArgGroup, Parser};
+use log::error;
+use serde_json::json;
+use std::fmt::Write;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(group(ArgGroup::new("input").required(true).args(["file", "stdin"])))]
+pub struct CommandFold {
+    /// JSON file containing an array of activities, as stored in day files
+    file: Option<PathBuf>,
+    /// Read the array of activities from stdin
+    #[arg(long)]
+    stdin: bool,
+    /// Job config to fold with, defaults to the one of the data directory
+    #[arg(long, value_name = "FILE")]
+    job_config: Option<PathBuf>,
+    /// Print a JSON object instead of text
+    #[arg(long)]
+    json: bool,
+}
+
+fn invalid_input(message: String) -> std::io::Error {
+    error!("{}", message);
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// parse and check the activities, errors name the index of the offending activity
+fn parse_activities(job_config: &JobConfig, input: &str) -> std::io::Result<Vec<Activity>> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(input)
+        .map_err(|e| invalid_input(format!("Expected a JSON array of activities: {}", e)))?;
+
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let activity: Activity = serde_json::from_value(value)
+                .map_err(|e| invalid_input(format!("Activity {}: {}", index, e)))?;
+            if job_config.resolve_class(&activity.class).is_none() {
+                return Err(invalid_input(format!(
+                    "Activity {}: unknown class {}",
+                    index, activity.class
+                )));
+            }
+            if let Some(project) = activity
+                .projects
+                .iter()
+                .find(|project| job_config.resolve_project(*project).is_none())
+            {
+                return Err(invalid_input(format!(
+                    "Activity {}: unknown project {}",
+                    index, project
+                )));
+            }
+            if activity
+                .time
+                .to_day_time(job_config.day_boundary)
+                .is_inverted()
+            {
+                return Err(invalid_input(format!(
+                    "Activity {}: ends before it starts",
+                    index
+                )));
+            }
+            Ok(activity)
+        })
+        .collect()
+}
+
+impl CommandFold {
+    /// the job config given by `--job-config`, `loaded` otherwise
+    fn resolve_job_config(&self, loaded: &JobConfig) -> std::io::Result<JobConfig> {
+        let Some(path) = &self.job_config else {
+            return Ok(loaded.clone());
+        };
+        let reader = File::open(path).inspect_err(|e| {
+            error!("Failed to open {}: {}", path.display(), e);
+        })?;
+        let job_config: JobConfig = serde_json::from_reader(reader).map_err(|e| {
+            error!("Failed to parse job config at {}: {}", path.display(), e);
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        })?;
+        if let Err(e) = job_config.validate_target_shares() {
+            error!("Invalid job config at {}: {}", path.display(), e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+        }
+        Ok(job_config)
+    }
+
+    /// closure segments and per-class totals of the activities in `input`
+    fn render(&self, job_config: &JobConfig, input: &str) -> std::io::Result<String> {
+        let activities = parse_activities(job_config, input)?;
+        let boundary = job_config.day_boundary;
+        let (closure, _) =
+            Activity::calculate_activity_closure(job_config, &activities, None, None);
+        let totals = Activity::summarize_by_class(&closure);
+        let closure = closure
+            .into_iter()
+            .map(|mut segment| {
+                segment.time = segment.time.from_day_time(boundary);
+                segment
+            })
+            .collect::<Vec<_>>();
+
+        if self.json {
+            let segments = closure
+                .iter()
+                .map(|segment| {
+                    json!({
+                        "start": format_time(segment.time.start),
+                        "end": segment.time.end.map(format_time),
+                        "class": class_name(job_config, &segment.class),
+                        "name": segment.name,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let totals = totals
+                .iter()
+                .map(|(class, duration)| {
+                    (
+                        class_name(job_config, class),
+                        json!(duration.whole_seconds()),
+                    )
+                })
+                .collect::<serde_json::Map<_, _>>();
+            return Ok(json!({ "closure": segments, "totals": totals }).to_string());
+        }
+
+        let mut out = String::new();
+        let _ = writeln!(out, "Closure:");
+        for segment in &closure {
+            let _ = writeln!(
+                out,
+                " - {} {}: {}",
+                format_interval(&segment.time),
+                class_name(job_config, &segment.class),
+                segment.name.as_deref().unwrap_or("<NO DESCRIPTION>")
+            );
+        }
+        let _ = writeln!(out, "Totals:");
+        for (class, duration) in &totals {
+            let _ = writeln!(
+                out,
+                " - {}: {}",
+                class_name(job_config, class),
+                format_duration_pretty(duration, true)
+            );
+        }
+        Ok(out)
+    }
+}
+
+impl ExecutableCommand for CommandFold {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        job_config: &mut JobConfig,
+        _manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let job_config = self.resolve_job_config(job_config)?;
+        let mut input = String::new();
+        match &self.file {
+            Some(path) => {
+                File::open(path)
+                    .and_then(|mut file| file.read_to_string(&mut input))
+                    .inspect_err(|e| error!("Failed to read {}: {}", path.display(), e))?;
+            }
+            None => {
+                std::io::stdin().read_to_string(&mut input)?;
+            }
+        }
+
+        let output = self.render(&job_config, &input)?;
+        if self.json {
+            println!("{}", output);
+        } else {
+            print!("{}", output);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold(args: &[&str], input: &str) -> std::io::Result<String> {
+        CommandFold::parse_from(["fold", "--stdin"].iter().chain(args))
+            .render(&JobConfig::default(), input)
+    }
+
+    const INPUT: &str = r#"[
+        {"id": "00000000-0000-4000-8000-000000000001", "name": "office", "class": "@work",
+         "time": {"start": "09:00:00", "end": "12:00:00"}},
+        {"id": "00000000-0000-4000-8000-000000000002", "name": "lunch", "class": "@break",
+         "time": {"start": "11:30:00", "end": "12:00:00"}}
+    ]"#;
+
+    #[test]
+    fn test_fold_text() {
+        assert_eq!(
+            fold(&[], INPUT).unwrap(),
+            "Closure:\n \
+             - 09:00:00 - 11:30:00 work: office\n \
+             - 11:30:00 - 12:00:00 break: lunch; office\n\
+             Totals:\n \
+             - work: 2h 30m 0s\n \
+             - break: 30m 0s\n"
+        );
+    }
+
+    #[test]
+    fn test_fold_json() {
+        let output: serde_json::Value =
+            serde_json::from_str(&fold(&["--json"], INPUT).unwrap()).unwrap();
+        assert_eq!(output["closure"][1]["class"], "break");
+        assert_eq!(output["closure"][1]["start"], "11:30:00");
+        assert_eq!(output["totals"], json!({"work": 9000, "break": 1800}));
+    }
+
+    #[test]
+    fn test_fold_errors_cite_the_index() {
+        let error = |input: &str| fold(&[], input).unwrap_err().to_string();
+
+        assert!(error("{}").starts_with("Expected a JSON array of activities"));
+        assert!(
+            error(r#"[{"id": "00000000-0000-4000-8000-000000000001", "class": "@work"}]"#)
+                .starts_with("Activity 0: missing field `time`")
+        );
+        assert_eq!(
+            error(&INPUT.replace("@break", "@nap")),
+            "Activity 1: unknown class @nap"
+        );
+        assert_eq!(
+            error(&INPUT.replace("12:00:00\"}},", "08:00:00\"}},")),
+            "Activity 0: ends before it starts"
+        );
+    }
+}
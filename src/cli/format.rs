@@ -0,0 +1,251 @@
+use crate::cli::color::visible_width;
+use itertools::Itertools;
+use std::borrow::Borrow;
+use time::Duration;
+
+/// format a duration as `1h 2m 3s`, leading zero components are omitted
+pub fn format_duration_pretty<Q: Borrow<Duration>>(duration: Q, show_seconds: bool) -> String {
+    let duration = duration.borrow();
+
+    let sign = if duration.is_negative() { "-" } else { "" };
+
+    let hours = duration.whole_hours().abs();
+    let minutes = (duration.whole_minutes() % 60).abs();
+    let seconds = (duration.whole_seconds() % 60).abs();
+
+    let hours = if hours > 0 {
+        format!("{}h ", hours)
+    } else {
+        "".to_string()
+    };
+    let minutes = if minutes > 0 || !hours.is_empty() {
+        format!("{}m ", minutes)
+    } else {
+        "".to_string()
+    };
+    let seconds = if show_seconds && (seconds > 0 || !minutes.is_empty() || !hours.is_empty()) {
+        format!("{}s", seconds)
+    } else {
+        "".to_string()
+    };
+
+    let result = format!("{sign}{hours}{minutes}{seconds}");
+    let result = result.trim_end();
+
+    if result.is_empty() {
+        if show_seconds { "0s" } else { "0m" }.to_string()
+    } else {
+        result.to_string()
+    }
+}
+
+/// format the elapsed time of an open activity, negative durations are shown as zero
+pub fn format_elapsed<Q: Borrow<Duration>>(elapsed: Q) -> String {
+    let elapsed = elapsed.borrow();
+    if elapsed.is_negative() {
+        "0m (clock moved backwards?)".to_string()
+    } else {
+        format_duration_pretty(elapsed, false)
+    }
+}
+
+/// width of the terminal in columns, taken from `COLUMNS` and 80 if unknown
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|columns| *columns > 0)
+        .unwrap_or(80)
+}
+
+/// progress bar like `[██████░░░░] 62%` fitting into `width` columns
+///
+/// the bar is full from 100% on, the percentage keeps counting
+pub fn format_progress_bar(fraction: f64, width: usize) -> String {
+    let fraction = if fraction.is_finite() {
+        fraction.max(0.0)
+    } else {
+        0.0
+    };
+    // brackets, space and up to four characters of percentage
+    let cells = width.saturating_sub(8).max(10);
+    let filled = ((fraction.min(1.0) * cells as f64).round() as usize).min(cells);
+
+    format!(
+        "[{}{}] {:.0}%",
+        "█".repeat(filled),
+        "░".repeat(cells - filled),
+        fraction * 100.0
+    )
+}
+
+/// rows with left-aligned, padded columns separated by two spaces, trailing spaces removed
+pub fn format_table<R: AsRef<[String]>>(rows: &[R]) -> Vec<String> {
+    let columns = rows.iter().map(|row| row.as_ref().len()).max().unwrap_or(0);
+    let widths = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.as_ref().get(column))
+                .map(|cell| visible_width(cell.as_str()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    rows.iter()
+        .map(|row| {
+            row.as_ref()
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    // escape sequences of colors take no columns
+                    format!("{}{}", cell, " ".repeat(width - visible_width(cell)))
+                })
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// expand `{key}` placeholders with `value`, unknown keys expand to nothing
+///
+/// text in brackets like `[: {name}]` is dropped if a placeholder inside expands to nothing
+pub fn expand_template<F: Fn(&str) -> Option<String>>(template: &str, value: F) -> String {
+    let mut out = String::new();
+    // text of the open bracket group and whether it is dropped
+    let mut group: Option<(String, bool)> = None;
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                let key = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+                let expanded = value(&key).unwrap_or_default();
+                match &mut group {
+                    Some((text, dropped)) => {
+                        *dropped |= expanded.is_empty();
+                        text.push_str(&expanded);
+                    }
+                    None => out.push_str(&expanded),
+                }
+            }
+            '[' if group.is_none() => group = Some((String::new(), false)),
+            ']' if group.is_some() => {
+                if let Some((text, false)) = group.take() {
+                    out.push_str(&text);
+                }
+            }
+            c => match &mut group {
+                Some((text, _)) => text.push(c),
+                None => out.push(c),
+            },
+        }
+    }
+    // an unclosed group is kept as written
+    if let Some((text, _)) = group {
+        out.push('[');
+        out.push_str(&text);
+    }
+    out
+}
+
+/// tags as `#tag` separated by spaces
+pub fn format_tags(tags: &[String]) -> String {
+    tags.iter().map(|tag| format!("#{}", tag)).join(" ")
+}
+
+/// `text` broken into lines of at most `width` columns at spaces, each line starts with
+/// `indent` and ends with a newline, words longer than a line are kept whole
+pub fn wrap_text(text: &str, width: usize, indent: &str) -> String {
+    let available = width.saturating_sub(indent.chars().count()).max(1);
+    let mut out = String::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > available {
+                out.push_str(indent);
+                out.push_str(&line);
+                out.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        out.push_str(indent);
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration_pretty() {
+        let duration = Duration::hours(1) + Duration::minutes(2) + Duration::seconds(3);
+        assert_eq!(format_duration_pretty(duration, true), "1h 2m 3s");
+        assert_eq!(format_duration_pretty(duration, false), "1h 2m");
+        assert_eq!(format_duration_pretty(-duration, false), "-1h 2m");
+        assert_eq!(format_duration_pretty(Duration::minutes(5), true), "5m 0s");
+        assert_eq!(format_duration_pretty(Duration::ZERO, true), "0s");
+        assert_eq!(format_duration_pretty(Duration::ZERO, false), "0m");
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::minutes(72)), "1h 12m");
+        assert_eq!(
+            format_elapsed(Duration::minutes(-3)),
+            "0m (clock moved backwards?)"
+        );
+    }
+
+    #[test]
+    fn test_format_progress_bar() {
+        assert_eq!(format_progress_bar(0.625, 24), "[██████████░░░░░░] 62%");
+        assert_eq!(format_progress_bar(0.0, 24), "[░░░░░░░░░░░░░░░░] 0%");
+        assert_eq!(format_progress_bar(1.5, 24), "[████████████████] 150%");
+        // narrow terminals still get a readable bar
+        assert_eq!(format_progress_bar(0.5, 5).chars().count(), 16);
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let value = |key: &str| match key {
+            "class" => Some("work".to_string()),
+            "name" => Some("refactor parser".to_string()),
+            "none" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_template("> {class}[: {name}]!", value),
+            "> work: refactor parser!"
+        );
+        assert_eq!(expand_template("> {class}[: {none}]!", value), "> work!");
+        assert_eq!(expand_template("{unknown}[{class}", value), "[work");
+    }
+
+    #[test]
+    fn test_format_tags() {
+        assert_eq!(
+            format_tags(&["urgent".into(), "client".into()]),
+            "#urgent #client"
+        );
+        assert_eq!(format_tags(&[]), "");
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(
+            wrap_text("fix the parser for nested groups", 16, "  "),
+            "  fix the parser\n  for nested\n  groups\n"
+        );
+        assert_eq!(
+            wrap_text("first\nsecond extraordinarily", 10, ""),
+            "first\nsecond\nextraordinarily\n"
+        );
+    }
+}
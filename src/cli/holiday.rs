@@ -0,0 +1,288 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_date_arg;
+use crate::cli::day::ensure_unlocked;
+use crate::cli::format::format_duration_pretty;
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayType;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::public_holiday::parse_ics;
+use crate::data::report::day_quota;
+use crate::error::Error;
+use clap::Parser;
+use log::{error, info};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use time::{Date, Duration, Time};
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct CommandHoliday {
+    #[command(subcommand)]
+    action: Option<HolidayAction>,
+    /// Day of the holiday (YYYY-MM-DD), defaults to today
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+    /// Span the whole day (00:00-23:59) instead of the work quota of the day
+    #[arg(long)]
+    full_day: bool,
+    /// Mark the day even if it has activities already
+    #[arg(short, long)]
+    force: bool,
+}
+
+/// public holidays of the job config, their work quota counts as met without tracking anything
+#[derive(Parser)]
+pub enum HolidayAction {
+    /// Add the public holidays of an ICS calendar to the job config
+    ImportIcs {
+        /// Calendar of the public holidays, e.g. exported from a holiday website
+        file: PathBuf,
+    },
+    /// List the public holidays of the job config
+    #[clap(aliases = ["ls"])]
+    List,
+}
+
+impl HolidayAction {
+    fn execute(&self, job_config: &mut JobConfig) -> crate::error::Result<()> {
+        match self {
+            HolidayAction::ImportIcs { file } => {
+                let contents = std::fs::read_to_string(file).inspect_err(|e| {
+                    error!("Failed to read {}: {}", file.display(), e);
+                })?;
+                let holidays = parse_ics(&contents).map_err(|e| {
+                    error!("Invalid calendar {}: {}", file.display(), e);
+                    std::io::Error::from(e)
+                })?;
+                let count = holidays.len();
+                let added = job_config.add_holidays(holidays);
+                info!("Imported {} public holidays from {}", count, file.display());
+                println!(
+                    "Imported {} public holidays from {}, {} of them new",
+                    count,
+                    file.display(),
+                    added
+                );
+            }
+            HolidayAction::List => {
+                if job_config.holidays.is_empty() {
+                    println!(
+                        "There are no public holidays, import them with `timetrax holiday import-ics`"
+                    );
+                }
+                for holiday in &job_config.holidays {
+                    println!("{} {}", holiday.date, holiday.name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ExecutableCommand for CommandHoliday {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        if let Some(action) = &self.action {
+            return action.execute(job_config);
+        }
+        let now = local_time::now();
+        let date = self.date.unwrap_or(job_config.date_of(now));
+        let Some(class) = job_config.resolve_class(Identifier::ByName("holiday".into())) else {
+            error!("There is no holiday class, add it with `timetrax class add holiday`");
+            return Err(Error::UnknownClass(Identifier::ByName("holiday".into())));
+        };
+
+        let day = manager.day(date);
+        if let Some(day) = day {
+            ensure_unlocked(date, day)?;
+            if !day.activities.is_empty() && !self.force {
+                error!(
+                    "The day {} has {} activities already, use --force to mark it as holiday anyway",
+                    date,
+                    day.activities.len()
+                );
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    "Day has activities",
+                )
+                .into());
+            }
+        }
+
+        // spans the quota from the start of the day, in day time
+        let end = if self.full_day {
+            Time::from_hms(23, 59, 0).expect("valid time")
+        } else {
+            let quota = day_quota(job_config, date, day, config.daily_quotas());
+            if quota <= Duration::ZERO || quota >= Duration::days(1) {
+                error!(
+                    "The work quota of {} is {}, use --full-day to mark it as holiday anyway",
+                    date,
+                    format_duration_pretty(quota, false)
+                );
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "No work quota to span",
+                )
+                .into());
+            }
+            Time::MIDNIGHT + quota
+        };
+        let boundary = job_config.day_boundary;
+        let activity = Activity {
+            id: Uuid::new_v4(),
+            class: Identifier::Uuid(class.id),
+            name: None,
+            projects: vec![],
+            weights: BTreeMap::new(),
+            description: None,
+            created_at: Some(now),
+            modified_at: None,
+            tags: vec![],
+            evidence: vec![],
+            origin: None,
+            extra: Default::default(),
+            time: Interval {
+                start: local_time::from_day_time(Time::MIDNIGHT, boundary),
+                end: Some(local_time::from_day_time(end, boundary)),
+            },
+        };
+
+        info!("Marking {} as holiday: {:?}", date, activity);
+        let day = manager.get_or_create_day_mut(date);
+        day.activities.push(activity.clone());
+        day.day_type = DayType::Holiday;
+        manager.save()?;
+
+        Journal::new(config, manager.data_path()).append(&JournalEntry {
+            at: now,
+            command: "holiday".to_string(),
+            date,
+            removed: vec![],
+            added: vec![activity.clone()],
+        })?;
+        println!("Marked {} as holiday: {}", date, activity);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandLog;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::Month;
+
+    fn holiday(dir: &TempDataDir, args: &[&str]) -> crate::error::Result<()> {
+        dir.execute(&CommandHoliday::parse_from(["holiday"].iter().chain(args)))
+    }
+
+    fn may(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::May, day).unwrap()
+    }
+
+    #[test]
+    fn test_holiday_fills_the_quota() {
+        // Monday
+        let clock = ManualClock::new(at(may(6), Time::from_hms(12, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        holiday(&dir, &[]).unwrap();
+
+        let day = dir.day(may(6)).unwrap().unwrap();
+        assert_eq!(day.day_type, DayType::Holiday);
+        assert_eq!(day.activities.len(), 1);
+        assert_eq!(day.activities[0].time.start, Time::MIDNIGHT);
+        assert_eq!(
+            day.activities[0].time.end,
+            Some(Time::from_hms(8, 0, 0).unwrap())
+        );
+        let json = serde_json::to_value(&day).unwrap();
+        assert_eq!(json["day_type"], "holiday");
+    }
+
+    #[test]
+    fn test_import_public_holidays() {
+        let clock = ManualClock::new(at(may(6), Time::from_hms(12, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let file = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/holidays.ics");
+        holiday(&dir, &["import-ics", file]).unwrap();
+
+        // past and future holidays alike, in order of their dates
+        let mut job_config = dir.job_config().unwrap();
+        let dates = job_config
+            .holidays
+            .iter()
+            .map(|holiday| holiday.date.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dates,
+            [
+                "2023-12-25",
+                "2024-05-01",
+                "2024-05-09",
+                "2024-10-03",
+                "2024-12-24",
+                "2024-12-25",
+                "2030-01-01"
+            ]
+        );
+        assert_eq!(
+            job_config.public_holiday(may(9)).unwrap().name,
+            "Christi Himmelfahrt"
+        );
+        assert_eq!(job_config.add_holidays(job_config.holidays.clone()), 0);
+
+        // the quota of a public holiday is met already
+        let result = holiday(&dir, &["--date", "2024-05-09"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+        let missing = holiday(&dir, &["import-ics", "/nonexistent/holidays.ics"]);
+        assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        assert!(CommandHoliday::try_parse_from(["holiday", "--full-day", "list"]).is_err());
+    }
+
+    #[test]
+    fn test_holiday_refuses_days_with_activities() {
+        let clock = ManualClock::new(at(may(6), Time::from_hms(12, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandLog::parse_from([
+            "log", "-c", "@work", "--from", "09:00", "--to", "10:00",
+        ]))
+        .unwrap();
+
+        let result = holiday(&dir, &[]);
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::AlreadyExists
+        );
+        holiday(&dir, &["--force", "--full-day"]).unwrap();
+        let day = dir.day(may(6)).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 2);
+        assert_eq!(
+            day.activities[1].time.end,
+            Some(Time::from_hms(23, 59, 0).unwrap())
+        );
+
+        // there is no quota to span on weekends
+        let result = holiday(&dir, &["--date", "2024-05-04"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert!(dir.day(may(4)).unwrap().is_none());
+        holiday(&dir, &["--date", "2024-05-04", "--full-day"]).unwrap();
+    }
+}
@@ -0,0 +1,947 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{parse_date_arg, parse_identifier_arg, parse_time_arg};
+use crate::cli::mapping::{MappingOptions, Prompt, StdinPrompt, format_unmapped, load_mappings};
+use crate::data::activity::Activity;
+use crate::data::activity_class::{ActivityClass, ActivityClassInner};
+use crate::data::app_config::AppConfig;
+use crate::data::day_limit::DayLimit;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::mapping::{MappingKind, MappingStore};
+use crate::data::project::{Project, ProjectInner};
+use crate::data::text::normalize_name;
+use clap::{Args, Parser};
+use itertools::Itertools;
+use log::{error, info, warn};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+use time::Date;
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub enum CommandImport {
+    /// Activities from a CSV file with a header line, one row per activity
+    Csv(ImportCsvOptions),
+}
+
+#[derive(Args, Clone)]
+pub struct ImportCsvOptions {
+    /// CSV file to import
+    file: PathBuf,
+    /// Column holding the date (YYYY-MM-DD)
+    #[arg(long, default_value = "date")]
+    col_date: String,
+    /// Column holding the start time (HH:MM[:SS])
+    #[arg(long, default_value = "start")]
+    col_start: String,
+    /// Column holding the end time (HH:MM[:SS])
+    #[arg(long, default_value = "end")]
+    col_end: String,
+    /// Column holding the class name or id
+    #[arg(long, default_value = "class")]
+    col_class: String,
+    /// Column holding the projects separated by `;`, defaults to `projects` if present
+    #[arg(long)]
+    col_project: Option<String>,
+    /// Column holding the activity name, defaults to `name` if present
+    #[arg(long)]
+    col_name: Option<String>,
+    /// Create classes and projects missing from the job config instead of rejecting the rows
+    #[arg(long)]
+    create_missing: bool,
+    /// Only print what would be imported
+    #[arg(long)]
+    dry_run: bool,
+    /// Import even if the activities of a day would add up to more than a day
+    #[arg(short, long)]
+    force: bool,
+    #[command(flatten)]
+    mapping: MappingOptions,
+}
+
+/// record of a CSV file and the line it starts on
+#[derive(Debug, Clone, PartialEq)]
+struct Record {
+    line: usize,
+    fields: Vec<String>,
+}
+
+/// split CSV text into records, quoted fields may contain separators, quotes (as `""`)
+/// and line breaks. empty lines are skipped
+fn parse_records(text: &str) -> Result<Vec<Record>, String> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                field.push(c);
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                // empty lines are dropped along with their single empty field
+                if fields.len() > 1 || !fields[0].is_empty() {
+                    records.push(Record {
+                        line: start,
+                        fields: std::mem::take(&mut fields),
+                    });
+                }
+                fields.clear();
+                line += 1;
+                start = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!(
+            "quoted field starting on line {start} is never closed"
+        ));
+    }
+    fields.push(field);
+    if fields.len() > 1 || !fields[0].is_empty() {
+        records.push(Record {
+            line: start,
+            fields,
+        });
+    }
+    Ok(records)
+}
+
+/// positions of the mapped columns in the header
+struct Columns {
+    date: usize,
+    start: usize,
+    end: usize,
+    class: usize,
+    project: Option<usize>,
+    name: Option<usize>,
+}
+
+/// activity read from a row, ready to be added to its day
+#[derive(Debug, Clone)]
+struct ImportRow {
+    line: usize,
+    date: Date,
+    activity: Activity,
+}
+
+/// rows to import, the rows which could not be read and the job config including
+/// the classes and projects created for the rows
+struct ImportPlan {
+    rows: Vec<ImportRow>,
+    errors: Vec<(usize, String)>,
+    job_config: JobConfig,
+    created: Vec<String>,
+    /// mappings of the data directory including the answers given with `--interactive`
+    mappings: MappingStore,
+    /// names without a mapping of the `--system`
+    unmapped: BTreeSet<(MappingKind, String)>,
+}
+
+impl ImportCsvOptions {
+    fn columns(&self, header: &Record) -> std::io::Result<Columns> {
+        let find = |name: &str| header.fields.iter().position(|field| field.trim() == name);
+        let required = |name: &str| {
+            find(name).ok_or_else(|| {
+                error!("Column '{}' not found in the header of the CSV file", name);
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "Column not found")
+            })
+        };
+        let optional = |name: &Option<String>, default: &str| match name {
+            Some(name) => required(name).map(Some),
+            None => Ok(find(default)),
+        };
+
+        Ok(Columns {
+            date: required(&self.col_date)?,
+            start: required(&self.col_start)?,
+            end: required(&self.col_end)?,
+            class: required(&self.col_class)?,
+            project: optional(&self.col_project, "projects")?,
+            name: optional(&self.col_name, "name")?,
+        })
+    }
+
+    /// read the rows and resolve their classes and projects, creating missing ones
+    /// in the returned job config if `--create-missing` is given
+    ///
+    /// names are translated with the mappings of the `--system` first, unmapped names are
+    /// asked for with `prompt`
+    fn plan(
+        &self,
+        job_config: &JobConfig,
+        manager: &Manager,
+        mappings: MappingStore,
+        prompt: Option<&dyn Prompt>,
+        text: &str,
+    ) -> std::io::Result<ImportPlan> {
+        let records = parse_records(text).map_err(|e| {
+            error!("Failed to read CSV file {}: {}", self.file.display(), e);
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        })?;
+        let Some((header, records)) = records.split_first() else {
+            error!("The CSV file {} is empty", self.file.display());
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Empty CSV file",
+            ));
+        };
+        let columns = self.columns(header)?;
+
+        let mut plan = ImportPlan {
+            rows: Vec::new(),
+            errors: Vec::new(),
+            job_config: job_config.clone(),
+            created: Vec::new(),
+            mappings,
+            unmapped: BTreeSet::new(),
+        };
+        for record in records {
+            match self.read_row(&columns, record, &mut plan, manager, prompt) {
+                Ok(row) => plan.rows.push(row),
+                Err(message) => plan.errors.push((record.line, message)),
+            }
+        }
+        Ok(plan)
+    }
+
+    fn read_row(
+        &self,
+        columns: &Columns,
+        record: &Record,
+        plan: &mut ImportPlan,
+        manager: &Manager,
+        prompt: Option<&dyn Prompt>,
+    ) -> Result<ImportRow, String> {
+        let field = |index: usize| {
+            record
+                .fields
+                .get(index)
+                .map(|field| field.trim())
+                .unwrap_or_default()
+        };
+
+        let date = parse_date_arg(field(columns.date))?;
+        let start = parse_time_arg(field(columns.start))?;
+        let end = parse_time_arg(field(columns.end))?;
+        let time = Interval {
+            start,
+            end: Some(end),
+        };
+        if time.to_day_time(plan.job_config.day_boundary).is_inverted() {
+            return Err(format!("ends at {} before it starts at {}", end, start));
+        }
+        if manager.day(date).is_some_and(|day| day.locked) {
+            return Err(format!("the day {} is locked", date));
+        }
+
+        let class = self.resolve_class(field(columns.class), plan, prompt)?;
+        let projects = columns
+            .project
+            .map(field)
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|project| !project.is_empty())
+            .map(|project| self.resolve_project(project, plan, prompt))
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = match columns.name.map(field) {
+            Some(name) => normalize_name(name, manager.app_config().max_name_length)
+                .map_err(|e| format!("invalid name: {e}"))?,
+            None => None,
+        };
+
+        Ok(ImportRow {
+            line: record.line,
+            date,
+            activity: Activity {
+                id: Uuid::new_v4(),
+                name,
+                class,
+                time,
+                projects,
+                weights: BTreeMap::new(),
+                description: None,
+                created_at: Some(local_time::now()),
+                modified_at: None,
+                tags: vec![],
+                evidence: vec![],
+                origin: None,
+                extra: Default::default(),
+            },
+        })
+    }
+
+    /// class or project the name maps to in the `--system`, `None` if it is unmapped
+    ///
+    /// each unmapped name is asked for once unless it names a class or project of the job
+    /// config, valid answers are added to the mappings
+    fn mapped(
+        &self,
+        kind: MappingKind,
+        name: &str,
+        plan: &mut ImportPlan,
+        prompt: Option<&dyn Prompt>,
+    ) -> Result<Option<Uuid>, String> {
+        let Some(system) = &self.mapping.system else {
+            return Ok(None);
+        };
+        if let Some(id) = plan
+            .mappings
+            .to_internal(&plan.job_config, system, kind, name)
+        {
+            return Ok(Some(id));
+        }
+        if !plan.unmapped.insert((kind, name.to_string())) {
+            return Ok(None);
+        }
+        let local = parse_identifier_arg(name).is_ok_and(|identifier| match kind {
+            MappingKind::Class => plan.job_config.resolve_class(&identifier).is_some(),
+            MappingKind::Project => plan.job_config.resolve_project(&identifier).is_some(),
+        });
+        if local {
+            return Ok(None);
+        }
+        let Some(prompt) = prompt else {
+            return Ok(None);
+        };
+
+        let question = format!(
+            "The {} {} '{}' is not mapped, enter the {} to import it as",
+            system, kind, name, kind
+        );
+        let Some(answer) = prompt
+            .prompt(&question)
+            .map_err(|e| format!("failed to ask for a mapping: {e}"))?
+        else {
+            return Ok(None);
+        };
+        let internal = parse_identifier_arg(&answer)?;
+        match plan
+            .mappings
+            .add(&plan.job_config, system, kind, name, &internal)
+        {
+            Ok(mapping) => {
+                let id = mapping.resolve(&plan.job_config).map(|(id, _)| id);
+                plan.unmapped.remove(&(kind, name.to_string()));
+                Ok(id)
+            }
+            Err(e) => {
+                warn!("{}, the name stays unmapped", e);
+                Ok(None)
+            }
+        }
+    }
+
+    fn resolve_class(
+        &self,
+        class: &str,
+        plan: &mut ImportPlan,
+        prompt: Option<&dyn Prompt>,
+    ) -> Result<Identifier, String> {
+        if let Some(id) = self.mapped(MappingKind::Class, class, plan, prompt)? {
+            return Ok(Identifier::Uuid(id));
+        }
+        let identifier = parse_identifier_arg(class).map_err(|e| format!("invalid class: {e}"))?;
+        if let Some(class) = plan.job_config.resolve_class(&identifier) {
+            return Ok(Identifier::Uuid(class.id));
+        }
+        let Identifier::ByName(name) = identifier else {
+            return Err(format!("unknown class {}", class));
+        };
+        if !self.create_missing {
+            return Err(format!("unknown class {} (see --create-missing)", name));
+        }
+
+        let class = ActivityClass {
+            id: Uuid::new_v4(),
+            inner: ActivityClassInner {
+                name: name.clone(),
+                priority: plan.job_config.lowest_priority_class().inner.priority,
+                description: None,
+                target_share: None,
+                color: None,
+                exclusive: false,
+            },
+        };
+        let id = class.id;
+        plan.job_config.classes.push(class);
+        plan.created.push(format!("class {}", name));
+        Ok(Identifier::Uuid(id))
+    }
+
+    fn resolve_project(
+        &self,
+        project: &str,
+        plan: &mut ImportPlan,
+        prompt: Option<&dyn Prompt>,
+    ) -> Result<Identifier, String> {
+        if let Some(id) = self.mapped(MappingKind::Project, project, plan, prompt)? {
+            return Ok(Identifier::Uuid(id));
+        }
+        let identifier =
+            parse_identifier_arg(project).map_err(|e| format!("invalid project: {e}"))?;
+        if let Some(project) = plan.job_config.resolve_project(&identifier) {
+            return Ok(Identifier::Uuid(project.id));
+        }
+        let Identifier::ByName(name) = identifier else {
+            return Err(format!("unknown project {}", project));
+        };
+        if !self.create_missing {
+            return Err(format!("unknown project {} (see --create-missing)", name));
+        }
+
+        let project = Project {
+            id: Uuid::new_v4(),
+            inner: ProjectInner {
+                name: name.clone(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        };
+        let id = project.id;
+        plan.job_config.projects.push(project);
+        plan.created.push(format!("project {}", name));
+        Ok(Identifier::Uuid(id))
+    }
+
+    /// import the rows of `text`, nothing is imported if any row is malformed.
+    /// returns the summary as printed
+    fn import(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: &mut Manager,
+        prompt: Option<&dyn Prompt>,
+        text: &str,
+    ) -> crate::error::Result<String> {
+        let mappings = match &self.mapping.system {
+            Some(system) => load_mappings(config, job_config, manager, system)?,
+            None => MappingStore::default(),
+        };
+        let stored = mappings.mappings.len();
+        let plan = self.plan(job_config, manager, mappings, prompt, text)?;
+        // answers are kept even if the rows are rejected, they don't depend on them
+        if plan.mappings.mappings.len() != stored && !self.dry_run {
+            plan.mappings
+                .save(&MappingStore::path(config, manager.data_path()))?;
+        }
+        let unmapped = match &self.mapping.system {
+            Some(system) => format_unmapped(system, &plan.unmapped),
+            None => String::new(),
+        };
+        if !plan.errors.is_empty() {
+            for (line, message) in &plan.errors {
+                error!("Line {}: {}", line, message);
+            }
+            error!(
+                "Nothing imported, {} of {} rows are malformed",
+                plan.errors.len(),
+                plan.errors.len() + plan.rows.len()
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Malformed rows in CSV file",
+            )
+            .into());
+        }
+
+        let mut out = String::new();
+        let by_date = plan.rows.into_iter().into_group_map_by(|row| row.date);
+        let new_days = by_date
+            .keys()
+            .filter(|date| !manager.contains_day(**date))
+            .count();
+        let count = by_date.values().map(Vec::len).sum::<usize>();
+        let days = by_date.len();
+
+        let limit = DayLimit::new(config, &plan.job_config);
+        let mut over = 0;
+        for (date, rows) in by_date.iter().sorted_by_key(|(date, _)| **date) {
+            let mut day = manager.day(*date).cloned().unwrap_or_default();
+            day.activities
+                .extend(rows.iter().map(|row| row.activity.clone()));
+            if let Err(e) = limit.check(*date, &day) {
+                if self.force {
+                    warn!("{}, importing anyway", e);
+                } else {
+                    e.log();
+                    over += 1;
+                }
+            }
+        }
+        if over > 0 {
+            error!(
+                "Nothing imported, {} days would track more than they can, see --force",
+                over
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Days over their maximum length",
+            )
+            .into());
+        }
+
+        if self.dry_run {
+            for created in &plan.created {
+                let _ = writeln!(out, "Would create {}", created);
+            }
+            for (date, rows) in by_date.iter().sorted_by_key(|(date, _)| **date) {
+                for row in rows {
+                    let _ = writeln!(out, "{} (line {}): {}", date, row.line, row.activity);
+                }
+            }
+            let _ = writeln!(
+                out,
+                "Would import {} activities into {} days ({} new).",
+                count, days, new_days
+            );
+            out.push_str(&unmapped);
+            return Ok(out);
+        }
+
+        *job_config = plan.job_config;
+        if self.force {
+            manager.force_day_limit();
+        }
+        for created in &plan.created {
+            let _ = writeln!(out, "Created {}", created);
+        }
+        let mut entries = Vec::new();
+        for (date, rows) in by_date.into_iter().sorted_by_key(|(date, _)| *date) {
+            let added = rows.into_iter().map(|row| row.activity).collect::<Vec<_>>();
+            info!("Importing {} activities into {}", added.len(), date);
+            manager
+                .get_or_create_day_mut(date)
+                .activities
+                .extend(added.clone());
+            entries.push(JournalEntry {
+                at: local_time::now(),
+                command: "import".to_string(),
+                date,
+                removed: vec![],
+                added,
+            });
+        }
+        // nothing is journaled if the days cannot be saved
+        manager.save()?;
+        let journal = Journal::new(config, manager.data_path());
+        for entry in &entries {
+            journal.append(entry)?;
+        }
+        let _ = writeln!(
+            out,
+            "Imported {} activities into {} days ({} new).",
+            count, days, new_days
+        );
+        out.push_str(&unmapped);
+        Ok(out)
+    }
+}
+
+impl ExecutableCommand for CommandImport {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let CommandImport::Csv(options) = self;
+        let text = std::fs::read_to_string(&options.file).inspect_err(|e| {
+            error!("Failed to read {}: {}", options.file.display(), e);
+        })?;
+        let prompt = options
+            .mapping
+            .interactive
+            .then_some(&StdinPrompt as &dyn Prompt);
+        print!(
+            "{}",
+            options.import(config, job_config, &mut manager, prompt, &text)?
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Month, Time};
+
+    const EXISTING_DAY: &str = r#"{"date": "2024-05-02", "activities": [{"id": "00000000-0000-4000-8000-000000000001",
+        "name": "standup", "class": "@work", "time": {"start": "08:00:00", "end": "08:15:00"}}]}"#;
+
+    const SPREADSHEET: &str = "Day,From,To,Kind,Project,Comment\r\n\
+        2024-05-02,09:00,12:00,work,alpha,\"coding, mostly\"\r\n\
+        \r\n\
+        2024-05-03,9.30,10:00,work,alpha;beta,\r\n\
+        2024-05-03,12:00,12:30,lunch,,\"said \"\"hi\"\"\"\r\n";
+
+    fn options(args: &[&str]) -> ImportCsvOptions {
+        let CommandImport::Csv(options) = CommandImport::parse_from(
+            [
+                "import",
+                "csv",
+                "spreadsheet.csv",
+                "--col-date",
+                "Day",
+                "--col-start",
+                "From",
+                "--col-end",
+                "To",
+                "--col-class",
+                "Kind",
+                "--col-project",
+                "Project",
+                "--col-name",
+                "Comment",
+            ]
+            .iter()
+            .chain(args),
+        );
+        options
+    }
+
+    fn setup() -> (ManualClock, TempDataDir) {
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", EXISTING_DAY).unwrap();
+        (clock, dir)
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let records =
+            parse_records("a,b\r\n\"x, y\",\"multi\nline\"\n\n\"\"\"q\"\"\",\nlast").unwrap();
+
+        assert_eq!(
+            records,
+            [
+                Record {
+                    line: 1,
+                    fields: vec!["a".into(), "b".into()]
+                },
+                Record {
+                    line: 2,
+                    fields: vec!["x, y".into(), "multi\nline".into()]
+                },
+                Record {
+                    line: 5,
+                    fields: vec!["\"q\"".into(), "".into()]
+                },
+                Record {
+                    line: 6,
+                    fields: vec!["last".into()]
+                },
+            ]
+        );
+        assert!(parse_records("a,\"b\nc").is_err());
+    }
+
+    #[test]
+    fn test_import_creates_and_appends_days() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let file = dir.path().join("spreadsheet.csv");
+        std::fs::write(&file, SPREADSHEET).unwrap();
+
+        let command = CommandImport::parse_from([
+            "import",
+            "csv",
+            file.to_str().unwrap(),
+            "--col-date",
+            "Day",
+            "--col-start",
+            "From",
+            "--col-end",
+            "To",
+            "--col-class",
+            "Kind",
+            "--col-project",
+            "Project",
+            "--col-name",
+            "Comment",
+            "--create-missing",
+        ]);
+        dir.execute(&command).unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        let lunch = job_config
+            .resolve_class(Identifier::ByName("lunch".into()))
+            .unwrap();
+        assert_eq!(lunch.inner.priority, 0);
+        assert!(
+            job_config
+                .resolve_project(Identifier::ByName("alpha".into()))
+                .is_some()
+        );
+        assert!(
+            job_config
+                .resolve_project(Identifier::ByName("beta".into()))
+                .is_some()
+        );
+
+        let existing = dir
+            .day(Date::from_calendar_date(2024, Month::May, 2).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(existing.activities.len(), 2);
+        assert_eq!(
+            existing.activities[1].name.as_deref(),
+            Some("coding, mostly")
+        );
+        assert_eq!(
+            existing.activities[1].time.start,
+            Time::from_hms(9, 0, 0).unwrap()
+        );
+
+        let new = dir
+            .day(Date::from_calendar_date(2024, Month::May, 3).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(new.activities.len(), 2);
+        assert_eq!(
+            new.activities[0].time.start,
+            Time::from_hms(9, 30, 0).unwrap()
+        );
+        assert_eq!(new.activities[0].projects.len(), 2);
+        assert_eq!(new.activities[0].name, None);
+        assert_eq!(new.activities[1].class, Identifier::Uuid(lunch.id));
+        assert_eq!(new.activities[1].name.as_deref(), Some("said \"hi\""));
+
+        let journal = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(
+            journal
+                .iter()
+                .map(|entry| (entry.command.as_str(), entry.date.day(), entry.added.len()))
+                .collect::<Vec<_>>(),
+            [("import", 2, 1), ("import", 3, 2)]
+        );
+    }
+
+    #[test]
+    fn test_import_dry_run_changes_nothing() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let mut job_config = dir.job_config().unwrap();
+        let mut manager = dir.manager().unwrap();
+
+        let output = options(&["--create-missing", "--dry-run"])
+            .import(
+                dir.app_config(),
+                &mut job_config,
+                &mut manager,
+                None,
+                SPREADSHEET,
+            )
+            .unwrap();
+
+        assert!(output.starts_with(
+            "Would create project alpha\nWould create project beta\nWould create class lunch\n"
+        ));
+        assert!(output.contains("2024-05-03 (line 5): 12:00:00 - 12:30:00: said \"hi\"\n"));
+        assert!(output.ends_with("Would import 3 activities into 2 days (1 new).\n"));
+        assert!(!manager.is_dirty());
+        assert_eq!(manager.dates().count(), 1);
+        assert!(job_config.projects.is_empty());
+        assert_eq!(job_config.classes.len(), 3);
+    }
+
+    #[test]
+    fn test_import_reports_all_malformed_rows() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let job_config = dir.job_config().unwrap();
+        let text = "date,start,end,class\n\
+            2024-05-03,09:00,10:00,work\n\
+            2024-05-03,9h,10:00,work\n\
+            2024-05-03,11:00,10:00,work\n\
+            2024-05-03,11:00,12:00,meetings\n";
+        let CommandImport::Csv(options) = CommandImport::parse_from(["import", "csv", "data.csv"]);
+
+        let plan = options
+            .plan(
+                &job_config,
+                &dir.manager().unwrap(),
+                MappingStore::default(),
+                None,
+                text,
+            )
+            .unwrap();
+        assert_eq!(plan.rows.len(), 1);
+        assert_eq!(
+            plan.errors
+                .iter()
+                .map(|(line, _)| *line)
+                .collect::<Vec<_>>(),
+            [3, 4, 5]
+        );
+        assert!(plan.errors[0].1.contains("invalid time '9h'"));
+        assert!(plan.errors[2].1.contains("unknown class meetings"));
+
+        let mut job_config = job_config;
+        let mut manager = dir.manager().unwrap();
+        let error = options
+            .import(dir.app_config(), &mut job_config, &mut manager, None, text)
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(!manager.is_dirty());
+    }
+
+    #[test]
+    fn test_import_translates_mapped_names() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let mut job_config = dir.job_config().unwrap();
+        job_config.projects.push(Project {
+            id: Uuid::new_v4(),
+            inner: ProjectInner {
+                name: "acme-web".to_string(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        });
+        let acme = job_config.projects[0].id;
+        let path = MappingStore::path(dir.app_config(), dir.path());
+        let mut store = MappingStore::default();
+        store
+            .add(
+                &job_config,
+                "toggl",
+                MappingKind::Project,
+                "Acme – Website",
+                &Identifier::ByName("acme-web".into()),
+            )
+            .unwrap();
+        store.save(&path).unwrap();
+
+        let text = "date,start,end,class,projects\n\
+            2024-05-03,09:00,10:00,Billable,Acme – Website\n\
+            2024-05-03,10:00,11:00,Billable,Internal\n\
+            2024-05-03,11:00,12:00,work,\n";
+        let questions = std::cell::RefCell::new(Vec::new());
+        let prompt = |question: &str| {
+            questions.borrow_mut().push(question.to_string());
+            Ok(question.contains("'Billable'").then(|| "work".to_string()))
+        };
+        let CommandImport::Csv(options) = CommandImport::parse_from([
+            "import",
+            "csv",
+            "toggl.csv",
+            "--system",
+            "Toggl",
+            "--interactive",
+            "--create-missing",
+        ]);
+        let mut manager = dir.manager().unwrap();
+        let output = options
+            .import(
+                dir.app_config(),
+                &mut job_config,
+                &mut manager,
+                Some(&prompt),
+                text,
+            )
+            .unwrap();
+
+        // every unmapped name is asked for once, names of the job config import as they are
+        assert_eq!(
+            *questions.borrow(),
+            [
+                "The Toggl class 'Billable' is not mapped, enter the class to import it as",
+                "The Toggl project 'Internal' is not mapped, enter the project to import it as",
+            ]
+        );
+        assert!(output.ends_with(
+            "Names without a toggl mapping (see `timetrax mapping add`):\n  class work\n  project Internal\n"
+        ));
+
+        let work = job_config
+            .resolve_class(Identifier::ByName("work".into()))
+            .unwrap()
+            .id;
+        let internal = job_config
+            .resolve_project(Identifier::ByName("Internal".into()))
+            .unwrap()
+            .id;
+        let day = manager
+            .day(Date::from_calendar_date(2024, Month::May, 3).unwrap())
+            .unwrap();
+        assert!(
+            day.activities
+                .iter()
+                .all(|activity| activity.class == Identifier::Uuid(work))
+        );
+        assert_eq!(day.activities[0].projects, [Identifier::Uuid(acme)]);
+        assert_eq!(day.activities[1].projects, [Identifier::Uuid(internal)]);
+
+        // the answer is stored for the next run
+        let store = MappingStore::load(&path).unwrap();
+        assert_eq!(
+            store.to_internal(&job_config, "toggl", MappingKind::Class, "Billable"),
+            Some(work)
+        );
+        assert_eq!(store.mappings.len(), 2);
+    }
+
+    #[test]
+    fn test_import_refuses_days_over_a_day() {
+        let (clock, mut dir) = setup();
+        dir.app_config_mut().max_day_total = time::Duration::hours(20);
+        let _guard = clock.install();
+        let text = "date,start,end,class\n\
+            2024-05-03,00:00,23:00,work\n\
+            2024-05-03,09:00,11:00,work\n\
+            2024-05-04,00:00,23:59,work\n";
+        let CommandImport::Csv(options) = CommandImport::parse_from(["import", "csv", "data.csv"]);
+        let mut job_config = dir.job_config().unwrap();
+        let mut manager = dir.manager().unwrap();
+
+        let error = options
+            .import(dir.app_config(), &mut job_config, &mut manager, None, text)
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(!manager.is_dirty());
+
+        let CommandImport::Csv(options) =
+            CommandImport::parse_from(["import", "csv", "data.csv", "--force"]);
+        manager.set_day_limit(DayLimit::new(dir.app_config(), &job_config));
+        options
+            .import(dir.app_config(), &mut job_config, &mut manager, None, text)
+            .unwrap();
+        manager.save().unwrap();
+        drop(manager);
+        let day = dir
+            .day(Date::from_calendar_date(2024, Month::May, 3).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(day.activities.len(), 2);
+    }
+}
@@ -0,0 +1,319 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_date_arg;
+use crate::cli::format::{
+    format_duration_pretty, format_elapsed, format_table, format_tags, terminal_width, wrap_text,
+};
+use crate::cli::locale::Locale;
+use crate::cli::output::{OutputFormat, RenderOutput, render_text_or_json};
+use crate::cli::show::{class_name, format_time};
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use clap::Parser;
+use serde::Serialize;
+use std::fmt::Write;
+use time::{Date, OffsetDateTime};
+
+#[derive(Parser)]
+pub struct CommandList {
+    /// Day to list (YYYY-MM-DD), defaults to today
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+}
+
+fn project_names(job_config: &JobConfig, activity: &Activity) -> String {
+    activity
+        .projects
+        .iter()
+        .map(|project| {
+            job_config
+                .resolve_project(project)
+                .map(|project| project.inner.name.clone())
+                .unwrap_or_else(|| project.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl CommandList {
+    /// table of the raw activities of the day, the short ids are accepted by `edit` and `remove`
+    fn render(
+        &self,
+        locale: &Locale,
+        job_config: &JobConfig,
+        manager: &Manager,
+        now: OffsetDateTime,
+    ) -> std::io::Result<String> {
+        let today = job_config.date_of(now);
+        let date = self.date.unwrap_or(today);
+        let mut out = String::new();
+        let Some(day) = manager.day(date).filter(|day| !day.activities.is_empty()) else {
+            let _ = writeln!(out, "No activities on {}.", date);
+            return Ok(out);
+        };
+
+        let boundary = job_config.day_boundary;
+        let header = [
+            "ID", "START", "END", "DURATION", "CLASS", "NAME", "PROJECTS", "TAGS",
+        ];
+        let mut rows = vec![header.map(String::from).to_vec()];
+        for activity in &day.activities {
+            let time = activity.time.to_day_time(boundary);
+            let duration = match time.duration() {
+                Some(duration) => format_duration_pretty(duration, false),
+                // open activities of other days do not run until now
+                None if date == today => {
+                    format_elapsed(time.elapsed_at(local_time::to_day_time(now.time(), boundary)))
+                }
+                None => "?".to_string(),
+            };
+            rows.push(vec![
+                activity.short_id(),
+                format_time(activity.time.start),
+                activity
+                    .time
+                    .end
+                    .map(format_time)
+                    .unwrap_or_else(|| "<OPEN>".to_string()),
+                duration,
+                locale.paint_class(
+                    job_config,
+                    &activity.class,
+                    &class_name(job_config, &activity.class),
+                ),
+                activity.name.clone().unwrap_or_default(),
+                project_names(job_config, activity),
+                format_tags(&activity.tags),
+            ]);
+        }
+
+        let _ = writeln!(out, "Activities of {}:", date);
+        let mut lines = format_table(&rows).into_iter();
+        if let Some(header) = lines.next() {
+            let _ = writeln!(out, "{}", header);
+        }
+        for (line, activity) in lines.zip(&day.activities) {
+            let _ = writeln!(out, "{}", line);
+            // below the row, indented past the short id
+            if let Some(description) = activity.description.as_deref()
+                && !description.trim().is_empty()
+            {
+                out.push_str(&wrap_text(description, terminal_width(), "        "));
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// result of `timetrax list`
+#[derive(Serialize, Debug)]
+pub struct ListOutput {
+    pub date: Date,
+    /// raw activities of the day
+    pub activities: Vec<Activity>,
+    /// the table as printed
+    #[serde(skip)]
+    pub text: String,
+}
+
+impl RenderOutput for ListOutput {
+    fn render(&self, out: &mut impl std::io::Write, format: OutputFormat) -> std::io::Result<()> {
+        render_text_or_json(out, format, &self.text, self)
+    }
+}
+
+impl ExecutableCommand for CommandList {
+    type Error = crate::error::Error;
+    type Output = ListOutput;
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let locale = Locale::new(config);
+        let now = local_time::now();
+        let date = self.date.unwrap_or(job_config.date_of(now));
+        Ok(ListOutput {
+            date,
+            activities: manager
+                .day(date)
+                .map(|day| day.activities.clone())
+                .unwrap_or_default(),
+            text: self.render(&locale, job_config, &manager, now)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::color::visible_width;
+    use crate::data::identifier::Identifier;
+    use crate::testing::{TempDataDir, at};
+    use itertools::Itertools;
+    use time::{Month, Time};
+
+    const FOLD_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/fold_day.json"
+    ));
+
+    fn setup() -> (OffsetDateTime, TempDataDir) {
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let now = at(date, Time::from_hms(15, 0, 0).unwrap());
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", FOLD_DAY).unwrap();
+        (now, dir)
+    }
+
+    fn list_with(
+        config: &AppConfig,
+        dir: &TempDataDir,
+        now: OffsetDateTime,
+        args: &[&str],
+    ) -> String {
+        CommandList::parse_from(["list"].iter().chain(args))
+            .render(
+                &Locale::new(config),
+                &dir.job_config().unwrap(),
+                &dir.manager().unwrap(),
+                now,
+            )
+            .unwrap()
+    }
+
+    fn list(dir: &TempDataDir, now: OffsetDateTime, args: &[&str]) -> String {
+        list_with(&AppConfig::default(), dir, now, args)
+    }
+
+    #[test]
+    fn test_list_table() {
+        let (now, dir) = setup();
+
+        assert_eq!(
+            list(&dir, now, &[]),
+            "Activities of 2024-05-02:\n\
+             ID      START     END       DURATION  CLASS  NAME                   PROJECTS  TAGS\n\
+             cyfsdn  09:00:00  18:00:00  9h 0m     work   Working at the office\n\
+             gmzyqv  12:00:00  13:00:00  1h 0m     break  Lunch break\n\
+             qzsxpm  10:00:00  11:00:00  1h 0m     work   Project meeting                  #meeting\n\
+             hmlfok  10:30:00  11:30:00  1h 0m     work   Project meeting 2\n\
+             ozhwwo  13:00:00  <OPEN>    2h 0m     work   Project meeting 3\n"
+        );
+        // open activities of past days do not run until now
+        let tomorrow = now + time::Duration::days(1);
+        assert!(
+            list(&dir, tomorrow, &["--date", "2024-05-02"])
+                .ends_with("ozhwwo  13:00:00  <OPEN>    ?         work   Project meeting 3\n")
+        );
+        assert_eq!(
+            list(&dir, now, &["--date", "2024-05-03"]),
+            "No activities on 2024-05-03.\n"
+        );
+    }
+
+    #[test]
+    fn test_list_shows_descriptions_below_the_row() {
+        let (now, dir) = setup();
+        let mut manager = dir.manager().unwrap();
+        let day = manager.get_or_create_day_mut(now.date());
+        day.activities[4].description = Some("Second pass over the parser".into());
+        day.activities[4].tags = vec!["review".into(), "parser".into()];
+        manager.save().unwrap();
+        drop(manager);
+
+        let output = list(&dir, now, &[]);
+        assert!(
+            output.ends_with(
+                "Project meeting 3                #review #parser\n        Second pass over the parser\n"
+            ),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_list_reads_without_the_lock() {
+        let (now, dir) = setup();
+        let expected = list(&dir, now, &[]);
+
+        // another instance holds the lock
+        let _held = dir.manager().unwrap();
+        let output = CommandList::parse_from(["list"])
+            .render(
+                &Locale::new(&AppConfig::default()),
+                &dir.job_config().unwrap(),
+                &Manager::open_read_only(dir.app_config(), dir.path()).unwrap(),
+                now,
+            )
+            .unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_list_short_ids_are_references() {
+        let (now, dir) = setup();
+        let day = dir.manager().unwrap().day(now.date()).unwrap().clone();
+
+        let ids = list(&dir, now, &[])
+            .lines()
+            .skip(2)
+            .map(|line| line.split_whitespace().next().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(ids.len(), day.activities.len());
+        assert!(ids.iter().all_unique());
+        for (id, activity) in ids.iter().zip(&day.activities) {
+            assert_eq!(
+                day.find_activity_by_reference(&id.parse().unwrap())
+                    .unwrap()
+                    .id,
+                activity.id
+            );
+        }
+    }
+
+    #[test]
+    fn test_list_colors_the_class_column() {
+        let (now, dir) = setup();
+        let mut job_config = dir.job_config().unwrap();
+        job_config
+            .resolve_class_mut(Identifier::ByName("work".into()))
+            .unwrap()
+            .inner
+            .color = Some("green".into());
+        let colored = AppConfig {
+            color: true,
+            ..AppConfig::default()
+        };
+        let render = |config: &AppConfig| {
+            CommandList::parse_from(["list"])
+                .render(
+                    &Locale::new(config),
+                    &job_config,
+                    &dir.manager().unwrap(),
+                    now,
+                )
+                .unwrap()
+        };
+
+        let plain = render(&AppConfig::default());
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(plain, list(&dir, now, &[]));
+
+        let output = render(&colored);
+        assert!(output.contains("\x1b[32mwork\x1b[0m"));
+        assert!(!output.contains("\x1b[32mbreak"));
+        // the columns stay aligned
+        assert_eq!(
+            output.lines().map(visible_width).collect::<Vec<_>>(),
+            plain
+                .lines()
+                .map(|line| line.chars().count())
+                .collect::<Vec<_>>()
+        );
+        // classes without a color are not colored even if colors are on
+        assert!(!list_with(&colored, &dir, now, &[]).contains('\x1b'));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::cli::color::Color;
+use crate::data::activity::Activity;
+use crate::data::app_config::{AppConfig, ClockFormat};
+use crate::data::blocker::Blocker;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use crate::data::report::week_start;
+use log::error;
+use std::str::FromStr;
+use time::format_description::{self, OwnedFormatItem};
+use time::{Date, Month, Time, Weekday};
+
+/// display settings for human readable output
+///
+/// data files and machine readable output stay ISO 8601 / 24h
+pub struct Locale<'a> {
+    config: &'a AppConfig,
+    time_format: OwnedFormatItem,
+    short_time_format: OwnedFormatItem,
+}
+
+impl<'a> Locale<'a> {
+    pub fn new(config: &'a AppConfig) -> Self {
+        let (time_format, short_time_format) = match config.clock {
+            ClockFormat::TwentyFourHour => (
+                "[hour padding:zero]:[minute padding:zero]:[second padding:zero]",
+                "[hour padding:zero]:[minute padding:zero]",
+            ),
+            ClockFormat::TwelveHour => (
+                "[hour repr:12 padding:none]:[minute padding:zero]:[second padding:zero] [period]",
+                "[hour repr:12 padding:none]:[minute padding:zero] [period]",
+            ),
+        };
+
+        Self {
+            config,
+            time_format: format_description::parse_owned::<2>(time_format).unwrap(),
+            short_time_format: format_description::parse_owned::<2>(short_time_format).unwrap(),
+        }
+    }
+
+    /// `text` in `color`, unchanged unless colors are enabled
+    pub fn paint(&self, color: Color, text: &str) -> String {
+        if self.config.color {
+            color.paint(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// `text` in the color of the class, unchanged if the class has no valid color
+    pub fn paint_class(&self, job_config: &JobConfig, class: &Identifier, text: &str) -> String {
+        match job_config
+            .resolve_class(class)
+            .and_then(|class| class.inner.color.as_deref())
+            .and_then(|color| Color::from_str(color).ok())
+        {
+            Some(color) => self.paint(color, text),
+            None => text.to_string(),
+        }
+    }
+
+    pub fn weekday_name(&self, weekday: Weekday) -> &str {
+        &self.config.weekday_names[weekday.number_days_from_monday() as usize]
+    }
+
+    pub fn month_name(&self, month: Month) -> &str {
+        &self.config.month_names[month as usize - 1]
+    }
+
+    /// weekdays in display order, starting with the configured first day of the week
+    pub fn week_days(&self) -> [Weekday; 7] {
+        let mut day = self.config.week_start;
+        [(); 7].map(|_| {
+            let current = day;
+            day = day.next();
+            current
+        })
+    }
+
+    /// first day of the week containing the date
+    pub fn week_start_of(&self, date: Date) -> Date {
+        week_start(date, self.config.week_start)
+    }
+
+    /// e.g. `Friday, 3 May 2024`
+    pub fn format_date(&self, date: Date) -> String {
+        format!(
+            "{}, {} {} {}",
+            self.weekday_name(date.weekday()),
+            date.day(),
+            self.month_name(date.month()),
+            date.year()
+        )
+    }
+
+    fn format_with(&self, time: Time, format: &OwnedFormatItem) -> String {
+        time.format(format).unwrap_or_else(|e| {
+            error!("Unable to format time: {e}. Report this as an issue.");
+            "<INVALID>".to_string()
+        })
+    }
+
+    /// time of day including seconds
+    pub fn format_time(&self, time: Time) -> String {
+        self.format_with(time, &self.time_format)
+    }
+
+    /// time of day without seconds
+    pub fn format_time_short(&self, time: Time) -> String {
+        self.format_with(time, &self.short_time_format)
+    }
+
+    pub fn format_interval(&self, interval: &Interval) -> String {
+        format!(
+            "{} - {}",
+            self.format_time(interval.start),
+            interval
+                .end
+                .map(|end| self.format_time(end))
+                .unwrap_or_else(|| "<OPEN>".to_string())
+        )
+    }
+
+    /// same layout as the `Display` implementation of activities
+    pub fn format_activity(&self, activity: &Activity) -> String {
+        format!(
+            "{}: {}",
+            self.format_interval(&activity.time),
+            activity.name.as_deref().unwrap_or("<NO DESCRIPTION>")
+        )
+    }
+
+    /// same layout as the `Display` implementation of blockers
+    pub fn format_blocker(&self, blocker: &Blocker) -> String {
+        format!(
+            "{}: {}",
+            self.format_interval(&blocker.time),
+            blocker.name.as_deref().unwrap_or("<NO DESCRIPTION>")
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use time::Month;
+
+    pub(crate) fn german_config() -> AppConfig {
+        AppConfig {
+            week_start: Weekday::Monday,
+            weekday_names: [
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+                "Sonntag",
+            ]
+            .map(String::from),
+            month_names: [
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ]
+            .map(String::from),
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_twelve_hour_clock() {
+        let config = AppConfig {
+            clock: ClockFormat::TwelveHour,
+            ..AppConfig::default()
+        };
+        let locale = Locale::new(&config);
+
+        assert_eq!(
+            locale.format_time(Time::from_hms(0, 5, 0).unwrap()),
+            "12:05:00 AM"
+        );
+        assert_eq!(
+            locale.format_time_short(Time::from_hms(13, 30, 0).unwrap()),
+            "1:30 PM"
+        );
+
+        let config = AppConfig::default();
+        let locale = Locale::new(&config);
+        assert_eq!(
+            locale.format_time(Time::from_hms(13, 30, 0).unwrap()),
+            "13:30:00"
+        );
+    }
+
+    #[test]
+    fn test_german_names() {
+        let config = german_config();
+        let locale = Locale::new(&config);
+        let date = Date::from_calendar_date(2024, Month::March, 1).unwrap();
+
+        assert_eq!(locale.format_date(date), "Freitag, 1 März 2024");
+    }
+
+    #[test]
+    fn test_week_start() {
+        let config = AppConfig {
+            week_start: Weekday::Sunday,
+            ..AppConfig::default()
+        };
+        let locale = Locale::new(&config);
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+
+        assert_eq!(locale.week_days()[0], Weekday::Sunday);
+        assert_eq!(locale.week_days()[6], Weekday::Saturday);
+        assert_eq!(
+            locale.week_start_of(date),
+            Date::from_calendar_date(2024, Month::April, 28).unwrap()
+        );
+    }
+}
@@ -0,0 +1,371 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{
+    DateArg, parse_duration_arg, parse_identifier_arg, parse_relative_date_arg, parse_time_arg,
+};
+use crate::cli::day::ensure_unlocked;
+use crate::cli::format::format_duration_pretty;
+use crate::data::activity::{Activity, ActivityOrigin};
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::text::normalize_name;
+use crate::error::Error;
+use clap::{ArgGroup, Parser};
+use log::{error, info, warn};
+use time::{Duration, Time};
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(group(ArgGroup::new("end").required(true).multiple(false)))]
+pub struct CommandLog {
+    /// Classification of the activity
+    #[arg(short, long, value_parser = parse_identifier_arg)]
+    class: Identifier,
+    /// Start of the activity (HH:MM)
+    #[arg(long, value_parser = parse_time_arg)]
+    from: Time,
+    /// End of the activity (HH:MM)
+    #[arg(long, value_parser = parse_time_arg, group = "end")]
+    to: Option<Time>,
+    /// Length of the activity instead of its end, e.g. 45m or 1h30m
+    #[arg(long, value_parser = parse_duration_arg, group = "end", value_name = "DURATION")]
+    duration: Option<Duration>,
+    /// Day of the activity (YYYY-MM-DD, or e.g. yesterday, monday, -3d), defaults to today
+    #[arg(long, value_parser = parse_relative_date_arg, allow_hyphen_values = true)]
+    date: Option<DateArg>,
+    /// Project worked on, may be given multiple times
+    #[arg(short, long, value_parser = parse_identifier_arg)]
+    project: Vec<Identifier>,
+    /// Short name for the activity
+    #[arg(short, long)]
+    name: Option<String>,
+    /// Tag the activity, may be given multiple times
+    #[arg(short, long)]
+    tag: Vec<String>,
+    /// Refuse to log the activity if it overlaps another activity of the same class
+    #[arg(long)]
+    no_overlap: bool,
+}
+
+impl CommandLog {
+    /// the logged time, validated to end after it starts and within the day
+    fn interval(&self, boundary: Option<Time>) -> std::io::Result<Interval> {
+        let start = local_time::to_day_time(self.from, boundary);
+        let end = match (self.to, self.duration) {
+            (Some(to), _) => local_time::to_day_time(to, boundary),
+            (None, Some(duration)) => {
+                let Some(end) = local_time::add_within_day(self.from, duration, boundary) else {
+                    error!(
+                        "An activity of {} starting at {} would last into the next day",
+                        format_duration_pretty(duration, false),
+                        self.from
+                    );
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Activity ends on the next day",
+                    ));
+                };
+                local_time::to_day_time(end, boundary)
+            }
+            (None, None) => unreachable!("clap requires --to or --duration"),
+        };
+        if start >= end {
+            error!(
+                "The activity must start before it ends: {} >= {}",
+                self.from,
+                local_time::from_day_time(end, boundary)
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Activity starts after it ends",
+            ));
+        }
+        Ok(Interval {
+            start: self.from,
+            end: Some(local_time::from_day_time(end, boundary)),
+        })
+    }
+}
+
+/// activities of the day of the same class as `class` overlapping `interval`
+fn overlapping<'a>(
+    job_config: &JobConfig,
+    day: &'a DayInner,
+    class: &Identifier,
+    interval: &Interval,
+) -> Vec<&'a Activity> {
+    let boundary = job_config.day_boundary;
+    let class = job_config.resolve_class(class).map(|class| class.id);
+    let interval = interval.to_day_time(boundary);
+    day.activities
+        .iter()
+        .filter(|activity| job_config.resolve_class(&activity.class).map(|c| c.id) == class)
+        .filter(|activity| {
+            let time = activity.time.to_day_time(boundary);
+            time.start < interval.end_time_or_end_of_day()
+                && time.end_time_or_end_of_day() > interval.start
+        })
+        .collect()
+}
+
+impl ExecutableCommand for CommandLog {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let now = local_time::now();
+        let today = job_config.date_of(now);
+        let date = self.date.map_or(today, |date| date.resolve(today));
+        let interval = self.interval(job_config.day_boundary)?;
+
+        if job_config.resolve_class(&self.class).is_none() {
+            error!("Failed to resolve classification: {:?}", self.class);
+            return Err(Error::UnknownClass(self.class.clone()));
+        }
+        for project in &self.project {
+            if job_config.resolve_project(project).is_none() {
+                error!("Failed to resolve project: {:?}", project);
+                return Err(Error::UnknownProject(project.clone()));
+            }
+        }
+        let name = match &self.name {
+            Some(name) => normalize_name(name, config.max_name_length)
+                .inspect_err(|e| error!("Invalid name '{}': {}", name, e))?,
+            None => None,
+        };
+
+        let day = manager.get_or_create_day_ref(date);
+        ensure_unlocked(date, day)?;
+        let overlapping = overlapping(job_config, day, &self.class, &interval);
+        if !overlapping.is_empty() {
+            for other in &overlapping {
+                warn!("The logged activity overlaps {}", other);
+            }
+            if self.no_overlap {
+                error!("Refusing to log an overlapping activity because of --no-overlap");
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Activity overlaps another activity of the same class",
+                )
+                .into());
+            }
+        }
+
+        let activity = Activity {
+            id: Uuid::new_v4(),
+            class: self.class.clone(),
+            name,
+            projects: self.project.clone(),
+            weights: Default::default(),
+            description: None,
+            created_at: Some(now),
+            modified_at: None,
+            tags: self.tag.clone(),
+            evidence: vec![],
+            origin: Some(ActivityOrigin::Explicit),
+            extra: Default::default(),
+            time: interval,
+        };
+        info!("Logging activity: {:?}", activity);
+        manager
+            .get_or_create_day_mut(date)
+            .activities
+            .push(activity.clone());
+        // nothing is journaled or reported if the day cannot be saved
+        manager.save()?;
+        Journal::new(config, manager.data_path()).append(&JournalEntry {
+            at: now,
+            command: "log".to_string(),
+            date,
+            removed: vec![],
+            added: vec![activity.clone()],
+        })?;
+        println!("Logged activity: {}", activity);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::day_limit::DayLimit;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Date, Duration, Month};
+
+    fn time(h: u8, m: u8) -> Time {
+        Time::from_hms(h, m, 0).unwrap()
+    }
+
+    fn parse(args: &[&str]) -> Result<CommandLog, clap::Error> {
+        CommandLog::try_parse_from(["log", "-c", "@work"].iter().chain(args))
+    }
+
+    fn setup() -> (ManualClock, TempDataDir, Date) {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, time(18, 0)));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        (clock, dir, date)
+    }
+
+    #[test]
+    fn test_log_end_from_duration() {
+        let log = parse(&["--from", "09:15", "--duration", "1h30m"]).unwrap();
+        let interval = log.interval(None).unwrap();
+        assert_eq!(interval.start, time(9, 15));
+        assert_eq!(interval.end, Some(time(10, 45)));
+
+        // durations are counted within the day starting at the boundary
+        let log = parse(&["--from", "23:00", "--duration", "2h"]).unwrap();
+        let interval = log.interval(Some(time(4, 0))).unwrap();
+        assert_eq!(interval.end, Some(time(1, 0)));
+        let error = log.interval(None).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_log_validates_arguments() {
+        let log = parse(&["--from", "10:00", "--to", "09:00"]).unwrap();
+        assert_eq!(
+            log.interval(None).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        let log = parse(&["--from", "10:00", "--to", "10:00"]).unwrap();
+        assert!(log.interval(None).is_err());
+
+        // exactly one of --to and --duration
+        assert!(parse(&["--from", "10:00"]).is_err());
+        assert!(parse(&["--from", "10:00", "--to", "11:00", "--duration", "1h"]).is_err());
+
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        let result = dir.execute(
+            &CommandLog::try_parse_from(["log", "-c", "@nope", "--from", "09:00", "--to", "10:00"])
+                .unwrap(),
+        );
+        assert!(result.is_err());
+        let result =
+            dir.execute(&parse(&["--from", "09:00", "--to", "10:00", "-p", "@nope"]).unwrap());
+        assert!(result.is_err());
+        assert!(
+            dir.day(date)
+                .unwrap()
+                .is_none_or(|day| day.activities.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_log_inserts_completed_activity() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        let yesterday = date.previous_day().unwrap();
+
+        dir.execute(
+            &parse(&[
+                "--from",
+                "09:00",
+                "--to",
+                "12:00",
+                "--date",
+                "2024-05-02",
+                "-n",
+                " coding  ",
+                "-t",
+                "focus",
+            ])
+            .unwrap(),
+        )
+        .unwrap();
+        let day = dir.day(yesterday).unwrap().unwrap();
+        let activity = &day.activities[0];
+        assert_eq!(activity.time.start, time(9, 0));
+        assert_eq!(activity.time.end, Some(time(12, 0)));
+        assert_eq!(activity.name.as_deref(), Some("coding"));
+        assert_eq!(activity.tags, ["focus"]);
+        assert_eq!(activity.origin, Some(ActivityOrigin::Explicit));
+        assert!(dir.day(date).unwrap().is_none());
+
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(entries[0].command, "log");
+        assert_eq!(entries[0].date, yesterday);
+    }
+
+    #[test]
+    fn test_log_refused_by_the_day_limit_is_not_journaled() {
+        let (clock, mut dir, date) = setup();
+        dir.app_config_mut().max_day_total = Duration::hours(2);
+        let _guard = clock.install();
+        let mut job_config = dir.job_config().unwrap();
+        let mut manager = dir.manager().unwrap();
+        manager.set_day_limit(DayLimit::new(dir.app_config(), &job_config));
+
+        let result = parse(&["--from", "09:00", "--to", "12:00"])
+            .unwrap()
+            .execute(dir.app_config(), &mut job_config, manager);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        assert!(dir.day(date).unwrap().is_none());
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn test_log_overlap() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        dir.execute(&parse(&["--from", "09:00", "--to", "12:00"]).unwrap())
+            .unwrap();
+
+        // other classes never overlap
+        dir.execute(
+            &CommandLog::try_parse_from([
+                "log",
+                "-c",
+                "@break",
+                "--from",
+                "11:00",
+                "--to",
+                "13:00",
+                "--no-overlap",
+            ])
+            .unwrap(),
+        )
+        .unwrap();
+        let result =
+            dir.execute(&parse(&["--from", "11:00", "--to", "13:00", "--no-overlap"]).unwrap());
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(dir.day(date).unwrap().unwrap().activities.len(), 2);
+
+        // adjacent activities do not overlap, overlapping ones are logged with a warning
+        dir.execute(&parse(&["--from", "12:00", "--to", "13:00", "--no-overlap"]).unwrap())
+            .unwrap();
+        dir.execute(&parse(&["--from", "11:00", "--to", "13:00"]).unwrap())
+            .unwrap();
+        assert_eq!(dir.day(date).unwrap().unwrap().activities.len(), 4);
+    }
+
+    #[test]
+    fn test_log_relative_date() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        for relative in ["yesterday", "-1d", "thursday"] {
+            dir.execute(&parse(&["--from", "09:00", "--to", "10:00", "--date", relative]).unwrap())
+                .unwrap();
+        }
+        assert!(dir.day(date).unwrap().is_none());
+        let yesterday = dir.day(date.previous_day().unwrap()).unwrap().unwrap();
+        assert_eq!(yesterday.activities.len(), 3);
+        assert!(parse(&["--from", "09:00", "--to", "10:00", "--date", "someday"]).is_err());
+    }
+}
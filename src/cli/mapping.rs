@@ -0,0 +1,259 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_identifier_arg;
+use crate::data::app_config::AppConfig;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::Manager;
+use crate::data::mapping::{Mapping, MappingKind, MappingStore, normalize_system};
+use clap::{ArgGroup, Args, Parser};
+use itertools::Itertools;
+use log::{error, warn};
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+
+#[derive(Parser)]
+pub enum CommandMapping {
+    /// Map a name of another system to a class or project,
+    /// e.g. `mapping add --system toggl --external "Acme – Website" --project @acme-web`
+    #[clap(aliases = ["new", "set"])]
+    #[command(group(ArgGroup::new("internal").required(true).args(["class", "project"])))]
+    Add {
+        /// Name of the other system, e.g. toggl, clockify or jira
+        #[arg(short, long)]
+        system: String,
+        /// Name of the class or project in the other system
+        #[arg(short, long)]
+        external: String,
+        /// Class the name stands for
+        #[arg(short, long, value_parser = parse_identifier_arg)]
+        class: Option<Identifier>,
+        /// Project the name stands for
+        #[arg(short, long, value_parser = parse_identifier_arg)]
+        project: Option<Identifier>,
+    },
+    /// List the mappings, stale ones point at removed classes or projects
+    #[clap(aliases = ["ls", "show"])]
+    List {
+        /// Only list the mappings of this system
+        #[arg(short, long)]
+        system: Option<String>,
+    },
+    /// Delete the mappings of an external name
+    #[clap(aliases = ["delete", "del", "rm"])]
+    Remove {
+        #[arg(short, long)]
+        system: String,
+        #[arg(short, long)]
+        external: String,
+    },
+}
+
+/// translation options shared by the import and export commands
+#[derive(Args, Clone, Default)]
+pub struct MappingOptions {
+    /// Translate class and project names with the mappings of this system (see `timetrax mapping`)
+    #[arg(long)]
+    pub system: Option<String>,
+    /// Ask for the translation of unmapped names and store the answers
+    #[arg(long, requires = "system")]
+    pub interactive: bool,
+}
+
+/// asks the user for a line of text, `None` if the question is skipped
+pub trait Prompt {
+    fn prompt(&self, question: &str) -> std::io::Result<Option<String>>;
+}
+
+impl<F: Fn(&str) -> std::io::Result<Option<String>>> Prompt for F {
+    fn prompt(&self, question: &str) -> std::io::Result<Option<String>> {
+        self(question)
+    }
+}
+
+/// asks on stderr, exports written to stdout stay intact, and reads the answer from stdin
+pub struct StdinPrompt;
+
+impl Prompt for StdinPrompt {
+    fn prompt(&self, question: &str) -> std::io::Result<Option<String>> {
+        eprint!("{} (empty to skip): ", question);
+        std::io::stderr().flush()?;
+
+        let mut answer = String::new();
+        if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+            eprintln!();
+            return Ok(None);
+        }
+        let answer = answer.trim();
+        Ok((!answer.is_empty()).then(|| answer.to_string()))
+    }
+}
+
+/// mappings of the data directory, with a warning about stale mappings of the system
+pub fn load_mappings(
+    config: &AppConfig,
+    job_config: &JobConfig,
+    manager: &Manager,
+    system: &str,
+) -> std::io::Result<MappingStore> {
+    let store = MappingStore::load(&MappingStore::path(config, manager.data_path()))?;
+    let system = normalize_system(system);
+    for mapping in store.stale(job_config) {
+        if mapping.system == system {
+            warn!(
+                "Ignoring the {} mapping of '{}', the {} {} no longer exists",
+                mapping.system, mapping.external, mapping.kind, mapping.internal
+            );
+        }
+    }
+    Ok(store)
+}
+
+/// summary of the names without a mapping, printed at the end of imports and exports
+pub fn format_unmapped(system: &str, unmapped: &BTreeSet<(MappingKind, String)>) -> String {
+    let mut out = String::new();
+    if unmapped.is_empty() {
+        return out;
+    }
+    let _ = writeln!(
+        out,
+        "Names without a {} mapping (see `timetrax mapping add`):",
+        normalize_system(system)
+    );
+    for (kind, name) in unmapped {
+        let _ = writeln!(out, "  {} {}", kind, name);
+    }
+    out
+}
+
+fn format_mapping(job_config: &JobConfig, mapping: &Mapping) -> String {
+    let internal = match mapping.resolve(job_config) {
+        Some((_, name)) => name.to_string(),
+        None => format!("{} (stale, removed from the job config)", mapping.internal),
+    };
+    format!(
+        "{}: {} '{}' -> {}",
+        mapping.system, mapping.kind, mapping.external, internal
+    )
+}
+
+impl ExecutableCommand for CommandMapping {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let path = MappingStore::path(config, manager.data_path());
+        let mut store = MappingStore::load(&path)?;
+
+        match self {
+            CommandMapping::Add {
+                system,
+                external,
+                class,
+                project,
+            } => {
+                let (kind, internal) = match (class, project) {
+                    (Some(class), _) => (MappingKind::Class, class),
+                    (None, Some(project)) => (MappingKind::Project, project),
+                    (None, None) => unreachable!("required by the argument group"),
+                };
+                let mapping = store
+                    .add(job_config, system, kind, external, internal)
+                    .inspect_err(|e| error!("{}", e))?;
+                println!("Added mapping {}", format_mapping(job_config, mapping));
+                store.save(&path)?;
+            }
+            CommandMapping::List { system } => {
+                let system = system.as_deref().map(normalize_system);
+                let mappings = store
+                    .mappings
+                    .iter()
+                    .filter(|mapping| system.as_ref().is_none_or(|s| mapping.system == *s))
+                    .sorted_by(|a, b| {
+                        (&a.system, a.kind, &a.external).cmp(&(&b.system, b.kind, &b.external))
+                    })
+                    .collect_vec();
+                if mappings.is_empty() {
+                    println!("No mappings found");
+                    return Ok(());
+                }
+                println!("Mappings:");
+                for mapping in mappings {
+                    println!(" - {}", format_mapping(job_config, mapping));
+                }
+            }
+            CommandMapping::Remove { system, external } => {
+                let removed = store.remove(system, None, external);
+                if removed.is_empty() {
+                    error!("No {} mapping of '{}' found", system, external);
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "Mapping not found",
+                    )
+                    .into());
+                }
+                for mapping in &removed {
+                    println!("Removed mapping {}", format_mapping(job_config, mapping));
+                }
+                store.save(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDataDir;
+
+    fn mapping(dir: &TempDataDir, args: &[&str]) -> crate::error::Result<()> {
+        dir.execute(&CommandMapping::parse_from(["mapping"].iter().chain(args)))
+    }
+
+    #[test]
+    fn test_mapping_add_list_remove() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let stored = || MappingStore::load(&MappingStore::path(dir.app_config(), dir.path()));
+        let add = ["add", "--system", "Toggl", "--external", "Billable"];
+        mapping(&dir, &[&add[..], &["--class", "@work"]].concat()).unwrap();
+
+        let store = stored().unwrap();
+        assert_eq!(store.mappings.len(), 1);
+        assert_eq!(store.mappings[0].system, "toggl");
+        assert_eq!(store.mappings[0].kind, MappingKind::Class);
+        assert!(matches!(store.mappings[0].internal, Identifier::Uuid(_)));
+
+        let result = mapping(&dir, &[&add[..], &["--class", "@break"]].concat());
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::AlreadyExists
+        );
+        let result = mapping(&dir, &[&add[..], &["--project", "@missing"]].concat());
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        assert!(CommandMapping::try_parse_from(["mapping"].iter().chain(&add)).is_err());
+        mapping(&dir, &["list", "--system", "toggl"]).unwrap();
+
+        mapping(&dir, &["remove", "-s", "toggl", "-e", "Billable"]).unwrap();
+        assert!(stored().unwrap().mappings.is_empty());
+        let result = mapping(&dir, &["remove", "-s", "toggl", "-e", "Billable"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_format_unmapped() {
+        let unmapped = BTreeSet::from([
+            (MappingKind::Project, "beta".to_string()),
+            (MappingKind::Class, "lunch".to_string()),
+        ]);
+        assert_eq!(
+            format_unmapped("Toggl", &unmapped),
+            "Names without a toggl mapping (see `timetrax mapping add`):\n  class lunch\n  project beta\n"
+        );
+        assert_eq!(format_unmapped("toggl", &BTreeSet::new()), "");
+    }
+}
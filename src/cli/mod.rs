@@ -1,26 +1,98 @@
+use crate::cli::args::parse_datetime_arg;
+use crate::cli::output::{CommandOutput, OutputFormat, RenderOutput};
 use crate::data::app_config::AppConfig;
 use crate::data::job_config::JobConfig;
 use crate::data::manager::Manager;
 use clap::Parser;
+use log::error;
 use std::path::PathBuf;
+use time::OffsetDateTime;
 
+pub mod args;
+mod blocker;
+mod break_time;
+mod cancel;
+mod check;
 mod class;
+pub mod color;
+mod compact;
 mod completion;
+mod config;
+mod day;
+mod describe;
+mod diff_data;
+mod doctor;
+mod edit;
+pub mod editor;
+mod evidence;
+mod export;
+mod fixup;
+mod fold;
+pub mod format;
+mod holiday;
+mod import;
+mod list;
+pub mod locale;
+mod log_activity;
+mod mapping;
+mod nightly;
+mod notify;
+pub mod output;
 mod pop;
 mod project;
 mod push;
+mod query;
+mod quota;
+mod reconcile;
+mod remove;
+mod repair;
+mod report;
+mod restore;
+mod show;
 mod status;
+mod trash;
 
+pub use blocker::*;
+pub use break_time::*;
+pub use cancel::*;
+pub use check::*;
 pub use class::*;
+pub use compact::*;
 pub use completion::*;
+pub use config::*;
+pub use day::*;
+pub use describe::*;
+pub use diff_data::*;
+pub use doctor::*;
+pub use edit::*;
+pub use evidence::*;
+pub use export::*;
+pub use fixup::*;
+pub use fold::*;
+pub use holiday::*;
+pub use import::*;
+pub use list::*;
+pub use log_activity::*;
+pub use mapping::*;
+pub use nightly::*;
+pub use notify::*;
 pub use pop::*;
 pub use project::*;
 pub use push::*;
+pub use query::*;
+pub use quota::*;
+pub use reconcile::*;
+pub use remove::*;
+pub use repair::*;
+pub use report::*;
+pub use restore::*;
+pub use show::*;
 pub use status::*;
+pub use trash::*;
 
 pub trait ExecutableCommand {
     type Error;
-    type Output;
+    type Output: RenderOutput;
     fn execute(
         &self,
         config: &AppConfig,
@@ -35,12 +107,46 @@ pub struct AppArgs {
     #[command(subcommand)]
     pub command: Option<Command>,
 
-    /// Path to the folder to which time tracking data will be saved
+    /// Path to the folder to which time tracking data will be saved,
+    /// overrides TIMETRAX_DATA_PATH and the config file
     #[arg(short, long)]
     pub data_path: Option<PathBuf>,
-    // /// App configuration file. If not provided, default config will be used
-    // #[arg(short, long)]
-    // pub config: Option<PathBuf>,
+
+    /// Pretend the current time is the given one (YYYY-MM-DD HH:MM[:SS] or RFC 3339)
+    #[arg(long, global = true, value_parser = parse_datetime_arg)]
+    pub now: Option<OffsetDateTime>,
+
+    /// Print a timing breakdown to stderr, also enabled by TIMETRAX_PROFILE=1
+    #[arg(long, global = true)]
+    pub profile: bool,
+
+    /// Save days even if their activities add up to more than a day
+    #[arg(long, global = true)]
+    pub force_save: bool,
+
+    /// Print plain text without colors, also disabled by NO_COLOR or if stdout is not a terminal
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Format of the output of status, list and report
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub format: OutputFormat,
+
+    /// Wait up to this many seconds for other instances to release the data directory,
+    /// 10 if given without a value, fails right away if omitted
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_value_t = 0,
+        default_missing_value = "10"
+    )]
+    pub wait: u64,
+    /// App configuration file, defaults to $XDG_CONFIG_HOME/timetrax/config.json
+    /// or ~/.config/timetrax/config.json, which may be missing
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
 }
 
 #[derive(Parser)]
@@ -51,18 +157,225 @@ pub enum Command {
     /// Pop the most recent activity from the stack
     #[clap(aliases = ["po"])]
     Pop(CommandPop),
+    /// Discard an open activity pushed by mistake, without recording it
+    Cancel(CommandCancel),
+    /// Record an already completed activity retroactively
+    Log(CommandLog),
+    /// Insert a completed break covering a past time window
+    #[clap(aliases = ["b", "pause"])]
+    Break(CommandBreak),
+    /// Mark a day as holiday with an activity spanning its work quota, or manage public holidays
+    #[clap(aliases = ["vacation"])]
+    Holiday(CommandHoliday),
+    /// Manage blockers, fixed amounts of time such as doctor appointments
+    #[command(subcommand, aliases = ["blockers", "block", "bl"])]
+    Blocker(CommandBlocker),
     /// Status of current activities
     #[clap(aliases = ["s", "st", "stat", "info", "i", "display"])]
     Status(CommandStatus),
+    /// Summarize tracked time per class and project
+    #[clap(aliases = ["r", "rep", "summary"])]
+    Report(CommandReport),
+    /// Export tracked activities for further processing
+    #[command(subcommand)]
+    Export(CommandExport),
+    /// Import activities from other tools
+    #[command(subcommand)]
+    Import(CommandImport),
+    /// Translate class and project names to and from other systems, e.g. Toggl or JIRA
+    #[command(subcommand, aliases = ["mappings", "map"])]
+    Mapping(CommandMapping),
+    /// Answer questions about tracked time using a small query language
+    #[clap(aliases = ["q"])]
+    Query(CommandQuery),
+    /// Edit the long description of an activity in $EDITOR (or from stdin)
+    #[clap(aliases = ["desc"])]
+    Describe(CommandDescribe),
+    /// Attach context, e.g. the file being edited, to an activity
+    #[clap(aliases = ["ev"])]
+    Evidence(CommandEvidence),
+    /// List the activities of a day with the short ids accepted by edit and remove
+    #[clap(aliases = ["ls"])]
+    List(CommandList),
+    /// Change the name, class, times, projects or description of a recorded activity
+    #[clap(aliases = ["amend"])]
+    Edit(CommandEdit),
+    /// Delete a recorded activity
+    #[clap(aliases = ["rm", "delete"])]
+    Remove(CommandRemove),
+    /// List and restore deleted activities
+    #[command(subcommand, aliases = ["bin"])]
+    Trash(CommandTrash),
+    /// List the backups of a day file and restore one of them
+    Restore(CommandRestore),
+    /// Merge adjacent fragments of the same activity
+    Compact(CommandCompact),
+    /// Walk through the issues of a day and fix them
+    Reconcile(CommandReconcile),
+    /// Find activities of previous days which were never stopped and stop them
+    Fixup(CommandFixup),
+    /// Fold an ad-hoc array of activities into a closure with per-class totals, without
+    /// touching the data directory
+    Fold(CommandFold),
+    /// Print the stored records and the closure of a day, for debugging and scripting
+    #[clap(aliases = ["dump"])]
+    Show(CommandShow),
+    /// Compare the data with an older copy, e.g. a backup, listing changed activities and totals
+    DiffData(CommandDiffData),
+    /// Lock or unlock days against modifications
+    #[command(subcommand)]
+    Day(CommandDay),
     /// Manage projects
     #[command(subcommand, aliases = ["projects", "proj", "prj", "p"])]
     Project(CommandProject),
     /// Manage activity classes
     #[command(subcommand, aliases = ["classes", "cls", "c", "ac"])]
     Class(CommandClass),
+    /// Manage time quotas of activity classes
+    #[command(subcommand, aliases = ["quotas"])]
+    Quota(CommandQuota),
+    /// Show and change the app config, export and import the job config
+    #[command(subcommand, aliases = ["cfg"])]
+    Config(CommandConfig),
+    /// Show a desktop notification when the quota is reached or a break is due, run periodically
+    NotifyCheck(CommandNotifyCheck),
+    /// Check the data directory for problems
+    #[clap(aliases = ["check-data"])]
+    Doctor(CommandDoctor),
+    /// Check day files for the integrity problems `doctor` lists, by severity and failing on
+    /// errors, e.g. from cron
+    Check(CommandCheck),
+    /// Show the day files which cannot be parsed, quarantine them or salvage their activities
+    Repair(CommandRepair),
+    /// Report the problems of the previous day as Markdown, meant to run nightly from cron
+    Nightly(CommandNightly),
     /// Generate shell competition scripts
     #[command(aliases = ["complete", "autocomplete", "shell", "completions"])]
     Completion(CommandCompletion),
+    /// Print completion candidates for the last of the given words
+    #[command(name = "__complete", hide = true)]
+    Complete(CommandComplete),
+}
+
+/// positionals completed with classes, projects, activities or dates by `__complete`,
+/// tests check the entries against the definitions above
+pub const POSITIONAL_COMPLETIONS: &[PositionalCompletion] = &[
+    PositionalCompletion {
+        path: &["describe"],
+        index: 0,
+        kind: CompletionKind::Activity,
+    },
+    PositionalCompletion {
+        path: &["edit"],
+        index: 0,
+        kind: CompletionKind::Activity,
+    },
+    PositionalCompletion {
+        path: &["remove"],
+        index: 0,
+        kind: CompletionKind::Activity,
+    },
+    PositionalCompletion {
+        path: &["pop"],
+        index: 0,
+        kind: CompletionKind::Activity,
+    },
+    PositionalCompletion {
+        path: &["evidence"],
+        index: 0,
+        kind: CompletionKind::Activity,
+    },
+    PositionalCompletion {
+        path: &["restore"],
+        index: 0,
+        kind: CompletionKind::Date,
+    },
+    PositionalCompletion {
+        path: &["day", "lock"],
+        index: 0,
+        kind: CompletionKind::Date,
+    },
+    PositionalCompletion {
+        path: &["day", "unlock"],
+        index: 0,
+        kind: CompletionKind::Date,
+    },
+    PositionalCompletion {
+        path: &["project", "remove"],
+        index: 0,
+        kind: CompletionKind::Project,
+    },
+    PositionalCompletion {
+        path: &["project", "edit"],
+        index: 0,
+        kind: CompletionKind::Project,
+    },
+    PositionalCompletion {
+        path: &["project", "use"],
+        index: 0,
+        kind: CompletionKind::Project,
+    },
+    PositionalCompletion {
+        path: &["class", "remove"],
+        index: 0,
+        kind: CompletionKind::Class,
+    },
+    PositionalCompletion {
+        path: &["class", "edit"],
+        index: 0,
+        kind: CompletionKind::Class,
+    },
+    PositionalCompletion {
+        path: &["quota", "set"],
+        index: 0,
+        kind: CompletionKind::Class,
+    },
+    PositionalCompletion {
+        path: &["quota", "remove"],
+        index: 0,
+        kind: CompletionKind::Class,
+    },
+];
+
+impl Command {
+    /// the command never modifies the data directory, so it runs without locking it
+    pub fn is_read_only(&self) -> bool {
+        match self {
+            Command::Export(export) => !export.writes_mappings(),
+            _ => matches!(
+                self,
+                Command::Status(_)
+                    | Command::Report(_)
+                    | Command::List(_)
+                    | Command::Query(_)
+                    | Command::Show(_)
+                    | Command::Check(_)
+            ),
+        }
+    }
+
+    /// error if the output of the command cannot be written as `format`, `--format json`
+    /// is global but only status, list and report return data
+    pub fn check_format(&self, format: OutputFormat) -> crate::error::Result<()> {
+        let supported = match (self, format) {
+            (_, OutputFormat::Text) => true,
+            (Command::Status(status), OutputFormat::Json) => status.supports_json(),
+            (Command::List(_) | Command::Report(_), OutputFormat::Json) => true,
+            _ => false,
+        };
+        if !supported {
+            error!(
+                "--format json is only available for status of a single day without --short, \
+                 --gaps, --verbose, --class or the terminal title, list and report"
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Output format not supported by the command",
+            )
+            .into());
+        }
+        Ok(())
+    }
 }
 
 impl Default for Command {
@@ -72,8 +385,8 @@ impl Default for Command {
 }
 
 impl ExecutableCommand for Command {
-    type Error = std::io::Error;
-    type Output = ();
+    type Error = crate::error::Error;
+    type Output = CommandOutput;
     fn execute(
         &self,
         config: &AppConfig,
@@ -81,12 +394,44 @@ impl ExecutableCommand for Command {
         manager: Manager,
     ) -> Result<Self::Output, Self::Error> {
         match self {
-            Command::Push(cmd) => cmd.execute(config, job_config, manager),
-            Command::Pop(cmd) => cmd.execute(config, job_config, manager),
-            Command::Status(cmd) => cmd.execute(config, job_config, manager),
-            Command::Project(cmd) => cmd.execute(config, job_config, manager),
-            Command::Class(cmd) => cmd.execute(config, job_config, manager),
-            Command::Completion(cmd) => cmd.execute(config, job_config, manager),
+            Command::Push(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Pop(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Cancel(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Log(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Break(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Holiday(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Blocker(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Status(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Report(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Export(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Import(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Mapping(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Query(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Describe(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Evidence(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::List(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Edit(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Remove(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Trash(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Restore(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Compact(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Reconcile(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Fixup(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Fold(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Show(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::DiffData(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Day(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Project(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Class(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Quota(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Config(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::NotifyCheck(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Doctor(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Check(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Repair(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Nightly(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Completion(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
+            Command::Complete(cmd) => cmd.execute(config, job_config, manager).map(Into::into),
         }
     }
 }
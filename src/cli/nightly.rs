@@ -0,0 +1,182 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::doctor::{Finding, check_day, render_findings};
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::reconcile::{self, Fix, ReconcilePolicy};
+use clap::Parser;
+use log::{error, info, trace};
+use std::path::PathBuf;
+use time::{Date, Duration, OffsetDateTime};
+
+#[derive(Parser)]
+pub struct CommandNightly {
+    /// Directory to write the Markdown report into, printed to stdout if omitted
+    #[arg(long, value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+}
+
+/// gaps, overlapping fragments and suspiciously long records, using the defaults of `reconcile`
+fn reconcile_findings(date: Date, day: &DayInner, boundary: Option<time::Time>) -> Vec<Finding> {
+    let policy = ReconcilePolicy {
+        min_gap: Duration::minutes(15),
+        max_duration: Duration::hours(10),
+        break_class: Identifier::ByName("break".to_string()),
+        day_over: true,
+        boundary,
+    };
+    reconcile::propose(day, &policy)
+        .into_iter()
+        // unterminated activities are already reported as stale
+        .filter(|proposal| !matches!(proposal.fix, Fix::Close { .. }))
+        .map(|proposal| Finding {
+            date,
+            message: format!("{}, proposed fix: {}", proposal.issue, proposal.fix),
+            fix: format!("timetrax reconcile --date {date}"),
+        })
+        .collect()
+}
+
+impl CommandNightly {
+    /// the previous effective day and its report, `None` if nothing was found
+    pub fn render(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        manager: &Manager,
+        now: OffsetDateTime,
+    ) -> (Date, Option<String>) {
+        let today = job_config.date_of(now);
+        let date = today.previous_day().unwrap_or(today);
+        let Some(day) = manager.annotated_day(date) else {
+            return (date, None);
+        };
+
+        let boundary = job_config.day_boundary;
+        let mut findings = check_day(config, job_config, date, day, today);
+        findings.extend(reconcile_findings(date, day.inner(), boundary));
+        if findings.is_empty() {
+            return (date, None);
+        }
+
+        let report = format!(
+            "# timetrax: anomalies of {}\n\n{}",
+            date,
+            render_findings(&findings)
+        );
+        (date, Some(report))
+    }
+}
+
+impl ExecutableCommand for CommandNightly {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let (date, report) = self.render(config, job_config, &manager, local_time::now());
+        let Some(report) = report else {
+            trace!("No anomalies on {}", date);
+            return Ok(());
+        };
+
+        let Some(dir) = &self.output_dir else {
+            print!("{}", report);
+            return Ok(());
+        };
+        let path = dir.join(format!("anomalies-{}.md", date));
+        std::fs::create_dir_all(dir)
+            .and_then(|_| std::fs::write(&path, report))
+            .inspect_err(|e| error!("Failed to write report to {}: {}", path.display(), e))?;
+        info!("Wrote anomaly report to {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TempDataDir, at};
+    use time::{Month, Time};
+
+    const ANOMALY_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/anomaly_day.json"
+    ));
+    // golden report of the fixture day, containing one anomaly of each kind
+    const ANOMALY_REPORT: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/anomaly_report.md"
+    ));
+
+    fn setup(job_config: &JobConfig) -> TempDataDir {
+        let dir = TempDataDir::new(job_config).unwrap();
+        dir.write_day_file("2024-05-02.json", ANOMALY_DAY).unwrap();
+        dir
+    }
+
+    fn nightly(dir: &TempDataDir, now: OffsetDateTime) -> (Date, Option<String>) {
+        let manager = Manager::open_read_only(dir.app_config(), dir.path()).unwrap();
+        CommandNightly::parse_from(["nightly"]).render(
+            dir.app_config(),
+            &dir.job_config().unwrap(),
+            &manager,
+            now,
+        )
+    }
+
+    fn may(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::May, day).unwrap()
+    }
+
+    #[test]
+    fn test_nightly_report() {
+        let dir = setup(&JobConfig::default());
+        let (date, report) = nightly(&dir, at(may(3), Time::from_hms(2, 0, 0).unwrap()));
+        let report = report
+            .unwrap()
+            .replace(&dir.path().display().to_string(), "<DATA>");
+
+        assert_eq!(date, may(2));
+        assert_eq!(report, ANOMALY_REPORT);
+    }
+
+    #[test]
+    fn test_nightly_honors_the_day_boundary() {
+        let job_config = JobConfig {
+            day_boundary: Some(Time::from_hms(4, 0, 0).unwrap()),
+            ..JobConfig::default()
+        };
+        let dir = setup(&job_config);
+
+        // 02:00 still belongs to the 2nd, whose previous day has no records
+        let (date, report) = nightly(&dir, at(may(3), Time::from_hms(2, 0, 0).unwrap()));
+        assert_eq!(date, may(1));
+        assert_eq!(report, None);
+
+        let (date, report) = nightly(&dir, at(may(3), Time::from_hms(5, 0, 0).unwrap()));
+        assert_eq!(date, may(2));
+        assert!(report.is_some());
+    }
+
+    #[test]
+    fn test_nightly_is_silent_without_anomalies() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            r#"{"date": "2024-05-02", "activities": [{
+                "id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+                "time": {"start": "09:00:00", "end": "12:00:00"}}]}"#,
+        )
+        .unwrap();
+
+        let (_, report) = nightly(&dir, at(may(3), Time::from_hms(2, 0, 0).unwrap()));
+        assert_eq!(report, None);
+    }
+}
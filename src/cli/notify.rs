@@ -0,0 +1,252 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::format::format_duration_pretty;
+use crate::data::app_config::AppConfig;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::notify::{Notification, NotifyState, due_notifications};
+use clap::Parser;
+use log::{error, info, trace};
+use std::process::Command;
+use time::OffsetDateTime;
+
+#[derive(Parser)]
+pub struct CommandNotifyCheck {
+    /// Print the notifications instead of showing them on the desktop
+    #[arg(long)]
+    print: bool,
+}
+
+/// shows a notification to the user
+pub trait Notifier {
+    fn notify(&self, summary: &str, body: &str) -> std::io::Result<()>;
+}
+
+impl<F: Fn(&str, &str) -> std::io::Result<()>> Notifier for F {
+    fn notify(&self, summary: &str, body: &str) -> std::io::Result<()> {
+        self(summary, body)
+    }
+}
+
+/// desktop notification using `osascript` on macOS and `notify-send` elsewhere
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, summary: &str, body: &str) -> std::io::Result<()> {
+        let mut command = if cfg!(target_os = "macos") {
+            let quote =
+                |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+            let mut command = Command::new("osascript");
+            command.arg("-e").arg(format!(
+                "display notification {} with title {}",
+                quote(body),
+                quote(summary)
+            ));
+            command
+        } else {
+            let mut command = Command::new("notify-send");
+            command.args(["--app-name", "timetrax", summary, body]);
+            command
+        };
+
+        trace!("Showing notification: {:?}", command);
+        match command.status() {
+            Err(e) => {
+                error!("Failed to launch {:?}: {}", command.get_program(), e);
+                Err(e)
+            }
+            Ok(status) if !status.success() => {
+                error!("{:?} exited with {}", command.get_program(), status);
+                Err(std::io::Error::other("Notifier exited unsuccessfully"))
+            }
+            Ok(_) => Ok(()),
+        }
+    }
+}
+
+/// prints the notifications to stdout
+pub struct PrintNotifier;
+
+impl Notifier for PrintNotifier {
+    fn notify(&self, summary: &str, body: &str) -> std::io::Result<()> {
+        println!("{}: {}", summary, body);
+        Ok(())
+    }
+}
+
+/// summary and body shown to the user
+fn message(notification: &Notification) -> (String, String) {
+    match notification {
+        Notification::QuotaReached { tracked, quota } => (
+            "Work quota reached".to_string(),
+            format!(
+                "You tracked {} of work today, your quota is {}.",
+                format_duration_pretty(tracked, false),
+                format_duration_pretty(quota, false)
+            ),
+        ),
+        Notification::BreakReminder { since, working } => (
+            "Time for a break".to_string(),
+            format!(
+                "You have been working for {} since {}:{:02} without a break.",
+                format_duration_pretty(working, false),
+                since.hour(),
+                since.minute()
+            ),
+        ),
+    }
+}
+
+impl CommandNotifyCheck {
+    /// show the notifications due at `now` that did not fire yet today
+    pub fn check_with(
+        &self,
+        notifier: &dyn Notifier,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        manager: &Manager,
+        now: OffsetDateTime,
+    ) -> crate::error::Result<()> {
+        let today = job_config.date_of(now);
+        let path = NotifyState::path(config, manager.data_path());
+        let mut state = NotifyState::load(&path, today)?;
+        let day = manager.day(today);
+
+        let due = due_notifications(config, job_config, today, day, now);
+        let new = due
+            .iter()
+            .filter(|notification| !state.has_fired(notification))
+            .collect::<Vec<_>>();
+        if new.is_empty() {
+            trace!("No new notifications");
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+        for notification in new {
+            info!("Notifying: {:?}", notification);
+            let (summary, body) = message(notification);
+            // notifications failing to show are retried on the next check
+            match notifier.notify(&summary, &body) {
+                Ok(()) => state.record(notification),
+                Err(e) => result = Err(e.into()),
+            }
+        }
+        state.save(&path).inspect_err(|e| {
+            error!(
+                "Failed to save notification state to {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        result
+    }
+}
+
+impl ExecutableCommand for CommandNotifyCheck {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let notifier: &dyn Notifier = if self.print {
+            &PrintNotifier
+        } else {
+            &DesktopNotifier
+        };
+        self.check_with(notifier, config, job_config, &manager, local_time::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use std::cell::RefCell;
+    use time::{Date, Duration, Month, Time};
+
+    #[test]
+    fn test_notifications_fire_once() {
+        // a Friday
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(8, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let config = AppConfig {
+            break_reminder_after: Some(Duration::hours(2)),
+            ..dir.app_config().clone()
+        };
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+
+        let shown = RefCell::new(Vec::new());
+        let notifier = |summary: &str, body: &str| {
+            shown.borrow_mut().push(format!("{summary}: {body}"));
+            Ok(())
+        };
+        let check = |hours: u8| {
+            clock.set(at(date, Time::from_hms(8 + hours, 0, 0).unwrap()));
+            let command = CommandNotifyCheck::parse_from(["notify-check"]);
+            let manager = dir.manager().unwrap();
+            command
+                .check_with(
+                    &notifier,
+                    &config,
+                    &dir.job_config().unwrap(),
+                    &manager,
+                    local_time::now(),
+                )
+                .unwrap();
+            shown.borrow_mut().drain(..).collect::<Vec<_>>()
+        };
+
+        assert!(check(1).is_empty());
+        assert_eq!(
+            check(2),
+            ["Time for a break: You have been working for 2h 0m since 8:00 without a break."]
+        );
+        assert!(check(3).is_empty());
+        assert_eq!(
+            check(8),
+            ["Work quota reached: You tracked 8h 0m of work today, your quota is 8h 0m."]
+        );
+        assert!(check(9).is_empty());
+    }
+
+    #[test]
+    fn test_failed_notifications_are_retried() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(8, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.set(at(date, Time::from_hms(17, 0, 0).unwrap()));
+
+        let command = CommandNotifyCheck::parse_from(["notify-check"]);
+        let check = |notifier: &dyn Notifier| {
+            command.check_with(
+                notifier,
+                dir.app_config(),
+                &dir.job_config().unwrap(),
+                &dir.manager().unwrap(),
+                local_time::now(),
+            )
+        };
+        let failing = |_: &str, _: &str| Err(std::io::Error::other("no display"));
+        assert!(check(&failing).is_err());
+
+        let shown = RefCell::new(0);
+        let counting = |_: &str, _: &str| {
+            *shown.borrow_mut() += 1;
+            Ok(())
+        };
+        check(&counting).unwrap();
+        check(&counting).unwrap();
+        assert_eq!(*shown.borrow(), 1);
+    }
+}
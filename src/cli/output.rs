@@ -0,0 +1,88 @@
+//! results of the commands and how `main` writes them to stdout
+
+use crate::cli::{ListOutput, ReportOutput, StatusOutput};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+
+/// format of the output written after a command ran
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// as printed for people
+    #[default]
+    Text,
+    /// pretty JSON of the computed data, for the commands returning data
+    Json,
+}
+
+/// output of a command, written by `main` once the command succeeded
+pub trait RenderOutput {
+    fn render(&self, out: &mut impl Write, format: OutputFormat) -> std::io::Result<()>;
+}
+
+/// commands without output of their own print while they run
+impl RenderOutput for () {
+    fn render(&self, _out: &mut impl Write, _format: OutputFormat) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `text` as is, or `data` as pretty JSON
+pub fn render_text_or_json<T: Serialize>(
+    out: &mut impl Write,
+    format: OutputFormat,
+    text: &str,
+    data: &T,
+) -> std::io::Result<()> {
+    match format {
+        OutputFormat::Text => out.write_all(text.as_bytes()),
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, data)?;
+            writeln!(out)
+        }
+    }
+}
+
+/// output of any [`crate::cli::Command`]
+pub enum CommandOutput {
+    /// the command printed while it ran
+    None,
+    Status(StatusOutput),
+    List(ListOutput),
+    Report(ReportOutput),
+}
+
+impl From<()> for CommandOutput {
+    fn from(_: ()) -> Self {
+        CommandOutput::None
+    }
+}
+
+impl From<StatusOutput> for CommandOutput {
+    fn from(output: StatusOutput) -> Self {
+        CommandOutput::Status(output)
+    }
+}
+
+impl From<ListOutput> for CommandOutput {
+    fn from(output: ListOutput) -> Self {
+        CommandOutput::List(output)
+    }
+}
+
+impl From<ReportOutput> for CommandOutput {
+    fn from(output: ReportOutput) -> Self {
+        CommandOutput::Report(output)
+    }
+}
+
+impl RenderOutput for CommandOutput {
+    fn render(&self, out: &mut impl Write, format: OutputFormat) -> std::io::Result<()> {
+        match self {
+            CommandOutput::None => Ok(()),
+            CommandOutput::Status(output) => output.render(out, format),
+            CommandOutput::List(output) => output.render(out, format),
+            CommandOutput::Report(output) => output.render(out, format),
+        }
+    }
+}
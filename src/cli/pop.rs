@@ -1,55 +1,989 @@
 use crate::cli::ExecutableCommand;
+use crate::cli::args::{TimeArg, parse_time_or_ago_arg};
+use crate::cli::blocker::stop_blocker;
+use crate::cli::day::ensure_unlocked;
+use crate::cli::edit::print_candidates;
+use crate::cli::format::format_duration_pretty;
+use crate::cli::show::format_time;
+use crate::data::activity::{Activity, ActivityOrigin};
 use crate::data::app_config::AppConfig;
+use crate::data::day::{ActivityLookupError, DayInner};
 use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
 use crate::data::manager::Manager;
+use crate::data::reference::Reference;
+use crate::error::Error;
 use clap::Parser;
 use log::{error, info};
-use time::OffsetDateTime;
+use std::io::{BufRead, IsTerminal, Write};
+use time::{Date, Duration, Time};
+use uuid::Uuid;
 
 #[derive(Parser)]
-pub struct CommandPop {}
+pub struct CommandPop {
+    /// Activity to stop (UUID, UUID prefix, short id or @name), defaults to the last started one
+    #[arg(conflicts_with_all = ["blocker", "stale", "all"])]
+    activity: Option<Reference>,
+    /// Stop the most recent open blocker instead of an activity
+    #[arg(short, long)]
+    blocker: bool,
+    /// Stop the open activities left on the most recent previous day
+    #[arg(long, conflicts_with = "blocker")]
+    stale: bool,
+    /// Stop all open activities instead of the last started one
+    #[arg(short, long, conflicts_with_all = ["blocker", "stale"])]
+    all: bool,
+    /// End (HH:MM[:SS]) or how long ago the activity stopped (e.g. -10m), defaults to now,
+    /// with --stale to the last end recorded on that day
+    #[arg(
+        long,
+        value_parser = parse_time_or_ago_arg,
+        allow_hyphen_values = true,
+        conflicts_with = "blocker"
+    )]
+    at: Option<TimeArg>,
+    /// Succeed even if there is no open activity to stop, for scripts
+    #[arg(short, long, visible_alias = "idempotent")]
+    quiet: bool,
+    /// Keep activities shorter than `min_activity_duration` of the job config without asking
+    #[arg(long, conflicts_with = "discard_short")]
+    keep_short: bool,
+    /// Discard activities shorter than `min_activity_duration` of the job config without asking
+    #[arg(long)]
+    discard_short: bool,
+}
 
-impl ExecutableCommand for CommandPop {
-    type Error = std::io::Error;
-    type Output = ();
-    fn execute(
+/// asks the user a yes/no question
+pub trait Confirm {
+    fn confirm(&self, question: &str) -> std::io::Result<bool>;
+}
+
+impl<F: Fn(&str) -> std::io::Result<bool>> Confirm for F {
+    fn confirm(&self, question: &str) -> std::io::Result<bool> {
+        self(question)
+    }
+}
+
+/// end of activities stopped `at` the given time, refusing ends in the future and relative
+/// ends reaching into the previous day
+fn end_at(at: TimeArg, boundary: Option<Time>) -> std::io::Result<Time> {
+    let end = at.resolve_within_day(boundary)?;
+    let now = local_time::to_day_time(local_time::now_time(), boundary);
+    if local_time::to_day_time(end, boundary) > now {
+        error!(
+            "Refusing to stop at {}, which is in the future",
+            format_time(end)
+        );
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "End lies in the future",
+        ));
+    }
+    Ok(end)
+}
+
+/// asks on stdout and reads the answer from stdin, anything but yes declines
+pub struct StdinConfirm;
+
+impl Confirm for StdinConfirm {
+    fn confirm(&self, question: &str) -> std::io::Result<bool> {
+        print!("{} [y/N]: ", question);
+        std::io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+            println!();
+            return Ok(false);
+        }
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+}
+
+/// e.g. `Stopped activity: ... (1h 30m)`
+pub fn stopped_message(activity: &Activity, boundary: Option<Time>) -> String {
+    match activity.time.to_day_time(boundary).duration() {
+        Some(duration) => format!(
+            "Stopped activity: {} ({})",
+            activity,
+            format_duration_pretty(duration, false)
+        ),
+        None => format!("Stopped activity: {}", activity),
+    }
+}
+
+/// id of the open activity of the day matching `reference`, see
+/// [`DayInner::find_activity_by_reference`]
+fn open_activity_by_reference(
+    date: Date,
+    day: &DayInner,
+    reference: &Reference,
+) -> crate::error::Result<Uuid> {
+    let activity = match day.find_activity_by_reference(reference) {
+        Ok(activity) => activity,
+        Err(e @ ActivityLookupError::AmbiguousReference(..)) => {
+            error!("{}", e);
+            println!("Matching activities of {}:", date);
+            for activity in reference.matches(&day.activities) {
+                println!(" - {} {} {}", activity.short_id(), activity.id, activity);
+            }
+            return Err(e.into());
+        }
+        Err(e) => {
+            error!("{}", e);
+            print_candidates(date, day);
+            return Err(e.into());
+        }
+    };
+    if activity.time.is_complete() {
+        error!("{} is already complete", activity);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Activity already complete",
+        )
+        .into());
+    }
+    Ok(activity.id)
+}
+
+/// stop the open activities of the most recent day before `today`
+fn pop_stale(
+    manager: &mut Manager,
+    today: Date,
+    at: Option<Time>,
+    boundary: Option<Time>,
+) -> crate::error::Result<()> {
+    let Some((date, day)) = manager
+        .previous_day_with_data(today)
+        .filter(|(_, day)| day.activities.iter().any(|a| !a.time.is_complete()))
+    else {
+        println!("No stale activities.");
+        return Ok(());
+    };
+    ensure_unlocked(date, day)?;
+
+    // without an explicit end, assume an activity lasted until the last end recorded after its start
+    let ends = day
+        .activities
+        .iter()
+        .map(|activity| {
+            if activity.time.is_complete() {
+                return Ok(None);
+            }
+            at.or_else(|| {
+                day.activities
+                    .iter()
+                    .filter_map(|other| other.time.end)
+                    .filter(|end| {
+                        local_time::to_day_time(*end, boundary)
+                            > local_time::to_day_time(activity.time.start, boundary)
+                    })
+                    .max_by_key(|end| local_time::to_day_time(*end, boundary))
+            })
+            .map(Some)
+            .ok_or_else(|| {
+                error!(
+                    "The end of {} on {} is unknown, pass it using --at HH:MM",
+                    activity, date
+                );
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "End of stale activity unknown",
+                )
+            })
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    let day = manager.get_or_create_day_mut(date);
+    let mut messages = vec![];
+    for (activity, end) in day.activities.iter_mut().zip(ends) {
+        let Some(end) = end else {
+            continue;
+        };
+        info!("Popping stale activity of {}: {:?}", date, activity);
+        if activity.time.complete_at(end, boundary) {
+            messages.push(format!(
+                "Warning: {} lies before the start of the activity, it was stopped with zero length.",
+                end
+            ));
+        }
+        messages.push(format!("Stopped activity of {}: {}", date, activity));
+    }
+    manager.save()?;
+    for message in messages {
+        println!("{}", message);
+    }
+
+    Ok(())
+}
+
+/// split the activities still open on the day before `date` once it has ended: they end at the
+/// last second of that day and continue as new activities starting with `date`. only activities
+/// started within `window` before the end of that day are split, others were most likely
+/// forgotten and are left to `pop --stale` and `fixup`. nothing is split if `date` has open
+/// activities of its own or the previous day is locked. only the previous day is looked at,
+/// activities left open for several days are not split across the days in between
+fn continue_overnight(
+    manager: &mut Manager,
+    date: Date,
+    boundary: Option<Time>,
+    window: Duration,
+) -> Vec<String> {
+    if manager
+        .day(date)
+        .is_some_and(|day| day.activities.iter().any(|a| !a.time.is_complete()))
+    {
+        return vec![];
+    }
+    let Some(previous) = date.previous_day() else {
+        return vec![];
+    };
+    if manager.day(previous).is_none_or(|day| day.locked) {
+        return vec![];
+    }
+
+    let now = local_time::now();
+    let split = manager
+        .get_or_create_day_mut(previous)
+        .activities
+        .iter_mut()
+        .filter(|activity| !activity.time.is_complete())
+        .filter(|activity| {
+            let start = local_time::to_day_time(activity.time.start, boundary);
+            Duration::DAY - (start - Time::MIDNIGHT) <= window
+        })
+        .map(|activity| {
+            let mut continuation = activity.clone();
+            continuation.id = Uuid::new_v4();
+            continuation.time = activity.time.split_at_day_end(boundary);
+            continuation.created_at = Some(now);
+            continuation.modified_at = None;
+            (activity.clone(), continuation)
+        })
+        .collect::<Vec<_>>();
+
+    let day = manager.get_or_create_day_mut(date);
+    let mut messages = vec![];
+    for (stopped, continuation) in split {
+        info!(
+            "Continuing activity of {} after midnight: {:?}",
+            previous, stopped
+        );
+        messages.push(format!(
+            "Stopped activity of {} at midnight: {}",
+            previous, stopped
+        ));
+        day.activities.push(continuation);
+    }
+    messages
+}
+
+impl CommandPop {
+    /// whether the just stopped `activity` is discarded for being shorter than
+    /// [`JobConfig::min_activity_duration`]. the flags decide first, then the answer to `confirm`
+    /// and without a way to ask [`JobConfig::discard_short_activities`]
+    fn discards_short(
+        &self,
+        job_config: &JobConfig,
+        confirm: Option<&dyn Confirm>,
+        activity: &Activity,
+    ) -> std::io::Result<bool> {
+        let Some(min) = job_config.min_activity_duration else {
+            return Ok(false);
+        };
+        let Some(duration) = activity
+            .time
+            .to_day_time(job_config.day_boundary)
+            .duration()
+        else {
+            return Ok(false);
+        };
+        // activities with explicitly given times are deliberate, however short
+        if duration >= min || activity.origin == Some(ActivityOrigin::Explicit) || self.keep_short {
+            return Ok(false);
+        }
+        if self.discard_short {
+            return Ok(true);
+        }
+        match confirm {
+            Some(confirm) => confirm.confirm(&format!(
+                "{} is shorter than {}, discard it?",
+                activity,
+                format_duration_pretty(min, false)
+            )),
+            None => Ok(job_config.discard_short_activities),
+        }
+    }
+
+    /// stop activities, asking `confirm` whether to discard short ones, see
+    /// [`CommandPop::discards_short`]
+    pub fn pop_with(
         &self,
-        _config: &AppConfig,
-        _job_config: &mut JobConfig,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        confirm: Option<&dyn Confirm>,
         mut manager: Manager,
-    ) -> Result<Self::Output, Self::Error> {
-        let today = OffsetDateTime::now_local()
-            .unwrap_or_else(|e| {
-                error!("Failed to get local time. Falling back to UTC: {}", e);
-                OffsetDateTime::now_utc()
-            })
-            .date();
+    ) -> crate::error::Result<()> {
+        let today = job_config.today();
+        let boundary = job_config.day_boundary;
+
+        if self.stale {
+            let at = self
+                .at
+                .map(|at| at.resolve_within_day(boundary))
+                .transpose()?;
+            return pop_stale(&mut manager, today, at, boundary);
+        }
 
+        let date = today;
+        // printed once the days are saved
+        let mut messages = vec![];
+        if !self.blocker {
+            messages = continue_overnight(&mut manager, date, boundary, config.overnight_window);
+        }
+        let journal = Journal::new(config, manager.data_path());
         let today = manager.get_or_create_day(today);
 
-        if !today.inner().activities.is_empty() {
-            let today = today.inner_mut();
-            today
-                .activities
-                .sort_by(|a, b| a.time.start.cmp(&b.time.start));
+        if self.blocker {
+            if !today.inner().blockers.iter().any(|b| !b.time.is_complete()) {
+                println!("No open blockers.");
+                return Ok(());
+            }
 
-            if let Some(activity) = today
-                .activities
+            if let Some(blocker) = today
+                .inner_mut()
+                .blockers
                 .iter_mut()
-                .filter(|a| !a.time.is_complete())
-                .last()
+                .filter(|b| !b.time.is_complete())
+                .max_by_key(|b| local_time::to_day_time(b.time.start, boundary))
             {
-                info!("Popping activity: {:?}", activity);
-                activity.time.complete_now();
+                messages = stop_blocker(blocker, boundary);
+            }
+            manager.save()?;
+            for message in messages {
+                println!("{}", message);
+            }
+            return Ok(());
+        }
+
+        let selected = self
+            .activity
+            .as_ref()
+            .map(|reference| open_activity_by_reference(date, today.inner(), reference))
+            .transpose()?;
 
-                println!("Stopped activity: {activity}");
+        let today = today.inner_mut();
+        today
+            .activities
+            .sort_by_key(|a| local_time::to_day_time(a.time.start, boundary));
 
-                if today.activities.iter_mut().all(|a| a.time.is_complete()) {
-                    println!("All activities for today are complete.");
-                }
+        let end = self.at.map(|at| end_at(at, boundary)).transpose()?;
+        let open = today
+            .activities
+            .iter_mut()
+            .filter(|a| !a.time.is_complete())
+            .filter(|a| selected.is_none_or(|id| a.id == id))
+            .collect::<Vec<_>>();
+        let skip = if self.all || selected.is_some() {
+            0
+        } else {
+            open.len().saturating_sub(1)
+        };
+        let mut popped = open.into_iter().skip(skip).collect::<Vec<_>>();
+        if popped.is_empty() {
+            if self.quiet {
+                return Ok(());
             }
+            println!("No open activity to stop.");
+            return Err(Error::NothingToPop);
+        }
+
+        if let Some(end) = end
+            && let Some(activity) = popped.iter().find(|activity| {
+                local_time::to_day_time(end, boundary)
+                    <= local_time::to_day_time(activity.time.start, boundary)
+            })
+        {
+            error!(
+                "Cannot stop {} at {}, it started at {}",
+                activity,
+                format_time(end),
+                format_time(activity.time.start)
+            );
+            return Err(Error::InvalidInterval {
+                start: activity.time.start,
+                end,
+            });
+        }
+
+        let mut discarded = Vec::new();
+        for activity in popped.iter_mut() {
+            info!("Popping activity: {:?}", activity);
+            let open = activity.clone();
+            if activity
+                .time
+                .complete_at(end.unwrap_or_else(local_time::now_time), boundary)
+            {
+                messages.push(
+                    "Warning: the clock moved backwards, the activity was stopped with zero length."
+                        .to_string(),
+                );
+            }
+
+            if self.discards_short(job_config, confirm, activity)? {
+                discarded.push((open, activity.clone()));
+                continue;
+            }
+            messages.push(stopped_message(activity, boundary));
+        }
+
+        let now = local_time::now();
+        for (_, stopped) in &discarded {
+            info!("Discarding short activity: {:?}", stopped);
+            today.delete_activity(stopped.id, now);
+        }
+        let all_complete = today.activities.iter().all(|a| a.time.is_complete());
+        manager.save()?;
+
+        for (open, stopped) in discarded {
+            journal.append(&JournalEntry {
+                at: now,
+                command: "pop".to_string(),
+                date,
+                removed: vec![open],
+                added: vec![],
+            })?;
+            messages.push(format!(
+                "Discarded activity shorter than {}: {}",
+                format_duration_pretty(job_config.min_activity_duration.unwrap_or_default(), false),
+                stopped
+            ));
+        }
+        for message in messages {
+            println!("{}", message);
+        }
+        if all_complete {
+            println!("All activities for today are complete.");
         }
 
         Ok(())
     }
 }
+
+impl ExecutableCommand for CommandPop {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        // only ask about short activities in an interactive terminal
+        let confirm = std::io::stdin()
+            .is_terminal()
+            .then_some(&StdinConfirm as &dyn Confirm);
+        self.pop_with(config, job_config, confirm, manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Date, Duration, Month, Time};
+
+    #[test]
+    fn test_pop_records_elapsed_time() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.advance(Duration::minutes(90));
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert_eq!(
+            day.activities[0].time.duration(),
+            Some(Duration::minutes(90))
+        );
+    }
+
+    #[test]
+    fn test_pop_stops_last_started() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "first",
+        ]))
+        .unwrap();
+        clock.advance(Duration::minutes(30));
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@break", "-n", "second",
+        ]))
+        .unwrap();
+        clock.advance(Duration::minutes(15));
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(!day.activities[0].time.is_complete());
+        assert_eq!(
+            day.activities[1].time.duration(),
+            Some(Duration::minutes(15))
+        );
+    }
+
+    #[test]
+    fn test_pop_without_open_activities() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        // empty day
+        let error = dir
+            .execute(&CommandPop::parse_from(["pop"]))
+            .expect_err("nothing to pop");
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert!(matches!(error, Error::NothingToPop));
+        assert_eq!(error.exit_code(), 66);
+        dir.execute(&CommandPop::parse_from(["pop", "--quiet"]))
+            .unwrap();
+
+        // all activities complete
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.advance(Duration::minutes(30));
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+        let error = dir
+            .execute(&CommandPop::parse_from(["pop"]))
+            .expect_err("nothing to pop");
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        dir.execute(&CommandPop::parse_from(["pop", "--idempotent"]))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_stopped_message_shows_the_duration() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding",
+        ]))
+        .unwrap();
+        clock.advance(Duration::minutes(90));
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+
+        let activity = &dir.day(date).unwrap().unwrap().activities[0];
+        let message = stopped_message(activity, None);
+        assert!(message.starts_with("Stopped activity: "), "{message}");
+        assert!(message.ends_with(" (1h 30m)"), "{message}");
+    }
+
+    #[test]
+    fn test_pop_activity_by_reference() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        for name in ["older", "newer"] {
+            dir.execute(&CommandPush::parse_from([
+                "push", "-c", "@work", "-n", name,
+            ]))
+            .unwrap();
+            clock.advance(Duration::minutes(30));
+        }
+        let older = dir.day(date).unwrap().unwrap().activities[0].clone();
+        dir.execute(&CommandPop::parse_from(["pop", &older.short_id()]))
+            .unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(
+            day.activities[0].time.end,
+            Some(Time::from_hms(10, 0, 0).unwrap())
+        );
+        assert!(!day.activities[1].time.is_complete());
+
+        // already complete
+        assert!(
+            dir.execute(&CommandPop::parse_from(["pop", &older.id.to_string()]))
+                .is_err()
+        );
+        assert!(
+            dir.execute(&CommandPop::parse_from(["pop", "unknown"]))
+                .is_err()
+        );
+        dir.execute(&CommandPop::parse_from(["pop", "@newer"]))
+            .unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(day.activities.iter().all(|a| a.time.is_complete()));
+        assert!(CommandPop::try_parse_from(["pop", "@newer", "--all"]).is_err());
+    }
+
+    #[test]
+    fn test_pop_at_a_given_time() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        for class in ["@work", "@break"] {
+            dir.execute(&CommandPush::parse_from(["push", "-c", class]))
+                .unwrap();
+            clock.advance(Duration::hours(1));
+        }
+        dir.execute(&CommandPop::parse_from(["pop", "--at", "-10m"]))
+            .unwrap();
+        dir.execute(&CommandPop::parse_from(["pop", "--at", "10:30"]))
+            .unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(
+            day.activities[1].time.end,
+            Some(Time::from_hms(10, 50, 0).unwrap())
+        );
+        assert_eq!(
+            day.activities[0].time.end,
+            Some(Time::from_hms(10, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_pop_at_rejects_ends_before_the_start() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.advance(Duration::minutes(30));
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@break"]))
+            .unwrap();
+        clock.advance(Duration::minutes(5));
+
+        assert!(
+            dir.execute(&CommandPop::parse_from(["pop", "--at", "-10m"]))
+                .is_err()
+        );
+        assert!(
+            dir.execute(&CommandPop::parse_from(["pop", "--at", "8:00"]))
+                .is_err()
+        );
+        // the start of the last started activity is checked as well with --all
+        assert!(
+            dir.execute(&CommandPop::parse_from(["pop", "--all", "--at", "9:15"]))
+                .is_err()
+        );
+        // as are ends in the future
+        assert!(
+            dir.execute(&CommandPop::parse_from(["pop", "--at", "9:40"]))
+                .is_err()
+        );
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(day.activities.iter().all(|a| !a.time.is_complete()));
+
+        dir.execute(&CommandPop::parse_from(["pop", "--all", "--at", "-1m"]))
+            .unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(
+            day.activities
+                .iter()
+                .all(|a| a.time.end == Some(Time::from_hms(9, 34, 0).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_pop_after_midnight_splits_the_activity() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let next = date.next_day().unwrap();
+        let clock = ManualClock::new(at(next, Time::from_hms(0, 45, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file(
+            "2024-05-03.json",
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/overnight_day.json"
+            )),
+        )
+        .unwrap();
+
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        let original = &day.activities[1];
+        assert_eq!(original.name.as_deref(), Some("release"));
+        // day files store whole seconds
+        assert_eq!(original.time.end, Some(Time::from_hms(23, 59, 59).unwrap()));
+        assert!(original.time.validate(None).is_ok());
+        assert_eq!(
+            day.activities[0].time.end,
+            Some(Time::from_hms(19, 0, 0).unwrap())
+        );
+
+        let next_day = dir.day(next).unwrap().unwrap();
+        assert_eq!(next_day.activities.len(), 1);
+        let continuation = &next_day.activities[0];
+        assert_ne!(continuation.id, original.id);
+        assert_eq!(continuation.name.as_deref(), Some("release"));
+        assert_eq!(continuation.class, original.class);
+        assert_eq!(continuation.time.start, Time::MIDNIGHT);
+        assert_eq!(continuation.time.duration(), Some(Duration::minutes(45)));
+
+        // nothing is left to continue
+        assert!(dir.execute(&CommandPop::parse_from(["pop"])).is_err());
+    }
+
+    #[test]
+    fn test_pop_leaves_forgotten_activities_to_the_stale_flow() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let next = date.next_day().unwrap();
+        let clock = ManualClock::new(at(next, Time::from_hms(0, 45, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let forgotten = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/overnight_day.json"
+        ))
+        .replace("22:30:00", "14:00:00");
+        dir.write_day_file("2024-05-03.json", &forgotten).unwrap();
+
+        // started more than the overnight window before midnight
+        assert!(dir.execute(&CommandPop::parse_from(["pop"])).is_err());
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(!day.activities[1].time.is_complete());
+        assert!(
+            dir.day(next)
+                .unwrap()
+                .is_none_or(|day| day.activities.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_pop_across_day_boundary() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(23, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let job_config = JobConfig {
+            day_boundary: Some(Time::from_hms(4, 0, 0).unwrap()),
+            ..JobConfig::default()
+        };
+        let dir = TempDataDir::new(&job_config).unwrap();
+
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.advance(Duration::minutes(150));
+        // 01:30 on the next calendar date still belongs to the same day
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@break"]))
+            .unwrap();
+        clock.advance(Duration::minutes(15));
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+
+        assert!(dir.day(date.next_day().unwrap()).unwrap().is_none());
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 2);
+        assert!(!day.activities[0].time.is_complete());
+        assert_eq!(
+            day.activities[1].time.start,
+            Time::from_hms(1, 30, 0).unwrap()
+        );
+        assert_eq!(
+            day.activities[1]
+                .time
+                .to_day_time(job_config.day_boundary)
+                .duration(),
+            Some(Duration::minutes(15))
+        );
+    }
+
+    /// a data directory offering to discard activities shorter than a minute, with a clock at
+    /// 2024-05-03 09:00
+    fn short_setup(discard: bool) -> (ManualClock, TempDataDir, Date) {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig {
+            min_activity_duration: Some(Duration::minutes(1)),
+            discard_short_activities: discard,
+            ..JobConfig::default()
+        })
+        .unwrap();
+        (clock, dir, date)
+    }
+
+    /// pop with the given arguments, asking `confirm`
+    fn pop_with(dir: &TempDataDir, confirm: Option<&dyn Confirm>, args: &[&str]) {
+        CommandPop::parse_from(["pop"].iter().chain(args))
+            .pop_with(
+                dir.app_config(),
+                &dir.job_config().unwrap(),
+                confirm,
+                dir.manager().unwrap(),
+            )
+            .unwrap();
+    }
+
+    /// push an activity named `name` and pop it after `seconds`
+    fn push_and_pop_with(
+        dir: &TempDataDir,
+        clock: &ManualClock,
+        name: &str,
+        seconds: i64,
+        confirm: Option<&dyn Confirm>,
+        args: &[&str],
+    ) {
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", name,
+        ]))
+        .unwrap();
+        clock.advance(Duration::seconds(seconds));
+        pop_with(dir, confirm, args);
+    }
+
+    fn names(dir: &TempDataDir, date: Date) -> Vec<String> {
+        (dir.day(date).unwrap().unwrap().activities.iter())
+            .map(|activity| activity.name.clone().unwrap_or_default())
+            .collect()
+    }
+
+    fn never_asked(question: &str) -> std::io::Result<bool> {
+        panic!("asked: {question}")
+    }
+
+    #[test]
+    fn test_pop_short_activity_flags() {
+        let (clock, dir, date) = short_setup(false);
+        let _guard = clock.install();
+
+        push_and_pop_with(
+            &dir,
+            &clock,
+            "accident",
+            5,
+            Some(&never_asked),
+            &["--discard-short"],
+        );
+        assert!(names(&dir, date).is_empty());
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.trash.len(), 1);
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(entries[0].command, "pop");
+        // the journal keeps the activity as it was before the pop
+        assert!(!entries[0].removed[0].time.is_complete());
+
+        push_and_pop_with(
+            &dir,
+            &clock,
+            "quick",
+            5,
+            Some(&never_asked),
+            &["--keep-short"],
+        );
+        // long enough activities are never in question
+        push_and_pop_with(
+            &dir,
+            &clock,
+            "long",
+            60,
+            Some(&never_asked),
+            &["--discard-short"],
+        );
+        assert_eq!(names(&dir, date), ["quick", "long"]);
+        assert!(CommandPop::try_parse_from(["pop", "--keep-short", "--discard-short"]).is_err());
+    }
+
+    #[test]
+    fn test_pop_short_activity_asks() {
+        let (clock, dir, date) = short_setup(false);
+        let _guard = clock.install();
+        let questions = std::cell::RefCell::new(Vec::new());
+        let questions = &questions;
+        let answer = |discard: bool| {
+            move |question: &str| {
+                questions.borrow_mut().push(question.to_string());
+                Ok(discard)
+            }
+        };
+
+        push_and_pop_with(&dir, &clock, "accident", 5, Some(&answer(true)), &[]);
+        push_and_pop_with(&dir, &clock, "quick", 10, Some(&answer(false)), &[]);
+        assert_eq!(names(&dir, date), ["quick"]);
+        assert_eq!(
+            questions.borrow()[0],
+            "09:00:00 - 09:00:05: accident is shorter than 1m, discard it?"
+        );
+        assert_eq!(questions.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_pop_short_activity_config_default() {
+        for (discard, expected) in [(true, vec![]), (false, vec!["accident"])] {
+            let (clock, dir, date) = short_setup(discard);
+            let _guard = clock.install();
+            push_and_pop_with(&dir, &clock, "accident", 5, None, &[]);
+            assert_eq!(names(&dir, date), expected);
+        }
+    }
+
+    #[test]
+    fn test_pop_keeps_short_explicit_activities() {
+        let (clock, dir, date) = short_setup(true);
+        let _guard = clock.install();
+        dir.write_day_file(
+            "2024-05-03.json",
+            r#"{
+                "date": "2024-05-03",
+                "activities": [{
+                    "id": "6f1c1c8e-2f4b-4a8e-9a57-2f0d7c1e2b11",
+                    "name": "deliberate",
+                    "class": "@work",
+                    "time": {"start": "08:59:50"},
+                    "origin": "explicit"
+                }]
+            }"#,
+        )
+        .unwrap();
+        pop_with(&dir, Some(&never_asked), &["--discard-short"]);
+        assert_eq!(names(&dir, date), ["deliberate"]);
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(day.activities[0].time.is_complete());
+        assert_eq!(day.activities[0].origin, Some(ActivityOrigin::Explicit));
+    }
+
+    #[test]
+    fn test_pop_keeps_short_activities_pushed_at_a_time() {
+        let (clock, dir, date) = short_setup(true);
+        let _guard = clock.install();
+        clock.advance(Duration::seconds(30));
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "-n",
+            "deliberate",
+            "--at",
+            "9:00",
+        ]))
+        .unwrap();
+        clock.advance(Duration::seconds(10));
+        pop_with(&dir, Some(&never_asked), &["--discard-short"]);
+        assert_eq!(names(&dir, date), ["deliberate"]);
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(day.activities[0].time.is_complete());
+        assert_eq!(day.activities[0].origin, Some(ActivityOrigin::Explicit));
+    }
+}
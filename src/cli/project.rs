@@ -1,24 +1,42 @@
 use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_identifier_arg;
+use crate::cli::day::ensure_unlocked;
 use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
 use crate::data::identifier::Identifier;
 use crate::data::job_config::JobConfig;
 use crate::data::manager::Manager;
 use crate::data::project::{Project, ProjectInner};
+use crate::data::report::ReportFilter;
+use crate::data::text::{did_you_mean, require_name};
+use crate::data::workspace::WorkspaceState;
+use crate::error::Error;
 use clap::Parser;
-use log::error;
+use itertools::Itertools;
+use log::{error, warn};
+use time::Date;
 use uuid::Uuid;
 
-#[derive(Parser, Default)]
+#[derive(Parser)]
 pub enum CommandProject {
     /// List all projects
-    #[default]
     #[clap(aliases = ["ls", "show", "info", "display"])]
-    List,
+    List {
+        /// Show projects as a tree grouped by client
+        #[arg(short, long)]
+        by_client: bool,
+    },
     /// Delete a project
     #[clap(aliases = ["delete", "del", "rm"])]
     Remove {
         /// Project identifier
         project: Identifier,
+        /// Make the activities and blockers referring to the project refer to this project
+        #[arg(short, long, value_name = "PROJECT", value_parser = parse_identifier_arg)]
+        reassign_to: Option<Identifier>,
+        /// Refuse to remove the project if activities or blockers refer to it
+        #[arg(long, conflicts_with = "reassign_to")]
+        abort_if_referenced: bool,
     },
     /// Create a new project
     #[clap(aliases = ["new", "create"])]
@@ -28,46 +46,361 @@ pub enum CommandProject {
         /// Description of the project
         description: Option<String>,
     },
+    /// Change name, description, client or target share of a project
+    #[clap(aliases = ["modify", "set"])]
+    Edit {
+        /// Project identifier
+        project: Identifier,
+        /// New project name
+        #[arg(short, long)]
+        name: Option<String>,
+        /// New description of the project
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Remove the description of the project
+        #[arg(
+            long,
+            visible_alias = "clear-description",
+            conflicts_with = "description"
+        )]
+        no_description: bool,
+        /// When renaming, make activities and blockers referring to the old name refer to
+        /// the project by UUID
+        #[arg(long, requires = "name")]
+        rewrite_references: bool,
+        /// Client the project is billed to
+        #[arg(short, long)]
+        client: Option<String>,
+        /// Remove the client of the project
+        #[arg(long, conflicts_with = "client")]
+        no_client: bool,
+        /// Targeted percentage of the tracked time, e.g. 70 for 70%
+        #[arg(short, long, value_name = "PERCENT")]
+        target_share: Option<f32>,
+        /// Remove the target share of the project
+        #[arg(long, conflicts_with = "target_share")]
+        no_target_share: bool,
+    },
+    /// Select the current project, assigned by `push` if no --project is given
+    #[clap(aliases = ["switch"])]
+    Use {
+        /// Project identifier
+        #[arg(required_unless_present = "none")]
+        project: Option<Identifier>,
+        /// Clear the current project
+        #[arg(long, conflicts_with = "project")]
+        none: bool,
+    },
+}
+
+impl Default for CommandProject {
+    fn default() -> Self {
+        CommandProject::List { by_client: false }
+    }
+}
+
+/// make the activities and blockers of all days refer to the project named `name` by its
+/// UUID `id`, locked days are skipped. returns the number of rewritten references
+fn rewrite_references_by_name(
+    manager: &mut Manager,
+    job_config: &JobConfig,
+    name: &str,
+    id: Uuid,
+) -> usize {
+    let old = Identifier::ByName(name.to_string());
+    replace_references(
+        manager,
+        job_config,
+        name,
+        |project| *project == old,
+        Some(&Identifier::Uuid(id)),
+    )
+}
+
+/// replace the project references matching `is_old` of the activities and blockers of all
+/// days by `replacement`, or remove them if there is none. locked days are skipped, `name`
+/// is used in their warning. returns the number of replaced references
+fn replace_references(
+    manager: &mut Manager,
+    job_config: &JobConfig,
+    name: &str,
+    is_old: impl Fn(&Identifier) -> bool,
+    replacement: Option<&Identifier>,
+) -> usize {
+    let refers = |day: &DayInner| {
+        day.activities
+            .iter()
+            .any(|a| a.projects.iter().any(&is_old) || a.weights.keys().any(&is_old))
+            || day.blockers.iter().any(|b| b.projects.iter().any(&is_old))
+    };
+
+    let replacement_id = replacement
+        .and_then(|replacement| job_config.resolve_project(replacement))
+        .map(|project| project.id);
+    let is_replacement = |project: &Identifier| {
+        replacement_id.is_some()
+            && job_config
+                .resolve_project(project)
+                .map(|project| project.id)
+                == replacement_id
+    };
+
+    let mut replaced = 0;
+    for date in manager.dates().collect_vec() {
+        let Some(day) = manager.annotated_day_mut(date) else {
+            continue;
+        };
+        // only touch days with references, touching marks them dirty
+        if !refers(day.inner()) {
+            continue;
+        }
+        if day.inner().locked {
+            warn!(
+                "The day {} is locked, its references to '{}' are kept",
+                date, name
+            );
+            continue;
+        }
+        let day = day.inner_mut();
+        let activities = day.activities.iter_mut().map(|a| &mut a.projects);
+        let blockers = day.blockers.iter_mut().map(|b| &mut b.projects);
+        for projects in activities.chain(blockers) {
+            let Some(index) = projects.iter().position(&is_old) else {
+                continue;
+            };
+            let before = projects.len();
+            projects.retain(|project| !is_old(project));
+            replaced += before - projects.len();
+            if let Some(replacement) = replacement
+                && !projects.iter().any(&is_replacement)
+            {
+                projects.insert(index.min(projects.len()), replacement.clone());
+            }
+        }
+        for activity in &mut day.activities {
+            let old_keys = activity
+                .weights
+                .keys()
+                .filter(|k| is_old(k))
+                .cloned()
+                .collect_vec();
+            for key in old_keys {
+                let weight = activity.weights.remove(&key).unwrap_or_default();
+                if let Some(replacement) = replacement {
+                    // add to the weight of an existing reference to the replacement
+                    let key = activity
+                        .weights
+                        .keys()
+                        .find(|k| is_replacement(k))
+                        .unwrap_or(replacement)
+                        .clone();
+                    *activity.weights.entry(key).or_default() += weight;
+                }
+                replaced += 1;
+            }
+        }
+    }
+    replaced
+}
+
+/// dates and ids of the activities and blockers of all days matching `is_referenced`
+fn project_references(
+    manager: &Manager,
+    is_referenced: impl Fn(&Identifier) -> bool,
+) -> Vec<(Date, Uuid)> {
+    manager
+        .days()
+        .flat_map(|(date, day)| {
+            let activities = day
+                .activities
+                .iter()
+                .filter(|a| {
+                    a.projects.iter().any(&is_referenced) || a.weights.keys().any(&is_referenced)
+                })
+                .map(|a| a.id);
+            let blockers = day
+                .blockers
+                .iter()
+                .filter(|b| b.projects.iter().any(&is_referenced))
+                .map(|b| b.id);
+            activities
+                .chain(blockers)
+                .map(|id| (*date, id))
+                .collect_vec()
+        })
+        .collect()
+}
+
+/// e.g. `Unknown project 'clinet'. Did you mean: client?`
+pub fn unknown_project_message(job_config: &JobConfig, project: &Identifier) -> String {
+    match project {
+        Identifier::ByName(name) => {
+            let suggestions = job_config.suggest_project(name);
+            let names = suggestions
+                .iter()
+                .map(|project| project.inner.name.as_str())
+                .collect::<Vec<_>>();
+            format!("Unknown project '{}'.{}", name, did_you_mean(&names))
+        }
+        Identifier::Uuid(id) => format!("Unknown project {}.", id),
+    }
+}
+
+/// fails with the names of the known projects if the filter refers to an unknown one
+pub fn check_project_filter(
+    job_config: &JobConfig,
+    filter: &ReportFilter,
+) -> crate::error::Result<()> {
+    let unknown = filter.unknown_projects(job_config);
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    error!(
+        "Unknown project {}, known projects: {}",
+        unknown.iter().join(", "),
+        job_config
+            .projects
+            .iter()
+            .map(|project| &project.inner.name)
+            .join(", ")
+    );
+    Err(Error::UnknownProject(unknown[0].clone()))
+}
+
+fn format_project(project: &Project) -> String {
+    format!(
+        "{}{} ({})",
+        project.inner.name,
+        project
+            .inner
+            .description
+            .as_ref()
+            .map(|description| format!(": {}", description))
+            .unwrap_or_default(),
+        project.id
+    )
 }
 
 impl ExecutableCommand for CommandProject {
-    type Error = std::io::Error;
+    type Error = crate::error::Error;
     type Output = ();
     fn execute(
         &self,
-        _config: &AppConfig,
+        config: &AppConfig,
         job_config: &mut JobConfig,
-        _manager: Manager,
+        manager: Manager,
     ) -> Result<Self::Output, Self::Error> {
         match self {
-            CommandProject::List => {
+            CommandProject::List { by_client } => {
                 if job_config.projects.is_empty() {
                     println!("No projects found");
                     return Ok(());
+                } else if *by_client {
+                    println!("Projects by client:");
+                    let clients = job_config
+                        .projects
+                        .iter()
+                        .into_group_map_by(|project| project.inner.client.clone());
+                    for (client, projects) in clients.into_iter().sorted_by(|a, b| a.0.cmp(&b.0)) {
+                        println!(" {}", client.as_deref().unwrap_or("(none)"));
+                        for project in projects {
+                            println!("  - {}", format_project(project));
+                        }
+                    }
                 } else {
                     println!("Projects:");
                     for project in &job_config.projects {
-                        println!(
-                            " - {}{} ({})",
-                            project.inner.name,
-                            project
-                                .inner
-                                .description
-                                .as_ref()
-                                .map(|description| format!(": {}", description))
-                                .unwrap_or_default(),
-                            project.id
-                        );
+                        println!(" - {}", format_project(project));
                     }
                 }
             }
+            CommandProject::Edit {
+                project,
+                name,
+                description,
+                no_description,
+                rewrite_references,
+                client,
+                no_client,
+                target_share,
+                no_target_share,
+            } => {
+                let previous = job_config.clone();
+
+                let name = match name {
+                    Some(name) => Some(
+                        require_name(name, config.max_name_length)
+                            .inspect_err(|e| error!("Invalid project name '{}': {}", name, e))?,
+                    ),
+                    None => None,
+                };
+                if let Some(name) = &name
+                    && let Some(existing) = job_config
+                        .projects
+                        .iter()
+                        .find(|p| p.inner.name == *name && !p.identifier_matches(project))
+                {
+                    error!(
+                        "Project with name '{}' already exists: {}",
+                        name, existing.id
+                    );
+                    return Err(std::io::Error::other("Project already exists").into());
+                }
+
+                let Some(target) = job_config.resolve_project_mut(project) else {
+                    error!("{}", unknown_project_message(job_config, project));
+                    return Err(Error::UnknownProject(project.clone()));
+                };
+
+                let id = target.id;
+                let old_name = target.inner.name.clone();
+                if let Some(name) = name {
+                    target.inner.name = name;
+                }
+                if let Some(description) = description {
+                    target.inner.description = Some(description.clone());
+                }
+                if *no_description {
+                    target.inner.description = None;
+                }
+                if let Some(client) = client {
+                    target.inner.client = Some(client.clone());
+                }
+                if *no_client {
+                    target.inner.client = None;
+                }
+                if let Some(target_share) = target_share {
+                    target.inner.target_share = Some(*target_share);
+                }
+                if *no_target_share {
+                    target.inner.target_share = None;
+                }
+                let updated = format_project(target);
+
+                if let Err(e) = job_config.validate_target_shares() {
+                    error!("{}", e);
+                    *job_config = previous;
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e).into());
+                }
+
+                println!("Updated project: {}", updated);
+
+                if *rewrite_references {
+                    let mut manager = manager;
+                    let rewritten =
+                        rewrite_references_by_name(&mut manager, job_config, &old_name, id);
+                    println!(
+                        "Rewrote {} references to '{}' to the project's UUID",
+                        rewritten, old_name
+                    );
+                }
+            }
             CommandProject::Add { name, description } => {
+                let name = &require_name(name, config.max_name_length)
+                    .inspect_err(|e| error!("Invalid project name '{}': {}", name, e))?;
                 if job_config.projects.iter().any(|p| p.inner.name == *name) {
                     error!("Project with name '{}' already exists", name);
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Project already exists",
-                    ));
+                    return Err(std::io::Error::other("Project already exists").into());
                 }
 
                 let new_project = Project {
@@ -75,36 +408,561 @@ impl ExecutableCommand for CommandProject {
                     inner: ProjectInner {
                         name: name.clone(),
                         description: description.clone(),
+                        client: None,
+                        target_share: None,
                     },
                 };
                 job_config.projects.push(new_project);
 
                 println!("Added new project: {}", name);
             }
-            CommandProject::Remove { project } => {
-                let len_before = job_config.projects.len();
-
-                job_config.projects.retain(|p| match project {
-                    Identifier::Uuid(id) => &p.id != id,
-                    Identifier::ByName(name) => &p.inner.name != name,
-                });
-
-                let len_after = job_config.projects.len();
+            CommandProject::Remove {
+                project,
+                reassign_to,
+                abort_if_referenced,
+            } => {
+                let Some(removed) = job_config.resolve_project(project).cloned() else {
+                    error!("{}", unknown_project_message(job_config, project));
+                    return Err(Error::UnknownProject(project.clone()));
+                };
+                let replacement = match reassign_to {
+                    Some(target) => {
+                        let Some(replacement) = job_config.resolve_project(target) else {
+                            error!("{}", unknown_project_message(job_config, target));
+                            return Err(Error::UnknownProject(target.clone()));
+                        };
+                        if replacement.id == removed.id {
+                            error!("Cannot reassign the references of a project to itself");
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidInput,
+                                "Replacement is the removed project",
+                            )
+                            .into());
+                        }
+                        Some(Identifier::Uuid(replacement.id))
+                    }
+                    None => None,
+                };
 
-                if len_before == len_after {
-                    error!("Project not found: {:?}", project);
+                let is_removed = |project: &Identifier| {
+                    job_config
+                        .resolve_project(project)
+                        .is_some_and(|p| p.id == removed.id)
+                };
+                let mut manager = manager;
+                let references = project_references(&manager, is_removed);
+                if *abort_if_referenced && !references.is_empty() {
+                    error!(
+                        "The project {} is still referenced by {} activities and blockers",
+                        removed.inner.name,
+                        references.len()
+                    );
+                    for (date, ids) in &references.iter().chunk_by(|(date, _)| *date) {
+                        println!(" - {}: {}", date, ids.count());
+                    }
                     return Err(std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        "Project not found",
-                    ));
-                } else {
-                    println!("Removed project: {:?}", project);
+                        std::io::ErrorKind::InvalidInput,
+                        "Project still referenced",
+                    )
+                    .into());
+                }
+                // the project is kept if any of its references cannot be changed
+                for date in references.iter().map(|(date, _)| *date).dedup() {
+                    if let Some(day) = manager.day(date) {
+                        ensure_unlocked(date, day)?;
+                    }
                 }
+                let replaced = replace_references(
+                    &mut manager,
+                    job_config,
+                    &removed.inner.name,
+                    is_removed,
+                    replacement.as_ref(),
+                );
 
-                // todo remove reference from other activities
+                job_config.projects.retain(|p| p.id != removed.id);
+                println!("Removed project: {}", removed.inner.name);
+                if replaced > 0 {
+                    match reassign_to {
+                        Some(target) => {
+                            println!("Reassigned {} references to {}", replaced, target)
+                        }
+                        None => println!("Removed {} references", replaced),
+                    }
+                }
+            }
+            CommandProject::Use { project, .. } => {
+                let path = WorkspaceState::path(config, manager.data_path());
+                let mut state = WorkspaceState::load(&path)?;
+                match project {
+                    Some(project) => {
+                        let Some(project) = job_config.resolve_project(project) else {
+                            error!("{}", unknown_project_message(job_config, project));
+                            return Err(Error::UnknownProject(project.clone()));
+                        };
+                        // by id, so that renaming the project keeps it current
+                        state.current_project = Some(Identifier::Uuid(project.id));
+                        println!("Current project: {}", project.inner.name);
+                    }
+                    None => {
+                        state.current_project = None;
+                        println!("Cleared the current project.");
+                    }
+                }
+                state.save(&path)?;
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Date, Month, Time};
+
+    #[test]
+    fn test_unknown_project_message() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        for name in ["website", "webshop", "backend"] {
+            dir.execute(&CommandProject::parse_from(["project", "add", name]))
+                .unwrap();
+        }
+        let job_config = dir.job_config().unwrap();
+        let message =
+            |name: &str| unknown_project_message(&job_config, &Identifier::ByName(name.into()));
+
+        assert_eq!(
+            message("webiste"),
+            "Unknown project 'webiste'. Did you mean: website?"
+        );
+        assert_eq!(
+            message("Backend"),
+            "Unknown project 'Backend'. Did you mean: backend?"
+        );
+        assert_eq!(
+            message("infrastructure"),
+            "Unknown project 'infrastructure'."
+        );
+        assert!(
+            dir.execute(&CommandProject::parse_from([
+                "project", "remove", "@webiste"
+            ]))
+            .is_err()
+        );
+    }
+
+    fn setup_rename() -> (ManualClock, TempDataDir, Date) {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandProject::parse_from(["project", "add", "websit"]))
+            .unwrap();
+        (clock, dir, date)
+    }
+
+    fn rename(dir: &TempDataDir, args: &[&str]) -> crate::error::Result<()> {
+        dir.execute(&CommandProject::parse_from(
+            ["project", "edit", "@websit", "-n", "website"]
+                .iter()
+                .chain(args),
+        ))
+    }
+
+    #[test]
+    fn test_rename_keeps_references_by_default() {
+        let (clock, dir, date) = setup_rename();
+        let _guard = clock.install();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-p", "@websit",
+        ]))
+        .unwrap();
+
+        rename(&dir, &[]).unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(
+            day.activities[0].projects,
+            [Identifier::ByName("websit".into())]
+        );
+        assert!(
+            job_config
+                .resolve_project(&day.activities[0].projects[0])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_rename_rewrites_references() {
+        let (clock, dir, date) = setup_rename();
+        let _guard = clock.install();
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "-p",
+            "@websit",
+            "-w",
+            "@websit=2",
+        ]))
+        .unwrap();
+        dir.execute(&CommandProject::parse_from(["project", "add", "website2"]))
+            .unwrap();
+
+        // duplicate names are rejected
+        assert!(
+            dir.execute(&CommandProject::parse_from([
+                "project", "edit", "@websit", "-n", "website2"
+            ]))
+            .is_err()
+        );
+        rename(&dir, &["--rewrite-references"]).unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        let project = job_config
+            .resolve_project(Identifier::ByName("website".into()))
+            .unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities[0].projects, [Identifier::Uuid(project.id)]);
+        assert_eq!(
+            day.activities[0].weights.get(&Identifier::Uuid(project.id)),
+            Some(&2.0)
+        );
+        assert!(
+            CommandProject::try_parse_from(["project", "edit", "@website", "--rewrite-references"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_clear_description() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandProject::parse_from([
+            "project",
+            "add",
+            "website",
+            "Company site",
+        ]))
+        .unwrap();
+
+        dir.execute(&CommandProject::parse_from([
+            "project",
+            "edit",
+            "@website",
+            "--clear-description",
+        ]))
+        .unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        let project = job_config
+            .resolve_project(Identifier::ByName("website".into()))
+            .unwrap();
+        assert_eq!(project.inner.description, None);
+    }
+
+    #[test]
+    fn test_edit_client() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandProject::parse_from(["project", "add", "website"]))
+            .unwrap();
+
+        dir.execute(&CommandProject::parse_from([
+            "project", "edit", "@website", "--client", "acme",
+        ]))
+        .unwrap();
+        let job_config = dir.job_config().unwrap();
+        let project = job_config
+            .resolve_project(Identifier::ByName("website".into()))
+            .unwrap();
+        assert_eq!(project.inner.client.as_deref(), Some("acme"));
+
+        dir.execute(&CommandProject::parse_from([
+            "project",
+            "edit",
+            "@website",
+            "--no-client",
+        ]))
+        .unwrap();
+        let job_config = dir.job_config().unwrap();
+        let project = job_config
+            .resolve_project(Identifier::ByName("website".into()))
+            .unwrap();
+        assert_eq!(project.inner.client, None);
+
+        assert!(
+            dir.execute(&CommandProject::parse_from([
+                "project", "edit", "@missing", "--client", "acme",
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_edit_target_share_validated() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        for name in ["projA", "projB"] {
+            dir.execute(&CommandProject::parse_from(["project", "add", name]))
+                .unwrap();
+        }
+
+        dir.execute(&CommandProject::parse_from([
+            "project", "edit", "@projA", "-t", "70",
+        ]))
+        .unwrap();
+        let result = dir.execute(&CommandProject::parse_from([
+            "project", "edit", "@projB", "-t", "40",
+        ]));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+        let job_config = dir.job_config().unwrap();
+        let share = |name: &str| {
+            job_config
+                .resolve_project(Identifier::ByName(name.into()))
+                .unwrap()
+                .inner
+                .target_share
+        };
+        assert_eq!(share("projA"), Some(70.0));
+        assert_eq!(share("projB"), None);
+    }
+
+    #[test]
+    fn test_project_names_are_normalized() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandProject::parse_from([
+            "project",
+            "add",
+            "  web   site ",
+        ]))
+        .unwrap();
+        assert!(
+            dir.job_config()
+                .unwrap()
+                .resolve_project(Identifier::ByName("web site".into()))
+                .is_some()
+        );
+
+        for args in [["project", "add", ""], ["project", "add", " \t "]] {
+            let result = dir.execute(&CommandProject::parse_from(args));
+            assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        }
+        let result = dir.execute(&CommandProject::parse_from([
+            "project",
+            "edit",
+            "@web site",
+            "--name",
+            "  ",
+        ]));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(dir.job_config().unwrap().projects.len(), 1);
+    }
+
+    #[test]
+    fn test_use_and_clear_current_project() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandProject::parse_from(["project", "add", "projA"]))
+            .unwrap();
+        let current = || {
+            let job_config = dir.job_config().unwrap();
+            WorkspaceState::current_project(dir.app_config(), dir.path(), &job_config)
+                .unwrap()
+                .map(|project| project.inner.name.clone())
+        };
+        assert_eq!(current(), None);
+
+        dir.execute(&CommandProject::parse_from(["project", "use", "@projA"]))
+            .unwrap();
+        assert_eq!(current().as_deref(), Some("projA"));
+
+        // the project is remembered by id and survives a rename
+        dir.execute(&CommandProject::parse_from([
+            "project", "edit", "@projA", "-n", "projB",
+        ]))
+        .unwrap();
+        assert_eq!(current().as_deref(), Some("projB"));
+
+        let result = dir.execute(&CommandProject::parse_from(["project", "use", "@missing"]));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(current().as_deref(), Some("projB"));
+
+        dir.execute(&CommandProject::parse_from(["project", "use", "--none"]))
+            .unwrap();
+        assert_eq!(current(), None);
+
+        assert!(CommandProject::try_parse_from(["project", "use"]).is_err());
+        assert!(CommandProject::try_parse_from(["project", "use", "@projB", "--none"]).is_err());
+    }
+
+    /// `website` is referenced by name on the first day and by UUID on the second day
+    fn setup_remove() -> (TempDataDir, Date, Date, Uuid) {
+        let first = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let second = first.next_day().unwrap();
+        let clock = ManualClock::new(at(first, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        for name in ["website", "backend"] {
+            dir.execute(&CommandProject::parse_from(["project", "add", name]))
+                .unwrap();
+        }
+        let website = dir
+            .job_config()
+            .unwrap()
+            .resolve_project(Identifier::ByName("website".into()))
+            .unwrap()
+            .id;
+
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "-p",
+            "@website",
+            "-w",
+            "@website=2",
+        ]))
+        .unwrap();
+        clock.set(at(second, Time::from_hms(9, 0, 0).unwrap()));
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "-p",
+            &website.to_string(),
+            "-p",
+            "@backend",
+        ]))
+        .unwrap();
+        (dir, first, second, website)
+    }
+
+    #[test]
+    fn test_remove_drops_references() {
+        let (dir, first, second, _) = setup_remove();
+        dir.execute(&CommandProject::parse_from([
+            "project", "remove", "website",
+        ]))
+        .unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        assert!(
+            job_config
+                .resolve_project(Identifier::ByName("website".into()))
+                .is_none()
+        );
+        let day = dir.day(first).unwrap().unwrap();
+        assert!(day.activities[0].projects.is_empty());
+        assert!(day.activities[0].weights.is_empty());
+        let day = dir.day(second).unwrap().unwrap();
+        assert_eq!(
+            day.activities[0].projects,
+            [Identifier::ByName("backend".into())]
+        );
+    }
+
+    #[test]
+    fn test_remove_reassigns_references() {
+        let (dir, first, second, _) = setup_remove();
+        let backend = dir
+            .job_config()
+            .unwrap()
+            .resolve_project(Identifier::ByName("backend".into()))
+            .unwrap()
+            .id;
+        dir.execute(&CommandProject::parse_from([
+            "project",
+            "remove",
+            "website",
+            "--reassign-to",
+            "backend",
+        ]))
+        .unwrap();
+
+        let day = dir.day(first).unwrap().unwrap();
+        assert_eq!(day.activities[0].projects, [Identifier::Uuid(backend)]);
+        assert_eq!(
+            day.activities[0].weights.get(&Identifier::Uuid(backend)),
+            Some(&2.0)
+        );
+        // already referenced by name, the reference is not duplicated
+        let day = dir.day(second).unwrap().unwrap();
+        assert_eq!(
+            day.activities[0].projects,
+            [Identifier::ByName("backend".into())]
+        );
+        assert!(
+            dir.execute(&CommandProject::parse_from([
+                "project",
+                "remove",
+                "backend",
+                "--reassign-to",
+                "backend",
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_remove_aborts_if_referenced() {
+        let (dir, first, second, website) = setup_remove();
+        let result = dir.execute(&CommandProject::parse_from([
+            "project",
+            "remove",
+            "website",
+            "--abort-if-referenced",
+        ]));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+        let job_config = dir.job_config().unwrap();
+        assert!(
+            job_config
+                .resolve_project(Identifier::Uuid(website))
+                .is_some()
+        );
+        let day = dir.day(first).unwrap().unwrap();
+        assert_eq!(
+            day.activities[0].projects,
+            [Identifier::ByName("website".into())]
+        );
+        let day = dir.day(second).unwrap().unwrap();
+        assert_eq!(day.activities[0].projects[0], Identifier::Uuid(website));
+        assert!(
+            CommandProject::try_parse_from([
+                "project",
+                "remove",
+                "website",
+                "--abort-if-referenced",
+                "--reassign-to",
+                "backend",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_remove_fails_on_locked_days() {
+        let (dir, first, second, website) = setup_remove();
+        dir.execute(&crate::cli::CommandDay::parse_from([
+            "day",
+            "lock",
+            &first.to_string(),
+        ]))
+        .unwrap();
+
+        let result = dir.execute(&CommandProject::parse_from([
+            "project", "remove", "website",
+        ]));
+        assert!(result.is_err());
+        assert!(
+            dir.job_config()
+                .unwrap()
+                .resolve_project(Identifier::Uuid(website))
+                .is_some()
+        );
+        let day = dir.day(second).unwrap().unwrap();
+        assert_eq!(day.activities[0].projects[0], Identifier::Uuid(website));
+    }
+}
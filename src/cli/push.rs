@@ -1,19 +1,35 @@
 use crate::cli::ExecutableCommand;
-use crate::data::activity::Activity;
+use crate::cli::args::{
+    TimeArg, parse_identifier_arg, parse_project_weight, parse_time_or_ago_arg,
+};
+use crate::cli::class::unknown_class_message;
+use crate::cli::fixup::stale_warning;
+use crate::cli::pop::stopped_message;
+use crate::cli::project::unknown_project_message;
+use crate::cli::show::format_time;
+use crate::data::activity::{Activity, ActivityOrigin};
 use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
 use crate::data::identifier::Identifier;
 use crate::data::interval::Interval;
 use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
 use crate::data::manager::Manager;
+use crate::data::project::{Project, ProjectInner};
+use crate::data::text::{normalize_name, require_name};
+use crate::data::workspace::WorkspaceState;
+use crate::error::Error;
 use clap::Parser;
-use log::{error, info};
-use time::OffsetDateTime;
+use itertools::Itertools;
+use log::{error, info, warn};
+use time::{Date, Time};
 use uuid::Uuid;
 
 #[derive(Parser)]
 pub struct CommandPush {
-    /// Project name
-    #[arg(short, long)]
+    /// Project name, defaults to the current project (see `timetrax project use`)
+    #[arg(short, long, value_parser = parse_identifier_arg)]
     project: Vec<Identifier>,
     /// Short name for the activity
     #[arg(short, long)]
@@ -24,66 +40,960 @@ pub struct CommandPush {
     /// Classification of the activity
     #[arg(short, long = "class")]
     classification: Identifier,
+    /// Relative weight of a project for weighted attribution (PROJECT=WEIGHT)
+    #[arg(short, long, value_parser = parse_project_weight)]
+    weight: Vec<(Identifier, f32)>,
+    /// Tag the activity, may be given multiple times
+    #[arg(short, long)]
+    tag: Vec<String>,
+    /// Start of the activity (HH:MM[:SS]) or how long ago it started (e.g. -15m), defaults to now
+    #[arg(long, value_parser = parse_time_or_ago_arg, allow_hyphen_values = true)]
+    at: Option<TimeArg>,
+    /// Allow --at to start the activity in the future
+    #[arg(long, requires = "at")]
+    allow_future: bool,
+    /// Create projects given by name which do not exist yet
+    #[arg(long)]
+    create_project: bool,
+    /// Stop the open activities when the new one starts, implied by exclusive classes
+    #[arg(short = 'x', long)]
+    exclusive: bool,
+}
+
+/// add a project named `name` to the job config for `--create-project`
+fn create_project(
+    config: &AppConfig,
+    job_config: &mut JobConfig,
+    name: &str,
+) -> std::io::Result<()> {
+    let normalized = require_name(name, config.max_name_length)
+        .inspect_err(|e| error!("Invalid project name '{}': {}", name, e))?;
+    // the activity refers to the project by the name as given
+    if normalized != name {
+        error!(
+            "Cannot create project '{}', project names must be normalized like '{}'",
+            name, normalized
+        );
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Project name not normalized",
+        ));
+    }
+
+    job_config.projects.push(Project {
+        id: Uuid::new_v4(),
+        inner: ProjectInner {
+            name: normalized,
+            description: None,
+            client: None,
+            target_share: None,
+        },
+    });
+    println!("Created new project: {}", name);
+    Ok(())
+}
+
+/// start of an activity pushed `at` the given time, refusing starts in the future
+/// unless `allow_future` and relative starts reaching into the previous day
+fn start_at(at: TimeArg, boundary: Option<Time>, allow_future: bool) -> std::io::Result<Time> {
+    let start = at.resolve_within_day(boundary)?;
+    let now = local_time::to_day_time(local_time::now_time(), boundary);
+    if !allow_future && local_time::to_day_time(start, boundary) > now {
+        error!(
+            "Refusing to start at {}, which is in the future, pass --allow-future to do so",
+            format_time(start)
+        );
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Start lies in the future",
+        ));
+    }
+    Ok(start)
+}
+
+/// completed activities of the day with the class of `pushed` which end after it starts
+fn overlapping_completed<'a>(
+    job_config: &JobConfig,
+    day: &'a DayInner,
+    pushed: &Activity,
+) -> Vec<&'a Activity> {
+    let boundary = job_config.day_boundary;
+    let class = |activity: &Activity| job_config.resolve_class(&activity.class).map(|c| c.id);
+    let start = local_time::to_day_time(pushed.time.start, boundary);
+    day.activities
+        .iter()
+        .filter(|activity| class(activity) == class(pushed))
+        .filter(|activity| {
+            activity
+                .time
+                .end
+                .is_some_and(|end| local_time::to_day_time(end, boundary) > start)
+        })
+        .collect()
+}
+
+/// warn about open activities left on previous days,
+/// refuses to continue instead if `block_on_stale` is configured
+pub fn check_stale(config: &AppConfig, manager: &Manager, today: Date) -> std::io::Result<()> {
+    let stale = manager.stale_open_activities(today);
+    if stale.is_empty() {
+        return Ok(());
+    }
+
+    print!("{}", stale_warning(&stale));
+
+    if config.block_on_stale {
+        error!("Refusing to push while previous days have open activities");
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Previous day has open activities",
+        ));
+    }
+    Ok(())
+}
+
+/// the most recently completed activity of the day if it has the class, name, projects and
+/// tags of `pushed` and ended at most `auto_merge_tolerance` before `pushed` starts
+fn adjacent_identical<'a>(
+    job_config: &JobConfig,
+    day: &'a DayInner,
+    pushed: &Activity,
+) -> Option<&'a Activity> {
+    let boundary = job_config.day_boundary;
+    let end_of = |activity: &Activity| {
+        activity
+            .time
+            .end
+            .map(|end| local_time::to_day_time(end, boundary))
+    };
+    let previous = day
+        .activities
+        .iter()
+        .filter(|activity| activity.time.is_complete())
+        .max_by_key(|activity| end_of(activity))?;
+
+    let gap = local_time::to_day_time(pushed.time.start, boundary) - end_of(previous)?;
+    if gap.is_negative() || gap > job_config.auto_merge_tolerance {
+        return None;
+    }
+
+    let class = |activity: &Activity| job_config.resolve_class(&activity.class).map(|c| c.id);
+    let projects = |activity: &Activity| {
+        activity
+            .projects
+            .iter()
+            .map(|project| job_config.resolve_project(project).map(|p| p.id))
+            .sorted()
+            .collect_vec()
+    };
+    let tags = |activity: &Activity| activity.tags.iter().sorted().dedup().cloned().collect_vec();
+    let identical = class(previous) == class(pushed)
+        && previous.name == pushed.name
+        && projects(previous) == projects(pushed)
+        && tags(previous) == tags(pushed);
+    identical.then_some(previous)
 }
 
 impl ExecutableCommand for CommandPush {
-    type Error = std::io::Error;
+    type Error = crate::error::Error;
     type Output = ();
     fn execute(
         &self,
-        _config: &AppConfig,
+        config: &AppConfig,
         job_config: &mut JobConfig,
         mut manager: Manager,
     ) -> Result<Self::Output, Self::Error> {
-        let today = OffsetDateTime::now_local()
-            .unwrap_or_else(|e| {
-                error!("Failed to get local time. Falling back to UTC: {}", e);
-                OffsetDateTime::now_utc()
-            })
-            .date();
-
-        if let None = job_config.resolve_class(&self.classification) {
+        let today = job_config.today();
+
+        let Some(class) = job_config.resolve_class(&self.classification) else {
             error!(
-                "Failed to resolve classification: {:?}",
-                self.classification
+                "{}",
+                unknown_class_message(job_config, &self.classification)
             );
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to resolve classification",
-            ));
+            return Err(Error::UnknownClass(self.classification.clone()));
         };
+        let exclusive = self.exclusive || class.inner.exclusive;
 
-        if let Err(err) = self
-            .project
-            .iter()
-            .map(|id| match job_config.resolve_project(id) {
-                Some(p) => Ok(p),
-                None => {
-                    error!("Failed to resolve project: {:?}", id);
-                    Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Failed to resolve project",
-                    ))
+        // printed once the day is saved
+        let mut messages = vec![];
+        let projects = match self.project.as_slice() {
+            [] => {
+                WorkspaceState::current_project_or_clear(config, manager.data_path(), job_config)?
+                    .inspect(|project| {
+                        messages.push(format!("Using the current project {}", project.inner.name))
+                    })
+                    .map(|project| Identifier::Uuid(project.id))
+                    .into_iter()
+                    .collect()
+            }
+            projects => projects.to_vec(),
+        };
+
+        for id in &projects {
+            if job_config.resolve_project(id).is_some() {
+                continue;
+            }
+            match id {
+                // projects given by UUID are never created, the UUID is most likely a typo
+                Identifier::ByName(name) if self.create_project => {
+                    create_project(config, job_config, name)?;
+                }
+                _ => {
+                    error!("{}", unknown_project_message(job_config, id));
+                    return Err(Error::UnknownProject(id.clone()));
                 }
-            })
-            .collect::<Result<Vec<_>, _>>()
+            }
+        }
+
+        if let Some((project, _)) = self
+            .weight
+            .iter()
+            .find(|(project, _)| !projects.contains(project))
         {
-            return Err(err);
+            error!("Weight given for project {} which is not pushed", project);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Weight given for unknown project",
+            )
+            .into());
         }
 
-        let today = manager.get_or_create_day_mut(today);
+        check_stale(config, &manager, today)?;
+
+        let mut name = match &self.name {
+            Some(name) => normalize_name(name, config.max_name_length)
+                .inspect_err(|e| error!("Invalid name '{}': {}", name, e))?,
+            None => None,
+        };
 
-        let activity = Activity {
+        let now = local_time::now();
+        let mut activity = Activity {
             id: Uuid::new_v4(),
             class: self.classification.clone(),
-            name: self.name.clone(),
-            projects: self.project.clone(),
-            time: Interval::start_now(),
+            name: name.clone(),
+            projects,
+            weights: self.weight.iter().cloned().collect(),
+            description: self.description.clone(),
+            created_at: Some(now),
+            modified_at: None,
+            // the first spelling of a tag wins
+            tags: self
+                .tag
+                .iter()
+                .unique_by(|tag| tag.to_lowercase())
+                .cloned()
+                .collect(),
+            evidence: vec![],
+            origin: Some(match self.at {
+                Some(_) => ActivityOrigin::Explicit,
+                None => ActivityOrigin::Live,
+            }),
+            extra: Default::default(),
+            time: match self.at {
+                Some(at) => Interval {
+                    start: start_at(at, job_config.day_boundary, self.allow_future)?,
+                    end: None,
+                },
+                None => Interval::start_now(),
+            },
         };
 
+        for overlapped in
+            overlapping_completed(job_config, manager.get_or_create_day_ref(today), &activity)
+        {
+            warn!(
+                "The new activity starts before {} ends, both have the same class",
+                overlapped
+            );
+        }
+
+        if exclusive {
+            // the stopped activities end where the new one starts, without gap or overlap
+            let boundary = job_config.day_boundary;
+            let start = local_time::to_day_time(activity.time.start, boundary);
+            if let Some(later) = manager
+                .get_or_create_day_ref(today)
+                .activities
+                .iter()
+                .filter(|other| !other.time.is_complete())
+                .find(|other| local_time::to_day_time(other.time.start, boundary) > start)
+            {
+                error!(
+                    "The open activity {} starts after the new activity, it cannot be stopped when the new one starts",
+                    later
+                );
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Open activity starts after the new activity",
+                )
+                .into());
+            }
+            for stopped in manager
+                .get_or_create_day_mut(today)
+                .activities
+                .iter_mut()
+                .filter(|other| !other.time.is_complete())
+            {
+                info!("Stopping activity for an exclusive push: {:?}", stopped);
+                stopped.time.complete_at(activity.time.start, boundary);
+                messages.push(stopped_message(stopped, boundary));
+            }
+        }
+
+        if job_config.auto_merge_adjacent
+            && let Some(previous) =
+                adjacent_identical(job_config, manager.get_or_create_day_ref(today), &activity)
+        {
+            let previous = previous.clone();
+            let mut reopened = previous.clone();
+            reopened.time.end = None;
+            reopened.modified_at = Some(now);
+
+            info!("Reopening activity instead of pushing an identical one: {reopened}");
+            let day = manager.get_or_create_day_mut(today);
+            if let Some(stored) = day.activities.iter_mut().find(|a| a.id == reopened.id) {
+                *stored = reopened.clone();
+            }
+            manager.save()?;
+            Journal::new(config, manager.data_path()).append(&JournalEntry {
+                at: now,
+                command: "push".to_string(),
+                date: today,
+                removed: vec![previous.clone()],
+                added: vec![reopened],
+            })?;
+            for message in &messages {
+                println!("{}", message);
+            }
+            println!(
+                "Continuing {}, which ended just before (auto_merge_adjacent)",
+                previous
+            );
+            return Ok(());
+        }
+
+        if job_config.unique_names_per_day
+            && let Some(requested) = &name
+        {
+            let unique = manager
+                .get_or_create_day_ref(today)
+                .unique_activity_name(requested);
+            if unique != *requested {
+                warn!(
+                    "An activity named '{}' already exists today, using '{}' instead",
+                    requested, unique
+                );
+                name = Some(unique);
+            }
+        }
+
+        activity.name = name;
+        let today = manager.get_or_create_day_mut(today);
+
         info!("Pushing new activity: {activity}");
         today.activities.push(activity);
+        manager.save()?;
+        for message in messages {
+            println!("{}", message);
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPop;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Date, Month, Time};
+
+    const STALE_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/stale_day.json"
+    ));
+
+    #[test]
+    fn test_push_starts_at_now() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding",
+        ]))
+        .unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert_eq!(day.activities[0].name.as_deref(), Some("coding"));
+        assert_eq!(
+            day.activities[0].time.start,
+            Time::from_hms(9, 0, 0).unwrap()
+        );
+        assert!(!day.activities[0].time.is_complete());
+    }
+
+    #[test]
+    fn test_push_unknown_class_or_project() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        let result = dir.execute(&CommandPush::parse_from(["push", "-c", "@wrok"]));
+        assert!(
+            matches!(result, Err(Error::UnknownClass(Identifier::ByName(ref name))) if name == "wrok")
+        );
+        let result = dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-p", "@nowhere",
+        ]));
+        assert!(matches!(result, Err(Error::UnknownProject(_))));
+        assert!(dir.day(date).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_push_at_a_given_time() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "early", "--at", "8:15",
+        ]))
+        .unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@break", "--at", "-15m",
+        ]))
+        .unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(
+            day.activities[0].time.start,
+            Time::from_hms(8, 15, 0).unwrap()
+        );
+        assert_eq!(
+            day.activities[1].time.start,
+            Time::from_hms(8, 45, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_push_at_rejects_the_future() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        assert!(
+            dir.execute(&CommandPush::parse_from([
+                "push", "-c", "@work", "--at", "9:30"
+            ]))
+            .is_err()
+        );
+        // relative starts must not reach into the previous day
+        assert!(
+            dir.execute(&CommandPush::parse_from([
+                "push", "-c", "@work", "--at", "-10h"
+            ]))
+            .is_err()
+        );
+        assert!(
+            dir.day(date)
+                .unwrap()
+                .is_none_or(|day| day.activities.is_empty())
+        );
+
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "--at",
+            "9:30",
+            "--allow-future",
+        ]))
+        .unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(
+            day.activities[0].time.start,
+            Time::from_hms(9, 30, 0).unwrap()
+        );
+        assert!(CommandPush::try_parse_from(["push", "-c", "@work", "--allow-future"]).is_err());
+    }
+
+    #[test]
+    fn test_push_at_finds_overlapped_activities_of_the_class() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        for class in ["@work", "@break"] {
+            dir.execute(&CommandPush::parse_from(["push", "-c", class]))
+                .unwrap();
+            clock.advance(time::Duration::hours(1));
+            dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+        }
+
+        let job_config = dir.job_config().unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        let pushed = |class: &str, start: u8| Activity {
+            class: Identifier::ByName(class.into()),
+            time: Interval {
+                start: Time::from_hms(start, 30, 0).unwrap(),
+                end: None,
+            },
+            ..day.activities[0].clone()
+        };
+        let overlapped = overlapping_completed(&job_config, &day, &pushed("work", 9));
+        assert_eq!(overlapped.len(), 1);
+        assert_eq!(overlapped[0].id, day.activities[0].id);
+        assert!(overlapping_completed(&job_config, &day, &pushed("work", 10)).is_empty());
+        assert!(overlapping_completed(&job_config, &day, &pushed("meeting", 9)).is_empty());
+    }
+
+    #[test]
+    fn test_push_exclusive_stops_open_activities() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "first",
+        ]))
+        .unwrap();
+        clock.advance(time::Duration::hours(1));
+        // not exclusive, both stay open
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "second",
+        ]))
+        .unwrap();
+        clock.advance(time::Duration::hours(1));
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(day.activities.iter().all(|a| !a.time.is_complete()));
+
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@break", "-n", "third", "-x",
+        ]))
+        .unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        let eleven = Time::from_hms(11, 0, 0).unwrap();
+        assert_eq!(day.activities[0].time.end, Some(eleven));
+        assert_eq!(day.activities[1].time.end, Some(eleven));
+        assert_eq!(day.activities[2].time.start, eleven);
+        assert!(!day.activities[2].time.is_complete());
+    }
+
+    #[test]
+    fn test_push_exclusive_refuses_open_activities_starting_later() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(10, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+
+        let result = dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@break", "-x", "--at", "09:00",
+        ]));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert!(!day.activities[0].time.is_complete());
+    }
+
+    #[test]
+    fn test_push_of_an_exclusive_class() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&crate::cli::CommandClass::parse_from([
+            "class",
+            "edit",
+            "@break",
+            "--exclusive",
+            "true",
+        ]))
+        .unwrap();
+
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.advance(time::Duration::minutes(90));
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@break"]))
+            .unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(
+            day.activities[0].time.end,
+            Some(day.activities[1].time.start)
+        );
+        assert_eq!(
+            day.activities[0].time.duration(),
+            Some(time::Duration::minutes(90))
+        );
+    }
+
+    #[test]
+    fn test_push_creates_missing_projects() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        // not without the flag
+        assert!(
+            dir.execute(&CommandPush::parse_from([
+                "push",
+                "-c",
+                "@work",
+                "-p",
+                "@newclient"
+            ]))
+            .is_err()
+        );
+        assert!(
+            dir.job_config()
+                .unwrap()
+                .resolve_project(Identifier::ByName("newclient".into()))
+                .is_none()
+        );
+        // UUIDs are never created
+        assert!(
+            dir.execute(&CommandPush::parse_from([
+                "push",
+                "-c",
+                "@work",
+                "-p",
+                "00000000-0000-4000-8000-000000000001",
+                "--create-project",
+            ]))
+            .is_err()
+        );
+
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "-p",
+            "@newclient",
+            "--create-project",
+        ]))
+        .unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        let project = job_config
+            .resolve_project(Identifier::ByName("newclient".into()))
+            .unwrap();
+        assert_eq!(project.inner.description, None);
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert_eq!(
+            job_config
+                .resolve_project(&day.activities[0].projects[0])
+                .unwrap()
+                .id,
+            project.id
+        );
+    }
+
+    #[test]
+    fn test_push_stores_description_and_tags() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "-d",
+            "Pair programming on the parser",
+            "-t",
+            "Parser",
+            "-t",
+            "pairing",
+            "-t",
+            "parser",
+        ]))
+        .unwrap();
+
+        // reloaded from the saved day file
+        let manager = dir.manager().unwrap();
+        let activity = &manager.day(date).unwrap().activities[0];
+        assert_eq!(
+            activity.description.as_deref(),
+            Some("Pair programming on the parser")
+        );
+        assert_eq!(activity.tags, ["Parser", "pairing"]);
+    }
+
+    #[test]
+    fn test_push_unknown_class() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        assert!(
+            dir.execute(&CommandPush::parse_from(["push", "-c", "@unknown"]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_push_suffixes_duplicate_names() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let push = CommandPush::parse_from(["push", "-c", "@work", "-n", "meeting"]);
+
+        let dir = TempDataDir::new(&JobConfig {
+            unique_names_per_day: true,
+            ..JobConfig::default()
+        })
+        .unwrap();
+        for _ in 0..3 {
+            dir.execute(&push).unwrap();
+        }
+        let names = dir
+            .day(date)
+            .unwrap()
+            .unwrap()
+            .activities
+            .into_iter()
+            .map(|a| a.name.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["meeting", "meeting (2)", "meeting (3)"]);
+
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&push).unwrap();
+        dir.execute(&push).unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert!(
+            day.activities
+                .iter()
+                .all(|a| a.name.as_deref() == Some("meeting"))
+        );
+    }
+
+    #[test]
+    fn test_push_with_stale_previous_day() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", STALE_DAY).unwrap();
+        let push = CommandPush::parse_from(["push", "-c", "@work"]);
+
+        let blocking = AppConfig {
+            block_on_stale: true,
+            ..dir.app_config().clone()
+        };
+        let push_blocking = || {
+            push.execute(
+                &blocking,
+                &mut dir.job_config().unwrap(),
+                Manager::open(&blocking, dir.path()).unwrap(),
+            )
+        };
+        assert_eq!(
+            push_blocking().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert!(dir.day(date).unwrap().is_none());
+
+        // only warns by default
+        dir.execute(&push).unwrap();
+        assert_eq!(dir.day(date).unwrap().unwrap().activities.len(), 1);
+
+        // nothing ended after the stale activity started, the end must be given
+        assert!(
+            dir.execute(&CommandPop::parse_from(["pop", "--stale"]))
+                .is_err()
+        );
+        dir.execute(&CommandPop::parse_from(["pop", "--stale", "--at", "17:30"]))
+            .unwrap();
+
+        let previous = dir.day(date.previous_day().unwrap()).unwrap().unwrap();
+        assert_eq!(
+            previous.activities[1].time.end,
+            Some(Time::from_hms(17, 30, 0).unwrap())
+        );
+        assert!(
+            !dir.day(date).unwrap().unwrap().activities[0]
+                .time
+                .is_complete()
+        );
+        push_blocking().unwrap();
+    }
+
+    #[test]
+    fn test_push_normalizes_the_name() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let push = |name: &str| {
+            dir.execute(&CommandPush::parse_from([
+                "push", "-c", "@work", "-n", name,
+            ]))
+        };
+
+        push("  refactor \t  parser ").unwrap();
+        push("   ").unwrap();
+        let result = push(&"x".repeat(201));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+        let day = dir.day(date).unwrap().unwrap();
+        let names = day
+            .activities
+            .iter()
+            .map(|a| a.name.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(names, [Some("refactor parser"), None]);
+    }
+
+    #[test]
+    fn test_push_defaults_to_the_current_project() {
+        use crate::cli::CommandProject;
+
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let project = |args: &[&str]| {
+            dir.execute(&CommandProject::parse_from(["project"].iter().chain(args)))
+                .unwrap()
+        };
+        let push = |args: &[&str]| {
+            dir.execute(&CommandPush::parse_from(
+                ["push", "-c", "@work"].iter().chain(args),
+            ))
+            .unwrap()
+        };
+        let last_projects = || {
+            let job_config = dir.job_config().unwrap();
+            let day = dir.day(date).unwrap().unwrap();
+            day.activities
+                .last()
+                .unwrap()
+                .projects
+                .iter()
+                .map(|id| job_config.resolve_project(id).unwrap().inner.name.clone())
+                .collect::<Vec<_>>()
+        };
+        project(&["add", "projA"]);
+        project(&["add", "projB"]);
+
+        push(&[]);
+        assert!(last_projects().is_empty());
+
+        project(&["use", "@projA"]);
+        push(&[]);
+        assert_eq!(last_projects(), ["projA"]);
+        // explicit projects replace the current one
+        push(&["-p", "@projB"]);
+        assert_eq!(last_projects(), ["projB"]);
+
+        project(&["use", "--none"]);
+        push(&[]);
+        assert!(last_projects().is_empty());
+
+        // a removed current project is cleared instead of failing the push
+        project(&["use", "@projB"]);
+        project(&["remove", "@projB"]);
+        push(&[]);
+        assert!(last_projects().is_empty());
+        let state = WorkspaceState::path(dir.app_config(), dir.path());
+        assert_eq!(WorkspaceState::load(&state).unwrap().current_project, None);
+    }
+
+    fn merging_dir(date: Date) -> (ManualClock, TempDataDir) {
+        let clock = ManualClock::new(at(date, Time::from_hms(10, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig {
+            auto_merge_adjacent: true,
+            ..JobConfig::default()
+        })
+        .unwrap();
+        (clock, dir)
+    }
+
+    fn push_and_pop(dir: &TempDataDir, clock: &ManualClock, args: &[&str]) {
+        dir.execute(&CommandPush::parse_from(
+            ["push", "-c", "@work"].iter().chain(args),
+        ))
+        .unwrap();
+        clock.advance(time::Duration::minutes(30));
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+    }
+
+    #[test]
+    fn test_push_reopens_an_identical_adjacent_activity() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let (clock, dir) = merging_dir(date);
+        let _guard = clock.install();
+        push_and_pop(&dir, &clock, &["-n", "coding", "-t", "a", "-t", "b"]);
+
+        // pushed exactly when the previous one ended
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding", "-t", "b", "-t", "a",
+        ]))
+        .unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert!(!day.activities[0].time.is_complete());
+        assert_eq!(
+            day.activities[0].time.start,
+            Time::from_hms(10, 0, 0).unwrap()
+        );
+        assert_eq!(day.activities[0].modified_at, Some(local_time::now()));
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(entries.last().unwrap().command, "push");
+        assert!(entries.last().unwrap().removed[0].time.is_complete());
+        assert!(!entries.last().unwrap().added[0].time.is_complete());
+
+        // a gap within the tolerance is merged as well
+        clock.advance(time::Duration::minutes(30));
+        dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+        clock.advance(time::Duration::seconds(45));
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding", "-t", "a", "-t", "b",
+        ]))
+        .unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert!(!day.activities[0].time.is_complete());
+    }
+
+    #[test]
+    fn test_push_keeps_different_or_distant_activities_apart() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let (clock, dir) = merging_dir(date);
+        let _guard = clock.install();
+        push_and_pop(&dir, &clock, &["-n", "coding"]);
+
+        for args in [
+            ["push", "-c", "@work", "-n", "review"].as_slice(),
+            &["push", "-c", "@work", "-n", "coding", "-t", "urgent"],
+            &["push", "-c", "@break", "-n", "coding"],
+        ] {
+            dir.execute(&CommandPush::parse_from(args)).unwrap();
+            clock.advance(time::Duration::minutes(30));
+            dir.execute(&CommandPop::parse_from(["pop"])).unwrap();
+        }
+        assert_eq!(dir.day(date).unwrap().unwrap().activities.len(), 4);
+
+        // identical, but the gap exceeds the tolerance
+        clock.advance(time::Duration::minutes(2));
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@break", "-n", "coding",
+        ]))
+        .unwrap();
+        assert_eq!(dir.day(date).unwrap().unwrap().activities.len(), 5);
+
+        // without the opt-in nothing is merged
+        let (clock, dir) = (clock, TempDataDir::new(&JobConfig::default()).unwrap());
+        push_and_pop(&dir, &clock, &["-n", "coding"]);
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding",
+        ]))
+        .unwrap();
+        assert_eq!(dir.day(date).unwrap().unwrap().activities.len(), 2);
+    }
+}
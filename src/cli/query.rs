@@ -0,0 +1,249 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::format::format_duration_pretty;
+use crate::data::app_config::AppConfig;
+use crate::data::attribution::Attribution;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::query::{Query, QueryRow, QueryValue};
+use clap::Parser;
+use log::error;
+use serde_json::json;
+
+#[derive(Parser)]
+pub struct CommandQuery {
+    /// Query, e.g. "sum duration where class=@work and date in 2024-05 group by project"
+    expression: String,
+    /// How time of segments with multiple projects is attributed to them
+    #[arg(short, long, value_enum)]
+    attribution: Option<Attribution>,
+    /// Print the result as JSON array
+    #[arg(long)]
+    json: bool,
+}
+
+fn format_value(value: &QueryValue) -> String {
+    match value {
+        QueryValue::Duration(duration) => format_duration_pretty(duration, false),
+        QueryValue::Count(count) => count.to_string(),
+    }
+}
+
+/// one row per group, the groups padded to the same width
+fn render_table(rows: &[QueryRow]) -> String {
+    let width = rows
+        .iter()
+        .filter_map(|row| row.group.as_ref())
+        .map(|group| group.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let mut output = String::new();
+    for row in rows {
+        match &row.group {
+            Some(group) => output.push_str(&format!(
+                "{:<width$}  {}\n",
+                group,
+                format_value(&row.value),
+                width = width
+            )),
+            None => output.push_str(&format!("{}\n", format_value(&row.value))),
+        }
+    }
+    output
+}
+
+fn render_json(rows: &[QueryRow]) -> String {
+    let rows = rows
+        .iter()
+        .map(|row| {
+            let mut object = serde_json::Map::new();
+            if let Some(group) = &row.group {
+                object.insert("group".to_string(), json!(group));
+            }
+            match row.value {
+                QueryValue::Duration(duration) => object.insert(
+                    "duration_seconds".to_string(),
+                    json!(duration.whole_seconds()),
+                ),
+                QueryValue::Count(count) => object.insert("count".to_string(), json!(count)),
+            };
+            serde_json::Value::Object(object)
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::Array(rows).to_string()
+}
+
+impl CommandQuery {
+    fn render(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        manager: &Manager,
+    ) -> std::io::Result<String> {
+        let query = self.expression.parse::<Query>().map_err(|e| {
+            error!("Invalid query: {}", e);
+            // printed without log prefix so that the pointer lines up with the query
+            eprintln!("{}", e.pointer(&self.expression));
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+        })?;
+
+        let unknown = query.unknown_identifiers(job_config);
+        if !unknown.is_empty() {
+            error!(
+                "Unknown classes or projects in query: {}",
+                unknown
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Unknown class or project in query",
+            ));
+        }
+
+        let rows = query.evaluate(
+            job_config,
+            manager,
+            local_time::now(),
+            self.attribution.unwrap_or(config.attribution),
+        );
+        Ok(if self.json {
+            render_json(&rows)
+        } else {
+            render_table(&rows)
+        })
+    }
+}
+
+impl ExecutableCommand for CommandQuery {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let output = self.render(config, job_config, &manager)?;
+        if self.json {
+            println!("{}", output);
+        } else {
+            print!("{}", output);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::project::{Project, ProjectInner};
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Date, Month, Time};
+    use uuid::Uuid;
+
+    fn fixture_dir() -> TempDataDir {
+        let project = |name: &str| Project {
+            id: Uuid::new_v4(),
+            inner: ProjectInner {
+                name: name.to_string(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        };
+        let job_config = JobConfig {
+            projects: vec![project("alpha"), project("beta")],
+            ..JobConfig::default()
+        };
+        let dir = TempDataDir::new(&job_config).unwrap();
+        for (name, contents) in [
+            (
+                "2024-05-06.json",
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/testdata/query/2024-05-06.json"
+                )),
+            ),
+            (
+                "2024-05-07.json",
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/testdata/query/2024-05-07.json"
+                )),
+            ),
+            (
+                "2024-06-03.json",
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/testdata/query/2024-06-03.json"
+                )),
+            ),
+        ] {
+            dir.write_day_file(name, contents).unwrap();
+        }
+        dir
+    }
+
+    fn run(dir: &TempDataDir, args: &[&str]) -> std::io::Result<String> {
+        let command = CommandQuery::parse_from(["query"].iter().chain(args));
+        command.render(
+            dir.app_config(),
+            &dir.job_config().unwrap(),
+            &dir.manager().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_query_fixture_directory() {
+        let date = Date::from_calendar_date(2024, Month::June, 10).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(12, 0, 0).unwrap())).install();
+        let dir = fixture_dir();
+
+        assert_eq!(
+            run(
+                &dir,
+                &["sum duration where class=@work and date in 2024-05 group by project"]
+            )
+            .unwrap(),
+            "alpha  5h 0m\nbeta   3h 0m\n"
+        );
+        assert_eq!(
+            run(&dir, &["sum duration where date in 2024-05"]).unwrap(),
+            "8h 30m\n"
+        );
+        assert_eq!(
+            run(
+                &dir,
+                &[
+                    "count activities where tag=#oncall and date >= 2024-05-07",
+                    "--json"
+                ]
+            )
+            .unwrap(),
+            r#"[{"count":2}]"#
+        );
+        assert_eq!(
+            run(&dir, &["count activities group by class", "--json"]).unwrap(),
+            r#"[{"count":1,"group":"break"},{"count":5,"group":"work"}]"#
+        );
+        assert_eq!(
+            run(&dir, &["sum duration where tag in (oncall) group by date"]).unwrap(),
+            "2024-05-06  3h 0m\n2024-05-07  1h 0m\n2024-06-03  1h 0m\n"
+        );
+    }
+
+    #[test]
+    fn test_query_rejects_invalid_and_unknown() {
+        let dir = fixture_dir();
+
+        let error = run(&dir, &["sum duration where class=@work or tag=x"]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+
+        let error = run(&dir, &["sum duration where project=@gamma"]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_identifier_arg;
+use crate::cli::format::format_duration_pretty;
+use crate::cli::show::class_name;
+use crate::data::app_config::AppConfig;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::Manager;
+use crate::data::quota::{Quota, QuotaInner};
+use crate::error::Error;
+use crate::serde::pretty_duration;
+use clap::Parser;
+use log::error;
+use time::Duration;
+use uuid::Uuid;
+
+#[derive(Parser)]
+pub enum CommandQuota {
+    /// List all quotas
+    #[clap(aliases = ["ls", "show", "info", "display"])]
+    List,
+    /// Set the quota of a class, e.g. `quota set @work 8h`
+    #[clap(aliases = ["add", "new", "create"])]
+    Set {
+        /// Class identifier
+        #[arg(value_parser = parse_identifier_arg)]
+        class: Identifier,
+        /// Duration of the quota, e.g. 8h, 7h 30m, 7.5h or 07h 30m 00s
+        #[arg(value_parser = pretty_duration::parse_duration)]
+        duration: Duration,
+        /// Description of the quota
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// Delete a quota
+    #[clap(aliases = ["delete", "del", "rm"])]
+    Remove {
+        /// Quota UUID or class identifier
+        #[arg(value_parser = parse_identifier_arg)]
+        quota: Identifier,
+    },
+}
+
+fn format_quota(job_config: &JobConfig, quota: &Quota) -> String {
+    format!(
+        "{}: {}{} ({})",
+        class_name(job_config, &quota.inner.class),
+        format_duration_pretty(quota.inner.duration, false),
+        quota
+            .inner
+            .description
+            .as_ref()
+            .map(|description| format!(", {}", description))
+            .unwrap_or_default(),
+        quota.id
+    )
+}
+
+impl ExecutableCommand for CommandQuota {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        job_config: &mut JobConfig,
+        _manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        match self {
+            CommandQuota::List => {
+                if job_config.quotas.is_empty() {
+                    println!("No quotas found");
+                    return Ok(());
+                }
+                println!("Quotas:");
+                for quota in &job_config.quotas {
+                    println!(" - {}", format_quota(job_config, quota));
+                }
+            }
+            CommandQuota::Set {
+                class,
+                duration,
+                description,
+            } => {
+                let Some(resolved) = job_config.resolve_class(class) else {
+                    error!("Activity class not found: {}", class);
+                    return Err(Error::UnknownClass(class.clone()));
+                };
+                if let Some(existing) = job_config.quota_of(class) {
+                    error!(
+                        "Class {} already has a quota ({}), remove it first",
+                        resolved.inner.name, existing.id
+                    );
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        "Quota already exists",
+                    )
+                    .into());
+                }
+
+                let quota = Quota {
+                    id: Uuid::new_v4(),
+                    inner: QuotaInner {
+                        class: Identifier::Uuid(resolved.id),
+                        duration: *duration,
+                        description: description.clone(),
+                    },
+                };
+                println!("Added quota: {}", format_quota(job_config, &quota));
+                job_config.quotas.push(quota);
+            }
+            CommandQuota::Remove { quota } => {
+                let position = job_config
+                    .quotas
+                    .iter()
+                    .position(
+                        |existing| matches!(quota, Identifier::Uuid(id) if existing.id == *id),
+                    )
+                    .or_else(|| {
+                        let found = job_config.quota_of(quota)?;
+                        job_config
+                            .quotas
+                            .iter()
+                            .position(|existing| existing.id == found.id)
+                    });
+                let Some(position) = position else {
+                    error!("Quota not found: {}", quota);
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "Quota not found",
+                    )
+                    .into());
+                };
+
+                let removed = job_config.quotas.remove(position);
+                println!("Removed quota: {}", format_quota(job_config, &removed));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDataDir;
+
+    fn quota(dir: &TempDataDir, args: &[&str]) -> crate::error::Result<()> {
+        dir.execute(&CommandQuota::parse_from(["quota"].iter().chain(args)))
+    }
+
+    #[test]
+    fn test_quota_set_list_remove() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        quota(&dir, &["set", "@work", "7h 30m", "-d", "part time"]).unwrap();
+
+        let job_config = dir.job_config().unwrap();
+        let work = job_config
+            .resolve_class(Identifier::ByName("work".into()))
+            .unwrap();
+        assert_eq!(job_config.quotas.len(), 1);
+        assert_eq!(job_config.quotas[0].inner.class, Identifier::Uuid(work.id));
+        assert_eq!(job_config.quotas[0].inner.duration, Duration::minutes(450));
+        assert_eq!(
+            format_quota(&job_config, &job_config.quotas[0]),
+            format!("work: 7h 30m, part time ({})", job_config.quotas[0].id)
+        );
+
+        // one quota per class, however the class is referenced
+        let result = quota(&dir, &["set", &work.id.to_string(), "8h"]);
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::AlreadyExists
+        );
+        let result = quota(&dir, &["set", "@missing", "8h"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+        quota(&dir, &["set", "@holiday", "08h 00m 00s"]).unwrap();
+        quota(&dir, &["list"]).unwrap();
+        assert_eq!(dir.job_config().unwrap().quotas.len(), 2);
+
+        // by class, then by quota id
+        quota(&dir, &["remove", "@work"]).unwrap();
+        let id = dir.job_config().unwrap().quotas[0].id.to_string();
+        quota(&dir, &["remove", &id]).unwrap();
+        assert!(dir.job_config().unwrap().quotas.is_empty());
+        let result = quota(&dir, &["remove", "@work"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_quota_duration_is_parsed_like_the_stored_form() {
+        let parse = |s: &str| CommandQuota::try_parse_from(["quota", "set", "@work", s]);
+        for valid in ["8h", "7h 30m", "07h 30m 00s", "45m", "1.5h", "1h30m"] {
+            assert!(parse(valid).is_ok(), "{}", valid);
+        }
+        for invalid in ["", "eight", "1x", "-1h"] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{parse_date_arg, parse_duration_arg};
+use crate::cli::day::ensure_unlocked;
+use crate::data::app_config::AppConfig;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::reconcile::{self, Proposal, ReconcilePolicy};
+use clap::Parser;
+use log::{error, info};
+use std::io::{BufRead, Write};
+use time::{Date, Duration};
+
+#[derive(Parser)]
+pub struct CommandReconcile {
+    /// Day to reconcile (YYYY-MM-DD), defaults to today
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+    /// Apply the safe fixes without asking, i.e. close stale activities at the last recorded end
+    #[arg(long)]
+    auto: bool,
+    /// Shortest gap between activities that is reported, e.g. 15m
+    #[arg(long, value_parser = parse_duration_arg, default_value = "15m")]
+    min_gap: Duration,
+    /// Longest activity that is not reported as suspicious, e.g. 10h
+    #[arg(long, value_parser = parse_duration_arg, default_value = "10h")]
+    max_duration: Duration,
+    /// Classification of inserted breaks
+    #[arg(long, default_value = "@break")]
+    break_class: Identifier,
+}
+
+/// answer to a proposed fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Apply,
+    Skip,
+    Quit,
+}
+
+/// decides interactively whether a proposed fix is applied
+pub trait Reviewer {
+    fn review(&self, proposal: &Proposal) -> std::io::Result<Decision>;
+}
+
+impl<F: Fn(&Proposal) -> std::io::Result<Decision>> Reviewer for F {
+    fn review(&self, proposal: &Proposal) -> std::io::Result<Decision> {
+        self(proposal)
+    }
+}
+
+/// asks on stdout and reads the answers from stdin, quits at the end of the input
+pub struct StdinReviewer;
+
+impl Reviewer for StdinReviewer {
+    fn review(&self, proposal: &Proposal) -> std::io::Result<Decision> {
+        loop {
+            print!("Fix: {}? [y]es, [n]o, [q]uit: ", proposal.fix);
+            std::io::stdout().flush()?;
+
+            let mut answer = String::new();
+            if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+                println!();
+                return Ok(Decision::Quit);
+            }
+            match answer.trim().to_lowercase().as_str() {
+                "y" | "yes" => return Ok(Decision::Apply),
+                "n" | "no" => return Ok(Decision::Skip),
+                "q" | "quit" => return Ok(Decision::Quit),
+                _ => println!("Please answer y, n or q."),
+            }
+        }
+    }
+}
+
+impl CommandReconcile {
+    /// walk through the issues of the day, asking `reviewer` unless `--auto` is given
+    pub fn reconcile_with(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        reviewer: &dyn Reviewer,
+        mut manager: Manager,
+    ) -> crate::error::Result<()> {
+        let now = local_time::now();
+        let today = job_config.date_of(now);
+        let date = self.date.unwrap_or(today);
+
+        if job_config.resolve_class(&self.break_class).is_none() {
+            error!("Failed to resolve break class: {}", self.break_class);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Failed to resolve break class",
+            )
+            .into());
+        }
+        let Some(day) = manager.annotated_day(date) else {
+            println!("No activities on {}.", date);
+            return Ok(());
+        };
+        ensure_unlocked(date, day.inner())?;
+
+        let policy = ReconcilePolicy {
+            min_gap: self.min_gap,
+            max_duration: self.max_duration,
+            break_class: self.break_class.clone(),
+            day_over: date < today,
+            boundary: job_config.day_boundary,
+        };
+        let journal = Journal::new(config, manager.data_path());
+
+        // proposals are recomputed after each fix, since fixes change the issues that follow
+        let mut handled = Vec::new();
+        // journaled and reported once the day is saved
+        let mut applied = Vec::new();
+        loop {
+            let day = manager.get_or_create_day_ref(date);
+            let Some(proposal) = reconcile::propose(day, &policy)
+                .into_iter()
+                .find(|proposal| !handled.contains(&proposal.fix) && (proposal.safe || !self.auto))
+            else {
+                break;
+            };
+            handled.push(proposal.fix.clone());
+
+            println!("Issue: {}", proposal.issue);
+            let decision = if self.auto {
+                Decision::Apply
+            } else {
+                reviewer.review(&proposal)?
+            };
+            match decision {
+                Decision::Apply => {}
+                Decision::Skip => continue,
+                Decision::Quit => {
+                    handled.pop();
+                    break;
+                }
+            }
+
+            let day = manager.get_or_create_day_mut(date);
+            let Some(changes) = reconcile::apply(day, &proposal.fix, &policy, now) else {
+                continue;
+            };
+            info!("Reconciling {}: {:?}", date, proposal.fix);
+            applied.push((proposal.fix, changes));
+        }
+
+        manager.save()?;
+        for (fix, changes) in &applied {
+            journal.append(&JournalEntry {
+                at: now,
+                command: "reconcile".to_string(),
+                date,
+                removed: changes.removed.clone(),
+                added: changes.added.clone(),
+            })?;
+            println!("Applied: {}", fix);
+        }
+        if handled.is_empty() && applied.is_empty() {
+            println!("Nothing reconciled on {}.", date);
+        } else {
+            println!(
+                "Applied {} of {} reviewed fixes on {}.",
+                applied.len(),
+                handled.len(),
+                date
+            );
+        }
+        Ok(())
+    }
+}
+
+impl ExecutableCommand for CommandReconcile {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        self.reconcile_with(config, job_config, &StdinReviewer, manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::reconcile::Fix;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use std::cell::RefCell;
+    use time::{Month, Time};
+
+    const MESSY_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/messy_day.json"
+    ));
+    const LONG_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/long_day.json"
+    ));
+
+    fn setup() -> (ManualClock, TempDataDir) {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(today, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", MESSY_DAY).unwrap();
+        dir.write_day_file("2024-05-01.json", LONG_DAY).unwrap();
+        (clock, dir)
+    }
+
+    fn reconcile(
+        dir: &TempDataDir,
+        args: &[&str],
+        reviewer: &dyn Reviewer,
+    ) -> crate::error::Result<()> {
+        let command = CommandReconcile::parse_from(["reconcile"].iter().chain(args));
+        command.reconcile_with(
+            dir.app_config(),
+            &dir.job_config().unwrap(),
+            reviewer,
+            dir.manager().unwrap(),
+        )
+    }
+
+    fn journal_changes(dir: &TempDataDir) -> Vec<(usize, usize)> {
+        Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap()
+            .iter()
+            .inspect(|entry| assert_eq!(entry.command, "reconcile"))
+            .map(|entry| (entry.removed.len(), entry.added.len()))
+            .collect()
+    }
+
+    #[test]
+    fn test_auto_applies_only_safe_fixes() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let never = |_: &Proposal| -> std::io::Result<Decision> { panic!("must not ask") };
+
+        reconcile(&dir, &["--date", "2024-05-02", "--auto"], &never).unwrap();
+        reconcile(&dir, &["--date", "2024-05-01", "--auto"], &never).unwrap();
+
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 5);
+        assert!(day.activities.iter().all(|a| a.time.is_complete()));
+        assert_eq!(
+            day.activities[3].time.end,
+            Some(Time::from_hms(16, 0, 0).unwrap())
+        );
+
+        // closing at the end of the day is a guess, it is left to the interactive mode
+        let day = dir.day(date.previous_day().unwrap()).unwrap().unwrap();
+        assert!(!day.activities[1].time.is_complete());
+
+        assert_eq!(journal_changes(&dir), [(1, 1)]);
+    }
+
+    #[test]
+    fn test_interactive_applies_accepted_fixes() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let asked = RefCell::new(Vec::new());
+        let reviewer = |proposal: &Proposal| {
+            asked.borrow_mut().push(proposal.fix.clone());
+            Ok(match proposal.fix {
+                Fix::Merge { .. } => Decision::Skip,
+                _ => Decision::Apply,
+            })
+        };
+
+        reconcile(&dir, &["--date", "2024-05-02"], &reviewer).unwrap();
+
+        let asked = asked.into_inner();
+        assert_eq!(asked.len(), 3);
+        assert!(matches!(asked[2], Fix::InsertBreak { .. }));
+
+        let day = dir
+            .day(Date::from_calendar_date(2024, Month::May, 2).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(day.activities.len(), 6);
+        let inserted = day.activities.last().unwrap();
+        assert_eq!(inserted.class, Identifier::ByName("break".into()));
+        assert_eq!(inserted.time.start, Time::from_hms(11, 0, 0).unwrap());
+        assert_eq!(inserted.time.end, Some(Time::from_hms(11, 45, 0).unwrap()));
+
+        assert_eq!(journal_changes(&dir), [(1, 1), (0, 1)]);
+    }
+
+    #[test]
+    fn test_interactive_quit_keeps_day() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let quit = |_: &Proposal| Ok(Decision::Quit);
+
+        reconcile(&dir, &["--date", "2024-05-01"], &quit).unwrap();
+
+        let day = dir
+            .day(Date::from_calendar_date(2024, Month::May, 1).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(day.activities.len(), 2);
+        assert!(journal_changes(&dir).is_empty());
+    }
+
+    #[test]
+    fn test_reconcile_refuses_locked_day() {
+        let (clock, dir) = setup();
+        let _guard = clock.install();
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        dir.manager().unwrap().get_or_create_day_mut(date).locked = true;
+        let never = |_: &Proposal| -> std::io::Result<Decision> { panic!("must not ask") };
+
+        let result = reconcile(&dir, &["--date", "2024-05-02"], &never);
+
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+    }
+}
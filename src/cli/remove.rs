@@ -0,0 +1,229 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_date_arg;
+use crate::cli::day::ensure_unlocked;
+use crate::cli::edit::print_candidates;
+use crate::cli::pop::{Confirm, StdinConfirm};
+use crate::data::app_config::AppConfig;
+use crate::data::day::ActivityLookupError;
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::reference::Reference;
+use clap::Parser;
+use log::{error, info};
+use time::Date;
+
+#[derive(Parser)]
+pub struct CommandRemove {
+    /// Activity UUID, UUID prefix, short id (see `timetrax show`) or @name
+    activity: Reference,
+    /// Day of the activity (YYYY-MM-DD), defaults to today
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+    /// Remove without asking for confirmation
+    #[arg(short, long)]
+    force: bool,
+}
+
+impl CommandRemove {
+    /// remove the activity after asking `confirm` unless `--force` is given
+    pub fn remove_with(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        confirm: &dyn Confirm,
+        mut manager: Manager,
+    ) -> crate::error::Result<()> {
+        let now = local_time::now();
+        let date = self.date.unwrap_or(job_config.date_of(now));
+        let Some(day) = manager.day(date) else {
+            error!("There are no activities on {}", date);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No day file for the date",
+            )
+            .into());
+        };
+        ensure_unlocked(date, day)?;
+
+        let activity = match day.find_activity_by_reference(&self.activity) {
+            Ok(activity) => activity.clone(),
+            Err(e @ ActivityLookupError::AmbiguousReference(..)) => {
+                error!("{}", e);
+                println!("Matching activities of {}:", date);
+                for activity in self.activity.matches(&day.activities) {
+                    println!(" - {} {} {}", activity.short_id(), activity.id, activity);
+                }
+                return Err(e.into());
+            }
+            Err(e) => {
+                error!("{}", e);
+                print_candidates(date, day);
+                return Err(e.into());
+            }
+        };
+
+        println!(
+            "Removing activity of {}: {} {} {}",
+            date,
+            activity.short_id(),
+            activity.id,
+            activity
+        );
+        if !self.force && !confirm.confirm("Remove this activity?")? {
+            println!("Nothing removed.");
+            return Ok(());
+        }
+
+        info!("Removing activity of {}: {:?}", date, activity);
+        manager
+            .get_or_create_day_mut(date)
+            .delete_activity(activity.id, now);
+        manager.save()?;
+        Journal::new(config, manager.data_path()).append(&JournalEntry {
+            at: now,
+            command: "remove".to_string(),
+            date,
+            removed: vec![activity.clone()],
+            added: vec![],
+        })?;
+        println!("Removed.");
+        Ok(())
+    }
+}
+
+impl ExecutableCommand for CommandRemove {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        self.remove_with(config, job_config, &StdinConfirm, manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::data::day::Day;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use std::cell::Cell;
+    use time::{Month, Time};
+    use uuid::Uuid;
+
+    fn setup() -> (ManualClock, TempDataDir, Date) {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        {
+            let _guard = clock.install();
+            for name in ["first", "second"] {
+                dir.execute(&CommandPush::parse_from([
+                    "push", "-c", "@work", "-n", name,
+                ]))
+                .unwrap();
+            }
+        }
+        (clock, dir, date)
+    }
+
+    fn remove(dir: &TempDataDir, confirm: &dyn Confirm, args: &[&str]) -> crate::error::Result<()> {
+        CommandRemove::parse_from(["remove"].iter().chain(args)).remove_with(
+            dir.app_config(),
+            &dir.job_config().unwrap(),
+            confirm,
+            dir.manager().unwrap(),
+        )
+    }
+
+    fn names(dir: &TempDataDir, date: Date) -> Vec<String> {
+        dir.day(date)
+            .unwrap()
+            .unwrap()
+            .activities
+            .into_iter()
+            .filter_map(|activity| activity.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_remove_asks_for_confirmation() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        let first = dir.day(date).unwrap().unwrap().activities[0].clone();
+
+        let asked = Cell::new(0);
+        let decline = |_: &str| {
+            asked.set(asked.get() + 1);
+            Ok(false)
+        };
+        remove(&dir, &decline, &[&first.short_id()]).unwrap();
+        assert_eq!(asked.get(), 1);
+        assert_eq!(names(&dir, date), ["first", "second"]);
+
+        let accept = |_: &str| Ok(true);
+        remove(&dir, &accept, &[&first.id.to_string()[..8]]).unwrap();
+        assert_eq!(names(&dir, date), ["second"]);
+
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].command, "remove");
+        assert_eq!(entries[0].removed[0].id, first.id);
+
+        let second = dir.day(date).unwrap().unwrap().activities[0].id;
+        let never = |_: &str| -> std::io::Result<bool> { panic!("must not ask") };
+        remove(&dir, &never, &[&second.to_string(), "--force"]).unwrap();
+        assert!(names(&dir, date).is_empty());
+    }
+
+    #[test]
+    fn test_remove_ambiguous_reference() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        let mut day = dir.day(date).unwrap().unwrap();
+        day.activities[0].id = Uuid::from_u128(0xabc1);
+        day.activities[1].id = Uuid::from_u128(0xabc2);
+        dir.write_day_file(
+            "2024-05-03.json",
+            &serde_json::to_string(&Day { date, inner: day }).unwrap(),
+        )
+        .unwrap();
+
+        let never = |_: &str| -> std::io::Result<bool> { panic!("must not ask") };
+        let result = remove(
+            &dir,
+            &never,
+            &["00000000-0000-0000-0000-00000000abc", "--force"],
+        );
+
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(names(&dir, date), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_remove_unknown_activity() {
+        let (clock, dir, date) = setup();
+        let _guard = clock.install();
+        let never = |_: &str| -> std::io::Result<bool> { panic!("must not ask") };
+
+        let result = remove(&dir, &never, &[&Uuid::nil().to_string(), "--force"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        let result = remove(&dir, &never, &["zzzz", "--date", "2024-05-02"]);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+        assert_eq!(names(&dir, date), ["first", "second"]);
+        assert!(
+            Journal::new(dir.app_config(), dir.path())
+                .entries()
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::cli::ExecutableCommand;
+use crate::data::BASIC_DATE_FORMAT;
+use crate::data::app_config::AppConfig;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::{Manager, corrupt_folder};
+use crate::data::salvage::salvage_day;
+use clap::Parser;
+use log::{error, info};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use time::Date;
+
+#[derive(Parser, Default)]
+pub struct CommandRepair {
+    /// Move the day files which cannot be parsed to the `corrupt` folder next to them
+    #[arg(short, long)]
+    quarantine: bool,
+    /// Recover the well-formed activities and blockers of the files as new days, the files
+    /// are quarantined
+    #[arg(short, long)]
+    salvage: bool,
+}
+
+/// the error of a day file with the line it points to, e.g.
+/// `2024-05-02.json: trailing comma at line 12 column 5`
+fn describe_error(path: &Path, error: &serde_json::Error) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, " - {}: {}", path.display(), error);
+    let line = std::fs::read_to_string(path).ok().and_then(|contents| {
+        Some(
+            contents
+                .lines()
+                .nth(error.line().checked_sub(1)?)?
+                .to_string(),
+        )
+    });
+    if let Some(line) = line {
+        let _ = writeln!(out, "   {}", line);
+        let _ = writeln!(out, "   {}^", " ".repeat(error.column().saturating_sub(1)));
+    }
+    out
+}
+
+/// move the file to `folder`, adding a number to its name if the folder has a file of that
+/// name already
+fn quarantine(path: &Path, folder: &Path) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(folder)?;
+    let name = path.file_name().unwrap_or_default().to_os_string();
+    let mut target = folder.join(&name);
+    let mut number = 1;
+    while target.exists() {
+        let mut numbered = name.clone();
+        numbered.push(format!(".{}", number));
+        target = folder.join(numbered);
+        number += 1;
+    }
+    std::fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// date of a day file named after it
+fn date_of_file(path: &Path) -> Option<Date> {
+    let stem = path.file_stem()?.to_str()?;
+    Date::parse(stem, &*BASIC_DATE_FORMAT).ok()
+}
+
+impl ExecutableCommand for CommandRepair {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        _job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        if let (Some(first), Some(last)) = (manager.dates().next(), manager.dates().next_back()) {
+            manager.load_range(first, last);
+        }
+        let broken = manager
+            .load_errors()
+            .into_iter()
+            .map(|(path, error)| (path.to_path_buf(), describe_error(path, error)))
+            .collect::<Vec<_>>();
+        if broken.is_empty() {
+            println!("All day files could be loaded.");
+            return Ok(());
+        }
+
+        println!("{} day file(s) could not be loaded:", broken.len());
+        for (_, description) in &broken {
+            print!("{}", description);
+        }
+        if !self.quarantine && !self.salvage {
+            println!(
+                "Move them out of the way with `timetrax repair --quarantine`, or recover their \
+                 activities with `timetrax repair --salvage`."
+            );
+            return Ok(());
+        }
+
+        let folder = corrupt_folder(config, manager.data_path())?;
+        for (path, _) in broken {
+            // read before the file is moved, the salvaged day takes its place
+            let salvaged = if self.salvage {
+                Some(salvage_day(&std::fs::read_to_string(&path)?))
+            } else {
+                None
+            };
+            let target = quarantine(&path, &folder).inspect_err(|e| {
+                error!("Failed to quarantine {}: {}", path.display(), e);
+            })?;
+            info!("Quarantined {} as {}", path.display(), target.display());
+            println!("Moved {} to {}", path.display(), target.display());
+
+            let Some(salvaged) = salvaged else {
+                continue;
+            };
+            let Some(date) = date_of_file(&path).or(salvaged.date) else {
+                println!(
+                    "The date of {} is unknown, nothing salvaged.",
+                    path.display()
+                );
+                continue;
+            };
+            if manager
+                .day(date)
+                .is_some_and(|day| !day.activities.is_empty())
+            {
+                println!(
+                    "{} has activities already, nothing salvaged from {}.",
+                    date,
+                    target.display()
+                );
+                continue;
+            }
+            println!(
+                "Salvaged {} activities and {} blockers of {}{}.",
+                salvaged.day.activities.len(),
+                salvaged.day.blockers.len(),
+                date,
+                match salvaged.skipped {
+                    0 => String::new(),
+                    skipped => format!(", skipped {} malformed", skipped),
+                }
+            );
+            *manager.get_or_create_day_mut(date) = salvaged.day;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::manager::CORRUPT_FOLDER;
+    use crate::testing::TempDataDir;
+    use time::Month;
+
+    fn may(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::May, day).unwrap()
+    }
+
+    /// a data directory with the days of testdata/corrupt and an intact day
+    fn corrupt_fixture() -> TempDataDir {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        for name in ["2024-05-02.json", "2024-05-03.json"] {
+            let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("testdata/corrupt")
+                .join(name);
+            dir.write_day_file(name, &std::fs::read_to_string(fixture).unwrap())
+                .unwrap();
+        }
+        dir.write_day_file(
+            "2024-05-06.json",
+            r#"{"date": "2024-05-06", "activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+                 "time": {"start": "09:00:00", "end": "10:00:00"}}]}"#,
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let dir = corrupt_fixture();
+        let manager = dir.manager().unwrap();
+        // nothing is parsed up front
+        assert!(manager.load_errors().is_empty());
+        assert!(
+            manager
+                .day(may(2))
+                .is_some_and(|day| day.activities.is_empty())
+        );
+        assert_eq!(manager.load_errors().len(), 1);
+        manager.load_range(may(1), may(31));
+
+        let errors = manager.load_errors();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].0.ends_with("2024-05-02.json"));
+        assert_eq!(errors[0].1.line(), 12);
+        // the string cut off at the end of the line
+        assert!(errors[1].1.is_syntax());
+        let description = describe_error(errors[0].0, errors[0].1);
+        assert!(description.ends_with("trailing comma at line 12 column 5\n       },\n       ^\n"));
+    }
+
+    #[test]
+    fn test_quarantine() {
+        let dir = corrupt_fixture();
+        let days = dir.path().join(&dir.app_config().job_day_folder_format);
+        dir.execute(&CommandRepair::parse_from(["repair"])).unwrap();
+        assert!(days.join("2024-05-02.json").exists());
+
+        dir.execute(&CommandRepair::parse_from(["repair", "--quarantine"]))
+            .unwrap();
+        let folder = days.join(CORRUPT_FOLDER);
+        assert!(folder.join("2024-05-02.json").exists());
+        assert!(folder.join("2024-05-03.json").exists());
+        assert!(!days.join("2024-05-02.json").exists());
+        let manager = dir.manager().unwrap();
+        assert!(!manager.contains_day(may(2)));
+        assert_eq!(manager.day(may(6)).unwrap().activities.len(), 1);
+        manager.load_range(may(1), may(31));
+        assert!(manager.load_errors().is_empty());
+    }
+
+    #[test]
+    fn test_salvage() {
+        let dir = corrupt_fixture();
+        dir.execute(&CommandRepair::parse_from(["repair", "--salvage"]))
+            .unwrap();
+
+        let day = dir.day(may(2)).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 2);
+        assert_eq!(day.blockers.len(), 1);
+        let day = dir.day(may(3)).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert_eq!(day.activities[0].name.as_deref(), Some("planning"));
+        let folder = dir
+            .path()
+            .join(&dir.app_config().job_day_folder_format)
+            .join(CORRUPT_FOLDER);
+        assert!(folder.join("2024-05-03.json").exists());
+
+        // nothing left to repair
+        let manager = dir.manager().unwrap();
+        manager.load_range(may(1), may(31));
+        assert!(manager.load_errors().is_empty());
+    }
+}
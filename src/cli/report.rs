@@ -0,0 +1,1484 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::{
+    DateArg, PeriodArg, parse_duration_arg, parse_identifier_arg, parse_relative_date_arg,
+};
+use crate::cli::class::check_class_filter;
+use crate::cli::color::{GREEN, RED};
+use crate::cli::format::{format_duration_pretty, format_table};
+use crate::cli::locale::Locale;
+use crate::cli::output::{OutputFormat, RenderOutput, render_text_or_json};
+use crate::cli::project::check_project_filter;
+use crate::cli::status::render_gaps;
+use crate::data::activity::Activity;
+use crate::data::activity_closure::ClosureDiagnostics;
+use crate::data::app_config::AppConfig;
+use crate::data::attribution::Attribution;
+use crate::data::break_rules::BreakCompliance;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::report::{
+    DailyQuotas, DayBalance, ReportFilter, Share, ShareKind, Totals, day_balances, day_closure,
+    day_compliance, day_quota, quota_time, raw_segments,
+};
+use crate::data::safe_duration::SafeDuration;
+use crate::data::team::{ForeignData, align_job_config, person_name};
+use crate::profile;
+use clap::{Parser, ValueEnum};
+use itertools::Itertools;
+use log::error;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+use time::{Date, Duration, OffsetDateTime};
+
+#[derive(Parser, Default, Clone)]
+pub struct CommandReport {
+    /// Single day to report (e.g. 2024-05-03 or yesterday), short for --from DATE --to DATE
+    #[arg(
+        long,
+        value_parser = parse_relative_date_arg,
+        allow_hyphen_values = true,
+        conflicts_with_all = ["from", "to"],
+    )]
+    date: Option<DateArg>,
+    /// Report the week containing --date (or today) day by day, with the delta to the work quota
+    #[arg(short, long, conflicts_with_all = ["from", "to", "merge_from"])]
+    week: bool,
+    /// Report the flextime balance: the time tracked each day less its work quota, added up from
+    /// --from, balance_start_date or the first tracked day up to --to or yesterday
+    #[arg(
+        long,
+        conflicts_with_all = ["date", "week", "period", "merge_from", "projects", "classes"],
+    )]
+    balance: bool,
+    /// Check the breaks of each day against the break rules of the job config, e.g. 30m of break
+    /// for more than 6h of work
+    #[arg(
+        long,
+        conflicts_with_all = ["week", "balance", "merge_from", "projects", "classes"],
+    )]
+    compliance: bool,
+    /// Report a named period, e.g. last-week or this-month
+    #[arg(long, value_enum, conflicts_with_all = ["date", "from", "to", "week"])]
+    period: Option<PeriodArg>,
+    /// First day of the report (YYYY-MM-DD, or e.g. yesterday, monday, -3d), defaults to today
+    #[arg(short, long, value_parser = parse_relative_date_arg, allow_hyphen_values = true)]
+    from: Option<DateArg>,
+    /// Last day of the report (YYYY-MM-DD, or e.g. yesterday, monday, -3d), defaults to the first day
+    #[arg(short, long, value_parser = parse_relative_date_arg, allow_hyphen_values = true)]
+    to: Option<DateArg>,
+    /// How time of segments with multiple projects is attributed to them
+    #[arg(short, long, value_enum)]
+    attribution: Option<Attribution>,
+    /// Group the project totals by project or by client
+    #[arg(short, long, value_enum, default_value = "project")]
+    group: ReportGroup,
+    /// Show the share of each class and project next to its target share
+    #[arg(short, long)]
+    shares: bool,
+    /// Allowed deviation from target shares in percentage points
+    #[arg(long, value_name = "POINTS", requires = "shares")]
+    tolerance: Option<f32>,
+    /// Only report activities of this project, may be given multiple times
+    #[arg(
+        short,
+        long = "project",
+        value_parser = parse_identifier_arg,
+        conflicts_with = "week"
+    )]
+    projects: Vec<Identifier>,
+    /// Only report time of this class, may be given multiple times. Time the activities of a
+    /// higher priority class take over, e.g. a break during work, does not count for the class
+    #[arg(short, long = "class", value_parser = parse_identifier_arg, conflicts_with = "week")]
+    classes: Vec<Identifier>,
+    /// Add up the durations of the activities of --class as they are, without folding them:
+    /// overlapping time counts for every activity and higher priority classes are ignored
+    #[arg(long, requires = "classes")]
+    raw: bool,
+    /// Fail instead of warning if activities refer to unknown classes
+    #[arg(long)]
+    strict: bool,
+    /// List the untracked time between the activities of each day
+    #[arg(long)]
+    show_gaps: bool,
+    /// Shortest untracked time listed by --show-gaps, e.g. 5m
+    #[arg(long, requires = "show_gaps", value_parser = parse_duration_arg)]
+    min_gap: Option<Duration>,
+    /// Data directories of other people to include, read-only, classes and projects match by name
+    #[arg(long, value_name = "DIR", num_args = 1..)]
+    merge_from: Vec<PathBuf>,
+}
+
+/// stands in for classes deleted from the job config
+const UNRESOLVED: &str = "<UNRESOLVED>";
+
+/// grouping of the project totals in a report
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportGroup {
+    #[default]
+    Project,
+    Client,
+}
+
+impl CommandReport {
+    /// the report including shares and warnings, as printed
+    #[cfg(test)]
+    fn render(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        manager: &Manager,
+    ) -> crate::error::Result<String> {
+        Ok(self.output(config, job_config, manager)?.text)
+    }
+
+    /// totals of the period along with the printed report
+    fn output(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        manager: &Manager,
+    ) -> crate::error::Result<ReportOutput> {
+        let now = local_time::now();
+        let today = job_config.date_of(now);
+        if self.balance {
+            return Ok(self.balance_output(config, job_config, manager, now));
+        }
+        let (from, to) = match self.period {
+            Some(period) => period.resolve(today, config.week_start),
+            None => {
+                let resolve = |date: Option<DateArg>| date.map(|date| date.resolve(today));
+                let from = resolve(self.date.or(self.from)).unwrap_or(today);
+                (from, resolve(self.date.or(self.to)).unwrap_or(from))
+            }
+        };
+
+        if from > to {
+            error!("The report must start before it ends: {} > {}", from, to);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Report starts after it ends",
+            )
+            .into());
+        }
+
+        if self.compliance {
+            manager.load_range(from, to);
+            let days = day_compliance(job_config, manager, from, to, now);
+            return Ok(ReportOutput {
+                from,
+                to,
+                total_seconds: None,
+                balance_seconds: None,
+                violations: Some(days.iter().filter(|(_, day)| !day.is_compliant()).count()),
+                classes: BTreeMap::new(),
+                projects: BTreeMap::new(),
+                text: render_compliance(&Locale::new(config), &days),
+            });
+        }
+
+        if self.week {
+            return Ok(week_output(
+                &Locale::new(config),
+                job_config,
+                manager,
+                from,
+                now,
+                config.daily_quotas(),
+                self.attribution.unwrap_or(config.attribution),
+            ));
+        }
+
+        // other people's days refer to a combined config, which extends the local one
+        let foreign = self
+            .merge_from
+            .iter()
+            .map(|path| ForeignData::open(config, path))
+            .collect::<crate::error::Result<Vec<_>>>()?;
+        let mut combined = job_config.clone();
+        let mut warnings = Vec::new();
+        manager.load_range(from, to);
+        let mut people = vec![(
+            person_name(manager.data_path()),
+            manager
+                .days_in(from..=to)
+                .map(|(date, day)| (*date, day.clone()))
+                .collect::<Vec<_>>(),
+        )];
+        for data in &foreign {
+            let (mapping, priority_warnings) =
+                align_job_config(&mut combined, &data.job_config, &data.name);
+            warnings.extend(priority_warnings);
+            people.push((
+                data.name.clone(),
+                data.manager()
+                    .days_in(from..=to)
+                    .map(|(date, day)| (*date, mapping.remap_day(&data.job_config, day)))
+                    .collect(),
+            ));
+        }
+        let job_config = &combined;
+        let filter = ReportFilter {
+            projects: self.projects.clone(),
+            ..ReportFilter::default()
+        };
+        check_project_filter(job_config, &filter)?;
+        let class_filter = ReportFilter {
+            classes: self.classes.clone(),
+            ..ReportFilter::default()
+        };
+        check_class_filter(job_config, &class_filter)?;
+
+        let attribution = self.attribution.unwrap_or(config.attribution);
+        let mut totals = Totals::new(attribution);
+        let mut person_totals = Vec::new();
+        let mut diagnostics = ClosureDiagnostics::default();
+        for (person, days) in &people {
+            let mut person_total = Totals::new(attribution);
+            for (date, day) in days {
+                let day = &filter.apply(job_config, day);
+                let (mut closure, day_diagnostics) = day_closure(job_config, *date, day, now);
+                diagnostics.merge(day_diagnostics);
+                let end_limit = (*date == today).then_some(now.time());
+                if self.raw {
+                    closure = raw_segments(job_config, &class_filter, day, end_limit);
+                } else {
+                    closure.retain(|segment| class_filter.matches(job_config, segment));
+                }
+                let blockers = class_filter.apply(job_config, day).blockers;
+                for totals in [&mut totals, &mut person_total] {
+                    totals.add_closure(job_config, &closure);
+                    totals.add_blockers(&blockers, end_limit, job_config.day_boundary);
+                }
+            }
+            person_totals.push((person.clone(), person_total));
+        }
+
+        diagnostics.check(self.strict)?;
+
+        let _timer = profile::timer("render");
+        let mut out = render_report(
+            &Locale::new(config),
+            job_config,
+            from,
+            to,
+            &totals,
+            self.group,
+        );
+
+        if !foreign.is_empty() {
+            out.push_str(&render_people(job_config, &person_totals));
+        }
+        for warning in warnings {
+            let _ = writeln!(out, "{}", warning);
+        }
+
+        if self.show_gaps {
+            // only the own days, the gaps of other people are theirs to fill
+            let min_gap = self.min_gap.unwrap_or(config.min_gap);
+            let locale = Locale::new(config);
+            let mut any = false;
+            for (date, day) in &people[0].1 {
+                let gaps = day.gaps(job_config, min_gap);
+                if !gaps.is_empty() {
+                    any = true;
+                    let _ = writeln!(out, "Gaps on {}:", locale.format_date(*date));
+                    out.push_str(&render_gaps(&locale, &gaps, job_config.day_boundary));
+                }
+            }
+            if !any {
+                let _ = writeln!(out, "No gaps between the activities.");
+            }
+        }
+
+        if self.shares {
+            out.push_str(&render_shares(
+                &totals.shares(job_config),
+                self.tolerance.unwrap_or(config.share_tolerance),
+            ));
+        }
+
+        if let Some(warning) = diagnostics.warning() {
+            let _ = writeln!(out, "{}", warning);
+        }
+        if let Some(warning) = totals.segments.warning() {
+            let _ = writeln!(out, "{}", warning);
+        }
+
+        let seconds = |duration: &Duration| duration.whole_seconds();
+        Ok(ReportOutput {
+            from,
+            to,
+            total_seconds: Some(seconds(&totals.total())),
+            balance_seconds: None,
+            violations: None,
+            classes: (totals.classes.iter())
+                .map(|(class, duration)| (class_name(job_config, class), seconds(duration)))
+                .collect(),
+            projects: (totals.projects.iter())
+                .map(|(project, duration)| (project_name(job_config, project), seconds(duration)))
+                .collect(),
+            text: out,
+        })
+    }
+
+    /// flextime balance of the days up to `--to` or yesterday, see [`day_balances`]
+    fn balance_output(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        manager: &Manager,
+        now: OffsetDateTime,
+    ) -> ReportOutput {
+        let today = job_config.date_of(now);
+        let resolve = |date: Option<DateArg>| date.map(|date| date.resolve(today));
+        let to = resolve(self.to).unwrap_or(today.previous_day().unwrap_or(today));
+        let from = resolve(self.from)
+            .or(config.balance_start_date)
+            .or_else(|| manager.dates().next())
+            .unwrap_or(today);
+
+        manager.load_range(from, to);
+        let days = day_balances(job_config, manager, from, to, now, config.daily_quotas());
+        let balance = days.iter().map(DayBalance::delta).sum::<Duration>();
+        ReportOutput {
+            from,
+            to,
+            total_seconds: None,
+            balance_seconds: Some(balance.whole_seconds()),
+            violations: None,
+            classes: BTreeMap::new(),
+            projects: BTreeMap::new(),
+            text: render_balance(&Locale::new(config), &days, balance),
+        }
+    }
+}
+
+/// result of `timetrax report`
+#[derive(Serialize, Debug)]
+pub struct ReportOutput {
+    pub from: Date,
+    pub to: Date,
+    /// time tracked in the period, not computed for `--balance` and `--compliance`
+    pub total_seconds: Option<i64>,
+    /// overtime of the period, negative if time is missing, only computed for `--balance`
+    /// and `--week`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance_seconds: Option<i64>,
+    /// days missing a required break, only computed for `--compliance`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violations: Option<usize>,
+    /// time per class name
+    pub classes: BTreeMap<String, i64>,
+    /// time per project name, attributed as configured
+    pub projects: BTreeMap<String, i64>,
+    /// the report as printed
+    #[serde(skip)]
+    pub text: String,
+}
+
+impl RenderOutput for ReportOutput {
+    fn render(&self, out: &mut impl std::io::Write, format: OutputFormat) -> std::io::Result<()> {
+        render_text_or_json(out, format, &self.text, self)
+    }
+}
+
+impl ExecutableCommand for CommandReport {
+    type Error = crate::error::Error;
+    type Output = ReportOutput;
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        self.output(config, job_config, &manager)
+    }
+}
+
+fn class_name(job_config: &JobConfig, class: &Identifier) -> String {
+    match job_config.resolve_class(class) {
+        Some(class) => class.inner.name.clone(),
+        None => UNRESOLVED.to_string(),
+    }
+}
+
+fn project_name(job_config: &JobConfig, project: &Identifier) -> String {
+    match job_config.resolve_project(project) {
+        Some(project) => project.inner.name.clone(),
+        None => {
+            error!("Failed to resolve project with id {}", project);
+            project.to_string()
+        }
+    }
+}
+
+/// one line per class, the time of classes deleted from the job config is summed up
+/// in a separate line after the known classes
+fn render_class_totals(
+    locale: &Locale,
+    job_config: &JobConfig,
+    totals: &BTreeMap<Identifier, Duration>,
+) -> String {
+    let mut out = String::new();
+    let (resolved, unresolved): (Vec<_>, Vec<_>) = totals
+        .iter()
+        .partition(|(class, _)| job_config.resolve_class(*class).is_some());
+
+    for (class, duration) in resolved {
+        let _ = writeln!(
+            out,
+            " - {}: {}",
+            locale.paint_class(job_config, class, &class_name(job_config, class)),
+            format_duration_pretty(duration, false)
+        );
+    }
+    if !unresolved.is_empty() {
+        for (class, _) in &unresolved {
+            error!("Failed to resolve class with id {}", class);
+        }
+        let _ = writeln!(
+            out,
+            " - {}: {}",
+            UNRESOLVED,
+            format_duration_pretty(
+                unresolved
+                    .iter()
+                    .map(|(_, duration)| **duration)
+                    .sum::<SafeDuration>()
+                    .total(),
+                false
+            )
+        );
+    }
+    out
+}
+
+/// human readable report
+fn render_report(
+    locale: &Locale,
+    job_config: &JobConfig,
+    from: Date,
+    to: Date,
+    totals: &Totals,
+    group: ReportGroup,
+) -> String {
+    let mut out = String::new();
+
+    if from == to {
+        let _ = writeln!(out, "Report for {}", locale.format_date(from));
+    } else {
+        let _ = writeln!(
+            out,
+            "Report from {} to {}",
+            locale.format_date(from),
+            locale.format_date(to)
+        );
+    }
+
+    if !totals.blockers.is_empty() {
+        let _ = writeln!(out, "Blocked time per class:");
+        out.push_str(&render_class_totals(locale, job_config, &totals.blockers));
+    }
+
+    if totals.classes.is_empty() {
+        let _ = writeln!(out, "No activities tracked.");
+        return out;
+    }
+
+    let _ = writeln!(out, "Time per class:");
+    out.push_str(&render_class_totals(locale, job_config, &totals.classes));
+    let _ = writeln!(
+        out,
+        "Total time tracked: {}",
+        format_duration_pretty(totals.total(), false)
+    );
+
+    match group {
+        ReportGroup::Project if !totals.projects.is_empty() => {
+            let _ = writeln!(out, "Time per project:");
+            for (project, duration) in &totals.projects {
+                let _ = writeln!(
+                    out,
+                    " - {}: {}",
+                    project_name(job_config, project),
+                    format_duration_pretty(duration, false)
+                );
+            }
+        }
+        ReportGroup::Client if !totals.clients.is_empty() => {
+            let _ = writeln!(out, "Time per client:");
+            for (client, duration) in &totals.clients {
+                let _ = writeln!(
+                    out,
+                    " - {}: {}",
+                    client.as_deref().unwrap_or("(none)"),
+                    format_duration_pretty(duration, false)
+                );
+            }
+        }
+        _ => {}
+    }
+    let _ = writeln!(
+        out,
+        "Project time attributed using policy: {}",
+        totals.attribution
+    );
+
+    out
+}
+
+/// e.g. `work 7h 30m, break 30m (8h 0m)`, `0m` if nothing was tracked
+fn format_class_line(
+    locale: &Locale,
+    job_config: &JobConfig,
+    classes: &BTreeMap<Identifier, Duration>,
+) -> String {
+    if classes.is_empty() {
+        return format_duration_pretty(Duration::ZERO, false);
+    }
+    format!(
+        "{} ({})",
+        classes
+            .iter()
+            .map(|(class, duration)| format!(
+                "{} {}",
+                locale.paint_class(job_config, class, &class_name(job_config, class)),
+                format_duration_pretty(duration, false)
+            ))
+            .join(", "),
+        format_duration_pretty(
+            classes.values().copied().sum::<SafeDuration>().total(),
+            false
+        )
+    )
+}
+
+/// one line per day of the week containing `date`, days without a file count as zero
+fn week_output(
+    locale: &Locale,
+    job_config: &JobConfig,
+    manager: &Manager,
+    date: Date,
+    now: OffsetDateTime,
+    quotas: DailyQuotas,
+    attribution: Attribution,
+) -> ReportOutput {
+    let start = locale.week_start_of(date);
+    let days = (0..7)
+        .map(|offset| start + Duration::days(offset))
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Week from {} to {}",
+        locale.format_date(days[0]),
+        locale.format_date(days[6])
+    );
+
+    let mut week = BTreeMap::new();
+    let mut tracked = SafeDuration::default();
+    let mut segments = SafeDuration::default();
+    let mut totals = Totals::new(attribution);
+    for date in &days {
+        let mut classes = BTreeMap::new();
+        if let Some(day) = manager.day(*date) {
+            let (closure, _) = day_closure(job_config, *date, day, now);
+            classes = Activity::summarize_by_class(&closure);
+            tracked += quota_time(job_config, &closure);
+            segments += Activity::sum_durations(&closure);
+            totals.add_closure(job_config, &closure);
+            let end_limit = (*date == job_config.date_of(now)).then_some(now.time());
+            totals.add_blockers(&day.blockers, end_limit, job_config.day_boundary);
+        }
+        for (class, duration) in &classes {
+            *week.entry(class.clone()).or_insert(SafeDuration::default()) += *duration;
+        }
+        let _ = writeln!(
+            out,
+            " - {}{}: {}",
+            locale.format_date(*date),
+            job_config
+                .public_holiday(*date)
+                .map(|holiday| format!(" ({})", holiday.name))
+                .unwrap_or_default(),
+            format_class_line(locale, job_config, &classes)
+        );
+    }
+    let week = week
+        .into_iter()
+        .map(|(class, sum)| (class, sum.total()))
+        .collect();
+    let _ = writeln!(
+        out,
+        "Week total: {}",
+        format_class_line(locale, job_config, &week)
+    );
+
+    let quota = days
+        .iter()
+        .map(|date| {
+            let day = manager.day(*date);
+            day_quota(job_config, *date, day, quotas)
+        })
+        .sum::<SafeDuration>()
+        .total();
+    let tracked = tracked.total();
+    let delta = tracked.saturating_sub(quota);
+    let _ = writeln!(
+        out,
+        "Work quota: {} of {} ({})",
+        format_duration_pretty(tracked, false),
+        format_duration_pretty(quota, false),
+        format_delta(locale, delta)
+    );
+    if let Some(warning) = segments.warning() {
+        let _ = writeln!(out, "{}", warning);
+    }
+
+    let seconds = |duration: &Duration| duration.whole_seconds();
+    ReportOutput {
+        from: days[0],
+        to: days[6],
+        total_seconds: Some(seconds(&totals.total())),
+        balance_seconds: Some(seconds(&delta)),
+        violations: None,
+        classes: (totals.classes.iter())
+            .map(|(class, duration)| (class_name(job_config, class), seconds(duration)))
+            .collect(),
+        projects: (totals.projects.iter())
+            .map(|(project, duration)| (project_name(job_config, project), seconds(duration)))
+            .collect(),
+        text: out,
+    }
+}
+
+/// signed difference to the work quota, overtime in red and time left to work in green
+fn format_delta(locale: &Locale, delta: Duration) -> String {
+    let text = format!(
+        "{}{}",
+        if delta.is_negative() { "" } else { "+" },
+        format_duration_pretty(delta, false)
+    );
+    if delta.is_positive() {
+        locale.paint(RED, &text)
+    } else if delta.is_negative() {
+        locale.paint(GREEN, &text)
+    } else {
+        text
+    }
+}
+
+/// days shown with their delta below the balance
+const BALANCE_RECENT_DAYS: usize = 7;
+
+/// the balance of all days and the deltas of the most recent ones
+fn render_balance(locale: &Locale, days: &[DayBalance], balance: Duration) -> String {
+    let mut out = String::new();
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        let _ = writeln!(out, "No days to balance.");
+        return out;
+    };
+    let _ = writeln!(
+        out,
+        "Balance from {} to {}: {}",
+        locale.format_date(first.date),
+        locale.format_date(last.date),
+        format_delta(locale, balance)
+    );
+    let _ = writeln!(out, "Last {} days:", days.len().min(BALANCE_RECENT_DAYS));
+    for day in &days[days.len().saturating_sub(BALANCE_RECENT_DAYS)..] {
+        let _ = writeln!(
+            out,
+            " - {}: {} of {} ({}){}",
+            locale.format_date(day.date),
+            format_duration_pretty(day.tracked, false),
+            format_duration_pretty(day.quota, false),
+            format_delta(locale, day.delta()),
+            match (&day.public_holiday, day.holiday) {
+                (Some(name), _) => format!(", {}", name),
+                (None, true) => ", holiday".to_string(),
+                (None, false) => String::new(),
+            }
+        );
+    }
+    out
+}
+
+/// work and break time of each day, with the break missing on days violating the break rules
+fn render_compliance(locale: &Locale, days: &[(Date, BreakCompliance)]) -> String {
+    let mut out = String::new();
+    if days.is_empty() {
+        let _ = writeln!(out, "No tracked days to check.");
+        return out;
+    }
+    let format = |duration| format_duration_pretty(duration, false);
+    for (date, day) in days {
+        if day.is_compliant() {
+            let _ = writeln!(
+                out,
+                " - {}: {} work, {} break",
+                locale.format_date(*date),
+                format(day.work),
+                format(day.breaks)
+            );
+        } else {
+            let _ = writeln!(
+                out,
+                " - {}: {} work but only {} break, {} missing",
+                locale.format_date(*date),
+                format(day.work),
+                format(day.breaks),
+                format(day.missing())
+            );
+        }
+    }
+    match days.iter().filter(|(_, day)| !day.is_compliant()).count() {
+        0 => {
+            let _ = writeln!(out, "All days comply with the break rules.");
+        }
+        violations => {
+            let _ = writeln!(
+                out,
+                "{} of {} days miss a required break.",
+                violations,
+                days.len()
+            );
+        }
+    }
+    out
+}
+
+/// time per class of each person, one column per person
+fn render_people(job_config: &JobConfig, people: &[(String, Totals)]) -> String {
+    let classes = people
+        .iter()
+        .flat_map(|(_, totals)| totals.classes.keys())
+        .unique()
+        .sorted_by_key(|class| std::cmp::Reverse(totals_of(people, class)))
+        .collect::<Vec<_>>();
+
+    let mut rows = vec![
+        std::iter::once(String::new())
+            .chain(people.iter().map(|(person, _)| person.clone()))
+            .collect::<Vec<_>>(),
+    ];
+    for class in classes {
+        rows.push(
+            std::iter::once(class_name(job_config, class))
+                .chain(people.iter().map(|(_, totals)| {
+                    format_duration_pretty(
+                        totals.classes.get(class).copied().unwrap_or_default(),
+                        false,
+                    )
+                }))
+                .collect(),
+        );
+    }
+    rows.push(
+        std::iter::once("total".to_string())
+            .chain(
+                people
+                    .iter()
+                    .map(|(_, totals)| format_duration_pretty(totals.total(), false)),
+            )
+            .collect(),
+    );
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Time per person:");
+    for line in format_table(&rows) {
+        let _ = writeln!(out, " {}", line);
+    }
+    out
+}
+
+fn totals_of(people: &[(String, Totals)], class: &Identifier) -> Duration {
+    people
+        .iter()
+        .filter_map(|(_, totals)| totals.classes.get(class).copied())
+        .sum::<SafeDuration>()
+        .total()
+}
+
+/// share of each class and project next to its target
+fn render_shares(shares: &[Share], tolerance: f32) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Shares of the tracked time (tolerance {:.1} percentage points):",
+        tolerance
+    );
+
+    for share in shares {
+        let kind = match share.kind {
+            ShareKind::Class => "class",
+            ShareKind::Project => "project",
+        };
+        let _ = write!(out, " - {} {}: {:.1}%", kind, share.name, share.actual);
+        if let (Some(target), Some(deviation)) = (share.target, share.deviation()) {
+            let _ = write!(out, " (target {:.1}%, {:+.1})", target, deviation);
+            if share.exceeds(tolerance) {
+                let _ = write!(out, " OFF TARGET");
+            }
+        }
+        let _ = writeln!(out);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::locale::tests::german_config;
+    use crate::data::activity::Activity;
+    use crate::data::day::{Day, DayInner};
+    use crate::data::public_holiday::PublicHoliday;
+    use crate::testing::{self, ManualClock, TempDataDir, at};
+    use time::{Month, Time};
+    use uuid::Uuid;
+
+    fn activity(class: &str, start: (u8, u8), end: (u8, u8)) -> Activity {
+        testing::activity(
+            class,
+            Time::from_hms(start.0, start.1, 0).unwrap(),
+            Some(Time::from_hms(end.0, end.1, 0).unwrap()),
+        )
+    }
+
+    fn fixture_days(today: Date) -> [Day; 2] {
+        [
+            Day {
+                date: today,
+                inner: DayInner {
+                    // the break interrupts the work, 2h 30m + 1h 15m of work
+                    activities: vec![
+                        activity("work", (9, 0), (12, 0)),
+                        activity("break", (10, 0), (10, 30)),
+                        activity("work", (13, 0), (14, 15)),
+                    ],
+                    ..DayInner::default()
+                },
+            },
+            Day {
+                date: today.previous_day().unwrap(),
+                inner: DayInner {
+                    activities: vec![activity("work", (8, 0), (9, 0))],
+                    ..DayInner::default()
+                },
+            },
+        ]
+    }
+
+    #[test]
+    fn test_report_per_class_totals() {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(today, Time::from_hms(18, 0, 0).unwrap())).install();
+        let job_config = JobConfig::default();
+        let dir = TempDataDir::with_days(&job_config, fixture_days(today)).unwrap();
+        let manager = dir.manager().unwrap();
+        let render = |args: &[&str]| {
+            CommandReport::parse_from(["report"].iter().chain(args))
+                .render(dir.app_config(), &job_config, &manager)
+                .unwrap()
+        };
+
+        let report = render(&[]);
+        assert!(report.starts_with("Report for Friday, 3 May 2024\n"));
+        assert!(report.contains(
+            "Time per class:\n - work: 3h 45m\n - break: 30m\nTotal time tracked: 4h 15m\n"
+        ));
+        assert_eq!(render(&["--date", "2024-05-03"]), report);
+
+        let report = render(&["--date", "2024-05-02"]);
+        assert!(report.contains("Time per class:\n - work: 1h 0m\nTotal time tracked: 1h 0m\n"));
+
+        let report = render(&["--from", "2024-05-02", "--to", "2024-05-03", "--show-gaps"]);
+        assert!(report.contains("Gaps on Friday, 3 May 2024:\n - 12:00:00 - 13:00:00 (1h 0m)\n"));
+        assert!(!report.contains("Gaps on Thursday"));
+        let report = render(&["--show-gaps", "--min-gap", "2h"]);
+        assert!(report.contains("No gaps between the activities.\n"));
+    }
+
+    #[test]
+    fn test_report_of_a_class() {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(today, Time::from_hms(18, 0, 0).unwrap())).install();
+        let job_config = JobConfig::default();
+        let dir = TempDataDir::with_days(&job_config, fixture_days(today)).unwrap();
+        let manager = dir.manager().unwrap();
+        let render = |args: &[&str]| {
+            CommandReport::parse_from(["report"].iter().chain(args)).render(
+                dir.app_config(),
+                &job_config,
+                &manager,
+            )
+        };
+
+        // the break from 10:00 to 10:30 takes over the work from 09:00 to 12:00
+        let report = render(&["--class", "@work"]).unwrap();
+        assert!(report.contains("Time per class:\n - work: 3h 45m\nTotal time tracked: 3h 45m\n"));
+        let report = render(&["--class", "@break"]).unwrap();
+        assert!(report.contains("Time per class:\n - break: 30m\nTotal time tracked: 30m\n"));
+        // the raw durations ignore the break
+        let report = render(&["--class", "@work", "--raw"]).unwrap();
+        assert!(report.contains("Time per class:\n - work: 4h 15m\nTotal time tracked: 4h 15m\n"));
+        let report = render(&["-c", "@work", "-c", "@break", "--raw"]).unwrap();
+        assert!(report.contains("Total time tracked: 4h 45m\n"), "{report}");
+
+        let error = render(&["--class", "@meeting"]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert!(CommandReport::try_parse_from(["report", "--raw"]).is_err());
+    }
+
+    #[test]
+    fn test_report_of_a_project() {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(today, Time::from_hms(18, 0, 0).unwrap())).install();
+        let mut job_config = JobConfig::default();
+        for name in ["clientA", "clientB"] {
+            job_config.projects.push(crate::data::project::Project {
+                id: Uuid::new_v4(),
+                inner: crate::data::project::ProjectInner {
+                    name: name.to_string(),
+                    description: None,
+                    client: None,
+                    target_share: None,
+                },
+            });
+        }
+        let with_project = |mut activity: Activity, project: &str| {
+            activity.projects = vec![Identifier::ByName(project.into())];
+            activity
+        };
+        let day = Day {
+            date: today,
+            inner: DayInner {
+                activities: vec![
+                    with_project(activity("work", (9, 0), (12, 0)), "clientA"),
+                    with_project(activity("work", (11, 0), (13, 0)), "clientB"),
+                ],
+                ..DayInner::default()
+            },
+        };
+        let dir = TempDataDir::with_days(&job_config, [day]).unwrap();
+        let manager = dir.manager().unwrap();
+        let render = |args: &[&str]| {
+            CommandReport::parse_from(["report"].iter().chain(args)).render(
+                dir.app_config(),
+                &job_config,
+                &manager,
+            )
+        };
+
+        assert!(render(&[]).unwrap().contains("Total time tracked: 4h 0m\n"));
+        // the overlapping time of client B does not leak into the total of client A
+        let report = render(&["--project", "@clientA"]).unwrap();
+        assert!(report.contains("Total time tracked: 3h 0m\n"), "{report}");
+        assert!(report.contains(" - clientA: 3h 0m\n"));
+        assert!(!report.contains("clientB"));
+
+        let error = render(&["--project", "@clientC"]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    fn snapshot(path: &std::path::Path) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(path).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(snapshot(&path));
+            } else {
+                files.push((path.clone(), std::fs::read(&path).unwrap()));
+            }
+        }
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_report_merges_team_directories() {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(today, Time::from_hms(18, 0, 0).unwrap())).install();
+        let job_config = JobConfig::default();
+        let dir = TempDataDir::new(&job_config).unwrap();
+        dir.write_day_file(
+            "2024-05-03.json",
+            r#"{"date": "2024-05-03", "activities": [{"id": "00000000-0000-4000-8000-000000000001",
+                "name": "coding", "class": "@work", "time": {"start": "09:00:00", "end": "11:00:00"}}]}"#,
+        )
+        .unwrap();
+        let team = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/team");
+        let before = snapshot(&team);
+
+        let report = CommandReport::parse_from([
+            "report".as_ref(),
+            "--merge-from".as_ref(),
+            team.join("alice").as_os_str(),
+            team.join("bob").as_os_str(),
+        ])
+        .render(dir.app_config(), &job_config, &dir.manager().unwrap())
+        .unwrap();
+
+        assert!(
+            report
+                .contains("Time per class:\n - work: 6h 30m\n - research: 1h 0m\n - break: 30m\n")
+        );
+        let local = person_name(dir.path());
+        let people = report.split("Time per person:\n").nth(1).unwrap();
+        let lines = people.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[..5]
+                .iter()
+                .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect::<Vec<_>>(),
+            [
+                format!("{} alice bob", local),
+                "work 2h 0m 2h 30m 2h 0m".to_string(),
+                "research 0m 1h 0m 0m".to_string(),
+                "break 0m 30m 0m".to_string(),
+                "total 2h 0m 4h 0m 2h 0m".to_string(),
+            ]
+        );
+        assert!(
+            report.contains("Warning: class break has priority 7 for alice, using priority 5.\n")
+        );
+        assert_eq!(snapshot(&team), before);
+    }
+
+    #[test]
+    fn test_weekly_report() {
+        let friday = Date::from_calendar_date(2024, Month::May, 10).unwrap();
+        let _guard = ManualClock::new(at(friday, Time::from_hms(18, 0, 0).unwrap())).install();
+        let job_config = JobConfig::default();
+        let dir = TempDataDir::new(&job_config).unwrap();
+        for (name, contents) in [
+            (
+                "2024-05-06.json",
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/testdata/week/2024-05-06.json"
+                )),
+            ),
+            (
+                "2024-05-07.json",
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/testdata/week/2024-05-07.json"
+                )),
+            ),
+            (
+                "2024-05-09.json",
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/testdata/week/2024-05-09.json"
+                )),
+            ),
+        ] {
+            dir.write_day_file(name, contents).unwrap();
+        }
+        let manager = dir.manager().unwrap();
+        let render = |args: &[&str]| {
+            CommandReport::parse_from(["report"].iter().chain(args))
+                .render(dir.app_config(), &job_config, &manager)
+                .unwrap()
+        };
+
+        let report = render(&["--week"]);
+        assert_eq!(
+            report,
+            "Week from Monday, 6 May 2024 to Sunday, 12 May 2024\n \
+             - Monday, 6 May 2024: work 7h 30m, break 30m (8h 0m)\n \
+             - Tuesday, 7 May 2024: work 4h 0m (4h 0m)\n \
+             - Wednesday, 8 May 2024: 0m\n \
+             - Thursday, 9 May 2024: work 2h 0m, break 15m (2h 15m)\n \
+             - Friday, 10 May 2024: 0m\n \
+             - Saturday, 11 May 2024: 0m\n \
+             - Sunday, 12 May 2024: 0m\n\
+             Week total: work 13h 30m, break 45m (14h 15m)\n\
+             Work quota: 13h 30m of 40h 0m (-26h 30m)\n"
+        );
+        assert_eq!(render(&["--week", "--date", "2024-05-12"]), report);
+
+        let output = CommandReport::parse_from(["report", "--week"])
+            .output(dir.app_config(), &job_config, &manager)
+            .unwrap();
+        assert_eq!(
+            output.from,
+            Date::from_calendar_date(2024, Month::May, 6).unwrap()
+        );
+        assert_eq!(
+            output.to,
+            Date::from_calendar_date(2024, Month::May, 12).unwrap()
+        );
+        assert_eq!(output.total_seconds, Some((14 * 60 + 15) * 60));
+        assert_eq!(output.balance_seconds, Some(-(26 * 60 + 30) * 60));
+        assert_eq!(output.classes["work"], (13 * 60 + 30) * 60);
+        assert_eq!(output.classes["break"], 45 * 60);
+        assert!(
+            render(&["--week", "--date", "2024-05-13"])
+                .contains("Week total: 0m\nWork quota: 0m of 40h 0m (-40h 0m)\n")
+        );
+    }
+
+    /// three weeks of days from 2024-05-06 to 2024-05-26
+    fn balance_fixture(job_config: &JobConfig) -> TempDataDir {
+        let dir = TempDataDir::new(job_config).unwrap();
+        let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/balance");
+        for entry in std::fs::read_dir(fixture).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            dir.write_day_file(name, &std::fs::read_to_string(&path).unwrap())
+                .unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_balance_report() {
+        let may = |day| Date::from_calendar_date(2024, Month::May, day).unwrap();
+        // the monday after the three weeks of the fixture
+        let _guard = ManualClock::new(at(may(27), Time::from_hms(10, 0, 0).unwrap())).install();
+        let job_config = JobConfig::default();
+        let dir = balance_fixture(&job_config);
+        let manager = dir.manager().unwrap();
+        let balance = |config: &AppConfig, args: &[&str]| {
+            CommandReport::parse_from(["report", "--balance"].iter().chain(args))
+                .output(config, &job_config, &manager)
+                .unwrap()
+        };
+
+        let report = balance(dir.app_config(), &[]);
+        assert_eq!((report.from, report.to), (may(6), may(26)));
+        // +1h in the first week, the untracked tuesday of the second week costs 8h,
+        // +2h on wednesday and on saturday, -1h and +2h in the third week
+        assert_eq!(report.balance_seconds, Some(-2 * 3600));
+        assert_eq!(
+            report.text,
+            "Balance from Monday, 6 May 2024 to Sunday, 26 May 2024: -2h 0m\n\
+             Last 7 days:\n \
+             - Monday, 20 May 2024: 8h 0m of 8h 0m (+0m)\n \
+             - Tuesday, 21 May 2024: 7h 0m of 8h 0m (-1h 0m)\n \
+             - Wednesday, 22 May 2024: 0m of 8h 0m (+0m), holiday\n \
+             - Thursday, 23 May 2024: 10h 0m of 8h 0m (+2h 0m)\n \
+             - Friday, 24 May 2024: 8h 0m of 8h 0m (+0m)\n \
+             - Saturday, 25 May 2024: 0m of 0m (+0m)\n \
+             - Sunday, 26 May 2024: 0m of 0m (+0m)\n"
+        );
+
+        assert_eq!(
+            balance(dir.app_config(), &["--to", "2024-05-12"]).balance_seconds,
+            Some(3600)
+        );
+        assert_eq!(
+            balance(dir.app_config(), &["--from", "2024-05-13"]).balance_seconds,
+            Some(-3 * 3600)
+        );
+        let config = AppConfig {
+            balance_start_date: Some(may(20)),
+            ..dir.app_config().clone()
+        };
+        assert_eq!(balance(&config, &[]).balance_seconds, Some(3600));
+        // today counts with the time tracked so far
+        assert_eq!(
+            balance(&config, &["--to", "today"]).balance_seconds,
+            Some(-7 * 3600)
+        );
+
+        let empty = TempDataDir::new(&job_config).unwrap();
+        let manager = empty.manager().unwrap();
+        let report = CommandReport::parse_from(["report", "--balance"])
+            .output(empty.app_config(), &job_config, &manager)
+            .unwrap();
+        assert_eq!(report.balance_seconds, Some(0));
+        assert_eq!(report.text, "No days to balance.\n");
+    }
+
+    #[test]
+    fn test_public_holidays_count_as_met() {
+        let may = |day| Date::from_calendar_date(2024, Month::May, day).unwrap();
+        let _guard = ManualClock::new(at(may(27), Time::from_hms(10, 0, 0).unwrap())).install();
+        let mut job_config = JobConfig::default();
+        // the untracked tuesday of the fixture
+        job_config.add_holidays([PublicHoliday {
+            date: may(14),
+            name: "Betriebsausflug".to_string(),
+        }]);
+        let dir = balance_fixture(&job_config);
+        let manager = dir.manager().unwrap();
+        let render = |args: &[&str]| {
+            CommandReport::parse_from(["report"].iter().chain(args))
+                .output(dir.app_config(), &job_config, &manager)
+                .unwrap()
+        };
+
+        // +2h on wednesday and on saturday, nothing missing on tuesday
+        let balance = render(&["--balance", "--from", "2024-05-13", "--to", "2024-05-19"]);
+        assert_eq!(balance.balance_seconds, Some(4 * 3600));
+        assert!(
+            balance
+                .text
+                .contains(" - Tuesday, 14 May 2024: 0m of 0m (+0m), Betriebsausflug\n"),
+            "{}",
+            balance.text
+        );
+        // the whole fixture is 8h less short
+        assert_eq!(render(&["--balance"]).balance_seconds, Some(6 * 3600));
+
+        let week = render(&["--week", "--date", "2024-05-14"]).text;
+        assert!(
+            week.contains(" - Tuesday, 14 May 2024 (Betriebsausflug): 0m\n"),
+            "{week}"
+        );
+        assert!(
+            week.contains("Work quota: 36h 0m of 32h 0m (+4h 0m)\n"),
+            "{week}"
+        );
+    }
+
+    #[test]
+    fn test_compliance_report() {
+        let date = |day| Date::from_calendar_date(2024, Month::May, day).unwrap();
+        let _guard = ManualClock::new(at(date(10), Time::from_hms(18, 0, 0).unwrap())).install();
+        let job_config = JobConfig::default();
+        let day = |day, activities| Day {
+            date: date(day),
+            inner: DayInner {
+                activities,
+                ..DayInner::default()
+            },
+        };
+        let dir = TempDataDir::with_days(
+            &job_config,
+            [
+                day(
+                    6,
+                    vec![
+                        activity("work", (8, 0), (12, 0)),
+                        activity("break", (12, 0), (12, 30)),
+                        activity("work", (12, 30), (17, 0)),
+                    ],
+                ),
+                day(
+                    7,
+                    vec![
+                        activity("work", (8, 0), (12, 0)),
+                        activity("break", (12, 0), (12, 15)),
+                        activity("work", (12, 15), (16, 25)),
+                    ],
+                ),
+                // exactly 6h of work need no break
+                day(8, vec![activity("work", (8, 0), (14, 0))]),
+            ],
+        )
+        .unwrap();
+        let manager = dir.manager().unwrap();
+        let output = |args: &[&str]| {
+            CommandReport::parse_from(["report", "--compliance"].iter().chain(args))
+                .output(dir.app_config(), &job_config, &manager)
+                .unwrap()
+        };
+
+        let report = output(&["--from", "2024-05-06", "--to", "2024-05-10"]);
+        assert_eq!(report.violations, Some(1));
+        assert_eq!(
+            report.text,
+            " - Monday, 6 May 2024: 8h 30m work, 30m break\n \
+             - Tuesday, 7 May 2024: 8h 10m work but only 15m break, 15m missing\n \
+             - Wednesday, 8 May 2024: 6h 0m work, 0m break\n\
+             1 of 3 days miss a required break.\n"
+        );
+        assert_eq!(
+            output(&["--date", "2024-05-06"]).text,
+            " - Monday, 6 May 2024: 8h 30m work, 30m break\n\
+             All days comply with the break rules.\n"
+        );
+        let empty = output(&[]);
+        assert_eq!(empty.violations, Some(0));
+        assert_eq!(empty.text, "No tracked days to check.\n");
+    }
+
+    #[test]
+    fn test_weekly_report_colors() {
+        let friday = Date::from_calendar_date(2024, Month::May, 10).unwrap();
+        let now = at(friday, Time::from_hms(18, 0, 0).unwrap());
+        let mut job_config = JobConfig::default();
+        job_config
+            .resolve_class_mut(Identifier::ByName("work".into()))
+            .unwrap()
+            .inner
+            .color = Some("#ff8800".into());
+        let dir = TempDataDir::new(&job_config).unwrap();
+        dir.write_day_file(
+            "2024-05-06.json",
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/week/2024-05-06.json"
+            )),
+        )
+        .unwrap();
+        let manager = dir.manager().unwrap();
+        let render = |color: bool, quota: Duration| {
+            let config = AppConfig {
+                color,
+                ..AppConfig::default()
+            };
+            week_output(
+                &Locale::new(&config),
+                &job_config,
+                &manager,
+                friday,
+                now,
+                DailyQuotas::uniform(quota),
+                Attribution::SplitEvenly,
+            )
+            .text
+        };
+
+        let plain = render(false, Duration::hours(8));
+        assert!(!plain.contains('\x1b'));
+        let colored = render(true, Duration::hours(8));
+        assert!(colored.contains("\x1b[38;2;255;136;0mwork\x1b[0m 7h 30m"));
+        // time left to work on the week in green, overtime in red
+        assert!(colored.contains("(\x1b[32m-32h 30m\x1b[0m)"));
+        assert!(render(true, Duration::hours(1)).contains("(\x1b[31m+2h 30m\x1b[0m)"));
+        assert!(!render(false, Duration::hours(1)).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_render_unresolved_classes_separately() {
+        let config = AppConfig::default();
+        let job_config = JobConfig::default();
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+
+        let mut totals = Totals::new(Attribution::SplitEvenly);
+        totals
+            .classes
+            .insert(Identifier::ByName("work".into()), Duration::minutes(90));
+        totals
+            .classes
+            .insert(Identifier::Uuid(Uuid::nil()), Duration::minutes(20));
+        totals
+            .classes
+            .insert(Identifier::ByName("retired".into()), Duration::minutes(10));
+        totals
+            .blockers
+            .insert(Identifier::ByName("retired".into()), Duration::minutes(15));
+
+        assert_eq!(
+            render_report(
+                &Locale::new(&config),
+                &job_config,
+                date,
+                date,
+                &totals,
+                ReportGroup::Project
+            ),
+            "Report for Friday, 3 May 2024\n\
+             Blocked time per class:\n\
+             \x20- <UNRESOLVED>: 15m\n\
+             Time per class:\n\
+             \x20- work: 1h 30m\n\
+             \x20- <UNRESOLVED>: 30m\n\
+             Total time tracked: 2h 0m\n\
+             Project time attributed using policy: split-evenly\n"
+        );
+    }
+
+    #[test]
+    fn test_report_strict_rejects_unknown_classes() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/unknown_class_day.json"
+            )),
+        )
+        .unwrap();
+
+        dir.execute(&CommandReport::parse_from(["report", "-f", "2024-05-02"]))
+            .unwrap();
+        assert!(
+            dir.execute(&CommandReport::parse_from([
+                "report",
+                "-f",
+                "2024-05-02",
+                "--strict"
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_render_german_header() {
+        let config = german_config();
+        let locale = Locale::new(&config);
+        let job_config = JobConfig::default();
+        let from = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+
+        let mut totals = Totals::new(Attribution::SplitEvenly);
+        totals
+            .classes
+            .insert(Identifier::ByName("work".into()), Duration::minutes(90));
+
+        assert_eq!(
+            render_report(
+                &locale,
+                &job_config,
+                from,
+                from,
+                &totals,
+                ReportGroup::Project
+            ),
+            "Report for Freitag, 3 Mai 2024\n\
+             Time per class:\n\
+             \x20- work: 1h 30m\n\
+             Total time tracked: 1h 30m\n\
+             Project time attributed using policy: split-evenly\n"
+        );
+
+        let to = from.next_day().unwrap();
+        assert!(
+            render_report(
+                &locale,
+                &job_config,
+                from,
+                to,
+                &Totals::default(),
+                ReportGroup::Client
+            )
+            .starts_with("Report from Freitag, 3 Mai 2024 to Samstag, 4 Mai 2024\n")
+        );
+    }
+
+    #[test]
+    fn test_render_shares() {
+        let share = |kind, name: &str, actual, target| Share {
+            kind,
+            name: name.into(),
+            actual,
+            target,
+        };
+        let shares = [
+            share(ShareKind::Class, "work", 100.0, None),
+            share(ShareKind::Project, "projA", 72.5, Some(70.0)),
+            share(ShareKind::Project, "support", 10.0, Some(20.0)),
+        ];
+
+        assert_eq!(
+            render_shares(&shares, 5.0),
+            "Shares of the tracked time (tolerance 5.0 percentage points):\n\
+             \x20- class work: 100.0%\n\
+             \x20- project projA: 72.5% (target 70.0%, +2.5)\n\
+             \x20- project support: 10.0% (target 20.0%, -10.0) OFF TARGET\n"
+        );
+    }
+}
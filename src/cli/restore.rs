@@ -0,0 +1,228 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_date_arg;
+use crate::cli::day::ensure_unlocked;
+use crate::cli::format::format_table;
+use crate::cli::show::format_time;
+use crate::data::app_config::AppConfig;
+use crate::data::backup::{Backup, list_backups};
+use crate::data::day::Day;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::{Manager, backup_folder, day_file_name};
+use clap::Parser;
+use log::{error, info};
+use std::fmt::Write;
+use time::{Date, OffsetDateTime};
+
+/// Backups of a day file are taken every time it is overwritten, see `backup_keep`
+#[derive(Parser)]
+pub struct CommandRestore {
+    /// Day to restore (YYYY-MM-DD)
+    #[arg(value_parser = parse_date_arg)]
+    pub date: Date,
+    /// Backup to restore, 1 is the newest, lists the backups if omitted
+    #[arg(long)]
+    pub version: Option<usize>,
+}
+
+/// backups of the day file of the date, the newest first
+fn day_backups(
+    config: &AppConfig,
+    manager: &Manager,
+    date: Date,
+) -> crate::error::Result<Vec<Backup>> {
+    let loaded_name = manager
+        .annotated_day(date)
+        .and_then(|day| day.origin())
+        .and_then(|origin| origin.file_name())
+        .and_then(|name| name.to_str());
+    let name = match loaded_name {
+        Some(name) => name.to_string(),
+        None => day_file_name(date)?,
+    };
+    Ok(list_backups(
+        &backup_folder(config, manager.data_path())?,
+        &name,
+    )?)
+}
+
+/// local time the backup was taken
+fn taken_at(backup: &Backup) -> String {
+    match OffsetDateTime::from_unix_timestamp(backup.timestamp) {
+        Ok(taken) => {
+            let taken = taken.to_offset(local_time::now().offset());
+            format!("{} {}", taken.date(), format_time(taken.time()))
+        }
+        Err(_) => backup.timestamp.to_string(),
+    }
+}
+
+/// table of the backups with the versions accepted by `--version`
+fn render_backups(date: Date, backups: &[Backup]) -> String {
+    let mut out = String::new();
+    if backups.is_empty() {
+        let _ = writeln!(out, "There are no backups of {}.", date);
+        return out;
+    }
+
+    let header = ["VERSION", "TAKEN", "FILE"];
+    let mut rows = vec![header.map(String::from).to_vec()];
+    for (index, backup) in backups.iter().enumerate() {
+        rows.push(vec![
+            (index + 1).to_string(),
+            taken_at(backup),
+            backup.path.display().to_string(),
+        ]);
+    }
+    for line in format_table(&rows) {
+        let _ = writeln!(out, "{}", line);
+    }
+    out
+}
+
+impl ExecutableCommand for CommandRestore {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        _job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let date = self.date;
+        let backups = day_backups(config, &manager, date)?;
+        let Some(version) = self.version else {
+            print!("{}", render_backups(date, &backups));
+            return Ok(());
+        };
+
+        let Some(backup) = version.checked_sub(1).and_then(|index| backups.get(index)) else {
+            error!(
+                "There is no backup {} of {}, see `timetrax restore {}`",
+                version, date, date
+            );
+            return Err(
+                std::io::Error::new(std::io::ErrorKind::NotFound, "Backup not found").into(),
+            );
+        };
+        let restored: Day = serde_json::from_str(&std::fs::read_to_string(&backup.path)?)?;
+        if restored.date != date {
+            error!(
+                "The backup {} is of {}, not of {}",
+                backup.path.display(),
+                restored.date,
+                date
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Backup of another day",
+            )
+            .into());
+        }
+        if let Some(current) = manager.day(date) {
+            ensure_unlocked(date, current)?;
+        }
+
+        // saved like any other modification, which backs up the current content first
+        *manager.get_or_create_day_mut(date) = restored.inner;
+        manager.save()?;
+        info!("Restored {} from {}", date, backup.path.display());
+        println!(
+            "Restored {} from the backup taken at {}",
+            date,
+            taken_at(backup)
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandRemove;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Duration, Month, Time};
+
+    const DAY: &str = r#"{"date": "2024-05-02", "activities": [
+        {"id": "00000000-0000-4000-8000-000000000001", "class": "@work", "name": "coding",
+         "time": {"start": "09:00:00", "end": "12:00:00"}},
+        {"id": "00000000-0000-4000-8000-000000000002", "class": "@work", "name": "meeting",
+         "time": {"start": "13:00:00", "end": "14:00:00"}}
+    ]}"#;
+
+    fn may(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::May, day).unwrap()
+    }
+
+    #[test]
+    fn test_restore_round_trip() {
+        let clock = ManualClock::new(at(may(3), Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", DAY).unwrap();
+        let original = dir.day(may(2)).unwrap().unwrap();
+
+        let manager = dir.manager().unwrap();
+        assert!(
+            day_backups(dir.app_config(), &manager, may(2))
+                .unwrap()
+                .is_empty()
+        );
+        drop(manager);
+
+        clock.advance(Duration::minutes(1));
+        dir.execute(&CommandRemove::parse_from([
+            "remove",
+            "00000000-0000-4000-8000-000000000002",
+            "--date",
+            "2024-05-02",
+            "--force",
+        ]))
+        .unwrap();
+        assert_eq!(dir.day(may(2)).unwrap().unwrap().activities.len(), 1);
+
+        let manager = dir.manager().unwrap();
+        let backups = day_backups(dir.app_config(), &manager, may(2)).unwrap();
+        drop(manager);
+        assert_eq!(backups.len(), 1);
+        let listed = render_backups(may(2), &backups);
+        assert!(listed.starts_with("VERSION"));
+        assert!(listed.contains("2024-05-03 09:01:00"));
+
+        clock.advance(Duration::minutes(1));
+        dir.execute(&CommandRestore::parse_from([
+            "restore",
+            "2024-05-02",
+            "--version",
+            "1",
+        ]))
+        .unwrap();
+        let restored = dir.day(may(2)).unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(&original).unwrap()
+        );
+
+        // restoring backed up the modified day, which can be restored in turn
+        let manager = dir.manager().unwrap();
+        let backups = day_backups(dir.app_config(), &manager, may(2)).unwrap();
+        drop(manager);
+        assert_eq!(backups.len(), 2);
+        dir.execute(&CommandRestore::parse_from([
+            "restore",
+            "2024-05-02",
+            "--version",
+            "1",
+        ]))
+        .unwrap();
+        assert_eq!(dir.day(may(2)).unwrap().unwrap().activities.len(), 1);
+
+        let missing = dir.execute(&CommandRestore::parse_from([
+            "restore",
+            "2024-05-02",
+            "--version",
+            "9",
+        ]));
+        assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_date_arg;
+use crate::data::BASIC_TIME_FORMAT;
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::{AnnotatedDayInformation, Manager};
+use clap::Parser;
+use serde_json::json;
+use std::fmt::Write;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, OffsetDateTime, Time};
+
+#[derive(Parser, Default)]
+pub struct CommandShow {
+    /// Day to show (YYYY-MM-DD), defaults to today
+    #[arg(long, value_parser = parse_date_arg)]
+    date: Option<Date>,
+    /// Show the stored activities only
+    #[arg(long, group = "part")]
+    raw: bool,
+    /// Show the closure segments only
+    #[arg(long, group = "part")]
+    closure: bool,
+    /// Show the stored activities and the closure segments (default)
+    #[arg(long, group = "part")]
+    both: bool,
+    /// Clamp open activities of today to now instead of the end of the day
+    #[arg(long)]
+    clamp: bool,
+    /// Print a JSON object instead of text
+    #[arg(long)]
+    json: bool,
+}
+
+pub(crate) fn format_time(time: Time) -> String {
+    time.format(&*BASIC_TIME_FORMAT)
+        .unwrap_or_else(|_| "<INVALID>".to_string())
+}
+
+fn format_timestamp(timestamp: OffsetDateTime) -> String {
+    timestamp
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| "<INVALID>".to_string())
+}
+
+pub(crate) fn format_interval(time: &Interval) -> String {
+    format!(
+        "{} - {}",
+        format_time(time.start),
+        time.end
+            .map(format_time)
+            .unwrap_or_else(|| "<OPEN>".to_string())
+    )
+}
+
+pub(crate) fn class_name(job_config: &JobConfig, class: &Identifier) -> String {
+    job_config
+        .resolve_class(class)
+        .map(|class| class.inner.name.clone())
+        .unwrap_or_else(|| class.to_string())
+}
+
+impl CommandShow {
+    fn show_raw(&self) -> bool {
+        self.raw || self.both || !self.closure
+    }
+
+    fn show_closure(&self) -> bool {
+        self.closure || self.both || !self.raw
+    }
+
+    /// closure segments in wall-clock time, open activities run until the end of the day
+    /// unless `--clamp` is given
+    fn closure(
+        &self,
+        job_config: &JobConfig,
+        date: Date,
+        activities: &[Activity],
+        now: OffsetDateTime,
+    ) -> Vec<Activity> {
+        let boundary = job_config.day_boundary;
+        let end_limit = (self.clamp && date == job_config.date_of(now)).then_some(now.time());
+        let (closure, _) =
+            Activity::calculate_activity_closure(job_config, activities, None, end_limit);
+        closure
+            .into_iter()
+            .map(|mut segment| {
+                segment.time = segment.time.from_day_time(boundary);
+                segment
+            })
+            .collect()
+    }
+
+    /// stored records and closure of the day as text, never creates the day
+    fn render(
+        &self,
+        job_config: &JobConfig,
+        manager: &Manager,
+        now: OffsetDateTime,
+    ) -> std::io::Result<String> {
+        let date = self.date.unwrap_or(job_config.date_of(now));
+        let day = manager.annotated_day(date);
+        if self.json {
+            return self.render_json(job_config, date, day, now);
+        }
+
+        let mut out = String::new();
+        let Some(day) = day else {
+            let _ = writeln!(out, "No day file for {}.", date);
+            return Ok(out);
+        };
+        let inner = day.inner();
+        let _ = writeln!(
+            out,
+            "Day {}{}",
+            date,
+            if inner.locked { " (locked)" } else { "" }
+        );
+        let _ = writeln!(
+            out,
+            "Origin: {}",
+            day.origin()
+                .map(|origin| origin.display().to_string())
+                .unwrap_or_else(|| "<UNSAVED>".to_string())
+        );
+
+        if self.show_raw() {
+            let _ = writeln!(out, "Activities:");
+            for activity in &inner.activities {
+                let _ = writeln!(
+                    out,
+                    " - {} {} {} {}: {}",
+                    activity.short_id(),
+                    activity.id,
+                    format_interval(&activity.time),
+                    class_name(job_config, &activity.class),
+                    activity.name.as_deref().unwrap_or("<NO DESCRIPTION>")
+                );
+                if !activity.projects.is_empty() {
+                    let projects = activity.projects.iter().map(ToString::to_string);
+                    let _ = writeln!(
+                        out,
+                        "     projects: {}",
+                        projects.collect::<Vec<_>>().join(", ")
+                    );
+                }
+                if !activity.tags.is_empty() {
+                    let _ = writeln!(out, "     tags: {}", activity.tags.join(", "));
+                }
+                if let Some(created_at) = activity.created_at {
+                    let _ = writeln!(out, "     created: {}", format_timestamp(created_at));
+                }
+                if let Some(modified_at) = activity.modified_at {
+                    let _ = writeln!(out, "     modified: {}", format_timestamp(modified_at));
+                }
+            }
+        }
+
+        if self.show_closure() {
+            let _ = writeln!(out, "Closure:");
+            for segment in self.closure(job_config, date, &inner.activities, now) {
+                let _ = writeln!(
+                    out,
+                    " - {} {}: {}",
+                    format_interval(&segment.time),
+                    class_name(job_config, &segment.class),
+                    segment.name.as_deref().unwrap_or("<NO DESCRIPTION>")
+                );
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn render_json(
+        &self,
+        job_config: &JobConfig,
+        date: Date,
+        day: Option<&AnnotatedDayInformation>,
+        now: OffsetDateTime,
+    ) -> std::io::Result<String> {
+        let activities = day
+            .map(|day| day.inner().activities.as_slice())
+            .unwrap_or_default();
+        let mut object = serde_json::Map::new();
+        object.insert("date".to_string(), json!(date.to_string()));
+        object.insert(
+            "origin".to_string(),
+            json!(day.and_then(|day| day.origin())),
+        );
+        object.insert(
+            "locked".to_string(),
+            json!(day.is_some_and(|day| day.inner().locked)),
+        );
+
+        if self.show_raw() {
+            let records = activities
+                .iter()
+                .map(|activity| {
+                    let mut record =
+                        serde_json::to_value(activity).map_err(std::io::Error::other)?;
+                    record["hash"] = json!(activity.short_id());
+                    Ok(record)
+                })
+                .collect::<std::io::Result<Vec<_>>>()?;
+            object.insert("activities".to_string(), json!(records));
+        }
+        if self.show_closure() {
+            let segments = self
+                .closure(job_config, date, activities, now)
+                .iter()
+                .map(|segment| {
+                    json!({
+                        "start": format_time(segment.time.start),
+                        "end": segment.time.end.map(format_time),
+                        "class": class_name(job_config, &segment.class),
+                        "name": segment.name,
+                    })
+                })
+                .collect::<Vec<_>>();
+            object.insert("closure".to_string(), json!(segments));
+        }
+
+        Ok(serde_json::Value::Object(object).to_string())
+    }
+}
+
+impl ExecutableCommand for CommandShow {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        _config: &AppConfig,
+        job_config: &mut JobConfig,
+        manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        let output = self.render(job_config, &manager, local_time::now())?;
+        if self.json {
+            println!("{}", output);
+        } else {
+            print!("{}", output);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TempDataDir, at};
+    use time::Month;
+
+    const FOLD_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/fold_day.json"
+    ));
+
+    fn setup() -> (OffsetDateTime, TempDataDir) {
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let now = at(date, Time::from_hms(15, 0, 0).unwrap());
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", FOLD_DAY).unwrap();
+        (now, dir)
+    }
+
+    fn show(dir: &TempDataDir, now: OffsetDateTime, args: &[&str]) -> String {
+        let output = CommandShow::parse_from(["show"].iter().chain(args))
+            .render(&dir.job_config().unwrap(), &dir.manager().unwrap(), now)
+            .unwrap();
+        output.replace(&dir.path().display().to_string(), "<DIR>")
+    }
+
+    #[test]
+    fn test_show_raw_layout() {
+        let (now, dir) = setup();
+
+        assert_eq!(
+            show(&dir, now, &["--raw", "--date", "2024-05-02"]),
+            "Day 2024-05-02 (locked)\n\
+             Origin: <DIR>/data/2024-05-02.json\n\
+             Activities:\n \
+             - cyfsdn 00000000-0000-4000-8000-000000000001 09:00:00 - 18:00:00 work: Working at the office\n     \
+             created: 2024-05-02T09:00:00Z\n \
+             - gmzyqv 00000000-0000-4000-8000-000000000002 12:00:00 - 13:00:00 break: Lunch break\n \
+             - qzsxpm 00000000-0000-4000-8000-000000000003 10:00:00 - 11:00:00 work: Project meeting\n     \
+             tags: meeting\n \
+             - hmlfok 00000000-0000-4000-8000-000000000004 10:30:00 - 11:30:00 work: Project meeting 2\n \
+             - ozhwwo 00000000-0000-4000-8000-000000000005 13:00:00 - <OPEN> work: Project meeting 3\n     \
+             created: 2024-05-02T13:00:00Z\n     \
+             modified: 2024-05-02T13:05:00Z\n"
+        );
+    }
+
+    #[test]
+    fn test_show_closure_layout() {
+        let (now, dir) = setup();
+
+        let closure = "Closure:\n \
+             - 09:00:00 - 10:00:00 work: Working at the office\n \
+             - 10:00:00 - 10:30:00 work: Project meeting; Working at the office\n \
+             - 10:30:00 - 11:00:00 work: Project meeting; Project meeting 2; Working at the office\n \
+             - 11:00:00 - 11:30:00 work: Project meeting 2; Working at the office\n \
+             - 11:30:00 - 12:00:00 work: Working at the office\n \
+             - 12:00:00 - 13:00:00 break: Lunch break; Working at the office\n \
+             - 13:00:00 - 18:00:00 work: Project meeting 3; Working at the office\n";
+        // the open activity is not clamped to now unless asked
+        assert_eq!(
+            show(&dir, now, &["--closure"]),
+            format!(
+                "Day 2024-05-02 (locked)\nOrigin: <DIR>/data/2024-05-02.json\n{closure} \
+                 - 18:00:00 - <OPEN> work: Project meeting 3\n"
+            )
+        );
+        assert!(
+            show(&dir, now, &["--closure", "--clamp"]).ends_with(
+                " - 13:00:00 - 15:00:00 work: Project meeting 3; Working at the office\n"
+            )
+        );
+
+        let both = show(&dir, now, &[]);
+        assert!(both.contains("Activities:\n"));
+        assert!(both.contains(closure));
+    }
+
+    #[test]
+    fn test_show_never_creates_the_day() {
+        let (now, dir) = setup();
+
+        assert_eq!(
+            show(&dir, now, &["--date", "2024-05-03"]),
+            "No day file for 2024-05-03.\n"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&show(&dir, now, &["--date", "2024-05-03", "--json"])).unwrap();
+        assert_eq!(json["origin"], serde_json::Value::Null);
+        assert_eq!(json["activities"], json!([]));
+        assert_eq!(dir.manager().unwrap().dates().count(), 1);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&show(&dir, now, &["--raw", "--json"])).unwrap();
+        assert_eq!(json["locked"], true);
+        assert_eq!(json["activities"][0]["hash"], "cyfsdn");
+        assert_eq!(json["activities"][4]["time"]["start"], "13:00:00");
+        assert!(json.get("closure").is_none());
+    }
+}
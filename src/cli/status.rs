@@ -1,150 +1,2102 @@
 use crate::cli::ExecutableCommand;
+use crate::cli::args::{
+    DateArg, parse_duration_arg, parse_identifier_arg, parse_relative_date_arg,
+};
+use crate::cli::class::check_class_filter;
+use crate::cli::color::{GREEN, RED};
+use crate::cli::fixup::stale_warning;
+use crate::cli::format::{
+    expand_template, format_duration_pretty, format_elapsed, format_progress_bar, format_tags,
+    terminal_width, wrap_text,
+};
+use crate::cli::locale::Locale;
+use crate::cli::output::{OutputFormat, RenderOutput, render_text_or_json};
+use crate::cli::project::check_project_filter;
 use crate::data::activity::Activity;
+use crate::data::activity_closure::ClosureDiagnostics;
 use crate::data::app_config::AppConfig;
+use crate::data::break_rules::BreakCompliance;
+use crate::data::day::{DayInner, DayType};
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
 use crate::data::job_config::JobConfig;
+use crate::data::local_time;
 use crate::data::manager::Manager;
-use clap::Parser;
-use itertools::Itertools;
-use log::error;
-use std::borrow::Borrow;
-use time::{Duration, OffsetDateTime};
+use crate::data::project::Project;
+use crate::data::report::{
+    QuotaForecast, ReportFilter, WeekProgress, day_quota, quota_time, raw_segments,
+};
+use crate::data::safe_duration::SafeDuration;
+use crate::data::workspace::WorkspaceState;
+use crate::profile;
+use crate::report::status::StatusReport;
+use clap::{ArgGroup, Parser};
+use log::{error, trace, warn};
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::io::IsTerminal;
+use time::{Date, Duration, OffsetDateTime, Time};
 
-fn format_duration_pretty<Q: Borrow<Duration>>(duration: Q, show_seconds: bool) -> String {
-    let duration = duration.borrow();
+#[derive(Parser, Default, Clone)]
+#[command(group(ArgGroup::new("day").multiple(false)))]
+pub struct CommandStatus {
+    /// Show the status of the given day (YYYY-MM-DD, or e.g. yesterday, monday, -3d)
+    #[arg(long, value_parser = parse_relative_date_arg, allow_hyphen_values = true, group = "day")]
+    date: Option<DateArg>,
+    /// Show the status of yesterday
+    #[arg(short = '1', long, group = "day")]
+    yesterday: bool,
+    /// Show the status of the day N days ago
+    #[arg(long, value_name = "N", group = "day")]
+    days_ago: Option<u32>,
+    /// Show the totals of the days from this day on (e.g. 2024-05-03 or -7d), up to --to or today
+    #[arg(
+        long,
+        value_parser = parse_relative_date_arg,
+        allow_hyphen_values = true,
+        conflicts_with_all = ["day", "osc_title", "osc_clear", "json", "short", "gaps"]
+    )]
+    from: Option<DateArg>,
+    /// Last day of the totals of --from (YYYY-MM-DD, or e.g. yesterday, monday, -3d)
+    #[arg(
+        long,
+        value_parser = parse_relative_date_arg,
+        allow_hyphen_values = true,
+        requires = "from",
+    )]
+    to: Option<DateArg>,
+    /// Only show activities of this project, may be given multiple times
+    #[arg(
+        short,
+        long = "project",
+        value_parser = parse_identifier_arg,
+        conflicts_with_all = ["osc_title", "osc_clear", "short"]
+    )]
+    projects: Vec<Identifier>,
+    /// Only show time of this class, may be given multiple times. Time the activities of a
+    /// higher priority class take over, e.g. a break during work, does not count for the class
+    #[arg(
+        short,
+        long = "class",
+        value_parser = parse_identifier_arg,
+        conflicts_with_all = ["osc_title", "osc_clear", "short", "json"]
+    )]
+    classes: Vec<Identifier>,
+    /// Add up the durations of the activities of --class as they are, without folding them:
+    /// overlapping time counts for every activity and higher priority classes are ignored
+    #[arg(long, requires = "classes")]
+    raw: bool,
+    /// Fail instead of warning if activities refer to unknown classes
+    #[arg(long)]
+    strict: bool,
+    /// Show the evidence recorded for each activity
+    #[arg(short, long)]
+    verbose: bool,
+    /// List the untracked time between the activities
+    #[arg(long)]
+    gaps: bool,
+    /// Shortest untracked time listed by --gaps, e.g. 5m
+    #[arg(long, requires = "gaps", value_parser = parse_duration_arg)]
+    min_gap: Option<Duration>,
+    /// Set the terminal title to the current activity instead of printing the status
+    #[arg(long, conflicts_with = "osc_clear")]
+    osc_title: bool,
+    /// Reset the terminal title set by --osc-title
+    #[arg(long)]
+    osc_clear: bool,
+    /// Emit the terminal title even if stdout is not a terminal
+    #[arg(long)]
+    force: bool,
+    /// Print the status as JSON for scripts, see `StatusReport` for the fields
+    #[arg(long, conflicts_with_all = ["osc_title", "osc_clear", "gaps", "verbose"])]
+    json: bool,
+    /// Print a single line for status bars, see `status_short_format` of the app config
+    #[arg(
+        long,
+        conflicts_with_all = ["osc_title", "osc_clear", "gaps", "verbose", "json"]
+    )]
+    short: bool,
+}
 
-    let sign = if duration.is_negative() { "-" } else { "" };
+/// terminal title of `--osc-title` while an activity is running
+const TITLE_TEMPLATE: &str = "▶ {class}[: {name}] {elapsed}";
+/// terminal title of `--osc-title` while nothing is running
+const IDLE_TITLE: &str = "■ idle";
 
-    let hours = duration.whole_hours().abs();
-    let minutes = (duration.whole_minutes() % 60).abs();
-    let seconds = (duration.whole_seconds() % 60).abs();
+impl CommandStatus {
+    /// the options leave a status of a single day to write as JSON, like those of `--json`
+    pub fn supports_json(&self) -> bool {
+        !(self.osc_title
+            || self.osc_clear
+            || self.gaps
+            || self.verbose
+            || self.short
+            || self.from.is_some()
+            || !self.classes.is_empty())
+    }
 
-    let hours = if hours > 0 {
-        format!("{}h ", hours)
-    } else {
-        "".to_string()
-    };
-    let minutes = if minutes > 0 || hours.len() > 0 {
-        format!("{}m ", minutes)
+    /// day to display
+    fn resolve_date(&self, today: Date) -> Date {
+        if let Some(date) = self.date {
+            date.resolve(today)
+        } else if self.yesterday {
+            today - Duration::days(1)
+        } else {
+            today - Duration::days(self.days_ago.unwrap_or(0) as i64)
+        }
+    }
+
+    /// human readable status of the given day, with a quota section if `week` is given
+    fn render(
+        &self,
+        locale: &Locale,
+        job_config: &JobConfig,
+        day: Option<&DayInner>,
+        week: Option<&WeekProgress>,
+        current_project: Option<&Project>,
+        now: OffsetDateTime,
+    ) -> std::io::Result<String> {
+        let mut out = String::new();
+        let today = job_config.date_of(now);
+        let date = self.resolve_date(today);
+        let is_today = date == today;
+        let boundary = job_config.day_boundary;
+        let elapsed = |time: &Interval| {
+            time.to_day_time(boundary)
+                .elapsed_at(local_time::to_day_time(now.time(), boundary))
+        };
+
+        let _ = writeln!(out, "Status for {} ({})", date, relative_day(date, today));
+        if let Some(project) = current_project {
+            let _ = writeln!(out, "Current project: {}", project.inner.name);
+        }
+        if day.is_some_and(|day| day.day_type == DayType::Holiday) {
+            let _ = writeln!(out, "Holiday");
+        }
+        if let Some(holiday) = job_config.public_holiday(date) {
+            let _ = writeln!(out, "Public holiday: {}", holiday.name);
+        }
+
+        let Some(day) = day.filter(|day| !day.activities.is_empty() || !day.blockers.is_empty())
+        else {
+            let _ = writeln!(out, "No activities for this day.");
+            return Ok(out);
+        };
+
+        // unterminated activities of other days are an anomaly, they do not run until now
+        let end_limit = is_today.then_some(now.time());
+        let (folded, mut diagnostics) = self.timeline(job_config, day, end_limit);
+        out.push_str(&render_timeline(locale, job_config, &folded, boundary));
+        let tracked = Activity::sum_durations(&folded);
+        let _ = writeln!(
+            out,
+            "Total time tracked: {}",
+            format_duration_pretty(tracked.total(), true)
+        );
+        if let Some(week) = week {
+            out.push_str(&render_quota(
+                locale,
+                week,
+                day_quota(job_config, date, Some(day), week.defaults),
+                quota_time(job_config, &folded),
+            ));
+        }
+
+        let class_name = |class: &Identifier| match job_config.resolve_class(class) {
+            Some(resolved) => locale.paint_class(job_config, class, &resolved.inner.name),
+            None => {
+                error!("Failed to resolve class with id {}", class);
+                "ERR".to_string()
+            }
+        };
+
+        let (ended, ongoing): (Vec<_>, Vec<_>) = day
+            .activities
+            .iter()
+            .filter(|activity| self.class_filter().matches(job_config, activity))
+            .partition(|a| a.time.is_complete());
+
+        if ongoing.is_empty() {
+            let _ = writeln!(out, "No ongoing activities.");
+        } else if is_today {
+            let status = Activity::fold_inner(
+                job_config,
+                ongoing.iter().copied(),
+                None,
+                None,
+                &mut diagnostics,
+            );
+            match status.and_then(|status| job_config.resolve_class(&status.class)) {
+                Some(class) => {
+                    let name = locale.paint_class(job_config, &class.id.into(), &class.inner.name);
+                    let _ = writeln!(out, "Status: {}", name);
+                }
+                None => {
+                    error!("Failed to compute status.");
+                    let _ = writeln!(out, "Status: ERR");
+                }
+            }
+
+            let _ = writeln!(out, "Ongoing activities:");
+            for activity in ongoing {
+                let _ = writeln!(
+                    out,
+                    " - [{}] {} ({})",
+                    class_name(&activity.class),
+                    format_listed(locale, activity),
+                    format_elapsed(elapsed(&activity.time))
+                );
+                out.push_str(&render_description(activity));
+                out.push_str(&self.render_evidence(locale, activity));
+            }
+        } else {
+            let _ = writeln!(
+                out,
+                "Unterminated activities (not counted, end them with `pop`):"
+            );
+            for activity in ongoing {
+                let _ = writeln!(
+                    out,
+                    " - [{}] {}",
+                    class_name(&activity.class),
+                    format_listed(locale, activity)
+                );
+                out.push_str(&render_description(activity));
+                out.push_str(&self.render_evidence(locale, activity));
+            }
+        }
+
+        // blockers are part of the timeline and the total above
+        if !day.blockers.is_empty() {
+            let _ = writeln!(out, "Blockers:");
+            for blocker in &day.blockers {
+                let time = match blocker.time.to_day_time(boundary).duration() {
+                    Some(duration) => format_duration_pretty(duration, false),
+                    None if is_today => format_elapsed(elapsed(&blocker.time)),
+                    None => "not terminated".to_string(),
+                };
+                let _ = writeln!(
+                    out,
+                    " - [{}] {} ({})",
+                    class_name(&blocker.class),
+                    locale.format_blocker(blocker),
+                    time
+                );
+            }
+        }
+
+        if ended.is_empty() {
+            let _ = writeln!(out, "No ended activities.");
+        } else {
+            let _ = writeln!(out, "Ended activities:");
+            for activity in ended {
+                let _ = writeln!(
+                    out,
+                    " - [{}] {}",
+                    class_name(&activity.class),
+                    format_listed(locale, activity)
+                );
+                out.push_str(&render_description(activity));
+                out.push_str(&self.render_evidence(locale, activity));
+            }
+        }
+
+        diagnostics.check(self.strict)?;
+        if let Some(warning) = diagnostics.warning() {
+            let _ = writeln!(out, "{}", warning);
+        }
+        if let Some(warning) = tracked.warning() {
+            let _ = writeln!(out, "{}", warning);
+        }
+        if is_today && day.day_type != DayType::Holiday {
+            out.push_str(&render_break_warning(job_config, day, now));
+        }
+
+        Ok(out)
+    }
+}
+
+impl CommandStatus {
+    /// restriction of `--project`
+    fn filter(&self) -> ReportFilter {
+        ReportFilter {
+            projects: self.projects.clone(),
+            ..ReportFilter::default()
+        }
+    }
+
+    /// the day with only the activities of `--project`, they are left out of the closure
+    /// and the listings
+    fn select<'a>(
+        &self,
+        job_config: &JobConfig,
+        day: Option<&'a DayInner>,
+    ) -> Option<Cow<'a, DayInner>> {
+        if self.projects.is_empty() {
+            day.map(Cow::Borrowed)
+        } else {
+            day.map(|day| Cow::Owned(self.filter().apply(job_config, day)))
+        }
+    }
+
+    /// restriction of `--class`
+    fn class_filter(&self) -> ReportFilter {
+        ReportFilter {
+            classes: self.classes.clone(),
+            ..ReportFilter::default()
+        }
+    }
+
+    /// closure of the day limited to the segments of `--class`,
+    /// or with `--raw` the activities of the classes as they are
+    fn timeline(
+        &self,
+        job_config: &JobConfig,
+        day: &DayInner,
+        end_limit: Option<Time>,
+    ) -> (Vec<Activity>, ClosureDiagnostics) {
+        let (mut timeline, diagnostics) = day.effective_timeline(job_config, end_limit);
+        if self.raw {
+            timeline = raw_segments(job_config, &self.class_filter(), day, end_limit);
+            timeline.sort_by_key(|activity| activity.time.start);
+        } else if !self.classes.is_empty() {
+            let filter = self.class_filter();
+            timeline.retain(|segment| filter.matches(job_config, segment));
+        }
+        (timeline, diagnostics)
+    }
+
+    /// time tracked on each day from `from` to `to` and in total,
+    /// only the closure of today is clamped to now
+    fn render_range(
+        &self,
+        locale: &Locale,
+        job_config: &JobConfig,
+        from: Date,
+        to: Date,
+        manager: &Manager,
+        now: OffsetDateTime,
+    ) -> std::io::Result<String> {
+        if from > to {
+            error!("The range must start before it ends: {} > {}", from, to);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Range starts after it ends",
+            ));
+        }
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Status from {} to {}",
+            locale.format_date(from),
+            locale.format_date(to)
+        );
+        let mut total = SafeDuration::default();
+        let mut diagnostics = ClosureDiagnostics::default();
+        for (date, day) in manager.days_in(from..=to) {
+            let date = *date;
+            let day = &self.filter().apply(job_config, day);
+            if day.activities.is_empty() && day.blockers.is_empty() {
+                continue;
+            }
+            let end_limit = (date == job_config.date_of(now)).then_some(now.time());
+            let (closure, day_diagnostics) = self.timeline(job_config, day, end_limit);
+            diagnostics.merge(day_diagnostics);
+            let tracked = Activity::sum_durations(&closure);
+            let _ = writeln!(
+                out,
+                " - {}: {}",
+                locale.format_date(date),
+                format_duration_pretty(tracked.total(), false)
+            );
+            total += tracked;
+        }
+        let _ = writeln!(
+            out,
+            "Total time tracked: {}",
+            format_duration_pretty(total.total(), false)
+        );
+
+        diagnostics.check(self.strict)?;
+        if let Some(warning) = diagnostics.warning() {
+            let _ = writeln!(out, "{}", warning);
+        }
+        if let Some(warning) = total.warning() {
+            let _ = writeln!(out, "{}", warning);
+        }
+        Ok(out)
+    }
+
+    /// machine readable status of the day, printed by `--json`
+    fn report(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        date: Date,
+        day: Option<&DayInner>,
+        now: OffsetDateTime,
+    ) -> std::io::Result<StatusReport> {
+        let (report, diagnostics) = StatusReport::new(config, job_config, date, day, now);
+        diagnostics.check(self.strict)?;
+        if let Some(warning) = diagnostics.warning() {
+            warn!("{}", warning);
+        }
+        Ok(report)
+    }
+
+    /// single line of `--short` with the quota left to work on the day
+    fn short(
+        &self,
+        config: &AppConfig,
+        job_config: &JobConfig,
+        day: Option<&DayInner>,
+        now: OffsetDateTime,
+    ) -> String {
+        let date = self.resolve_date(job_config.date_of(now));
+        let is_today = date == job_config.date_of(now);
+        let (folded, _) = day
+            .map(|day| day.effective_timeline(job_config, is_today.then_some(now.time())))
+            .unwrap_or_default();
+        let quota = day_quota(job_config, date, day, config.daily_quotas());
+        let remaining = (quota - quota_time(job_config, &folded)).max(Duration::ZERO);
+
+        let compact = |duration| format_duration_pretty(duration, false).replace(' ', "");
+        let remaining = compact(remaining);
+        let Some(activity) = day
+            .filter(|_| is_today)
+            .and_then(|day| current_activity(job_config, day))
+        else {
+            return expand_template(&config.status_short_idle_format, |key| match key {
+                "remaining" => Some(remaining.clone()),
+                _ => None,
+            });
+        };
+
+        let boundary = job_config.day_boundary;
+        expand_template(&config.status_short_format, |key| match key {
+            // classes missing from the job config are shown as written in the day file
+            "class" => Some(
+                job_config
+                    .resolve_class(&activity.class)
+                    .map_or_else(|| activity.class.to_string(), |c| c.inner.name.clone()),
+            ),
+            "name" => activity.name.clone(),
+            "elapsed" => Some(compact(
+                activity
+                    .time
+                    .to_day_time(boundary)
+                    .elapsed_at(local_time::to_day_time(now.time(), boundary)),
+            )),
+            "project" => activity.projects.first().map(|project| {
+                job_config
+                    .resolve_project(project)
+                    .map_or_else(|| project.to_string(), |p| p.inner.name.clone())
+            }),
+            "remaining" => Some(remaining.clone()),
+            _ => None,
+        })
+    }
+
+    /// compact status of today for the terminal title, the most recently started open activity
+    fn title(&self, job_config: &JobConfig, day: Option<&DayInner>, now: OffsetDateTime) -> String {
+        let boundary = job_config.day_boundary;
+        let Some(activity) = day.and_then(|day| current_activity(job_config, day)) else {
+            return IDLE_TITLE.to_string();
+        };
+
+        let title = expand_template(TITLE_TEMPLATE, |key| match key {
+            "class" => job_config
+                .resolve_class(&activity.class)
+                .map(|class| class.inner.name.clone()),
+            "name" => activity.name.clone(),
+            "elapsed" => {
+                let elapsed = activity
+                    .time
+                    .to_day_time(boundary)
+                    .elapsed_at(local_time::to_day_time(now.time(), boundary));
+                Some(format_elapsed(elapsed).replace(' ', ""))
+            }
+            _ => None,
+        });
+        // control characters of names would end the escape sequence early
+        title.chars().filter(|c| !c.is_control()).collect()
+    }
+
+    /// write the OSC sequence setting (or with `--osc-clear` resetting) the terminal title,
+    /// nothing is written unless `out` is a terminal or `--force` is given
+    fn write_title(
+        &self,
+        out: &mut dyn std::io::Write,
+        is_terminal: bool,
+        job_config: &JobConfig,
+        day: Option<&DayInner>,
+        now: OffsetDateTime,
+    ) -> std::io::Result<()> {
+        if !is_terminal && !self.force {
+            trace!("Not setting the terminal title, stdout is not a terminal");
+            return Ok(());
+        }
+        let title = if self.osc_clear {
+            String::new()
+        } else {
+            self.title(job_config, day, now)
+        };
+        write!(out, "\x1b]0;{}\x07", title)?;
+        out.flush()
+    }
+
+    /// evidence lines of an activity, empty unless verbose
+    fn render_evidence(&self, locale: &Locale, activity: &Activity) -> String {
+        if !self.verbose {
+            return String::new();
+        }
+        activity
+            .evidence
+            .iter()
+            .map(|entry| {
+                format!(
+                    "     {} {}\n",
+                    locale.format_time(entry.at.time()),
+                    entry.text
+                )
+            })
+            .collect()
+    }
+}
+
+/// activity of the listings, followed by its tags
+fn format_listed(locale: &Locale, activity: &Activity) -> String {
+    let formatted = locale.format_activity(activity);
+    if activity.tags.is_empty() {
+        formatted
     } else {
-        "".to_string()
+        format!("{} {}", formatted, format_tags(&activity.tags))
+    }
+}
+
+/// description of a listed activity, wrapped to the terminal below it
+fn render_description(activity: &Activity) -> String {
+    match activity.description.as_deref() {
+        Some(description) if !description.trim().is_empty() => {
+            wrap_text(description, terminal_width(), "     ")
+        }
+        _ => String::new(),
+    }
+}
+
+/// the most recently started open activity of the day
+fn current_activity<'a>(job_config: &JobConfig, day: &'a DayInner) -> Option<&'a Activity> {
+    day.activities
+        .iter()
+        .filter(|activity| !activity.time.is_complete())
+        .max_by_key(|activity| {
+            local_time::to_day_time(activity.time.start, job_config.day_boundary)
+        })
+}
+
+/// how long before the work of today exceeds a break rule `status` warns about it
+const BREAK_WARNING_AHEAD: Duration = Duration::hours(1);
+
+/// warning if today misses a required break, or will soon if the ongoing work continues
+fn render_break_warning(job_config: &JobConfig, day: &DayInner, now: OffsetDateTime) -> String {
+    let mut out = String::new();
+    let (closure, _) = day.effective_timeline(job_config, Some(now.time()));
+    let compliance = BreakCompliance::of(job_config, &closure);
+    if !compliance.is_compliant() {
+        let _ = writeln!(
+            out,
+            "Warning: {} of work but only {} of break, {} missing",
+            format_duration_pretty(compliance.work, false),
+            format_duration_pretty(compliance.breaks, false),
+            format_duration_pretty(compliance.missing(), false)
+        );
+        return out;
+    }
+    let working = current_activity(job_config, day)
+        .is_some_and(|activity| job_config.counts_towards_quota(&activity.class));
+    if let Some((left, rule)) = compliance.upcoming(&job_config.break_rules)
+        && working
+        && left <= BREAK_WARNING_AHEAD
+    {
+        let _ = writeln!(
+            out,
+            "Warning: {} of break required after {} of work, in {} without a break",
+            format_duration_pretty(rule.break_required, false),
+            format_duration_pretty(rule.work_over, false),
+            format_duration_pretty(left, false)
+        );
+    }
+    out
+}
+
+/// progress bar of the day's work quota and the progress of the week
+fn render_quota(
+    locale: &Locale,
+    week: &WeekProgress,
+    quota: Duration,
+    tracked: Duration,
+) -> String {
+    let mut out = String::new();
+
+    if quota > Duration::ZERO {
+        let remaining = (quota - tracked).max(Duration::ZERO);
+        let text = format_duration_pretty(remaining, false);
+        // green only while there is time left to work
+        let remaining = if remaining > Duration::ZERO {
+            locale.paint(GREEN, &text)
+        } else {
+            text
+        };
+        let _ = writeln!(
+            out,
+            "Work quota: {} of {}, {} remaining",
+            overtime(locale, tracked, quota),
+            format_duration_pretty(quota, false),
+            remaining
+        );
+        let _ = writeln!(
+            out,
+            "{}",
+            format_progress_bar(
+                tracked.as_seconds_f64() / quota.as_seconds_f64(),
+                terminal_width()
+            )
+        );
+    }
+
+    let forecast = match week.forecast() {
+        QuotaForecast::Met => "met".to_string(),
+        QuotaForecast::On(date) => format!(
+            "expected to be met on {} at the current pace",
+            locale.format_date(date)
+        ),
+        QuotaForecast::Unknown => "no pace yet".to_string(),
     };
-    let seconds = if show_seconds && (seconds > 0 || minutes.len() > 0 || hours.len() > 0) {
-        format!("{}s", seconds)
+    let _ = writeln!(
+        out,
+        "This week: {} tracked of {} so far, weekly quota of {} {}",
+        overtime(locale, week.tracked, week.quota_so_far()),
+        format_duration_pretty(week.quota_so_far(), false),
+        format_duration_pretty(week.week_quota(), false),
+        forecast
+    );
+
+    out
+}
+
+/// `tracked` formatted, in red if it exceeds `quota`
+fn overtime(locale: &Locale, tracked: Duration, quota: Duration) -> String {
+    let formatted = format_duration_pretty(tracked, false);
+    if tracked > quota {
+        locale.paint(RED, &formatted)
     } else {
-        "".to_string()
-    };
+        formatted
+    }
+}
 
-    format!("{sign}{hours}{minutes}{seconds}")
+/// e.g. `today`, `yesterday`, `3 days ago` or `in 2 days`
+fn relative_day(date: Date, today: Date) -> String {
+    match (today - date).whole_days() {
+        0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        -1 => "tomorrow".to_string(),
+        days if days > 0 => format!("{days} days ago"),
+        days => format!("in {} days", -days),
+    }
 }
 
-#[derive(Parser, Default, Clone)]
-pub struct CommandStatus {}
+/// result of `timetrax status`
+#[derive(Debug)]
+pub struct StatusOutput {
+    /// status of the day, not computed for ranges and the terminal title
+    pub report: Option<StatusReport>,
+    /// the status as printed
+    pub text: String,
+}
+
+impl RenderOutput for StatusOutput {
+    fn render(&self, out: &mut impl std::io::Write, format: OutputFormat) -> std::io::Result<()> {
+        if format == OutputFormat::Json && self.report.is_none() {
+            error!("JSON is only available for the status of a single day");
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "No status of a single day",
+            ));
+        }
+        render_text_or_json(out, format, &self.text, &self.report)
+    }
+}
 
 impl ExecutableCommand for CommandStatus {
-    type Error = std::io::Error;
-    type Output = ();
+    type Error = crate::error::Error;
+    type Output = StatusOutput;
     fn execute(
         &self,
-        _config: &AppConfig,
+        config: &AppConfig,
         job_config: &mut JobConfig,
-        mut manager: Manager,
+        manager: Manager,
     ) -> Result<Self::Output, Self::Error> {
-        let today = OffsetDateTime::now_local()
-            .unwrap_or_else(|e| {
-                error!("Failed to get local time. Falling back to UTC: {}", e);
-                OffsetDateTime::now_utc()
-            })
-            .date();
+        let now = local_time::now();
+        if self.osc_title || self.osc_clear {
+            let today = job_config.date_of(now);
+            let day = manager.day(today);
+            let is_terminal = std::io::stdout().is_terminal();
+            let mut title = Vec::new();
+            self.write_title(&mut title, is_terminal, job_config, day, now)?;
+            return Ok(StatusOutput {
+                report: None,
+                text: String::from_utf8_lossy(&title).into_owned(),
+            });
+        }
 
-        let today = manager.get_or_create_day_ref(today);
+        check_project_filter(job_config, &self.filter())?;
+        check_class_filter(job_config, &self.class_filter())?;
+        // on stderr to keep the output parsable, e.g. for --json. only the most recent day,
+        // older ones are warned about by push and fixup. --short stays a single line
+        let stale = manager.recent_stale_open_activities(job_config.date_of(now));
+        if !stale.is_empty() && !self.short {
+            eprint!("{}", stale_warning(&stale));
+        }
+        if let Some(from) = self.from {
+            let today = job_config.date_of(now);
+            let from = from.resolve(today);
+            let to = self.to.map_or(today, |to| to.resolve(today));
+            manager.load_range(from, to);
+            let mut text =
+                self.render_range(&Locale::new(config), job_config, from, to, &manager, now)?;
+            text.push_str(&render_load_errors(&manager));
+            return Ok(StatusOutput { report: None, text });
+        }
 
-        if today.activities.is_empty() {
-            println!("No activities for today.");
-        } else {
-            let now = OffsetDateTime::now_local().unwrap_or_else(|e| {
-                error!("Failed to get local time. Falling back to UTC: {}", e);
-                OffsetDateTime::now_utc()
+        let date = self.resolve_date(job_config.date_of(now));
+        let day = manager.day(date);
+        let day = self.select(job_config, day);
+        let day = day.as_deref();
+        if self.json {
+            let mut report = self.report(config, job_config, date, day, now)?;
+            report.unloadable_day_files = unloadable_day_files(&manager);
+            let text = serde_json::to_string_pretty(&report)? + "\n";
+            return Ok(StatusOutput {
+                report: Some(report),
+                text,
             });
+        }
+        let (mut report, _) = StatusReport::new(config, job_config, date, day, now);
+        report.unloadable_day_files = unloadable_day_files(&manager);
 
-            let folded = Activity::calculate_activity_closure(
-                job_config,
-                &today.activities,
-                None,
-                Some(now.time()),
-            );
-            for activity in &folded {
-                println!(" --> {}", activity);
-            }
-            println!(
-                "Total time tracked today: {}",
-                format_duration_pretty(
-                    folded
-                        .iter()
-                        .map(|a| a.time.duration().unwrap_or_default())
-                        .sum::<Duration>(),
-                    true
-                )
-            );
+        if self.short {
+            // on stderr as --short stays a single line
+            eprint!("{}", render_load_errors(&manager));
+            return Ok(StatusOutput {
+                report: Some(report),
+                text: self.short(config, job_config, day, now) + "\n",
+            });
+        }
+        let week = WeekProgress::new(job_config, &manager, date, now, config.daily_quotas());
 
-            let ended = today
-                .activities
-                .iter()
-                .filter(|a| a.time.is_complete())
-                .cloned()
-                .collect_vec();
-            let ongoing = today
-                .activities
-                .iter()
-                .filter(|a| !a.time.is_complete())
-                .cloned()
-                .collect_vec();
-
-            if !ongoing.is_empty() {
-                let status = Activity::fold_inner(job_config, ongoing.iter(), None, None);
-                if let Some(status) = status {
-                    if let Some(class) = job_config.resolve_class(&status.class) {
-                        println!("Status: {}", class.inner.name);
-                    } else {
-                        error!("Failed to resolve class with id {}", status.class);
-                        println!("Status: ERR");
-                    }
-                } else {
-                    error!("Failed to compute status.");
-                    println!("Status: ERR");
-                }
+        let current_project =
+            WorkspaceState::current_project(config, manager.data_path(), job_config)?;
 
-                println!("Ongoing activities:");
-                for activity in ongoing {
-                    let class = match job_config.resolve_class(&activity.class) {
-                        Some(class) => class.inner.name.as_str(),
-                        None => {
-                            error!("Failed to resolve class with id {}", activity.class);
-                            "ERR"
-                        }
-                    };
-                    println!(" - [{}] {}", class, activity);
-                }
+        let _timer = profile::timer("render");
+        let mut text = self.render(
+            &Locale::new(config),
+            job_config,
+            day,
+            // the quota is about the whole day, not the time of some projects
+            (self.projects.is_empty() && self.classes.is_empty()).then_some(&week),
+            current_project,
+            now,
+        )?;
+        if self.gaps
+            && let Some(day) = day
+        {
+            let gaps = day.gaps(job_config, self.min_gap.unwrap_or(config.min_gap));
+            if gaps.is_empty() {
+                text.push_str("No gaps between the activities.\n");
             } else {
-                println!("No ongoing activities.");
+                text.push_str("Gaps:\n");
+                text.push_str(&render_gaps(
+                    &Locale::new(config),
+                    &gaps,
+                    job_config.day_boundary,
+                ));
             }
+        }
 
-            if !ended.is_empty() {
-                println!("Ended activities:");
-                for activity in ended {
-                    let class = match job_config.resolve_class(&activity.class) {
-                        Some(class) => class.inner.name.as_str(),
-                        None => {
-                            error!("Failed to resolve class with id {}", activity.class);
-                            "ERR"
-                        }
-                    };
-                    println!(" - [{}] {}", class, activity);
-                }
-            } else {
-                println!("No ended activities.");
-            }
+        text.push_str(&render_load_errors(&manager));
+
+        Ok(StatusOutput {
+            report: Some(report),
+            text,
+        })
+    }
+}
+
+/// day files used by the status which could not be parsed
+fn unloadable_day_files(manager: &Manager) -> Vec<std::path::PathBuf> {
+    (manager.load_errors().into_iter())
+        .map(|(path, _)| path.to_path_buf())
+        .collect()
+}
+
+/// warning about the day files used by the status which could not be parsed
+fn render_load_errors(manager: &Manager) -> String {
+    match manager.load_errors().len() {
+        0 => String::new(),
+        count => format!(
+            "Warning: {} day file(s) could not be loaded, run `timetrax repair`\n",
+            count
+        ),
+    }
+}
+
+/// one line per segment of the activity closure, segments are given in day time
+fn render_timeline(
+    locale: &Locale,
+    job_config: &JobConfig,
+    closure: &[Activity],
+    boundary: Option<Time>,
+) -> String {
+    closure
+        .iter()
+        .map(|activity| {
+            let activity = Activity {
+                time: activity.time.from_day_time(boundary),
+                ..activity.clone()
+            };
+            let line = locale.format_activity(&activity);
+            format!(
+                " --> {}\n",
+                locale.paint_class(job_config, &activity.class, &line)
+            )
+        })
+        .collect()
+}
+
+/// one line per untracked interval, `gaps` are in day time
+pub fn render_gaps(locale: &Locale, gaps: &[Interval], boundary: Option<Time>) -> String {
+    let mut out = String::new();
+    for gap in gaps {
+        let _ = writeln!(
+            out,
+            " - {} ({})",
+            locale.format_interval(&gap.from_day_time(boundary)),
+            format_duration_pretty(gap.duration().unwrap_or_default(), false)
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::data::app_config::ClockFormat;
+    use crate::data::day::Day;
+    use crate::data::public_holiday::PublicHoliday;
+    use crate::data::report::DailyQuotas;
+    use crate::testing::{self, Clock, ManualClock, TempDataDir, at};
+    use time::Month;
+    use uuid::Uuid;
+
+    const UNKNOWN_CLASS_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/unknown_class_day.json"
+    ));
+
+    #[test]
+    fn test_status_does_not_modify_activities() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.advance(Duration::hours(2));
+        dir.execute(&CommandStatus::default()).unwrap();
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert_eq!(
+            day.activities[0].time.start,
+            Time::from_hms(9, 0, 0).unwrap()
+        );
+        assert!(!day.activities[0].time.is_complete());
+    }
+
+    #[test]
+    fn test_status_parses_at_most_two_days() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        for _ in 0..5 {
+            dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+                .unwrap();
+            clock.advance(Duration::days(1));
+        }
+
+        profile::enable();
+        dir.execute(&CommandStatus::default()).unwrap();
+        let parsed = profile::counter("day files parsed");
+        profile::disable();
+
+        assert!(parsed <= 2, "status parsed {} day files", parsed);
+    }
+
+    #[test]
+    fn test_format_json_is_checked_like_the_json_flag() {
+        use crate::cli::AppArgs;
+        let check = |args: &[&str]| {
+            let args = AppArgs::parse_from(["timetrax"].iter().chain(args));
+            args.command
+                .unwrap_or_default()
+                .check_format(args.format)
+                .is_ok()
+        };
+        assert!(check(&["--format", "json"]));
+        assert!(check(&[
+            "--format",
+            "json",
+            "status",
+            "--date",
+            "2024-05-03"
+        ]));
+        assert!(check(&["status", "--gaps"]));
+        for args in [
+            &["status", "--format", "json", "--gaps"][..],
+            &["status", "--format", "json", "--short"],
+            &["status", "--format", "json", "--verbose"],
+            &["status", "--format", "json", "--osc-title"],
+            &["status", "--format", "json", "--class", "@work"],
+            &["status", "--format", "json", "--from", "2024-05-01"],
+        ] {
+            assert!(!check(args), "{args:?}");
         }
 
-        Ok(())
+        // only status, list and report return data
+        assert!(check(&["--format", "json", "list"]));
+        assert!(check(&["--format", "json", "report", "--week"]));
+        assert!(!check(&["--format", "json", "push", "-c", "@work"]));
+        assert!(!check(&["pop", "--format", "json"]));
+    }
+
+    #[test]
+    fn test_status_json_deserializes() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding",
+        ]))
+        .unwrap();
+        clock.advance(Duration::hours(2));
+
+        let command = CommandStatus::parse_from(["status", "--json"]);
+        let output = dir.execute(&command).unwrap();
+        let report: StatusReport = serde_json::from_str(&output.text).unwrap();
+        assert_eq!(output.report.as_ref(), Some(&report));
+        assert_eq!(report.date, date);
+        assert_eq!(report.total_seconds, 2 * 3600);
+        assert_eq!(report.current_class.as_deref(), Some("work"));
+        assert_eq!(report.ongoing[0].name.as_deref(), Some("coding"));
+        assert!(report.ended.is_empty());
+        assert_eq!(report.remaining_quota_seconds, 6 * 3600);
+        assert!(CommandStatus::try_parse_from(["status", "--json", "--osc-title"]).is_err());
+    }
+
+    /// manager holding the given days in memory, nothing is read from or written to disk
+    fn in_memory_manager<'a>(config: &'a AppConfig, days: &[&str]) -> Manager<'a> {
+        Manager::in_memory(
+            config,
+            days.iter()
+                .map(|day| serde_json::from_str::<Day>(day).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_status_output_of_an_in_memory_manager() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(12, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let config = AppConfig::default();
+        let days = [
+            day_file("2024-05-02", "09:00:00", Some("17:00:00")),
+            day_file("2024-05-03", "09:00:00", Some("10:30:00")),
+            day_file("2024-05-03", "11:00:00", None),
+        ];
+        // the two activities of the day are merged into one file
+        let mut today: Day = serde_json::from_str(&days[1]).unwrap();
+        let open: Day = serde_json::from_str(&days[2]).unwrap();
+        today.inner.activities.extend(open.inner.activities);
+        let today = serde_json::to_string(&today).unwrap();
+
+        let output = CommandStatus::default()
+            .execute(
+                &config,
+                &mut JobConfig::default(),
+                in_memory_manager(&config, &[&days[0], &today]),
+            )
+            .unwrap();
+        let report = output.report.as_ref().unwrap();
+        assert_eq!(report.date, date);
+        assert_eq!(report.total_seconds, 2 * 3600 + 30 * 60);
+        assert_eq!(report.current_class.as_deref(), Some("work"));
+        assert_eq!(report.ongoing.len(), 1);
+        assert_eq!(report.ended.len(), 1);
+        assert_eq!(report.remaining_quota_seconds, 5 * 3600 + 30 * 60);
+        assert!(
+            output.text.starts_with("Status for 2024-05-03 (today)\n"),
+            "{}",
+            output.text
+        );
+
+        let mut text = Vec::new();
+        output.render(&mut text, OutputFormat::Text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), output.text);
+        let mut json = Vec::new();
+        output.render(&mut json, OutputFormat::Json).unwrap();
+        let parsed: StatusReport = serde_json::from_slice(&json).unwrap();
+        assert_eq!(&parsed, report);
+
+        // a range has no status of a single day
+        let output = CommandStatus::parse_from(["status", "--from", "2024-05-02"])
+            .execute(
+                &config,
+                &mut JobConfig::default(),
+                in_memory_manager(&config, &[&days[0], &today]),
+            )
+            .unwrap();
+        assert!(output.report.is_none());
+        assert!(
+            output.text.contains("Total time tracked: 10h 30m\n"),
+            "{}",
+            output.text
+        );
+        assert!(output.render(&mut Vec::new(), OutputFormat::Json).is_err());
+    }
+
+    fn day_file(date: &str, start: &str, end: Option<&str>) -> String {
+        let end = end.map_or(String::new(), |end| format!(r#", "end": "{}""#, end));
+        format!(
+            r#"{{"date": "{}", "activities": [{{"id": "{}", "class": "@work",
+                "time": {{"start": "{}"{}}}}}]}}"#,
+            date,
+            Uuid::new_v4(),
+            start,
+            end
+        )
+    }
+
+    #[test]
+    fn test_status_of_a_past_day_is_not_clamped_to_now() {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let now = at(today, Time::from_hms(9, 0, 0).unwrap());
+        let _guard = ManualClock::new(now).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        for date in ["2024-05-02", "2024-05-03"] {
+            dir.write_day_file(
+                &format!("{date}.json"),
+                &day_file(date, "13:00:00", Some("17:00:00")),
+            )
+            .unwrap();
+        }
+
+        // the afternoon of yesterday lies after the current time of day
+        let command = CommandStatus::parse_from(["status", "--date", "2024-05-02"]);
+        assert!(render(&dir, &command, now).contains("Total time tracked: 4h 0m 0s\n"));
+        // today the closure ends now
+        let output = render(&dir, &CommandStatus::default(), now);
+        assert!(output.contains("Total time tracked: 0s\n"), "{output}");
+    }
+
+    #[test]
+    fn test_status_of_a_range() {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let now = at(today, Time::from_hms(9, 0, 0).unwrap());
+        let _guard = ManualClock::new(now).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let days = [
+            ("2024-04-30", "09:00:00", Some("10:00:00")),
+            ("2024-05-01", "09:00:00", Some("10:00:00")),
+            ("2024-05-02", "13:00:00", Some("17:00:00")),
+            ("2024-05-03", "08:00:00", None),
+        ];
+        for (date, start, end) in days {
+            dir.write_day_file(&format!("{date}.json"), &day_file(date, start, end))
+                .unwrap();
+        }
+        let manager = dir.manager().unwrap();
+        let range = |args: &[&str]| {
+            let command = CommandStatus::parse_from(["status"].iter().chain(args));
+            let from = command.from.unwrap().resolve(today);
+            let to = command.to.map_or(today, |to| to.resolve(today));
+            command.render_range(
+                &Locale::new(&AppConfig::default()),
+                &JobConfig::default(),
+                from,
+                to,
+                &manager,
+                now,
+            )
+        };
+
+        assert_eq!(
+            range(&["--from", "2024-05-01"]).unwrap(),
+            "Status from Wednesday, 1 May 2024 to Friday, 3 May 2024\n \
+             - Wednesday, 1 May 2024: 1h 0m\n \
+             - Thursday, 2 May 2024: 4h 0m\n \
+             - Friday, 3 May 2024: 1h 0m\n\
+             Total time tracked: 6h 0m\n"
+        );
+        let output = range(&["--from", "2024-04-30", "--to", "2024-05-01"]).unwrap();
+        assert!(output.ends_with("Total time tracked: 2h 0m\n"));
+
+        let error = range(&["--from", "2024-05-03", "--to", "2024-05-01"]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        let Err(error) = CommandStatus::try_parse_from(["status", "--from", "2024-13-01"]) else {
+            panic!("invalid dates are rejected");
+        };
+        assert!(error.to_string().contains("expected YYYY-MM-DD"));
+        assert!(CommandStatus::try_parse_from(["status", "--to", "2024-05-01"]).is_err());
+        assert!(CommandStatus::try_parse_from(["status", "--from", "2024-05-01", "-1"]).is_err());
+    }
+
+    fn client_projects() -> JobConfig {
+        let mut job_config = JobConfig::default();
+        for name in ["clientA", "clientB"] {
+            job_config.projects.push(Project {
+                id: Uuid::new_v4(),
+                inner: crate::data::project::ProjectInner {
+                    name: name.to_string(),
+                    description: None,
+                    client: None,
+                    target_share: None,
+                },
+            });
+        }
+        job_config
+    }
+
+    #[test]
+    fn test_status_of_a_project() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let now = at(date, Time::from_hms(18, 0, 0).unwrap());
+        let _guard = ManualClock::new(now).install();
+        let dir = TempDataDir::new(&client_projects()).unwrap();
+        dir.write_day_file(
+            "2024-05-03.json",
+            r#"{"date": "2024-05-03", "activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work", "name": "design",
+                 "projects": ["@clientA"], "time": {"start": "09:00:00", "end": "12:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000002", "class": "@work", "name": "review",
+                 "projects": ["@clientB"], "time": {"start": "11:00:00", "end": "13:00:00"}}
+            ]}"#,
+        )
+        .unwrap();
+        let job_config = dir.job_config().unwrap();
+        let day = dir.day(date).unwrap();
+        let status = |args: &[&str]| {
+            let command = CommandStatus::parse_from(["status"].iter().chain(args));
+            let day = command.select(&job_config, day.as_ref());
+            command
+                .render(
+                    &Locale::new(&AppConfig::default()),
+                    &job_config,
+                    day.as_deref(),
+                    None,
+                    None,
+                    now,
+                )
+                .unwrap()
+        };
+
+        assert!(status(&[]).contains("Total time tracked: 4h 0m 0s\n"));
+        // the overlapping review of client B does not count towards client A
+        let output = status(&["--project", "@clientA"]);
+        assert!(
+            output.contains("Total time tracked: 3h 0m 0s\n"),
+            "{output}"
+        );
+        assert!(output.contains("design"));
+        assert!(!output.contains("review"));
+        let clientb = job_config.projects[1].id.to_string();
+        let output = status(&["-p", &clientb]);
+        assert!(
+            output.contains("Total time tracked: 2h 0m 0s\n"),
+            "{output}"
+        );
+        let output = status(&["-p", "@clientA", "-p", "@clientB"]);
+        assert!(
+            output.contains("Total time tracked: 4h 0m 0s\n"),
+            "{output}"
+        );
+
+        let command = CommandStatus::parse_from(["status", "-p", "@clientC"]);
+        let error = check_project_filter(&job_config, &command.filter()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(
+            dir.execute(&command).unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_status_of_a_class() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let now = at(date, Time::from_hms(18, 0, 0).unwrap());
+        let _guard = ManualClock::new(now).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file(
+            "2024-05-03.json",
+            r#"{"date": "2024-05-03", "activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work", "name": "office",
+                 "time": {"start": "09:00:00", "end": "17:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000002", "class": "@break", "name": "lunch",
+                 "time": {"start": "12:00:00", "end": "13:00:00"}}
+            ]}"#,
+        )
+        .unwrap();
+        let job_config = dir.job_config().unwrap();
+        let day = dir.day(date).unwrap();
+        let status = |args: &[&str]| {
+            let command = CommandStatus::parse_from(["status"].iter().chain(args));
+            command
+                .render(
+                    &Locale::new(&AppConfig::default()),
+                    &job_config,
+                    day.as_ref(),
+                    None,
+                    None,
+                    now,
+                )
+                .unwrap()
+        };
+
+        // the lunch break takes over an hour of the work
+        let output = status(&["--class", "@work"]);
+        assert!(
+            output.contains("Total time tracked: 7h 0m 0s\n"),
+            "{output}"
+        );
+        assert!(output.contains(" --> 13:00:00 - 17:00:00: office\n"));
+        assert!(!output.contains("lunch"));
+        let output = status(&["--class", "@break"]);
+        assert!(
+            output.contains("Total time tracked: 1h 0m 0s\n"),
+            "{output}"
+        );
+        // without folding the work counts as tracked
+        let output = status(&["--class", "@work", "--raw"]);
+        assert!(
+            output.contains("Total time tracked: 8h 0m 0s\n"),
+            "{output}"
+        );
+        let output = status(&["-c", "@work", "-c", "@break", "--raw"]);
+        assert!(
+            output.contains("Total time tracked: 9h 0m 0s\n"),
+            "{output}"
+        );
+
+        let command = CommandStatus::parse_from(["status", "--class", "@meeting"]);
+        assert_eq!(
+            dir.execute(&command).unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_status_on_empty_day() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandStatus::default()).unwrap();
+    }
+
+    fn yesterday_fixture() -> (ManualClock, TempDataDir, Date) {
+        let today = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let yesterday = today.previous_day().unwrap();
+        let clock = ManualClock::new(at(yesterday, Time::from_hms(9, 0, 0).unwrap()));
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        {
+            let _guard = clock.install();
+            dir.execute(&CommandPush::parse_from([
+                "push", "-c", "@work", "-n", "done",
+            ]))
+            .unwrap();
+            clock.advance(Duration::hours(2));
+            dir.execute(&crate::cli::CommandPop::parse_from(["pop"]))
+                .unwrap();
+            dir.execute(&CommandPush::parse_from([
+                "push", "-c", "@work", "-n", "forgot",
+            ]))
+            .unwrap();
+        }
+        clock.set(at(today, Time::from_hms(12, 0, 0).unwrap()));
+        (clock, dir, today)
+    }
+
+    fn render(dir: &TempDataDir, command: &CommandStatus, now: OffsetDateTime) -> String {
+        let config = AppConfig::default();
+        let day = dir.day(command.resolve_date(now.date())).unwrap();
+        command
+            .render(
+                &Locale::new(&config),
+                &JobConfig::default(),
+                day.as_ref(),
+                None,
+                None,
+                now,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_status_yesterday_matches_explicit_date() {
+        let (clock, dir, today) = yesterday_fixture();
+        let now = clock.now();
+
+        let short = render(&dir, &CommandStatus::parse_from(["status", "-1"]), now);
+        let long = render(
+            &dir,
+            &CommandStatus::parse_from(["status", "--yesterday"]),
+            now,
+        );
+        let ago = render(
+            &dir,
+            &CommandStatus::parse_from(["status", "--days-ago", "1"]),
+            now,
+        );
+        let explicit = render(
+            &dir,
+            &CommandStatus::parse_from(["status", "--date", "2024-05-02"]),
+            now,
+        );
+
+        assert!(short.starts_with("Status for 2024-05-02 (yesterday)\n"));
+        assert_eq!(short, long);
+        assert_eq!(short, ago);
+        assert_eq!(short, explicit);
+
+        let current = render(&dir, &CommandStatus::default(), now);
+        assert_eq!(
+            current,
+            format!("Status for {today} (today)\nNo activities for this day.\n")
+        );
+    }
+
+    #[test]
+    fn test_status_past_day_does_not_count_unterminated() {
+        let (clock, dir, _) = yesterday_fixture();
+        let output = render(
+            &dir,
+            &CommandStatus::parse_from(["status", "-1"]),
+            clock.now(),
+        );
+
+        assert!(output.contains("Total time tracked: 2h 0m 0s\n"));
+        assert!(output.contains("Unterminated activities"));
+        assert!(!output.contains("Status: "));
+    }
+
+    #[test]
+    fn test_status_lists_open_blockers() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&crate::cli::CommandBlocker::parse_from([
+            "blocker", "add", "-c", "@work", "-n", "doctor",
+        ]))
+        .unwrap();
+        clock.advance(Duration::minutes(45));
+
+        let output = render(&dir, &CommandStatus::default(), clock.now());
+        assert!(output.contains("Blockers:\n - [work] 09:00:00 - <OPEN>: doctor (45m)\n"));
+    }
+
+    #[test]
+    fn test_status_counts_blockers() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let blocker = |from: &str, to: &str, name: &str| {
+            dir.execute(&crate::cli::CommandBlocker::parse_from([
+                "blocker", "add", "-c", "@work", "--from", from, "--to", to, "-n", name,
+            ]))
+            .unwrap()
+        };
+
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.advance(Duration::hours(3));
+        dir.execute(&crate::cli::CommandPop::parse_from(["pop"]))
+            .unwrap();
+        // overlaps the activity of the same class, counted once
+        blocker("11:00", "12:30", "overlapping");
+        clock.set(at(date, Time::from_hms(16, 0, 0).unwrap()));
+        let output = render(&dir, &CommandStatus::default(), clock.now());
+        assert!(output.contains("Total time tracked: 3h 30m 0s\n"));
+
+        blocker("14:00", "15:00", "doctor");
+        let output = render(&dir, &CommandStatus::default(), clock.now());
+        assert!(output.contains("Total time tracked: 4h 30m 0s\n"));
+        assert!(output.contains(" --> 14:00:00 - 15:00:00: doctor\n"));
+        assert!(output.contains(
+            "Blockers:\n - [work] 11:00:00 - 12:30:00: overlapping (1h 30m)\n \
+             - [work] 14:00:00 - 15:00:00: doctor (1h 0m)\n"
+        ));
+    }
+
+    #[test]
+    fn test_status_verbose_lists_evidence() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "coding",
+        ]))
+        .unwrap();
+        clock.advance(Duration::minutes(10));
+        dir.execute(&crate::cli::CommandEvidence::parse_from([
+            "evidence",
+            "@coding",
+            "src/main.rs",
+        ]))
+        .unwrap();
+
+        let output = render(&dir, &CommandStatus::default(), clock.now());
+        assert!(!output.contains("src/main.rs"));
+
+        let output = render(
+            &dir,
+            &CommandStatus::parse_from(["status", "--verbose"]),
+            clock.now(),
+        );
+        assert!(output.contains("09:00:00 - <OPEN>: coding (10m)\n     09:10:00 src/main.rs\n"));
+    }
+
+    #[test]
+    fn test_status_across_day_boundary() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(22, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let job_config = JobConfig {
+            day_boundary: Some(Time::from_hms(4, 0, 0).unwrap()),
+            ..JobConfig::default()
+        };
+        let dir = TempDataDir::new(&job_config).unwrap();
+
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "late",
+        ]))
+        .unwrap();
+        clock.advance(Duration::minutes(210));
+
+        let now = clock.now();
+        let command = CommandStatus::default();
+        let day = dir.day(date).unwrap();
+        let output = command
+            .render(
+                &Locale::new(&AppConfig::default()),
+                &job_config,
+                day.as_ref(),
+                None,
+                None,
+                now,
+            )
+            .unwrap();
+
+        assert!(output.starts_with(&format!("Status for {date} (today)\n")));
+        assert!(output.contains(" --> 22:00:00 - 01:30:00: late\n"));
+        assert!(output.contains("Total time tracked: 3h 30m 0s\n"));
+        assert!(output.contains("(3h 30m)\n"));
+    }
+
+    #[test]
+    fn test_status_warns_about_unknown_classes() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", UNKNOWN_CLASS_DAY)
+            .unwrap();
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let now = at(date, Time::from_hms(18, 0, 0).unwrap());
+
+        let output = render(&dir, &CommandStatus::default(), now);
+        assert!(output.contains("Warning: unknown classes were replaced"));
+        assert!(output.contains(" - @retired (activities c84e2f19-6d3a-4b7c-8e05-1a9f3d6b2e70)\n"));
+
+        let strict = CommandStatus::parse_from(["status", "--strict"]);
+        let day = dir.day(date).unwrap();
+        assert!(
+            strict
+                .render(
+                    &Locale::new(&AppConfig::default()),
+                    &JobConfig::default(),
+                    day.as_ref(),
+                    None,
+                    None,
+                    now,
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_status_total_ignores_inverted_activities() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file(
+            "2024-05-02.json",
+            r#"{"date": "2024-05-02", "activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+                 "time": {"start": "09:00:00", "end": "11:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000002", "class": "@work",
+                 "time": {"start": "15:00:00", "end": "13:00:00"}}]}"#,
+        )
+        .unwrap();
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let now = at(date, Time::from_hms(18, 0, 0).unwrap());
+
+        let output = render(&dir, &CommandStatus::default(), now);
+        assert!(output.contains("Total time tracked: 2h 0m 0s\n"));
+    }
+
+    #[test]
+    fn test_status_shows_the_current_project() {
+        let (clock, dir, _) = yesterday_fixture();
+        let project = Project {
+            id: Uuid::new_v4(),
+            inner: crate::data::project::ProjectInner {
+                name: "projA".to_string(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        };
+        let command = CommandStatus::parse_from(["status", "-1"]);
+        let day = dir.day(command.resolve_date(clock.now().date())).unwrap();
+        let output = command
+            .render(
+                &Locale::new(&AppConfig::default()),
+                &JobConfig::default(),
+                day.as_ref(),
+                None,
+                Some(&project),
+                clock.now(),
+            )
+            .unwrap();
+
+        let lines = output.lines().take(2).collect::<Vec<_>>();
+        assert_eq!(lines[1], "Current project: projA");
+        assert!(!render(&dir, &command, clock.now()).contains("Current project"));
+    }
+
+    /// `status --short` on Monday, 6 May 2024 at `time` with the activities of the day file
+    fn short(activities: &str, time: Time) -> String {
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let now = at(date, time);
+        let _guard = ManualClock::new(now).install();
+        let mut job_config = JobConfig::default();
+        job_config.projects.push(Project {
+            id: Uuid::new_v4(),
+            inner: crate::data::project::ProjectInner {
+                name: "projA".to_string(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        });
+        let dir = TempDataDir::new(&job_config).unwrap();
+        dir.write_day_file(
+            "2024-05-06.json",
+            &format!(
+                r#"{{"date": "2024-05-06", "activities": [{}]}}"#,
+                activities
+            ),
+        )
+        .unwrap();
+
+        let manager = dir.manager().unwrap();
+        let job_config = dir.job_config().unwrap();
+        CommandStatus::parse_from(["status", "--short"]).short(
+            dir.app_config(),
+            &job_config,
+            manager.day(date),
+            now,
+        )
+    }
+
+    const STANDUP: &str = r#"{"id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+        "name": "standup", "time": {"start": "08:00:00", "end": "09:00:00"}}"#;
+
+    #[test]
+    fn test_status_short_of_an_ongoing_activity() {
+        let coding = r#"{"id": "00000000-0000-4000-8000-000000000002", "class": "@work",
+            "name": "coding", "projects": ["@projA"], "time": {"start": "09:00:00"}}"#;
+        let line = short(
+            &format!("{}, {}", STANDUP, coding),
+            Time::from_hms(11, 12, 0).unwrap(),
+        );
+        assert_eq!(line, "work 2h12m ▶ projA | 4h48m left");
+
+        // the project is left out if the activity has none
+        let coding = coding.replace(r#""projects": ["@projA"], "#, "");
+        let line = short(&coding, Time::from_hms(11, 12, 0).unwrap());
+        assert_eq!(line, "work 2h12m | 5h48m left");
+    }
+
+    #[test]
+    fn test_status_short_while_idle() {
+        assert_eq!(
+            short(STANDUP, Time::from_hms(11, 0, 0).unwrap()),
+            "idle | 7h0m"
+        );
+        assert_eq!(short("", Time::from_hms(11, 0, 0).unwrap()), "idle | 8h0m");
+        assert!(CommandStatus::try_parse_from(["status", "--short", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_status_short_of_an_unknown_class() {
+        let retired = r#"{"id": "00000000-0000-4000-8000-000000000002", "class": "@retired",
+            "time": {"start": "09:00:00"}}"#;
+        let line = short(retired, Time::from_hms(9, 30, 0).unwrap());
+        assert!(line.starts_with("@retired 30m | "), "{line}");
+    }
+
+    #[test]
+    fn test_status_shows_quota_progress() {
+        // Monday
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        clock.advance(Duration::hours(5));
+
+        let now = clock.now();
+        let manager = dir.manager().unwrap();
+        let job_config = dir.job_config().unwrap();
+        let week = WeekProgress::new(
+            &job_config,
+            &manager,
+            date,
+            now,
+            DailyQuotas::uniform(Duration::hours(8)),
+        );
+        let output = CommandStatus::default()
+            .render(
+                &Locale::new(&AppConfig::default()),
+                &job_config,
+                manager.day(date),
+                Some(&week),
+                None,
+                now,
+            )
+            .unwrap();
+
+        assert!(output.contains("Work quota: 5h 0m of 8h 0m, 3h 0m remaining\n["));
+        assert!(output.contains("] 62%\n"));
+        assert!(output.contains(
+            "This week: 5h 0m tracked of 8h 0m so far, weekly quota of 40h 0m expected to be met on Wednesday, 15 May 2024 at the current pace\n"
+        ));
+    }
+
+    #[test]
+    fn test_status_shows_tags_and_descriptions() {
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "-n",
+            "Coding",
+            "-d",
+            "Parser rewrite",
+            "-t",
+            "rust",
+            "-t",
+            "focus",
+        ]))
+        .unwrap();
+        clock.advance(Duration::hours(1));
+
+        let output = render(&dir, &CommandStatus::default(), clock.now());
+        assert!(
+            output.contains(
+                " - [work] 09:00:00 - <OPEN>: Coding #rust #focus (1h 0m)\n     Parser rewrite\n"
+            ),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_status_colors() {
+        // Monday
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let mut job_config = JobConfig::default();
+        job_config
+            .resolve_class_mut(Identifier::ByName("work".into()))
+            .unwrap()
+            .inner
+            .color = Some("blue".into());
+        let dir = TempDataDir::new(&job_config).unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push", "-c", "@work", "-n", "Coding",
+        ]))
+        .unwrap();
+        clock.advance(Duration::hours(10));
+
+        let now = clock.now();
+        let manager = dir.manager().unwrap();
+        let week = WeekProgress::new(
+            &job_config,
+            &manager,
+            date,
+            now,
+            DailyQuotas::uniform(Duration::hours(8)),
+        );
+        let render = |color: bool, week: Option<&WeekProgress>| {
+            let config = AppConfig {
+                color,
+                ..AppConfig::default()
+            };
+            CommandStatus::default()
+                .render(
+                    &Locale::new(&config),
+                    &job_config,
+                    manager.day(date),
+                    week,
+                    None,
+                    now,
+                )
+                .unwrap()
+        };
+
+        let plain = render(false, Some(&week));
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.contains("Status: work\n"));
+        assert!(plain.contains(" - [work] "));
+
+        let colored = render(true, Some(&week));
+        assert!(colored.contains("Status: \x1b[34mwork\x1b[0m\n"));
+        assert!(colored.contains(" - [\x1b[34mwork\x1b[0m] "));
+        assert!(colored.contains(" --> \x1b[34m09:00:00 - 19:00:00: Coding\x1b[0m\n"));
+        // overtime in red, nothing remaining is left plain
+        assert!(colored.contains("Work quota: \x1b[31m10h 0m\x1b[0m of 8h 0m, 0m remaining\n"));
+        assert_eq!(
+            colored
+                .replace("\x1b[34m", "")
+                .replace("\x1b[31m", "")
+                .replace("\x1b[32m", "")
+                .replace("\x1b[0m", ""),
+            plain
+        );
+    }
+
+    #[test]
+    fn test_status_uses_the_quota_of_the_day() {
+        // Monday
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from(["push", "-c", "@work"]))
+            .unwrap();
+        dir.execute(&crate::cli::CommandDay::parse_from(["day", "quota", "4h"]))
+            .unwrap();
+        clock.advance(Duration::hours(3));
+
+        let now = clock.now();
+        let manager = dir.manager().unwrap();
+        let job_config = dir.job_config().unwrap();
+        let week = WeekProgress::new(
+            &job_config,
+            &manager,
+            date,
+            now,
+            DailyQuotas::uniform(Duration::hours(8)),
+        );
+        let output = CommandStatus::default()
+            .render(
+                &Locale::new(&AppConfig::default()),
+                &job_config,
+                manager.day(date),
+                Some(&week),
+                None,
+                now,
+            )
+            .unwrap();
+
+        assert!(output.contains("Work quota: 3h 0m of 4h 0m, 1h 0m remaining\n["));
+        assert!(output.contains("] 75%\n"));
+        assert!(
+            output.contains("This week: 3h 0m tracked of 4h 0m so far, weekly quota of 36h 0m")
+        );
+    }
+
+    #[test]
+    fn test_status_of_a_holiday_has_nothing_remaining() {
+        // Monday
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(12, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&crate::cli::CommandHoliday::parse_from(["holiday"]))
+            .unwrap();
+
+        let now = clock.now();
+        let manager = dir.manager().unwrap();
+        let job_config = dir.job_config().unwrap();
+        let week = WeekProgress::new(
+            &job_config,
+            &manager,
+            date,
+            now,
+            DailyQuotas::uniform(Duration::hours(8)),
+        );
+        let output = CommandStatus::default()
+            .render(
+                &Locale::new(&AppConfig::default()),
+                &job_config,
+                manager.day(date),
+                Some(&week),
+                None,
+                now,
+            )
+            .unwrap();
+
+        assert!(output.contains("(today)\nHoliday\n"));
+        assert!(output.contains("Work quota: 8h 0m of 8h 0m, 0m remaining\n"));
+        assert!(output.contains("] 100%\n"));
+    }
+
+    #[test]
+    fn test_status_of_a_public_holiday_has_nothing_remaining() {
+        // Thursday
+        let date = Date::from_calendar_date(2024, Month::May, 9).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(12, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let mut job_config = JobConfig::default();
+        job_config.add_holidays([PublicHoliday {
+            date,
+            name: "Christi Himmelfahrt".to_string(),
+        }]);
+        let dir = TempDataDir::new(&job_config).unwrap();
+
+        let now = clock.now();
+        let config = AppConfig::default();
+        let manager = dir.manager().unwrap();
+        let week = WeekProgress::new(&job_config, &manager, date, now, config.daily_quotas());
+        assert_eq!(
+            day_quota(&job_config, date, None, config.daily_quotas()),
+            Duration::ZERO
+        );
+        assert_eq!(week.week_quota(), Duration::hours(32));
+        let output = CommandStatus::default()
+            .render(
+                &Locale::new(&config),
+                &job_config,
+                None,
+                Some(&week),
+                None,
+                now,
+            )
+            .unwrap();
+        assert!(output.contains("(today)\nPublic holiday: Christi Himmelfahrt\n"));
+        assert!(!output.contains("Work quota:"));
+
+        let (report, _) = StatusReport::new(&config, &job_config, date, None, now);
+        assert_eq!(
+            report.public_holiday.as_deref(),
+            Some("Christi Himmelfahrt")
+        );
+        assert_eq!(report.remaining_quota_seconds, 0);
+    }
+
+    #[test]
+    fn test_status_warns_about_missing_breaks() {
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(13, 30, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file(
+            "2024-05-06.json",
+            r#"{"date": "2024-05-06", "activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+                    "time": {"start": "08:00:00"}}]}"#,
+        )
+        .unwrap();
+        let command = CommandStatus::default();
+        let warning = |output: String| {
+            output
+                .lines()
+                .find(|line| line.starts_with("Warning:"))
+                .map(str::to_string)
+        };
+
+        // 5h 30m of work, the 6h rule is 30m away
+        assert_eq!(
+            warning(render(&dir, &command, clock.now())).as_deref(),
+            Some("Warning: 30m of break required after 6h 0m of work, in 30m without a break")
+        );
+        // exactly 6h are still fine, but the rule applies from now on
+        clock.set(at(date, Time::from_hms(14, 0, 0).unwrap()));
+        assert!(
+            warning(render(&dir, &command, clock.now()))
+                .unwrap()
+                .ends_with("in 0m without a break")
+        );
+        clock.set(at(date, Time::from_hms(14, 10, 0).unwrap()));
+        assert_eq!(
+            warning(render(&dir, &command, clock.now())).as_deref(),
+            Some("Warning: 6h 10m of work but only 0m of break, 30m missing")
+        );
+        // far from the threshold there is nothing to warn about
+        clock.set(at(date, Time::from_hms(10, 0, 0).unwrap()));
+        assert_eq!(warning(render(&dir, &command, clock.now())), None);
+    }
+
+    #[test]
+    fn test_status_day_flags_conflict() {
+        assert!(CommandStatus::try_parse_from(["status", "-1", "--date", "2024-05-02"]).is_err());
+        assert!(CommandStatus::try_parse_from(["status", "-1", "--days-ago", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_render_timeline_twelve_hour() {
+        let config = AppConfig {
+            clock: ClockFormat::TwelveHour,
+            ..AppConfig::default()
+        };
+        let locale = Locale::new(&config);
+        let segment = |start: u8, end: Option<u8>, name: Option<&str>| Activity {
+            id: Uuid::nil(),
+            name: name.map(String::from),
+            ..testing::activity(
+                "work",
+                Time::from_hms(start, 0, 0).unwrap(),
+                end.map(|end| Time::from_hms(end, 0, 0).unwrap()),
+            )
+        };
+
+        let timeline = render_timeline(
+            &locale,
+            &JobConfig::default(),
+            &[
+                segment(9, Some(12), Some("Standup")),
+                segment(12, Some(13), None),
+                segment(13, None, Some("Review")),
+            ],
+            None,
+        );
+
+        assert_eq!(
+            timeline,
+            " --> 9:00:00 AM - 12:00:00 PM: Standup\n\
+             \x20--> 12:00:00 PM - 1:00:00 PM: <NO DESCRIPTION>\n\
+             \x20--> 1:00:00 PM - <OPEN>: Review\n"
+        );
+    }
+
+    #[test]
+    fn test_status_osc_title() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.execute(&CommandPush::parse_from([
+            "push",
+            "-c",
+            "@work",
+            "-n",
+            "refactor parser",
+        ]))
+        .unwrap();
+        clock.advance(Duration::minutes(72));
+
+        let title = |args: &[&str], is_terminal: bool| {
+            let mut out = Vec::new();
+            let day = dir.day(date).unwrap();
+            CommandStatus::parse_from(["status"].iter().chain(args))
+                .write_title(
+                    &mut out,
+                    is_terminal,
+                    &JobConfig::default(),
+                    day.as_ref(),
+                    clock.now(),
+                )
+                .unwrap();
+            out
+        };
+
+        assert_eq!(
+            title(&["--osc-title"], true),
+            "\x1b]0;▶ work: refactor parser 1h12m\x07".as_bytes()
+        );
+        assert_eq!(title(&["--osc-clear"], true), b"\x1b]0;\x07");
+        // pipes only get the sequence when forced
+        assert!(title(&["--osc-title"], false).is_empty());
+        assert_eq!(
+            title(&["--osc-title", "--force"], false),
+            title(&["--osc-title"], true)
+        );
+
+        dir.execute(&crate::cli::CommandPop::parse_from(["pop"]))
+            .unwrap();
+        assert_eq!(
+            title(&["--osc-title"], true),
+            "\x1b]0;■ idle\x07".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_status_warns_about_unloadable_days() {
+        let date = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(12, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = dir
+            .write_day_file(
+                "2024-05-06.json",
+                r#"{"date": "2024-05-06", "activities": [,]}"#,
+            )
+            .unwrap();
+
+        let output = dir.execute(&CommandStatus::default()).unwrap();
+        assert!(
+            output
+                .text
+                .ends_with("Warning: 1 day file(s) could not be loaded, run `timetrax repair`\n")
+        );
+        assert_eq!(
+            output.report.unwrap().unloadable_day_files,
+            std::slice::from_ref(&path)
+        );
+        // the broken file is left alone
+        let output = dir.execute(&CommandStatus::default()).unwrap();
+        assert!(output.text.contains("could not be loaded"));
+
+        let output = dir
+            .execute(&CommandStatus::parse_from(["status", "--json"]))
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output.text).unwrap();
+        assert_eq!(json["unloadable_day_files"], serde_json::json!([path]));
+        let output = dir
+            .execute(&CommandStatus::parse_from(["status", "--short"]))
+            .unwrap();
+        assert_eq!(output.report.unwrap().unloadable_day_files, [path]);
+        assert_eq!(output.text.lines().count(), 1);
     }
 }
@@ -0,0 +1,288 @@
+use crate::cli::ExecutableCommand;
+use crate::cli::args::parse_date_arg;
+use crate::cli::day::ensure_unlocked;
+use crate::cli::format::format_table;
+use crate::cli::show::{class_name, format_time};
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::job_config::JobConfig;
+use crate::data::journal::{Journal, JournalEntry};
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::reference::Reference;
+use clap::Parser;
+use itertools::Itertools;
+use log::{error, info};
+use std::fmt::Write;
+use time::Date;
+use uuid::Uuid;
+
+/// Deleted activities are kept in the trash of their day until the retention expires
+#[derive(Parser)]
+pub enum CommandTrash {
+    /// List deleted activities with their ids
+    #[clap(aliases = ["ls"])]
+    List {
+        /// Day to list (YYYY-MM-DD), lists all days if omitted
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<Date>,
+    },
+    /// Move a deleted activity back to its day
+    #[clap(aliases = ["undelete"])]
+    Restore {
+        /// Activity UUID, UUID prefix, short id (see `timetrax trash list`) or @name
+        id: Reference,
+        /// Day of the activity (YYYY-MM-DD), searches all days if omitted
+        #[arg(long, value_parser = parse_date_arg)]
+        date: Option<Date>,
+    },
+}
+
+/// table of the trash of the days, the short ids are accepted by `trash restore`
+fn render_trash<'a>(
+    job_config: &JobConfig,
+    days: impl Iterator<Item = (&'a Date, &'a DayInner)>,
+) -> String {
+    let header = ["DATE", "ID", "UUID", "CLASS", "ACTIVITY", "DELETED"];
+    let mut rows = vec![header.map(String::from).to_vec()];
+    for (date, day) in days {
+        for entry in &day.trash {
+            let activity = &entry.activity;
+            rows.push(vec![
+                date.to_string(),
+                activity.short_id(),
+                activity.id.to_string(),
+                class_name(job_config, &activity.class),
+                activity.to_string(),
+                format!(
+                    "{} {}",
+                    entry.deleted_at.date(),
+                    format_time(entry.deleted_at.time())
+                ),
+            ]);
+        }
+    }
+
+    let mut out = String::new();
+    if rows.len() == 1 {
+        let _ = writeln!(out, "The trash is empty.");
+        return out;
+    }
+    for line in format_table(&rows) {
+        let _ = writeln!(out, "{}", line);
+    }
+    out
+}
+
+/// date and id of the trash entry matching the reference
+fn find_trashed<'a>(
+    days: impl Iterator<Item = (&'a Date, &'a DayInner)>,
+    reference: &Reference,
+) -> std::io::Result<(Date, Uuid)> {
+    let matches = days
+        .flat_map(|(date, day)| {
+            (reference.matches(&day.trash).into_iter()).map(|entry| (*date, entry.activity.id))
+        })
+        .unique()
+        .collect_vec();
+
+    match matches.as_slice() {
+        [found] => Ok(*found),
+        [] => {
+            error!("No deleted activity matches the reference '{}'", reference);
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Activity not found in the trash",
+            ))
+        }
+        matches => {
+            error!(
+                "Reference '{}' matches several deleted activities, use one of the ids: {}",
+                reference,
+                matches.iter().map(|(_, id)| id).join(", ")
+            );
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Ambiguous activity reference",
+            ))
+        }
+    }
+}
+
+impl ExecutableCommand for CommandTrash {
+    type Error = crate::error::Error;
+    type Output = ();
+    fn execute(
+        &self,
+        config: &AppConfig,
+        job_config: &mut JobConfig,
+        mut manager: Manager,
+    ) -> Result<Self::Output, Self::Error> {
+        match self {
+            CommandTrash::List { date } => {
+                let days = manager
+                    .days()
+                    .filter(|(day, _)| date.is_none_or(|date| **day == date));
+                print!("{}", render_trash(job_config, days));
+            }
+            CommandTrash::Restore {
+                id: reference,
+                date,
+            } => {
+                let days = manager
+                    .days()
+                    .filter(|(day, _)| date.is_none_or(|date| **day == date));
+                let (date, id) = find_trashed(days, reference)?;
+                let day = manager.get_or_create_day_ref(date);
+                ensure_unlocked(date, day)?;
+                if day.activities.iter().any(|activity| activity.id == id) {
+                    error!(
+                        "An activity with the id {} exists on {} already, remove it first",
+                        id, date
+                    );
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        "Activity exists already",
+                    )
+                    .into());
+                }
+
+                let now = local_time::now();
+                let day = manager.get_or_create_day_mut(date);
+                let restored = day.restore_activity(id).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "Activity not found in the trash",
+                    )
+                })?;
+                info!("Restoring activity of {}: {:?}", date, restored);
+                manager.save()?;
+                Journal::new(config, manager.data_path()).append(&JournalEntry {
+                    at: now,
+                    command: "restore".to_string(),
+                    date,
+                    removed: vec![],
+                    added: vec![restored.clone()],
+                })?;
+                println!("Restored activity of {}: {}", date, restored);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandRemove;
+    use crate::data::activity::Activity;
+    use crate::data::report::day_closure;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use std::collections::BTreeMap;
+    use time::{Duration, Month, Time};
+
+    const DAY: &str = r#"{"date": "2024-05-02", "activities": [
+        {"id": "00000000-0000-4000-8000-000000000001", "class": "@work", "name": "coding",
+         "time": {"start": "09:00:00", "end": "12:00:00"}},
+        {"id": "00000000-0000-4000-8000-000000000002", "class": "@work", "name": "meeting",
+         "time": {"start": "13:00:00", "end": "14:00:00"}}
+    ]}"#;
+
+    fn may(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::May, day).unwrap()
+    }
+
+    fn totals(dir: &TempDataDir) -> BTreeMap<String, Duration> {
+        let job_config = dir.job_config().unwrap();
+        let day = dir.day(may(2)).unwrap().unwrap();
+        let now = at(may(3), Time::from_hms(9, 0, 0).unwrap());
+        let (closure, _) = day_closure(&job_config, may(2), &day, now);
+        Activity::summarize_by_class(&closure)
+            .into_iter()
+            .map(|(class, duration)| (class_name(&job_config, &class), duration))
+            .collect()
+    }
+
+    #[test]
+    fn test_delete_and_restore_keeps_totals() {
+        let clock = ManualClock::new(at(may(3), Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", DAY).unwrap();
+        let before = totals(&dir);
+        assert_eq!(before["work"], Duration::hours(4));
+
+        dir.execute(&CommandRemove::parse_from([
+            "remove",
+            "00000000-0000-4000-8000-000000000002",
+            "--date",
+            "2024-05-02",
+            "--force",
+        ]))
+        .unwrap();
+        // the trash is ignored by closures and totals
+        assert_eq!(totals(&dir)["work"], Duration::hours(3));
+        let day = dir.day(may(2)).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 1);
+        assert_eq!(day.trash.len(), 1);
+        assert_eq!(day.trash[0].deleted_at, local_time::now());
+        assert_eq!(day.trash[0].activity.name.as_deref(), Some("meeting"));
+
+        let manager = dir.manager().unwrap();
+        let listed = render_trash(&dir.job_config().unwrap(), manager.days());
+        drop(manager);
+        assert!(listed.contains("13:00:00 - 14:00:00: meeting"));
+        assert!(listed.contains("2024-05-03 09:00:00"));
+
+        let short_id = day.trash[0].activity.short_id();
+        dir.execute(&CommandTrash::parse_from(["trash", "restore", &short_id]))
+            .unwrap();
+        assert_eq!(totals(&dir), before);
+        let day = dir.day(may(2)).unwrap().unwrap();
+        assert!(day.trash.is_empty());
+        let entries = Journal::new(dir.app_config(), dir.path())
+            .entries()
+            .unwrap();
+        assert_eq!(entries.last().unwrap().command, "restore");
+
+        let result = dir.execute(&CommandTrash::parse_from(["trash", "restore", &short_id]));
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_trash_is_purged_after_the_retention() {
+        let clock = ManualClock::new(at(may(3), Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        dir.write_day_file("2024-05-02.json", DAY).unwrap();
+
+        let mut manager = dir.manager().unwrap();
+        let day = manager.get_or_create_day_mut(may(2));
+        let coding = day.activities[0].id;
+        day.delete_activity(coding, local_time::now());
+        clock.advance(Duration::days(20));
+        let meeting = day.activities[0].id;
+        day.delete_activity(meeting, local_time::now());
+        drop(manager);
+        assert_eq!(dir.day(may(2)).unwrap().unwrap().trash.len(), 2);
+
+        // saving purges entries older than 30 days, even of unmodified days once they are loaded
+        clock.advance(Duration::days(11));
+        let mut manager = dir.manager().unwrap();
+        manager.day(may(2));
+        manager.save().unwrap();
+        drop(manager);
+        let trash = dir.day(may(2)).unwrap().unwrap().trash;
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].activity.id, meeting);
+
+        // locked days keep their trash
+        dir.manager().unwrap().get_or_create_day_mut(may(2)).locked = true;
+        clock.advance(Duration::days(20));
+        let mut manager = dir.manager().unwrap();
+        manager.day(may(2));
+        manager.save().unwrap();
+        drop(manager);
+        assert_eq!(dir.day(may(2)).unwrap().unwrap().trash.len(), 1);
+    }
+}
@@ -1,11 +1,14 @@
 use crate::az_hash::AZHash;
 use crate::data::BASIC_TIME_FORMAT;
+use crate::data::evidence::EvidenceEntry;
 use crate::data::identifier::Identifier;
 use crate::data::interval::Interval;
 use digest::Digest;
 use log::error;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 /// Activity
@@ -28,6 +31,48 @@ pub struct Activity {
     /// Projects worked on
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub projects: Vec<Identifier>,
+    /// Relative weight per project, used by weighted attribution, missing projects weigh 1
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub weights: BTreeMap<Identifier, f32>,
+    /// Optional long description of the activity
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
+    /// Time the activity was recorded
+    #[serde(
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    /// Last time the activity was edited after its creation
+    #[serde(
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub modified_at: Option<OffsetDateTime>,
+    /// Free-form tags for filtering and analysis
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    /// Context recorded while the activity was running, e.g. the file open in the editor
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub evidence: Vec<EvidenceEntry>,
+    /// How the times were given, `None` for activities recorded before it was tracked
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub origin: Option<ActivityOrigin>,
+    /// Unknown fields, kept so that data of other tools survives a round-trip
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// how the times of an activity were given
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityOrigin {
+    /// started and stopped as it happened, e.g. by `push` and `pop`
+    Live,
+    /// times given deliberately, e.g. by `log --from --to`, `break` or `push --at`
+    Explicit,
 }
 
 impl Display for Activity {
@@ -61,3 +106,16 @@ impl AZHash for Activity {
         self.id.az_hash::<D>()
     }
 }
+
+/// number of characters of the hash used as short id of an activity
+pub const SHORT_ID_LENGTH: usize = 6;
+
+impl Activity {
+    /// short id to tell activities apart on the command line, a prefix of the SHA256 az-hash
+    pub fn short_id(&self) -> String {
+        self.az_hash_sha256()
+            .chars()
+            .take(SHORT_ID_LENGTH)
+            .collect()
+    }
+}
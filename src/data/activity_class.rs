@@ -4,7 +4,7 @@ use std::borrow::Borrow;
 use uuid::Uuid;
 
 /// activity class data
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ActivityClassInner {
     /// its name
     pub name: String,
@@ -13,10 +13,19 @@ pub struct ActivityClassInner {
     /// optional description
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub description: Option<String>,
+    /// targeted percentage of the tracked time, e.g. 70 for 70%
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub target_share: Option<f32>,
+    /// terminal color of the class, a name like `red` or a hex code like `#ff8800`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub color: Option<String>,
+    /// pushing an activity of the class stops the open activities, see `push --exclusive`
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub exclusive: bool,
 }
 
 /// an activity class with unique id
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ActivityClass {
     /// unique identifier
     pub id: Uuid,
@@ -1,73 +1,156 @@
 use crate::data::activity::Activity;
+use crate::data::attribution::Attribution;
+use crate::data::identifier::Identifier;
 use crate::data::interval::Interval;
 use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::safe_duration::SafeDuration;
+use crate::profile;
 use itertools::Itertools;
 use log::{error, trace};
 use std::borrow::Borrow;
-use std::collections::BinaryHeap;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
 use std::fmt::{Display, Formatter};
-use time::Time;
+use time::{Duration, Time};
 use uuid::Uuid;
 
+/// classes the closure could not resolve, their time was attributed to the lowest priority class
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClosureDiagnostics {
+    /// how often the lowest priority class was substituted while folding
+    pub substitutions: usize,
+    /// unresolved class identifiers and the activities referring to them
+    pub unresolved: BTreeMap<Identifier, BTreeSet<Uuid>>,
+}
+
+impl ClosureDiagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.substitutions == 0
+    }
+
+    fn record(&mut self, class: &Identifier, activity: Uuid) {
+        self.substitutions += 1;
+        self.unresolved
+            .entry(class.clone())
+            .or_default()
+            .insert(activity);
+    }
+
+    /// combine the diagnostics of multiple closures, e.g. of several days
+    pub fn merge(&mut self, other: ClosureDiagnostics) {
+        self.substitutions += other.substitutions;
+        for (class, activities) in other.unresolved {
+            self.unresolved.entry(class).or_default().extend(activities);
+        }
+    }
+
+    /// user facing warning listing the unresolved classes, none if there are none
+    pub fn warning(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut out = format!(
+            "Warning: unknown classes were replaced by the lowest priority class {} times, the class totals are wrong:",
+            self.substitutions
+        );
+        for (class, activities) in &self.unresolved {
+            out.push_str(&format!(
+                "\n - {} (activities {})",
+                class,
+                activities.iter().join(", ")
+            ));
+        }
+        Some(out)
+    }
+
+    /// fails if any class was substituted and `strict` is set
+    pub fn check(&self, strict: bool) -> std::io::Result<()> {
+        if strict && !self.is_empty() {
+            error!(
+                "Unknown classes {} in the activity closure. Did you remove them from the job config?",
+                self.unresolved.keys().join(", ")
+            );
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Unknown classes in the activity closure",
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl Activity {
     /// Folds multiple activities into a single activity
     /// the largest start time and smallest end time is used
     /// the highest priority class is used
     /// all other attributes are combined
     ///
+    /// unknown classes are replaced by the lowest priority class and recorded in `diagnostics`
+    ///
     /// will return none if no activities are provided or the collapsed start_time > end_time
     pub fn fold_inner<Q: Borrow<Activity>, I: Iterator<Item = Q>>(
         job_config: &JobConfig,
         activities: I,
         start_time_limit: Option<&Time>,
         end_time_limit: Option<&Time>,
+        diagnostics: &mut ClosureDiagnostics,
     ) -> Option<Activity> {
         let mut start_time = None;
         let mut end_time = None;
         let mut class = job_config.lowest_priority_class();
         let mut names = Vec::new();
         let mut projects = Vec::new();
+        let mut weights: BTreeMap<Identifier, f32> = BTreeMap::new();
+        let mut tags = Vec::new();
 
         for activity in activities {
             let activity = activity.borrow();
             if start_time.is_none() || &activity.time.start > start_time.as_ref().unwrap() {
                 start_time = Some(activity.time.start);
             }
-            if let Some(end_time) = end_time.as_mut() {
-                if let Some(activity_end) = activity.time.end {
-                    if activity_end < *end_time {
-                        *end_time = activity_end;
-                    }
-                }
-            }
+            if let Some(activity_end) = activity.time.end
+                && end_time.is_none_or(|end_time| activity_end < end_time)
             {
-                end_time = activity.time.end;
+                end_time = Some(activity_end);
             }
 
-            let activity_class = job_config.resolve_class(&activity.class).unwrap_or_else(|| {
-                error!("Class {} not resolved. Did you removed it from the job config? Encountered on activity with ID {}", activity.class, activity.id);
-                job_config.lowest_priority_class()
-            });
+            let activity_class = job_config
+                .resolve_class(&activity.class)
+                .unwrap_or_else(|| {
+                    diagnostics.record(&activity.class, activity.id);
+                    job_config.lowest_priority_class()
+                });
             if activity_class.inner.priority > class.inner.priority {
                 class = activity_class;
             }
 
-            if let Some(activity_name) = &activity.name {
-                if !names.contains(activity_name) {
-                    names.push(activity_name.clone());
+            // names stored before they were normalized may be blank
+            if let Some(activity_name) = &activity.name
+                && !activity_name.trim().is_empty()
+                && !names.contains(activity_name)
+            {
+                names.push(activity_name.clone());
+            }
+
+            for tag in &activity.tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
                 }
             }
 
             for project in &activity.projects {
                 projects.push(project.clone());
+                *weights.entry(project.clone()).or_default() +=
+                    activity.weights.get(project).copied().unwrap_or(1.0);
             }
         }
 
         if let Some(mut start_time) = start_time {
-            if let Some(start_time_limit) = start_time_limit {
-                if start_time < *start_time_limit {
-                    start_time = *start_time_limit;
-                }
+            if let Some(start_time_limit) = start_time_limit
+                && start_time < *start_time_limit
+            {
+                start_time = *start_time_limit;
             }
 
             if let Some(end_time_limit) = end_time_limit {
@@ -80,21 +163,22 @@ impl Activity {
                 }
             }
 
-            if let Some(end_time) = end_time {
-                if start_time > end_time {
-                    return None;
-                }
+            if let Some(end_time) = end_time
+                && start_time > end_time
+            {
+                return None;
             }
 
             names.sort();
             projects.sort();
+            tags.sort();
 
             Some(Activity {
                 id: Uuid::new_v4(),
-                name: if names.len() == 0 {
+                name: if names.is_empty() {
                     None
                 } else {
-                    Some(names.into_iter().join("; ").into())
+                    Some(names.into_iter().join("; "))
                 },
                 class: class.id.into(),
                 time: Interval {
@@ -102,6 +186,14 @@ impl Activity {
                     end: end_time,
                 },
                 projects,
+                weights,
+                description: None,
+                created_at: None,
+                modified_at: None,
+                tags,
+                evidence: vec![],
+                origin: None,
+                extra: Default::default(),
             })
         } else {
             None
@@ -110,12 +202,88 @@ impl Activity {
 
     /// calculate activity closure for the day
     /// activity closure meaning a linear timeline of non-overlapping activities
+    ///
+    /// the day runs from the configured day boundary to the next one. if a boundary is set,
+    /// the times of the segments are relative to it (see [`Interval::to_day_time`]),
+    /// convert them with [`Interval::from_day_time`] before displaying them
+    ///
+    /// the diagnostics list the classes which could not be resolved
     pub fn calculate_activity_closure<Q: Borrow<Activity>>(
         job_config: &JobConfig,
-        activities: &Vec<Q>,
+        activities: &[Q],
+        start: Option<Time>,
+        end: Option<Time>,
+    ) -> (Vec<Activity>, ClosureDiagnostics) {
+        let _timer = profile::timer("compute closures");
+
+        let Some(boundary) = job_config.day_boundary else {
+            return Self::calculate_day_time_closure(job_config, activities, start, end);
+        };
+        let boundary = Some(boundary);
+        let activities = activities
+            .iter()
+            .map(|activity| {
+                let mut activity = activity.borrow().clone();
+                activity.time = activity.time.to_day_time(boundary);
+                activity
+            })
+            .collect_vec();
+        Self::calculate_day_time_closure(
+            job_config,
+            &activities,
+            start.map(|start| local_time::to_day_time(start, boundary)),
+            end.map(|end| local_time::to_day_time(end, boundary)),
+        )
+    }
+
+    /// untracked time between the segments of the closure of the activities, in day time.
+    /// the gaps are limited to `day_start` and `day_end`, which default to the start of the
+    /// first and the end of the last activity, gaps shorter than `min_gap` are left out
+    pub fn calculate_gaps<Q: Borrow<Activity>>(
+        job_config: &JobConfig,
+        activities: &[Q],
+        day_start: Option<Time>,
+        day_end: Option<Time>,
+        min_gap: Duration,
+    ) -> Vec<Interval> {
+        let (closure, _) = Self::calculate_activity_closure(job_config, activities, None, None);
+        let boundary = job_config.day_boundary;
+        let segments = closure
+            .iter()
+            .map(|segment| (segment.time.start, segment.time.end_time_or_end_of_day()))
+            .sorted()
+            .collect_vec();
+        let (Some(first), Some(last)) = (
+            segments.first().map(|(start, _)| *start),
+            segments.iter().map(|(_, end)| *end).max(),
+        ) else {
+            return Vec::new();
+        };
+        let day_start = day_start.map_or(first, |start| local_time::to_day_time(start, boundary));
+        let day_end = day_end.map_or(last, |end| local_time::to_day_time(end, boundary));
+
+        let mut gaps = Vec::new();
+        let mut cursor = day_start;
+        for (start, end) in segments.into_iter().chain([(day_end, day_end)]) {
+            let start = start.min(day_end);
+            if start > cursor && start - cursor >= min_gap {
+                gaps.push(Interval {
+                    start: cursor,
+                    end: Some(start),
+                });
+            }
+            cursor = cursor.max(end);
+        }
+        gaps
+    }
+
+    /// closure of activities whose times are ordered within the day
+    fn calculate_day_time_closure<Q: Borrow<Activity>>(
+        job_config: &JobConfig,
+        activities: &[Q],
         start: Option<Time>,
         end: Option<Time>,
-    ) -> Vec<Activity> {
+    ) -> (Vec<Activity>, ClosureDiagnostics) {
         #[repr(transparent)]
         #[derive(Debug)]
         struct ActivitySortByEndTime<'a>(&'a Activity);
@@ -148,18 +316,17 @@ impl Activity {
         fn fold_report(
             job_config: &JobConfig,
             stack: &BinaryHeap<ActivitySortByEndTime>,
-            open_ended: &Vec<&Activity>,
+            open_ended: &[&Activity],
             start_time: Option<&Time>,
             end_time: Option<&Time>,
+            diagnostics: &mut ClosureDiagnostics,
         ) -> Option<Activity> {
             let folded = Activity::fold_inner(
                 job_config,
-                stack
-                    .iter()
-                    .map(|x| x.0)
-                    .chain(open_ended.iter().map(|x| *x)),
+                stack.iter().map(|x| x.0).chain(open_ended.iter().copied()),
                 start_time,
                 end_time,
+                diagnostics,
             );
 
             if let Some(folded) = folded {
@@ -189,15 +356,15 @@ impl Activity {
             }
         }
 
-        if let (Some(start), Some(end)) = (start, end) {
-            if start >= end {
-                return Vec::new();
-            }
+        if let (Some(start), Some(end)) = (start, end)
+            && start >= end
+        {
+            return (Vec::new(), ClosureDiagnostics::default());
         }
 
         let mut activities: Vec<&Activity> = activities.iter().map(|x| x.borrow()).collect_vec();
         // sort by start time
-        activities.sort_by(|a, b| a.time.start.cmp(&b.time.start));
+        activities.sort_by_key(|a| a.time.start);
 
         let mut closure: Vec<Activity> = Vec::with_capacity(activities.len() * 2);
         let mut activity_stack: BinaryHeap<ActivitySortByEndTime> =
@@ -212,6 +379,7 @@ impl Activity {
             }
         }
 
+        let mut diagnostics = ClosureDiagnostics::default();
         let mut last_activity_end = None;
         for activity in activities.into_iter() {
             trace!(" - Processing activity: {}", activity);
@@ -233,6 +401,7 @@ impl Activity {
                         &open_ended_activities,
                         last_activity_end.as_ref(),
                         Some(&top_activity.0.time.end_time_or_end_of_day()),
+                        &mut diagnostics,
                     ) {
                         trace!(
                             "   -> Folding current stack up to end of dropped activity: {}",
@@ -243,7 +412,7 @@ impl Activity {
 
                     last_activity_end = Some(top_activity.0.time.end_time_or_end_of_day());
 
-                    drop(activity_stack.pop());
+                    activity_stack.pop();
                 } else {
                     break;
                 }
@@ -255,6 +424,7 @@ impl Activity {
                 &open_ended_activities,
                 last_activity_end.as_ref(),
                 Some(&activity.time.start),
+                &mut diagnostics,
             ) {
                 trace!(
                     "   -> Folding current stack up to start of new activity: {}",
@@ -290,16 +460,17 @@ impl Activity {
                 &open_ended_activities,
                 last_activity_end.as_ref(),
                 activity.0.time.end.as_ref(),
+                &mut diagnostics,
             ) {
                 closure.push(folded);
             }
 
             last_activity_end = Some(activity.0.time.end_time_or_end_of_day());
 
-            drop(activity_stack.pop());
+            activity_stack.pop();
         }
 
-        if open_ended_activities.len() > 0 {
+        if !open_ended_activities.is_empty() {
             trace!("   -> Folding remaining open-ended activities");
             if let Some(folded) = fold_report(
                 job_config,
@@ -307,13 +478,22 @@ impl Activity {
                 &open_ended_activities,
                 last_activity_end.as_ref(),
                 None,
+                &mut diagnostics,
             ) {
                 closure.push(folded);
             }
         }
 
+        debug_assert!(
+            closure
+                .iter()
+                .all(|segment| segment.time.validate(None).is_ok()),
+            "closure has segments of negative duration: {:?}",
+            closure
+        );
+
         if start.is_none() && end.is_none() {
-            return closure;
+            return (closure, diagnostics);
         }
 
         trace!("Clamping closure to provided time limits");
@@ -343,20 +523,97 @@ impl Activity {
             result.push(activity);
         }
 
-        result
+        (result, diagnostics)
+    }
+}
+
+impl Activity {
+    /// sum up the duration of closure segments per class
+    ///
+    /// open-ended segments do not contribute, clamp the closure beforehand.
+    /// segments of negative duration are skipped, see [`Activity::sum_durations`]
+    pub fn summarize_by_class<Q: Borrow<Activity>>(
+        closure: &[Q],
+    ) -> BTreeMap<Identifier, Duration> {
+        let mut totals = BTreeMap::new();
+        for segment in closure {
+            let segment = segment.borrow();
+            *totals
+                .entry(segment.class.clone())
+                .or_insert(SafeDuration::default()) += segment.time.duration().unwrap_or_default();
+        }
+        into_totals(totals)
+    }
+
+    /// total duration of the closure segments, counting the segments of negative duration
+    pub fn sum_durations<Q: Borrow<Activity>>(closure: &[Q]) -> SafeDuration {
+        closure
+            .iter()
+            .map(|segment| segment.borrow().time.duration().unwrap_or_default())
+            .sum()
+    }
+
+    /// sum up the duration of closure segments per project using the given attribution policy
+    ///
+    /// segments without projects do not contribute
+    pub fn summarize_by_project<Q: Borrow<Activity>>(
+        closure: &[Q],
+        attribution: Attribution,
+    ) -> BTreeMap<Identifier, Duration> {
+        let mut totals = BTreeMap::new();
+        for segment in closure {
+            for (project, duration) in attribution.attribute(segment.borrow()) {
+                *totals.entry(project).or_insert(SafeDuration::default()) += duration;
+            }
+        }
+        into_totals(totals)
+    }
+
+    /// sum up the duration of closure segments per client of their projects
+    ///
+    /// builds on the per-project attribution of each segment, projects sharing
+    /// a client within the same segment count at most once for that client.
+    /// projects without a client (or unknown projects) are grouped under `None`
+    pub fn summarize_by_client<Q: Borrow<Activity>>(
+        job_config: &JobConfig,
+        closure: &[Q],
+        attribution: Attribution,
+    ) -> BTreeMap<Option<String>, Duration> {
+        let mut totals = BTreeMap::new();
+        for segment in closure {
+            let segment = segment.borrow();
+            let duration = segment.time.duration().unwrap_or_default();
+
+            let mut clients: BTreeMap<Option<String>, Duration> = BTreeMap::new();
+            for (project, share) in Self::summarize_by_project(&[segment], attribution) {
+                let client = job_config
+                    .resolve_project(&project)
+                    .and_then(|project| project.inner.client.clone());
+                *clients.entry(client).or_insert(Duration::ZERO) += share;
+            }
+
+            for (client, share) in clients {
+                *totals.entry(client).or_insert(SafeDuration::default()) += share.min(duration);
+            }
+        }
+        into_totals(totals)
     }
 }
 
+fn into_totals<K: Ord>(sums: BTreeMap<K, SafeDuration>) -> BTreeMap<K, Duration> {
+    sums.into_iter()
+        .map(|(key, sum)| (key, sum.total()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::data::activity_class::ActivityClass;
-    use crate::data::identifier::Identifier;
-    use time::Time;
+    use crate::testing;
 
-    #[test]
-    fn test_fold_activities() {
-        let job_config = JobConfig {
+    fn fixture_job_config() -> JobConfig {
+        JobConfig {
             classes: vec![
                 ActivityClass {
                     id: Uuid::from_u128(1),
@@ -364,6 +621,9 @@ mod tests {
                         name: "work".into(),
                         priority: 1,
                         description: None,
+                        target_share: None,
+                        color: None,
+                        exclusive: false,
                     },
                 },
                 ActivityClass {
@@ -372,77 +632,87 @@ mod tests {
                         name: "break".into(),
                         priority: 2,
                         description: None,
+                        target_share: None,
+                        color: None,
+                        exclusive: false,
                     },
                 },
             ],
             projects: vec![],
-        };
+            ..JobConfig::default()
+        }
+    }
+
+    fn fixture_day() -> Vec<Activity> {
         let work_day = Activity {
             id: Uuid::nil(),
             name: Some("Working at the office".into()),
-            class: Identifier::ByName("work".into()),
-            time: Interval {
-                start: Time::from_hms(9, 0, 0).unwrap(),
-                end: Some(Time::from_hms(18, 0, 0).unwrap()),
-            },
-            projects: vec![],
+            ..testing::activity(
+                "work",
+                Time::from_hms(9, 0, 0).unwrap(),
+                Some(Time::from_hms(18, 0, 0).unwrap()),
+            )
         };
         let break_time = Activity {
             id: Uuid::nil(),
             name: Some("Lunch break".into()),
-            class: Identifier::ByName("break".into()),
-            time: Interval {
-                start: Time::from_hms(12, 0, 0).unwrap(),
-                end: Some(Time::from_hms(13, 0, 0).unwrap()),
-            },
-            projects: vec![],
+            ..testing::activity(
+                "break",
+                Time::from_hms(12, 0, 0).unwrap(),
+                Some(Time::from_hms(13, 0, 0).unwrap()),
+            )
         };
         let project_meeting = Activity {
             id: Uuid::nil(),
             name: Some("Project meeting".into()),
-            class: Identifier::ByName("work".into()),
-            time: Interval {
-                start: Time::from_hms(10, 0, 0).unwrap(),
-                end: Some(Time::from_hms(11, 0, 0).unwrap()),
-            },
-            projects: vec![],
+            ..testing::activity(
+                "work",
+                Time::from_hms(10, 0, 0).unwrap(),
+                Some(Time::from_hms(11, 0, 0).unwrap()),
+            )
         };
 
         let project_meeting2 = Activity {
             id: Uuid::nil(),
             name: Some("Project meeting 2".into()),
-            class: Identifier::ByName("work".into()),
-            time: Interval {
-                start: Time::from_hms(10, 30, 0).unwrap(),
-                end: Some(Time::from_hms(11, 30, 0).unwrap()),
-            },
-            projects: vec![],
+            ..testing::activity(
+                "work",
+                Time::from_hms(10, 30, 0).unwrap(),
+                Some(Time::from_hms(11, 30, 0).unwrap()),
+            )
         };
 
         let project_meeting3 = Activity {
             id: Uuid::nil(),
             name: Some("Project meeting 3".into()),
-            class: Identifier::ByName("work".into()),
-            time: Interval {
-                start: Time::from_hms(13, 0, 0).unwrap(),
-                end: Some(Time::from_hms(14, 0, 0).unwrap()),
-            },
-            projects: vec![],
+            ..testing::activity(
+                "work",
+                Time::from_hms(13, 0, 0).unwrap(),
+                Some(Time::from_hms(14, 0, 0).unwrap()),
+            )
         };
 
-        let day = vec![
+        vec![
             work_day,
             break_time,
             project_meeting,
             project_meeting2,
             project_meeting3,
-        ];
+        ]
+    }
+
+    #[test]
+    fn test_fold_activities() {
+        let job_config = fixture_job_config();
+        let day = fixture_day();
 
-        let closure = Activity::calculate_activity_closure(&job_config, &day, None, None);
+        let (closure, diagnostics) =
+            Activity::calculate_activity_closure(&job_config, &day, None, None);
         for activity in &closure {
             println!(" - {}", activity);
         }
 
+        assert!(diagnostics.is_empty());
         assert_eq!(closure.len(), 8);
         assert_eq!(
             format!("{}", closure[0]),
@@ -485,4 +755,180 @@ mod tests {
         );
         assert_eq!(closure[7].class, Uuid::from_u128(1).into());
     }
+
+    fn fold(job_config: &JobConfig, activities: &[&Activity]) -> Option<(Time, Option<Time>)> {
+        let mut diagnostics = ClosureDiagnostics::default();
+        Activity::fold_inner(
+            job_config,
+            activities.iter().copied(),
+            None,
+            None,
+            &mut diagnostics,
+        )
+        .map(|folded| (folded.time.start, folded.time.end))
+    }
+
+    #[test]
+    fn test_fold_activities_crossing_midnight() {
+        let job_config = fixture_job_config();
+        let mut day = fixture_day();
+        // written before pop split activities at midnight, ending before it starts
+        day[0].time.end = Some(Time::from_hms(1, 0, 0).unwrap());
+        day[0].time.start = Time::from_hms(23, 0, 0).unwrap();
+
+        let (closure, _) = Activity::calculate_activity_closure(&job_config, &day, None, None);
+        assert!(closure.iter().all(|segment| !segment.time.is_inverted()));
+    }
+
+    #[test]
+    fn test_fold_end_time_is_independent_of_order() {
+        let job_config = fixture_job_config();
+        let day = fixture_day();
+        // project meetings 10:00-11:00 and 10:30-11:30
+        let (meeting, meeting2) = (&day[2], &day[3]);
+        let expected = Some((
+            Time::from_hms(10, 30, 0).unwrap(),
+            Some(Time::from_hms(11, 0, 0).unwrap()),
+        ));
+        assert_eq!(fold(&job_config, &[meeting, meeting2]), expected);
+        assert_eq!(fold(&job_config, &[meeting2, meeting]), expected);
+    }
+
+    #[test]
+    fn test_fold_ignores_open_ended_activities_for_the_end_time() {
+        let job_config = fixture_job_config();
+        let day = fixture_day();
+        let mut open = day[1].clone();
+        open.time = Interval {
+            start: Time::from_hms(12, 30, 0).unwrap(),
+            end: None,
+        };
+        // working at the office 09:00-18:00 and lunch break 12:00-13:00
+        let (work, lunch) = (&day[0], &day[1]);
+        let expected = Some((
+            Time::from_hms(12, 30, 0).unwrap(),
+            Some(Time::from_hms(13, 0, 0).unwrap()),
+        ));
+        for order in [
+            [work, &open, lunch],
+            [&open, work, lunch],
+            [work, lunch, &open],
+            [lunch, &open, work],
+        ] {
+            assert_eq!(fold(&job_config, &order), expected);
+        }
+
+        // only open-ended activities stay open
+        let mut open2 = open.clone();
+        open2.time.start = Time::from_hms(14, 0, 0).unwrap();
+        let expected = Some((Time::from_hms(14, 0, 0).unwrap(), None));
+        assert_eq!(fold(&job_config, &[&open, &open2]), expected);
+        assert_eq!(fold(&job_config, &[&open2, &open]), expected);
+    }
+
+    #[test]
+    fn test_gaps_of_a_day_with_a_hole() {
+        let job_config = fixture_job_config();
+        let hm = |h, m| Time::from_hms(h, m, 0).unwrap();
+        let mut day = fixture_day();
+        // the work day ends at 12:00 and continues at 12:30, the lunch break is not tracked
+        day[0].time.end = Some(hm(12, 0));
+        day[1].time = Interval {
+            start: hm(12, 30),
+            end: Some(hm(18, 0)),
+        };
+        day[1].class = Identifier::ByName("work".into());
+
+        let gaps = Activity::calculate_gaps(&job_config, &day, None, None, Duration::minutes(1));
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].start, hm(12, 0));
+        assert_eq!(gaps[0].duration(), Some(Duration::minutes(30)));
+
+        // the limits of the day add gaps before the first and after the last activity
+        let gaps = Activity::calculate_gaps(
+            &job_config,
+            &day,
+            Some(hm(8, 0)),
+            Some(hm(18, 10)),
+            Duration::minutes(1),
+        );
+        let gaps = gaps
+            .iter()
+            .map(|gap| (gap.start, gap.end.unwrap()))
+            .collect_vec();
+        assert_eq!(
+            gaps,
+            [
+                (hm(8, 0), hm(9, 0)),
+                (hm(12, 0), hm(12, 30)),
+                (hm(18, 0), hm(18, 10))
+            ]
+        );
+
+        // gaps shorter than the threshold are left out
+        let gaps = Activity::calculate_gaps(&job_config, &day, None, None, Duration::hours(1));
+        assert!(gaps.is_empty());
+    }
+
+    #[test]
+    fn test_no_gaps_in_a_covered_day() {
+        let job_config = fixture_job_config();
+        let day = fixture_day();
+        let gaps = Activity::calculate_gaps(&job_config, &day, None, None, Duration::minutes(1));
+        assert!(gaps.is_empty());
+        let gaps = Activity::calculate_gaps::<Activity>(
+            &job_config,
+            &[],
+            None,
+            None,
+            Duration::minutes(1),
+        );
+        assert!(gaps.is_empty());
+    }
+
+    #[test]
+    fn test_summarize_by_class() {
+        let job_config = fixture_job_config();
+        let day = fixture_day();
+
+        let (closure, _) = Activity::calculate_activity_closure(&job_config, &day, None, None);
+        let totals = Activity::summarize_by_class(&closure);
+
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[&Uuid::from_u128(1).into()], Duration::hours(8));
+        assert_eq!(totals[&Uuid::from_u128(2).into()], Duration::hours(1));
+    }
+
+    #[test]
+    fn test_unknown_class_is_diagnosed() {
+        let job_config = fixture_job_config();
+        let mut day = fixture_day();
+        // the lunch break refers to a class missing from the job config
+        day[1].id = Uuid::from_u128(42);
+        day[1].class = Identifier::ByName("retired".into());
+
+        let (closure, diagnostics) =
+            Activity::calculate_activity_closure(&job_config, &day, None, None);
+        let totals = Activity::summarize_by_class(&closure);
+
+        // the break was counted as work, the lowest priority class
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[&Uuid::from_u128(1).into()], Duration::hours(9));
+        assert!(!diagnostics.is_empty());
+        assert_eq!(
+            diagnostics.unresolved,
+            BTreeMap::from([(
+                Identifier::ByName("retired".into()),
+                BTreeSet::from([Uuid::from_u128(42)])
+            )])
+        );
+        assert!(
+            diagnostics
+                .warning()
+                .unwrap()
+                .ends_with(" - @retired (activities 00000000-0000-0000-0000-00000000002a)")
+        );
+        assert!(diagnostics.check(false).is_ok());
+        assert!(diagnostics.check(true).is_err());
+    }
 }
@@ -0,0 +1,141 @@
+use crate::data::activity::Activity;
+use log::error;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// a field that differs between two versions of an activity, values as stored in day files
+///
+/// `None` means the field is not stored in that version, e.g. a missing name
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// field-level differences of two versions of the same activity
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ActivityDiff {
+    pub id: Uuid,
+    pub changes: Vec<FieldChange>,
+}
+
+/// stored fields of the activity by name
+fn fields(activity: &Activity) -> BTreeMap<String, Value> {
+    match serde_json::to_value(activity) {
+        Ok(Value::Object(fields)) => fields.into_iter().collect(),
+        Ok(_) => BTreeMap::new(),
+        Err(e) => {
+            error!("Unable to serialize activity {}: {}", activity.id, e);
+            BTreeMap::new()
+        }
+    }
+}
+
+impl ActivityDiff {
+    /// changes from `old` to `new` in field order, `None` if both versions are the same
+    pub fn between(old: &Activity, new: &Activity) -> Option<Self> {
+        let mut old_fields = fields(old);
+        let mut new_fields = fields(new);
+        let names = old_fields
+            .keys()
+            .chain(new_fields.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+
+        let changes = names
+            .into_iter()
+            .filter_map(|field| {
+                let old = old_fields.remove(&field);
+                let new = new_fields.remove(&field);
+                (old != new).then_some(FieldChange { field, old, new })
+            })
+            .collect::<Vec<_>>();
+        (!changes.is_empty()).then_some(Self {
+            id: new.id,
+            changes,
+        })
+    }
+}
+
+/// activities of two versions of a day matched by id
+#[derive(Debug, Clone, Default)]
+pub struct ActivitiesDiff {
+    /// only in the new version
+    pub added: Vec<Activity>,
+    /// only in the old version
+    pub removed: Vec<Activity>,
+    /// in both versions with different fields
+    pub modified: Vec<ActivityDiff>,
+}
+
+impl ActivitiesDiff {
+    pub fn between(old: &[Activity], new: &[Activity]) -> Self {
+        let mut diff = Self::default();
+        for activity in new {
+            match old.iter().find(|other| other.id == activity.id) {
+                Some(previous) => diff
+                    .modified
+                    .extend(ActivityDiff::between(previous, activity)),
+                None => diff.added.push(activity.clone()),
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|activity| !new.iter().any(|other| other.id == activity.id))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn activity(value: Value) -> Activity {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_activity_diff_lists_changed_fields() {
+        let old = activity(json!({
+            "id": "00000000-0000-4000-8000-000000000001", "class": "@work", "name": "coding",
+            "time": {"start": "09:00:00", "end": "12:00:00"}
+        }));
+        let new = activity(json!({
+            "id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+            "time": {"start": "08:30:00", "end": "12:00:00"}, "projects": ["@projA"]
+        }));
+
+        assert_eq!(ActivityDiff::between(&old, &old), None);
+        let diff = ActivityDiff::between(&old, &new).unwrap();
+        assert_eq!(
+            diff.changes,
+            [
+                FieldChange {
+                    field: "name".to_string(),
+                    old: Some(json!("coding")),
+                    new: None,
+                },
+                FieldChange {
+                    field: "projects".to_string(),
+                    old: None,
+                    new: Some(json!(["@projA"])),
+                },
+                FieldChange {
+                    field: "time".to_string(),
+                    old: Some(json!({"start": "09:00:00", "end": "12:00:00"})),
+                    new: Some(json!({"start": "08:30:00", "end": "12:00:00"})),
+                },
+            ]
+        );
+    }
+}
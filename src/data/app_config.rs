@@ -1,8 +1,39 @@
-use log::warn;
+use crate::cli::AppArgs;
+use crate::data::attribution::Attribution;
+use crate::data::manager::write_atomic;
+use crate::data::report::DailyQuotas;
+use crate::error::Error;
+use crate::serde::pretty_duration::parse_duration;
+use log::{error, trace, warn};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::env;
-use std::path::PathBuf;
-use time::Duration;
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use time::{Date, Duration, Weekday};
+
+/// location of the app config within the config directory
+pub const APP_CONFIG_PATH: &str = "timetrax/config.json";
+
+/// app config used without `--config`, `$XDG_CONFIG_HOME/timetrax/config.json`
+/// or `~/.config/timetrax/config.json`
+pub fn default_config_path() -> Option<PathBuf> {
+    config_path_from(env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME"))
+}
+
+/// like [`default_config_path`], relative XDG paths are ignored as the spec demands
+fn config_path_from(xdg_config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let config_home = xdg_config_home
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| {
+            home.filter(|home| !home.is_empty())
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join(APP_CONFIG_PATH))
+}
 
 /// app configuration on disk
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -14,7 +45,220 @@ pub struct AppConfigDisk {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub job_day_folder_format: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub journal_file_name: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
     pub work_quota_default: Option<Duration>,
+    #[serde(
+        skip_serializing_if = "crate::serde::pretty_weekday_durations::is_empty",
+        default,
+        with = "crate::serde::pretty_weekday_durations"
+    )]
+    pub work_quota_per_weekday: [Option<Duration>; 7],
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub attribution: Option<Attribution>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub share_tolerance: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub strict_parsing: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub block_on_stale: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub check_on_load: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
+    pub compact_tolerance: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub evidence_limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub flush_on_mutation: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub week_start: Option<Weekday>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub clock: Option<ClockFormat>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub weekday_names: Option<[String; 7]>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub month_names: Option<[String; 12]>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub notify_on_quota: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
+    pub break_reminder_after: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub notify_state_file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_name_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub workspace_state_file_name: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
+    pub trash_retention: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mapping_file_name: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
+    pub max_day_total: Option<Duration>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
+    pub min_gap: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub status_short_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub status_short_idle_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub backup_on_save: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub backup_keep: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub balance_start_date: Option<Date>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
+    pub overnight_window: Option<Duration>,
+}
+
+/// settings of the app config, by their names in the config file
+pub const APP_CONFIG_KEYS: [&str; 33] = [
+    "default_data_path",
+    "job_config_file_name",
+    "job_day_folder_format",
+    "journal_file_name",
+    "work_quota_default",
+    "work_quota_per_weekday",
+    "attribution",
+    "share_tolerance",
+    "strict_parsing",
+    "block_on_stale",
+    "check_on_load",
+    "compact_tolerance",
+    "evidence_limit",
+    "flush_on_mutation",
+    "week_start",
+    "clock",
+    "weekday_names",
+    "month_names",
+    "notify_on_quota",
+    "break_reminder_after",
+    "notify_state_file_name",
+    "max_name_length",
+    "workspace_state_file_name",
+    "trash_retention",
+    "mapping_file_name",
+    "max_day_total",
+    "min_gap",
+    "status_short_format",
+    "status_short_idle_format",
+    "backup_on_save",
+    "backup_keep",
+    "balance_start_date",
+    "overnight_window",
+];
+
+/// settings holding durations, written like `7h 30m`
+pub const DURATION_KEYS: [&str; 7] = [
+    "work_quota_default",
+    "compact_tolerance",
+    "break_reminder_after",
+    "trash_retention",
+    "max_day_total",
+    "min_gap",
+    "overnight_window",
+];
+
+/// settings holding a duration per weekday, set one weekday at a time like
+/// `work_quota_per_weekday.friday`
+pub const WEEKDAY_DURATION_KEYS: [&str; 1] = ["work_quota_per_weekday"];
+
+impl From<&AppConfig> for AppConfigDisk {
+    fn from(config: &AppConfig) -> Self {
+        AppConfigDisk {
+            default_data_path: Some(config.default_data_path.clone()),
+            job_config_file_name: Some(config.job_config_file_name.clone()),
+            job_day_folder_format: Some(config.job_day_folder_format.clone()),
+            journal_file_name: Some(config.journal_file_name.clone()),
+            work_quota_default: Some(config.work_quota_default),
+            work_quota_per_weekday: config.work_quota_per_weekday,
+            attribution: Some(config.attribution),
+            share_tolerance: Some(config.share_tolerance),
+            strict_parsing: Some(config.strict_parsing),
+            block_on_stale: Some(config.block_on_stale),
+            check_on_load: Some(config.check_on_load),
+            compact_tolerance: Some(config.compact_tolerance),
+            evidence_limit: Some(config.evidence_limit),
+            flush_on_mutation: Some(config.flush_on_mutation),
+            week_start: Some(config.week_start),
+            clock: Some(config.clock),
+            weekday_names: Some(config.weekday_names.clone()),
+            month_names: Some(config.month_names.clone()),
+            notify_on_quota: Some(config.notify_on_quota),
+            break_reminder_after: config.break_reminder_after,
+            notify_state_file_name: Some(config.notify_state_file_name.clone()),
+            max_name_length: Some(config.max_name_length),
+            workspace_state_file_name: Some(config.workspace_state_file_name.clone()),
+            trash_retention: Some(config.trash_retention),
+            mapping_file_name: Some(config.mapping_file_name.clone()),
+            max_day_total: Some(config.max_day_total),
+            min_gap: Some(config.min_gap),
+            status_short_format: Some(config.status_short_format.clone()),
+            status_short_idle_format: Some(config.status_short_idle_format.clone()),
+            backup_on_save: Some(config.backup_on_save),
+            backup_keep: Some(config.backup_keep),
+            balance_start_date: config.balance_start_date,
+            overnight_window: Some(config.overnight_window),
+        }
+    }
+}
+
+/// where the value of a setting comes from, see [`AppConfig::resolve`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigSource {
+    #[default]
+    Default,
+    File,
+    Environment,
+    Flag,
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ConfigSource::Default => "default",
+            ConfigSource::File => "file",
+            ConfigSource::Environment => "env",
+            ConfigSource::Flag => "flag",
+        })
+    }
+}
+
+/// clock used to display times in human readable output
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClockFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+    #[serde(rename = "12h")]
+    TwelveHour,
 }
 
 /// app configuration used by the app
@@ -24,7 +268,74 @@ pub struct AppConfig {
     pub default_data_path: PathBuf,
     pub job_config_file_name: String,
     pub job_day_folder_format: String,
+    /// file recording modifications of days so they can be undone
+    pub journal_file_name: String,
     pub work_quota_default: Duration,
+    /// default work quotas of single weekdays, starting with monday, overriding
+    /// `work_quota_default` on working days and nothing on weekends
+    pub work_quota_per_weekday: [Option<Duration>; 7],
+    /// how time of segments with multiple projects is attributed
+    pub attribution: Attribution,
+    /// allowed deviation from target shares in percentage points
+    pub share_tolerance: f32,
+    /// reject day files containing unknown fields instead of preserving them
+    pub strict_parsing: bool,
+    /// refuse to push while the previous day still has open activities
+    pub block_on_stale: bool,
+    /// warn about the problems `timetrax check --all` finds whenever the data directory is opened
+    pub check_on_load: bool,
+    /// largest gap between fragments that `compact` still merges
+    pub compact_tolerance: Duration,
+    /// evidence entries kept per activity, the oldest are dropped first
+    pub evidence_limit: usize,
+    /// long-running modes save every modification right away instead of periodically
+    pub flush_on_mutation: bool,
+    /// first day of the week in human readable output
+    pub week_start: Weekday,
+    /// clock used for times in human readable output
+    pub clock: ClockFormat,
+    /// display names of the weekdays, starting with monday
+    pub weekday_names: [String; 7],
+    /// display names of the months, starting with january
+    pub month_names: [String; 12],
+    /// `notify-check` tells when the work tracked today reaches the daily quota
+    pub notify_on_quota: bool,
+    /// `notify-check` reminds to take a break after working this long without one
+    pub break_reminder_after: Option<Duration>,
+    /// file recording the notifications already shown today
+    pub notify_state_file_name: String,
+    /// names of activities, blockers, projects and classes are limited to this many characters
+    pub max_name_length: usize,
+    /// file remembering per-workspace choices such as the current project
+    pub workspace_state_file_name: String,
+    /// deleted activities are purged from the trash after this long
+    pub trash_retention: Duration,
+    /// file translating class and project names to and from other systems
+    pub mapping_file_name: String,
+    /// days whose activities cover more than this are not saved without `--force-save`
+    pub max_day_total: Duration,
+    /// shortest untracked time shown by `status --gaps` and `report --show-gaps`
+    pub min_gap: Duration,
+    /// line of `status --short` while an activity is running,
+    /// with the placeholders `{class}`, `{name}`, `{elapsed}`, `{project}` and `{remaining}`
+    pub status_short_format: String,
+    /// line of `status --short` while nothing is running, with the placeholder `{remaining}`
+    pub status_short_idle_format: String,
+    /// copy day files and the job config to the backup folder before overwriting them
+    pub backup_on_save: bool,
+    /// backups kept per file, older ones are deleted
+    pub backup_keep: usize,
+    /// first day of the flextime balance of `report --balance`, the first tracked day if unset
+    pub balance_start_date: Option<Date>,
+    /// `pop` stops an activity left open overnight at the end of its day and continues it
+    /// today only if it started this long before the end of the day
+    pub overnight_window: Duration,
+    /// colorize human readable output, not stored, enabled for terminals at startup
+    pub color: bool,
+    /// app config file read at startup and written by `config set`, not stored
+    pub file: Option<PathBuf>,
+    /// sources of the settings not taken from the defaults, not stored
+    pub sources: BTreeMap<String, ConfigSource>,
 }
 
 impl From<AppConfigDisk> for AppConfig {
@@ -40,14 +351,240 @@ impl From<AppConfigDisk> for AppConfig {
         if let Some(job_day_folder_format) = disk.job_day_folder_format {
             result.job_day_folder_format = job_day_folder_format;
         }
+        if let Some(journal_file_name) = disk.journal_file_name {
+            result.journal_file_name = journal_file_name;
+        }
         if let Some(work_quota_default) = disk.work_quota_default {
             result.work_quota_default = work_quota_default;
         }
+        result.work_quota_per_weekday = disk.work_quota_per_weekday;
+        if let Some(attribution) = disk.attribution {
+            result.attribution = attribution;
+        }
+        if let Some(share_tolerance) = disk.share_tolerance {
+            result.share_tolerance = share_tolerance;
+        }
+        if let Some(strict_parsing) = disk.strict_parsing {
+            result.strict_parsing = strict_parsing;
+        }
+        if let Some(block_on_stale) = disk.block_on_stale {
+            result.block_on_stale = block_on_stale;
+        }
+        if let Some(check_on_load) = disk.check_on_load {
+            result.check_on_load = check_on_load;
+        }
+        if let Some(compact_tolerance) = disk.compact_tolerance {
+            result.compact_tolerance = compact_tolerance;
+        }
+        if let Some(evidence_limit) = disk.evidence_limit {
+            result.evidence_limit = evidence_limit;
+        }
+        if let Some(flush_on_mutation) = disk.flush_on_mutation {
+            result.flush_on_mutation = flush_on_mutation;
+        }
+        if let Some(week_start) = disk.week_start {
+            result.week_start = week_start;
+        }
+        if let Some(clock) = disk.clock {
+            result.clock = clock;
+        }
+        if let Some(weekday_names) = disk.weekday_names {
+            result.weekday_names = weekday_names;
+        }
+        if let Some(month_names) = disk.month_names {
+            result.month_names = month_names;
+        }
+        if let Some(notify_on_quota) = disk.notify_on_quota {
+            result.notify_on_quota = notify_on_quota;
+        }
+        if let Some(break_reminder_after) = disk.break_reminder_after {
+            result.break_reminder_after = Some(break_reminder_after);
+        }
+        if let Some(notify_state_file_name) = disk.notify_state_file_name {
+            result.notify_state_file_name = notify_state_file_name;
+        }
+        if let Some(max_name_length) = disk.max_name_length {
+            result.max_name_length = max_name_length;
+        }
+        if let Some(workspace_state_file_name) = disk.workspace_state_file_name {
+            result.workspace_state_file_name = workspace_state_file_name;
+        }
+        if let Some(trash_retention) = disk.trash_retention {
+            result.trash_retention = trash_retention;
+        }
+        if let Some(mapping_file_name) = disk.mapping_file_name {
+            result.mapping_file_name = mapping_file_name;
+        }
+        if let Some(max_day_total) = disk.max_day_total {
+            result.max_day_total = max_day_total.min(Duration::DAY);
+        }
+        if let Some(min_gap) = disk.min_gap {
+            result.min_gap = min_gap;
+        }
+        if let Some(status_short_format) = disk.status_short_format {
+            result.status_short_format = status_short_format;
+        }
+        if let Some(status_short_idle_format) = disk.status_short_idle_format {
+            result.status_short_idle_format = status_short_idle_format;
+        }
+        if let Some(backup_on_save) = disk.backup_on_save {
+            result.backup_on_save = backup_on_save;
+        }
+        if let Some(backup_keep) = disk.backup_keep {
+            result.backup_keep = backup_keep;
+        }
+        if let Some(balance_start_date) = disk.balance_start_date {
+            result.balance_start_date = Some(balance_start_date);
+        }
+        if let Some(overnight_window) = disk.overnight_window {
+            result.overnight_window = overnight_window;
+        }
 
         result
     }
 }
 
+impl AppConfigDisk {
+    /// load the app config at `path`, none if the file does not exist
+    pub fn load(path: &Path) -> crate::error::Result<Option<Self>> {
+        let contents = match std::fs::read_to_string(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                trace!("No app config at {}, using the defaults", path.display());
+                return Ok(None);
+            }
+            Err(e) => {
+                error!("Failed to read the app config {}: {}", path.display(), e);
+                return Err(e.into());
+            }
+            Ok(contents) => contents,
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|source| Error::Config {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+}
+
+/// set `key` to `value` in the app config file at `path`, keeping its other contents such as
+/// unknown fields. the file and its folders are created if missing
+pub fn update_config_file(path: &Path, key: &str, value: Value) -> crate::error::Result<()> {
+    let mut contents = match std::fs::read_to_string(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Map::new(),
+        Err(e) => {
+            error!("Failed to read the app config {}: {}", path.display(), e);
+            return Err(e.into());
+        }
+        Ok(contents) => serde_json::from_str(&contents).map_err(|source| Error::Config {
+            path: path.to_path_buf(),
+            source,
+        })?,
+    };
+    contents.insert(key.to_string(), value);
+    let contents = Value::Object(contents);
+    // refuse values the app could not load afterwards
+    AppConfigDisk::deserialize(&contents).map_err(|source| Error::Config {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    if let Some(folder) = path.parent() {
+        std::fs::create_dir_all(folder)?;
+    }
+    write_atomic(path, |out| {
+        serde_json::to_writer_pretty(&mut *out, &contents)?;
+        writeln!(out)?;
+        Ok(())
+    })
+}
+
+/// environment variable overriding the data path, see [`AppConfig::resolve`]
+pub const ENV_DATA_PATH: &str = "TIMETRAX_DATA_PATH";
+/// environment variable overriding the job config file, relative to the data path
+pub const ENV_JOB_CONFIG: &str = "TIMETRAX_JOB_CONFIG";
+/// environment variable overriding the default work quota, e.g. `7h 30m`
+pub const ENV_WORK_QUOTA: &str = "TIMETRAX_WORK_QUOTA";
+
+impl AppConfig {
+    /// app config from the defaults, overridden by the config file, then by the environment
+    /// variables read through `env`, then by the command line. empty variables are ignored
+    pub fn resolve(
+        cli: &AppArgs,
+        disk: Option<AppConfigDisk>,
+        env: &impl Fn(&str) -> Option<String>,
+    ) -> crate::error::Result<Self> {
+        let from_file = match disk.as_ref().map(serde_json::to_value) {
+            Some(Ok(Value::Object(set))) => set.into_iter().map(|(key, _)| key).collect(),
+            _ => Vec::new(),
+        };
+        let mut config = disk.map(AppConfig::from).unwrap_or_default();
+        for key in from_file {
+            config.sources.insert(key, ConfigSource::File);
+        }
+        let var = |name: &str| env(name).filter(|value| !value.is_empty());
+
+        if let Some(data_path) = var(ENV_DATA_PATH) {
+            config.default_data_path = PathBuf::from(data_path);
+            config.set_source("default_data_path", ConfigSource::Environment);
+        }
+        if let Some(job_config) = var(ENV_JOB_CONFIG) {
+            config.job_config_file_name = job_config;
+            config.set_source("job_config_file_name", ConfigSource::Environment);
+        }
+        if let Some(quota) = var(ENV_WORK_QUOTA) {
+            config.work_quota_default =
+                parse_duration(&quota).map_err(|message| Error::Environment {
+                    variable: ENV_WORK_QUOTA.to_string(),
+                    message,
+                })?;
+            config.set_source("work_quota_default", ConfigSource::Environment);
+        }
+
+        if let Some(data_path) = &cli.data_path {
+            config.default_data_path = data_path.clone();
+            config.set_source("default_data_path", ConfigSource::Flag);
+        }
+        config.file = cli.config.clone().or_else(|| {
+            config_path_from(
+                env("XDG_CONFIG_HOME").map(OsString::from),
+                env("HOME").map(OsString::from),
+            )
+        });
+        Ok(config)
+    }
+
+    fn set_source(&mut self, key: &str, source: ConfigSource) {
+        self.sources.insert(key.to_string(), source);
+    }
+
+    /// where the value of the setting comes from
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources.get(key).copied().unwrap_or_default()
+    }
+
+    /// default work quota of each weekday, for days without a quota of their own
+    pub fn daily_quotas(&self) -> DailyQuotas {
+        let mut quotas = DailyQuotas::uniform(self.work_quota_default);
+        let mut weekday = Weekday::Monday;
+        for quota in self.work_quota_per_weekday {
+            if let Some(quota) = quota {
+                quotas = quotas.with(weekday, quota);
+            }
+            weekday = weekday.next();
+        }
+        quotas
+    }
+
+    /// the settings by their names in the config file, as they would be stored
+    pub fn values(&self) -> crate::error::Result<Map<String, Value>> {
+        match serde_json::to_value(AppConfigDisk::from(self))? {
+            Value::Object(values) => Ok(values),
+            _ => Ok(Map::new()),
+        }
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -65,7 +602,250 @@ impl Default for AppConfig {
                 }),
             job_config_file_name: "job.json".to_string(),
             job_day_folder_format: "data".to_string(),
+            journal_file_name: "journal.jsonl".to_string(),
             work_quota_default: Duration::hours(8),
+            work_quota_per_weekday: [None; 7],
+            attribution: Attribution::default(),
+            share_tolerance: 5.0,
+            strict_parsing: false,
+            block_on_stale: false,
+            check_on_load: false,
+            compact_tolerance: Duration::minutes(1),
+            evidence_limit: 50,
+            flush_on_mutation: false,
+            week_start: Weekday::Monday,
+            clock: ClockFormat::default(),
+            weekday_names: [
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ]
+            .map(String::from),
+            month_names: [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ]
+            .map(String::from),
+            notify_on_quota: true,
+            break_reminder_after: None,
+            notify_state_file_name: "notify.json".to_string(),
+            max_name_length: 200,
+            workspace_state_file_name: "state.json".to_string(),
+            trash_retention: Duration::days(30),
+            mapping_file_name: "mappings.json".to_string(),
+            max_day_total: Duration::DAY,
+            min_gap: Duration::minutes(1),
+            status_short_format: "{class} {elapsed}[ ▶ {project}] | {remaining} left".to_string(),
+            status_short_idle_format: "idle | {remaining}".to_string(),
+            backup_on_save: true,
+            backup_keep: 5,
+            balance_start_date: None,
+            overnight_window: Duration::hours(2),
+            color: false,
+            file: None,
+            sources: BTreeMap::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::job_config::JobConfig;
+    use crate::testing::TempDataDir;
+    use clap::Parser;
+
+    #[test]
+    fn test_config_path_prefers_xdg() {
+        let path = |xdg: Option<&str>, home: Option<&str>| {
+            config_path_from(xdg.map(OsString::from), home.map(OsString::from))
+        };
+        assert_eq!(
+            path(Some("/xdg"), Some("/home/me")),
+            Some(PathBuf::from("/xdg/timetrax/config.json"))
+        );
+        for xdg in [None, Some(""), Some("relative")] {
+            assert_eq!(
+                path(xdg, Some("/home/me")),
+                Some(PathBuf::from("/home/me/.config/timetrax/config.json"))
+            );
+        }
+        assert_eq!(path(None, None), None);
+        assert_eq!(path(None, Some("")), None);
+    }
+
+    #[test]
+    fn test_load_app_config() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = dir.path().join("config.json");
+        assert!(AppConfigDisk::load(&path).unwrap().is_none());
+
+        std::fs::write(
+            &path,
+            r#"{"work_quota_default": "7h 30m", "min_gap": "5m", "backup_keep": 2}"#,
+        )
+        .unwrap();
+        let config = AppConfig::from(AppConfigDisk::load(&path).unwrap().unwrap());
+        assert_eq!(config.work_quota_default, Duration::minutes(450));
+        assert_eq!(config.min_gap, Duration::minutes(5));
+        assert_eq!(config.backup_keep, 2);
+        assert_eq!(config.job_config_file_name, "job.json");
+
+        std::fs::write(&path, r#"{"work_quota_default": "lots"}"#).unwrap();
+        let error = AppConfigDisk::load(&path).unwrap_err();
+        assert_eq!(error.exit_code(), 78);
+        let message = error.to_string();
+        assert!(message.contains(&path.display().to_string()), "{message}");
+        assert!(
+            message.contains("Invalid duration format: lots"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_app_config_keys_cover_every_setting() {
+        let mut config = AppConfig::default();
+        // settings without a value are not stored
+        config.work_quota_per_weekday[4] = Some(Duration::hours(6));
+        config.break_reminder_after = Some(Duration::hours(2));
+        config.balance_start_date =
+            Some(Date::from_calendar_date(2024, time::Month::May, 6).unwrap());
+        let Value::Object(values) = serde_json::to_value(AppConfigDisk::from(&config)).unwrap()
+        else {
+            panic!("settings are not an object");
+        };
+        let mut keys = values.keys().map(String::as_str).collect::<Vec<_>>();
+        let mut expected = APP_CONFIG_KEYS.to_vec();
+        keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let disk = || {
+            Some(AppConfigDisk {
+                default_data_path: Some("/disk".into()),
+                job_config_file_name: Some("disk.json".into()),
+                work_quota_default: Some(Duration::hours(7)),
+                ..serde_json::from_str("{}").unwrap()
+            })
+        };
+        let env = |name: &str| match name {
+            ENV_DATA_PATH => Some("/env".to_string()),
+            ENV_JOB_CONFIG => Some("env.json".to_string()),
+            ENV_WORK_QUOTA => Some("6h 30m".to_string()),
+            _ => None,
+        };
+        let no_env = |_: &str| None;
+        let no_cli = AppArgs::parse_from(["timetrax"]);
+        let cli = AppArgs::parse_from(["timetrax", "--data-path", "/cli"]);
+        let resolve = |cli, disk, env: &dyn Fn(&str) -> Option<String>| {
+            let config = AppConfig::resolve(cli, disk, &env).unwrap();
+            (
+                config.default_data_path,
+                config.job_config_file_name,
+                config.work_quota_default,
+            )
+        };
+        let defaults = AppConfig::default();
+
+        // defaults < config file < environment < command line
+        assert_eq!(
+            resolve(&no_cli, None, &no_env),
+            (
+                defaults.default_data_path.clone(),
+                defaults.job_config_file_name.clone(),
+                defaults.work_quota_default
+            )
+        );
+        assert_eq!(
+            resolve(&no_cli, disk(), &no_env),
+            ("/disk".into(), "disk.json".into(), Duration::hours(7))
+        );
+        let from_env = ("/env".into(), "env.json".into(), Duration::minutes(390));
+        assert_eq!(resolve(&no_cli, None, &env), from_env);
+        assert_eq!(resolve(&no_cli, disk(), &env), from_env);
+        assert_eq!(
+            resolve(&cli, None, &no_env),
+            (
+                "/cli".into(),
+                defaults.job_config_file_name.clone(),
+                defaults.work_quota_default
+            )
+        );
+        assert_eq!(
+            resolve(&cli, disk(), &no_env),
+            ("/cli".into(), "disk.json".into(), Duration::hours(7))
+        );
+        assert_eq!(
+            resolve(&cli, disk(), &env),
+            ("/cli".into(), "env.json".into(), Duration::minutes(390))
+        );
+
+        // variables override one setting each, empty ones are ignored
+        let only_quota = |name: &str| (name == ENV_WORK_QUOTA).then(|| "5h".to_string());
+        assert_eq!(
+            resolve(&no_cli, disk(), &only_quota),
+            ("/disk".into(), "disk.json".into(), Duration::hours(5))
+        );
+        let empty = |_: &str| Some(String::new());
+        assert_eq!(
+            resolve(&no_cli, disk(), &empty),
+            ("/disk".into(), "disk.json".into(), Duration::hours(7))
+        );
+    }
+
+    #[test]
+    fn test_resolve_records_sources() {
+        let disk = serde_json::from_str(r#"{"min_gap": "5m", "backup_keep": 2}"#).unwrap();
+        let env = |name: &str| match name {
+            ENV_WORK_QUOTA => Some("7h".to_string()),
+            "HOME" => Some("/home/me".to_string()),
+            _ => None,
+        };
+        let cli = AppArgs::parse_from(["timetrax", "--data-path", "/cli"]);
+        let config = AppConfig::resolve(&cli, Some(disk), &env).unwrap();
+        assert_eq!(config.source("min_gap"), ConfigSource::File);
+        assert_eq!(config.source("backup_keep"), ConfigSource::File);
+        assert_eq!(
+            config.source("work_quota_default"),
+            ConfigSource::Environment
+        );
+        assert_eq!(config.source("default_data_path"), ConfigSource::Flag);
+        assert_eq!(config.source("week_start"), ConfigSource::Default);
+        assert_eq!(
+            config.file,
+            Some(PathBuf::from("/home/me/.config/timetrax/config.json"))
+        );
+
+        let cli = AppArgs::parse_from(["timetrax", "--config", "/etc/timetrax.json"]);
+        let config = AppConfig::resolve(&cli, None, &env).unwrap();
+        assert_eq!(config.file, Some(PathBuf::from("/etc/timetrax.json")));
+    }
+
+    #[test]
+    fn test_resolve_rejects_invalid_variables() {
+        let cli = AppArgs::parse_from(["timetrax"]);
+        let env = |name: &str| (name == ENV_WORK_QUOTA).then(|| "a while".to_string());
+        let error = AppConfig::resolve(&cli, None, &env).unwrap_err();
+        assert_eq!(error.exit_code(), 78);
+        let message = error.to_string();
+        assert!(message.contains("TIMETRAX_WORK_QUOTA"), "{message}");
+        assert!(message.contains("a while"), "{message}");
+    }
+}
@@ -0,0 +1,116 @@
+use crate::data::activity::Activity;
+use crate::data::identifier::Identifier;
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use time::Duration;
+
+/// policy deciding how the time of a segment carrying multiple projects is attributed
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Attribution {
+    /// every project receives the full duration, totals may exceed the tracked time
+    Full,
+    /// the duration is split evenly between all projects
+    #[default]
+    SplitEvenly,
+    /// only the first project (in sorted order) receives the duration
+    FirstOnly,
+    /// the duration is split according to the per-project weights of the activities
+    Weighted,
+}
+
+impl Display for Attribution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Attribution::Full => write!(f, "full"),
+            Attribution::SplitEvenly => write!(f, "split-evenly"),
+            Attribution::FirstOnly => write!(f, "first-only"),
+            Attribution::Weighted => write!(f, "weighted"),
+        }
+    }
+}
+
+impl Attribution {
+    /// attribute the duration of a closure segment to its projects
+    ///
+    /// projects are deduplicated, segments without projects yield nothing,
+    /// open-ended segments count as zero length
+    pub fn attribute(&self, segment: &Activity) -> Vec<(Identifier, Duration)> {
+        let duration = segment.time.duration().unwrap_or_default();
+        let projects = segment.projects.iter().unique().cloned().collect_vec();
+
+        if projects.is_empty() {
+            return Vec::new();
+        }
+
+        match self {
+            Attribution::Full => projects.into_iter().map(|p| (p, duration)).collect(),
+            Attribution::FirstOnly => vec![(projects[0].clone(), duration)],
+            Attribution::SplitEvenly => {
+                let weights = vec![1.0; projects.len()];
+                projects
+                    .into_iter()
+                    .zip(split(duration, &weights))
+                    .collect()
+            }
+            Attribution::Weighted => {
+                let weights = projects
+                    .iter()
+                    .map(|p| segment.weights.get(p).copied().unwrap_or(1.0).max(0.0) as f64)
+                    .collect_vec();
+                projects
+                    .into_iter()
+                    .zip(split(duration, &weights))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// split a duration according to the given weights, the parts always sum up to the duration
+fn split(duration: Duration, weights: &[f64]) -> Vec<Duration> {
+    let total_weight: f64 = weights.iter().sum();
+    let total = duration.whole_nanoseconds() as i64;
+
+    let mut parts = if total_weight > 0.0 {
+        weights
+            .iter()
+            .map(|w| (total as f64 * w / total_weight) as i64)
+            .collect_vec()
+    } else {
+        vec![total / weights.len() as i64; weights.len()]
+    };
+
+    // assign rounding remainders to the first part
+    let remainder = total - parts.iter().sum::<i64>();
+    parts[0] += remainder;
+
+    parts.into_iter().map(Duration::nanoseconds).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_sums_up() {
+        let duration = Duration::hours(1);
+        let parts = split(duration, &[1.0, 1.0, 1.0]);
+        assert_eq!(parts, vec![Duration::minutes(20); 3]);
+
+        let duration = Duration::seconds(10);
+        let parts = split(duration, &[1.0, 1.0, 1.0]);
+        assert_eq!(parts.iter().copied().sum::<Duration>(), duration);
+
+        let parts = split(duration, &[3.0, 1.0]);
+        assert_eq!(
+            parts,
+            vec![Duration::milliseconds(7500), Duration::milliseconds(2500)]
+        );
+
+        let parts = split(duration, &[0.0, 0.0]);
+        assert_eq!(parts, vec![Duration::seconds(5); 2]);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::data::local_time;
+use log::trace;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// folder of the backups, inside the day folder
+pub const BACKUP_FOLDER: &str = "backup";
+
+/// copy of a file taken before it was overwritten, stored as `<name>.<unix timestamp>`, further
+/// copies taken within the same second as `<name>.<unix timestamp>.<n>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// time the copy was taken, in seconds since the unix epoch
+    pub timestamp: i64,
+    /// number of earlier copies taken within the same second
+    pub collision: u32,
+}
+
+/// timestamp and collision counter of a backup file name without the `<name>.` prefix
+fn parse_suffix(suffix: &str) -> Option<(i64, u32)> {
+    match suffix.split_once('.') {
+        Some((timestamp, collision)) => Some((timestamp.parse().ok()?, collision.parse().ok()?)),
+        None => Some((suffix.parse().ok()?, 0)),
+    }
+}
+
+/// backups of the file named `name` in `folder`, the newest first
+pub fn list_backups(folder: &Path, name: &str) -> std::io::Result<Vec<Backup>> {
+    if !folder.exists() {
+        return Ok(vec![]);
+    }
+    let prefix = format!("{}.", name);
+    let mut backups = vec![];
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        let parsed = path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.strip_prefix(&prefix))
+            .and_then(parse_suffix);
+        if let Some((timestamp, collision)) = parsed {
+            backups.push(Backup {
+                path,
+                timestamp,
+                collision,
+            });
+        }
+    }
+    backups.sort_by_key(|backup| std::cmp::Reverse((backup.timestamp, backup.collision)));
+    Ok(backups)
+}
+
+/// copy the file at `path` into `folder` and delete all but the newest `keep` backups of it
+pub fn backup_file(path: &Path, folder: &Path, keep: usize) -> std::io::Result<Backup> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid file name")
+        })?;
+    std::fs::create_dir_all(folder)?;
+
+    // several saves within a second keep their own backups
+    let timestamp = local_time::now().unix_timestamp();
+    let mut collision = 0;
+    let backup = loop {
+        let file_name = match collision {
+            0 => format!("{}.{}", name, timestamp),
+            n => format!("{}.{}.{}", name, timestamp, n),
+        };
+        let backup = Backup {
+            path: folder.join(file_name),
+            timestamp,
+            collision,
+        };
+        // claim the name without replacing a backup taken concurrently
+        match File::create_new(&backup.path) {
+            Ok(_) => break backup,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => collision += 1,
+            Err(e) => return Err(e),
+        }
+    };
+    trace!("Backing up {} to {}", path.display(), backup.path.display());
+    std::fs::copy(path, &backup.path)?;
+
+    for old in list_backups(folder, name)?.into_iter().skip(keep) {
+        trace!("Deleting old backup {}", old.path.display());
+        std::fs::remove_file(&old.path)?;
+    }
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::job_config::JobConfig;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use time::{Date, Duration, Month, Time};
+
+    #[test]
+    fn test_rotation_keeps_the_newest() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = dir.path().join("2024-05-03.json");
+        let folder = dir.path().join(BACKUP_FOLDER);
+
+        for version in 0..5 {
+            clock.advance(Duration::minutes(1));
+            std::fs::write(&path, version.to_string()).unwrap();
+            backup_file(&path, &folder, 3).unwrap();
+        }
+        // a second backup within the same second
+        backup_file(&path, &folder, 3).unwrap();
+
+        let backups = list_backups(&folder, "2024-05-03.json").unwrap();
+        let contents = backups
+            .iter()
+            .map(|backup| std::fs::read_to_string(&backup.path).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["4", "4", "3"]);
+        assert_eq!(backups[0].timestamp, backups[1].timestamp);
+        assert_eq!(backups[0].timestamp, local_time::now().unix_timestamp());
+        assert_eq!((backups[0].collision, backups[1].collision), (1, 0));
+        assert!(
+            backups[0]
+                .path
+                .to_string_lossy()
+                .ends_with(&format!(".{}.1", backups[0].timestamp))
+        );
+        assert_eq!(std::fs::read_dir(&folder).unwrap().count(), 3);
+        assert!(list_backups(&folder, "2024-05-04.json").unwrap().is_empty());
+    }
+}
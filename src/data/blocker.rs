@@ -1,8 +1,16 @@
 use crate::az_hash::AZHash;
+use crate::data::BASIC_TIME_FORMAT;
+use crate::data::activity::{Activity, SHORT_ID_LENGTH};
 use crate::data::identifier::Identifier;
 use crate::data::interval::Interval;
+use crate::data::local_time;
 use digest::Digest;
+use log::error;
 use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use time::{Duration, Time};
 use uuid::Uuid;
 
 /// Blocker
@@ -23,8 +31,136 @@ pub struct Blocker {
     pub projects: Vec<Identifier>,
 }
 
+impl Display for Blocker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} - {}: {}",
+            self.time
+                .start
+                .format(&*BASIC_TIME_FORMAT)
+                .unwrap_or_else(|e| {
+                    error!("Unable to format time: {e}. Report this as an issue.");
+                    "<INVALID>".to_string()
+                }),
+            self.time
+                .end
+                .map(|t| t.format(&*BASIC_TIME_FORMAT).unwrap_or_else(|e| {
+                    error!("Unable to format time: {e}. Report this as an issue.");
+                    "<INVALID>".to_string()
+                }))
+                .unwrap_or_else(|| "<OPEN>".to_string()),
+            self.name
+                .clone()
+                .unwrap_or_else(|| "<NO DESCRIPTION>".to_string())
+        )
+    }
+}
+
+impl Blocker {
+    /// sum up the blocked time per class
+    ///
+    /// open blockers count until `end_limit` (usually now), without a limit they do not contribute
+    pub fn summarize_by_class<Q: Borrow<Blocker>>(
+        blockers: &[Q],
+        end_limit: Option<Time>,
+        boundary: Option<Time>,
+    ) -> BTreeMap<Identifier, Duration> {
+        let mut totals = BTreeMap::new();
+        for blocker in blockers {
+            let blocker = blocker.borrow();
+            let time = blocker.time.to_day_time(boundary);
+            let duration = match (time.end, end_limit) {
+                (Some(_), _) => time.duration().unwrap_or_default(),
+                (None, Some(end_limit)) => {
+                    time.elapsed_at(local_time::to_day_time(end_limit, boundary))
+                }
+                (None, None) => continue,
+            };
+            *totals
+                .entry(blocker.class.clone())
+                .or_insert(Duration::ZERO) += duration.max(Duration::ZERO);
+        }
+        totals
+    }
+}
+
+impl Blocker {
+    /// short id to tell blockers apart on the command line, see [`Activity::short_id`]
+    ///
+    /// [`Activity::short_id`]: crate::data::activity::Activity::short_id
+    pub fn short_id(&self) -> String {
+        self.az_hash_sha256()
+            .chars()
+            .take(SHORT_ID_LENGTH)
+            .collect()
+    }
+
+    /// the blocker as an activity of its class, e.g. to fold it into a closure
+    pub fn to_activity(&self) -> Activity {
+        Activity {
+            id: self.id,
+            name: self.name.clone(),
+            class: self.class.clone(),
+            time: self.time.clone(),
+            projects: self.projects.clone(),
+            weights: BTreeMap::new(),
+            description: None,
+            created_at: None,
+            modified_at: None,
+            tags: vec![],
+            evidence: vec![],
+            origin: None,
+            extra: Default::default(),
+        }
+    }
+}
+
 impl AZHash for Blocker {
     fn az_hash<D: Digest>(&self) -> String {
         self.id.az_hash::<D>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocker(class: &str, start: u8, end: Option<u8>) -> Blocker {
+        Blocker {
+            id: Uuid::new_v4(),
+            name: None,
+            class: Identifier::ByName(class.into()),
+            time: Interval {
+                start: Time::from_hms(start, 0, 0).unwrap(),
+                end: end.map(|end| Time::from_hms(end, 0, 0).unwrap()),
+            },
+            projects: vec![],
+        }
+    }
+
+    #[test]
+    fn test_summarize_clamps_open_blockers() {
+        let blockers = [
+            blocker("excused", 8, Some(9)),
+            blocker("excused", 10, None),
+            blocker("holiday", 13, None),
+        ];
+
+        let totals =
+            Blocker::summarize_by_class(&blockers, Some(Time::from_hms(12, 0, 0).unwrap()), None);
+        assert_eq!(
+            totals,
+            BTreeMap::from([
+                (Identifier::ByName("excused".into()), Duration::hours(3)),
+                (Identifier::ByName("holiday".into()), Duration::ZERO),
+            ])
+        );
+
+        let totals = Blocker::summarize_by_class(&blockers, None, None);
+        assert_eq!(
+            totals,
+            BTreeMap::from([(Identifier::ByName("excused".into()), Duration::hours(1))])
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::data::activity::Activity;
+use crate::data::job_config::JobConfig;
+use crate::data::safe_duration::SafeDuration;
+use serde::{Deserialize, Serialize};
+use time::Duration;
+
+/// break required once the work of a day exceeds `work_over`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakRule {
+    #[serde(with = "crate::serde::pretty_duration")]
+    pub work_over: Duration,
+    #[serde(with = "crate::serde::pretty_duration")]
+    pub break_required: Duration,
+}
+
+/// the German working time act: 30m of break for more than 6h of work, 45m for more than 9h
+pub fn default_break_rules() -> Vec<BreakRule> {
+    vec![
+        BreakRule {
+            work_over: Duration::hours(6),
+            break_required: Duration::minutes(30),
+        },
+        BreakRule {
+            work_over: Duration::hours(9),
+            break_required: Duration::minutes(45),
+        },
+    ]
+}
+
+/// break required for `work`, the longest of the rules exceeded
+pub fn required_break(rules: &[BreakRule], work: Duration) -> Duration {
+    rules
+        .iter()
+        .filter(|rule| work > rule.work_over)
+        .map(|rule| rule.break_required)
+        .max()
+        .unwrap_or(Duration::ZERO)
+}
+
+/// work and break time of a day, see [`JobConfig::break_rules`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakCompliance {
+    /// time of the classes counting towards the work quota
+    pub work: Duration,
+    /// time of the break classes
+    pub breaks: Duration,
+    /// break required for `work`
+    pub required: Duration,
+}
+
+impl BreakCompliance {
+    /// compliance of the closure of a day
+    pub fn of(job_config: &JobConfig, closure: &[Activity]) -> Self {
+        let sum = |counts: &dyn Fn(&Activity) -> bool| {
+            closure
+                .iter()
+                .filter(|segment| counts(segment))
+                .map(|segment| segment.time.duration().unwrap_or_default())
+                .sum::<SafeDuration>()
+                .total()
+        };
+        let work = sum(&|segment| job_config.counts_towards_quota(&segment.class));
+        let breaks = sum(&|segment| job_config.is_break(&segment.class));
+        Self {
+            work,
+            breaks,
+            required: required_break(&job_config.break_rules, work),
+        }
+    }
+
+    /// break time missing to comply with the rules
+    pub fn missing(&self) -> Duration {
+        (self.required - self.breaks).max(Duration::ZERO)
+    }
+
+    pub fn is_compliant(&self) -> bool {
+        self.missing() == Duration::ZERO
+    }
+
+    /// the next rule violated if work continues without a break, with the work time left until
+    /// it is. `None` while the day is already in violation
+    pub fn upcoming(&self, rules: &[BreakRule]) -> Option<(Duration, BreakRule)> {
+        if !self.is_compliant() {
+            return None;
+        }
+        rules
+            .iter()
+            .filter(|rule| rule.work_over >= self.work && rule.break_required > self.breaks)
+            .min_by_key(|rule| rule.work_over)
+            .map(|rule| (rule.work_over - self.work, *rule))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    use time::Time;
+
+    fn segment(class: &str, start: (u8, u8), end: (u8, u8)) -> Activity {
+        let time = |(h, m): (u8, u8)| Time::from_hms(h, m, 0).unwrap();
+        testing::activity(class, time(start), Some(time(end)))
+    }
+
+    fn compliance(closure: &[Activity]) -> BreakCompliance {
+        BreakCompliance::of(&JobConfig::default(), closure)
+    }
+
+    #[test]
+    fn test_required_break() {
+        let rules = default_break_rules();
+        let required =
+            |hours: i64, minutes| required_break(&rules, Duration::minutes(hours * 60 + minutes));
+        assert_eq!(required(5, 0), Duration::ZERO);
+        // exceeding the threshold, not reaching it, requires the break
+        assert_eq!(required(6, 0), Duration::ZERO);
+        assert_eq!(required(6, 1), Duration::minutes(30));
+        assert_eq!(required(9, 0), Duration::minutes(30));
+        assert_eq!(required(9, 1), Duration::minutes(45));
+        assert_eq!(required_break(&[], Duration::hours(12)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_compliant_day() {
+        let day = compliance(&[
+            segment("work", (8, 0), (12, 0)),
+            segment("break", (12, 0), (12, 30)),
+            segment("work", (12, 30), (17, 0)),
+        ]);
+        assert_eq!(day.work, Duration::minutes(8 * 60 + 30));
+        assert_eq!(day.breaks, Duration::minutes(30));
+        assert!(day.is_compliant());
+        // 30m of work left until the 9h rule applies
+        let (left, rule) = day.upcoming(&default_break_rules()).unwrap();
+        assert_eq!(left, Duration::minutes(30));
+        assert_eq!(rule.break_required, Duration::minutes(45));
+    }
+
+    #[test]
+    fn test_violating_day() {
+        let day = compliance(&[
+            segment("work", (8, 0), (12, 0)),
+            segment("break", (12, 0), (12, 15)),
+            segment("work", (12, 15), (16, 25)),
+        ]);
+        assert_eq!(day.work, Duration::minutes(8 * 60 + 10));
+        assert_eq!(day.required, Duration::minutes(30));
+        assert_eq!(day.missing(), Duration::minutes(15));
+        assert!(day.upcoming(&default_break_rules()).is_none());
+    }
+
+    #[test]
+    fn test_boundary_exact_days() {
+        let day = compliance(&[segment("work", (8, 0), (14, 0))]);
+        assert!(day.is_compliant());
+        assert_eq!(
+            day.upcoming(&default_break_rules()).unwrap().0,
+            Duration::ZERO
+        );
+
+        let day = compliance(&[
+            segment("work", (7, 0), (12, 0)),
+            segment("break", (12, 0), (12, 30)),
+            segment("work", (12, 30), (16, 30)),
+        ]);
+        assert_eq!(day.work, Duration::hours(9));
+        assert!(day.is_compliant());
+
+        let day = compliance(&[segment("work", (8, 0), (14, 1))]);
+        assert_eq!(day.missing(), Duration::minutes(30));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::data::activity::Activity;
+use crate::data::day::DayInner;
+use itertools::Itertools;
+use time::{Duration, OffsetDateTime, Time};
+use uuid::Uuid;
+
+/// fragments of the same activity which are merged into a single activity
+#[derive(Debug, Clone)]
+pub struct Merge {
+    /// merged activities in chronological order
+    pub fragments: Vec<Activity>,
+    /// activity replacing the fragments
+    pub merged: Activity,
+}
+
+/// fragments belong to the same activity if they only differ in time and bookkeeping
+pub fn same_activity(a: &Activity, b: &Activity) -> bool {
+    a.class == b.class
+        && a.name == b.name
+        && a.projects == b.projects
+        && a.weights == b.weights
+        && a.tags == b.tags
+}
+
+/// plan the merges of completed activities sharing class, name, projects and tags
+/// which follow each other with a gap of at most `tolerance`
+///
+/// fragments are only merged if no other activity of the day lies between them,
+/// so the activity closure of the day keeps its totals
+pub fn plan(day: &DayInner, tolerance: Duration) -> Vec<Merge> {
+    let completed = day
+        .activities
+        .iter()
+        .filter(|activity| activity.time.is_complete() && !activity.time.is_inverted())
+        .sorted_by_key(|activity| activity.time.start)
+        .collect_vec();
+
+    let others_overlap = |group: &[&Activity], candidate: &Activity, from: Time, to: Time| {
+        day.activities.iter().any(|other| {
+            other.id != candidate.id
+                && group.iter().all(|fragment| fragment.id != other.id)
+                && other.time.start < to
+                && other.time.end_time_or_end_of_day() > from
+        })
+    };
+
+    let mut merges = Vec::new();
+    let mut group: Vec<&Activity> = Vec::new();
+    for activity in completed {
+        let extends = match (group.first(), group.last()) {
+            (Some(first), Some(last)) => {
+                let last_end = last.time.end_time_or_end_of_day();
+                same_activity(last, activity)
+                    && activity.time.start >= last_end
+                    && activity.time.start - last_end <= tolerance
+                    && !others_overlap(
+                        &group,
+                        activity,
+                        first.time.start,
+                        activity.time.end_time_or_end_of_day(),
+                    )
+            }
+            _ => false,
+        };
+
+        if !extends {
+            merges.extend(merge(&group));
+            group.clear();
+        }
+        group.push(activity);
+    }
+    merges.extend(merge(&group));
+
+    merges
+}
+
+/// single activity covering the total duration of the fragments, starting with the first one
+fn merge(fragments: &[&Activity]) -> Option<Merge> {
+    let [first, ..] = fragments else {
+        return None;
+    };
+    if fragments.len() < 2 {
+        return None;
+    }
+
+    let duration: Duration = fragments
+        .iter()
+        .map(|fragment| fragment.time.duration().unwrap_or_default())
+        .sum();
+
+    let mut merged = (*first).clone();
+    merged.time.end = Some(first.time.start + duration);
+    merged.created_at = fragments.iter().filter_map(|f| f.created_at).min();
+    merged.modified_at = fragments.iter().filter_map(|f| f.modified_at).max();
+    let descriptions = fragments
+        .iter()
+        .filter_map(|fragment| fragment.description.as_deref())
+        .unique()
+        .join("\n");
+    merged.description = (!descriptions.is_empty()).then_some(descriptions);
+    for fragment in &fragments[1..] {
+        for (key, value) in &fragment.extra {
+            merged
+                .extra
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    Some(Merge {
+        fragments: fragments.iter().map(|f| (*f).clone()).collect(),
+        merged,
+    })
+}
+
+/// replace the fragments of each merge by the merged activity, the fragments go to the trash
+pub fn apply(day: &mut DayInner, merges: &[Merge], now: OffsetDateTime) {
+    for merge in merges {
+        let ids: Vec<Uuid> = merge.fragments.iter().map(|f| f.id).collect();
+        let Some(position) = day.activities.iter().position(|a| ids.contains(&a.id)) else {
+            continue;
+        };
+        for id in ids {
+            day.delete_activity(id, now);
+        }
+        day.activities.insert(position, merge.merged.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::identifier::Identifier;
+    use crate::data::job_config::JobConfig;
+    use crate::testing;
+    use std::collections::BTreeMap;
+    use time::OffsetDateTime;
+
+    fn time(h: u8, m: u8, s: u8) -> Time {
+        Time::from_hms(h, m, s).unwrap()
+    }
+
+    fn activity(class: &str, name: &str, start: Time, end: Option<Time>) -> Activity {
+        Activity {
+            name: Some(name.into()),
+            projects: vec![Identifier::ByName("projA".into())],
+            ..testing::activity(class, start, end)
+        }
+    }
+
+    fn class_totals(job_config: &JobConfig, day: &DayInner) -> BTreeMap<Identifier, Duration> {
+        let (closure, _) = Activity::calculate_activity_closure(
+            job_config,
+            &day.activities,
+            None,
+            Some(time(23, 0, 0)),
+        );
+        Activity::summarize_by_class(&closure)
+    }
+
+    fn fragmented_day() -> DayInner {
+        let mut first = activity("work", "coding", time(9, 0, 0), Some(time(9, 20, 0)));
+        first.created_at = Some(OffsetDateTime::UNIX_EPOCH + Duration::hours(2));
+        let mut second = activity("work", "coding", time(9, 20, 30), Some(time(9, 40, 0)));
+        second.created_at = Some(OffsetDateTime::UNIX_EPOCH + Duration::hours(1));
+
+        DayInner {
+            activities: vec![
+                first,
+                second,
+                activity("work", "coding", time(9, 40, 0), Some(time(10, 0, 0))),
+                // other name, not merged
+                activity("work", "review", time(10, 0, 0), Some(time(10, 30, 0))),
+                // break in between, not merged with the fragment before it
+                activity("work", "coding", time(10, 30, 0), Some(time(11, 0, 0))),
+                activity("break", "lunch", time(11, 0, 0), Some(time(11, 30, 0))),
+                activity("work", "coding", time(11, 30, 0), Some(time(12, 0, 0))),
+                // gap too large
+                activity("work", "coding", time(12, 5, 0), Some(time(12, 30, 0))),
+                activity("work", "coding", time(13, 0, 0), None),
+            ],
+            ..DayInner::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_merges_adjacent_fragments() {
+        let day = fragmented_day();
+        let merges = plan(&day, Duration::minutes(1));
+
+        assert_eq!(merges.len(), 1);
+        let merge = &merges[0];
+        assert_eq!(merge.fragments.len(), 3);
+        assert_eq!(merge.merged.id, day.activities[0].id);
+        assert_eq!(merge.merged.time.start, time(9, 0, 0));
+        // the gap of 30 seconds is not added to the activity
+        assert_eq!(merge.merged.time.end, Some(time(9, 59, 30)));
+        assert_eq!(
+            merge.merged.created_at,
+            Some(OffsetDateTime::UNIX_EPOCH + Duration::hours(1))
+        );
+    }
+
+    #[test]
+    fn test_compaction_keeps_class_totals() {
+        let job_config = JobConfig::default();
+        let mut day = fragmented_day();
+        let before = class_totals(&job_config, &day);
+
+        let merges = plan(&day, Duration::minutes(10));
+        assert_eq!(merges.len(), 2);
+        apply(&mut day, &merges, OffsetDateTime::UNIX_EPOCH);
+
+        assert_eq!(day.activities.len(), 6);
+        assert_eq!(class_totals(&job_config, &day), before);
+        assert!(plan(&day, Duration::minutes(10)).is_empty());
+    }
+}
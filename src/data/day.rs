@@ -1,9 +1,19 @@
 use crate::az_hash::AZHash;
 use crate::data::activity::Activity;
+use crate::data::activity_closure::ClosureDiagnostics;
 use crate::data::blocker::Blocker;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::{JobConfig, unique_name};
 use crate::data::quota::Quota;
+use crate::data::reference::{Reference, ReferenceError};
+use crate::data::trash::TrashEntry;
 use digest::Digest;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use time::{Duration, OffsetDateTime, Time};
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 /// data structure for a single day
@@ -26,6 +36,247 @@ pub struct DayInner {
     /// quotas
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub quotas: Vec<Quota>,
+    /// locked days must not be modified
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub locked: bool,
+    /// work quota of the day, overrides the default of the app config
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
+    pub work_quota: Option<Duration>,
+    /// deleted activities, restorable until they are purged
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub trash: Vec<TrashEntry>,
+    /// kind of the day, e.g. set by `holiday`
+    #[serde(skip_serializing_if = "DayType::is_work", default)]
+    pub day_type: DayType,
+    /// unknown fields, kept so that data of other tools survives a round-trip
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// kind of a day, regular days are work days
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DayType {
+    #[default]
+    Work,
+    /// vacation or public holiday, marked with `timetrax holiday`
+    Holiday,
+}
+
+impl DayType {
+    pub fn is_work(&self) -> bool {
+        *self == DayType::Work
+    }
+}
+
+/// reasons an activity could not be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityLookupError {
+    /// no activity matches the identifier
+    NotFound(Identifier),
+    /// multiple activities share the name, a UUID is required to address one of them
+    Ambiguous(String, Vec<Uuid>),
+    /// no activity matches the UUID prefix or short id
+    ReferenceNotFound(String),
+    /// multiple activities match the UUID prefix or short id
+    AmbiguousReference(String, Vec<Uuid>),
+}
+
+impl Display for ActivityLookupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivityLookupError::NotFound(identifier) => {
+                write!(f, "Activity {} not found", identifier)
+            }
+            ActivityLookupError::Ambiguous(name, ids) => write!(
+                f,
+                "Activity name '{}' is ambiguous, use one of the ids: {}",
+                name,
+                ids.iter().join(", ")
+            ),
+            ActivityLookupError::ReferenceNotFound(reference) => {
+                write!(f, "No activity matches the reference '{}'", reference)
+            }
+            ActivityLookupError::AmbiguousReference(reference, ids) => write!(
+                f,
+                "Activity reference '{}' matches several activities, use one of the ids: {}",
+                reference,
+                ids.iter().join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ActivityLookupError {}
+
+impl From<ActivityLookupError> for std::io::Error {
+    fn from(e: ActivityLookupError) -> Self {
+        let kind = match e {
+            ActivityLookupError::NotFound(_) | ActivityLookupError::ReferenceNotFound(_) => {
+                std::io::ErrorKind::NotFound
+            }
+            ActivityLookupError::Ambiguous(..) | ActivityLookupError::AmbiguousReference(..) => {
+                std::io::ErrorKind::InvalidInput
+            }
+        };
+        std::io::Error::new(kind, e)
+    }
+}
+
+impl DayInner {
+    /// resolve an activity by UUID or by name
+    ///
+    /// names must match exactly one activity of the day, otherwise the lookup is ambiguous
+    pub fn find_activity(&self, identifier: &Identifier) -> Result<&Activity, ActivityLookupError> {
+        self.find_activity_index(identifier)
+            .map(|index| &self.activities[index])
+    }
+
+    pub fn find_activity_mut(
+        &mut self,
+        identifier: &Identifier,
+    ) -> Result<&mut Activity, ActivityLookupError> {
+        self.find_activity_index(identifier)
+            .map(|index| &mut self.activities[index])
+    }
+
+    /// resolve an activity by `@name`, UUID, UUID prefix, short id (see [`Activity::short_id`])
+    /// or name, see [`Reference`]
+    pub fn find_activity_by_reference(
+        &self,
+        reference: &Reference,
+    ) -> Result<&Activity, ActivityLookupError> {
+        reference
+            .resolve(&self.activities)
+            .map_err(|e| match (e, reference) {
+                (ReferenceError::NotFound, Reference::Id(id)) => {
+                    ActivityLookupError::NotFound(Identifier::Uuid(*id))
+                }
+                (ReferenceError::NotFound, Reference::Name(name)) => {
+                    ActivityLookupError::NotFound(Identifier::ByName(name.clone()))
+                }
+                (ReferenceError::NotFound, Reference::Prefix(_)) => {
+                    ActivityLookupError::ReferenceNotFound(reference.to_string())
+                }
+                (ReferenceError::Ambiguous(ids), Reference::Name(name)) => {
+                    ActivityLookupError::Ambiguous(name.clone(), ids)
+                }
+                (ReferenceError::Ambiguous(ids), _) => {
+                    ActivityLookupError::AmbiguousReference(reference.to_string(), ids)
+                }
+            })
+    }
+
+    fn find_activity_index(&self, identifier: &Identifier) -> Result<usize, ActivityLookupError> {
+        let matches = self
+            .activities
+            .iter()
+            .positions(|activity| match identifier {
+                Identifier::Uuid(id) => activity.id == *id,
+                Identifier::ByName(name) => activity.name.as_ref() == Some(name),
+            })
+            .collect_vec();
+
+        match (matches.as_slice(), identifier) {
+            ([], _) => Err(ActivityLookupError::NotFound(identifier.clone())),
+            ([index], _) => Ok(*index),
+            (_, Identifier::ByName(name)) => Err(ActivityLookupError::Ambiguous(
+                name.clone(),
+                matches.iter().map(|i| self.activities[*i].id).collect(),
+            )),
+            // duplicated ids are a data error, keep using the first one
+            ([index, ..], Identifier::Uuid(_)) => Ok(*index),
+        }
+    }
+
+    /// unknown fields of the day and its activities, e.g. `mood` or `activity <id>: mood`
+    pub fn unknown_fields(&self) -> Vec<String> {
+        let day = self.extra.keys().cloned();
+        let activities = self.activities.iter().flat_map(|activity| {
+            activity
+                .extra
+                .keys()
+                .map(move |key| format!("activity {}: {}", activity.id, key))
+        });
+        day.chain(activities).collect()
+    }
+
+    /// name not used by any other activity of the day, suffixed like `meeting (2)` if taken
+    pub fn unique_activity_name(&self, name: &str) -> String {
+        unique_name(
+            name,
+            self.activities
+                .iter()
+                .filter_map(|activity| activity.name.as_deref()),
+        )
+    }
+
+    pub fn find_blocker(&self, id: Uuid) -> Option<&Blocker> {
+        self.blockers.iter().find(|blocker| blocker.id == id)
+    }
+
+    pub fn find_blocker_mut(&mut self, id: Uuid) -> Option<&mut Blocker> {
+        self.blockers.iter_mut().find(|blocker| blocker.id == id)
+    }
+
+    /// activities of the day followed by its blockers as activities of their class
+    pub(crate) fn timeline_activities(&self) -> Vec<Activity> {
+        self.activities
+            .iter()
+            .cloned()
+            .chain(self.blockers.iter().map(Blocker::to_activity))
+            .collect_vec()
+    }
+
+    /// closure of the activities and the blockers of the day, clamped to `end` if given
+    ///
+    /// blockers are folded like activities of their class, time covered by a blocker
+    /// and an activity of the same class counts once. open blockers are clamped
+    /// like open activities, without `end` they do not count
+    pub fn effective_timeline(
+        &self,
+        job_config: &JobConfig,
+        end: Option<Time>,
+    ) -> (Vec<Activity>, ClosureDiagnostics) {
+        Activity::calculate_activity_closure(job_config, &self.timeline_activities(), None, end)
+    }
+
+    /// untracked time between the activities and blockers of the day, in day time,
+    /// see [`Activity::calculate_gaps`]
+    pub fn gaps(&self, job_config: &JobConfig, min_gap: Duration) -> Vec<Interval> {
+        Activity::calculate_gaps(job_config, &self.timeline_activities(), None, None, min_gap)
+    }
+
+    /// move the activity to the trash, every deletion of an activity goes through here.
+    /// returns the deleted activity, `None` if the day has no activity with the id
+    pub fn delete_activity(&mut self, id: Uuid, now: OffsetDateTime) -> Option<Activity> {
+        let position = self.activities.iter().position(|a| a.id == id)?;
+        let activity = self.activities.remove(position);
+        self.trash.push(TrashEntry {
+            deleted_at: now,
+            activity: activity.clone(),
+        });
+        Some(activity)
+    }
+
+    /// move the activity back from the trash, `None` if the trash has no activity with the id
+    pub fn restore_activity(&mut self, id: Uuid) -> Option<Activity> {
+        let position = self.trash.iter().position(|e| e.activity.id == id)?;
+        let activity = self.trash.remove(position).activity;
+        self.activities.push(activity.clone());
+        Some(activity)
+    }
+
+    /// drop the trash entries deleted before `cutoff`, returns how many were dropped
+    pub fn purge_trash(&mut self, cutoff: OffsetDateTime) -> usize {
+        let before = self.trash.len();
+        self.trash.retain(|entry| !entry.expired(cutoff));
+        before - self.trash.len()
+    }
 }
 
 impl AZHash for Day {
@@ -43,3 +294,236 @@ impl Day {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::app_config::AppConfig;
+    use crate::data::job_config::JobConfig;
+    use crate::data::manager::Manager;
+    use crate::testing::{self, TempDataDir};
+    use std::path::PathBuf;
+    use time::{Date, Month, Time};
+
+    fn day(names: &[Option<&str>]) -> DayInner {
+        DayInner {
+            activities: names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| Activity {
+                    id: Uuid::from_u128(i as u128 + 1),
+                    name: name.map(String::from),
+                    ..testing::activity("work", Time::from_hms(9 + i as u8, 0, 0).unwrap(), None)
+                })
+                .collect(),
+            ..DayInner::default()
+        }
+    }
+
+    #[test]
+    fn test_find_activity_by_reference() {
+        let day = day(&[Some("meeting"), Some("coding"), Some("coding")]);
+        let first = &day.activities[0];
+
+        let find = |reference: &str| day.find_activity_by_reference(&reference.parse().unwrap());
+        for reference in [
+            "@meeting".to_string(),
+            "meeting".to_string(),
+            first.id.to_string(),
+            first.id.simple().to_string()[..32].to_string(),
+            first.short_id(),
+            first.short_id()[..4].to_uppercase(),
+        ] {
+            assert_eq!(find(&reference).unwrap().id, first.id, "{reference}");
+        }
+
+        assert_eq!(
+            find("00000000-0000").unwrap_err(),
+            ActivityLookupError::AmbiguousReference(
+                "00000000-0000".into(),
+                vec![Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3)]
+            )
+        );
+        assert_eq!(
+            find("@coding").unwrap_err(),
+            ActivityLookupError::Ambiguous(
+                "coding".into(),
+                vec![Uuid::from_u128(2), Uuid::from_u128(3)]
+            )
+        );
+        for reference in ["-", "00000000-1", "Meeting"] {
+            assert_eq!(
+                find(reference).unwrap_err(),
+                ActivityLookupError::ReferenceNotFound(reference.into())
+            );
+        }
+        assert_eq!(
+            find(&Uuid::from_u128(4).to_string()).unwrap_err(),
+            ActivityLookupError::NotFound(Identifier::Uuid(Uuid::from_u128(4)))
+        );
+    }
+
+    #[test]
+    fn test_find_activity_unique() {
+        let day = day(&[Some("meeting"), Some("coding"), None]);
+
+        let by_name = day.find_activity(&Identifier::ByName("coding".into()));
+        assert_eq!(by_name.unwrap().id, Uuid::from_u128(2));
+        let by_id = day.find_activity(&Identifier::Uuid(Uuid::from_u128(3)));
+        assert_eq!(by_id.unwrap().id, Uuid::from_u128(3));
+        assert_eq!(
+            day.find_activity(&Identifier::ByName("review".into()))
+                .unwrap_err(),
+            ActivityLookupError::NotFound(Identifier::ByName("review".into()))
+        );
+    }
+
+    #[test]
+    fn test_find_activity_ambiguous() {
+        let day = day(&[Some("meeting"), Some("coding"), Some("meeting")]);
+
+        assert_eq!(
+            day.find_activity(&Identifier::ByName("meeting".into()))
+                .unwrap_err(),
+            ActivityLookupError::Ambiguous(
+                "meeting".into(),
+                vec![Uuid::from_u128(1), Uuid::from_u128(3)]
+            )
+        );
+        // the UUID still addresses a single activity
+        let by_id = day.find_activity(&Identifier::Uuid(Uuid::from_u128(3)));
+        assert_eq!(by_id.unwrap().id, Uuid::from_u128(3));
+    }
+
+    #[test]
+    fn test_unique_activity_name() {
+        let day = day(&[Some("meeting"), Some("meeting (2)"), None]);
+
+        assert_eq!(day.unique_activity_name("coding"), "coding");
+        assert_eq!(day.unique_activity_name("meeting"), "meeting (3)");
+    }
+
+    const DAY_WITH_EXTRAS: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/day_with_extras.json"
+    ));
+
+    fn write_fixture(dir: &TempDataDir) -> PathBuf {
+        dir.write_day_file("2024-05-03.json", DAY_WITH_EXTRAS)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_unknown_fields_survive_round_trip() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = write_fixture(&dir);
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+
+        {
+            let mut manager = dir.manager().unwrap();
+            let day = manager.annotated_day_mut(date).unwrap();
+            assert_eq!(
+                day.inner().unknown_fields(),
+                [
+                    "mood",
+                    "synced_by",
+                    "activity 6f1c1c8e-2f4b-4a8e-9a57-2f0d7c1e2b11: billing",
+                    "activity 6f1c1c8e-2f4b-4a8e-9a57-2f0d7c1e2b11: jira_ticket",
+                ]
+            );
+            day.inner_mut().activities[0].name = Some("renamed".into());
+            manager.save().unwrap();
+        }
+
+        let original: serde_json::Value = serde_json::from_str(DAY_WITH_EXTRAS).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(saved["activities"][0]["name"], "renamed");
+        for key in ["mood", "synced_by"] {
+            assert_eq!(saved[key], original[key]);
+        }
+        for key in ["jira_ticket", "billing"] {
+            assert_eq!(saved["activities"][0][key], original["activities"][0][key]);
+        }
+    }
+
+    #[test]
+    fn test_work_quota_round_trip() {
+        let day: Day = serde_json::from_str(
+            r#"{"date": "2024-06-07", "work_quota": "06h 00m 00s", "activities": []}"#,
+        )
+        .unwrap();
+        assert_eq!(day.inner.work_quota, Some(Duration::hours(6)));
+        assert!(day.inner.unknown_fields().is_empty());
+
+        let json = serde_json::to_value(&day).unwrap();
+        assert_eq!(json["work_quota"], "06h 00m 00s");
+        let parsed: Day = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.inner.work_quota, Some(Duration::hours(6)));
+
+        // days without an override do not store the field
+        let json = serde_json::to_value(Day::new(day.date)).unwrap();
+        assert!(json.get("work_quota").is_none());
+        assert!(
+            serde_json::from_str::<Day>(r#"{"date": "2024-06-07", "work_quota": "6 days"}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_strict_parsing_rejects_unknown_fields() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        write_fixture(&dir);
+
+        let strict = AppConfig {
+            strict_parsing: true,
+            ..dir.app_config().clone()
+        };
+        let result = Manager::open(&strict, dir.path());
+
+        assert_eq!(
+            result.err().unwrap().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_effective_timeline_includes_blockers() {
+        let job_config = JobConfig::default();
+        let day: DayInner = serde_json::from_str(
+            r#"{"activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+                 "time": {"start": "09:00:00", "end": "12:00:00"}}
+            ], "blockers": [
+                {"id": "00000000-0000-4000-8000-000000000002", "class": "@work",
+                 "name": "overlapping", "time": {"start": "11:00:00", "end": "13:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000003", "class": "@work",
+                 "name": "doctor", "time": {"start": "14:00:00", "end": "15:30:00"}},
+                {"id": "00000000-0000-4000-8000-000000000004", "class": "@work",
+                 "time": {"start": "16:00:00"}}
+            ]}"#,
+        )
+        .unwrap();
+        let total = |closure: &[Activity]| Activity::sum_durations(closure).total();
+
+        // 9-13 counts once although activity and blocker overlap, 14-15:30 adds up,
+        // the open blocker does not count without an end
+        let (closure, diagnostics) = day.effective_timeline(&job_config, None);
+        assert!(diagnostics.is_empty());
+        assert_eq!(total(&closure), time::Duration::minutes(5 * 60 + 30));
+        assert!(
+            closure
+                .iter()
+                .any(|segment| segment.name.as_deref() == Some("doctor"))
+        );
+
+        let (closure, _) =
+            day.effective_timeline(&job_config, Some(Time::from_hms(17, 0, 0).unwrap()));
+        assert_eq!(total(&closure), time::Duration::minutes(6 * 60 + 30));
+
+        let (activities_only, _) =
+            Activity::calculate_activity_closure(&job_config, &day.activities, None, None);
+        assert_eq!(total(&activities_only), time::Duration::hours(3));
+    }
+}
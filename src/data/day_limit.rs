@@ -0,0 +1,237 @@
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use itertools::Itertools;
+use log::error;
+use std::fmt::{Display, Formatter};
+use time::{Date, Duration, Time};
+
+/// time tracked on a day, open activities are clamped to the end of the day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayTotals {
+    /// time covered by any activity, the total of the closure. nested and overlapping
+    /// activities count once
+    pub covered: Duration,
+}
+
+/// interval of the activity in day time, ending at the end of the day if open
+fn clamped(activity: &Activity, boundary: Option<Time>) -> (Time, Time) {
+    let time: Interval = activity.time.to_day_time(boundary);
+    let end = time.end_time_or_end_of_day().max(time.start);
+    (time.start, end)
+}
+
+/// length of the interval, `Time::MAX` counts as midnight of the next day
+fn length(start: Time, end: Time) -> Duration {
+    if end == Time::MAX {
+        Duration::DAY - (start - Time::MIDNIGHT)
+    } else {
+        end - start
+    }
+}
+
+/// time covered by any of the intervals
+fn union<'a>(intervals: impl Iterator<Item = &'a (Time, Time)>) -> Duration {
+    let mut covered = Duration::ZERO;
+    let mut current: Option<(Time, Time)> = None;
+    for &(start, end) in intervals.sorted() {
+        current = match current {
+            Some((from, to)) if start <= to => Some((from, to.max(end))),
+            Some((from, to)) => {
+                covered += length(from, to);
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((from, to)) = current {
+        covered += length(from, to);
+    }
+    covered
+}
+
+impl DayTotals {
+    pub fn of(day: &DayInner, boundary: Option<Time>) -> Self {
+        let intervals = day
+            .activities
+            .iter()
+            .map(|activity| clamped(activity, boundary))
+            .collect_vec();
+        Self {
+            covered: union(intervals.iter()),
+        }
+    }
+}
+
+/// e.g. `25h 0m`
+fn format_hours(duration: Duration) -> String {
+    format!(
+        "{}h {}m",
+        duration.whole_hours(),
+        duration.whole_minutes() % 60
+    )
+}
+
+/// confidence check of days before they are saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayLimit {
+    pub boundary: Option<Time>,
+    /// maximum time covered by the activities of a day, at most a day
+    pub cap: Duration,
+    /// save days failing the check with a warning
+    pub force: bool,
+}
+
+/// a day covers more time than the cap
+#[derive(Debug, Clone)]
+pub struct DayLimitError {
+    pub date: Date,
+    pub totals: DayTotals,
+    pub cap: Duration,
+    pub activities: Vec<Activity>,
+}
+
+impl Display for DayLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The activities of {} cover {}, more than the maximum of {}",
+            self.date,
+            format_hours(self.totals.covered),
+            format_hours(self.cap)
+        )
+    }
+}
+
+impl std::error::Error for DayLimitError {}
+
+impl From<DayLimitError> for std::io::Error {
+    fn from(e: DayLimitError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
+
+impl DayLimitError {
+    /// log the error along with the activities of the day
+    pub fn log(&self) {
+        error!("{}:", self);
+        for activity in &self.activities {
+            error!("  {} ({})", activity, activity.id);
+        }
+    }
+}
+
+impl DayLimit {
+    pub fn new(app_config: &AppConfig, job_config: &JobConfig) -> Self {
+        Self {
+            boundary: job_config.day_boundary,
+            cap: app_config.max_day_total,
+            force: false,
+        }
+    }
+
+    /// the activities of the day cover at most the cap
+    pub fn check(&self, date: Date, day: &DayInner) -> Result<DayTotals, DayLimitError> {
+        let totals = DayTotals::of(day, self.boundary);
+        if totals.covered > self.cap {
+            return Err(DayLimitError {
+                date,
+                totals,
+                cap: self.cap,
+                activities: day.activities.clone(),
+            });
+        }
+        Ok(totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    use time::Month;
+
+    fn activity(start: (u8, u8), end: Option<(u8, u8)>) -> Activity {
+        let time = |(h, m): (u8, u8)| Time::from_hms(h, m, 0).unwrap();
+        testing::activity("work", time(start), end.map(time))
+    }
+
+    fn day(activities: Vec<Activity>) -> DayInner {
+        DayInner {
+            activities,
+            ..DayInner::default()
+        }
+    }
+
+    const LIMIT: DayLimit = DayLimit {
+        boundary: None,
+        cap: Duration::DAY,
+        force: false,
+    };
+
+    fn date() -> Date {
+        Date::from_calendar_date(2024, Month::May, 2).unwrap()
+    }
+
+    #[test]
+    fn test_day_of_23h59m_passes() {
+        let totals = LIMIT
+            .check(date(), &day(vec![activity((0, 0), Some((23, 59)))]))
+            .unwrap();
+        assert_eq!(totals.covered, Duration::minutes(23 * 60 + 59));
+
+        // nested activities count once
+        let nested = day(vec![
+            activity((8, 0), Some((20, 0))),
+            activity((9, 0), Some((10, 0))),
+        ]);
+        let totals = LIMIT.check(date(), &nested).unwrap();
+        assert_eq!(totals.covered, Duration::hours(12));
+    }
+
+    #[test]
+    fn test_activities_adding_up_to_25h_count_once() {
+        let over = day(vec![
+            activity((0, 0), Some((23, 0))),
+            activity((9, 0), Some((11, 0))),
+        ]);
+        assert_eq!(
+            LIMIT.check(date(), &over).unwrap().covered,
+            Duration::hours(23)
+        );
+
+        // open activities run until the end of the day, completed ones nested inside them
+        // are covered already, e.g. logs while an activity pushed at 00:10 is running
+        let open = day(vec![
+            activity((0, 10), None),
+            activity((12, 0), None),
+            activity((9, 0), Some((9, 10))),
+            activity((9, 10), Some((9, 20))),
+        ]);
+        assert_eq!(
+            LIMIT.check(date(), &open).unwrap().covered,
+            Duration::DAY - Duration::minutes(10)
+        );
+    }
+
+    #[test]
+    fn test_stricter_cap() {
+        let limit = DayLimit {
+            cap: Duration::hours(16),
+            ..LIMIT
+        };
+        let long = day(vec![
+            activity((6, 0), Some((12, 0))),
+            activity((12, 0), Some((23, 0))),
+        ]);
+        let error = limit.check(date(), &long).unwrap_err();
+        assert_eq!(error.totals.covered, Duration::hours(17));
+        assert_eq!(
+            error.to_string(),
+            "The activities of 2024-05-02 cover 17h 0m, more than the maximum of 16h 0m"
+        );
+    }
+}
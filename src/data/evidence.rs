@@ -0,0 +1,90 @@
+use crate::data::activity::Activity;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// context recorded while an activity was running, e.g. the window title or the file being edited
+///
+/// evidence does not influence folding, it only helps to judge a record afterwards
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EvidenceEntry {
+    /// time the evidence was recorded
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+    /// short description, e.g. `src/main.rs`
+    pub text: String,
+}
+
+impl Activity {
+    /// attach evidence, keeping at most `limit` entries by dropping the oldest
+    ///
+    /// returns the number of dropped entries
+    pub fn add_evidence(&mut self, entry: EvidenceEntry, limit: usize) -> usize {
+        self.evidence.push(entry);
+        self.evidence.sort_by_key(|entry| entry.at);
+        let dropped = self.evidence.len().saturating_sub(limit);
+        self.evidence.drain(..dropped);
+        dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, at};
+    use time::{Date, Duration, Month, Time, UtcOffset};
+
+    fn activity() -> Activity {
+        Activity {
+            name: Some("coding".into()),
+            ..testing::activity("work", Time::from_hms(9, 0, 0).unwrap(), None)
+        }
+    }
+
+    #[test]
+    fn test_evidence_is_capped_dropping_oldest() {
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let start = at(date, Time::from_hms(9, 0, 0).unwrap());
+        let mut activity = activity();
+
+        for minute in 0..5 {
+            let dropped = activity.add_evidence(
+                EvidenceEntry {
+                    at: start + Duration::minutes(minute),
+                    text: format!("file{}.rs", minute),
+                },
+                3,
+            );
+            assert_eq!(dropped, (minute as usize).saturating_sub(2).min(1));
+        }
+
+        let texts = activity
+            .evidence
+            .iter()
+            .map(|entry| entry.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["file2.rs", "file3.rs", "file4.rs"]);
+    }
+
+    #[test]
+    fn test_evidence_round_trip() {
+        let mut activity = activity();
+        let json = serde_json::to_value(&activity).unwrap();
+        assert!(json.get("evidence").is_none());
+
+        activity.add_evidence(
+            EvidenceEntry {
+                at: Date::from_calendar_date(2024, Month::May, 2)
+                    .unwrap()
+                    .with_hms(9, 15, 0)
+                    .unwrap()
+                    .assume_offset(UtcOffset::from_hms(2, 0, 0).unwrap()),
+                text: "Firefox — timetrax docs".into(),
+            },
+            50,
+        );
+        let json = serde_json::to_string(&activity).unwrap();
+        let parsed: Activity = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.evidence, activity.evidence);
+        assert!(parsed.extra.is_empty());
+    }
+}
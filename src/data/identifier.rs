@@ -3,7 +3,7 @@ use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use uuid::Uuid;
 
-#[derive(Serialize, Debug, Clone, PartialEq, PartialOrd, Ord, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, PartialOrd, Ord, Eq, Hash)]
 #[serde(into = "String")]
 pub enum Identifier {
     Uuid(Uuid),
@@ -22,12 +22,21 @@ impl From<Uuid> for Identifier {
     }
 }
 
+/// only a leading `@` is stripped, see [`legacy_normalize`] for the former behavior
 impl From<String> for Identifier {
     fn from(value: String) -> Self {
-        Identifier::ByName(value.replace("@", ""))
+        match value.strip_prefix('@') {
+            Some(name) => Identifier::ByName(name.to_string()),
+            None => Identifier::ByName(value),
+        }
     }
 }
 
+/// normalization of names before only a leading `@` was stripped, every `@` was removed
+pub fn legacy_normalize(name: &str) -> String {
+    name.replace('@', "")
+}
+
 impl Display for IdentifierConvertError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -53,9 +62,9 @@ impl FromStr for Identifier {
     type Err = IdentifierConvertError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.is_empty() {
-            return Err(IdentifierConvertError::Empty);
-        } else if s.starts_with("@") {
-            Ok(Identifier::ByName(s[1..].to_string()))
+            Err(IdentifierConvertError::Empty)
+        } else if let Some(name) = s.strip_prefix("@") {
+            Ok(Identifier::ByName(name.to_string()))
         } else {
             Uuid::from_str(s)
                 .map(Identifier::Uuid)
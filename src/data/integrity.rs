@@ -0,0 +1,369 @@
+use crate::data::activity::Activity;
+use crate::data::day::DayInner;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use std::fmt::{Display, Formatter};
+use time::Time;
+use uuid::Uuid;
+
+/// how bad a problem of a day is, errors corrupt the tracked time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// record of a day a problem was found on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Record {
+    Activity,
+    Blocker,
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Record::Activity => write!(f, "activity"),
+            Record::Blocker => write!(f, "blocker"),
+        }
+    }
+}
+
+/// what is wrong with a record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// the record ends before it starts, see [`DayInner::inverted_intervals`]
+    InvertedInterval {
+        start: Time,
+        end: Time,
+    },
+    /// an earlier record of the day has the same id
+    DuplicateId {
+        earlier: Record,
+    },
+    UnknownClass(Identifier),
+    UnknownProject(Identifier),
+    EmptyProjectIdentifier,
+    /// the activity overlaps the identical activity `earlier`
+    IdenticalOverlap {
+        earlier: Uuid,
+    },
+}
+
+impl ProblemKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            ProblemKind::InvertedInterval { .. } | ProblemKind::DuplicateId { .. } => {
+                Severity::Error
+            }
+            ProblemKind::UnknownClass(_)
+            | ProblemKind::UnknownProject(_)
+            | ProblemKind::EmptyProjectIdentifier
+            | ProblemKind::IdenticalOverlap { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl Display for ProblemKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProblemKind::InvertedInterval { start, end } => {
+                write!(f, "ends at {} before it starts at {}", end, start)
+            }
+            ProblemKind::DuplicateId { earlier } => {
+                write!(f, "shares its id with an earlier {}", earlier)
+            }
+            ProblemKind::UnknownClass(class) => write!(f, "refers to the unknown class {}", class),
+            ProblemKind::UnknownProject(project) => {
+                write!(f, "refers to the unknown project {}", project)
+            }
+            ProblemKind::EmptyProjectIdentifier => write!(f, "has an empty project identifier"),
+            ProblemKind::IdenticalOverlap { earlier } => {
+                write!(f, "overlaps the identical activity {}", earlier)
+            }
+        }
+    }
+}
+
+/// a problem of a single activity or blocker of a day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub record: Record,
+    /// id of the activity or blocker
+    pub id: Uuid,
+    pub kind: ProblemKind,
+}
+
+impl Problem {
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: {} {}",
+            self.severity(),
+            self.id,
+            self.record,
+            self.kind
+        )
+    }
+}
+
+/// kind, id, time, class and projects of an activity or blocker
+type RecordRef<'a> = (
+    Record,
+    Uuid,
+    &'a Interval,
+    &'a Identifier,
+    &'a Vec<Identifier>,
+);
+
+impl DayInner {
+    /// all problems of the day, see the individual checks
+    pub fn problems(&self, job_config: &JobConfig) -> Vec<Problem> {
+        let mut problems = self.inverted_intervals(job_config.day_boundary);
+        problems.extend(self.duplicate_ids());
+        problems.extend(self.unresolved_identifiers(job_config));
+        problems.extend(self.empty_project_identifiers());
+        problems.extend(self.identical_overlaps(job_config.day_boundary));
+        problems
+    }
+
+    /// the activities followed by the blockers of the day
+    fn records(&self) -> impl Iterator<Item = RecordRef<'_>> {
+        let activities = (self.activities.iter())
+            .map(|a| (Record::Activity, a.id, &a.time, &a.class, &a.projects));
+        let blockers =
+            (self.blockers.iter()).map(|b| (Record::Blocker, b.id, &b.time, &b.class, &b.projects));
+        activities.chain(blockers)
+    }
+
+    /// activities and blockers ending before they start, times after midnight but before the
+    /// day boundary count as late times
+    pub fn inverted_intervals(&self, boundary: Option<Time>) -> Vec<Problem> {
+        self.records()
+            .filter(|(_, _, time, ..)| time.validate(boundary).is_err())
+            .map(|(record, id, time, ..)| Problem {
+                record,
+                id,
+                kind: ProblemKind::InvertedInterval {
+                    start: time.start,
+                    end: time.end.unwrap_or(time.start),
+                },
+            })
+            .collect()
+    }
+
+    /// activities and blockers sharing their UUID with an earlier activity or blocker of the day
+    pub fn duplicate_ids(&self) -> Vec<Problem> {
+        let records = self.records().collect::<Vec<_>>();
+        records
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (record, id, ..))| {
+                let (earlier, ..) = records[..index].iter().find(|(_, other, ..)| other == id)?;
+                Some(Problem {
+                    record: *record,
+                    id: *id,
+                    kind: ProblemKind::DuplicateId { earlier: *earlier },
+                })
+            })
+            .collect()
+    }
+
+    /// classes and projects of activities and blockers missing from the job config
+    pub fn unresolved_identifiers(&self, job_config: &JobConfig) -> Vec<Problem> {
+        self.records()
+            .flat_map(|(record, id, _, class, projects)| {
+                let class = (job_config.resolve_class(class).is_none())
+                    .then(|| ProblemKind::UnknownClass(class.clone()));
+                let projects = projects
+                    .iter()
+                    .filter(|project| !is_empty_identifier(project))
+                    .filter(|project| job_config.resolve_project(*project).is_none())
+                    .map(|project| ProblemKind::UnknownProject(project.clone()));
+                class
+                    .into_iter()
+                    .chain(projects)
+                    .map(move |kind| Problem { record, id, kind })
+            })
+            .collect()
+    }
+
+    /// activities and blockers with a blank project name, e.g. `@` in a hand-edited file
+    pub fn empty_project_identifiers(&self) -> Vec<Problem> {
+        self.records()
+            .filter(|(.., projects)| projects.iter().any(is_empty_identifier))
+            .map(|(record, id, ..)| Problem {
+                record,
+                id,
+                kind: ProblemKind::EmptyProjectIdentifier,
+            })
+            .collect()
+    }
+
+    /// activities overlapping an earlier activity with the same class, name and projects,
+    /// e.g. pasted twice. their time only counts once
+    pub fn identical_overlaps(&self, boundary: Option<Time>) -> Vec<Problem> {
+        let identical = |a: &Activity, b: &Activity| {
+            a.class == b.class && a.name == b.name && a.projects == b.projects
+        };
+        let overlap = |a: &Activity, b: &Activity| {
+            let (a, b) = (a.time.to_day_time(boundary), b.time.to_day_time(boundary));
+            a.start < b.end_time_or_end_of_day() && b.start < a.end_time_or_end_of_day()
+        };
+        self.activities
+            .iter()
+            .enumerate()
+            .filter_map(|(index, activity)| {
+                let earlier = self.activities[..index]
+                    .iter()
+                    .find(|earlier| identical(earlier, activity) && overlap(earlier, activity))?;
+                Some(Problem {
+                    record: Record::Activity,
+                    id: activity.id,
+                    kind: ProblemKind::IdenticalOverlap {
+                        earlier: earlier.id,
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+fn is_empty_identifier(identifier: &Identifier) -> bool {
+    matches!(identifier, Identifier::ByName(name) if name.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::blocker::Blocker;
+    use crate::data::day::Day;
+
+    fn broken_day() -> Day {
+        serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/broken/2024-05-02.json"
+        )))
+        .unwrap()
+    }
+
+    fn id(n: u128) -> Uuid {
+        Uuid::from_u128(0x00000000_0000_4000_8000_000000000000 | n)
+    }
+
+    #[test]
+    fn test_problems_of_a_broken_day() {
+        let day = broken_day().inner;
+        let problems = day.problems(&JobConfig::default());
+        let found = problems
+            .iter()
+            .map(|problem| (problem.severity(), problem.id, problem.kind.clone()))
+            .collect::<Vec<_>>();
+        let time = |h, m| Time::from_hms(h, m, 0).unwrap();
+        assert_eq!(
+            found,
+            [
+                (
+                    Severity::Error,
+                    id(2),
+                    ProblemKind::InvertedInterval {
+                        start: time(11, 0),
+                        end: time(10, 0)
+                    }
+                ),
+                (
+                    Severity::Error,
+                    id(3),
+                    ProblemKind::DuplicateId {
+                        earlier: Record::Activity
+                    }
+                ),
+                (
+                    Severity::Warning,
+                    id(4),
+                    ProblemKind::UnknownClass(Identifier::ByName("retired".into()))
+                ),
+                (
+                    Severity::Warning,
+                    id(5),
+                    ProblemKind::UnknownProject(Identifier::ByName("gone".into()))
+                ),
+                (
+                    Severity::Warning,
+                    id(5),
+                    ProblemKind::EmptyProjectIdentifier
+                ),
+                (
+                    Severity::Warning,
+                    id(7),
+                    ProblemKind::IdenticalOverlap { earlier: id(6) }
+                ),
+            ]
+        );
+        assert_eq!(
+            problems[0].to_string(),
+            "error 00000000-0000-4000-8000-000000000002: activity ends at 10:00:00.0 before it \
+             starts at 11:00:00.0"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_ids_across_activities_and_blockers() {
+        let mut day = broken_day().inner;
+        day.activities.truncate(1);
+        day.blockers.push(Blocker {
+            id: id(1),
+            name: None,
+            class: Identifier::ByName("work".into()),
+            time: day.activities[0].time.clone(),
+            projects: vec![],
+        });
+        let problems = day.duplicate_ids();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].record, Record::Blocker);
+        assert_eq!(
+            problems[0].kind,
+            ProblemKind::DuplicateId {
+                earlier: Record::Activity
+            }
+        );
+    }
+
+    #[test]
+    fn test_late_times_are_no_inverted_intervals() {
+        let day = broken_day().inner;
+        // 11:00 to 10:00 is no valid interval with any boundary
+        assert_eq!(day.inverted_intervals(None).len(), 1);
+        let mut late = day.clone();
+        late.activities[1].time.start = Time::from_hms(23, 0, 0).unwrap();
+        late.activities[1].time.end = Some(Time::from_hms(1, 0, 0).unwrap());
+        assert_eq!(late.inverted_intervals(None).len(), 1);
+        assert!(
+            late.inverted_intervals(Some(Time::from_hms(4, 0, 0).unwrap()))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_no_problems_in_a_clean_day() {
+        let mut day = broken_day().inner;
+        day.activities.truncate(1);
+        assert!(day.problems(&JobConfig::default()).is_empty());
+    }
+}
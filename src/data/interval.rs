@@ -1,4 +1,5 @@
-use log::error;
+use crate::data::local_time;
+use log::warn;
 use serde::{Deserialize, Serialize};
 
 /// Specified time interval, may be open-ended
@@ -17,11 +18,7 @@ pub struct Interval {
 impl Interval {
     /// Duration if interval ended
     pub fn duration(&self) -> Option<time::Duration> {
-        if let Some(end_time) = self.end {
-            Some(end_time - self.start)
-        } else {
-            None
-        }
+        self.end.map(|end_time| end_time - self.start)
     }
 
     /// Interval completed
@@ -37,27 +34,215 @@ impl Interval {
     /// create a new interval from now on
     pub fn start_now() -> Self {
         Self {
-            start: time::OffsetDateTime::now_local()
-                .unwrap_or_else(|e| {
-                    error!("Failed to get local time: {e}. Falling back to UTC time.");
-                    time::OffsetDateTime::now_utc()
-                })
-                .time(),
+            start: local_time::now_time(),
             end: None,
         }
     }
 
-    /// complete this interval if it is open
-    pub fn complete_now(&mut self) {
-        if self.end.is_none() {
-            self.end = Some(
-                time::OffsetDateTime::now_local()
-                    .unwrap_or_else(|e| {
-                        error!("Failed to get local time: {e}. Falling back to UTC time.");
-                        time::OffsetDateTime::now_utc()
-                    })
-                    .time(),
+    /// interval ends before it starts
+    pub fn is_inverted(&self) -> bool {
+        self.end.is_some_and(|end| end < self.start)
+    }
+
+    /// time elapsed since the start, negative if `now` lies before the start
+    pub fn elapsed_at(&self, now: time::Time) -> time::Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+
+    /// complete this interval at the given time if it is open
+    ///
+    /// if `end` lies before the start (e.g. the clock moved backwards), the interval
+    /// is completed with zero length instead. returns true if the end had to be clamped.
+    /// times are compared within a day starting at `boundary`, see [`JobConfig::day_boundary`]
+    ///
+    /// [`JobConfig::day_boundary`]: crate::data::job_config::JobConfig::day_boundary
+    pub fn complete_at(&mut self, end: time::Time, boundary: Option<time::Time>) -> bool {
+        if self.end.is_some() {
+            return false;
+        }
+
+        if local_time::to_day_time(end, boundary) < local_time::to_day_time(self.start, boundary) {
+            warn!(
+                "End time {} lies before start time {}. Did the clock move backwards? Completing with zero length.",
+                end, self.start
             );
+            self.end = Some(self.start);
+            true
+        } else {
+            self.end = Some(end);
+            false
+        }
+    }
+
+    /// error if the interval ends before it starts within a day starting at `boundary`
+    pub fn validate(&self, boundary: Option<time::Time>) -> crate::error::Result<()> {
+        match self.end {
+            Some(end) if self.to_day_time(boundary).is_inverted() => {
+                Err(crate::error::Error::InvalidInterval {
+                    start: self.start,
+                    end,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// complete this open interval at the last second of its day, which ends at `boundary`,
+    /// and return the open continuation starting with the next day. whole seconds, as stored
+    /// in day files
+    pub fn split_at_day_end(&mut self, boundary: Option<time::Time>) -> Self {
+        let last_second = time::Time::from_hms(23, 59, 59).unwrap();
+        self.end = Some(local_time::from_day_time(last_second, boundary));
+        Self {
+            start: local_time::from_day_time(time::Time::MIDNIGHT, boundary),
+            end: None,
+        }
+    }
+
+    /// complete this interval now if it is open, see [`Interval::complete_at`]
+    pub fn complete_now(&mut self, boundary: Option<time::Time>) -> bool {
+        self.complete_at(local_time::now_time(), boundary)
+    }
+
+    /// same interval with times relative to the start of a day beginning at `boundary`,
+    /// so that intervals crossing midnight before the boundary keep their order and duration
+    pub fn to_day_time(&self, boundary: Option<time::Time>) -> Self {
+        Self {
+            start: local_time::to_day_time(self.start, boundary),
+            end: self.end.map(|end| local_time::to_day_time(end, boundary)),
+        }
+    }
+
+    /// inverse of [`Interval::to_day_time`]
+    pub fn from_day_time(&self, boundary: Option<time::Time>) -> Self {
+        Self {
+            start: local_time::from_day_time(self.start, boundary),
+            end: self.end.map(|end| local_time::from_day_time(end, boundary)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Duration, Time};
+
+    fn time(h: u8, m: u8) -> Time {
+        Time::from_hms(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_complete_at() {
+        let mut interval = Interval {
+            start: time(9, 0),
+            end: None,
+        };
+
+        assert!(!interval.complete_at(time(10, 30), None));
+        assert_eq!(interval.duration(), Some(Duration::minutes(90)));
+
+        // completing twice keeps the first end
+        assert!(!interval.complete_at(time(11, 0), None));
+        assert_eq!(interval.end, Some(time(10, 30)));
+    }
+
+    #[test]
+    fn test_complete_at_clock_backwards() {
+        let mut interval = Interval {
+            start: time(9, 5),
+            end: None,
+        };
+
+        assert_eq!(interval.elapsed_at(time(9, 0)), Duration::minutes(-5));
+        assert!(interval.complete_at(time(9, 0), None));
+        assert_eq!(interval.end, Some(time(9, 5)));
+        assert_eq!(interval.duration(), Some(Duration::ZERO));
+        assert!(!interval.is_inverted());
+    }
+
+    #[test]
+    fn test_is_inverted() {
+        let interval = Interval {
+            start: time(10, 0),
+            end: Some(time(9, 0)),
+        };
+        assert!(interval.is_inverted());
+        assert!(
+            !Interval {
+                start: time(10, 0),
+                end: None
+            }
+            .is_inverted()
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let interval = |start, end| Interval { start, end };
+        assert!(
+            interval(time(9, 0), Some(time(10, 0)))
+                .validate(None)
+                .is_ok()
+        );
+        assert!(
+            interval(time(9, 0), Some(time(9, 0)))
+                .validate(None)
+                .is_ok()
+        );
+        assert!(interval(time(9, 0), None).validate(None).is_ok());
+        assert!(
+            interval(time(23, 0), Some(time(1, 0)))
+                .validate(None)
+                .is_err()
+        );
+        assert!(
+            interval(time(23, 0), Some(time(1, 0)))
+                .validate(Some(time(4, 0)))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_split_at_day_end() {
+        let mut interval = Interval {
+            start: time(22, 0),
+            end: None,
+        };
+        let continuation = interval.split_at_day_end(None);
+        assert_eq!(interval.end, Some(Time::from_hms(23, 59, 59).unwrap()));
+        assert_eq!(continuation.start, Time::MIDNIGHT);
+        assert!(!continuation.is_complete());
+
+        let mut interval = Interval {
+            start: time(22, 0),
+            end: None,
+        };
+        let continuation = interval.split_at_day_end(Some(time(4, 0)));
+        assert_eq!(interval.end, Some(Time::from_hms(3, 59, 59).unwrap()));
+        assert_eq!(continuation.start, time(4, 0));
+        assert!(interval.validate(Some(time(4, 0))).is_ok());
+    }
+
+    #[test]
+    fn test_complete_across_day_boundary() {
+        let boundary = Some(time(4, 0));
+        let mut interval = Interval {
+            start: time(22, 0),
+            end: None,
+        };
+
+        assert!(!interval.complete_at(time(1, 30), boundary));
+        assert_eq!(interval.end, Some(time(1, 30)));
+        let day_time = interval.to_day_time(boundary);
+        assert_eq!(day_time.duration(), Some(Duration::minutes(210)));
+        assert!(!day_time.is_inverted());
+        assert_eq!(day_time.from_day_time(boundary).start, time(22, 0));
+
+        let mut interval = Interval {
+            start: time(22, 0),
+            end: None,
+        };
+        // the boundary itself already belongs to the next day
+        assert!(interval.complete_at(time(4, 0), boundary));
+    }
+}
@@ -1,11 +1,18 @@
 use crate::data::activity_class::{ActivityClass, ActivityClassInner};
+use crate::data::break_rules::{BreakRule, default_break_rules};
 use crate::data::identifier::Identifier;
+use crate::data::local_time;
 use crate::data::project::Project;
+use crate::data::public_holiday::PublicHoliday;
+use crate::data::quota::Quota;
+use crate::data::text::closest_names;
 use log::error;
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use std::sync::LazyLock;
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time};
 use uuid::Uuid;
 
 static DUMMY_ACTIVITY_CLASS: LazyLock<ActivityClass> = LazyLock::new(|| ActivityClass {
@@ -14,11 +21,14 @@ static DUMMY_ACTIVITY_CLASS: LazyLock<ActivityClass> = LazyLock::new(|| Activity
         priority: 0,
         name: "<UNDEFINED>".to_string(),
         description: Some("No classes specified in job config. Using a dummy class.".to_string()),
+        target_share: None,
+        color: None,
+        exclusive: false,
     },
 });
 
 /// configuration file for the job instance
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct JobConfig {
     /// activity classes
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
@@ -26,10 +36,361 @@ pub struct JobConfig {
     /// projects
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub projects: Vec<Project>,
-    // /// daily quotas
+    /// suffix duplicate activity names of a day ("meeting (2)") so names can address activities
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub unique_names_per_day: bool,
+    /// `pop` offers to discard activities shorter than this, e.g. pushed by accident. activities
+    /// with explicitly given times are kept
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_duration_option"
+    )]
+    pub min_activity_duration: Option<Duration>,
+    /// discard activities shorter than `min_activity_duration` when `pop` cannot ask, e.g. in
+    /// scripts, instead of keeping them
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub discard_short_activities: bool,
+    /// time at which a new day starts, e.g. 04:00 for work past midnight, defaults to midnight
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        with = "crate::serde::pretty_time_option"
+    )]
+    pub day_boundary: Option<Time>,
+    /// classes whose time counts towards the work quota
+    #[serde(default = "default_quota_classes")]
+    pub quota_classes: Vec<Identifier>,
+    /// classes whose time counts as a break for the break rules
+    #[serde(
+        skip_serializing_if = "is_default_break_classes",
+        default = "default_break_classes"
+    )]
+    pub break_classes: Vec<Identifier>,
+    /// breaks required for long work days, checked by `report --compliance` and `status`
+    #[serde(
+        skip_serializing_if = "is_default_break_rules",
+        default = "default_break_rules"
+    )]
+    pub break_rules: Vec<BreakRule>,
+    /// time quotas per class, at most one per class
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub quotas: Vec<Quota>,
+    /// `push` reopens the most recently completed activity instead of adding an identical one
+    /// starting when it ended
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub auto_merge_adjacent: bool,
+    /// largest gap between the end of an activity and an identical push that is still merged
+    #[serde(
+        skip_serializing_if = "is_default_auto_merge_tolerance",
+        default = "default_auto_merge_tolerance",
+        with = "crate::serde::pretty_duration"
+    )]
+    pub auto_merge_tolerance: Duration,
+    /// public holidays, their work quota counts as met, see `holiday import-ics`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub holidays: Vec<PublicHoliday>,
+}
+
+fn default_auto_merge_tolerance() -> Duration {
+    Duration::minutes(1)
+}
+
+fn is_default_auto_merge_tolerance(tolerance: &Duration) -> bool {
+    *tolerance == default_auto_merge_tolerance()
+}
+
+fn default_quota_classes() -> Vec<Identifier> {
+    vec![
+        Identifier::ByName("work".to_string()),
+        Identifier::ByName("holiday".to_string()),
+    ]
+}
+
+fn default_break_classes() -> Vec<Identifier> {
+    vec![Identifier::ByName("break".to_string())]
+}
+
+fn is_default_break_classes(classes: &Vec<Identifier>) -> bool {
+    *classes == default_break_classes()
+}
+
+fn is_default_break_rules(rules: &Vec<BreakRule>) -> bool {
+    *rules == default_break_rules()
+}
+
+/// kind of entry touched while merging two job configs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeEntryKind {
+    Class,
+    Project,
+}
+
+/// decision taken for a single entry while merging two job configs
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeDecision {
+    /// entry did not exist locally and was added
+    Added {
+        kind: MergeEntryKind,
+        id: Uuid,
+        name: String,
+    },
+    /// entry did not exist locally, but its name was taken, so it was added under a new name
+    Renamed {
+        kind: MergeEntryKind,
+        id: Uuid,
+        from: String,
+        to: String,
+    },
+    /// an entry with the same UUID already exists locally, the local version is kept
+    Kept {
+        kind: MergeEntryKind,
+        id: Uuid,
+        name: String,
+    },
+}
+
+impl Display for MergeEntryKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeEntryKind::Class => write!(f, "class"),
+            MergeEntryKind::Project => write!(f, "project"),
+        }
+    }
+}
+
+impl Display for MergeDecision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeDecision::Added { kind, id, name } => {
+                write!(f, "added {kind} '{name}' ({id})")
+            }
+            MergeDecision::Renamed { kind, id, from, to } => {
+                write!(
+                    f,
+                    "added {kind} '{from}' as '{to}' ({id}), name already in use"
+                )
+            }
+            MergeDecision::Kept { kind, id, name } => {
+                write!(f, "kept existing {kind} '{name}' ({id})")
+            }
+        }
+    }
+}
+
+/// find a name not contained in `taken` by appending a numeric suffix
+pub(crate) fn unique_name<'a, I: Iterator<Item = &'a str> + Clone>(name: &str, taken: I) -> String {
+    let mut candidate = name.to_string();
+    let mut counter = 2;
+    while taken.clone().any(|t| t == candidate) {
+        candidate = format!("{name} ({counter})");
+        counter += 1;
+    }
+    candidate
 }
 
 impl JobConfig {
+    /// copy of this config suitable for sharing with other machines or people
+    ///
+    /// if `include_projects` is false, all projects are stripped
+    pub fn portable(&self, include_projects: bool) -> JobConfig {
+        let mut result = self.clone();
+        if !include_projects {
+            result.projects.clear();
+        }
+        result
+    }
+
+    /// the public holiday on the date
+    pub fn public_holiday(&self, date: Date) -> Option<&PublicHoliday> {
+        self.holidays.iter().find(|holiday| holiday.date == date)
+    }
+
+    /// add public holidays in order of their dates, holidays on known dates replace the names.
+    /// returns how many dates were new
+    pub fn add_holidays(&mut self, holidays: impl IntoIterator<Item = PublicHoliday>) -> usize {
+        let mut added = 0;
+        for holiday in holidays {
+            match self
+                .holidays
+                .iter_mut()
+                .find(|known| known.date == holiday.date)
+            {
+                Some(known) => known.name = holiday.name,
+                None => {
+                    self.holidays.push(holiday);
+                    added += 1;
+                }
+            }
+        }
+        self.holidays.sort_by_key(|holiday| holiday.date);
+        added
+    }
+
+    /// check if this config is equal to the default config
+    pub fn is_default(&self) -> bool {
+        *self == JobConfig::default()
+    }
+
+    /// merge another config into this one
+    ///
+    /// classes and projects are unioned by UUID, entries already present locally are kept as is.
+    /// new entries whose name is already in use are renamed with a numeric suffix.
+    /// returns every decision taken, in order.
+    pub fn merge(&mut self, other: JobConfig) -> Vec<MergeDecision> {
+        let mut decisions = Vec::new();
+
+        for class in other.classes {
+            if let Some(existing) = self.classes.iter().find(|c| c.id == class.id) {
+                decisions.push(MergeDecision::Kept {
+                    kind: MergeEntryKind::Class,
+                    id: existing.id,
+                    name: existing.inner.name.clone(),
+                });
+                continue;
+            }
+
+            let name = unique_name(
+                &class.inner.name,
+                self.classes.iter().map(|c| c.inner.name.as_str()),
+            );
+            decisions.push(if name == class.inner.name {
+                MergeDecision::Added {
+                    kind: MergeEntryKind::Class,
+                    id: class.id,
+                    name: name.clone(),
+                }
+            } else {
+                MergeDecision::Renamed {
+                    kind: MergeEntryKind::Class,
+                    id: class.id,
+                    from: class.inner.name.clone(),
+                    to: name.clone(),
+                }
+            });
+
+            let mut class = class;
+            class.inner.name = name;
+            self.classes.push(class);
+        }
+
+        for project in other.projects {
+            if let Some(existing) = self.projects.iter().find(|p| p.id == project.id) {
+                decisions.push(MergeDecision::Kept {
+                    kind: MergeEntryKind::Project,
+                    id: existing.id,
+                    name: existing.inner.name.clone(),
+                });
+                continue;
+            }
+
+            let name = unique_name(
+                &project.inner.name,
+                self.projects.iter().map(|p| p.inner.name.as_str()),
+            );
+            decisions.push(if name == project.inner.name {
+                MergeDecision::Added {
+                    kind: MergeEntryKind::Project,
+                    id: project.id,
+                    name: name.clone(),
+                }
+            } else {
+                MergeDecision::Renamed {
+                    kind: MergeEntryKind::Project,
+                    id: project.id,
+                    from: project.inner.name.clone(),
+                    to: name.clone(),
+                }
+            });
+
+            let mut project = project;
+            project.inner.name = name;
+            self.projects.push(project);
+        }
+
+        decisions
+    }
+
+    /// target shares must lie between 0 and 100 and sum up to at most 100 per kind
+    pub fn validate_target_shares(&self) -> Result<(), String> {
+        fn check<'a, I: Iterator<Item = (&'a str, Option<f32>)>>(
+            kind: &str,
+            shares: I,
+        ) -> Result<(), String> {
+            let mut sum = 0.0;
+            for (name, share) in shares {
+                let Some(share) = share else { continue };
+                if !(0.0..=100.0).contains(&share) {
+                    return Err(format!(
+                        "target share of {kind} '{name}' must lie between 0 and 100, got {share}"
+                    ));
+                }
+                sum += share;
+            }
+            // allow for rounding errors of e.g. 33.3 + 33.3 + 33.4
+            if sum > 100.0 + 1e-3 {
+                return Err(format!(
+                    "target shares of all {kind}s sum up to {sum}%, at most 100% are possible"
+                ));
+            }
+            Ok(())
+        }
+
+        check(
+            "class",
+            self.classes
+                .iter()
+                .map(|c| (c.inner.name.as_str(), c.inner.target_share)),
+        )?;
+        check(
+            "project",
+            self.projects
+                .iter()
+                .map(|p| (p.inner.name.as_str(), p.inner.target_share)),
+        )
+    }
+
+    /// date of the day containing `now`, honoring the day boundary
+    pub fn date_of(&self, now: OffsetDateTime) -> Date {
+        local_time::effective_date(
+            PrimitiveDateTime::new(now.date(), now.time()),
+            self.day_boundary,
+        )
+    }
+
+    /// date of the current day, honoring the day boundary
+    pub fn today(&self) -> Date {
+        self.date_of(local_time::now())
+    }
+
+    /// time of the class counts towards the work quota
+    pub fn counts_towards_quota(&self, class: &Identifier) -> bool {
+        let Some(class) = self.resolve_class(class) else {
+            return false;
+        };
+        self.quota_classes
+            .iter()
+            .any(|quota_class| class.identifier_matches(quota_class))
+    }
+
+    /// time of the class counts as a break for the break rules
+    pub fn is_break(&self, class: &Identifier) -> bool {
+        let Some(class) = self.resolve_class(class) else {
+            return false;
+        };
+        self.break_classes
+            .iter()
+            .any(|break_class| class.identifier_matches(break_class))
+    }
+
+    /// the quota of the class, quotas referring to unknown classes never match
+    pub fn quota_of(&self, class: &Identifier) -> Option<&Quota> {
+        let class = self.resolve_class(class)?;
+        self.quotas
+            .iter()
+            .find(|quota| class.identifier_matches(&quota.inner.class))
+    }
+
     pub fn lowest_priority_class(&self) -> &ActivityClass {
         self.classes.iter().min_by(|a, b| a.inner.priority.cmp(&b.inner.priority)).unwrap_or_else(|| {
             error!("Your job configuration does not specify any activity classes. This will lead to wrong total time calculation!");
@@ -52,6 +413,26 @@ impl JobConfig {
             .find(|class| class.identifier_matches(identifier.borrow()))
     }
 
+    /// up to three classes with names close to `name`, see [`closest_names`]
+    pub fn suggest_class(&self, name: &str) -> Vec<&ActivityClass> {
+        closest_names(
+            name,
+            self.classes
+                .iter()
+                .map(|class| (class, class.inner.name.as_str())),
+        )
+    }
+
+    /// up to three projects with names close to `name`, see [`closest_names`]
+    pub fn suggest_project(&self, name: &str) -> Vec<&Project> {
+        closest_names(
+            name,
+            self.projects
+                .iter()
+                .map(|project| (project, project.inner.name.as_str())),
+        )
+    }
+
     pub fn resolve_project<Q: Borrow<Identifier>>(&self, identifier: Q) -> Option<&Project> {
         self.projects
             .iter()
@@ -78,6 +459,9 @@ impl Default for JobConfig {
                         priority: 0,
                         name: "work".to_string(),
                         description: Some("Work. Counted against work quota.".to_string()),
+                        target_share: None,
+                        color: None,
+                        exclusive: false,
                     }
                 },
                 ActivityClass {
@@ -86,6 +470,9 @@ impl Default for JobConfig {
                         priority: 5,
                         name: "break".to_string(),
                         description: Some("Activities classified as a short break during work. Legally required break-time.".to_string()),
+                        target_share: None,
+                        color: None,
+                        exclusive: false,
                     }
                 },
                 ActivityClass {
@@ -94,10 +481,256 @@ impl Default for JobConfig {
                         priority: 10,
                         name: "holiday".to_string(),
                         description: Some("Holiday/Vacation time.".to_string()),
+                        target_share: None,
+                        color: None,
+                        exclusive: false,
                     }
                 }
             ],
             projects: vec![],
+            unique_names_per_day: false,
+            min_activity_duration: None,
+            discard_short_activities: false,
+            day_boundary: None,
+            quota_classes: default_quota_classes(),
+            break_classes: default_break_classes(),
+            break_rules: default_break_rules(),
+            quotas: vec![],
+            auto_merge_adjacent: false,
+            auto_merge_tolerance: default_auto_merge_tolerance(),
+            holidays: vec![],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::project::{Project, ProjectInner};
+    use crate::data::quota::QuotaInner;
+
+    fn class(id: u128, name: &str) -> ActivityClass {
+        ActivityClass {
+            id: Uuid::from_u128(id),
+            inner: ActivityClassInner {
+                name: name.into(),
+                priority: id as i32,
+                description: None,
+                target_share: None,
+                color: None,
+                exclusive: false,
+            },
+        }
+    }
+
+    fn project(id: u128, name: &str) -> Project {
+        Project {
+            id: Uuid::from_u128(id),
+            inner: ProjectInner {
+                name: name.into(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_quotas_round_trip() {
+        let job_config = JobConfig {
+            classes: vec![class(1, "work")],
+            quotas: vec![Quota {
+                id: Uuid::from_u128(20),
+                inner: QuotaInner {
+                    class: Identifier::Uuid(Uuid::from_u128(1)),
+                    duration: Duration::minutes(450),
+                    description: Some("part time".to_string()),
+                },
+            }],
+            ..JobConfig::default()
+        };
+
+        let json = serde_json::to_value(&job_config).unwrap();
+        assert_eq!(json["quotas"][0]["duration"], "07h 30m 00s");
+        let parsed: JobConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, job_config);
+        assert_eq!(
+            parsed.quota_of(&Identifier::ByName("work".to_string())),
+            Some(&job_config.quotas[0])
+        );
+
+        // no quotas are not stored at all
+        let json = serde_json::to_value(JobConfig::default()).unwrap();
+        assert!(json.get("quotas").is_none());
+        let parsed: JobConfig = serde_json::from_value(json).unwrap();
+        assert!(parsed.quotas.is_empty());
+    }
+
+    #[test]
+    fn test_merge_unions_by_uuid() {
+        let mut local = JobConfig {
+            classes: vec![class(1, "work"), class(2, "break")],
+            projects: vec![project(10, "projA")],
+            ..JobConfig::default()
+        };
+        let remote = JobConfig {
+            classes: vec![class(1, "work renamed remotely"), class(3, "excused")],
+            projects: vec![project(10, "projA"), project(11, "projB")],
+            ..JobConfig::default()
+        };
+
+        let decisions = local.merge(remote);
+
+        assert_eq!(
+            decisions,
+            vec![
+                MergeDecision::Kept {
+                    kind: MergeEntryKind::Class,
+                    id: Uuid::from_u128(1),
+                    name: "work".into(),
+                },
+                MergeDecision::Added {
+                    kind: MergeEntryKind::Class,
+                    id: Uuid::from_u128(3),
+                    name: "excused".into(),
+                },
+                MergeDecision::Kept {
+                    kind: MergeEntryKind::Project,
+                    id: Uuid::from_u128(10),
+                    name: "projA".into(),
+                },
+                MergeDecision::Added {
+                    kind: MergeEntryKind::Project,
+                    id: Uuid::from_u128(11),
+                    name: "projB".into(),
+                },
+            ]
+        );
+        assert_eq!(local.classes.len(), 3);
+        assert_eq!(local.classes[0].inner.name, "work");
+        assert_eq!(local.projects.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_renames_on_name_collision() {
+        let mut local = JobConfig {
+            classes: vec![class(1, "work"), class(2, "work (2)")],
+            projects: vec![project(10, "projA")],
+            ..JobConfig::default()
+        };
+        let remote = JobConfig {
+            classes: vec![class(5, "work")],
+            projects: vec![project(20, "projA")],
+            ..JobConfig::default()
+        };
+
+        let decisions = local.merge(remote);
+
+        assert_eq!(
+            decisions,
+            vec![
+                MergeDecision::Renamed {
+                    kind: MergeEntryKind::Class,
+                    id: Uuid::from_u128(5),
+                    from: "work".into(),
+                    to: "work (3)".into(),
+                },
+                MergeDecision::Renamed {
+                    kind: MergeEntryKind::Project,
+                    id: Uuid::from_u128(20),
+                    from: "projA".into(),
+                    to: "projA (2)".into(),
+                },
+            ]
+        );
+        assert!(
+            local
+                .resolve_class(Identifier::ByName("work (3)".into()))
+                .is_some_and(|c| c.id == Uuid::from_u128(5))
+        );
+        assert!(
+            local
+                .resolve_project(Identifier::ByName("projA (2)".into()))
+                .is_some_and(|p| p.id == Uuid::from_u128(20))
+        );
+    }
+
+    #[test]
+    fn test_merge_is_idempotent() {
+        let mut local = JobConfig::default();
+        let remote = JobConfig {
+            classes: vec![class(7, "meeting")],
+            projects: vec![project(8, "projA")],
+            ..JobConfig::default()
+        };
+
+        local.merge(remote.clone());
+        let after_first = local.clone();
+        let decisions = local.merge(remote);
+
+        assert_eq!(local, after_first);
+        assert!(
+            decisions
+                .iter()
+                .all(|d| matches!(d, MergeDecision::Kept { .. }))
+        );
+    }
+
+    #[test]
+    fn test_portable_strips_projects() {
+        let config = JobConfig {
+            classes: vec![class(1, "work")],
+            projects: vec![project(10, "projA")],
+            ..JobConfig::default()
+        };
+
+        assert_eq!(config.portable(true), config);
+        let stripped = config.portable(false);
+        assert!(stripped.projects.is_empty());
+        assert_eq!(stripped.classes, config.classes);
+    }
+
+    #[test]
+    fn test_validate_target_shares() {
+        let mut config = JobConfig {
+            classes: vec![class(1, "work"), class(2, "support")],
+            projects: vec![project(10, "projA"), project(11, "projB")],
+            ..JobConfig::default()
+        };
+        assert_eq!(config.validate_target_shares(), Ok(()));
+
+        config.projects[0].inner.target_share = Some(70.0);
+        config.projects[1].inner.target_share = Some(30.0);
+        config.classes[0].inner.target_share = Some(80.0);
+        assert_eq!(config.validate_target_shares(), Ok(()));
+
+        config.projects[1].inner.target_share = Some(30.5);
+        assert!(
+            config
+                .validate_target_shares()
+                .unwrap_err()
+                .contains("projects sum up to 100.5%")
+        );
+
+        config.projects[1].inner.target_share = Some(-1.0);
+        assert!(config.validate_target_shares().is_err());
+        config.projects[1].inner.target_share = Some(f32::NAN);
+        assert!(config.validate_target_shares().is_err());
+    }
+
+    #[test]
+    fn test_suggest_class() {
+        let config = JobConfig::default();
+        let names = |name| {
+            config
+                .suggest_class(name)
+                .iter()
+                .map(|class| class.inner.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("wrok"), ["work"]);
+        assert_eq!(names("@Work"), ["work"]);
+        assert_eq!(names("BREAK"), ["break"]);
+        assert!(names("vacation").is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use log::{error, trace};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use time::{Date, OffsetDateTime};
+
+/// single modification of a day, enough to restore its previous state
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalEntry {
+    /// time of the modification
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+    /// command that modified the day, e.g. `compact`
+    pub command: String,
+    /// modified day
+    pub date: Date,
+    /// activities removed from the day
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub removed: Vec<Activity>,
+    /// activities added to the day
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub added: Vec<Activity>,
+}
+
+/// append-only log of modifications, one JSON entry per line
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new<P: AsRef<Path>>(app_config: &AppConfig, data_path: P) -> Self {
+        Self {
+            path: data_path.as_ref().join(&app_config.journal_file_name),
+        }
+    }
+
+    pub fn append(&self, entry: &JournalEntry) -> std::io::Result<()> {
+        trace!("Appending journal entry to {}", self.path.display());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .inspect_err(|e| {
+                error!("Failed to open journal at {}: {}", self.path.display(), e);
+            })?;
+
+        let line = serde_json::to_string(entry)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// all entries, oldest first
+    pub fn entries(&self) -> std::io::Result<Vec<JournalEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        BufReader::new(file)
+            .lines()
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect()
+    }
+}
@@ -0,0 +1,272 @@
+use crate::data::day::DayInner;
+use crate::data::identifier::{Identifier, legacy_normalize};
+use crate::data::job_config::JobConfig;
+use itertools::Itertools;
+use std::fmt::{Display, Formatter};
+
+/// identifier of a day that only resolves under the legacy normalization of names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyIdentifier {
+    /// where the identifier is used, e.g. `project of activity <uuid>`
+    pub location: String,
+    pub stored: Identifier,
+    /// identifier resolving to the same class or project under the current normalization
+    pub canonical: Identifier,
+}
+
+impl Display for LegacyIdentifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} only resolves as {}",
+            self.location, self.stored, self.canonical
+        )
+    }
+}
+
+/// names of the classes and projects identifiers are resolved against
+struct Names<'a> {
+    classes: Vec<&'a str>,
+    projects: Vec<&'a str>,
+}
+
+impl<'a> Names<'a> {
+    fn new(job_config: &'a JobConfig) -> Self {
+        Self {
+            classes: job_config
+                .classes
+                .iter()
+                .map(|class| class.inner.name.as_str())
+                .collect(),
+            projects: job_config
+                .projects
+                .iter()
+                .map(|project| project.inner.name.as_str())
+                .collect(),
+        }
+    }
+}
+
+/// the current name `identifier` resolved to under the legacy normalization,
+/// none if it resolves as is or is ambiguous under the legacy normalization
+fn canonical(names: &[&str], identifier: &Identifier) -> Option<Identifier> {
+    let Identifier::ByName(name) = identifier else {
+        return None;
+    };
+    if names.contains(&name.as_str()) {
+        return None;
+    }
+    let normalized = legacy_normalize(name);
+    names
+        .iter()
+        .filter(|candidate| legacy_normalize(candidate) == normalized)
+        .exactly_one()
+        .ok()
+        .map(|candidate| Identifier::ByName(candidate.to_string()))
+}
+
+/// every identifier slot of the day with its location and the names it resolves against
+fn visit(
+    day: &mut DayInner,
+    names: &Names,
+    mut visitor: impl FnMut(String, &[&str], &mut Identifier),
+) {
+    for activity in &mut day.activities {
+        let id = activity.id;
+        visitor(
+            format!("class of activity {id}"),
+            &names.classes,
+            &mut activity.class,
+        );
+        for project in &mut activity.projects {
+            visitor(
+                format!("project of activity {id}"),
+                &names.projects,
+                project,
+            );
+        }
+        // weights are keyed by project, the keys are rewritten by rebuilding the map
+        activity.weights = std::mem::take(&mut activity.weights)
+            .into_iter()
+            .map(|(mut project, weight)| {
+                visitor(
+                    format!("weight of activity {id}"),
+                    &names.projects,
+                    &mut project,
+                );
+                (project, weight)
+            })
+            .collect();
+    }
+    for blocker in &mut day.blockers {
+        let id = blocker.id;
+        visitor(
+            format!("class of blocker {id}"),
+            &names.classes,
+            &mut blocker.class,
+        );
+        for project in &mut blocker.projects {
+            visitor(format!("project of blocker {id}"), &names.projects, project);
+        }
+    }
+    for quota in &mut day.quotas {
+        visitor(
+            format!("class of quota {}", quota.id),
+            &names.classes,
+            &mut quota.inner.class,
+        );
+    }
+}
+
+/// identifiers of the day that no longer resolve since only a leading `@` is stripped,
+/// but did under the legacy normalization removing every `@`
+pub fn legacy_identifiers(job_config: &JobConfig, day: &DayInner) -> Vec<LegacyIdentifier> {
+    let names = Names::new(job_config);
+    let mut found = Vec::new();
+    visit(&mut day.clone(), &names, |location, names, identifier| {
+        if let Some(canonical) = canonical(names, identifier) {
+            found.push(LegacyIdentifier {
+                location,
+                stored: identifier.clone(),
+                canonical,
+            });
+        }
+    });
+    found
+}
+
+/// rewrite the legacy identifiers of the day to their canonical form, returns how many changed
+pub fn fix_legacy_identifiers(job_config: &JobConfig, day: &mut DayInner) -> usize {
+    let names = Names::new(job_config);
+    let mut fixed = 0;
+    visit(day, &names, |_, names, identifier| {
+        if let Some(canonical) = canonical(names, identifier) {
+            *identifier = canonical;
+            fixed += 1;
+        }
+    });
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::day::Day;
+
+    const JOB: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/legacy_identifiers/job.json"
+    ));
+    const OLD_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/legacy_identifiers/old_day.json"
+    ));
+    const NEW_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/legacy_identifiers/new_day.json"
+    ));
+
+    fn load(day: &str) -> (JobConfig, DayInner) {
+        let job_config: JobConfig = serde_json::from_str(JOB).unwrap();
+        let day: Day = serde_json::from_str(day).unwrap();
+        (job_config, day.inner)
+    }
+
+    /// the identifiers of the day that resolve neither as is nor as a legacy identifier
+    fn unresolved(job_config: &JobConfig, day: &DayInner) -> Vec<Identifier> {
+        let legacy = legacy_identifiers(job_config, day)
+            .into_iter()
+            .map(|legacy| legacy.stored)
+            .collect_vec();
+        let resolves = |identifier: &Identifier, is_class: bool| {
+            legacy.contains(identifier)
+                || if is_class {
+                    job_config.resolve_class(identifier).is_some()
+                } else {
+                    job_config.resolve_project(identifier).is_some()
+                }
+        };
+        let mut unresolved = Vec::new();
+        for activity in &day.activities {
+            unresolved.extend(Some(&activity.class).filter(|class| !resolves(class, true)));
+            unresolved.extend(
+                activity
+                    .projects
+                    .iter()
+                    .chain(activity.weights.keys())
+                    .filter(|project| !resolves(project, false)),
+            );
+        }
+        for blocker in &day.blockers {
+            unresolved.extend(Some(&blocker.class).filter(|class| !resolves(class, true)));
+            unresolved.extend(
+                blocker
+                    .projects
+                    .iter()
+                    .filter(|project| !resolves(project, false)),
+            );
+        }
+        unresolved.into_iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_from_string_only_strips_a_leading_at() {
+        assert_eq!(
+            Identifier::from("@user@example".to_string()),
+            Identifier::ByName("user@example".to_string())
+        );
+        assert_eq!(
+            Identifier::from("user@example".to_string()),
+            Identifier::ByName("user@example".to_string())
+        );
+        assert_eq!(legacy_normalize("@user@example"), "userexample");
+    }
+
+    #[test]
+    fn test_new_form_needs_no_migration() {
+        let (job_config, day) = load(NEW_DAY);
+
+        assert!(legacy_identifiers(&job_config, &day).is_empty());
+        assert!(unresolved(&job_config, &day).is_empty());
+    }
+
+    #[test]
+    fn test_old_form_is_reported_and_fixed() {
+        let (job_config, mut day) = load(OLD_DAY);
+
+        // no identifier silently stops resolving, each one resolves or is reported
+        assert!(unresolved(&job_config, &day).is_empty());
+        let legacy = legacy_identifiers(&job_config, &day);
+        assert_eq!(
+            legacy
+                .iter()
+                .map(|legacy| (legacy.stored.to_string(), legacy.canonical.to_string()))
+                .collect_vec(),
+            [
+                ("@oncall".to_string(), "@on@call".to_string()),
+                ("@userexample".to_string(), "@user@example".to_string()),
+                ("@ops@team".to_string(), "@opsteam".to_string()),
+                ("@userexample".to_string(), "@user@example".to_string()),
+            ]
+        );
+
+        assert_eq!(fix_legacy_identifiers(&job_config, &mut day), legacy.len());
+        assert!(legacy_identifiers(&job_config, &day).is_empty());
+        let activity = &day.activities[0];
+        assert!(job_config.resolve_class(&activity.class).is_some());
+        assert!(
+            activity
+                .projects
+                .iter()
+                .chain(activity.weights.keys())
+                .all(|project| job_config.resolve_project(project).is_some())
+        );
+
+        // the fixed day reads back in the new form
+        let (_, new) = load(NEW_DAY);
+        assert_eq!(
+            serde_json::to_value(&day.activities).unwrap(),
+            serde_json::to_value(&new.activities).unwrap()
+        );
+    }
+}
@@ -1,11 +1,62 @@
 use log::warn;
-use time::OffsetDateTime;
+use std::cell::RefCell;
+use std::sync::Arc;
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+/// source of the current time
+pub trait Clock: Send + Sync {
+    /// current date and time
+    fn now(&self) -> OffsetDateTime;
+}
+
+/// clock reading the system time in the local timezone
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_local().unwrap_or_else(|e| {
+            warn!("Unable to determine local time: {}", e);
+            OffsetDateTime::now_utc()
+        })
+    }
+}
+
+/// clock always returning the same point in time
+pub struct FixedClock(pub OffsetDateTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> OffsetDateTime {
+        self.0
+    }
+}
+
+thread_local! {
+    static CLOCK: RefCell<Option<Arc<dyn Clock>>> = const { RefCell::new(None) };
+}
+
+/// restores the previously installed clock when dropped
+pub struct ClockGuard {
+    previous: Option<Arc<dyn Clock>>,
+}
+
+impl Drop for ClockGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CLOCK.with(|clock| *clock.borrow_mut() = previous);
+    }
+}
+
+/// install a clock for the current thread, replacing the system clock
+/// until the returned guard is dropped
+pub fn set_clock(clock: Arc<dyn Clock>) -> ClockGuard {
+    let previous = CLOCK.with(|current| current.borrow_mut().replace(clock));
+    ClockGuard { previous }
+}
 
 pub fn now() -> OffsetDateTime {
-    OffsetDateTime::now_local().unwrap_or_else(|e| {
-        warn!("Unable to determine local time: {}", e);
-        OffsetDateTime::now_utc()
-    })
+    CLOCK
+        .with(|clock| clock.borrow().as_ref().map(|clock| clock.now()))
+        .unwrap_or_else(|| SystemClock.now())
 }
 pub fn now_time() -> time::Time {
     now().time()
@@ -13,3 +64,142 @@ pub fn now_time() -> time::Time {
 pub fn now_date() -> time::Date {
     now().date()
 }
+
+/// best-known offset of the local timezone at the given date and time,
+/// falling back to the current offset if it cannot be determined
+pub fn local_offset_at(date: Date, time: Time) -> UtcOffset {
+    UtcOffset::local_offset_at(date.with_time(time).assume_utc()).unwrap_or_else(|_| now().offset())
+}
+
+/// date of the day containing `now`, days start at `boundary` instead of midnight if given
+///
+/// e.g. with a boundary of 04:00, 01:30 still belongs to the previous day
+pub fn effective_date(now: PrimitiveDateTime, boundary: Option<Time>) -> Date {
+    match boundary {
+        Some(boundary) if now.time() < boundary => now.date().previous_day().unwrap_or(now.date()),
+        _ => now.date(),
+    }
+}
+
+/// start (inclusive) and end (exclusive) of the day, running from boundary to boundary
+pub fn day_window(date: Date, boundary: Option<Time>) -> (PrimitiveDateTime, PrimitiveDateTime) {
+    let start = date.with_time(boundary.unwrap_or(Time::MIDNIGHT));
+    (start, start + Duration::days(1))
+}
+
+/// calendar date and time of a time of day on the given day,
+/// times before the boundary lie on the following calendar date
+pub fn day_datetime(date: Date, time: Time, boundary: Option<Time>) -> PrimitiveDateTime {
+    match boundary {
+        Some(boundary) if time < boundary => date.next_day().unwrap_or(date).with_time(time),
+        _ => date.with_time(time),
+    }
+}
+
+/// time since the start of a day beginning at `boundary`, as time of day
+///
+/// times after midnight but before the boundary sort after all other times of the day
+pub fn to_day_time(time: Time, boundary: Option<Time>) -> Time {
+    time - (boundary.unwrap_or(Time::MIDNIGHT) - Time::MIDNIGHT)
+}
+
+/// inverse of [`to_day_time`]
+pub fn from_day_time(time: Time, boundary: Option<Time>) -> Time {
+    time + (boundary.unwrap_or(Time::MIDNIGHT) - Time::MIDNIGHT)
+}
+
+/// time `duration` after `time`, none if that lies on the next day starting at `boundary`
+pub fn add_within_day(time: Time, duration: Duration, boundary: Option<Time>) -> Option<Time> {
+    let start = to_day_time(time, boundary);
+    let end = (start - Time::MIDNIGHT).checked_add(duration)?;
+    (end < Duration::DAY).then(|| from_day_time(start + duration, boundary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    fn at(date: Date, h: u8, m: u8) -> PrimitiveDateTime {
+        date.with_time(Time::from_hms(h, m, 0).unwrap())
+    }
+
+    #[test]
+    fn test_effective_date() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let previous = date.previous_day().unwrap();
+        let boundary = Some(Time::from_hms(4, 0, 0).unwrap());
+
+        assert_eq!(effective_date(at(date, 3, 59), boundary), previous);
+        assert_eq!(effective_date(at(date, 4, 0), boundary), date);
+        assert_eq!(effective_date(at(date, 0, 0), None), date);
+        assert_eq!(effective_date(at(date, 23, 59), None), date);
+    }
+
+    #[test]
+    fn test_day_window() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let next = date.next_day().unwrap();
+
+        assert_eq!(day_window(date, None), (at(date, 0, 0), at(next, 0, 0)));
+        assert_eq!(
+            day_window(date, Some(Time::from_hms(4, 0, 0).unwrap())),
+            (at(date, 4, 0), at(next, 4, 0))
+        );
+    }
+
+    #[test]
+    fn test_day_datetime() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let next = date.next_day().unwrap();
+        let boundary = Some(Time::from_hms(4, 0, 0).unwrap());
+
+        assert_eq!(
+            day_datetime(date, Time::from_hms(3, 59, 0).unwrap(), boundary),
+            at(next, 3, 59)
+        );
+        assert_eq!(
+            day_datetime(date, Time::from_hms(4, 0, 0).unwrap(), boundary),
+            at(date, 4, 0)
+        );
+        assert_eq!(
+            day_datetime(date, Time::from_hms(1, 0, 0).unwrap(), None),
+            at(date, 1, 0)
+        );
+    }
+
+    #[test]
+    fn test_day_time() {
+        let boundary = Some(Time::from_hms(4, 0, 0).unwrap());
+        let time = |h, m| Time::from_hms(h, m, 0).unwrap();
+
+        assert_eq!(to_day_time(time(4, 0), boundary), Time::MIDNIGHT);
+        assert_eq!(to_day_time(time(3, 59), boundary), time(23, 59));
+        assert!(to_day_time(time(1, 30), boundary) > to_day_time(time(22, 0), boundary));
+        assert_eq!(
+            from_day_time(to_day_time(time(1, 30), boundary), boundary),
+            time(1, 30)
+        );
+        assert_eq!(to_day_time(time(1, 30), None), time(1, 30));
+    }
+
+    #[test]
+    fn test_add_within_day() {
+        let boundary = Some(Time::from_hms(4, 0, 0).unwrap());
+        let time = |h, m| Time::from_hms(h, m, 0).unwrap();
+
+        assert_eq!(
+            add_within_day(time(9, 15), Duration::minutes(90), None),
+            Some(time(10, 45))
+        );
+        assert_eq!(add_within_day(time(23, 0), Duration::hours(2), None), None);
+        assert_eq!(
+            add_within_day(time(23, 0), Duration::hours(2), boundary),
+            Some(time(1, 0))
+        );
+        assert_eq!(
+            add_within_day(time(3, 0), Duration::hours(1), boundary),
+            None
+        );
+    }
+}
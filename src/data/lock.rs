@@ -0,0 +1,72 @@
+use crate::error::Error;
+use log::{error, info, trace};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// lock file in the data directory, containing the PID of the instance holding the lock
+pub const LOCK_FILE_NAME: &str = ".timetrax.lock";
+
+/// how often a waiting instance checks whether the lock was released
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// advisory lock keeping other timetrax instances from modifying the data directory,
+/// released when dropped
+#[derive(Debug)]
+pub struct DataLock {
+    _file: File,
+}
+
+impl DataLock {
+    /// lock the data directory, waiting up to `wait` for another instance to release it
+    pub fn acquire(data_path: &Path, wait: Duration) -> crate::error::Result<Self> {
+        let path = data_path.join(LOCK_FILE_NAME);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        let deadline = Instant::now() + wait;
+        let mut waiting = false;
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => {
+                    let holder = read_pid(&mut file);
+                    let now = Instant::now();
+                    if now >= deadline {
+                        let error = Error::Locked(holder);
+                        error!("{}, retry later or use --wait", error);
+                        return Err(error);
+                    }
+                    if !waiting {
+                        info!("Waiting for the lock of {}", path.display());
+                        waiting = true;
+                    }
+                    std::thread::sleep(RETRY_INTERVAL.min(deadline - now));
+                }
+                Err(TryLockError::Error(e)) => {
+                    error!("Failed to lock {}: {}", path.display(), e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", std::process::id())?;
+        trace!("Locked {}", path.display());
+        Ok(Self { _file: file })
+    }
+}
+
+/// PID written to the lock file by the instance holding it
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.rewind().ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
@@ -1,72 +1,375 @@
 use crate::data::BASIC_DATE_FORMAT;
+use crate::data::activity::Activity;
 use crate::data::app_config::AppConfig;
-use crate::data::day::{Day, DayInner};
+use crate::data::backup::{BACKUP_FOLDER, backup_file};
+use crate::data::day::{ActivityLookupError, Day, DayInner};
+use crate::data::day_limit::DayLimit;
 use crate::data::dirty::DirtyMarker;
+use crate::data::identifier::Identifier;
 use crate::data::job_config::JobConfig;
-use log::{error, trace, warn};
-use std::collections::BTreeMap;
+use crate::data::local_time;
+use crate::data::lock::DataLock;
+use crate::error::Error;
+use crate::profile;
+use log::{error, info, trace, warn};
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
-use std::ops::Deref;
-use std::path::{Path, PathBuf};
-use time::Date;
+use std::io::{BufWriter, Read, Write};
+use std::ops::{Deref, RangeBounds};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, LazyLock};
+use std::time::SystemTime;
+use time::{Date, Duration, OffsetDateTime, Time};
+use uuid::Uuid;
+
+/// folder of the day files, the configured folder must stay inside the data directory
+pub fn day_folder(app_config: &AppConfig, data_path: &Path) -> std::io::Result<PathBuf> {
+    let folder = Path::new(&app_config.job_day_folder_format);
+    if folder.as_os_str().is_empty()
+        || !folder
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        error!(
+            "Day folder {:?} must be a relative path inside the data directory",
+            app_config.job_day_folder_format
+        );
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Day folder outside of the data directory",
+        ));
+    }
+    Ok(data_path.join(folder))
+}
+
+/// `path` resolves inside `data_path`, following symbolic links
+fn ensure_inside(data_path: &Path, path: &Path) -> std::io::Result<()> {
+    let resolved = path.canonicalize()?;
+    if !resolved.starts_with(data_path.canonicalize()?) {
+        error!(
+            "{} resolves to {}, outside of the data directory {}",
+            path.display(),
+            resolved.display(),
+            data_path.display()
+        );
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Path outside of the data directory",
+        ));
+    }
+    Ok(())
+}
+
+/// the name is a single file name, without separators or references to parent folders
+pub fn sanitize_file_name(name: &str) -> std::io::Result<&str> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(component)), None) if component == name => Ok(name),
+        _ => {
+            error!("{:?} is not a valid file name", name);
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid file name",
+            ))
+        }
+    }
+}
+
+/// name of a new day file for the date
+pub fn day_file_name(date: Date) -> crate::error::Result<String> {
+    Ok(date
+        .format(&*BASIC_DATE_FORMAT)
+        .inspect_err(|e| error!("Failed to format date {} for saving: {}", date, e))?
+        + ".json")
+}
+
+/// where a new day file for the date is written
+fn day_file_path(
+    app_config: &AppConfig,
+    data_path: &Path,
+    date: Date,
+) -> crate::error::Result<PathBuf> {
+    let name = day_file_name(date)?;
+    let folder = day_folder(app_config, data_path)?;
+    ensure_inside(data_path, &folder)?;
+    Ok(folder.join(sanitize_file_name(&name)?))
+}
+
+/// folder of the backups taken before day files and the job config are overwritten
+pub fn backup_folder(app_config: &AppConfig, data_path: &Path) -> std::io::Result<PathBuf> {
+    Ok(day_folder(app_config, data_path)?.join(BACKUP_FOLDER))
+}
+
+/// folder day files which cannot be parsed are moved to by `timetrax repair`
+pub fn corrupt_folder(app_config: &AppConfig, data_path: &Path) -> std::io::Result<PathBuf> {
+    Ok(day_folder(app_config, data_path)?.join(CORRUPT_FOLDER))
+}
+
+/// write a file by writing `<name>.tmp` next to it and renaming that over the file once it is
+/// complete and synced, a failed or interrupted write leaves the previous content untouched
+pub fn write_atomic(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> crate::error::Result<()>,
+) -> crate::error::Result<()> {
+    write_through_tmp(path, write, |tmp_path| std::fs::rename(tmp_path, path))
+}
+
+/// like [`write_atomic`], but fails with [`std::io::ErrorKind::AlreadyExists`] instead of
+/// replacing an existing file, the complete temp file is hard linked to `path` which never
+/// clobbers whatever appeared there in the meantime
+pub fn write_atomic_new(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> crate::error::Result<()>,
+) -> crate::error::Result<()> {
+    write_through_tmp(path, write, |tmp_path| {
+        std::fs::hard_link(tmp_path, path)?;
+        std::fs::remove_file(tmp_path)
+    })
+}
+
+/// write the complete, synced `<name>.tmp` of `path` and move it into place with `commit`,
+/// removing the temp file again if anything fails
+fn write_through_tmp(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> crate::error::Result<()>,
+    commit: impl FnOnce(&Path) -> std::io::Result<()>,
+) -> crate::error::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = BufWriter::new(File::create(&tmp_path)?);
+        write(&mut file)?;
+        let file = file.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        commit(&tmp_path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// serialize the day file of `date` into `file`
+fn write_day(file: &mut dyn Write, date: Date, day: &DayInner) -> crate::error::Result<()> {
+    Ok(serde_json::to_writer_pretty(
+        file,
+        &Day {
+            date,
+            inner: day.clone(),
+        },
+    )?)
+}
+
+/// parse the day file at `path`, reading it into `contents`
+fn parse_day_file(path: &Path, contents: &mut String) -> crate::error::Result<Day> {
+    let _timer = profile::timer("parse day files");
+    contents.clear();
+    {
+        let _handle = profile::file_opened();
+        File::open(path).and_then(|mut f| f.read_to_string(contents))?;
+    }
+    profile::count("day files parsed", 1);
+    profile::count("day file bytes parsed", contents.len() as u64);
+    Ok(serde_json::from_str(contents)?)
+}
+
+/// the day stored at `path`, `None` if the file cannot be read or parsed. parse errors are
+/// kept in `error`, intervals ending before they start are logged once `day_boundary` is known
+fn load_day_file(
+    path: &Path,
+    contents: &mut String,
+    error: &OnceCell<serde_json::Error>,
+    day_boundary: Option<Option<Time>>,
+) -> Option<DirtyMarker<DayInner>> {
+    trace!("Loading day file at {}", path.display());
+    match parse_day_file(path, contents) {
+        Err(e) => {
+            warn!("Failed to load day file at {}: {}", path.display(), e);
+            if let Error::Serde(e) = e {
+                let _ = error.set(e);
+            }
+            None
+        }
+        Ok(day) => {
+            let inverted = day_boundary.map(|boundary| day.inner.inverted_intervals(boundary));
+            for problem in inverted.into_iter().flatten() {
+                warn!(
+                    "Day file at {} contains an invalid interval: {} {} {}",
+                    path.display(),
+                    problem.record,
+                    problem.id,
+                    problem.kind
+                );
+            }
+            Some(DirtyMarker::clean(day.inner))
+        }
+    }
+}
+
+/// day boundary of the job config shared by the manager with its days, set once the day limit
+/// is, see [`Manager::set_day_limit`]
+type SharedDayBoundary = Rc<OnceCell<Option<Time>>>;
+
+/// name of the folder of unparsable day files, inside the day folder
+pub const CORRUPT_FOLDER: &str = "corrupt";
+
+/// stands in for day files which cannot be loaded
+static UNREADABLE_DAY: LazyLock<DayInner> = LazyLock::new(DayInner::default);
 
 pub enum AnnotatedDayInformation {
+    /// day file in the day folder, parsed when the day is first used,
+    /// `None` once parsing failed
     OnDisk {
-        day: DirtyMarker<DayInner>,
+        day: OnceCell<Option<DirtyMarker<DayInner>>>,
         origin: PathBuf,
+        /// why parsing the file failed, see [`Manager::load_errors`]
+        error: OnceCell<serde_json::Error>,
+        /// intervals are validated against the boundary when the file is parsed
+        day_boundary: SharedDayBoundary,
     },
-    Unsaved {
-        day: DirtyMarker<DayInner>,
-    },
+    /// day without a file, saved once it is marked dirty. days created by the manager are
+    /// dirty right away, days given to [`Manager::in_memory`] only once they are modified
+    Unsaved { day: DirtyMarker<DayInner> },
 }
 
 impl AnnotatedDayInformation {
     pub fn new(day: DayInner, origin: Option<PathBuf>) -> Self {
         match origin {
             Some(path) => AnnotatedDayInformation::OnDisk {
-                day: DirtyMarker::clean(day),
+                day: OnceCell::from(Some(DirtyMarker::clean(day))),
                 origin: path,
+                error: OnceCell::new(),
+                day_boundary: SharedDayBoundary::default(),
             },
             None => AnnotatedDayInformation::Unsaved {
                 day: DirtyMarker::clean(day),
             },
         }
     }
-    pub fn inner(&self) -> &DayInner {
+    /// day file which is parsed once the day is used
+    fn unloaded(origin: PathBuf, day_boundary: SharedDayBoundary) -> Self {
+        AnnotatedDayInformation::OnDisk {
+            day: OnceCell::new(),
+            origin,
+            error: OnceCell::new(),
+            day_boundary,
+        }
+    }
+    /// the day was parsed already, or never was on disk
+    pub fn is_loaded(&self) -> bool {
         match self {
-            AnnotatedDayInformation::OnDisk { day, .. } => day.deref(),
+            AnnotatedDayInformation::OnDisk { day, .. } => day.get().is_some(),
+            AnnotatedDayInformation::Unsaved { .. } => true,
+        }
+    }
+    /// the day has to be saved
+    pub fn is_dirty(&self) -> bool {
+        match self {
+            AnnotatedDayInformation::OnDisk { day, .. } => day
+                .get()
+                .is_some_and(|day| day.as_ref().is_some_and(|day| day.is_dirty())),
+            AnnotatedDayInformation::Unsaved { day } => day.is_dirty(),
+        }
+    }
+    /// parse the day file if that did not happen yet, reading it into `contents`
+    fn load_with(&self, contents: &mut String) -> &DayInner {
+        match self {
+            AnnotatedDayInformation::OnDisk {
+                day,
+                origin,
+                error,
+                day_boundary,
+            } => day
+                .get_or_init(|| load_day_file(origin, contents, error, day_boundary.get().copied()))
+                .as_deref()
+                .unwrap_or(&UNREADABLE_DAY),
             AnnotatedDayInformation::Unsaved { day } => day.deref(),
         }
     }
+    /// the day, parsing its file on first use. days whose file cannot be parsed are empty
+    pub fn inner(&self) -> &DayInner {
+        self.load_with(&mut String::new())
+    }
+    /// file the day was loaded from, if any
+    pub fn origin(&self) -> Option<&Path> {
+        match self {
+            AnnotatedDayInformation::OnDisk { origin, .. } => Some(origin),
+            AnnotatedDayInformation::Unsaved { .. } => None,
+        }
+    }
+    /// why the day file could not be parsed, only known once the day was used
+    pub fn load_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            AnnotatedDayInformation::OnDisk { error, .. } => error.get(),
+            AnnotatedDayInformation::Unsaved { .. } => None,
+        }
+    }
+    /// the day for modifications, marking it dirty. days whose file cannot be parsed start
+    /// over as new days, which are never saved over the existing file
     pub fn inner_mut(&mut self) -> &mut DayInner {
+        self.inner();
+        if let AnnotatedDayInformation::OnDisk { day, .. } = self
+            && day.get().is_some_and(Option::is_none)
+        {
+            *self = AnnotatedDayInformation::Unsaved {
+                day: DirtyMarker::dirty(DayInner::default()),
+            };
+        }
         match self {
-            AnnotatedDayInformation::OnDisk { day, .. } => &mut **day,
-            AnnotatedDayInformation::Unsaved { day } => &mut **day,
+            AnnotatedDayInformation::OnDisk { day, .. } => day
+                .get_mut()
+                .and_then(Option::as_mut)
+                .expect("day file was loaded"),
+            AnnotatedDayInformation::Unsaved { day } => day,
         }
     }
 }
 
 pub struct Manager<'a> {
-    pub app_config: &'a AppConfig,
-    pub data_path: PathBuf,
+    app_config: &'a AppConfig,
+    data_path: PathBuf,
 
-    pub days: BTreeMap<Date, AnnotatedDayInformation>,
+    days: BTreeMap<Date, AnnotatedDayInformation>,
+    /// files of removed days, deleted on save
+    removed: Vec<PathBuf>,
+
+    /// time of the last save, or of opening the manager
+    last_save: OffsetDateTime,
+    /// refuse to write anything to the data path, e.g. for directories of other people
+    read_only: bool,
+    /// days failing the check are not saved
+    day_limit: Option<DayLimit>,
+    /// boundary of the day limit, shared with the days not loaded yet
+    day_boundary: SharedDayBoundary,
+    /// lock against other instances, held by managers opened for modifications
+    lock: Option<Arc<DataLock>>,
+    /// files not named after their date which could not be parsed to find it
+    unnamed_errors: Vec<(PathBuf, serde_json::Error)>,
+    /// modification time and size of the day files when [`Manager::refresh`] last read them
+    refreshed: HashMap<PathBuf, (SystemTime, u64)>,
 }
 
 impl<'a> Manager<'a> {
     pub fn open_job_config<P: AsRef<Path>>(
         app_config: &'a AppConfig,
         data_path: P,
-    ) -> std::io::Result<JobConfig> {
+    ) -> crate::error::Result<JobConfig> {
         let data_path = data_path.as_ref();
 
         let job_config_path = data_path.join(&app_config.job_config_file_name);
 
         trace!("Opening job config at path: {}", data_path.display());
+        let _timer = profile::timer("load job config");
+        let _handle = profile::file_opened();
         let job = match File::open(&job_config_path) {
             Err(err) => {
                 error!("Failed to open job config file: {}", err);
-                return Err(err);
+                return Err(err.into());
             }
             Ok(file) => {
                 trace!(
@@ -77,11 +380,16 @@ impl<'a> Manager<'a> {
                 let job = match serde_json::from_reader(file) {
                     Err(err) => {
                         error!("Failed to parse job config file: {}", err);
-                        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
+                        return Err(err.into());
                     }
                     Ok(job) => job,
                 };
 
+                if let Err(err) = JobConfig::validate_target_shares(&job) {
+                    error!("Invalid job config: {}", err);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err).into());
+                }
+
                 trace!("Successfully loaded job config.");
                 job
             }
@@ -89,12 +397,89 @@ impl<'a> Manager<'a> {
 
         Ok(job)
     }
-    pub fn open<P: AsRef<Path>>(app_config: &'a AppConfig, data_path: P) -> std::io::Result<Self> {
-        let data_path = data_path.as_ref();
+    pub fn save_job_config<P: AsRef<Path>>(
+        app_config: &AppConfig,
+        data_path: P,
+        job_config: &JobConfig,
+    ) -> crate::error::Result<()> {
+        let job_config_path = data_path.as_ref().join(&app_config.job_config_file_name);
+
+        trace!("Writing job config to {}", job_config_path.display());
+        if let Err(err) = write_atomic(&job_config_path, |file| {
+            Ok(serde_json::to_writer_pretty(file, job_config)?)
+        }) {
+            error!(
+                "Failed to write job config to {}: {}",
+                job_config_path.display(),
+                err
+            );
+            return Err(err);
+        }
+
+        trace!(
+            "Successfully saved job config to {}",
+            job_config_path.display()
+        );
+        Ok(())
+    }
+
+    /// open a data directory for modifications, failing if another instance has it open
+    pub fn open<P: AsRef<Path>>(
+        app_config: &'a AppConfig,
+        data_path: P,
+    ) -> crate::error::Result<Self> {
+        Self::open_waiting(app_config, data_path, std::time::Duration::ZERO)
+    }
+
+    /// open a data directory for modifications, waiting up to `wait` for other instances
+    /// to release it
+    pub fn open_waiting<P: AsRef<Path>>(
+        app_config: &'a AppConfig,
+        data_path: P,
+        wait: std::time::Duration,
+    ) -> crate::error::Result<Self> {
+        Self::open_with(app_config, data_path.as_ref(), Some(wait))
+    }
+
+    /// open a data directory without ever writing to it or locking it,
+    /// saving fails if days were modified
+    pub fn open_read_only<P: AsRef<Path>>(
+        app_config: &'a AppConfig,
+        data_path: P,
+    ) -> crate::error::Result<Self> {
+        Self::open_with(app_config, data_path.as_ref(), None)
+    }
+
+    /// open for modifications without locking the data directory, for tests writing through
+    /// other managers at the same time
+    #[cfg(test)]
+    pub(crate) fn open_unlocked<P: AsRef<Path>>(
+        app_config: &'a AppConfig,
+        data_path: P,
+    ) -> crate::error::Result<Self> {
+        let mut manager = Self::open_read_only(app_config, data_path)?;
+        manager.read_only = false;
+        Ok(manager)
+    }
+
+    /// read-only without `lock_wait`
+    fn open_with(
+        app_config: &'a AppConfig,
+        data_path: &Path,
+        lock_wait: Option<std::time::Duration>,
+    ) -> crate::error::Result<Self> {
+        let read_only = lock_wait.is_none();
+        let _timer = profile::timer("scan data directory");
 
         let mut days = BTreeMap::new();
-        let day_folder_path = data_path.join(&app_config.job_day_folder_format);
+        let day_folder_path = day_folder(app_config, data_path)?;
 
+        if read_only && !day_folder_path.exists() {
+            error!("Day folder does not exist at {}", day_folder_path.display());
+            return Err(
+                std::io::Error::new(std::io::ErrorKind::NotFound, "Day folder not found").into(),
+            );
+        }
         if !day_folder_path.exists() {
             trace!(
                 "Day folder path does not exist at {}, creating it.",
@@ -106,10 +491,19 @@ impl<'a> Manager<'a> {
                     day_folder_path.display(),
                     err
                 );
-                return Err(err);
+                return Err(err.into());
             }
         }
+        ensure_inside(data_path, &day_folder_path)?;
+        let lock = match lock_wait {
+            Some(wait) => Some(Arc::new(DataLock::acquire(data_path, wait)?)),
+            None => None,
+        };
 
+        // only the listing, day files are parsed once their day is used
+        let mut contents = String::new();
+        let mut unnamed_errors = Vec::new();
+        let day_boundary = SharedDayBoundary::default();
         for day_file in std::fs::read_dir(&day_folder_path)? {
             let day_file = match day_file {
                 Err(e) => {
@@ -123,29 +517,53 @@ impl<'a> Manager<'a> {
                 Ok(entry) => entry,
             };
             let path = day_file.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                trace!("Loading day file at {}", path.display());
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let named = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| Date::parse(stem, &*BASIC_DATE_FORMAT).ok());
+            if let Some(date) = named {
+                trace!("Found day file for date {} at {}", date, path.display());
+                let day = AnnotatedDayInformation::unloaded(path, day_boundary.clone());
+                days.insert(date, day);
+                continue;
+            }
 
-                let file = match File::open(&path) {
-                    Err(e) => {
-                        warn!("Failed to open day file at {}: {}", path.display(), e);
-                        continue;
-                    }
-                    Ok(f) => f,
-                };
-                let day: Day = match serde_json::from_reader(file) {
-                    Err(e) => {
-                        warn!("Failed to parse day file at {}: {}", path.display(), e);
-                        continue;
-                    }
-                    Ok(d) => d,
-                };
+            // only the contents tell the date of files named otherwise
+            match parse_day_file(&path, &mut contents) {
+                Err(Error::Serde(e)) => {
+                    warn!("Failed to load day file at {}: {}", path.display(), e);
+                    unnamed_errors.push((path, e));
+                }
+                Err(e) => warn!("Failed to load day file at {}: {}", path.display(), e),
+                Ok(day) => {
+                    trace!("Successfully loaded day for date {}", day.date);
+                    days.insert(
+                        day.date,
+                        AnnotatedDayInformation::new(day.inner, Some(path)),
+                    );
+                }
+            }
+        }
 
-                trace!("Successfully loaded day for date {}", day.date);
-                days.insert(
-                    day.date,
-                    AnnotatedDayInformation::new(day.inner, Some(path)),
-                );
+        // unknown fields are only found by parsing, strict parsing loads every day up front
+        if app_config.strict_parsing {
+            for day in days.values() {
+                let unknown = day.load_with(&mut contents).unknown_fields();
+                if !unknown.is_empty() {
+                    error!(
+                        "Day file at {} contains unknown fields: {}",
+                        day.origin().unwrap_or(Path::new("?")).display(),
+                        unknown.join(", ")
+                    );
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Unknown fields in day file",
+                    )
+                    .into());
+                }
             }
         }
 
@@ -153,105 +571,394 @@ impl<'a> Manager<'a> {
             days,
             app_config,
             data_path: data_path.to_path_buf(),
+            last_save: local_time::now(),
+            removed: Vec::new(),
+            read_only,
+            day_limit: None,
+            day_boundary,
+            lock,
+            unnamed_errors,
+            refreshed: HashMap::new(),
         })
     }
 
-    pub fn save(&mut self) -> std::io::Result<()> {
-        let mut error = None;
+    /// read-only manager holding the given days in memory, for commands which do not use the
+    /// data directory and library users computing over days from elsewhere
+    pub fn in_memory(app_config: &'a AppConfig, days: impl IntoIterator<Item = Day>) -> Self {
+        Manager {
+            app_config,
+            data_path: PathBuf::new(),
+            days: days
+                .into_iter()
+                .map(|day| (day.date, AnnotatedDayInformation::new(day.inner, None)))
+                .collect(),
+            removed: Vec::new(),
+            last_save: local_time::now(),
+            read_only: true,
+            day_limit: None,
+            day_boundary: SharedDayBoundary::default(),
+            lock: None,
+            unnamed_errors: Vec::new(),
+            refreshed: HashMap::new(),
+        }
+    }
+
+    /// read-only manager without any days, see [`Manager::in_memory`]
+    pub fn empty(app_config: &'a AppConfig) -> Self {
+        Self::in_memory(app_config, [])
+    }
+
+    pub fn app_config(&self) -> &'a AppConfig {
+        self.app_config
+    }
+
+    /// the data directory, empty for managers held in memory
+    pub fn data_path(&self) -> &Path {
+        &self.data_path
+    }
+
+    /// see [`Manager::open_read_only`]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// lock of the data directory, callers writing other files of the directory after the
+    /// manager is dropped keep a clone until they are done
+    pub fn lock(&self) -> Option<Arc<DataLock>> {
+        self.lock.clone()
+    }
+
+    /// check days against the limit before saving them. day files parsed from now on have
+    /// their intervals validated against the boundary of the limit
+    pub fn set_day_limit(&mut self, day_limit: DayLimit) {
+        let _ = self.day_boundary.set(day_limit.boundary);
+        self.day_limit = Some(day_limit);
+    }
+
+    /// dates of the days, in order and without loading the days
+    pub fn dates(&self) -> impl DoubleEndedIterator<Item = Date> + '_ {
+        self.days.keys().copied()
+    }
+
+    /// the manager holds a day of the date
+    pub fn contains_day(&self, date: Date) -> bool {
+        self.days.contains_key(&date)
+    }
+
+    /// the days in order of their dates
+    pub fn days(&self) -> impl DoubleEndedIterator<Item = (&Date, &DayInner)> + Clone {
+        self.days.iter().map(|(date, day)| (date, day.inner()))
+    }
+
+    /// the days within the range, in order of their dates
+    pub fn days_in(
+        &self,
+        range: impl RangeBounds<Date>,
+    ) -> impl DoubleEndedIterator<Item = (&Date, &DayInner)> + Clone {
+        self.days
+            .range(range)
+            .map(|(date, day)| (date, day.inner()))
+    }
+
+    /// the days within the range with the files they are stored in
+    pub fn annotated_days_in(
+        &self,
+        range: impl RangeBounds<Date>,
+    ) -> impl DoubleEndedIterator<Item = (&Date, &AnnotatedDayInformation)> + Clone {
+        self.days.range(range)
+    }
+
+    pub fn day(&self, date: Date) -> Option<&DayInner> {
+        self.days.get(&date).map(AnnotatedDayInformation::inner)
+    }
+
+    /// the day with the file it is stored in
+    pub fn annotated_day(&self, date: Date) -> Option<&AnnotatedDayInformation> {
+        self.days.get(&date)
+    }
+
+    /// the day with the file it is stored in, only [`AnnotatedDayInformation::inner_mut`]
+    /// marks it dirty
+    pub fn annotated_day_mut(&mut self, date: Date) -> Option<&mut AnnotatedDayInformation> {
+        self.days.get_mut(&date)
+    }
+
+    /// remove the day, its file is deleted on save
+    pub fn remove_day(&mut self, date: Date) -> Option<DayInner> {
+        let day = self.days.remove(&date)?;
+        let inner = day.inner().clone();
+        if let Some(origin) = day.origin() {
+            self.removed.push(origin.to_path_buf());
+        }
+        Some(inner)
+    }
+
+    /// parse the day files from `from` to `to` up front sharing one buffer,
+    /// for commands going through many days such as reports
+    pub fn load_range(&self, from: Date, to: Date) {
+        if from > to {
+            return;
+        }
+        let _timer = profile::timer("load day range");
+        let mut contents = String::new();
+        for day in self.days.range(from..=to).map(|(_, day)| day) {
+            day.load_with(&mut contents);
+        }
+    }
+
+    /// days were modified or created since the last save
+    pub fn is_dirty(&self) -> bool {
+        !self.removed.is_empty() || self.days.values().any(AnnotatedDayInformation::is_dirty)
+    }
+
+    /// save the modified days if the last save is at least `max_interval` ago,
+    /// or right away if `flush_on_mutation` is configured. returns true if it saved
+    ///
+    /// long-running modes holding the manager open call this from their loops
+    pub fn autosave_if_due(&mut self, max_interval: Duration) -> crate::error::Result<bool> {
+        if !self.is_dirty() {
+            return Ok(false);
+        }
+        if !self.app_config.flush_on_mutation && local_time::now() - self.last_save < max_interval {
+            return Ok(false);
+        }
+
+        let started = std::time::Instant::now();
+        self.save()?;
+        let elapsed = started.elapsed();
+        if elapsed > std::time::Duration::from_millis(200) {
+            warn!("Autosave took {} ms", elapsed.as_millis());
+        } else {
+            info!("Autosave took {} ms", elapsed.as_millis());
+        }
+        Ok(true)
+    }
+
+    pub fn save(&mut self) -> crate::error::Result<()> {
+        if self.read_only {
+            if self.is_dirty() {
+                error!(
+                    "Refusing to save modified days to the read-only data directory {}",
+                    self.data_path.display()
+                );
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    "Data directory is read-only",
+                )
+                .into());
+            }
+            return Ok(());
+        }
+
+        let _timer = profile::timer("save days");
+        let mut error: Option<Error> = None;
+        self.last_save = local_time::now();
+
+        for origin in std::mem::take(&mut self.removed) {
+            trace!("Deleting the file {} of a removed day", origin.display());
+            let backup = if self.app_config.backup_on_save && origin.exists() {
+                backup_folder(self.app_config, &self.data_path)
+                    .and_then(|folder| backup_file(&origin, &folder, self.app_config.backup_keep))
+                    .map(|_| ())
+            } else {
+                Ok(())
+            };
+            let result = backup.and_then(|()| match std::fs::remove_file(&origin) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            });
+            if let Err(e) = result {
+                error!("Failed to delete the day file {}: {}", origin.display(), e);
+                error = Some(e.into());
+                self.removed.push(origin);
+            }
+        }
+
+        if let Some(cutoff) = self.last_save.checked_sub(self.app_config.trash_retention) {
+            for (date, day) in self.days.iter_mut() {
+                // days which were never loaded are purged once they are used, locked days
+                // keep their trash
+                if day.is_loaded()
+                    && !day.inner().locked
+                    && day.inner().trash.iter().any(|entry| entry.expired(cutoff))
+                {
+                    let purged = day.inner_mut().purge_trash(cutoff);
+                    info!("Purged {} expired trash entries of {}", purged, date);
+                }
+            }
+        }
 
         for (date, day_boxed) in self.days.iter_mut() {
-            if let AnnotatedDayInformation::OnDisk { day, origin } = day_boxed {
-                if day.is_dirty() {
+            if day_boxed.is_dirty()
+                && let Some(limit) = &self.day_limit
+                && let Err(e) = limit.check(*date, day_boxed.inner())
+            {
+                if day_boxed.inner().locked {
+                    warn!("{}, saving the locked day anyway", e);
+                } else if limit.force {
+                    warn!("{}, saving anyway (--force-save)", e);
+                } else {
+                    e.log();
+                    error!(
+                        "Not saving {}, fix its activities or use --force-save",
+                        date
+                    );
+                    error = Some(std::io::Error::from(e).into());
+                    continue;
+                }
+            }
+
+            if let AnnotatedDayInformation::OnDisk { day, origin, .. } = day_boxed {
+                if let Some(Some(day)) = day.get_mut()
+                    && day.is_dirty()
+                {
                     trace!(
                         "Saving modified day for date {} to {}",
                         date,
                         origin.display()
                     );
-                    let file = match File::create(&origin) {
-                        Err(e) => {
-                            error!(
-                                "Failed to open day file for writing at {}: {}",
-                                origin.display(),
-                                e
-                            );
-                            error = Some(e);
-                            continue;
-                        }
-                        Ok(f) => f,
-                    };
-
-                    if let Err(e) = serde_json::to_writer_pretty(
-                        file,
-                        &Day {
-                            date: *date,
-                            inner: day.inner.clone(),
-                        },
-                    ) {
+                    if self.app_config.backup_on_save
+                        && origin.exists()
+                        && let Err(e) =
+                            backup_folder(self.app_config, &self.data_path).and_then(|folder| {
+                                backup_file(origin, &folder, self.app_config.backup_keep)
+                            })
+                    {
+                        error!(
+                            "Not saving {}, failed to back up {}: {}",
+                            date,
+                            origin.display(),
+                            e
+                        );
+                        error = Some(e.into());
+                        continue;
+                    }
+                    let _handle = profile::file_opened();
+                    if let Err(e) = write_atomic(origin, |file| write_day(file, *date, &day.inner))
+                    {
                         error!("Failed to write day file at {}: {}", origin.display(), e);
-                        error = Some(std::io::Error::new(std::io::ErrorKind::Other, e));
+                        error = Some(e);
                         continue;
                     }
 
                     day.mark_clean()
                 }
-            } else if let AnnotatedDayInformation::Unsaved { day } = day_boxed {
-                let date_format = match date.format(&*BASIC_DATE_FORMAT) {
+            } else if let AnnotatedDayInformation::Unsaved { day } = day_boxed
+                && day.is_dirty()
+            {
+                let day_path = match day_file_path(self.app_config, &self.data_path, *date) {
                     Err(e) => {
-                        error!("Failed to format date {} for saving: {}", date, e);
-                        error = Some(std::io::Error::new(std::io::ErrorKind::Other, e));
+                        error = Some(e);
                         continue;
                     }
-                    Ok(f) => f,
+                    Ok(path) => path,
                 };
-                let day_path = self
-                    .data_path
-                    .join(self.app_config.job_day_folder_format.as_str())
-                    .join(date_format)
-                    .with_extension("json");
 
                 trace!("Saving new day for date {} to {}", date, day_path.display());
 
-                let file = match File::create_new(&day_path) {
-                    Err(e) => {
-                        error!(
-                            "Failed to open day file for writing at {}: {}",
-                            day_path.display(),
-                            e
-                        );
-                        error = Some(e);
-                        continue;
-                    }
-                    Ok(f) => f,
-                };
-
-                if let Err(e) = serde_json::to_writer_pretty(
-                    file,
-                    &Day {
-                        date: *date,
-                        inner: day.inner.clone(),
-                    },
-                ) {
+                let _handle = profile::file_opened();
+                // never replace a day file this manager did not load
+                if let Err(e) = write_atomic_new(&day_path, |file| write_day(file, *date, day)) {
                     error!("Failed to write day file at {}: {}", day_path.display(), e);
-                    error = Some(std::io::Error::new(std::io::ErrorKind::Other, e));
+                    error = Some(e);
                     continue;
                 }
 
-                *day_boxed = AnnotatedDayInformation::OnDisk {
-                    day: DirtyMarker::clean(day.clone()),
-                    origin: day_path,
-                };
+                *day_boxed = AnnotatedDayInformation::new(day.inner.clone(), Some(day_path));
             }
         }
 
         if let Some(e) = error { Err(e) } else { Ok(()) }
     }
 
-    pub fn get_or_create_day(&mut self, date: Date) -> &mut AnnotatedDayInformation {
-        self.days.entry(date).or_insert_with(|| {
-            let x = AnnotatedDayInformation::new(DayInner::default(), None);
+    /// save days failing the day limit with a warning, for commands given `--force`
+    pub fn force_day_limit(&mut self) {
+        if let Some(limit) = &mut self.day_limit {
+            limit.force = true;
+        }
+    }
 
-            x
-        })
+    /// re-read the day files another process wrote since they were loaded, e.g. a CLI `push`
+    /// while this manager is held open, and load day files created in the meantime. days
+    /// modified or removed here since the last save are kept as they are. returns the refreshed
+    /// dates
+    ///
+    /// long-running holders keep a read-only manager which they refresh before reading days, and
+    /// take the lock through a short-lived manager only around mutations, so that neither they
+    /// nor other processes overwrite the changes of the other
+    pub fn refresh(&mut self) -> std::io::Result<Vec<Date>> {
+        let _timer = profile::timer("refresh day files");
+        let day_folder_path = day_folder(self.app_config, &self.data_path)?;
+        ensure_inside(&self.data_path, &day_folder_path)?;
+        let mut refreshed = vec![];
+
+        let origins = (self.days.iter())
+            .filter_map(|(date, day)| Some((day.origin()?.to_path_buf(), *date)))
+            .collect::<HashMap<_, _>>();
+        let mut contents = String::new();
+        for day_file in std::fs::read_dir(&day_folder_path)? {
+            let day_file = day_file?;
+            let path = day_file.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            // days removed here stay removed until the next save deletes their files
+            if self.removed.contains(&path) {
+                continue;
+            }
+            let known = origins.get(&path).and_then(|date| self.days.get(date));
+            // days not parsed yet read the current file once they are used
+            if known.is_some_and(|day| !day.is_loaded()) {
+                continue;
+            }
+            // files are replaced on every write, unchanged files keep their time and size
+            let stamp = day_file
+                .metadata()
+                .ok()
+                .and_then(|meta| Some((meta.modified().ok()?, meta.len())));
+            if known.is_some() && stamp.is_some() && self.refreshed.get(&path) == stamp.as_ref() {
+                continue;
+            }
+            if let Some(stamp) = stamp {
+                self.refreshed.insert(path.clone(), stamp);
+            }
+            let day = match parse_day_file(&path, &mut contents) {
+                Err(e) => {
+                    warn!("Failed to refresh day file at {}: {}", path.display(), e);
+                    continue;
+                }
+                Ok(day) => day,
+            };
+
+            if let Some(known) = self.days.get(&day.date) {
+                if known.is_dirty() {
+                    trace!("Keeping the modified day {} over its file", day.date);
+                    continue;
+                }
+                let unchanged = known.origin().is_some()
+                    && serde_json::to_value(known.inner()).ok()
+                        == serde_json::to_value(&day.inner).ok();
+                if unchanged {
+                    continue;
+                }
+            }
+
+            trace!("Refreshed day {} from {}", day.date, path.display());
+            refreshed.push(day.date);
+            self.days.insert(
+                day.date,
+                AnnotatedDayInformation::new(day.inner, Some(path)),
+            );
+        }
+        Ok(refreshed)
+    }
+
+    pub fn get_or_create_day(&mut self, date: Date) -> &mut AnnotatedDayInformation {
+        self.days
+            .entry(date)
+            .or_insert_with(|| AnnotatedDayInformation::Unsaved {
+                day: DirtyMarker::dirty(DayInner::default()),
+            })
     }
 
     pub fn get_or_create_day_ref(&mut self, date: Date) -> &DayInner {
@@ -261,6 +968,146 @@ impl<'a> Manager<'a> {
     pub fn get_or_create_day_mut(&mut self, date: Date) -> &mut DayInner {
         self.get_or_create_day(date).inner_mut()
     }
+
+    /// date of the day containing the activity, only looking at `date` if given,
+    /// otherwise using the most recent day on which the identifier matches
+    pub fn locate_activity(
+        &self,
+        identifier: &Identifier,
+        date: Option<Date>,
+    ) -> Result<Date, ActivityLookupError> {
+        let not_found = || ActivityLookupError::NotFound(identifier.clone());
+        match date {
+            Some(date) => {
+                let day = self.days.get(&date).ok_or_else(not_found)?;
+                day.inner().find_activity(identifier).map(|_| date)
+            }
+            None => self
+                .days
+                .iter()
+                .rev()
+                .map(|(date, day)| (date, day.inner().find_activity(identifier)))
+                .find(|(_, result)| !matches!(result, Err(ActivityLookupError::NotFound(_))))
+                .map(|(date, result)| result.map(|_| *date))
+                .unwrap_or_else(|| Err(not_found())),
+        }
+    }
+
+    /// day files which could not be parsed, of the days used so far and of files not named
+    /// after their date. [`Manager::load_range`] finds the errors of the days not used yet
+    pub fn load_errors(&self) -> Vec<(&Path, &serde_json::Error)> {
+        let unnamed = (self.unnamed_errors.iter()).map(|(path, error)| (path.as_path(), error));
+        let days = self
+            .days
+            .values()
+            .filter_map(|day| Some((day.origin()?, day.load_error()?)));
+        unnamed.chain(days).collect()
+    }
+
+    /// log a warning for every problem of every day, see [`DayInner::problems`], returning
+    /// how many were found. used with `check_on_load` right after opening the data directory
+    pub fn warn_problems(&self, job_config: &JobConfig) -> usize {
+        let _timer = profile::timer("check days");
+        let mut count = 0;
+        for (date, day) in self.days() {
+            for problem in day.problems(job_config) {
+                warn!("{}: {}", date, problem);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// most recent day before `date` containing activities
+    pub fn previous_day_with_data(&self, date: Date) -> Option<(Date, &DayInner)> {
+        self.days
+            .range(..date)
+            .rev()
+            .map(|(date, day)| (*date, day.inner()))
+            .find(|(_, day)| !day.activities.is_empty())
+    }
+
+    /// activities of days before `today` which were never stopped
+    pub fn stale_open_activities(&self, today: Date) -> Vec<(Date, &Activity)> {
+        self.days
+            .range(..today)
+            .flat_map(|(date, day)| {
+                day.inner()
+                    .activities
+                    .iter()
+                    .filter(|activity| !activity.time.is_complete())
+                    .map(move |activity| (*date, activity))
+            })
+            .collect()
+    }
+
+    /// stale open activities of the most recent day with data before `today`,
+    /// found without loading all previous days
+    pub fn recent_stale_open_activities(&self, today: Date) -> Vec<(Date, &Activity)> {
+        self.previous_day_with_data(today)
+            .into_iter()
+            .flat_map(|(date, day)| {
+                day.activities
+                    .iter()
+                    .filter(|activity| !activity.time.is_complete())
+                    .map(move |activity| (date, activity))
+            })
+            .collect()
+    }
+
+    /// stop the activities found by [`Manager::stale_open_activities`] at `end` of their day,
+    /// returns the stopped activities. activities starting after `end` are left open
+    pub fn close_stale_activities(
+        &mut self,
+        today: Date,
+        end: Time,
+        boundary: Option<Time>,
+    ) -> Vec<(Date, Activity)> {
+        let mut closed = Vec::new();
+        for (date, day) in self.days.range_mut(..today) {
+            // only touch days with open activities, touching marks them dirty
+            if day.inner().activities.iter().all(|a| a.time.is_complete()) {
+                continue;
+            }
+            for activity in day
+                .inner_mut()
+                .activities
+                .iter_mut()
+                .filter(|activity| !activity.time.is_complete())
+            {
+                if local_time::to_day_time(end, boundary)
+                    < local_time::to_day_time(activity.time.start, boundary)
+                {
+                    warn!(
+                        "Not closing the activity of {} at {}, it starts later: {}",
+                        date, end, activity
+                    );
+                    continue;
+                }
+                info!("Closing stale activity of {}: {:?}", date, activity);
+                activity.time.complete_at(end, boundary);
+                closed.push((*date, activity.clone()));
+            }
+        }
+        closed
+    }
+
+    /// date of the day containing the blocker, only looking at `date` if given
+    pub fn locate_blocker(&self, id: Uuid, date: Option<Date>) -> Option<Date> {
+        match date {
+            Some(date) => self
+                .days
+                .get(&date)
+                .and_then(|day| day.inner().find_blocker(id))
+                .map(|_| date),
+            None => self
+                .days
+                .iter()
+                .rev()
+                .find(|(_, day)| day.inner().find_blocker(id).is_some())
+                .map(|(date, _)| *date),
+        }
+    }
 }
 
 impl<'a> Drop for Manager<'a> {
@@ -270,3 +1117,497 @@ impl<'a> Drop for Manager<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandPush;
+    use crate::testing::{ManualClock, TempDataDir, at};
+    use clap::Parser;
+    use time::{Month, Time};
+
+    #[test]
+    fn test_corrupt_job_config() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = dir.path().join(&dir.app_config().job_config_file_name);
+        std::fs::write(&path, "{\"classes\": [").unwrap();
+
+        let result = Manager::open_job_config(dir.app_config(), dir.path());
+        assert!(matches!(result, Err(Error::Serde(_))));
+        assert_eq!(result.unwrap_err().exit_code(), 65);
+    }
+
+    /// fails every write once `left` bytes were written
+    struct PoisonedWriter<'a> {
+        inner: &'a mut dyn Write,
+        left: usize,
+    }
+
+    impl Write for PoisonedWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.left == 0 {
+                return Err(std::io::Error::other("poisoned"));
+            }
+            let n = buf.len().min(self.left);
+            self.left -= n;
+            self.inner.write(&buf[..n])
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    #[test]
+    fn test_failed_write_keeps_the_original() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = dir.path().join(&dir.app_config().job_config_file_name);
+        let original = std::fs::read_to_string(&path).unwrap();
+        let tmp_path = dir
+            .path()
+            .join(format!("{}.tmp", dir.app_config().job_config_file_name));
+
+        let job_config = JobConfig {
+            unique_names_per_day: true,
+            ..JobConfig::default()
+        };
+        let result = write_atomic(&path, |file| {
+            let writer = PoisonedWriter {
+                inner: file,
+                left: 8,
+            };
+            Ok(serde_json::to_writer_pretty(writer, &job_config)?)
+        });
+        assert!(matches!(result, Err(Error::Serde(_))));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+        assert!(!tmp_path.exists());
+
+        Manager::save_job_config(dir.app_config(), dir.path(), &job_config).unwrap();
+        assert_ne!(std::fs::read_to_string(&path).unwrap(), original);
+        assert!(!tmp_path.exists());
+    }
+
+    #[test]
+    fn test_new_day_never_replaces_an_existing_file() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let mut manager = dir.manager().unwrap();
+        manager.get_or_create_day_mut(date).locked = true;
+
+        // another process creates the day file after this manager looked for it
+        let path = dir.write_day_file("2024-05-03.json", "{}").unwrap();
+        let result = manager.save();
+        assert!(
+            matches!(&result, Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists)
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        assert!(!path.with_file_name("2024-05-03.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_second_writer_is_locked_out() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let first = dir.manager().unwrap();
+
+        let started = std::time::Instant::now();
+        let second = Manager::open(dir.app_config(), dir.path());
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        match second {
+            Err(e @ Error::Locked(Some(pid))) => {
+                assert_eq!(pid, std::process::id());
+                assert_eq!(e.exit_code(), 75);
+                assert!(e.to_string().contains(&pid.to_string()));
+            }
+            _ => panic!("second mutating open was not refused"),
+        }
+        assert!(Manager::open_read_only(dir.app_config(), dir.path()).is_ok());
+
+        // a waiting open gets the lock once the first manager is dropped
+        std::thread::scope(|scope| {
+            let waiting = scope.spawn(|| {
+                Manager::open_waiting(
+                    dir.app_config(),
+                    dir.path(),
+                    std::time::Duration::from_secs(10),
+                )
+                .map(|_| ())
+            });
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            drop(first);
+            waiting.join().unwrap().unwrap();
+        });
+        assert!(dir.manager().is_ok());
+    }
+
+    #[test]
+    fn test_unreadable_day_file_is_never_overwritten() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = dir
+            .write_day_file("2024-05-02.json", "{\"date\": ")
+            .unwrap();
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+
+        let mut manager = dir.manager().unwrap();
+        assert!(!manager.days[&date].is_loaded());
+        assert!(manager.days[&date].inner().activities.is_empty());
+        assert!(!manager.is_dirty());
+        manager.get_or_create_day_mut(date).locked = true;
+        assert_eq!(
+            manager.save().unwrap_err().kind(),
+            std::io::ErrorKind::AlreadyExists
+        );
+        drop(manager);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "{\"date\": ");
+    }
+
+    #[test]
+    fn test_days_iterate_in_date_order() {
+        let config = AppConfig::default();
+        let may = |day| Date::from_calendar_date(2024, Month::May, day).unwrap();
+        let manager = Manager::in_memory(
+            &config,
+            [may(3), may(1), may(2)].map(|date| Day {
+                date,
+                inner: DayInner::default(),
+            }),
+        );
+        let dates = manager.days().map(|(date, _)| *date).collect::<Vec<_>>();
+        assert_eq!(dates, [may(1), may(2), may(3)]);
+        assert_eq!(
+            manager.dates().rev().collect::<Vec<_>>(),
+            [may(3), may(2), may(1)]
+        );
+        let within = manager.days_in(may(2)..).map(|(date, _)| *date);
+        assert_eq!(within.collect::<Vec<_>>(), [may(2), may(3)]);
+        assert!(!manager.is_dirty());
+    }
+
+    #[test]
+    fn test_removed_day_stays_removed() {
+        let date = Date::from_calendar_date(2024, Month::May, 2).unwrap();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = dir
+            .write_day_file(
+                "2024-05-02.json",
+                r#"{"date": "2024-05-02", "locked": true}"#,
+            )
+            .unwrap();
+
+        let mut manager = dir.manager().unwrap();
+        assert!(manager.remove_day(date).unwrap().locked);
+        assert!(!manager.contains_day(date));
+        assert!(manager.remove_day(date).is_none());
+        assert!(manager.is_dirty());
+        manager.save().unwrap();
+        drop(manager);
+
+        assert!(!path.exists());
+        assert!(!dir.manager().unwrap().contains_day(date));
+    }
+
+    #[test]
+    fn test_autosave_cadence() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let clock = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap()));
+        let _guard = clock.install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let interval = Duration::minutes(1);
+
+        let mut manager = dir.manager().unwrap();
+        // clean managers are never saved
+        clock.advance(Duration::minutes(5));
+        assert!(!manager.autosave_if_due(interval).unwrap());
+
+        manager.get_or_create_day_mut(date).locked = true;
+        assert!(manager.autosave_if_due(interval).unwrap());
+        assert!(dir.day(date).unwrap().unwrap().locked);
+
+        manager.get_or_create_day_mut(date).locked = false;
+        clock.advance(Duration::seconds(30));
+        assert!(!manager.autosave_if_due(interval).unwrap());
+        assert!(dir.day(date).unwrap().unwrap().locked);
+
+        clock.advance(Duration::seconds(30));
+        assert!(manager.autosave_if_due(interval).unwrap());
+        assert!(!dir.day(date).unwrap().unwrap().locked);
+
+        clock.advance(Duration::minutes(5));
+        assert!(!manager.autosave_if_due(interval).unwrap());
+    }
+
+    #[test]
+    fn test_read_only_manager_never_writes() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        // creates the day folder
+        dir.manager().unwrap();
+
+        {
+            let mut manager = Manager::open_read_only(dir.app_config(), dir.path()).unwrap();
+            assert!(manager.save().is_ok());
+            manager.get_or_create_day_mut(date).locked = true;
+            assert_eq!(
+                manager.save().unwrap_err().kind(),
+                std::io::ErrorKind::PermissionDenied
+            );
+        }
+        assert!(dir.day(date).unwrap().is_none());
+
+        let missing = dir.path().join("missing");
+        assert!(Manager::open_read_only(dir.app_config(), &missing).is_err());
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_days_over_the_limit_are_not_saved() {
+        let may = |day| Date::from_calendar_date(2024, Month::May, day).unwrap();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        // grandfathered: locked before the check existed
+        dir.write_day_file(
+            "2024-05-02.json",
+            r#"{"date": "2024-05-02", "locked": true, "activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+                 "time": {"start": "00:00:00", "end": "23:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000002", "class": "@work",
+                 "time": {"start": "09:00:00", "end": "11:00:00"}}
+            ]}"#,
+        )
+        .unwrap();
+        let job_config = dir.job_config().unwrap();
+        let over = dir.day(may(2)).unwrap().unwrap().activities;
+
+        let mut manager = dir.manager().unwrap();
+        manager.day_limit = Some(DayLimit {
+            cap: Duration::hours(20),
+            ..DayLimit::new(dir.app_config(), &job_config)
+        });
+        manager.days.get_mut(&may(2)).unwrap().inner_mut();
+        manager.get_or_create_day_mut(may(3)).activities = over.clone();
+        manager.get_or_create_day_mut(may(6)).activities = over[1..].to_vec();
+        assert_eq!(
+            manager.save().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        assert!(dir.day(may(3)).unwrap().is_none());
+        assert_eq!(dir.day(may(6)).unwrap().unwrap().activities.len(), 1);
+
+        manager.force_day_limit();
+        manager.save().unwrap();
+        drop(manager);
+        assert_eq!(dir.day(may(3)).unwrap().unwrap().activities.len(), 2);
+    }
+
+    #[test]
+    fn test_autosave_flush_on_mutation() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let _guard = ManualClock::new(at(date, Time::from_hms(9, 0, 0).unwrap())).install();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let app_config = AppConfig {
+            flush_on_mutation: true,
+            ..dir.app_config().clone()
+        };
+
+        let mut manager = Manager::open(&app_config, dir.path()).unwrap();
+        assert!(!manager.autosave_if_due(Duration::hours(1)).unwrap());
+        manager.get_or_create_day_mut(date).locked = true;
+        assert!(manager.autosave_if_due(Duration::hours(1)).unwrap());
+        assert!(dir.day(date).unwrap().unwrap().locked);
+    }
+
+    #[test]
+    fn test_refresh_loses_no_writes() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let push = |class: &str| {
+            dir.execute(&CommandPush::parse_from(["push", "-c", class]))
+                .unwrap()
+        };
+        // held open the way a long-running mode does
+        let mut held = Manager::open_unlocked(dir.app_config(), dir.path()).unwrap();
+        assert!(held.refresh().unwrap().is_empty());
+
+        push("@work");
+        let date = held.refresh().unwrap()[0];
+        assert_eq!(held.days[&date].inner().activities.len(), 1);
+        assert!(held.refresh().unwrap().is_empty());
+
+        // mutate right after refreshing and save right away
+        held.get_or_create_day_mut(date)
+            .extra
+            .insert("synced_by".into(), "held".into());
+        held.save().unwrap();
+        push("@break");
+        assert_eq!(held.refresh().unwrap(), [date]);
+        assert_eq!(held.days[&date].inner().activities.len(), 2);
+
+        // modified days are kept until they are saved
+        held.get_or_create_day_mut(date).locked = true;
+        assert!(held.refresh().unwrap().is_empty());
+        drop(held);
+
+        let day = dir.day(date).unwrap().unwrap();
+        assert_eq!(day.activities.len(), 2);
+        assert_eq!(day.extra["synced_by"], "held");
+        assert!(day.locked);
+    }
+
+    #[test]
+    fn test_refresh_of_a_read_only_manager() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let push = |class: &str| {
+            dir.execute(&CommandPush::parse_from(["push", "-c", class]))
+                .unwrap()
+        };
+        // held open the way a long-running mode does, taking the lock only to mutate
+        let mut held = Manager::open_read_only(dir.app_config(), dir.path()).unwrap();
+        assert!(held.refresh().unwrap().is_empty());
+
+        push("@work");
+        let date = held.refresh().unwrap()[0];
+        assert_eq!(held.days[&date].inner().activities.len(), 1);
+        assert!(held.refresh().unwrap().is_empty());
+
+        dir.manager()
+            .unwrap()
+            .get_or_create_day_mut(date)
+            .extra
+            .insert("synced_by".into(), "held".into());
+        push("@break");
+        assert_eq!(held.refresh().unwrap(), [date]);
+        assert_eq!(held.days[&date].inner().activities.len(), 2);
+        assert_eq!(held.days[&date].inner().extra["synced_by"], "held");
+
+        // modified and removed days are kept as they are
+        held.get_or_create_day_mut(date).locked = true;
+        assert!(held.refresh().unwrap().is_empty());
+        assert!(held.days[&date].inner().locked);
+        held.remove_day(date);
+        assert!(held.refresh().unwrap().is_empty());
+        assert!(held.day(date).is_none());
+    }
+
+    #[test]
+    fn test_day_folder_outside_data_directory() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let data_path = dir.path().join("a").join("b");
+        std::fs::create_dir_all(&data_path).unwrap();
+
+        for folder in ["../../etc/x", "data/../../x", "", "/tmp/timetrax-absolute"] {
+            let app_config = AppConfig {
+                job_day_folder_format: folder.to_string(),
+                ..dir.app_config().clone()
+            };
+            let result = Manager::open(&app_config, &data_path);
+            assert_eq!(
+                result.err().map(|e| e.kind()),
+                Some(std::io::ErrorKind::InvalidInput),
+                "{folder:?}"
+            );
+        }
+        assert!(!dir.path().join("etc").exists());
+        assert!(!dir.path().join("a").join("x").exists());
+        assert!(!Path::new("/tmp/timetrax-absolute").exists());
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(
+            sanitize_file_name("2024-05-03.json").unwrap(),
+            "2024-05-03.json"
+        );
+        for name in [
+            "2024/05/03.json",
+            "../2024-05-03.json",
+            "..",
+            ".",
+            "",
+            "/x.json",
+            "x/",
+        ] {
+            assert!(sanitize_file_name(name).is_err(), "{name:?}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_day_folder_is_not_written() {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let outside = TempDataDir::new(&JobConfig::default()).unwrap();
+        let folder = dir.path().join(&dir.app_config().job_day_folder_format);
+
+        let mut manager = dir.manager().unwrap();
+        manager.get_or_create_day_mut(date).locked = true;
+        std::fs::remove_dir(&folder).unwrap();
+        std::os::unix::fs::symlink(outside.path(), &folder).unwrap();
+
+        assert_eq!(
+            manager.save().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert!(!outside.path().join("2024-05-03.json").exists());
+        assert!(dir.manager().is_err());
+    }
+
+    #[test]
+    fn test_scan_keeps_one_file_open() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let start = Date::from_calendar_date(2024, Month::January, 1).unwrap();
+        for offset in 0..200 {
+            let date = start + Duration::days(offset);
+            dir.write_day_file(
+                &format!("{}.json", date),
+                &format!(r#"{{"date": "{}", "activities": []}}"#, date),
+            )
+            .unwrap();
+        }
+
+        profile::enable();
+        let manager = dir.manager().unwrap();
+        // opening only lists the day folder
+        assert_eq!(profile::counter("day files parsed"), 0);
+        manager.load_range(start, start + Duration::days(199));
+        let peak = profile::peak_open_files();
+        let parsed = profile::counter("day files parsed");
+        profile::disable();
+
+        assert_eq!(manager.days.len(), 200);
+        assert_eq!(parsed, 200);
+        assert_eq!(peak, 1);
+    }
+
+    #[test]
+    fn test_refresh_skips_unchanged_files() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let start = Date::from_calendar_date(2024, Month::January, 1).unwrap();
+        for offset in 0..3 {
+            let date = start + Duration::days(offset);
+            dir.write_day_file(
+                &format!("{}.json", date),
+                &format!(r#"{{"date": "{}", "activities": []}}"#, date),
+            )
+            .unwrap();
+        }
+        let mut held = Manager::open_read_only(dir.app_config(), dir.path()).unwrap();
+        held.load_range(start, start + Duration::days(2));
+
+        profile::enable();
+        assert!(held.refresh().unwrap().is_empty());
+        let first = profile::counter("day files parsed");
+        assert!(held.refresh().unwrap().is_empty());
+        let second = profile::counter("day files parsed") - first;
+        dir.write_day_file(
+            &format!("{}.json", start),
+            &format!(r#"{{"date": "{}", "locked": true}}"#, start),
+        )
+        .unwrap();
+        let refreshed = held.refresh().unwrap();
+        let third = profile::counter("day files parsed") - first - second;
+        profile::disable();
+
+        assert_eq!(first, 3);
+        assert_eq!(second, 0);
+        assert_eq!(refreshed, [start]);
+        assert_eq!(third, 1);
+        assert!(held.day(start).unwrap().locked);
+    }
+}
@@ -0,0 +1,377 @@
+use crate::data::app_config::AppConfig;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::write_atomic;
+use log::{error, trace};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// kind of job config entry an external name stands for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum MappingKind {
+    Class,
+    Project,
+}
+
+impl Display for MappingKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MappingKind::Class => write!(f, "class"),
+            MappingKind::Project => write!(f, "project"),
+        }
+    }
+}
+
+/// name of a class or project in another system, e.g. a Toggl project
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    /// external system in lowercase, e.g. `toggl`
+    pub system: String,
+    /// name in the external system
+    pub external: String,
+    pub kind: MappingKind,
+    /// class or project of the job config, stored as UUID
+    pub internal: Identifier,
+}
+
+impl Mapping {
+    /// id and name of the class or project the mapping points to, `None` if it was removed
+    pub fn resolve<'a>(&self, job_config: &'a JobConfig) -> Option<(Uuid, &'a str)> {
+        match self.kind {
+            MappingKind::Class => job_config
+                .resolve_class(&self.internal)
+                .map(|class| (class.id, class.inner.name.as_str())),
+            MappingKind::Project => job_config
+                .resolve_project(&self.internal)
+                .map(|project| (project.id, project.inner.name.as_str())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MappingError {
+    /// the system or external name is blank
+    EmptyName,
+    /// the job config has no class or project with the identifier
+    UnknownTarget(MappingKind, Identifier),
+    /// the external name is mapped already
+    Exists(Mapping),
+}
+
+impl Display for MappingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MappingError::EmptyName => write!(f, "System and external name must not be empty"),
+            MappingError::UnknownTarget(kind, identifier) => {
+                write!(f, "Unknown {} {}", kind, identifier)
+            }
+            MappingError::Exists(mapping) => write!(
+                f,
+                "The {} {} '{}' is mapped to {} already, remove it first",
+                mapping.system, mapping.kind, mapping.external, mapping.internal
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MappingError {}
+
+impl From<MappingError> for std::io::Error {
+    fn from(e: MappingError) -> Self {
+        let kind = match e {
+            MappingError::EmptyName => std::io::ErrorKind::InvalidInput,
+            MappingError::UnknownTarget(..) => std::io::ErrorKind::NotFound,
+            MappingError::Exists(_) => std::io::ErrorKind::AlreadyExists,
+        };
+        std::io::Error::new(kind, e)
+    }
+}
+
+/// translation of class and project names to and from other systems, used by import and export
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MappingStore {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub mappings: Vec<Mapping>,
+}
+
+/// systems are compared case-insensitively, e.g. `Toggl` and `toggl`
+pub fn normalize_system(system: &str) -> String {
+    system.trim().to_lowercase()
+}
+
+impl MappingStore {
+    pub fn path<P: AsRef<Path>>(app_config: &AppConfig, data_path: P) -> PathBuf {
+        data_path.as_ref().join(&app_config.mapping_file_name)
+    }
+
+    /// mappings stored at `path`, empty if there are none
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                error!("Failed to read mappings {}: {}", path.display(), e);
+                return Err(e);
+            }
+        };
+        serde_json::from_str(&text).map_err(|e| {
+            error!("Failed to parse mappings {}: {}", path.display(), e);
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> crate::error::Result<()> {
+        trace!("Saving mappings to {}", path.display());
+        write_atomic(path, |file| Ok(serde_json::to_writer_pretty(file, self)?))
+    }
+
+    /// mapping of the external name
+    pub fn find(&self, system: &str, kind: MappingKind, external: &str) -> Option<&Mapping> {
+        let system = normalize_system(system);
+        self.mappings.iter().find(|mapping| {
+            mapping.system == system && mapping.kind == kind && mapping.external == external.trim()
+        })
+    }
+
+    /// class or project the external name maps to, stale mappings are ignored
+    pub fn to_internal(
+        &self,
+        job_config: &JobConfig,
+        system: &str,
+        kind: MappingKind,
+        external: &str,
+    ) -> Option<Uuid> {
+        self.find(system, kind, external)?
+            .resolve(job_config)
+            .map(|(id, _)| id)
+    }
+
+    /// external name of the class or project, the first one if several names map to it
+    pub fn to_external(
+        &self,
+        job_config: &JobConfig,
+        system: &str,
+        kind: MappingKind,
+        id: Uuid,
+    ) -> Option<&str> {
+        let system = normalize_system(system);
+        self.mappings
+            .iter()
+            .filter(|mapping| mapping.system == system && mapping.kind == kind)
+            .find(|mapping| {
+                mapping
+                    .resolve(job_config)
+                    .is_some_and(|(internal, _)| internal == id)
+            })
+            .map(|mapping| mapping.external.as_str())
+    }
+
+    /// mappings to classes or projects no longer in the job config
+    pub fn stale(&self, job_config: &JobConfig) -> Vec<&Mapping> {
+        self.mappings
+            .iter()
+            .filter(|mapping| mapping.resolve(job_config).is_none())
+            .collect()
+    }
+
+    /// add a mapping to a class or project of the job config
+    pub fn add(
+        &mut self,
+        job_config: &JobConfig,
+        system: &str,
+        kind: MappingKind,
+        external: &str,
+        internal: &Identifier,
+    ) -> Result<&Mapping, MappingError> {
+        let system = normalize_system(system);
+        let external = external.trim();
+        if system.is_empty() || external.is_empty() {
+            return Err(MappingError::EmptyName);
+        }
+        if let Some(existing) = self.find(&system, kind, external) {
+            return Err(MappingError::Exists(existing.clone()));
+        }
+        let mut mapping = Mapping {
+            system,
+            external: external.to_string(),
+            kind,
+            internal: internal.clone(),
+        };
+        let Some((id, _)) = mapping.resolve(job_config) else {
+            return Err(MappingError::UnknownTarget(kind, internal.clone()));
+        };
+        mapping.internal = Identifier::Uuid(id);
+
+        self.mappings.push(mapping);
+        Ok(self.mappings.last().expect("pushed above"))
+    }
+
+    /// remove the mappings of the external name, of both kinds unless `kind` is given
+    pub fn remove(
+        &mut self,
+        system: &str,
+        kind: Option<MappingKind>,
+        external: &str,
+    ) -> Vec<Mapping> {
+        let system = normalize_system(system);
+        let (removed, kept) = std::mem::take(&mut self.mappings)
+            .into_iter()
+            .partition(|mapping| {
+                mapping.system == system
+                    && kind.is_none_or(|kind| mapping.kind == kind)
+                    && mapping.external == external.trim()
+            });
+        self.mappings = kept;
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::project::{Project, ProjectInner};
+
+    fn job_config() -> JobConfig {
+        let mut job_config = JobConfig::default();
+        job_config.projects.push(Project {
+            id: Uuid::new_v4(),
+            inner: ProjectInner {
+                name: "acme-web".to_string(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        });
+        job_config
+    }
+
+    #[test]
+    fn test_translation_in_both_directions() {
+        let job_config = job_config();
+        let project = job_config.projects[0].id;
+        let work = job_config
+            .resolve_class(Identifier::ByName("work".into()))
+            .unwrap()
+            .id;
+        let mut store = MappingStore::default();
+        store
+            .add(
+                &job_config,
+                "Toggl",
+                MappingKind::Project,
+                " Acme – Website ",
+                &Identifier::ByName("acme-web".into()),
+            )
+            .unwrap();
+        store
+            .add(
+                &job_config,
+                "toggl",
+                MappingKind::Class,
+                "Billable",
+                &Identifier::ByName("work".into()),
+            )
+            .unwrap();
+        // internal identifiers are stored as UUID so renames keep the mapping
+        assert_eq!(store.mappings[0].internal, Identifier::Uuid(project));
+        assert_eq!(store.mappings[0].system, "toggl");
+
+        let in_toggl = |kind, external| store.to_internal(&job_config, "TOGGL", kind, external);
+        assert_eq!(
+            in_toggl(MappingKind::Project, "Acme – Website"),
+            Some(project)
+        );
+        assert_eq!(in_toggl(MappingKind::Class, "Billable"), Some(work));
+        assert_eq!(in_toggl(MappingKind::Class, "Acme – Website"), None);
+        assert_eq!(
+            store.to_internal(&job_config, "clockify", MappingKind::Class, "Billable"),
+            None
+        );
+        assert_eq!(
+            store.to_external(&job_config, "toggl", MappingKind::Project, project),
+            Some("Acme – Website")
+        );
+        assert_eq!(
+            store.to_external(&job_config, "toggl", MappingKind::Class, work),
+            Some("Billable")
+        );
+        assert_eq!(
+            store.to_external(&job_config, "jira", MappingKind::Class, work),
+            None
+        );
+
+        let json = serde_json::to_string(&store).unwrap();
+        assert_eq!(serde_json::from_str::<MappingStore>(&json).unwrap(), store);
+
+        assert_eq!(store.remove("toggl", None, "Billable").len(), 1);
+        assert_eq!(store.mappings.len(), 1);
+    }
+
+    #[test]
+    fn test_mappings_are_validated_against_the_job_config() {
+        let mut job_config = job_config();
+        let project = job_config.projects[0].id;
+        let mut store = MappingStore::default();
+        let add = |store: &mut MappingStore, external: &str, internal: &str| {
+            store
+                .add(
+                    &job_config,
+                    "toggl",
+                    MappingKind::Project,
+                    external,
+                    &Identifier::ByName(internal.into()),
+                )
+                .map(|mapping| mapping.internal.clone())
+        };
+
+        assert!(matches!(
+            add(&mut store, "Unknown", "missing"),
+            Err(MappingError::UnknownTarget(MappingKind::Project, _))
+        ));
+        assert!(matches!(
+            add(&mut store, " ", "acme-web"),
+            Err(MappingError::EmptyName)
+        ));
+        add(&mut store, "Acme", "acme-web").unwrap();
+        assert!(matches!(
+            add(&mut store, "Acme", "acme-web"),
+            Err(MappingError::Exists(_))
+        ));
+        assert!(store.stale(&job_config).is_empty());
+
+        // the project is deleted, its mapping is stale and no longer translates
+        job_config.projects.clear();
+        assert_eq!(store.stale(&job_config).len(), 1);
+        assert_eq!(
+            store.to_internal(&job_config, "toggl", MappingKind::Project, "Acme"),
+            None
+        );
+        assert_eq!(
+            store.to_external(&job_config, "toggl", MappingKind::Project, project),
+            None
+        );
+    }
+
+    #[test]
+    fn test_missing_store_is_empty() {
+        let dir = crate::testing::TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = MappingStore::path(dir.app_config(), dir.path());
+        assert_eq!(MappingStore::load(&path).unwrap(), MappingStore::default());
+
+        let mut store = MappingStore::default();
+        store
+            .add(
+                &job_config(),
+                "jira",
+                MappingKind::Class,
+                "Development",
+                &Identifier::ByName("work".into()),
+            )
+            .unwrap();
+        store.save(&path).unwrap();
+        assert_eq!(MappingStore::load(&path).unwrap(), store);
+    }
+}
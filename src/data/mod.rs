@@ -4,17 +4,41 @@ use time::format_description;
 pub mod activity;
 pub mod activity_class;
 pub mod activity_closure;
+pub mod activity_diff;
 pub mod app_config;
+pub mod attribution;
+pub mod backup;
 pub mod blocker;
+pub mod break_rules;
+pub mod compact;
 pub mod day;
+pub mod day_limit;
 pub mod dirty;
+pub mod evidence;
 pub mod identifier;
+pub mod integrity;
 pub mod interval;
 pub mod job_config;
+pub mod journal;
+pub mod legacy;
 pub mod local_time;
+pub mod lock;
 pub mod manager;
+pub mod mapping;
+pub mod notify;
 pub mod project;
+pub mod public_holiday;
+pub mod query;
 pub mod quota;
+pub mod reconcile;
+pub mod reference;
+pub mod report;
+pub mod safe_duration;
+pub mod salvage;
+pub mod team;
+pub mod text;
+pub mod trash;
+pub mod workspace;
 
 pub static BASIC_TIME_FORMAT: LazyLock<Vec<format_description::BorrowedFormatItem<'_>>> =
     LazyLock::new(|| {
@@ -0,0 +1,322 @@
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::write_atomic;
+use crate::data::report::{day_closure, day_quota, quota_time};
+use log::{error, trace};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use time::{Date, Duration, OffsetDateTime, Time};
+
+/// notification due according to the tracked time of today
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    /// the work tracked today reached the daily quota
+    QuotaReached { tracked: Duration, quota: Duration },
+    /// working without a break since `since` (wall-clock time)
+    BreakReminder { since: Time, working: Duration },
+}
+
+impl Notification {
+    /// key recorded in the state file, a reminder fires once per stretch of work
+    pub fn key(&self) -> String {
+        match self {
+            Notification::QuotaReached { .. } => "quota".to_string(),
+            Notification::BreakReminder { since, .. } => format!("break {}", since),
+        }
+    }
+}
+
+/// notifications already fired on a day
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NotifyState {
+    pub date: Date,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty", default)]
+    pub fired: BTreeSet<String>,
+}
+
+impl NotifyState {
+    pub fn path<P: AsRef<Path>>(app_config: &AppConfig, data_path: P) -> PathBuf {
+        data_path.as_ref().join(&app_config.notify_state_file_name)
+    }
+
+    /// state of `date`, the notifications of previous days are forgotten
+    pub fn load(path: &Path, date: Date) -> std::io::Result<Self> {
+        let empty = Self {
+            date,
+            fired: BTreeSet::new(),
+        };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(empty),
+            Err(e) => {
+                error!(
+                    "Failed to read notification state {}: {}",
+                    path.display(),
+                    e
+                );
+                return Err(e);
+            }
+        };
+        match serde_json::from_str::<Self>(&text) {
+            Ok(state) if state.date == date => Ok(state),
+            Ok(_) => Ok(empty),
+            Err(e) => {
+                // the state only suppresses repetitions, starting over is harmless
+                error!(
+                    "Ignoring corrupt notification state {}: {}",
+                    path.display(),
+                    e
+                );
+                Ok(empty)
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> crate::error::Result<()> {
+        trace!("Saving notification state to {}", path.display());
+        write_atomic(path, |file| Ok(serde_json::to_writer(file, self)?))
+    }
+
+    pub fn has_fired(&self, notification: &Notification) -> bool {
+        self.fired.contains(&notification.key())
+    }
+
+    pub fn record(&mut self, notification: &Notification) {
+        self.fired.insert(notification.key());
+    }
+}
+
+/// start (day time) and length of the uninterrupted work leading up to `now` (day time)
+///
+/// segments not counting towards the quota and gaps between segments interrupt the work
+pub fn working_streak(
+    job_config: &JobConfig,
+    closure: &[Activity],
+    now: Time,
+) -> Option<(Time, Duration)> {
+    let mut start = now;
+    for segment in closure.iter().rev() {
+        let Some(end) = segment.time.end else {
+            continue;
+        };
+        if end < start {
+            break;
+        }
+        if !job_config.counts_towards_quota(&segment.class) {
+            break;
+        }
+        start = start.min(segment.time.start);
+    }
+    (start < now).then(|| (start, now - start))
+}
+
+/// notifications due at `now` for the day `date`, including those that already fired
+pub fn due_notifications(
+    config: &AppConfig,
+    job_config: &JobConfig,
+    date: Date,
+    day: Option<&DayInner>,
+    now: OffsetDateTime,
+) -> Vec<Notification> {
+    let Some(day) = day else {
+        return Vec::new();
+    };
+    let boundary = job_config.day_boundary;
+    let (closure, _) = day_closure(job_config, date, day, now);
+    let mut due = Vec::new();
+
+    let quota = day_quota(job_config, date, Some(day), config.daily_quotas());
+    let tracked = quota_time(job_config, &closure);
+    if config.notify_on_quota && quota > Duration::ZERO && tracked >= quota {
+        due.push(Notification::QuotaReached { tracked, quota });
+    }
+
+    if let Some(limit) = config.break_reminder_after
+        && let Some((start, working)) = working_streak(
+            job_config,
+            &closure,
+            local_time::to_day_time(now.time(), boundary),
+        )
+        && working >= limit
+    {
+        due.push(Notification::BreakReminder {
+            since: local_time::from_day_time(start, boundary),
+            working,
+        });
+    }
+
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, TempDataDir, at};
+    use time::Month;
+    use uuid::Uuid;
+
+    fn hm(hour: u8, minute: u8) -> Time {
+        Time::from_hms(hour, minute, 0).unwrap()
+    }
+
+    fn day(activities: &[(&str, Time, Option<Time>)]) -> DayInner {
+        DayInner {
+            activities: activities
+                .iter()
+                .enumerate()
+                .map(|(i, (class, start, end))| Activity {
+                    id: Uuid::from_u128(i as u128 + 1),
+                    ..testing::activity(class, *start, *end)
+                })
+                .collect(),
+            ..DayInner::default()
+        }
+    }
+
+    fn config() -> AppConfig {
+        AppConfig {
+            notify_on_quota: true,
+            break_reminder_after: Some(Duration::hours(2)),
+            ..AppConfig::default()
+        }
+    }
+
+    // a Friday
+    fn date() -> Date {
+        Date::from_calendar_date(2024, Month::May, 3).unwrap()
+    }
+
+    #[test]
+    fn test_break_reminder_threshold() {
+        let job_config = JobConfig::default();
+        let day = day(&[
+            ("work", hm(8, 0), Some(hm(10, 0))),
+            ("break", hm(10, 0), Some(hm(10, 15))),
+            ("work", hm(10, 15), None),
+        ]);
+        let due =
+            |time| due_notifications(&config(), &job_config, date(), Some(&day), at(date(), time));
+
+        assert_eq!(due(hm(12, 14)), []);
+        assert_eq!(
+            due(hm(12, 15)),
+            [Notification::BreakReminder {
+                since: hm(10, 15),
+                working: Duration::hours(2)
+            }]
+        );
+        let disabled = AppConfig {
+            break_reminder_after: None,
+            ..config()
+        };
+        assert_eq!(
+            due_notifications(
+                &disabled,
+                &job_config,
+                date(),
+                Some(&day),
+                at(date(), hm(13, 0))
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn test_working_streak_is_interrupted_by_gaps() {
+        let job_config = JobConfig::default();
+        let closure =
+            |day: &DayInner, now| day_closure(&job_config, date(), day, at(date(), now)).0;
+
+        // adjacent work segments form one streak
+        let adjacent = day(&[("work", hm(8, 0), Some(hm(9, 0))), ("work", hm(9, 0), None)]);
+        assert_eq!(
+            working_streak(&job_config, &closure(&adjacent, hm(11, 0)), hm(11, 0)),
+            Some((hm(8, 0), Duration::hours(3)))
+        );
+
+        // untracked time counts as a break
+        let gap = day(&[
+            ("work", hm(8, 0), Some(hm(9, 0))),
+            ("work", hm(9, 30), None),
+        ]);
+        assert_eq!(
+            working_streak(&job_config, &closure(&gap, hm(11, 0)), hm(11, 0)),
+            Some((hm(9, 30), Duration::minutes(90)))
+        );
+
+        // not working right now
+        let stopped = day(&[("work", hm(8, 0), Some(hm(10, 0)))]);
+        assert_eq!(
+            working_streak(&job_config, &closure(&stopped, hm(11, 0)), hm(11, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_quota_notification() {
+        let job_config = JobConfig::default();
+        let day = day(&[
+            ("work", hm(8, 0), Some(hm(12, 0))),
+            ("break", hm(12, 0), Some(hm(12, 30))),
+            ("work", hm(12, 30), None),
+        ]);
+        let config = AppConfig {
+            break_reminder_after: None,
+            ..config()
+        };
+        let due =
+            |date, time| due_notifications(&config, &job_config, date, Some(&day), at(date, time));
+
+        assert_eq!(due(date(), hm(16, 29)), []);
+        assert_eq!(
+            due(date(), hm(16, 30)),
+            [Notification::QuotaReached {
+                tracked: Duration::hours(8),
+                quota: Duration::hours(8)
+            }]
+        );
+        // weekends have no quota
+        let saturday = date().next_day().unwrap();
+        assert_eq!(due(saturday, hm(18, 0)), []);
+        assert_eq!(
+            due_notifications(&config, &job_config, date(), None, at(date(), hm(18, 0))),
+            []
+        );
+    }
+
+    #[test]
+    fn test_state_is_kept_per_day() {
+        let dir = TempDataDir::new(&JobConfig::default()).unwrap();
+        let path = NotifyState::path(dir.app_config(), dir.path());
+        let quota = Notification::QuotaReached {
+            tracked: Duration::hours(8),
+            quota: Duration::hours(8),
+        };
+        let reminder = |since| Notification::BreakReminder {
+            since,
+            working: Duration::hours(2),
+        };
+
+        let mut state = NotifyState::load(&path, date()).unwrap();
+        assert!(!state.has_fired(&quota));
+        state.record(&quota);
+        state.record(&reminder(hm(8, 0)));
+        state.save(&path).unwrap();
+
+        let state = NotifyState::load(&path, date()).unwrap();
+        assert!(state.has_fired(&quota));
+        assert!(state.has_fired(&reminder(hm(8, 0))));
+        // a new stretch of work gets its own reminder
+        assert!(!state.has_fired(&reminder(hm(10, 15))));
+
+        let tomorrow = NotifyState::load(&path, date().next_day().unwrap()).unwrap();
+        assert!(tomorrow.fired.is_empty());
+
+        std::fs::write(&path, "{").unwrap();
+        assert!(NotifyState::load(&path, date()).unwrap().fired.is_empty());
+    }
+}
@@ -3,14 +3,20 @@ use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use uuid::Uuid;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ProjectInner {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub description: Option<String>,
+    /// client the project is billed to
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub client: Option<String>,
+    /// targeted percentage of the tracked time, e.g. 70 for 70%
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub target_share: Option<f32>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Project {
     pub id: Uuid,
     #[serde(flatten)]
@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+use time::format_description::BorrowedFormatItem;
+use time::{Date, Duration};
+
+/// statutory holiday, its work quota counts as met without tracking anything
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PublicHoliday {
+    pub date: Date,
+    pub name: String,
+}
+
+/// events longer than this are no holidays, a typo in a calendar must not flood the job config
+const MAX_EVENT_DAYS: i64 = 31;
+
+/// dates of ICS values, `20241225` or the date part of `20241225T000000Z`
+static ICS_DATE_FORMAT: LazyLock<Vec<BorrowedFormatItem<'_>>> =
+    LazyLock::new(|| time::format_description::parse("[year][month][day]").unwrap());
+
+/// malformed ICS calendar, with the number of the event starting at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcsError {
+    /// the calendar has no events
+    NoEvents,
+    /// an event has no start date
+    MissingStart(usize),
+    /// a start or end date cannot be parsed
+    InvalidDate(usize, String),
+    /// an event ends before it starts or spans more than [`MAX_EVENT_DAYS`]
+    InvalidSpan(usize),
+}
+
+impl Display for IcsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IcsError::NoEvents => write!(f, "The calendar has no events"),
+            IcsError::MissingStart(event) => write!(f, "Event {} has no DTSTART", event),
+            IcsError::InvalidDate(event, value) => {
+                write!(f, "Event {} has an invalid date: {}", event, value)
+            }
+            IcsError::InvalidSpan(event) => write!(
+                f,
+                "Event {} ends before it starts or spans more than {} days",
+                event, MAX_EVENT_DAYS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IcsError {}
+
+impl From<IcsError> for std::io::Error {
+    fn from(e: IcsError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// properties of a VEVENT read so far
+#[derive(Default)]
+struct Event {
+    start: Option<String>,
+    end: Option<String>,
+    summary: Option<String>,
+}
+
+/// holidays of the events of an ICS calendar, one per day of events spanning several days.
+/// events without a SUMMARY are named "Holiday"
+pub fn parse_ics(contents: &str) -> Result<Vec<PublicHoliday>, IcsError> {
+    let mut holidays = Vec::new();
+    let mut event: Option<Event> = None;
+    let mut events = 0;
+    for line in unfold(contents) {
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        // parameters such as `;VALUE=DATE` are not needed
+        let name = property.split(';').next().unwrap_or_default();
+        match (name.to_ascii_uppercase().as_str(), event.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some(Event::default());
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                events += 1;
+                let finished = event.take().unwrap_or_default();
+                holidays.extend(event_holidays(events, finished)?);
+            }
+            ("DTSTART", Some(event)) => event.start = Some(value.to_string()),
+            ("DTEND", Some(event)) => event.end = Some(value.to_string()),
+            ("SUMMARY", Some(event)) => event.summary = Some(unescape(value)),
+            _ => {}
+        }
+    }
+    if events == 0 {
+        return Err(IcsError::NoEvents);
+    }
+    Ok(holidays)
+}
+
+/// holidays of a single event, `number` counts the events from 1
+fn event_holidays(number: usize, event: Event) -> Result<Vec<PublicHoliday>, IcsError> {
+    let date = |value: &str| {
+        value
+            .get(..8)
+            .and_then(|date| Date::parse(date, &ICS_DATE_FORMAT).ok())
+            .ok_or_else(|| IcsError::InvalidDate(number, value.to_string()))
+    };
+    let start = date(
+        event
+            .start
+            .as_deref()
+            .ok_or(IcsError::MissingStart(number))?,
+    )?;
+    // the end is exclusive, events without one or ending on their start last a day
+    let days = match event.end.as_deref() {
+        Some(end) => match (date(end)? - start).whole_days() {
+            0 => 1,
+            days => days,
+        },
+        None => 1,
+    };
+    if !(1..=MAX_EVENT_DAYS).contains(&days) {
+        return Err(IcsError::InvalidSpan(number));
+    }
+    let name = event
+        .summary
+        .filter(|summary| !summary.trim().is_empty())
+        .unwrap_or_else(|| "Holiday".to_string());
+    Ok((0..days)
+        .map(|offset| PublicHoliday {
+            date: start + Duration::days(offset),
+            name: name.trim().to_string(),
+        })
+        .collect())
+}
+
+/// content lines of the calendar, lines starting with a space or tab continue the previous one
+fn unfold(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// text value with the ICS escapes `\,`, `\;`, `\n` and `\\` resolved
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => result.push(' '),
+            Some(escaped) => result.push(escaped),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let holidays = parse_ics(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/holidays.ics"
+        )))
+        .unwrap();
+        let holiday = |year, month, day, name: &str| PublicHoliday {
+            date: date(year, month, day),
+            name: name.to_string(),
+        };
+        assert_eq!(
+            holidays,
+            [
+                holiday(2023, Month::December, 25, "1. Weihnachtstag"),
+                holiday(2024, Month::May, 1, "Tag der Arbeit"),
+                holiday(2024, Month::May, 9, "Christi Himmelfahrt"),
+                holiday(
+                    2024,
+                    Month::October,
+                    3,
+                    "Tag der Deutschen Einheit, bundesweit"
+                ),
+                holiday(2024, Month::December, 24, "Betriebsferien"),
+                holiday(2024, Month::December, 25, "Betriebsferien"),
+                holiday(2030, Month::January, 1, "Neujahr"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ics_rejects_malformed_events() {
+        let event = |properties: &str| {
+            format!(
+                "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\n{}\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+                properties
+            )
+        };
+        assert_eq!(
+            parse_ics("BEGIN:VCALENDAR\nEND:VCALENDAR\n"),
+            Err(IcsError::NoEvents)
+        );
+        assert_eq!(
+            parse_ics(&event("SUMMARY:Neujahr")),
+            Err(IcsError::MissingStart(1))
+        );
+        assert_eq!(
+            parse_ics(&event("DTSTART;VALUE=DATE:2024-01-01")),
+            Err(IcsError::InvalidDate(1, "2024-01-01".to_string()))
+        );
+        assert_eq!(
+            parse_ics(&event("DTSTART:20240101\r\nDTEND:20250101")),
+            Err(IcsError::InvalidSpan(1))
+        );
+        assert_eq!(
+            parse_ics(&event("DTSTART:20240102\r\nDTEND:20240101")),
+            Err(IcsError::InvalidSpan(1))
+        );
+        // a missing summary falls back to a generic name
+        assert_eq!(
+            parse_ics(&event("DTSTART:20240101T000000Z")).unwrap()[0].name,
+            "Holiday"
+        );
+    }
+}
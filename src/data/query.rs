@@ -0,0 +1,806 @@
+//! tiny query language, e.g. `sum duration where class=@work and date in 2024-05 group by project`
+//!
+//! ```text
+//! query     := aggregate [ "where" condition { "and" condition } ] [ "group" "by" field ]
+//! aggregate := "sum" "duration" | "count" "activities"
+//! condition := ("class" | "project" | "tag") ( "=" value | "in" "(" value { "," value } ")" )
+//!            | "date" ( "=" | ">=" | "<=" | ">" | "<" ) YYYY-MM-DD
+//!            | "date" "in" ( YYYY | YYYY-MM | YYYY-MM-DD )
+//! field     := "class" | "project" | "tag" | "date"
+//! ```
+
+use crate::data::activity::Activity;
+use crate::data::attribution::Attribution;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::Manager;
+use crate::data::report::{ReportFilter, filtered_day_closure};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use time::{Date, Duration, Month, OffsetDateTime};
+use uuid::Uuid;
+
+/// what is computed for the selected activities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// total time of the closure of the selected activities
+    SumDuration,
+    /// number of selected activities
+    CountActivities,
+}
+
+/// grouping of the query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Class,
+    Project,
+    Tag,
+    Date,
+}
+
+/// parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub aggregate: Aggregate,
+    pub filter: ReportFilter,
+    pub group_by: Option<GroupBy>,
+}
+
+/// syntax error pointing at the offending part of the query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// offset of the offending token in characters
+    pub position: usize,
+    /// length of the offending token in characters, at least one
+    pub length: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    /// the query with the offending token underlined
+    pub fn pointer(&self, source: &str) -> String {
+        format!(
+            "{}\n{}{}",
+            source,
+            " ".repeat(self.position),
+            "^".repeat(self.length.max(1))
+        )
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Quoted,
+    Operator,
+    Open,
+    Close,
+    Comma,
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    /// offset in characters
+    position: usize,
+    /// length in characters
+    length: usize,
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn describe(&self) -> String {
+        match self.kind {
+            TokenKind::End => "end of query".to_string(),
+            _ => format!("`{}`", self.text),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || "@#-_.:/".contains(c)
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                i += 1;
+                TokenKind::Open
+            }
+            ')' => {
+                i += 1;
+                TokenKind::Close
+            }
+            ',' => {
+                i += 1;
+                TokenKind::Comma
+            }
+            '=' => {
+                i += 1;
+                TokenKind::Operator
+            }
+            '<' | '>' => {
+                i += 1;
+                if chars.get(i) == Some(&'=') {
+                    i += 1;
+                }
+                TokenKind::Operator
+            }
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    i += 1;
+                }
+                if i == chars.len() {
+                    return Err(QueryError {
+                        position: start,
+                        length: i - start,
+                        message: "unterminated string".to_string(),
+                    });
+                }
+                i += 1;
+                tokens.push(Token {
+                    kind: TokenKind::Quoted,
+                    text: chars[start + 1..i - 1].iter().collect(),
+                    position: start,
+                    length: i - start,
+                });
+                continue;
+            }
+            c if is_word_char(c) => {
+                while i < chars.len() && is_word_char(chars[i]) {
+                    i += 1;
+                }
+                TokenKind::Word
+            }
+            c => {
+                return Err(QueryError {
+                    position: start,
+                    length: 1,
+                    message: format!("unexpected character `{}`", c),
+                });
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: chars[start..i].iter().collect(),
+            position: start,
+            length: i - start,
+        });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        text: String::new(),
+        position: chars.len(),
+        length: 1,
+    });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error(token: &Token, message: impl Into<String>) -> QueryError {
+        QueryError {
+            position: token.position,
+            length: token.length,
+            message: message.into(),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<Token, QueryError> {
+        let token = self.next();
+        if token.is_keyword(keyword) {
+            Ok(token)
+        } else {
+            Err(Self::error(
+                &token,
+                format!("expected `{}`, found {}", keyword, token.describe()),
+            ))
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Result<Token, QueryError> {
+        let token = self.next();
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(Self::error(
+                &token,
+                format!("expected {}, found {}", expected, token.describe()),
+            ))
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, QueryError> {
+        let aggregate = self.aggregate()?;
+
+        let mut filter = ReportFilter::default();
+        if self.peek().is_keyword("where") {
+            self.next();
+            self.condition(&mut filter)?;
+            while self.peek().is_keyword("and") {
+                self.next();
+                self.condition(&mut filter)?;
+            }
+        }
+
+        let mut group_by = None;
+        if self.peek().is_keyword("group") {
+            self.next();
+            self.expect_keyword("by")?;
+            let token = self.next();
+            group_by = Some(match token.text.to_ascii_lowercase().as_str() {
+                _ if token.kind != TokenKind::Word => {
+                    return Err(Self::error(&token, "expected class, project, tag or date"));
+                }
+                "class" => GroupBy::Class,
+                "project" => GroupBy::Project,
+                "tag" => GroupBy::Tag,
+                "date" => GroupBy::Date,
+                _ => {
+                    return Err(Self::error(
+                        &token,
+                        format!(
+                            "cannot group by {}, expected class, project, tag or date",
+                            token.describe()
+                        ),
+                    ));
+                }
+            });
+        }
+
+        let token = self.peek();
+        if token.kind != TokenKind::End {
+            let expected = match (filter == ReportFilter::default(), group_by) {
+                (_, Some(_)) => "end of query",
+                (true, None) => "`where`, `group by` or end of query",
+                (false, None) => "`and`, `group by` or end of query",
+            };
+            return Err(Self::error(
+                token,
+                format!("expected {}, found {}", expected, token.describe()),
+            ));
+        }
+
+        Ok(Query {
+            aggregate,
+            filter,
+            group_by,
+        })
+    }
+
+    fn aggregate(&mut self) -> Result<Aggregate, QueryError> {
+        let token = self.next();
+        if token.is_keyword("sum") {
+            self.expect_keyword("duration")?;
+            Ok(Aggregate::SumDuration)
+        } else if token.is_keyword("count") {
+            self.expect_keyword("activities")?;
+            Ok(Aggregate::CountActivities)
+        } else {
+            Err(Self::error(
+                &token,
+                format!("expected `sum` or `count`, found {}", token.describe()),
+            ))
+        }
+    }
+
+    fn condition(&mut self, filter: &mut ReportFilter) -> Result<(), QueryError> {
+        let field = self.expect(TokenKind::Word, "class, project, tag or date")?;
+        match field.text.to_ascii_lowercase().as_str() {
+            "class" => {
+                let values = self.values(&field, filter.classes.is_empty())?;
+                filter
+                    .classes
+                    .extend(values.iter().map(|value| identifier(&value.text)));
+            }
+            "project" => {
+                let values = self.values(&field, filter.projects.is_empty())?;
+                filter
+                    .projects
+                    .extend(values.iter().map(|value| identifier(&value.text)));
+            }
+            "tag" => {
+                let values = self.values(&field, filter.tags.is_empty())?;
+                filter.tags.extend(
+                    values
+                        .iter()
+                        .map(|value| value.text.trim_start_matches('#').to_string()),
+                );
+            }
+            "date" => self.date_condition(filter)?,
+            _ => {
+                return Err(Self::error(
+                    &field,
+                    format!(
+                        "unknown field {}, expected class, project, tag or date",
+                        field.describe()
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// `= value` or `in (value, ...)`, a field may only be restricted once
+    fn values(&mut self, field: &Token, unrestricted: bool) -> Result<Vec<Token>, QueryError> {
+        if !unrestricted {
+            return Err(Self::error(
+                field,
+                format!(
+                    "{} is already restricted, list alternatives using `{} in (a, b)`",
+                    field.text, field.text
+                ),
+            ));
+        }
+
+        let operator = self.next();
+        if operator.kind == TokenKind::Operator && operator.text == "=" {
+            return Ok(vec![self.value()?]);
+        }
+        if !operator.is_keyword("in") {
+            return Err(Self::error(
+                &operator,
+                format!("expected `=` or `in`, found {}", operator.describe()),
+            ));
+        }
+
+        self.expect(TokenKind::Open, "`(`")?;
+        let mut values = vec![self.value()?];
+        while self.peek().kind == TokenKind::Comma {
+            self.next();
+            values.push(self.value()?);
+        }
+        self.expect(TokenKind::Close, "`,` or `)`")?;
+        Ok(values)
+    }
+
+    fn value(&mut self) -> Result<Token, QueryError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Word | TokenKind::Quoted if !token.text.is_empty() => Ok(token),
+            _ => Err(Self::error(
+                &token,
+                format!("expected a value, found {}", token.describe()),
+            )),
+        }
+    }
+
+    fn date_condition(&mut self, filter: &mut ReportFilter) -> Result<(), QueryError> {
+        let operator = self.next();
+        let (from, to) = if operator.is_keyword("in") {
+            let value = self.expect(TokenKind::Word, "a year, month or date")?;
+            period(&value.text).ok_or_else(|| {
+                Self::error(
+                    &value,
+                    format!(
+                        "invalid period `{}`, expected YYYY, YYYY-MM or YYYY-MM-DD",
+                        value.text
+                    ),
+                )
+            })?
+        } else if operator.kind == TokenKind::Operator {
+            let value = self.expect(TokenKind::Word, "a date")?;
+            let date = match period(&value.text) {
+                Some((from, to)) if from == to => from,
+                _ => {
+                    return Err(Self::error(
+                        &value,
+                        format!("invalid date `{}`, expected YYYY-MM-DD", value.text),
+                    ));
+                }
+            };
+            match operator.text.as_str() {
+                "=" => (date, date),
+                ">=" => (date, Date::MAX),
+                ">" => (date.next_day().unwrap_or(Date::MAX), Date::MAX),
+                "<=" => (Date::MIN, date),
+                _ => (Date::MIN, date.previous_day().unwrap_or(Date::MIN)),
+            }
+        } else {
+            return Err(Self::error(
+                &operator,
+                format!(
+                    "expected `=`, `>=`, `<=`, `>`, `<` or `in`, found {}",
+                    operator.describe()
+                ),
+            ));
+        };
+
+        filter.from = Some(filter.from.map_or(from, |current| current.max(from)));
+        filter.to = Some(filter.to.map_or(to, |current| current.min(to)));
+        Ok(())
+    }
+}
+
+/// UUID, `@name` or plain name
+fn identifier(value: &str) -> Identifier {
+    match Uuid::from_str(value) {
+        Ok(id) => Identifier::Uuid(id),
+        Err(_) => Identifier::ByName(value.strip_prefix('@').unwrap_or(value).to_string()),
+    }
+}
+
+/// first and last day of `YYYY`, `YYYY-MM` or `YYYY-MM-DD`
+fn period(value: &str) -> Option<(Date, Date)> {
+    let parts = value
+        .split('-')
+        .map(|part| part.parse::<u16>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match parts.as_slice() {
+        [year] => Some((
+            Date::from_calendar_date(*year as i32, Month::January, 1).ok()?,
+            Date::from_calendar_date(*year as i32, Month::December, 31).ok()?,
+        )),
+        [year, month] => {
+            let month = Month::try_from(u8::try_from(*month).ok()?).ok()?;
+            let first = Date::from_calendar_date(*year as i32, month, 1).ok()?;
+            let last =
+                Date::from_calendar_date(*year as i32, month, month.length(*year as i32)).ok()?;
+            Some((first, last))
+        }
+        [year, month, day] => {
+            let month = Month::try_from(u8::try_from(*month).ok()?).ok()?;
+            let date =
+                Date::from_calendar_date(*year as i32, month, u8::try_from(*day).ok()?).ok()?;
+            Some((date, date))
+        }
+        _ => None,
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        }
+        .query()
+    }
+}
+
+/// value of a result row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryValue {
+    Duration(Duration),
+    Count(usize),
+}
+
+/// result row, the group is none if the query is not grouped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryRow {
+    pub group: Option<String>,
+    pub value: QueryValue,
+}
+
+impl Query {
+    /// classes and projects of the filter which the job config does not know
+    pub fn unknown_identifiers(&self, job_config: &JobConfig) -> Vec<Identifier> {
+        let classes = self
+            .filter
+            .classes
+            .iter()
+            .filter(|class| job_config.resolve_class(*class).is_none());
+        let projects = self
+            .filter
+            .projects
+            .iter()
+            .filter(|project| job_config.resolve_project(*project).is_none());
+        classes.chain(projects).cloned().collect()
+    }
+
+    /// evaluate the query over the given days, project time follows `attribution`
+    pub fn evaluate(
+        &self,
+        job_config: &JobConfig,
+        manager: &Manager,
+        now: OffsetDateTime,
+        attribution: Attribution,
+    ) -> Vec<QueryRow> {
+        let class_name = |class: &Identifier| {
+            job_config
+                .resolve_class(class)
+                .map(|class| class.inner.name.clone())
+                .unwrap_or_else(|| class.to_string())
+        };
+        let project_name = |project: &Identifier| {
+            job_config
+                .resolve_project(project)
+                .map(|project| project.inner.name.clone())
+                .unwrap_or_else(|| project.to_string())
+        };
+
+        let mut durations: BTreeMap<Option<String>, Duration> = BTreeMap::new();
+        let mut counts: BTreeMap<Option<String>, usize> = BTreeMap::new();
+        for (date, day) in manager
+            .days()
+            .filter(|(date, _)| self.filter.includes_date(**date))
+        {
+            match self.aggregate {
+                Aggregate::SumDuration => {
+                    let (closure, _) =
+                        filtered_day_closure(job_config, &self.filter, *date, day, now);
+                    for (group, duration) in self.duration_groups(
+                        &closure,
+                        *date,
+                        attribution,
+                        &class_name,
+                        &project_name,
+                    ) {
+                        *durations.entry(group).or_insert(Duration::ZERO) += duration;
+                    }
+                }
+                Aggregate::CountActivities => {
+                    for activity in self.filter.activities(job_config, day) {
+                        let groups: Vec<Option<String>> = match self.group_by {
+                            None => vec![None],
+                            Some(GroupBy::Class) => vec![Some(class_name(&activity.class))],
+                            Some(GroupBy::Project) => activity
+                                .projects
+                                .iter()
+                                .map(|p| Some(project_name(p)))
+                                .collect(),
+                            Some(GroupBy::Tag) => {
+                                activity.tags.iter().map(|t| Some(t.clone())).collect()
+                            }
+                            Some(GroupBy::Date) => vec![Some(date.to_string())],
+                        };
+                        for group in groups {
+                            *counts.entry(group).or_default() += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        let rows: Vec<QueryRow> = match self.aggregate {
+            Aggregate::SumDuration => durations
+                .into_iter()
+                .map(|(group, duration)| QueryRow {
+                    group,
+                    value: QueryValue::Duration(duration),
+                })
+                .collect(),
+            Aggregate::CountActivities => counts
+                .into_iter()
+                .map(|(group, count)| QueryRow {
+                    group,
+                    value: QueryValue::Count(count),
+                })
+                .collect(),
+        };
+
+        if rows.is_empty() && self.group_by.is_none() {
+            return vec![QueryRow {
+                group: None,
+                value: match self.aggregate {
+                    Aggregate::SumDuration => QueryValue::Duration(Duration::ZERO),
+                    Aggregate::CountActivities => QueryValue::Count(0),
+                },
+            }];
+        }
+        rows
+    }
+
+    /// time of the closure segments per group
+    fn duration_groups(
+        &self,
+        closure: &[Activity],
+        date: Date,
+        attribution: Attribution,
+        class_name: &dyn Fn(&Identifier) -> String,
+        project_name: &dyn Fn(&Identifier) -> String,
+    ) -> Vec<(Option<String>, Duration)> {
+        let total = || {
+            closure
+                .iter()
+                .map(|segment| segment.time.duration().unwrap_or_default())
+                .sum::<Duration>()
+        };
+        match self.group_by {
+            None => vec![(None, total())],
+            Some(GroupBy::Date) => vec![(Some(date.to_string()), total())],
+            Some(GroupBy::Class) => Activity::summarize_by_class(closure)
+                .into_iter()
+                .map(|(class, duration)| (Some(class_name(&class)), duration))
+                .collect(),
+            Some(GroupBy::Project) => Activity::summarize_by_project(closure, attribution)
+                .into_iter()
+                .map(|(project, duration)| (Some(project_name(&project)), duration))
+                .collect(),
+            Some(GroupBy::Tag) => closure
+                .iter()
+                .flat_map(|segment| {
+                    let duration = segment.time.duration().unwrap_or_default();
+                    segment
+                        .tags
+                        .iter()
+                        .map(move |tag| (Some(tag.clone()), duration))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    fn parse(source: &str) -> Query {
+        source
+            .parse()
+            .unwrap_or_else(|e: QueryError| panic!("{}\n{}", e, e.pointer(source)))
+    }
+
+    fn parse_error(source: &str) -> QueryError {
+        source.parse::<Query>().unwrap_err()
+    }
+
+    #[test]
+    fn test_parse_sum_grouped() {
+        let query = parse("sum duration where class=@work and date in 2024-05 group by project");
+        assert_eq!(query.aggregate, Aggregate::SumDuration);
+        assert_eq!(query.group_by, Some(GroupBy::Project));
+        assert_eq!(
+            query.filter,
+            ReportFilter {
+                classes: vec![Identifier::ByName("work".into())],
+                from: Some(date(2024, Month::May, 1)),
+                to: Some(date(2024, Month::May, 31)),
+                ..ReportFilter::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_count_with_tag_and_date_bounds() {
+        let query = parse(
+            "COUNT activities WHERE tag=#oncall and date >= 2024-01-01 and date < 2024-03-01",
+        );
+        assert_eq!(query.aggregate, Aggregate::CountActivities);
+        assert_eq!(query.group_by, None);
+        assert_eq!(query.filter.tags, ["oncall"]);
+        assert_eq!(query.filter.from, Some(date(2024, Month::January, 1)));
+        assert_eq!(query.filter.to, Some(date(2024, Month::February, 29)));
+    }
+
+    #[test]
+    fn test_parse_lists_and_quoted_values() {
+        let query = parse(
+            r#"sum duration where project in (@projA, "client work", 00000000-0000-0000-0000-000000000001) group by date"#,
+        );
+        assert_eq!(
+            query.filter.projects,
+            [
+                Identifier::ByName("projA".into()),
+                Identifier::ByName("client work".into()),
+                Identifier::Uuid(Uuid::from_u128(1)),
+            ]
+        );
+        assert_eq!(query.group_by, Some(GroupBy::Date));
+
+        let query = parse("count activities where date in 2024 and date = 2024-07-04");
+        assert_eq!(query.filter.from, Some(date(2024, Month::July, 4)));
+        assert_eq!(query.filter.to, Some(date(2024, Month::July, 4)));
+    }
+
+    #[test]
+    fn test_errors_point_at_offending_token() {
+        let cases = [
+            ("", 0, "expected `sum` or `count`, found end of query"),
+            (
+                "average duration",
+                0,
+                "expected `sum` or `count`, found `average`",
+            ),
+            ("sum hours", 4, "expected `duration`, found `hours`"),
+            (
+                "sum duration wher class=@work",
+                13,
+                "expected `where`, `group by` or end of query, found `wher`",
+            ),
+            (
+                "sum duration where colour=red",
+                19,
+                "unknown field `colour`, expected class, project, tag or date",
+            ),
+            (
+                "sum duration where class=@a and class=@b",
+                32,
+                "class is already restricted, list alternatives using `class in (a, b)`",
+            ),
+            (
+                "sum duration where date in 2024-13",
+                27,
+                "invalid period `2024-13`, expected YYYY, YYYY-MM or YYYY-MM-DD",
+            ),
+            (
+                "sum duration where date >= 2024-05",
+                27,
+                "invalid date `2024-05`, expected YYYY-MM-DD",
+            ),
+            (
+                "sum duration where tag in (a b)",
+                29,
+                "expected `,` or `)`, found `b`",
+            ),
+            (
+                "sum duration group by week",
+                22,
+                "cannot group by `week`, expected class, project, tag or date",
+            ),
+            ("sum duration where tag=\"x", 23, "unterminated string"),
+            ("sum duration where tag=x;", 24, "unexpected character `;`"),
+            (
+                "sum duration where class=@a or class=@b",
+                28,
+                "expected `and`, `group by` or end of query, found `or`",
+            ),
+        ];
+
+        for (source, position, message) in cases {
+            let error = parse_error(source);
+            assert_eq!(
+                (error.position, error.message.as_str()),
+                (position, message),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_error_pointer() {
+        let source = "sum hours";
+        let error = parse_error(source);
+        assert_eq!(error.pointer(source), "sum hours\n    ^^^^^");
+    }
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use time::Duration;
 
 /// inner quota data structure, no id
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct QuotaInner {
     /// identifier of the class
     pub class: Identifier,
@@ -16,7 +16,7 @@ pub struct QuotaInner {
 }
 
 /// quota data structure
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Quota {
     /// unique id
     pub id: uuid::Uuid,
@@ -0,0 +1,465 @@
+use crate::data::BASIC_TIME_FORMAT;
+use crate::data::activity::Activity;
+use crate::data::compact::same_activity;
+use crate::data::day::DayInner;
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::local_time;
+use itertools::Itertools;
+use log::error;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use time::{Duration, OffsetDateTime, Time};
+use uuid::Uuid;
+
+/// thresholds of the detectors used to reconcile a day
+#[derive(Debug, Clone)]
+pub struct ReconcilePolicy {
+    /// gaps between activities of at least this length are reported
+    pub min_gap: Duration,
+    /// completed activities longer than this are suspicious
+    pub max_duration: Duration,
+    /// class of inserted breaks
+    pub break_class: Identifier,
+    /// the day is over, its unterminated activities are stale instead of running
+    pub day_over: bool,
+    /// see [`JobConfig::day_boundary`](crate::data::job_config::JobConfig::day_boundary)
+    pub boundary: Option<Time>,
+}
+
+/// modification of a day fixing an issue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    /// end the activity at the given time
+    Close { id: Uuid, at: Time },
+    /// replace overlapping fragments of the same activity by one covering all of them
+    Merge { ids: Vec<Uuid> },
+    /// insert a break covering the gap
+    InsertBreak { from: Time, to: Time },
+    /// remove the activity
+    Delete { id: Uuid },
+}
+
+fn format_time(time: Time) -> String {
+    time.format(&*BASIC_TIME_FORMAT).unwrap_or_else(|e| {
+        error!("Unable to format time: {e}. Report this as an issue.");
+        "<INVALID>".to_string()
+    })
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fix::Close { at, .. } => write!(f, "close at {}", format_time(*at)),
+            Fix::Merge { ids } => write!(f, "merge the {} fragments into one activity", ids.len()),
+            Fix::InsertBreak { from, to } => write!(
+                f,
+                "insert a break from {} to {}",
+                format_time(*from),
+                format_time(*to)
+            ),
+            Fix::Delete { .. } => write!(f, "delete the activity"),
+        }
+    }
+}
+
+/// detected issue and the fix proposed for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal {
+    /// description of the issue
+    pub issue: String,
+    pub fix: Fix,
+    /// applied without asking by `reconcile --auto`
+    pub safe: bool,
+}
+
+/// activities removed and added by applying a fix, as recorded in the journal
+#[derive(Debug, Clone, Default)]
+pub struct Applied {
+    pub removed: Vec<Activity>,
+    pub added: Vec<Activity>,
+}
+
+/// all issues of the day in the order they are reviewed:
+/// unterminated activities, conflicts, suspiciously long records and gaps
+pub fn propose(day: &DayInner, policy: &ReconcilePolicy) -> Vec<Proposal> {
+    let mut proposals = unterminated(day, policy);
+    proposals.extend(conflicts(day, policy));
+    proposals.extend(long_records(day, policy));
+    proposals.extend(gaps(day, policy));
+    proposals
+}
+
+/// completed activities in day time, sorted by start
+fn completed(day: &DayInner, boundary: Option<Time>) -> Vec<(&Activity, Interval)> {
+    day.activities
+        .iter()
+        .filter(|activity| activity.time.is_complete() && !activity.time.is_inverted())
+        .map(|activity| (activity, activity.time.to_day_time(boundary)))
+        .sorted_by_key(|(_, time)| time.start)
+        .collect()
+}
+
+/// stale activities of past days are closed at the last end recorded after their start,
+/// which is safe, or at the end of the day if nothing ended after them
+fn unterminated(day: &DayInner, policy: &ReconcilePolicy) -> Vec<Proposal> {
+    if !policy.day_over {
+        return Vec::new();
+    }
+    let boundary = policy.boundary;
+    let end_of_day = local_time::from_day_time(Time::from_hms(23, 59, 0).unwrap(), boundary);
+
+    day.activities
+        .iter()
+        .filter(|activity| !activity.time.is_complete())
+        .map(|activity| {
+            let start = local_time::to_day_time(activity.time.start, boundary);
+            let last_end = day
+                .activities
+                .iter()
+                .filter_map(|other| other.time.end)
+                .filter(|end| local_time::to_day_time(*end, boundary) > start)
+                .max_by_key(|end| local_time::to_day_time(*end, boundary));
+            Proposal {
+                issue: format!("{} was never stopped", activity),
+                fix: Fix::Close {
+                    id: activity.id,
+                    at: last_end.unwrap_or(end_of_day),
+                },
+                safe: last_end.is_some(),
+            }
+        })
+        .collect()
+}
+
+/// overlapping fragments of the same activity, these count only once in the closure
+/// but usually stem from pushing the same activity twice
+fn conflicts(day: &DayInner, policy: &ReconcilePolicy) -> Vec<Proposal> {
+    let mut groups: Vec<(Vec<&Activity>, Time)> = Vec::new();
+    for (activity, time) in completed(day, policy.boundary) {
+        let end = time.end_time_or_end_of_day();
+        let group = groups.iter_mut().find(|(group, group_end)| {
+            time.start < *group_end && same_activity(group[0], activity)
+        });
+        match group {
+            Some((group, group_end)) => {
+                group.push(activity);
+                *group_end = (*group_end).max(end);
+            }
+            None => groups.push((vec![activity], end)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(group, _)| group.len() > 1)
+        .map(|(group, _)| Proposal {
+            issue: format!(
+                "{} overlapping records of the same activity: {}",
+                group.len(),
+                group.iter().join(", ")
+            ),
+            fix: Fix::Merge {
+                ids: group.iter().map(|activity| activity.id).collect(),
+            },
+            safe: false,
+        })
+        .collect()
+}
+
+fn long_records(day: &DayInner, policy: &ReconcilePolicy) -> Vec<Proposal> {
+    completed(day, policy.boundary)
+        .into_iter()
+        .filter(|(_, time)| time.duration().unwrap_or_default() > policy.max_duration)
+        .map(|(activity, _)| Proposal {
+            issue: format!("{} is suspiciously long", activity),
+            fix: Fix::Delete { id: activity.id },
+            safe: false,
+        })
+        .collect()
+}
+
+/// time between the first start and the last end of the day not covered by any activity
+fn gaps(day: &DayInner, policy: &ReconcilePolicy) -> Vec<Proposal> {
+    let boundary = policy.boundary;
+    let mut proposals = Vec::new();
+    let mut covered_until: Option<Time> = None;
+
+    // open activities cover the rest of the day, they are running or reported as unterminated
+    let times = day
+        .activities
+        .iter()
+        .filter(|activity| !activity.time.is_inverted())
+        .map(|activity| activity.time.to_day_time(boundary))
+        .sorted_by_key(|time| time.start);
+    for time in times {
+        let end = time.end_time_or_end_of_day();
+        if let Some(until) = covered_until
+            && time.start - until >= policy.min_gap
+        {
+            let (from, to) = (
+                local_time::from_day_time(until, boundary),
+                local_time::from_day_time(time.start, boundary),
+            );
+            proposals.push(Proposal {
+                issue: format!(
+                    "nothing was tracked from {} to {}",
+                    format_time(from),
+                    format_time(to)
+                ),
+                fix: Fix::InsertBreak { from, to },
+                safe: false,
+            });
+        }
+        covered_until = Some(covered_until.map_or(end, |until| until.max(end)));
+    }
+
+    proposals
+}
+
+/// apply the fix to the day, returns none if the day is locked or the activities the fix refers
+/// to are gone. replaced activities are moved to the trash
+pub fn apply(
+    day: &mut DayInner,
+    fix: &Fix,
+    policy: &ReconcilePolicy,
+    now: OffsetDateTime,
+) -> Option<Applied> {
+    if day.locked {
+        return None;
+    }
+    let boundary = policy.boundary;
+    match fix {
+        Fix::Close { id, at } => {
+            let activity = day
+                .activities
+                .iter_mut()
+                .find(|activity| activity.id == *id && !activity.time.is_complete())?;
+            let removed = activity.clone();
+            activity.time.complete_at(*at, boundary);
+            activity.modified_at = Some(now);
+            Some(Applied {
+                removed: vec![removed],
+                added: vec![activity.clone()],
+            })
+        }
+        Fix::Merge { ids } => {
+            let fragments = ids
+                .iter()
+                .map(|id| day.activities.iter().find(|activity| activity.id == *id))
+                .collect::<Option<Vec<_>>>()?;
+            let (first, _) = fragments.split_first()?;
+
+            let times = fragments
+                .iter()
+                .map(|fragment| fragment.time.to_day_time(boundary))
+                .collect_vec();
+            let mut merged = (*first).clone();
+            merged.time = Interval {
+                start: times.iter().map(|time| time.start).min()?,
+                end: times.iter().filter_map(|time| time.end).max(),
+            }
+            .from_day_time(boundary);
+            merged.modified_at = Some(now);
+            merged.evidence = fragments
+                .iter()
+                .flat_map(|fragment| fragment.evidence.iter().cloned())
+                .sorted_by_key(|entry| entry.at)
+                .collect();
+
+            let removed = fragments.into_iter().cloned().collect_vec();
+            let position = day
+                .activities
+                .iter()
+                .position(|activity| ids.contains(&activity.id))?;
+            for id in ids {
+                day.delete_activity(*id, now);
+            }
+            day.activities.insert(position, merged.clone());
+            Some(Applied {
+                removed,
+                added: vec![merged],
+            })
+        }
+        Fix::InsertBreak { from, to } => {
+            let activity = Activity {
+                id: Uuid::new_v4(),
+                name: None,
+                class: policy.break_class.clone(),
+                time: Interval {
+                    start: *from,
+                    end: Some(*to),
+                },
+                projects: vec![],
+                weights: BTreeMap::new(),
+                description: None,
+                created_at: Some(now),
+                modified_at: None,
+                tags: vec![],
+                evidence: vec![],
+                origin: None,
+                extra: Default::default(),
+            };
+            day.activities.push(activity.clone());
+            Some(Applied {
+                removed: vec![],
+                added: vec![activity],
+            })
+        }
+        Fix::Delete { id } => Some(Applied {
+            removed: vec![day.delete_activity(*id, now)?],
+            added: vec![],
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::day::Day;
+    use crate::testing::at;
+
+    const MESSY_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/messy_day.json"
+    ));
+    const LONG_DAY: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/long_day.json"
+    ));
+
+    fn time(h: u8, m: u8) -> Time {
+        Time::from_hms(h, m, 0).unwrap()
+    }
+
+    fn policy() -> ReconcilePolicy {
+        ReconcilePolicy {
+            min_gap: Duration::minutes(15),
+            max_duration: Duration::hours(10),
+            break_class: Identifier::ByName("break".into()),
+            day_over: true,
+            boundary: None,
+        }
+    }
+
+    fn id(day: &Day, name: &str) -> Uuid {
+        day.inner
+            .activities
+            .iter()
+            .find(|activity| activity.name.as_deref() == Some(name))
+            .unwrap()
+            .id
+    }
+
+    #[test]
+    fn test_propose_messy_day() {
+        let day: Day = serde_json::from_str(MESSY_DAY).unwrap();
+        let proposals = propose(&day.inner, &policy());
+
+        let fixes = proposals.iter().map(|p| p.fix.clone()).collect_vec();
+        assert_eq!(
+            fixes,
+            [
+                Fix::Close {
+                    id: id(&day, "review"),
+                    at: time(16, 0)
+                },
+                Fix::Merge {
+                    ids: day.inner.activities[..2].iter().map(|a| a.id).collect()
+                },
+                Fix::InsertBreak {
+                    from: time(11, 0),
+                    to: time(11, 45)
+                },
+            ]
+        );
+        assert_eq!(
+            proposals.iter().map(|p| p.safe).collect_vec(),
+            [true, false, false]
+        );
+
+        // running activities of today are not stale
+        let today = ReconcilePolicy {
+            day_over: false,
+            ..policy()
+        };
+        assert_eq!(propose(&day.inner, &today).len(), 2);
+    }
+
+    #[test]
+    fn test_propose_long_day() {
+        let day: Day = serde_json::from_str(LONG_DAY).unwrap();
+        let proposals = propose(&day.inner, &policy());
+
+        assert_eq!(
+            proposals
+                .iter()
+                .map(|p| (p.fix.clone(), p.safe))
+                .collect_vec(),
+            [
+                (
+                    Fix::Close {
+                        id: id(&day, "standup"),
+                        at: time(23, 59)
+                    },
+                    false
+                ),
+                (
+                    Fix::Delete {
+                        id: id(&day, "timer")
+                    },
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let mut day: Day = serde_json::from_str(MESSY_DAY).unwrap();
+        let now = at(day.date.next_day().unwrap(), time(9, 0));
+        let policy = policy();
+
+        for proposal in propose(&day.inner, &policy) {
+            assert!(apply(&mut day.inner, &proposal.fix, &policy, now).is_some());
+        }
+        assert!(propose(&day.inner, &policy).is_empty());
+
+        let coding = day.inner.activities[0].clone();
+        assert_eq!(coding.name.as_deref(), Some("coding"));
+        assert_eq!(coding.time.start, time(8, 0));
+        assert_eq!(coding.time.end, Some(time(11, 0)));
+        assert_eq!(day.inner.activities.len(), 5);
+
+        // fixes of vanished activities are not applied
+        let gone = Fix::Delete { id: Uuid::nil() };
+        assert!(apply(&mut day.inner, &gone, &policy, now).is_none());
+    }
+
+    #[test]
+    fn test_merged_fragments_go_to_the_trash() {
+        let mut day: Day = serde_json::from_str(MESSY_DAY).unwrap();
+        let now = at(day.date.next_day().unwrap(), time(9, 0));
+        let policy = policy();
+        let merge = propose(&day.inner, &policy)
+            .into_iter()
+            .find(|proposal| matches!(proposal.fix, Fix::Merge { .. }))
+            .unwrap()
+            .fix;
+        let Fix::Merge { ids } = &merge else {
+            unreachable!()
+        };
+
+        let mut locked = day.inner.clone();
+        locked.locked = true;
+        assert!(apply(&mut locked, &merge, &policy, now).is_none());
+        assert!(locked.trash.is_empty());
+
+        let applied = apply(&mut day.inner, &merge, &policy, now).unwrap();
+        let trashed = (day.inner.trash.iter())
+            .map(|entry| entry.activity.id)
+            .collect::<Vec<_>>();
+        assert_eq!(&trashed, ids);
+        assert_eq!(applied.removed.len(), ids.len());
+    }
+}
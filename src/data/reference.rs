@@ -0,0 +1,340 @@
+use crate::az_hash::AZHash;
+use crate::data::activity::Activity;
+use crate::data::blocker::Blocker;
+use itertools::Itertools;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// hex digits a UUID prefix needs at least, shorter ones are read as hash prefixes only
+pub const MIN_UUID_PREFIX_LENGTH: usize = 4;
+
+/// activities and blockers which can be addressed by a [`Reference`]
+pub trait Referable: AZHash {
+    fn id(&self) -> Uuid;
+    fn name(&self) -> Option<&str>;
+}
+
+impl Referable for Activity {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl Referable for Blocker {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// an activity or blocker given on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reference {
+    /// a full UUID
+    Id(Uuid),
+    /// `@name`, the exact name
+    Name(String),
+    /// prefix of the UUID or of the az-hash (see [`Activity::short_id`]) ignoring case, or the
+    /// exact name if no prefix matches
+    Prefix(String),
+}
+
+#[derive(Debug)]
+pub enum ReferenceParseError {
+    Empty,
+}
+
+impl Display for ReferenceParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceParseError::Empty => write!(f, "Reference is empty"),
+        }
+    }
+}
+
+impl std::error::Error for ReferenceParseError {}
+
+impl FromStr for Reference {
+    type Err = ReferenceParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            Err(ReferenceParseError::Empty)
+        } else if let Some(name) = s.strip_prefix('@') {
+            Ok(Reference::Name(name.to_string()))
+        } else if let Ok(id) = Uuid::from_str(s) {
+            Ok(Reference::Id(id))
+        } else {
+            Ok(Reference::Prefix(s.to_string()))
+        }
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reference::Id(id) => write!(f, "{}", id),
+            Reference::Name(name) => write!(f, "@{}", name),
+            Reference::Prefix(prefix) => write!(f, "{}", prefix),
+        }
+    }
+}
+
+/// why a reference does not address a single item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceError {
+    NotFound,
+    /// the ids of all matching items
+    Ambiguous(Vec<Uuid>),
+}
+
+impl Reference {
+    /// the items the reference matches, in their order
+    pub fn matches<'a, T: Referable>(&self, items: &'a [T]) -> Vec<&'a T> {
+        let by_name = |name: &str| {
+            items
+                .iter()
+                .filter(|item| item.name() == Some(name))
+                .collect_vec()
+        };
+        match self {
+            Reference::Id(id) => items.iter().filter(|item| item.id() == *id).collect(),
+            Reference::Name(name) => by_name(name),
+            Reference::Prefix(prefix) => {
+                let matches = items
+                    .iter()
+                    .filter(|item| matches_prefix(*item, prefix))
+                    .unique_by(|item| item.id())
+                    .collect_vec();
+                if matches.is_empty() {
+                    by_name(prefix)
+                } else {
+                    matches
+                }
+            }
+        }
+    }
+
+    /// the single item the reference matches
+    pub fn resolve<'a, T: Referable>(&self, items: &'a [T]) -> Result<&'a T, ReferenceError> {
+        let matches = self.matches(items);
+        match (matches.as_slice(), self) {
+            ([], _) => Err(ReferenceError::NotFound),
+            ([item], _) => Ok(item),
+            // duplicated ids are a data error, keep using the first one
+            ([item, ..], Reference::Id(_)) => Ok(item),
+            _ => Err(ReferenceError::Ambiguous(
+                matches.iter().map(|item| item.id()).collect(),
+            )),
+        }
+    }
+}
+
+/// whether the UUID or the az-hash of the item starts with `prefix`, ignoring case. UUID
+/// prefixes may leave out the hyphens
+fn matches_prefix(item: &impl Referable, prefix: &str) -> bool {
+    let prefix = prefix.to_lowercase();
+    let simple = prefix.replace('-', "");
+    let is_uuid_prefix = simple.len() >= MIN_UUID_PREFIX_LENGTH
+        && prefix.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    let id = item.id();
+    (is_uuid_prefix
+        && (id.hyphenated().to_string().starts_with(&prefix)
+            || id.simple().to_string().starts_with(&simple)))
+        || (!prefix.is_empty() && item.az_hash_sha256().starts_with(&prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    use time::Time;
+
+    fn activity(id: u128, name: Option<&str>) -> Activity {
+        Activity {
+            id: Uuid::from_u128(id),
+            name: name.map(str::to_string),
+            ..testing::activity("work", Time::MIDNIGHT, None)
+        }
+    }
+
+    fn resolve(activities: &[Activity], reference: &str) -> Result<u128, ReferenceError> {
+        let reference = Reference::from_str(reference).unwrap();
+        (reference.resolve(activities)).map(|activity| activity.id.as_u128())
+    }
+
+    fn ambiguous(ids: &[u128]) -> Result<u128, ReferenceError> {
+        Err(ReferenceError::Ambiguous(
+            ids.iter().copied().map(Uuid::from_u128).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_parse() {
+        let id = Uuid::from_u128(0xabcd);
+        assert_eq!(
+            Reference::from_str(&id.to_string()).unwrap(),
+            Reference::Id(id)
+        );
+        assert_eq!(
+            Reference::from_str(&id.simple().to_string().to_uppercase()).unwrap(),
+            Reference::Id(id)
+        );
+        assert_eq!(
+            Reference::from_str("@Meeting").unwrap(),
+            Reference::Name("Meeting".into())
+        );
+        assert_eq!(
+            Reference::from_str("Abc1").unwrap(),
+            Reference::Prefix("Abc1".into())
+        );
+        assert!(Reference::from_str("").is_err());
+        for reference in ["@coding", "abcd", &id.to_string()] {
+            assert_eq!(
+                Reference::from_str(reference).unwrap().to_string(),
+                reference
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_uuids() {
+        let activities = [
+            activity(0xabcd_0001 << 96, None),
+            activity(0xabcd_0002 << 96, None),
+            activity(0x1234_0000 << 96, None),
+        ];
+        let first = activities[0].id;
+
+        assert_eq!(
+            resolve(&activities, &first.to_string()),
+            Ok(first.as_u128())
+        );
+        assert_eq!(resolve(&activities, "abcd0001"), Ok(first.as_u128()));
+        // with or without hyphens, in any case
+        assert_eq!(resolve(&activities, "ABCD0001-0000"), Ok(first.as_u128()));
+        assert_eq!(resolve(&activities, "abcd00010000"), Ok(first.as_u128()));
+        assert_eq!(resolve(&activities, "1234"), Ok(activities[2].id.as_u128()));
+        // the prefix is shared
+        assert_eq!(
+            resolve(&activities, "abcd000"),
+            ambiguous(&[0xabcd_0001 << 96, 0xabcd_0002 << 96])
+        );
+        assert_eq!(
+            resolve(&activities, "abcd0003"),
+            Err(ReferenceError::NotFound)
+        );
+        // too short for a UUID prefix, and no hash consists of digits
+        assert_eq!(resolve(&activities, "123"), Err(ReferenceError::NotFound));
+        assert_eq!(resolve(&activities, "-"), Err(ReferenceError::NotFound));
+        assert_eq!(
+            resolve(&activities, &Uuid::from_u128(7).to_string()),
+            Err(ReferenceError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_resolve_hashes() {
+        let activities = (1..=40).map(|id| activity(id, None)).collect_vec();
+        let first = &activities[0];
+
+        assert_eq!(resolve(&activities, &first.short_id()), Ok(1));
+        assert_eq!(
+            resolve(&activities, &first.short_id().to_uppercase()),
+            Ok(1)
+        );
+        assert_eq!(resolve(&activities, &first.az_hash_sha256()), Ok(1));
+
+        // a single letter is shared by several of the 40 hashes
+        let letter = &first.short_id()[..1];
+        let sharing = (activities.iter())
+            .filter(|activity| activity.short_id().starts_with(letter))
+            .map(|activity| activity.id.as_u128())
+            .collect_vec();
+        assert!(sharing.len() > 1);
+        assert_eq!(resolve(&activities, letter), ambiguous(&sharing));
+
+        // the shortest prefix telling the first activity apart
+        let short_id = first.short_id();
+        let unique = (1..=short_id.len())
+            .map(|length| &short_id[..length])
+            .find(|prefix| {
+                (activities.iter())
+                    .filter(|activity| activity.short_id().starts_with(prefix))
+                    .count()
+                    == 1
+            })
+            .unwrap();
+        assert_eq!(resolve(&activities, unique), Ok(1));
+    }
+
+    #[test]
+    fn test_resolve_names() {
+        let activities = [
+            activity(1, Some("meeting")),
+            activity(2, Some("coding")),
+            activity(3, Some("coding")),
+            activity(4, Some("Review")),
+        ];
+
+        assert_eq!(resolve(&activities, "@meeting"), Ok(1));
+        assert_eq!(resolve(&activities, "meeting"), Ok(1));
+        assert_eq!(resolve(&activities, "@coding"), ambiguous(&[2, 3]));
+        assert_eq!(resolve(&activities, "coding"), ambiguous(&[2, 3]));
+        // names are exact
+        assert_eq!(resolve(&activities, "Review"), Ok(4));
+        assert_eq!(
+            resolve(&activities, "review"),
+            Err(ReferenceError::NotFound)
+        );
+        assert_eq!(
+            resolve(&activities, "@Meeting"),
+            Err(ReferenceError::NotFound)
+        );
+        assert_eq!(resolve(&activities, "@"), Err(ReferenceError::NotFound));
+    }
+
+    #[test]
+    fn test_prefixes_take_precedence_over_names() {
+        let mut activities = [activity(0xbeef << 112, None), activity(2, None)];
+        activities[1].name = Some("beef".into());
+        assert_eq!(resolve(&activities, "beef"), Ok(0xbeef << 112));
+        assert_eq!(resolve(&activities, "@beef"), Ok(2));
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let activities = [activity(1, Some("first")), activity(1, Some("second"))];
+        let first = Reference::Id(Uuid::from_u128(1)).resolve(&activities);
+        assert_eq!(first.unwrap().name.as_deref(), Some("first"));
+        // a prefix matches the id once
+        let prefix = Reference::from_str("00000000").unwrap();
+        assert_eq!(prefix.matches(&activities).len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_blockers() {
+        let blockers = [activity(1, Some("dentist")), activity(2, None)]
+            .iter()
+            .map(|activity| Blocker {
+                id: activity.id,
+                name: activity.name.clone(),
+                class: activity.class.clone(),
+                time: activity.time.clone(),
+                projects: vec![],
+            })
+            .collect_vec();
+        let reference = Reference::from_str(&blockers[1].short_id()).unwrap();
+        assert_eq!(reference.resolve(&blockers).unwrap().id, blockers[1].id);
+        let reference = Reference::from_str("dentist").unwrap();
+        assert_eq!(reference.resolve(&blockers).unwrap().id, blockers[0].id);
+    }
+}
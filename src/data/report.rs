@@ -0,0 +1,1043 @@
+use crate::data::activity::Activity;
+use crate::data::activity_closure::ClosureDiagnostics;
+use crate::data::attribution::Attribution;
+use crate::data::blocker::Blocker;
+use crate::data::break_rules::BreakCompliance;
+use crate::data::day::{DayInner, DayType};
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use crate::data::safe_duration::SafeDuration;
+use std::collections::BTreeMap;
+use time::{Date, Duration, OffsetDateTime, Time, Weekday};
+
+/// closure of a day, clamped to the current time if the day is today. blockers count like
+/// activities, see [`DayInner::effective_timeline`]
+pub fn day_closure(
+    job_config: &JobConfig,
+    date: Date,
+    day: &DayInner,
+    now: OffsetDateTime,
+) -> (Vec<Activity>, ClosureDiagnostics) {
+    filtered_day_closure(job_config, &ReportFilter::default(), date, day, now)
+}
+
+/// closure of the activities and blockers of a day passing the filter, see [`day_closure`]
+pub fn filtered_day_closure(
+    job_config: &JobConfig,
+    filter: &ReportFilter,
+    date: Date,
+    day: &DayInner,
+    now: OffsetDateTime,
+) -> (Vec<Activity>, ClosureDiagnostics) {
+    let end = (date == job_config.date_of(now)).then_some(now.time());
+    filter
+        .apply(job_config, day)
+        .effective_timeline(job_config, end)
+}
+
+/// activities and blockers of the day passing the filter as they are, without folding them into
+/// a closure, so overlapping time counts once per activity. times are in day time, open
+/// activities end at `end` and are left out without it
+pub fn raw_segments(
+    job_config: &JobConfig,
+    filter: &ReportFilter,
+    day: &DayInner,
+    end: Option<Time>,
+) -> Vec<Activity> {
+    let boundary = job_config.day_boundary;
+    filter
+        .apply(job_config, day)
+        .timeline_activities()
+        .into_iter()
+        .filter_map(|activity| {
+            let mut time = activity.time.to_day_time(boundary);
+            if time.end.is_none() {
+                let end = local_time::to_day_time(end?, boundary);
+                time.end = Some(end.max(time.start));
+            }
+            Some(Activity { time, ..activity })
+        })
+        .collect()
+}
+
+/// restricts the activities taken into account, empty lists do not restrict anything
+///
+/// activities are filtered before folding them into a closure
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportFilter {
+    /// activities of any of these classes
+    pub classes: Vec<Identifier>,
+    /// activities with any of these projects
+    pub projects: Vec<Identifier>,
+    /// activities with any of these tags
+    pub tags: Vec<String>,
+    /// first day taken into account
+    pub from: Option<Date>,
+    /// last day taken into account
+    pub to: Option<Date>,
+}
+
+impl ReportFilter {
+    pub fn includes_date(&self, date: Date) -> bool {
+        self.from.is_none_or(|from| from <= date) && self.to.is_none_or(|to| date <= to)
+    }
+
+    /// the activity passes the class, project and tag restrictions
+    pub fn matches(&self, job_config: &JobConfig, activity: &Activity) -> bool {
+        let same_class = |filter: &Identifier| match job_config.resolve_class(filter) {
+            Some(class) => class.identifier_matches(&activity.class),
+            None => filter == &activity.class,
+        };
+        let same_project = |filter: &Identifier| {
+            activity
+                .projects
+                .iter()
+                .any(|project| match job_config.resolve_project(filter) {
+                    Some(resolved) => resolved.identifier_matches(project),
+                    None => filter == project,
+                })
+        };
+
+        (self.classes.is_empty() || self.classes.iter().any(same_class))
+            && (self.projects.is_empty() || self.projects.iter().any(same_project))
+            && (self.tags.is_empty() || self.tags.iter().any(|tag| activity.tags.contains(tag)))
+    }
+
+    /// activities of the day passing the filter
+    pub fn activities<'a>(&self, job_config: &JobConfig, day: &'a DayInner) -> Vec<&'a Activity> {
+        day.activities
+            .iter()
+            .filter(|activity| self.matches(job_config, activity))
+            .collect()
+    }
+
+    /// copy of the day with only the activities and blockers passing the filter
+    pub fn apply(&self, job_config: &JobConfig, day: &DayInner) -> DayInner {
+        DayInner {
+            activities: self
+                .activities(job_config, day)
+                .into_iter()
+                .cloned()
+                .collect(),
+            blockers: day
+                .blockers
+                .iter()
+                .filter(|blocker| self.matches(job_config, &blocker.to_activity()))
+                .cloned()
+                .collect(),
+            ..day.clone()
+        }
+    }
+
+    /// classes of the filter the job config does not know
+    pub fn unknown_classes(&self, job_config: &JobConfig) -> Vec<&Identifier> {
+        self.classes
+            .iter()
+            .filter(|class| job_config.resolve_class(*class).is_none())
+            .collect()
+    }
+
+    /// projects of the filter the job config does not know
+    pub fn unknown_projects(&self, job_config: &JobConfig) -> Vec<&Identifier> {
+        self.projects
+            .iter()
+            .filter(|project| job_config.resolve_project(*project).is_none())
+            .collect()
+    }
+}
+
+/// kind of entry a share belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareKind {
+    Class,
+    Project,
+}
+
+/// share of a class or project in the tracked time of a period
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    pub kind: ShareKind,
+    pub name: String,
+    /// percentage of the tracked time
+    pub actual: f32,
+    /// targeted percentage, if configured
+    pub target: Option<f32>,
+}
+
+impl Share {
+    /// actual minus targeted share in percentage points
+    pub fn deviation(&self) -> Option<f32> {
+        self.target.map(|target| self.actual - target)
+    }
+
+    /// deviation from the target is larger than the tolerance (in percentage points)
+    pub fn exceeds(&self, tolerance: f32) -> bool {
+        self.deviation()
+            .is_some_and(|deviation| deviation.abs() > tolerance)
+    }
+}
+
+/// percentage of `part` in `total`, zero if nothing was tracked
+pub fn percentage(part: Duration, total: Duration) -> f32 {
+    if total <= Duration::ZERO {
+        0.0
+    } else {
+        (part.as_seconds_f64() / total.as_seconds_f64() * 100.0) as f32
+    }
+}
+
+/// closure time of the classes counted towards the work quota
+pub fn quota_time(job_config: &JobConfig, closure: &[Activity]) -> Duration {
+    closure
+        .iter()
+        .filter(|segment| job_config.counts_towards_quota(&segment.class))
+        .map(|segment| segment.time.duration().unwrap_or_default())
+        .sum::<SafeDuration>()
+        .total()
+}
+
+/// weekends have no work quota
+pub fn is_working_day(date: Date) -> bool {
+    !matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday)
+}
+
+/// default work quota of each weekday, see [`AppConfig::daily_quotas`]
+///
+/// [`AppConfig::daily_quotas`]: crate::data::app_config::AppConfig::daily_quotas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyQuotas([Duration; 7]);
+
+impl DailyQuotas {
+    /// `quota` on working days and nothing on weekends
+    pub fn uniform(quota: Duration) -> Self {
+        Self([
+            quota,
+            quota,
+            quota,
+            quota,
+            quota,
+            Duration::ZERO,
+            Duration::ZERO,
+        ])
+    }
+
+    /// the quotas with the one of `weekday` replaced
+    pub fn with(mut self, weekday: Weekday, quota: Duration) -> Self {
+        self.0[weekday.number_days_from_monday() as usize] = quota;
+        self
+    }
+
+    /// default quota of the weekday of `date`
+    pub fn of(&self, date: Date) -> Duration {
+        self.0[date.weekday().number_days_from_monday() as usize]
+    }
+}
+
+/// work quota of the day, the override of the day if it has one, otherwise nothing on public
+/// holidays and the default of its weekday on other days
+pub fn day_quota(
+    job_config: &JobConfig,
+    date: Date,
+    day: Option<&DayInner>,
+    defaults: DailyQuotas,
+) -> Duration {
+    match day.and_then(|day| day.work_quota) {
+        Some(quota) => quota,
+        None if job_config.public_holiday(date).is_some() => Duration::ZERO,
+        None => defaults.of(date),
+    }
+}
+
+/// Monday of the ISO week containing `date`
+pub fn iso_week_start(date: Date) -> Date {
+    week_start(date, Weekday::Monday)
+}
+
+/// first day of the week containing `date`, weeks starting on `first`
+pub fn week_start(date: Date, first: Weekday) -> Date {
+    let offset =
+        (date.weekday().number_days_from_monday() + 7 - first.number_days_from_monday()) % 7;
+    date - Duration::days(offset as i64)
+}
+
+/// when the weekly quota is expected to be met
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaForecast {
+    /// the quota is already met
+    Met,
+    /// working day on which the quota is met at the current pace
+    On(Date),
+    /// nothing tracked yet, the pace is unknown
+    Unknown,
+}
+
+/// work quota progress of an ISO week up to and including `date`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekProgress {
+    /// last day taken into account
+    pub date: Date,
+    /// quota time tracked from Monday up to `date`
+    pub tracked: Duration,
+    /// default quota of each weekday
+    pub defaults: DailyQuotas,
+    /// quota of each day of the week, see [`day_quota`]
+    pub quotas: DailyQuotas,
+}
+
+impl WeekProgress {
+    /// sum up the quota time of the week containing `date`, days after `date` are ignored.
+    /// blockers count like activities, see [`DayInner::effective_timeline`]
+    pub fn new(
+        job_config: &JobConfig,
+        manager: &Manager,
+        date: Date,
+        now: OffsetDateTime,
+        defaults: DailyQuotas,
+    ) -> Self {
+        let tracked = manager
+            .days_in(iso_week_start(date)..=date)
+            .map(|(day_date, day)| {
+                let end = (*day_date == job_config.date_of(now)).then_some(now.time());
+                let (closure, _) = day.effective_timeline(job_config, end);
+                quota_time(job_config, &closure)
+            })
+            .sum::<SafeDuration>()
+            .total();
+        let start = iso_week_start(date);
+        let quotas = DailyQuotas(std::array::from_fn(|weekday| {
+            let date = start + Duration::days(weekday as i64);
+            day_quota(job_config, date, manager.day(date), defaults)
+        }));
+        Self {
+            date,
+            tracked,
+            defaults,
+            quotas,
+        }
+    }
+
+    /// quotas of the days from Monday up to and including `last`
+    fn quota_until(&self, last: Date) -> Duration {
+        let mut date = iso_week_start(self.date);
+        let mut quota = SafeDuration::default();
+        while date <= last {
+            quota += self.quotas.of(date);
+            date += Duration::days(1);
+        }
+        quota.total()
+    }
+
+    /// working days from Monday up to and including `date`
+    pub fn elapsed_working_days(&self) -> u32 {
+        (self.date.weekday().number_days_from_monday() as u32 + 1).min(5)
+    }
+
+    /// quota of the days up to `date`
+    pub fn quota_so_far(&self) -> Duration {
+        self.quota_until(self.date)
+    }
+
+    /// quota of all days of the week
+    pub fn week_quota(&self) -> Duration {
+        self.quota_until(iso_week_start(self.date) + Duration::days(6))
+    }
+
+    /// day on which the weekly quota is met, assuming the average pace of the elapsed working days
+    pub fn forecast(&self) -> QuotaForecast {
+        let remaining = self.week_quota().saturating_sub(self.tracked);
+        if remaining <= Duration::ZERO {
+            return QuotaForecast::Met;
+        }
+        let elapsed = self.elapsed_working_days();
+        if elapsed == 0 || self.tracked <= Duration::ZERO {
+            return QuotaForecast::Unknown;
+        }
+
+        let pace = self.tracked / elapsed;
+        let mut days = (remaining.as_seconds_f64() / pace.as_seconds_f64()).ceil() as u32;
+        let mut date = self.date;
+        while days > 0 {
+            let Some(next) = date.next_day() else {
+                return QuotaForecast::Unknown;
+            };
+            date = next;
+            if is_working_day(date) {
+                days -= 1;
+            }
+        }
+        QuotaForecast::On(date)
+    }
+}
+
+/// quota time tracked on a day against its work quota, see [`day_balances`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayBalance {
+    pub date: Date,
+    /// quota time tracked on the day
+    pub tracked: Duration,
+    /// work quota of the day, see [`day_quota`]
+    pub quota: Duration,
+    /// time missing on holidays is no deficit
+    pub holiday: bool,
+    /// name of the public holiday on the day, see [`JobConfig::holidays`]
+    pub public_holiday: Option<String>,
+}
+
+impl DayBalance {
+    /// overtime of the day, negative if time is missing
+    pub fn delta(&self) -> Duration {
+        let delta = self.tracked - self.quota;
+        if self.holiday {
+            delta.max(Duration::ZERO)
+        } else {
+            delta
+        }
+    }
+}
+
+/// balance of each day from `from` up to and including `to`, days without a file are
+/// untracked. blockers count like activities, see [`DayInner::effective_timeline`]
+pub fn day_balances(
+    job_config: &JobConfig,
+    manager: &Manager,
+    from: Date,
+    to: Date,
+    now: OffsetDateTime,
+    defaults: DailyQuotas,
+) -> Vec<DayBalance> {
+    let today = job_config.date_of(now);
+    std::iter::successors(Some(from), |date| date.next_day())
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let day = manager.day(date);
+            let tracked = day.map_or(Duration::ZERO, |day| {
+                let end = (date == today).then_some(now.time());
+                let (closure, _) = day.effective_timeline(job_config, end);
+                quota_time(job_config, &closure)
+            });
+            DayBalance {
+                date,
+                tracked,
+                quota: day_quota(job_config, date, day, defaults),
+                holiday: day.is_some_and(|day| day.day_type == DayType::Holiday),
+                public_holiday: job_config
+                    .public_holiday(date)
+                    .map(|holiday| holiday.name.clone()),
+            }
+        })
+        .collect()
+}
+
+/// break compliance of the tracked days from `from` to `to`, leaving out holidays
+pub fn day_compliance(
+    job_config: &JobConfig,
+    manager: &Manager,
+    from: Date,
+    to: Date,
+    now: OffsetDateTime,
+) -> Vec<(Date, BreakCompliance)> {
+    let today = job_config.date_of(now);
+    manager
+        .days_in(from..=to)
+        .filter(|(_, day)| day.day_type != DayType::Holiday)
+        .map(|(date, day)| {
+            let end = (*date == today).then_some(now.time());
+            let (closure, _) = day.effective_timeline(job_config, end);
+            (*date, BreakCompliance::of(job_config, &closure))
+        })
+        .collect()
+}
+
+/// aggregated time per class and project
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Totals {
+    /// time per class
+    pub classes: BTreeMap<Identifier, Duration>,
+    /// time per project, according to `attribution`
+    pub projects: BTreeMap<Identifier, Duration>,
+    /// time per client of the projects, `None` for projects without a client
+    pub clients: BTreeMap<Option<String>, Duration>,
+    /// blocked time per class, open blockers clamped to the current time
+    pub blockers: BTreeMap<Identifier, Duration>,
+    /// policy used to attribute time to projects
+    pub attribution: Attribution,
+    /// all closure segments added, counts the ones skipped for their negative duration
+    pub segments: SafeDuration,
+}
+
+impl Totals {
+    pub fn new(attribution: Attribution) -> Self {
+        Self {
+            attribution,
+            ..Self::default()
+        }
+    }
+
+    /// add the segments of a closure to the totals
+    pub fn add_closure(&mut self, job_config: &JobConfig, closure: &[Activity]) {
+        self.segments.merge(Activity::sum_durations(closure));
+        for (class, duration) in Activity::summarize_by_class(closure) {
+            let total = self.classes.entry(class).or_insert(Duration::ZERO);
+            *total = total.saturating_add(duration);
+        }
+        for (project, duration) in Activity::summarize_by_project(closure, self.attribution) {
+            let total = self.projects.entry(project).or_insert(Duration::ZERO);
+            *total = total.saturating_add(duration);
+        }
+        for (client, duration) in
+            Activity::summarize_by_client(job_config, closure, self.attribution)
+        {
+            let total = self.clients.entry(client).or_insert(Duration::ZERO);
+            *total = total.saturating_add(duration);
+        }
+    }
+
+    /// add the blockers of a day, open blockers count until `end_limit`
+    pub fn add_blockers(
+        &mut self,
+        blockers: &[Blocker],
+        end_limit: Option<Time>,
+        boundary: Option<Time>,
+    ) {
+        for (class, duration) in Blocker::summarize_by_class(blockers, end_limit, boundary) {
+            let total = self.blockers.entry(class).or_insert(Duration::ZERO);
+            *total = total.saturating_add(duration);
+        }
+    }
+
+    /// total time over all classes
+    pub fn total(&self) -> Duration {
+        self.classes.values().copied().sum::<SafeDuration>().total()
+    }
+
+    /// share of every class and project in the total tracked time
+    ///
+    /// project time follows the attribution policy of the totals. entries without
+    /// time are only listed if they have a target share
+    pub fn shares(&self, job_config: &JobConfig) -> Vec<Share> {
+        let total = self.total();
+        let mut shares = Vec::new();
+
+        let mut classes: BTreeMap<String, (Duration, Option<f32>)> = BTreeMap::new();
+        for class in &job_config.classes {
+            classes.insert(
+                class.inner.name.clone(),
+                (Duration::ZERO, class.inner.target_share),
+            );
+        }
+        for (class, duration) in &self.classes {
+            let name = job_config
+                .resolve_class(class)
+                .map(|class| class.inner.name.clone())
+                .unwrap_or_else(|| class.to_string());
+            classes.entry(name).or_insert((Duration::ZERO, None)).0 += *duration;
+        }
+
+        let mut projects: BTreeMap<String, (Duration, Option<f32>)> = BTreeMap::new();
+        for project in &job_config.projects {
+            projects.insert(
+                project.inner.name.clone(),
+                (Duration::ZERO, project.inner.target_share),
+            );
+        }
+        for (project, duration) in &self.projects {
+            let name = job_config
+                .resolve_project(project)
+                .map(|project| project.inner.name.clone())
+                .unwrap_or_else(|| project.to_string());
+            projects.entry(name).or_insert((Duration::ZERO, None)).0 += *duration;
+        }
+
+        for (kind, entries) in [(ShareKind::Class, classes), (ShareKind::Project, projects)] {
+            for (name, (duration, target)) in entries {
+                if duration > Duration::ZERO || target.is_some() {
+                    shares.push(Share {
+                        kind,
+                        name,
+                        actual: percentage(duration, total),
+                        target,
+                    });
+                }
+            }
+        }
+
+        shares
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity_class::{ActivityClass, ActivityClassInner};
+    use crate::data::app_config::AppConfig;
+    use crate::data::day::Day;
+    use crate::data::project::{Project, ProjectInner};
+    use crate::testing;
+    use time::{Month, Time};
+    use uuid::Uuid;
+
+    fn fixture_job_config() -> JobConfig {
+        fixture_job_config_with_clients([None, None])
+    }
+
+    fn fixture_job_config_with_clients(clients: [Option<&str>; 2]) -> JobConfig {
+        JobConfig {
+            classes: vec![ActivityClass {
+                id: Uuid::from_u128(1),
+                inner: ActivityClassInner {
+                    name: "work".into(),
+                    priority: 1,
+                    description: None,
+                    target_share: None,
+                    color: None,
+                    exclusive: false,
+                },
+            }],
+            projects: ["projA", "projB"]
+                .into_iter()
+                .zip(clients)
+                .enumerate()
+                .map(|(i, (name, client))| Project {
+                    id: Uuid::from_u128(10 + i as u128),
+                    inner: ProjectInner {
+                        name: name.into(),
+                        description: None,
+                        client: client.map(String::from),
+                        target_share: None,
+                    },
+                })
+                .collect(),
+            ..JobConfig::default()
+        }
+    }
+
+    fn activity(start: u8, end: u8, projects: &[&str], weights: &[(&str, f32)]) -> Activity {
+        Activity {
+            projects: projects
+                .iter()
+                .map(|p| Identifier::ByName(p.to_string()))
+                .collect(),
+            weights: weights
+                .iter()
+                .map(|(p, w)| (Identifier::ByName(p.to_string()), *w))
+                .collect(),
+            ..testing::activity(
+                "work",
+                Time::from_hms(start, 0, 0).unwrap(),
+                Some(Time::from_hms(end, 0, 0).unwrap()),
+            )
+        }
+    }
+
+    /// 9-12 on projA, 10-11 additionally on projB (weight 3), 13-14 on both
+    fn fixture_day() -> DayInner {
+        DayInner {
+            activities: vec![
+                activity(9, 12, &["projA"], &[]),
+                activity(10, 11, &["projB"], &[("projB", 3.0)]),
+                activity(13, 14, &["projA", "projB"], &[]),
+            ],
+            ..DayInner::default()
+        }
+    }
+
+    fn totals(job_config: &JobConfig, attribution: Attribution) -> Totals {
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let now = date.next_day().unwrap().midnight().assume_utc();
+
+        let mut totals = Totals::new(attribution);
+        totals.add_closure(
+            job_config,
+            &day_closure(job_config, date, &fixture_day(), now).0,
+        );
+        totals
+    }
+
+    fn project_totals(attribution: Attribution) -> (Totals, Duration, Duration) {
+        let totals = totals(&fixture_job_config(), attribution);
+
+        let get = |name: &str| {
+            totals
+                .projects
+                .get(&Identifier::ByName(name.into()))
+                .copied()
+                .unwrap_or_default()
+        };
+        let (a, b) = (get("projA"), get("projB"));
+        (totals, a, b)
+    }
+
+    #[test]
+    fn test_attribution_policies() {
+        let (totals, a, b) = project_totals(Attribution::Full);
+        assert_eq!(totals.total(), Duration::hours(4));
+        assert_eq!((a, b), (Duration::hours(4), Duration::hours(2)));
+
+        let (_, a, b) = project_totals(Attribution::SplitEvenly);
+        assert_eq!((a, b), (Duration::hours(3), Duration::hours(1)));
+
+        let (_, a, b) = project_totals(Attribution::FirstOnly);
+        assert_eq!((a, b), (Duration::hours(4), Duration::ZERO));
+
+        let (_, a, b) = project_totals(Attribution::Weighted);
+        assert_eq!((a, b), (Duration::minutes(165), Duration::minutes(75)));
+    }
+
+    #[test]
+    fn test_split_policies_preserve_tracked_time() {
+        for attribution in [
+            Attribution::SplitEvenly,
+            Attribution::FirstOnly,
+            Attribution::Weighted,
+        ] {
+            let (totals, _, _) = project_totals(attribution);
+            assert_eq!(
+                totals.projects.values().copied().sum::<Duration>(),
+                totals.total(),
+                "{attribution}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_totals_skip_negative_segments() {
+        let job_config = fixture_job_config();
+        // an inverted segment, as a hand-edited file or a faulty import could produce it
+        let closure = [
+            activity(9, 12, &["projA"], &[]),
+            activity(15, 13, &["projA", "projB"], &[]),
+        ];
+        let mut totals = Totals::new(Attribution::SplitEvenly);
+        totals.add_closure(&job_config, &closure);
+
+        assert_eq!(totals.total(), Duration::hours(3));
+        assert_eq!(
+            totals.projects[&Identifier::ByName("projA".into())],
+            Duration::hours(3)
+        );
+        assert!(totals.projects.values().all(|d| !d.is_negative()));
+        assert!(totals.clients.values().all(|d| !d.is_negative()));
+        assert_eq!(quota_time(&job_config, &closure), Duration::hours(3));
+        assert_eq!(totals.segments.negative(), 1);
+        assert!(totals.segments.warning().is_some());
+        assert_eq!(totals.segments.total(), Duration::hours(3));
+    }
+
+    #[test]
+    fn test_totals_saturate() {
+        let work = Identifier::ByName("work".into());
+        let mut totals = Totals::default();
+        totals.classes.insert(work.clone(), Duration::MAX);
+        totals
+            .classes
+            .insert(Identifier::ByName("break".into()), Duration::MAX);
+        assert_eq!(totals.total(), Duration::MAX);
+
+        totals.add_closure(&fixture_job_config(), &[activity(9, 12, &["projA"], &[])]);
+        assert_eq!(totals.classes[&work], Duration::MAX);
+        assert!(totals.segments.is_exact());
+    }
+
+    #[test]
+    fn test_clients_count_shared_segments_once() {
+        let client = |name: &str| Some(name.to_string());
+
+        let job_config = fixture_job_config_with_clients([Some("acme"), Some("acme")]);
+        for attribution in [
+            Attribution::Full,
+            Attribution::SplitEvenly,
+            Attribution::FirstOnly,
+            Attribution::Weighted,
+        ] {
+            assert_eq!(
+                totals(&job_config, attribution).clients,
+                BTreeMap::from([(client("acme"), Duration::hours(4))]),
+                "{attribution}"
+            );
+        }
+
+        let job_config = fixture_job_config_with_clients([Some("acme"), None]);
+        assert_eq!(
+            totals(&job_config, Attribution::Full).clients,
+            BTreeMap::from([
+                (None, Duration::hours(2)),
+                (client("acme"), Duration::hours(4))
+            ])
+        );
+        assert_eq!(
+            totals(&job_config, Attribution::SplitEvenly).clients,
+            BTreeMap::from([
+                (None, Duration::hours(1)),
+                (client("acme"), Duration::hours(3))
+            ])
+        );
+    }
+
+    #[test]
+    fn test_percentage_of_empty_period() {
+        assert_eq!(percentage(Duration::ZERO, Duration::ZERO), 0.0);
+        assert_eq!(percentage(Duration::hours(1), Duration::hours(4)), 25.0);
+    }
+
+    #[test]
+    fn test_shares_follow_attribution() {
+        let mut job_config = fixture_job_config();
+        job_config.projects[0].inner.target_share = Some(70.0);
+        job_config.projects[1].inner.target_share = Some(30.0);
+
+        let share = |shares: &[Share], name: &str| {
+            shares
+                .iter()
+                .find(|s| s.kind == ShareKind::Project && s.name == name)
+                .cloned()
+                .unwrap()
+        };
+
+        // 4h tracked: projA 3h, projB 1h when splitting evenly
+        let shares = totals(&job_config, Attribution::SplitEvenly).shares(&job_config);
+        assert_eq!(share(&shares, "projA").actual, 75.0);
+        assert_eq!(share(&shares, "projB").deviation(), Some(-5.0));
+        assert!(!share(&shares, "projA").exceeds(5.0));
+        assert!(share(&shares, "projB").exceeds(4.0));
+
+        // first-only hands everything to projA
+        let shares = totals(&job_config, Attribution::FirstOnly).shares(&job_config);
+        assert_eq!(share(&shares, "projA").actual, 100.0);
+        assert_eq!(share(&shares, "projB").actual, 0.0);
+
+        let work = shares.iter().find(|s| s.kind == ShareKind::Class).unwrap();
+        assert_eq!(
+            (work.name.as_str(), work.actual, work.target),
+            ("work", 100.0, None)
+        );
+    }
+
+    #[test]
+    fn test_shares_of_empty_period() {
+        let mut job_config = fixture_job_config();
+        job_config.projects[0].inner.target_share = Some(70.0);
+
+        let shares = Totals::default().shares(&job_config);
+
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0].actual, 0.0);
+        assert_eq!(shares[0].deviation(), Some(-70.0));
+    }
+
+    fn eight_hours() -> DailyQuotas {
+        DailyQuotas::uniform(Duration::hours(8))
+    }
+
+    fn week_progress(date: Date, tracked_hours: i64) -> WeekProgress {
+        WeekProgress {
+            date,
+            tracked: Duration::hours(tracked_hours),
+            defaults: eight_hours(),
+            quotas: eight_hours(),
+        }
+    }
+
+    #[test]
+    fn test_forecast_on_first_day_of_week() {
+        let monday = Date::from_calendar_date(2024, Month::May, 6).unwrap();
+        assert_eq!(iso_week_start(monday), monday);
+
+        let progress = week_progress(monday, 10);
+        assert_eq!(progress.elapsed_working_days(), 1);
+        assert_eq!(progress.quota_so_far(), Duration::hours(8));
+        // 30h left at 10h per day
+        assert_eq!(
+            progress.forecast(),
+            QuotaForecast::On(Date::from_calendar_date(2024, Month::May, 9).unwrap())
+        );
+
+        assert_eq!(week_progress(monday, 0).forecast(), QuotaForecast::Unknown);
+    }
+
+    #[test]
+    fn test_forecast_skips_weekend() {
+        let thursday = Date::from_calendar_date(2024, Month::May, 9).unwrap();
+        assert_eq!(
+            iso_week_start(thursday),
+            Date::from_calendar_date(2024, Month::May, 6).unwrap()
+        );
+
+        // 6h per day, 16h left, done on tuesday of the next week
+        let progress = week_progress(thursday, 24);
+        assert_eq!(
+            progress.forecast(),
+            QuotaForecast::On(Date::from_calendar_date(2024, Month::May, 14).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_forecast_already_met() {
+        let friday = Date::from_calendar_date(2024, Month::May, 10).unwrap();
+        assert_eq!(week_progress(friday, 40).forecast(), QuotaForecast::Met);
+
+        let sunday = Date::from_calendar_date(2024, Month::May, 12).unwrap();
+        let progress = week_progress(sunday, 42);
+        assert_eq!(progress.elapsed_working_days(), 5);
+        assert_eq!(progress.forecast(), QuotaForecast::Met);
+    }
+
+    #[test]
+    fn test_week_progress_counts_quota_classes() {
+        let job_config = fixture_job_config();
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let now = date.next_day().unwrap().midnight().assume_utc();
+        let config = AppConfig::default();
+        let days = Manager::in_memory(
+            &config,
+            [
+                Day {
+                    date,
+                    inner: fixture_day(),
+                },
+                // after the date, ignored
+                Day {
+                    date: date.next_day().unwrap(),
+                    inner: fixture_day(),
+                },
+            ],
+        );
+
+        let progress = WeekProgress::new(&job_config, &days, date, now, eight_hours());
+        let (closure, _) = day_closure(&job_config, date, &fixture_day(), now);
+        assert_eq!(progress.tracked, quota_time(&job_config, &closure));
+        assert!(progress.tracked > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_day_closure_counts_blockers() {
+        let job_config = fixture_job_config();
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let now = date.next_day().unwrap().midnight().assume_utc();
+        let mut day = fixture_day();
+        // 11-12 overlaps the activity of the same class, 15-16 does not
+        for (start, end) in [(11, 12), (15, 16)] {
+            day.blockers.push(Blocker {
+                id: Uuid::new_v4(),
+                name: None,
+                class: Identifier::ByName("work".into()),
+                time: testing::activity(
+                    "work",
+                    Time::from_hms(start, 0, 0).unwrap(),
+                    Some(Time::from_hms(end, 0, 0).unwrap()),
+                )
+                .time,
+                projects: vec![],
+            });
+        }
+
+        let (closure, _) = day_closure(&job_config, date, &day, now);
+        assert_eq!(
+            Activity::sum_durations(&closure).total(),
+            Duration::hours(5)
+        );
+        let filter = ReportFilter {
+            projects: vec![Identifier::ByName("projB".into())],
+            ..ReportFilter::default()
+        };
+        let (closure, _) = filtered_day_closure(&job_config, &filter, date, &day, now);
+        assert_eq!(
+            Activity::sum_durations(&closure).total(),
+            Duration::hours(2)
+        );
+        assert_eq!(
+            raw_segments(&job_config, &ReportFilter::default(), &day, None).len(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_week_progress_uses_day_quota_overrides() {
+        let job_config = fixture_job_config();
+        let may = |day| Date::from_calendar_date(2024, Month::May, day).unwrap();
+        let with_quota = |day, hours| Day {
+            date: may(day),
+            inner: DayInner {
+                work_quota: Some(Duration::hours(hours)),
+                ..DayInner::default()
+            },
+        };
+        // reduced hours on friday, some work on saturday
+        let config = AppConfig::default();
+        let days = Manager::in_memory(&config, [with_quota(10, 6), with_quota(11, 2)]);
+        let now = may(8).midnight().assume_utc();
+
+        let progress = WeekProgress::new(&job_config, &days, may(8), now, eight_hours());
+        assert_eq!(progress.quotas.of(may(8)), Duration::hours(8));
+        assert_eq!(progress.quotas.of(may(10)), Duration::hours(6));
+        assert_eq!(progress.quotas.of(may(11)), Duration::hours(2));
+        assert_eq!(progress.quotas.of(may(12)), Duration::ZERO);
+        assert_eq!(progress.quota_so_far(), Duration::hours(24));
+        assert_eq!(progress.week_quota(), Duration::hours(40));
+
+        let friday = days.day(may(10));
+        assert_eq!(
+            day_quota(&job_config, may(10), friday, eight_hours()),
+            Duration::hours(6)
+        );
+        assert_eq!(
+            day_quota(&job_config, may(9), None, eight_hours()),
+            Duration::hours(8)
+        );
+        assert_eq!(
+            day_quota(&job_config, may(12), None, eight_hours()),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_week_progress_uses_weekday_quotas() {
+        let job_config = fixture_job_config();
+        let may = |day| Date::from_calendar_date(2024, Month::May, day).unwrap();
+        let config: AppConfig = serde_json::from_str(
+            r#"{"work_quota_default": "7h",
+                "work_quota_per_weekday": {"friday": "4h", "Saturday": "1h 30m", "monday": "8h"}}"#,
+        )
+        .unwrap();
+        let quotas = config.daily_quotas();
+        // tracked on tuesday, a day off on wednesday overriding the default of the weekday
+        let days = Manager::in_memory(
+            &config,
+            [
+                Day {
+                    date: may(7),
+                    inner: fixture_day(),
+                },
+                Day {
+                    date: may(8),
+                    inner: DayInner {
+                        work_quota: Some(Duration::ZERO),
+                        ..DayInner::default()
+                    },
+                },
+            ],
+        );
+        let now = may(9).midnight().assume_utc();
+
+        let progress = WeekProgress::new(&job_config, &days, may(8), now, quotas);
+        assert_eq!(progress.tracked, Duration::hours(4));
+        assert_eq!(progress.quotas.of(may(6)), Duration::hours(8));
+        assert_eq!(progress.quotas.of(may(7)), Duration::hours(7));
+        assert_eq!(progress.quotas.of(may(8)), Duration::ZERO);
+        assert_eq!(progress.quotas.of(may(9)), Duration::hours(7));
+        assert_eq!(progress.quotas.of(may(10)), Duration::hours(4));
+        assert_eq!(progress.quotas.of(may(11)), Duration::minutes(90));
+        assert_eq!(progress.quotas.of(may(12)), Duration::ZERO);
+        // 8h + 7h + 0h + 7h + 4h + 1h 30m, less the 4h tracked
+        assert_eq!(progress.week_quota(), Duration::minutes(27 * 60 + 30));
+        assert_eq!(
+            progress.week_quota() - progress.tracked,
+            Duration::minutes(23 * 60 + 30)
+        );
+        assert_eq!(
+            day_quota(&job_config, may(11), days.day(may(11)), quotas),
+            Duration::minutes(90)
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::iter::Sum;
+use std::ops::AddAssign;
+use time::Duration;
+
+/// sum of durations that neither panics nor goes negative on bad data
+///
+/// negative inputs, e.g. of intervals ending before they start, are skipped and counted.
+/// the total saturates at [`Duration::MAX`] instead of overflowing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SafeDuration {
+    total: Duration,
+    negative: usize,
+    saturated: bool,
+}
+
+impl SafeDuration {
+    pub fn add(&mut self, duration: Duration) {
+        if duration.is_negative() {
+            self.negative += 1;
+            return;
+        }
+        match self.total.checked_add(duration) {
+            Some(total) => self.total = total,
+            None => {
+                self.total = Duration::MAX;
+                self.saturated = true;
+            }
+        }
+    }
+
+    /// combine two sums, e.g. of several days, keeping the counts of both
+    pub fn merge(&mut self, other: SafeDuration) {
+        self.add(other.total);
+        self.negative += other.negative;
+        self.saturated |= other.saturated;
+    }
+
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// how many negative inputs were skipped
+    pub fn negative(&self) -> usize {
+        self.negative
+    }
+
+    /// the total was capped at [`Duration::MAX`]
+    pub fn saturated(&self) -> bool {
+        self.saturated
+    }
+
+    /// true if the total is the plain sum of all inputs
+    pub fn is_exact(&self) -> bool {
+        self.negative == 0 && !self.saturated
+    }
+
+    /// user facing data-quality warning, none if the total is exact
+    pub fn warning(&self) -> Option<String> {
+        let mut problems = Vec::new();
+        if self.negative > 0 {
+            problems.push(format!(
+                "{} record(s) with a negative duration were ignored",
+                self.negative
+            ));
+        }
+        if self.saturated {
+            problems.push("a total exceeded the largest duration and was capped".to_string());
+        }
+        if problems.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Warning: {}, the totals are wrong. Check the records with `timetrax doctor`.",
+            problems.join(" and ")
+        ))
+    }
+}
+
+impl AddAssign<Duration> for SafeDuration {
+    fn add_assign(&mut self, duration: Duration) {
+        self.add(duration);
+    }
+}
+
+impl AddAssign for SafeDuration {
+    fn add_assign(&mut self, other: SafeDuration) {
+        self.merge(other);
+    }
+}
+
+impl Sum<Duration> for SafeDuration {
+    fn sum<I: Iterator<Item = Duration>>(iter: I) -> Self {
+        let mut sum = SafeDuration::default();
+        iter.for_each(|duration| sum.add(duration));
+        sum
+    }
+}
+
+impl Sum for SafeDuration {
+    fn sum<I: Iterator<Item = SafeDuration>>(iter: I) -> Self {
+        let mut sum = SafeDuration::default();
+        iter.for_each(|other| sum.merge(other));
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_duration_skips_negative_inputs() {
+        let sum = [
+            Duration::hours(2),
+            Duration::minutes(-30),
+            Duration::minutes(45),
+            Duration::seconds(-1),
+        ]
+        .into_iter()
+        .sum::<SafeDuration>();
+
+        assert_eq!(sum.total(), Duration::minutes(165));
+        assert_eq!(sum.negative(), 2);
+        assert!(!sum.saturated());
+        assert_eq!(
+            sum.warning().unwrap(),
+            "Warning: 2 record(s) with a negative duration were ignored, the totals are wrong. \
+             Check the records with `timetrax doctor`."
+        );
+    }
+
+    #[test]
+    fn test_safe_duration_saturates() {
+        let mut sum = [Duration::MAX, Duration::hours(1)]
+            .into_iter()
+            .sum::<SafeDuration>();
+        assert_eq!(sum.total(), Duration::MAX);
+        assert!(sum.saturated());
+
+        sum += Duration::hours(-1);
+        assert_eq!(sum.total(), Duration::MAX);
+        assert!(sum.warning().unwrap().contains("1 record(s)"));
+        assert!(sum.warning().unwrap().contains("capped"));
+    }
+
+    #[test]
+    fn test_safe_duration_merge() {
+        let days = [
+            [Duration::hours(8), Duration::hours(-1)]
+                .into_iter()
+                .sum::<SafeDuration>(),
+            [Duration::hours(7)].into_iter().sum(),
+            SafeDuration::default(),
+        ];
+        let week = days.into_iter().sum::<SafeDuration>();
+
+        assert_eq!(week.total(), Duration::hours(15));
+        assert_eq!(week.negative(), 1);
+        assert!(!week.is_exact());
+        assert!(SafeDuration::default().is_exact());
+        assert_eq!(SafeDuration::default().warning(), None);
+
+        let mut huge = [Duration::MAX].into_iter().sum::<SafeDuration>();
+        huge += week;
+        assert_eq!(huge.total(), Duration::MAX);
+        assert!(huge.saturated());
+        assert_eq!(huge.negative(), 1);
+    }
+}
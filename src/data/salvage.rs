@@ -0,0 +1,159 @@
+use crate::data::day::DayInner;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use time::Date;
+
+/// what could be recovered from a day file which does not parse as a whole
+#[derive(Debug, Default)]
+pub struct Salvaged {
+    /// date stored in the file, if it is readable
+    pub date: Option<Date>,
+    /// the well-formed activities and blockers
+    pub day: DayInner,
+    /// activities and blockers which were found but are malformed themselves
+    pub skipped: usize,
+}
+
+/// recover the date, activities and blockers of a malformed day file, e.g. with trailing commas
+/// or truncated. the elements of the lists are read one by one until the list ends or cannot be
+/// read any further, all other fields of the day are left at their defaults
+pub fn salvage_day(contents: &str) -> Salvaged {
+    let contents = &strip_trailing_commas(contents);
+    let mut salvaged = Salvaged {
+        date: value_of(contents, "date").and_then(|date| serde_json::from_value(date).ok()),
+        ..Salvaged::default()
+    };
+    let (activities, skipped) = list_of(contents, "activities");
+    salvaged.day.activities = activities;
+    salvaged.skipped += skipped;
+    let (blockers, skipped) = list_of(contents, "blockers");
+    salvaged.day.blockers = blockers;
+    salvaged.skipped += skipped;
+    salvaged
+}
+
+/// the text without commas directly before a closing bracket or brace, outside of strings
+fn strip_trailing_commas(contents: &str) -> String {
+    let mut result = String::with_capacity(contents.len());
+    let (mut in_string, mut escaped) = (false, false);
+    for (index, c) in contents.char_indices() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' && contents[index + 1..].trim_start().starts_with([']', '}']) {
+            continue;
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// text following the first `"key":`
+fn after_key<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+    let start = contents.find(&format!("\"{}\"", key))? + key.len() + 2;
+    contents[start..].trim_start().strip_prefix(':')
+}
+
+/// the first JSON value at the start of `text`, with the number of bytes it takes
+fn next_value(text: &str) -> Option<(Value, usize)> {
+    let mut values = serde_json::Deserializer::from_str(text).into_iter::<Value>();
+    let value = values.next()?.ok()?;
+    Some((value, values.byte_offset()))
+}
+
+/// value of the first `"key"`
+fn value_of(contents: &str, key: &str) -> Option<Value> {
+    next_value(after_key(contents, key)?).map(|(value, _)| value)
+}
+
+/// the readable elements of the list under `key`, with the number of elements which are no `T`
+fn list_of<T: DeserializeOwned>(contents: &str, key: &str) -> (Vec<T>, usize) {
+    let mut elements = Vec::new();
+    let mut skipped = 0;
+    let Some(mut rest) =
+        after_key(contents, key).and_then(|rest| rest.trim_start().strip_prefix('['))
+    else {
+        return (elements, skipped);
+    };
+    loop {
+        rest = rest.trim_start().trim_start_matches(',').trim_start();
+        if rest.is_empty() || rest.starts_with(']') {
+            break;
+        }
+        let Some((value, length)) = next_value(rest) else {
+            // truncated or garbled, nothing after this can be trusted
+            break;
+        };
+        match serde_json::from_value(value) {
+            Ok(element) => elements.push(element),
+            Err(_) => skipped += 1,
+        }
+        rest = &rest[length..];
+    }
+    (elements, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    #[test]
+    fn test_salvage_trailing_commas() {
+        let salvaged = salvage_day(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/corrupt/2024-05-02.json"
+        )));
+        assert!(
+            serde_json::from_str::<Value>(include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/corrupt/2024-05-02.json"
+            )))
+            .is_err()
+        );
+        assert_eq!(
+            salvaged.date,
+            Some(Date::from_calendar_date(2024, Month::May, 2).unwrap())
+        );
+        let names = (salvaged.day.activities.iter())
+            .map(|activity| activity.name.as_deref().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["standup", "coding"]);
+        assert_eq!(salvaged.day.blockers.len(), 1);
+        assert_eq!(salvaged.skipped, 0);
+    }
+
+    #[test]
+    fn test_salvage_truncated_file() {
+        let salvaged = salvage_day(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/corrupt/2024-05-03.json"
+        )));
+        assert_eq!(
+            salvaged.date,
+            Some(Date::from_calendar_date(2024, Month::May, 3).unwrap())
+        );
+        // the second activity lacks its class, the third is cut off
+        assert_eq!(salvaged.day.activities.len(), 1);
+        assert_eq!(salvaged.skipped, 1);
+        assert!(salvaged.day.blockers.is_empty());
+    }
+
+    #[test]
+    fn test_strip_trailing_commas() {
+        assert_eq!(
+            strip_trailing_commas(r#"{"a": [1, 2, ], "b": "x, ]",}"#),
+            r#"{"a": [1, 2 ], "b": "x, ]"}"#
+        );
+        assert_eq!(strip_trailing_commas(r#"["\\", ]"#), r#"["\\" ]"#);
+    }
+
+    #[test]
+    fn test_salvage_garbage() {
+        let salvaged = salvage_day("not a day file");
+        assert_eq!(salvaged.date, None);
+        assert!(salvaged.day.activities.is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::day::DayInner;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::Manager;
+use std::collections::HashMap;
+use std::path::Path;
+use uuid::Uuid;
+
+/// name of the person a data directory belongs to, the name of the directory
+pub fn person_name(data_path: &Path) -> String {
+    data_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| data_path.display().to_string())
+}
+
+/// data directory of another person, e.g. an unpacked archive of a team member
+///
+/// the directory is opened read-only, nothing is ever written to it
+pub struct ForeignData<'a> {
+    /// name of the person, see [`person_name`]
+    pub name: String,
+    pub job_config: JobConfig,
+    manager: Manager<'a>,
+}
+
+impl<'a> ForeignData<'a> {
+    pub fn open(app_config: &'a AppConfig, path: &Path) -> crate::error::Result<Self> {
+        Ok(Self {
+            name: person_name(path),
+            job_config: Manager::open_job_config(app_config, path)?,
+            manager: Manager::open_read_only(app_config, path)?,
+        })
+    }
+
+    pub fn manager(&self) -> &Manager<'a> {
+        &self.manager
+    }
+}
+
+/// classes and projects of a foreign job config mapped onto a combined config,
+/// entries with the same name are the same logical class or project
+#[derive(Debug, Default)]
+pub struct NameMapping {
+    classes: HashMap<Uuid, Uuid>,
+    projects: HashMap<Uuid, Uuid>,
+}
+
+/// add the classes and projects of `foreign` missing by name to `combined`,
+/// returns the mapping and a warning for each class whose priority differs from the combined one
+pub fn align_job_config(
+    combined: &mut JobConfig,
+    foreign: &JobConfig,
+    person: &str,
+) -> (NameMapping, Vec<String>) {
+    let mut mapping = NameMapping::default();
+    let mut warnings = Vec::new();
+
+    for class in &foreign.classes {
+        match combined
+            .classes
+            .iter()
+            .find(|local| local.inner.name == class.inner.name)
+        {
+            Some(local) => {
+                if local.inner.priority != class.inner.priority {
+                    warnings.push(format!(
+                        "Warning: class {} has priority {} for {}, using priority {}.",
+                        class.inner.name, class.inner.priority, person, local.inner.priority
+                    ));
+                }
+                mapping.classes.insert(class.id, local.id);
+            }
+            None => {
+                let mut added = class.clone();
+                if combined.classes.iter().any(|local| local.id == added.id) {
+                    added.id = Uuid::new_v4();
+                }
+                mapping.classes.insert(class.id, added.id);
+                combined.classes.push(added);
+            }
+        }
+    }
+
+    for project in &foreign.projects {
+        match combined
+            .projects
+            .iter()
+            .find(|local| local.inner.name == project.inner.name)
+        {
+            Some(local) => {
+                mapping.projects.insert(project.id, local.id);
+            }
+            None => {
+                let mut added = project.clone();
+                if combined.projects.iter().any(|local| local.id == added.id) {
+                    added.id = Uuid::new_v4();
+                }
+                mapping.projects.insert(project.id, added.id);
+                combined.projects.push(added);
+            }
+        }
+    }
+
+    (mapping, warnings)
+}
+
+impl NameMapping {
+    fn class(&self, foreign: &JobConfig, class: &Identifier) -> Identifier {
+        foreign
+            .resolve_class(class)
+            .and_then(|class| self.classes.get(&class.id))
+            .map(|id| Identifier::Uuid(*id))
+            .unwrap_or_else(|| class.clone())
+    }
+
+    fn project(&self, foreign: &JobConfig, project: &Identifier) -> Identifier {
+        foreign
+            .resolve_project(project)
+            .and_then(|project| self.projects.get(&project.id))
+            .map(|id| Identifier::Uuid(*id))
+            .unwrap_or_else(|| project.clone())
+    }
+
+    /// the day with classes and projects referring to the combined config,
+    /// unknown references are kept as they are
+    pub fn remap_day(&self, foreign: &JobConfig, day: &DayInner) -> DayInner {
+        let mut day = day.clone();
+        for activity in &mut day.activities {
+            self.remap_activity(foreign, activity);
+        }
+        for blocker in &mut day.blockers {
+            blocker.class = self.class(foreign, &blocker.class);
+            blocker.projects = blocker
+                .projects
+                .iter()
+                .map(|project| self.project(foreign, project))
+                .collect();
+        }
+        day
+    }
+
+    fn remap_activity(&self, foreign: &JobConfig, activity: &mut Activity) {
+        activity.class = self.class(foreign, &activity.class);
+        activity.projects = activity
+            .projects
+            .iter()
+            .map(|project| self.project(foreign, project))
+            .collect();
+        activity.weights = activity
+            .weights
+            .iter()
+            .map(|(project, weight)| (self.project(foreign, project), *weight))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity_class::{ActivityClass, ActivityClassInner};
+
+    fn class(id: u128, name: &str, priority: i32) -> ActivityClass {
+        ActivityClass {
+            id: Uuid::from_u128(id),
+            inner: ActivityClassInner {
+                name: name.into(),
+                priority,
+                description: None,
+                target_share: None,
+                color: None,
+                exclusive: false,
+            },
+        }
+    }
+
+    #[test]
+    fn test_align_job_config_by_name() {
+        let mut combined = JobConfig {
+            classes: vec![class(1, "work", 0), class(2, "break", 5)],
+            ..JobConfig::default()
+        };
+        let foreign = JobConfig {
+            // same UUID as the local work class, but a different logical class
+            classes: vec![
+                class(11, "work", 0),
+                class(12, "break", 7),
+                class(1, "research", 1),
+            ],
+            ..JobConfig::default()
+        };
+
+        let (mapping, warnings) = align_job_config(&mut combined, &foreign, "alice");
+
+        assert_eq!(
+            warnings,
+            ["Warning: class break has priority 7 for alice, using priority 5."]
+        );
+        assert_eq!(combined.classes.len(), 3);
+        let research = combined.classes[2].id;
+        assert_ne!(research, Uuid::from_u128(1));
+        assert_eq!(combined.classes[2].inner.name, "research");
+        assert_eq!(
+            mapping.class(&foreign, &Identifier::ByName("work".into())),
+            Identifier::Uuid(Uuid::from_u128(1))
+        );
+        assert_eq!(
+            mapping.class(&foreign, &Identifier::Uuid(Uuid::from_u128(1))),
+            Identifier::Uuid(research)
+        );
+        assert_eq!(
+            mapping.class(&foreign, &Identifier::ByName("retired".into())),
+            Identifier::ByName("retired".into())
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use crate::data::day::DayInner;
+use std::fmt::{Display, Formatter};
+
+/// reasons a name given by the user is rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    /// nothing but whitespace
+    Empty,
+    /// longer than the configured maximum, lengths are counted in characters
+    TooLong { length: usize, max: usize },
+}
+
+impl Display for NameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "Name must not be empty"),
+            NameError::TooLong { length, max } => write!(
+                f,
+                "Name is {} characters long, at most {} are allowed",
+                length, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+impl From<NameError> for std::io::Error {
+    fn from(e: NameError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// trim the name and collapse internal runs of whitespace to a single space,
+/// `None` if nothing is left
+pub fn normalize_name(name: &str, max_length: usize) -> Result<Option<String>, NameError> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let length = name.chars().count();
+    if name.is_empty() {
+        Ok(None)
+    } else if length > max_length {
+        Err(NameError::TooLong {
+            length,
+            max: max_length,
+        })
+    } else {
+        Ok(Some(name))
+    }
+}
+
+/// like [`normalize_name`] for names that must not be empty, e.g. of projects and classes
+pub fn require_name(name: &str, max_length: usize) -> Result<String, NameError> {
+    normalize_name(name, max_length)?.ok_or(NameError::Empty)
+}
+
+/// normalize the names of the activities and blockers of the day, names too long are kept.
+/// returns how many names changed
+pub fn normalize_day_names(day: &mut DayInner, max_length: usize) -> usize {
+    let activities = day.activities.iter_mut().map(|a| &mut a.name);
+    let blockers = day.blockers.iter_mut().map(|b| &mut b.name);
+    let mut changed = 0;
+    for name in activities.chain(blockers) {
+        let Some(current) = name.as_deref() else {
+            continue;
+        };
+        if let Ok(normalized) = normalize_name(current, max_length)
+            && normalized.as_deref() != Some(current)
+        {
+            *name = normalized;
+            changed += 1;
+        }
+    }
+    changed
+}
+
+/// edit distance of the strings ignoring case, swapping two adjacent characters counts as one edit
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.to_lowercase().chars().collect::<Vec<_>>();
+    let b = b.to_lowercase().chars().collect::<Vec<_>>();
+    // distances[i][j] between the first i characters of a and the first j of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// up to three of the `candidates` with names close to `query`, closest first,
+/// ignoring case and a leading `@`
+pub fn closest_names<'a, T>(query: &str, candidates: impl Iterator<Item = (T, &'a str)>) -> Vec<T> {
+    let query = query.strip_prefix('@').unwrap_or(query);
+    // allow about one edit per two characters, a single one for very short names
+    let max_distance = (query.chars().count() / 2).max(1);
+    let mut close = candidates
+        .map(|(candidate, name)| (edit_distance(query, name), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    close.sort_by_key(|(distance, _)| *distance);
+    close
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// e.g. ` Did you mean: work, workshop?`, nothing without suggestions
+pub fn did_you_mean(suggestions: &[&str]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" Did you mean: {}?", suggestions.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(
+            normalize_name("  refactor \t parser\n", 200),
+            Ok(Some("refactor parser".to_string()))
+        );
+        assert_eq!(
+            normalize_name("coding", 200),
+            Ok(Some("coding".to_string()))
+        );
+        assert_eq!(normalize_name("   ", 200), Ok(None));
+        assert_eq!(normalize_name("", 200), Ok(None));
+    }
+
+    #[test]
+    fn test_normalize_name_length() {
+        // the length is counted after normalizing, in characters
+        assert_eq!(normalize_name("  äöü  ", 3), Ok(Some("äöü".to_string())));
+        assert_eq!(normalize_name("a  b", 3), Ok(Some("a b".to_string())));
+        assert_eq!(
+            normalize_name("abcd", 3),
+            Err(NameError::TooLong { length: 4, max: 3 })
+        );
+    }
+
+    #[test]
+    fn test_require_name() {
+        assert_eq!(require_name(" projA ", 200), Ok("projA".to_string()));
+        assert_eq!(require_name(" \t ", 200), Err(NameError::Empty));
+        assert_eq!(
+            require_name("abcd", 3).unwrap_err().to_string(),
+            "Name is 4 characters long, at most 3 are allowed"
+        );
+    }
+
+    #[test]
+    fn test_normalize_day_names() {
+        let mut day: DayInner = serde_json::from_str(
+            r#"{"activities": [
+                {"id": "00000000-0000-4000-8000-000000000001", "class": "@work",
+                 "name": "  messy   name ", "time": {"start": "09:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000002", "class": "@work",
+                 "name": "   ", "time": {"start": "10:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000003", "class": "@work",
+                 "name": "fine", "time": {"start": "11:00:00"}},
+                {"id": "00000000-0000-4000-8000-000000000004", "class": "@work",
+                 "name": "far too  long", "time": {"start": "12:00:00"}}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(normalize_day_names(&mut day, 10), 2);
+        let names = day
+            .activities
+            .iter()
+            .map(|a| a.name.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                Some("messy name"),
+                None,
+                Some("fine"),
+                Some("far too  long")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("work", "work"), 0);
+        assert_eq!(edit_distance("Work", "wORK"), 0);
+        assert_eq!(edit_distance("wrok", "work"), 1);
+        assert_eq!(edit_distance("wor", "work"), 1);
+        assert_eq!(edit_distance("", "break"), 5);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_closest_names() {
+        let names = ["work", "break", "workshop", "meeting"];
+        let closest = |query| closest_names(query, names.iter().map(|name| (*name, *name)));
+        assert_eq!(closest("wrok"), ["work"]);
+        assert_eq!(closest("@WORK"), ["work"]);
+        assert_eq!(closest("workshp"), ["workshop", "work"]);
+        assert_eq!(closest("braek"), ["break"]);
+        assert!(closest("lunch").is_empty());
+        assert!(closest("xyz").is_empty());
+        assert_eq!(did_you_mean(&closest("wrok")), " Did you mean: work?");
+        assert_eq!(did_you_mean(&[]), "");
+    }
+}
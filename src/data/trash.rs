@@ -0,0 +1,44 @@
+use crate::az_hash::AZHash;
+use crate::data::activity::Activity;
+use crate::data::reference::Referable;
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// activity deleted from a day, stored like the activity itself plus the time of deletion
+///
+/// trash entries are kept until the retention expires, reports and closures ignore them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashEntry {
+    /// time the activity was deleted
+    #[serde(with = "time::serde::rfc3339")]
+    pub deleted_at: OffsetDateTime,
+    /// the deleted activity
+    #[serde(flatten)]
+    pub activity: Activity,
+}
+
+impl TrashEntry {
+    /// the entry is older than the retention and can be purged
+    pub fn expired(&self, cutoff: OffsetDateTime) -> bool {
+        self.deleted_at < cutoff
+    }
+}
+
+impl AZHash for TrashEntry {
+    fn az_hash<D: Digest>(&self) -> String {
+        self.activity.az_hash::<D>()
+    }
+}
+
+/// trash entries are referenced like the deleted activity
+impl Referable for TrashEntry {
+    fn id(&self) -> Uuid {
+        self.activity.id
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.activity.name.as_deref()
+    }
+}
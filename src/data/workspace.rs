@@ -0,0 +1,143 @@
+use crate::data::app_config::AppConfig;
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::manager::write_atomic;
+use crate::data::project::Project;
+use log::{error, trace, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// choices remembered between invocations on the same data directory
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceState {
+    /// project `push` assigns if no project is given, set by `project use`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub current_project: Option<Identifier>,
+}
+
+impl WorkspaceState {
+    pub fn path<P: AsRef<Path>>(app_config: &AppConfig, data_path: P) -> PathBuf {
+        data_path
+            .as_ref()
+            .join(&app_config.workspace_state_file_name)
+    }
+
+    /// state stored at `path`, empty if there is none
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                error!("Failed to read workspace state {}: {}", path.display(), e);
+                return Err(e);
+            }
+        };
+        serde_json::from_str(&text).map_err(|e| {
+            error!("Failed to parse workspace state {}: {}", path.display(), e);
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> crate::error::Result<()> {
+        trace!("Saving workspace state to {}", path.display());
+        write_atomic(path, |file| Ok(serde_json::to_writer_pretty(file, self)?))
+    }
+
+    /// the current project of the workspace at `data_path`, without writing the state
+    ///
+    /// a current project that was removed from the job config is ignored with a warning
+    pub fn current_project<'a, P: AsRef<Path>>(
+        app_config: &AppConfig,
+        data_path: P,
+        job_config: &'a JobConfig,
+    ) -> std::io::Result<Option<&'a Project>> {
+        let state = Self::load(&Self::path(app_config, data_path))?;
+        Ok(state.resolve_current_project(job_config))
+    }
+
+    /// the current project of the workspace at `data_path`
+    ///
+    /// a current project that was removed from the job config is cleared with a warning
+    pub fn current_project_or_clear<'a, P: AsRef<Path>>(
+        app_config: &AppConfig,
+        data_path: P,
+        job_config: &'a JobConfig,
+    ) -> std::io::Result<Option<&'a Project>> {
+        let path = Self::path(app_config, data_path);
+        let mut state = Self::load(&path)?;
+        let project = state.resolve_current_project(job_config);
+        if project.is_none()
+            && state.current_project.take().is_some()
+            && let Err(e) = state.save(&path)
+        {
+            error!("Failed to clear the current project: {}", e);
+        }
+        Ok(project)
+    }
+
+    /// the current project in `job_config`, `None` with a warning if it was removed
+    fn resolve_current_project<'a>(&self, job_config: &'a JobConfig) -> Option<&'a Project> {
+        let current = self.current_project.as_ref()?;
+        let project = job_config.resolve_project(current);
+        if project.is_none() {
+            warn!(
+                "The current project {} no longer exists, select another one with `timetrax project use`",
+                current
+            );
+        }
+        project
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDataDir;
+
+    #[test]
+    fn test_removed_current_project_is_cleared() {
+        let mut job_config = JobConfig::default();
+        job_config.projects.push(Project {
+            id: uuid::Uuid::new_v4(),
+            inner: crate::data::project::ProjectInner {
+                name: "projA".to_string(),
+                description: None,
+                client: None,
+                target_share: None,
+            },
+        });
+        let id = job_config.projects[0].id;
+        let dir = TempDataDir::new(&job_config).unwrap();
+        let path = WorkspaceState::path(dir.app_config(), dir.path());
+
+        let current = |job_config: &JobConfig| {
+            WorkspaceState::current_project_or_clear(dir.app_config(), dir.path(), job_config)
+                .unwrap()
+                .map(|project| project.id)
+        };
+        assert_eq!(current(&job_config), None);
+
+        WorkspaceState {
+            current_project: Some(Identifier::Uuid(id)),
+        }
+        .save(&path)
+        .unwrap();
+        assert_eq!(current(&job_config), Some(id));
+
+        job_config.projects.clear();
+        // reading the current project leaves the state alone
+        assert_eq!(
+            WorkspaceState::current_project(dir.app_config(), dir.path(), &job_config).unwrap(),
+            None
+        );
+        assert_eq!(
+            WorkspaceState::load(&path).unwrap().current_project,
+            Some(Identifier::Uuid(id))
+        );
+        assert_eq!(current(&job_config), None);
+        assert_eq!(
+            WorkspaceState::load(&path).unwrap(),
+            WorkspaceState::default()
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! error type of the library and the commands
+
+use crate::data::day::ActivityLookupError;
+use crate::data::day_limit::DayLimitError;
+use crate::data::identifier::Identifier;
+use crate::data::mapping::MappingError;
+use crate::data::text::NameError;
+use itertools::Itertools;
+use std::path::PathBuf;
+use time::Time;
+use uuid::Uuid;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// reading or writing files, and failures without a variant of their own
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// malformed job config or day files
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+    /// no class matches the identifier
+    #[error("Activity class {0} not found")]
+    UnknownClass(Identifier),
+    /// no project matches the identifier
+    #[error("Project {0} not found")]
+    UnknownProject(Identifier),
+    /// a name or reference matches several activities or blockers
+    #[error("Reference is ambiguous, use one of the ids: {}", .0.iter().join(", "))]
+    AmbiguousReference(Vec<Uuid>),
+    /// an interval would end before it starts
+    #[error("Interval from {start} to {end} ends before it starts")]
+    InvalidInterval { start: Time, end: Time },
+    /// formatting a date or time failed
+    #[error(transparent)]
+    TimeFormat(#[from] time::error::Format),
+    /// another instance holds the lock of the data directory, with its PID if known
+    #[error(
+        "Data directory is locked by another timetrax instance{}",
+        .0.map(|pid| format!(" (PID {})", pid)).unwrap_or_default()
+    )]
+    Locked(Option<u32>),
+    /// invalid value of an environment variable overriding a setting
+    #[error("Invalid value of the environment variable {variable}: {message}")]
+    Environment { variable: String, message: String },
+    /// `pop` found no open activity to stop
+    #[error("No open activity to stop")]
+    NothingToPop,
+    /// malformed app config, with the file it was read from
+    #[error("Invalid app config {}: {source}", .path.display())]
+    Config {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl Error {
+    /// closest io error kind, for callers only distinguishing io failures
+    pub fn kind(&self) -> std::io::ErrorKind {
+        match self {
+            Error::Io(e) => e.kind(),
+            Error::Serde(_) | Error::Config { .. } => std::io::ErrorKind::InvalidData,
+            Error::Environment { .. } => std::io::ErrorKind::InvalidInput,
+            Error::UnknownClass(_) | Error::UnknownProject(_) | Error::NothingToPop => {
+                std::io::ErrorKind::NotFound
+            }
+            Error::AmbiguousReference(_) | Error::InvalidInterval { .. } => {
+                std::io::ErrorKind::InvalidInput
+            }
+            Error::TimeFormat(_) => std::io::ErrorKind::Other,
+            Error::Locked(_) => std::io::ErrorKind::WouldBlock,
+        }
+    }
+
+    /// exit code of the process, following the `sysexits.h` conventions
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Io(_) => 1,
+            // EX_DATAERR
+            Error::Serde(_) => 65,
+            // EX_USAGE
+            Error::UnknownClass(_)
+            | Error::UnknownProject(_)
+            | Error::AmbiguousReference(_)
+            | Error::InvalidInterval { .. } => 64,
+            // EX_SOFTWARE
+            Error::TimeFormat(_) => 70,
+            // EX_NOINPUT, scripts tell an idle tracker from failures
+            Error::NothingToPop => 66,
+            // EX_TEMPFAIL
+            Error::Locked(_) => 75,
+            // EX_CONFIG
+            Error::Config { .. } | Error::Environment { .. } => 78,
+        }
+    }
+}
+
+impl From<ActivityLookupError> for Error {
+    fn from(e: ActivityLookupError) -> Self {
+        match e {
+            ActivityLookupError::Ambiguous(_, ids)
+            | ActivityLookupError::AmbiguousReference(_, ids) => Error::AmbiguousReference(ids),
+            e => Error::Io(e.into()),
+        }
+    }
+}
+
+impl From<DayLimitError> for Error {
+    fn from(e: DayLimitError) -> Self {
+        Error::Io(e.into())
+    }
+}
+
+impl From<NameError> for Error {
+    fn from(e: NameError) -> Self {
+        Error::Io(e.into())
+    }
+}
+
+impl From<MappingError> for Error {
+    fn from(e: MappingError) -> Self {
+        Error::Io(e.into())
+    }
+}
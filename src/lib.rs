@@ -1,4 +1,9 @@
 pub mod az_hash;
 pub mod cli;
 pub mod data;
+pub mod error;
+pub mod profile;
+pub mod report;
 pub mod serde;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
@@ -1,13 +1,20 @@
 use clap::Parser;
 use log::{debug, error, info, trace};
-use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::time::Duration;
+use timetrax::cli::output::{CommandOutput, OutputFormat, RenderOutput};
 use timetrax::cli::{AppArgs, Command, ExecutableCommand};
-use timetrax::data::app_config::AppConfig;
+use timetrax::data::app_config::{AppConfig, AppConfigDisk, default_config_path};
+use timetrax::data::backup::backup_file;
+use timetrax::data::day_limit::DayLimit;
 use timetrax::data::dirty::DirtyMarker;
 use timetrax::data::job_config::JobConfig;
-use timetrax::data::manager::Manager;
+use timetrax::data::local_time;
+use timetrax::data::local_time::FixedClock;
+use timetrax::data::manager::{Manager, backup_folder, day_folder};
+use timetrax::profile;
 
 fn main() {
     env_logger::init();
@@ -18,34 +25,98 @@ fn main() {
     );
 
     let args = AppArgs::parse();
-    let config = AppConfig::default();
-
-    if let Some(command) = &args.command {
-        if let Command::Completion(_) = command {
-            trace!("Completion command detected, skipping data path setup.");
-            if let Err(err) = command.execute(
-                &config,
-                &mut JobConfig::default(),
-                Manager {
-                    app_config: &config,
-                    days: BTreeMap::new(),
-                    data_path: PathBuf::new(),
-                },
-            ) {
-                error!("Command execution failed: {}", err);
-                std::process::exit(1);
+    let format = args.format;
+    if let Some(command) = &args.command
+        && let Err(err) = command.check_format(format)
+    {
+        std::process::exit(err.exit_code());
+    }
+    if args.profile || profile::requested_by_env() {
+        profile::enable();
+    }
+
+    let config = {
+        let _timer = profile::timer("resolve config");
+        let disk = match &args.config {
+            Some(path) => AppConfigDisk::load(path).and_then(|disk| {
+                disk.map(Some).ok_or_else(|| {
+                    error!("The app config {} does not exist", path.display());
+                    std::io::Error::from(std::io::ErrorKind::NotFound).into()
+                })
+            }),
+            None => default_config_path().map_or(Ok(None), |path| AppConfigDisk::load(&path)),
+        };
+        let loaded =
+            disk.and_then(|disk| AppConfig::resolve(&args, disk, &|name| std::env::var(name).ok()));
+        let loaded = match loaded {
+            Ok(config) => config,
+            Err(err) => {
+                error!("Failed to load the app config: {}", err);
+                std::process::exit(err.exit_code());
             }
-            return;
+        };
+        AppConfig {
+            color: !args.no_color
+                && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                && std::io::stdout().is_terminal(),
+            ..loaded
         }
-    }
+    };
 
-    let data_path = args.data_path.unwrap_or_else(|| {
-        trace!("No data path provided, using default.");
-        config.default_data_path.clone()
+    let _clock = args.now.map(|now| {
+        debug!("Overriding current time with {}", now);
+        local_time::set_clock(Arc::new(FixedClock(now)))
     });
 
+    if let Some(command) = &args.command
+        && match command {
+            Command::Completion(_) => true,
+            Command::Config(config) => !config.uses_data_directory(),
+            _ => false,
+        }
+    {
+        trace!("Command without data directory detected, skipping data path setup.");
+        if let Err(err) =
+            command.execute(&config, &mut JobConfig::default(), Manager::empty(&config))
+        {
+            error!("Command execution failed: {}", err);
+            std::process::exit(err.exit_code());
+        }
+        return;
+    }
+
+    // --data-path and TIMETRAX_DATA_PATH are part of the resolved config
+    let data_path = config.default_data_path.clone();
+
     debug!("Using data path: {:?}", data_path);
 
+    if let Some(command @ (Command::Fold(_) | Command::Nightly(_) | Command::DiffData(_))) =
+        &args.command
+    {
+        trace!("Read-only command detected, leaving the data path untouched.");
+        let job_config_path = data_path.join(&config.job_config_file_name);
+        let job_config = if job_config_path.exists() {
+            Manager::open_job_config(&config, &data_path)
+        } else {
+            Ok(JobConfig::default())
+        };
+        let manager = match command {
+            // fold only looks at its input
+            Command::Fold(_) => Ok(Manager::empty(&config)),
+            _ => Manager::open_read_only(&config, &data_path),
+        };
+        let result = job_config
+            .and_then(|mut job_config| command.execute(&config, &mut job_config, manager?));
+        match result {
+            Ok(output) => write_output(&output, format),
+            Err(err) => {
+                error!("Command execution failed: {}", err);
+                std::process::exit(err.exit_code());
+            }
+        }
+        return;
+    }
+
     let data_dir_exists = match fs::exists(&data_path) {
         Ok(exists) => exists,
         Err(err) => {
@@ -68,6 +139,29 @@ fn main() {
         }
     }
 
+    let command = args.command.unwrap_or_else(|| {
+        trace!("No command provided, defaulting.");
+        Command::default()
+    });
+
+    // read-only commands skip the lock, unless the day folder has yet to be created
+    let read_only = command.is_read_only()
+        && day_folder(&config, &data_path).is_ok_and(|folder| folder.exists());
+    let opened = if read_only {
+        Manager::open_read_only(&config, &data_path)
+    } else {
+        Manager::open_waiting(&config, &data_path, Duration::from_secs(args.wait))
+    };
+    let mut manager = match opened {
+        Ok(mgr) => mgr,
+        Err(err) => {
+            error!("Failed to load data directory: {}", err);
+            std::process::exit(err.exit_code());
+        }
+    };
+    // the job config is saved after the manager is gone, keep the data directory locked
+    let _lock = manager.lock();
+
     let job_config_path = data_path.join(&config.job_config_file_name);
     if !job_config_path.exists() {
         info!(
@@ -75,22 +169,9 @@ fn main() {
             job_config_path
         );
 
-        trace!("Opening job config file {:?}", job_config_path);
-        let job_config_file = match fs::File::create(&job_config_path) {
-            Ok(file) => file,
-            Err(err) => {
-                error!("Failed to job config at {:?}: {}", job_config_path, err);
-                std::process::exit(1);
-            }
-        };
-
-        trace!("Writing job config to {:?}", job_config_path);
-        if let Err(err) = serde_json::to_writer_pretty(job_config_file, &JobConfig::default()) {
-            error!(
-                "Failed to write default job config to {:?}: {}",
-                job_config_path, err
-            );
-            std::process::exit(1);
+        if let Err(err) = Manager::save_job_config(&config, &data_path, &JobConfig::default()) {
+            error!("Failed to write default job config: {}", err);
+            std::process::exit(err.exit_code());
         }
     }
 
@@ -98,56 +179,64 @@ fn main() {
         Ok(job) => job,
         Err(err) => {
             error!("Failed to load job config: {}", err);
-            std::process::exit(1);
+            std::process::exit(err.exit_code());
         }
     };
 
-    let mut job_config = DirtyMarker::from(job_config);
+    if config.check_on_load {
+        manager.warn_problems(&job_config);
+    }
 
-    let manager = match Manager::open(&config, &data_path) {
-        Ok(mgr) => mgr,
-        Err(err) => {
-            error!("Failed to load data directory: {}", err);
-            std::process::exit(1);
-        }
-    };
+    let mut job_config = DirtyMarker::from(job_config);
 
-    let command = args.command.unwrap_or_else(|| {
-        trace!("No command provided, defaulting.");
-        Command::default()
+    manager.set_day_limit(DayLimit {
+        force: args.force_save,
+        ..DayLimit::new(&config, &job_config)
     });
 
-    if let Err(err) = command.execute(&config, &mut job_config, manager) {
-        error!("Command execution failed: {}", err);
-        std::process::exit(1);
+    match command.execute(&config, &mut job_config, manager) {
+        Ok(output) => write_output(&output, format),
+        Err(err) => {
+            error!("Command execution failed: {}", err);
+            print_profile();
+            std::process::exit(err.exit_code());
+        }
     }
 
     if job_config.is_dirty() {
         trace!("Job config marked as dirty, saving changes.");
 
-        let job_config_path = data_path.join(&config.job_config_file_name);
-        let job_config_file = match fs::File::create(&job_config_path) {
-            Ok(file) => file,
-            Err(err) => {
-                error!(
-                    "Failed to open job config file at {:?} for writing: {}",
-                    job_config_path, err
-                );
-                std::process::exit(1);
-            }
-        };
-
-        if let Err(err) = serde_json::to_writer_pretty(job_config_file, &*job_config) {
-            error!(
-                "Failed to write updated job config to {:?}: {}",
-                job_config_path, err
-            );
+        // commands get the job config mutably, only back it up if they changed it
+        if config.backup_on_save
+            && Manager::open_job_config(&config, &data_path)
+                .is_ok_and(|on_disk| on_disk != *job_config)
+            && let Err(err) = backup_folder(&config, &data_path)
+                .and_then(|folder| backup_file(&job_config_path, &folder, config.backup_keep))
+        {
+            error!("Failed to back up the job config, not saving it: {}", err);
             std::process::exit(1);
         }
 
-        trace!(
-            "Successfully saved updated job config to {:?}",
-            job_config_path
-        );
+        if let Err(err) = Manager::save_job_config(&config, &data_path, &job_config) {
+            error!("Failed to save updated job config: {}", err);
+            std::process::exit(err.exit_code());
+        }
+    }
+
+    print_profile();
+}
+
+/// write the output of the command to stdout
+fn write_output(output: &CommandOutput, format: OutputFormat) {
+    let _timer = profile::timer("write output");
+    if let Err(err) = output.render(&mut std::io::stdout().lock(), format) {
+        error!("Failed to write the output: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn print_profile() {
+    if let Some(summary) = profile::summary() {
+        eprint!("{}", summary);
     }
 }
@@ -0,0 +1,184 @@
+//! lightweight timing and counters for `--profile`
+//!
+//! nothing is recorded unless profiling was enabled on the current thread
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// environment variable enabling profiling, e.g. `TIMETRAX_PROFILE=1`
+pub const PROFILE_ENV: &str = "TIMETRAX_PROFILE";
+
+#[derive(Debug, Default)]
+struct Profile {
+    /// total time per phase, in order of first occurrence
+    timings: Vec<(&'static str, Duration)>,
+    counters: BTreeMap<&'static str, u64>,
+    open_files: u64,
+    peak_open_files: u64,
+}
+
+thread_local! {
+    static PROFILE: RefCell<Option<Profile>> = const { RefCell::new(None) };
+}
+
+fn with_profile<F: FnOnce(&mut Profile)>(f: F) {
+    PROFILE.with(|profile| {
+        if let Some(profile) = profile.borrow_mut().as_mut() {
+            f(profile);
+        }
+    });
+}
+
+/// start recording, discarding previous measurements
+pub fn enable() {
+    PROFILE.with(|profile| *profile.borrow_mut() = Some(Profile::default()));
+}
+
+/// stop recording
+pub fn disable() {
+    PROFILE.with(|profile| *profile.borrow_mut() = None);
+}
+
+pub fn is_enabled() -> bool {
+    PROFILE.with(|profile| profile.borrow().is_some())
+}
+
+/// profiling was requested using the environment
+pub fn requested_by_env() -> bool {
+    std::env::var(PROFILE_ENV).is_ok_and(|value| value == "1")
+}
+
+/// measures the time until it is dropped, repeated phases add up
+#[must_use]
+pub struct ScopedTimer {
+    phase: &'static str,
+    start: Instant,
+}
+
+impl Drop for ScopedTimer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        with_profile(
+            |profile| match profile.timings.iter_mut().find(|(p, _)| *p == self.phase) {
+                Some((_, total)) => *total += elapsed,
+                None => profile.timings.push((self.phase, elapsed)),
+            },
+        );
+    }
+}
+
+/// time the current scope as the given phase
+pub fn timer(phase: &'static str) -> ScopedTimer {
+    ScopedTimer {
+        phase,
+        start: Instant::now(),
+    }
+}
+
+/// add to a counter, e.g. the number of parsed day files
+pub fn count(counter: &'static str, amount: u64) {
+    with_profile(|profile| *profile.counters.entry(counter).or_default() += amount);
+}
+
+/// current value of a counter, zero if profiling is disabled
+pub fn counter(counter: &'static str) -> u64 {
+    PROFILE.with(|profile| {
+        profile
+            .borrow()
+            .as_ref()
+            .and_then(|profile| profile.counters.get(counter).copied())
+            .unwrap_or_default()
+    })
+}
+
+/// most files open at the same time, zero if profiling is disabled
+pub fn peak_open_files() -> u64 {
+    PROFILE.with(|profile| {
+        profile
+            .borrow()
+            .as_ref()
+            .map(|profile| profile.peak_open_files)
+            .unwrap_or_default()
+    })
+}
+
+/// tracks an open file handle until it is dropped
+#[must_use]
+pub struct FileHandleGuard(());
+
+impl Drop for FileHandleGuard {
+    fn drop(&mut self) {
+        with_profile(|profile| profile.open_files = profile.open_files.saturating_sub(1));
+    }
+}
+
+/// record that a file was opened, keep the guard as long as the file
+pub fn file_opened() -> FileHandleGuard {
+    count("files opened", 1);
+    with_profile(|profile| {
+        profile.open_files += 1;
+        profile.peak_open_files = profile.peak_open_files.max(profile.open_files);
+    });
+    FileHandleGuard(())
+}
+
+/// breakdown of the measurements, none if profiling is disabled
+pub fn summary() -> Option<String> {
+    PROFILE.with(|profile| {
+        let profile = profile.borrow();
+        let profile = profile.as_ref()?;
+
+        let mut out = String::new();
+        let _ = writeln!(out, "Profile:");
+        for (phase, duration) in &profile.timings {
+            let _ = writeln!(
+                out,
+                " - {}: {:.3} ms",
+                phase,
+                duration.as_secs_f64() * 1000.0
+            );
+        }
+        for (counter, value) in &profile.counters {
+            let _ = writeln!(out, " - {}: {}", counter, value);
+        }
+        let _ = writeln!(out, " - peak open files: {}", profile.peak_open_files);
+        Some(out)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_records_nothing() {
+        disable();
+        count("day files parsed", 1);
+        drop(timer("render"));
+        assert_eq!(counter("day files parsed"), 0);
+        assert!(summary().is_none());
+    }
+
+    #[test]
+    fn test_summary() {
+        enable();
+        count("day files parsed", 2);
+        count("day files parsed", 1);
+        drop(timer("render"));
+        drop(timer("render"));
+        {
+            let _first = file_opened();
+            let _second = file_opened();
+        }
+        let _third = file_opened();
+
+        assert_eq!(counter("day files parsed"), 3);
+        assert_eq!(counter("files opened"), 3);
+        let summary = summary().unwrap();
+        assert_eq!(summary.matches("render").count(), 1);
+        assert!(summary.contains(" - peak open files: 2\n"));
+        disable();
+    }
+}
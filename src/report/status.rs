@@ -0,0 +1,232 @@
+use crate::data::activity::Activity;
+use crate::data::activity_closure::ClosureDiagnostics;
+use crate::data::app_config::AppConfig;
+use crate::data::day::{DayInner, DayType};
+use crate::data::identifier::Identifier;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::report::{day_quota, quota_time};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use time::{Date, Duration, OffsetDateTime, Time};
+use uuid::Uuid;
+
+/// status of a day as printed by `timetrax status --json`.
+///
+/// the field names are stable, new fields may be added. times are local wall clock
+/// times (`HH:MM:SS`), durations are whole seconds
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusReport {
+    /// day of the status, `YYYY-MM-DD`
+    pub date: Date,
+    /// the day is marked as holiday
+    pub holiday: bool,
+    /// name of the public holiday on the day, its quota counts as met
+    #[serde(default)]
+    pub public_holiday: Option<String>,
+    /// segments of the closure of the activities and blockers, clamped to now if the day is today
+    pub timeline: Vec<StatusSegment>,
+    /// time covered by the timeline
+    pub total_seconds: i64,
+    /// class of the ongoing activities, only set for today
+    pub current_class: Option<String>,
+    /// open activities, which are unterminated if the day is not today
+    pub ongoing: Vec<StatusActivity>,
+    pub ended: Vec<StatusActivity>,
+    /// work quota of the day, zero if it has none
+    pub quota_seconds: i64,
+    /// time of the timeline counting towards the quota
+    pub quota_tracked_seconds: i64,
+    /// quota left to work, never negative
+    pub remaining_quota_seconds: i64,
+    /// day files used by the status which could not be parsed, see `timetrax repair`
+    #[serde(default)]
+    pub unloadable_day_files: Vec<PathBuf>,
+}
+
+/// folded segment of the timeline
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusSegment {
+    #[serde(with = "crate::serde::pretty_time")]
+    pub start: Time,
+    /// not set if the segment runs until the end of the day
+    #[serde(with = "crate::serde::pretty_time_option")]
+    pub end: Option<Time>,
+    /// class with the highest priority of the folded activities
+    pub class: String,
+    /// names of the folded activities, separated by `; `
+    pub name: Option<String>,
+    pub projects: Vec<String>,
+    pub duration_seconds: Option<i64>,
+}
+
+/// activity of the day with resolved class and project names
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusActivity {
+    pub id: Uuid,
+    pub class: String,
+    pub name: Option<String>,
+    pub projects: Vec<String>,
+    #[serde(with = "crate::serde::pretty_time")]
+    pub start: Time,
+    #[serde(with = "crate::serde::pretty_time_option")]
+    pub end: Option<Time>,
+    /// time elapsed so far for ongoing activities of today, not set for unterminated ones
+    pub duration_seconds: Option<i64>,
+}
+
+/// name of the class, the identifier if the job config does not know it
+fn class_name(job_config: &JobConfig, class: &Identifier) -> String {
+    job_config
+        .resolve_class(class)
+        .map_or_else(|| class.to_string(), |class| class.inner.name.clone())
+}
+
+fn project_names(job_config: &JobConfig, projects: &[Identifier]) -> Vec<String> {
+    projects
+        .iter()
+        .map(|project| {
+            job_config
+                .resolve_project(project)
+                .map_or_else(|| project.to_string(), |project| project.inner.name.clone())
+        })
+        .collect()
+}
+
+impl StatusReport {
+    /// status of the day at `now`, along with the classes the timeline could not resolve
+    pub fn new(
+        config: &AppConfig,
+        job_config: &JobConfig,
+        date: Date,
+        day: Option<&DayInner>,
+        now: OffsetDateTime,
+    ) -> (Self, ClosureDiagnostics) {
+        let boundary = job_config.day_boundary;
+        let is_today = job_config.date_of(now) == date;
+        let now_time = local_time::to_day_time(now.time(), boundary);
+        let empty = DayInner::default();
+        let day_inner = day.unwrap_or(&empty);
+
+        let (folded, mut diagnostics) =
+            day_inner.effective_timeline(job_config, is_today.then_some(now.time()));
+        let timeline = folded
+            .iter()
+            .map(|segment| {
+                let time = segment.time.from_day_time(boundary);
+                StatusSegment {
+                    start: time.start,
+                    end: time.end,
+                    class: class_name(job_config, &segment.class),
+                    name: segment.name.clone(),
+                    projects: project_names(job_config, &segment.projects),
+                    duration_seconds: segment.time.duration().map(|d| d.whole_seconds()),
+                }
+            })
+            .collect();
+
+        let (ended, ongoing): (Vec<_>, Vec<_>) = day_inner
+            .activities
+            .iter()
+            .partition(|activity| activity.time.is_complete());
+        let current_class = if is_today && !ongoing.is_empty() {
+            Activity::fold_inner(
+                job_config,
+                ongoing.iter().copied(),
+                None,
+                None,
+                &mut diagnostics,
+            )
+            .map(|status| class_name(job_config, &status.class))
+        } else {
+            None
+        };
+        let activity = |activity: &Activity| {
+            let time = activity.time.to_day_time(boundary);
+            StatusActivity {
+                id: activity.id,
+                class: class_name(job_config, &activity.class),
+                name: activity.name.clone(),
+                projects: project_names(job_config, &activity.projects),
+                start: activity.time.start,
+                end: activity.time.end,
+                duration_seconds: (time.is_complete() || is_today)
+                    .then(|| time.elapsed_at(now_time).whole_seconds()),
+            }
+        };
+
+        let quota = day_quota(job_config, date, day, config.daily_quotas());
+        let tracked = quota_time(job_config, &folded);
+        let report = Self {
+            date,
+            holiday: day_inner.day_type == DayType::Holiday,
+            public_holiday: job_config
+                .public_holiday(date)
+                .map(|holiday| holiday.name.clone()),
+            timeline,
+            total_seconds: Activity::sum_durations(&folded).total().whole_seconds(),
+            current_class,
+            ongoing: ongoing.into_iter().map(activity).collect(),
+            ended: ended.into_iter().map(activity).collect(),
+            quota_seconds: quota.whole_seconds(),
+            quota_tracked_seconds: tracked.whole_seconds(),
+            remaining_quota_seconds: (quota - tracked).max(Duration::ZERO).whole_seconds(),
+            unloadable_day_files: vec![],
+        };
+        (report, diagnostics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, at};
+    use time::Month;
+
+    fn activity(name: &str, class: &str, start: (u8, u8), end: Option<(u8, u8)>) -> Activity {
+        let time = |(h, m): (u8, u8)| Time::from_hms(h, m, 0).unwrap();
+        Activity {
+            name: Some(name.to_string()),
+            ..testing::activity(class, time(start), end.map(time))
+        }
+    }
+
+    #[test]
+    fn test_status_report_round_trip() {
+        // Friday
+        let date = Date::from_calendar_date(2024, Month::May, 3).unwrap();
+        let now = at(date, Time::from_hms(12, 0, 0).unwrap());
+        let day = DayInner {
+            activities: vec![
+                activity("standup", "work", (9, 0), Some((9, 30))),
+                activity("coding", "work", (10, 0), None),
+            ],
+            ..DayInner::default()
+        };
+        let (report, diagnostics) = StatusReport::new(
+            &AppConfig::default(),
+            &JobConfig::default(),
+            date,
+            Some(&day),
+            now,
+        );
+        assert!(diagnostics.is_empty());
+        assert_eq!(report.timeline.len(), 2);
+        assert_eq!(
+            report.timeline[1].end,
+            Some(Time::from_hms(12, 0, 0).unwrap())
+        );
+        assert_eq!(report.total_seconds, 150 * 60);
+        assert_eq!(report.current_class.as_deref(), Some("work"));
+        assert_eq!(report.ongoing[0].name.as_deref(), Some("coding"));
+        assert_eq!(report.ongoing[0].duration_seconds, Some(2 * 3600));
+        assert_eq!(report.ended[0].duration_seconds, Some(30 * 60));
+        assert_eq!(report.quota_seconds, 8 * 3600);
+        assert_eq!(report.remaining_quota_seconds, (8 * 60 - 150) * 60);
+
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        assert!(json.contains(r#""date": "2024-05-03""#));
+        assert!(json.contains(r#""start": "09:00:00""#));
+        assert_eq!(serde_json::from_str::<StatusReport>(&json).unwrap(), report);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod pretty_duration;
+pub mod pretty_duration_option;
 pub mod pretty_time;
 pub mod pretty_time_option;
+pub mod pretty_weekday_durations;
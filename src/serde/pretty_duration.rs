@@ -1,11 +1,6 @@
 use serde::Deserialize;
-use std::sync::LazyLock;
 use time::Duration;
 
-static REGEX_PRETTY_DURATION: LazyLock<regex::Regex> = LazyLock::new(|| {
-    regex::Regex::new(r"^(?P<hours>\d{1,}?)h(ours?)?\s+(?P<minutes>\d{1,2}?)m(in(utes?)?)?\s+(?P<seconds>\d{1,2}?)s(sec(onds?)?)?$").unwrap()
-});
-
 pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -30,33 +25,164 @@ where
     D: serde::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
+    parse_duration(&s).map_err(serde::de::Error::custom)
+}
+
+/// seconds per unit of a duration component, by the names the unit may be written as
+const UNITS: [(i64, &[&str]); 3] = [
+    (3600, &["h", "hour", "hours"]),
+    (60, &["m", "min", "mins", "minute", "minutes"]),
+    (1, &["s", "sec", "secs", "second", "seconds"]),
+];
 
-    let captures = REGEX_PRETTY_DURATION
-        .captures(&s)
-        .ok_or_else(|| serde::de::Error::custom(format!("Invalid duration format: {}", s)))?;
-
-    let hours = match captures.name("hours").map(|m| m.as_str()) {
-        None => 0,
-        Some(h) => h.parse::<u32>().map_err(|e| {
-            serde::de::Error::custom(format!("Invalid hours in duration: {}: {}", h, e))
-        })?,
-    };
-
-    let minutes = match captures.name("minutes").map(|m| m.as_str()) {
-        None => 0,
-        Some(m) => m.parse::<u32>().map_err(|e| {
-            serde::de::Error::custom(format!("Invalid minutes in duration: {}: {}", m, e))
-        })?,
-    };
-
-    let seconds = match captures.name("seconds").map(|m| m.as_str()) {
-        None => 0,
-        Some(s) => s.parse::<u32>().map_err(|e| {
-            serde::de::Error::custom(format!("Invalid seconds in duration: {}: {}", s, e))
-        })?,
-    };
-
-    Ok(Duration::hours(hours as i64)
-        + Duration::minutes(minutes as i64)
-        + Duration::seconds(seconds as i64))
+/// parse a duration as stored, e.g. `08h 00m 00s`, or as written by hand, e.g. `8h`,
+/// `7h 30m`, `1h30m`, `90m`, `7.5h` or `7,5h`
+///
+/// each unit may appear once, in any order. decimals have to add up to whole seconds
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let error = || format!("Invalid duration format: {}", s);
+    if s.trim_start().starts_with('-') {
+        return Err(format!("Negative durations are not supported: {}", s));
+    }
+
+    let mut seen = [false; UNITS.len()];
+    let mut total = Duration::ZERO;
+    let mut rest = s.trim_start();
+    if rest.is_empty() {
+        return Err(error());
+    }
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.' && c != ',')
+            .unwrap_or(rest.len());
+        let (number, remainder) = rest.split_at(number_end);
+        let remainder = remainder.trim_start();
+        let unit_end = remainder
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(remainder.len());
+        let (unit, remainder) = remainder.split_at(unit_end);
+
+        let index = UNITS
+            .iter()
+            .position(|(_, names)| names.contains(&unit.to_ascii_lowercase().as_str()))
+            .ok_or_else(error)?;
+        if std::mem::replace(&mut seen[index], true) {
+            return Err(format!("Duplicate unit {} in duration: {}", unit, s));
+        }
+        let component = component_seconds(number, UNITS[index].0).ok_or_else(error)?;
+        let component = component.ok_or_else(|| {
+            format!(
+                "Invalid duration {}, durations have second precision but {}{} is not a whole number of seconds",
+                s, number, unit
+            )
+        })?;
+        total = total
+            .checked_add(Duration::seconds(component))
+            .ok_or_else(error)?;
+        rest = remainder.trim_start();
+    }
+    Ok(total)
+}
+
+/// seconds of `<whole>[.<fraction>]` units, the fraction may also follow a comma. none if
+/// malformed, `Some(None)` if not a whole number of seconds
+fn component_seconds(number: &str, unit_seconds: i64) -> Option<Option<i64>> {
+    let (whole, fraction) = number.split_once(['.', ',']).unwrap_or((number, ""));
+    if whole.is_empty() || number.ends_with(['.', ',']) || fraction.contains(['.', ',']) {
+        return None;
+    }
+    let whole = whole.parse::<i64>().ok()?.checked_mul(unit_seconds)?;
+    if fraction.is_empty() {
+        return Some(Some(whole));
+    }
+    let denominator = 10i64.checked_pow(fraction.len() as u32)?;
+    let fraction = fraction.parse::<i64>().ok()?.checked_mul(unit_seconds)?;
+    if fraction % denominator != 0 {
+        return Some(None);
+    }
+    Some(Some(whole.checked_add(fraction / denominator)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        let hm = |hours, minutes| Duration::hours(hours) + Duration::minutes(minutes);
+        let accepted = [
+            ("08h 00m 00s", hm(8, 0)),
+            ("07h 30m 15s", hm(7, 30) + Duration::seconds(15)),
+            ("8h", hm(8, 0)),
+            ("7h 30m", hm(7, 30)),
+            ("7h30m", hm(7, 30)),
+            ("1h30m", hm(1, 30)),
+            ("90m", hm(1, 30)),
+            ("45s", Duration::seconds(45)),
+            ("30m 7h", hm(7, 30)),
+            ("15s 1h", hm(1, 0) + Duration::seconds(15)),
+            ("7.5h", hm(7, 30)),
+            ("7,5h", hm(7, 30)),
+            ("0.25h", hm(0, 15)),
+            ("1.5m", Duration::seconds(90)),
+            ("7.5h 10m", hm(7, 40)),
+            ("  8h  ", hm(8, 0)),
+            ("8 h 30 m", hm(8, 30)),
+            ("8hours 30min", hm(8, 30)),
+            ("1 hour 1 minute 1 second", hm(1, 1) + Duration::seconds(1)),
+            ("2H", hm(2, 0)),
+            ("0m", Duration::ZERO),
+            ("100h", hm(100, 0)),
+        ];
+        for (input, expected) in accepted {
+            assert_eq!(parse_duration(input), Ok(expected), "{input:?}");
+        }
+
+        let rejected = [
+            "",
+            "   ",
+            "8",
+            "h",
+            "1x",
+            "8h x",
+            "8h 5",
+            "1h 2h",
+            "30m 1h 15m",
+            "1hm",
+            ".5h",
+            "5.h",
+            "1.5.5h",
+            "1,5,h",
+            "1.5,5h",
+            "1.333h",
+            "1.5s",
+            "8h-",
+            "8d",
+            "8h30",
+            "99999999999999999999h",
+        ];
+        for input in rejected {
+            assert!(parse_duration(input).is_err(), "{input:?}");
+        }
+
+        for input in ["-8h", " -1h30m", "-0m"] {
+            let error = parse_duration(input).unwrap_err();
+            assert!(error.contains("Negative"), "{input:?}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_serialized_durations_parse_back() {
+        for seconds in [0, 59, 60, 3599, 3600, 8 * 3600, 27_000, 100 * 3600 + 61] {
+            let duration = Duration::seconds(seconds);
+            let serialized = serialize(&duration, serde_json::value::Serializer).unwrap();
+            assert_eq!(
+                parse_duration(serialized.as_str().unwrap()),
+                Ok(duration),
+                "{serialized}"
+            );
+        }
+        let serialized = serialize(&Duration::minutes(450), serde_json::value::Serializer);
+        assert_eq!(serialized.unwrap(), "07h 30m 00s");
+    }
 }
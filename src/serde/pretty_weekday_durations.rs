@@ -0,0 +1,63 @@
+use crate::serde::pretty_duration;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use time::{Duration, Weekday};
+
+/// durations of some weekdays, starting with monday, stored as a map from lowercase weekday
+/// names to pretty durations, e.g. `{"friday": "06h 00m 00s"}`
+pub fn serialize<S>(durations: &[Option<Duration>; 7], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    struct Pretty<'a>(&'a Duration);
+
+    impl Serialize for Pretty<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            pretty_duration::serialize(self.0, serializer)
+        }
+    }
+
+    let mut map = serializer.serialize_map(Some(durations.iter().flatten().count()))?;
+    for (weekday, duration) in weekdays().zip(durations) {
+        if let Some(duration) = duration {
+            map.serialize_entry(&weekday_name(weekday), &Pretty(duration))?;
+        }
+    }
+    map.end()
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<[Option<Duration>; 7], D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let map = BTreeMap::<String, String>::deserialize(deserializer)?;
+    let mut durations = [None; 7];
+    for (name, duration) in map {
+        let weekday = parse_weekday_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("Unknown weekday: {}", name)))?;
+        durations[weekday.number_days_from_monday() as usize] =
+            Some(pretty_duration::parse_duration(&duration).map_err(serde::de::Error::custom)?);
+    }
+    Ok(durations)
+}
+
+/// whether no weekday has a duration, for `skip_serializing_if`
+pub fn is_empty(durations: &[Option<Duration>; 7]) -> bool {
+    durations.iter().all(Option::is_none)
+}
+
+/// the weekdays starting with monday
+fn weekdays() -> impl Iterator<Item = Weekday> {
+    std::iter::successors(Some(Weekday::Monday), |weekday| Some(weekday.next())).take(7)
+}
+
+/// name of the weekday as stored, e.g. `friday`
+pub fn weekday_name(weekday: Weekday) -> String {
+    weekday.to_string().to_ascii_lowercase()
+}
+
+/// weekday of a stored name, ignoring case
+pub fn parse_weekday_name(name: &str) -> Option<Weekday> {
+    weekdays().find(|weekday| weekday_name(*weekday).eq_ignore_ascii_case(name))
+}
@@ -0,0 +1,181 @@
+//! Helpers to test code built on top of timetrax deterministically.
+//!
+//! Only available with the `test-util` feature.
+
+use crate::cli::ExecutableCommand;
+use crate::data::activity::Activity;
+use crate::data::app_config::AppConfig;
+use crate::data::day::{Day, DayInner};
+use crate::data::identifier::Identifier;
+use crate::data::interval::Interval;
+use crate::data::job_config::JobConfig;
+use crate::data::local_time;
+use crate::data::manager::Manager;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use time::{Date, Duration, OffsetDateTime, Time};
+use uuid::Uuid;
+
+pub use crate::data::local_time::{Clock, ClockGuard, FixedClock};
+
+/// point in time on the given date and time of day (UTC)
+pub fn at(date: Date, time: Time) -> OffsetDateTime {
+    date.with_time(time).assume_utc()
+}
+
+/// activity of the class named `class` from `start` to `end`, with a random id and every other
+/// field empty. set further fields using struct update syntax:
+/// `Activity { name: Some("coding".into()), ..activity("work", start, None) }`
+pub fn activity(class: &str, start: Time, end: Option<Time>) -> Activity {
+    Activity {
+        id: Uuid::new_v4(),
+        name: None,
+        class: Identifier::ByName(class.into()),
+        time: Interval { start, end },
+        projects: vec![],
+        weights: BTreeMap::new(),
+        description: None,
+        created_at: None,
+        modified_at: None,
+        tags: vec![],
+        evidence: vec![],
+        origin: None,
+        extra: Default::default(),
+    }
+}
+
+/// install a clock for the current thread until the guard is dropped
+pub fn install_clock<C: Clock + 'static>(clock: C) -> ClockGuard {
+    local_time::set_clock(Arc::new(clock))
+}
+
+/// clock that only moves when told to, clones share the same time
+#[derive(Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<OffsetDateTime>>,
+}
+
+impl ManualClock {
+    pub fn new(start: OffsetDateTime) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    /// move the clock forward (or backwards for negative durations)
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+
+    /// jump to the given point in time
+    pub fn set(&self, now: OffsetDateTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// install this clock for the current thread until the guard is dropped
+    pub fn install(&self) -> ClockGuard {
+        install_clock(self.clone())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> OffsetDateTime {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// temporary data directory, removed when dropped
+pub struct TempDataDir {
+    path: PathBuf,
+    app_config: AppConfig,
+}
+
+impl TempDataDir {
+    /// create an empty data directory using the given job config
+    pub fn new(job_config: &JobConfig) -> crate::error::Result<Self> {
+        let path = std::env::temp_dir().join(format!("timetrax-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&path)?;
+
+        let app_config = AppConfig {
+            default_data_path: path.clone(),
+            ..AppConfig::default()
+        };
+
+        Manager::save_job_config(&app_config, &path, job_config)?;
+        std::fs::create_dir_all(path.join(&app_config.job_day_folder_format))?;
+
+        Ok(Self { path, app_config })
+    }
+
+    /// create a data directory pre-populated with the given days
+    pub fn with_days<I: IntoIterator<Item = Day>>(
+        job_config: &JobConfig,
+        days: I,
+    ) -> crate::error::Result<Self> {
+        let dir = Self::new(job_config)?;
+        {
+            let mut manager = dir.manager()?;
+            for day in days {
+                *manager.get_or_create_day_mut(day.date) = day.inner;
+            }
+            manager.save()?;
+        }
+        Ok(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn app_config(&self) -> &AppConfig {
+        &self.app_config
+    }
+
+    pub fn app_config_mut(&mut self) -> &mut AppConfig {
+        &mut self.app_config
+    }
+
+    /// load the job config currently stored in the directory
+    pub fn job_config(&self) -> crate::error::Result<JobConfig> {
+        Manager::open_job_config(&self.app_config, &self.path)
+    }
+
+    /// open a manager on the directory
+    pub fn manager(&self) -> crate::error::Result<Manager<'_>> {
+        Manager::open(&self.app_config, &self.path)
+    }
+
+    /// place a raw day file, e.g. a fixture from `testdata`, in the day folder
+    pub fn write_day_file(&self, file_name: &str, contents: &str) -> std::io::Result<PathBuf> {
+        let folder = self.path.join(&self.app_config.job_day_folder_format);
+        std::fs::create_dir_all(&folder)?;
+        let path = folder.join(file_name);
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    /// load a single day from disk, without locking the directory
+    pub fn day(&self, date: Date) -> crate::error::Result<Option<DayInner>> {
+        Ok(Manager::open_read_only(&self.app_config, &self.path)?
+            .day(date)
+            .cloned())
+    }
+
+    /// run a command against the directory the same way the binary does
+    pub fn execute<C: ExecutableCommand<Error = crate::error::Error>>(
+        &self,
+        command: &C,
+    ) -> crate::error::Result<C::Output> {
+        let mut job_config = self.job_config()?;
+        let result = command.execute(&self.app_config, &mut job_config, self.manager()?);
+        Manager::save_job_config(&self.app_config, &self.path, &job_config)?;
+        result
+    }
+}
+
+impl Drop for TempDataDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
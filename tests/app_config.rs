@@ -0,0 +1,127 @@
+//! loading the app config from the config directory and through `--config`
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// empty home directory, removed when dropped
+struct TempHome {
+    path: PathBuf,
+}
+
+impl TempHome {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "timetrax-app-config-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    fn write(&self, relative: &str, contents: &str) -> PathBuf {
+        let path = self.path.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// run timetrax with `HOME` and optionally `XDG_CONFIG_HOME` inside the directory
+    fn run(&self, xdg_config_home: Option<&Path>, args: &[&str]) -> Output {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_timetrax"));
+        command
+            .env("HOME", &self.path)
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("TIMETRAX_DATA_PATH")
+            .env_remove("TIMETRAX_JOB_CONFIG")
+            .env_remove("TIMETRAX_WORK_QUOTA")
+            .args(["--now", "2024-05-03 12:00"])
+            .args(args);
+        if let Some(xdg_config_home) = xdg_config_home {
+            command.env("XDG_CONFIG_HOME", xdg_config_home);
+        }
+        command.output().unwrap()
+    }
+
+    fn status(&self, xdg_config_home: Option<&Path>, args: &[&str]) -> String {
+        let output = self.run(xdg_config_home, &[args, &["status", "--short"]].concat());
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    }
+}
+
+impl Drop for TempHome {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[test]
+fn test_missing_config_uses_the_defaults() {
+    let home = TempHome::new("missing");
+    assert_eq!(home.status(None, &[]), "idle | 8h0m\n");
+    assert_eq!(
+        home.status(Some(&home.path.join("xdg")), &[]),
+        "idle | 8h0m\n"
+    );
+}
+
+#[test]
+fn test_config_overrides_the_work_quota() {
+    let home = TempHome::new("quota");
+    home.write(
+        ".config/timetrax/config.json",
+        r#"{"work_quota_default": "6h"}"#,
+    );
+    assert_eq!(home.status(None, &[]), "idle | 6h0m\n");
+
+    // XDG_CONFIG_HOME takes precedence over ~/.config
+    home.write(
+        "xdg/timetrax/config.json",
+        r#"{"work_quota_default": "7.5h"}"#,
+    );
+    assert_eq!(
+        home.status(Some(&home.path.join("xdg")), &[]),
+        "idle | 7h30m\n"
+    );
+
+    // and --config over both
+    let explicit = home.write("explicit.json", r#"{"work_quota_default": "4h 15m"}"#);
+    let explicit = explicit.to_str().unwrap();
+    assert_eq!(
+        home.status(Some(&home.path.join("xdg")), &["--config", explicit]),
+        "idle | 4h15m\n"
+    );
+}
+
+#[test]
+fn test_config_show_leaves_the_data_directory_alone() {
+    let home = TempHome::new("show");
+    let data = home.path.join(".timetrax");
+    for args in [&["config", "show"][..], &["config", "get", "min_gap"]] {
+        let output = home.run(None, args);
+        assert!(output.status.success(), "{:?}", output);
+    }
+    // neither created nor locked
+    assert!(!data.exists());
+}
+
+#[test]
+fn test_malformed_config_is_an_error() {
+    let home = TempHome::new("malformed");
+    let path = home.write(
+        ".config/timetrax/config.json",
+        r#"{"work_quota_default": 8"#,
+    );
+    let output = home.run(None, &["status"]);
+    assert_eq!(output.status.code(), Some(78));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&path.display().to_string()), "{stderr}");
+    assert!(stderr.contains("expected a string"), "{stderr}");
+
+    // an explicitly given config has to exist
+    let missing = home.path.join("missing.json");
+    let output = home.run(None, &["--config", missing.to_str().unwrap(), "status"]);
+    assert!(!output.status.success());
+}